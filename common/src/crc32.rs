@@ -0,0 +1,52 @@
+//! Este modulo contiene la implementacion de CRC32 (IEEE 802.3) usada para
+//! verificar la integridad de datos persistidos
+
+const CRC32_POLY: u32 = 0xEDB8_8320;
+
+const CRC32TAB: [u32; 256] = generar_tabla();
+
+/// Genera la tabla de CRC32 en tiempo de compilacion
+const fn generar_tabla() -> [u32; 256] {
+    let mut tabla = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        tabla[i] = crc;
+        i += 1;
+    }
+    tabla
+}
+
+/// Calcula el CRC32 de un buffer
+pub fn crc32(buf: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &b in buf {
+        let index = ((crc ^ b as u32) & 0xFF) as usize;
+        crc = (crc >> 8) ^ CRC32TAB[index];
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32;
+
+    #[test]
+    fn test_01_crc32_valores_conocidos() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+}
@@ -2,8 +2,10 @@ pub mod char_entry;
 pub mod common_error;
 pub mod consts_resp;
 pub mod cr16;
+pub mod crc32;
 pub mod lcs;
 pub mod pila;
+pub mod sha1;
 pub mod sheet;
 pub mod text;
 pub mod thread_pool;
@@ -0,0 +1,89 @@
+//! Este modulo contiene la implementacion de SHA-1 usada para identificar
+//! los scripts cacheados por su contenido
+
+const H_INICIAL: [u32; 5] = [
+    0x6745_2301,
+    0xEFCD_AB89,
+    0x98BA_DCFE,
+    0x1032_5476,
+    0xC3D2_E1F0,
+];
+
+/// Calcula el digest SHA-1 de un buffer
+pub fn sha1(buf: &[u8]) -> [u8; 20] {
+    let mut mensaje = buf.to_vec();
+    mensaje.push(0x80);
+    while mensaje.len() % 64 != 56 {
+        mensaje.push(0);
+    }
+    mensaje.extend_from_slice(&((buf.len() as u64) * 8).to_be_bytes());
+
+    let mut h = H_INICIAL;
+    for bloque in mensaje.chunks_exact(64) {
+        procesar_bloque(&mut h, bloque);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, palabra) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&palabra.to_be_bytes());
+    }
+    digest
+}
+
+/// Aplica la funcion de compresion a un bloque de 64 bytes
+fn procesar_bloque(h: &mut [u32; 5], bloque: &[u8]) {
+    let mut w = [0u32; 80];
+    for (i, palabra) in bloque.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([palabra[0], palabra[1], palabra[2], palabra[3]]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *h;
+    for (i, palabra) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+            20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+            _ => (b ^ c ^ d, 0xCA62_C1D6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*palabra);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+    for (registro, valor) in h.iter_mut().zip([a, b, c, d, e]) {
+        *registro = registro.wrapping_add(valor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sha1;
+
+    fn hex(digest: [u8; 20]) -> String {
+        digest.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_01_sha1_valores_conocidos() {
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(sha1(b"The quick brown fox jumps over the lazy dog")),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+        // Mensaje que ocupa mas de un bloque
+        assert_eq!(
+            hex(sha1(&[b'a'; 1000])),
+            "291e9a6c66994949b57ba5e650361e98fc36b1ba"
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::estructuras::errores::Error;
 use crate::estructuras::palabra::Palabra;
 use crate::estructuras::pila::Pila;
 use crate::operaciones::operaciones_mapeo::Operaciones;
+use crate::operaciones::palabras_externas::PalabrasExternas;
 use crate::operaciones::words::{definir_word, es_definir_word, parsear_numero};
 use std::collections::HashMap;
 
@@ -18,6 +19,7 @@ use std::collections::HashMap;
 ///   las mismas
 /// - 'dicc_words': diccionario de pares (nombre_de_palabra, palabra)
 /// - 'resultado': string de resultado de la ejecucion
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber un error, se corta la ejecucion y se devuelve como option
 pub fn interpretar(
@@ -26,6 +28,7 @@ pub fn interpretar(
     dicc_operaciones: &mut Operaciones,
     dicc_words: &mut HashMap<String, Palabra>,
     resultado: &mut String,
+    externas: &mut dyn PalabrasExternas,
 ) -> Option<Error> {
     if secuencia.is_empty() {
         return None;
@@ -40,6 +43,7 @@ pub fn interpretar(
                     dicc_words,
                     None,
                     resultado,
+                    externas,
                 );
             }
             Err(_) => {
@@ -54,6 +58,7 @@ pub fn interpretar(
         dicc_words,
         None,
         resultado,
+        externas,
     )
 }
 
@@ -71,6 +76,7 @@ pub fn interpretar(
 /// - 'word_actual': de estar ejectuando la definicion de una palabra previamente definida,
 ///   se mantiene el nombre de la misma para conocer que versiones de otras palabras referencia
 /// - 'resultado': string de resultado de la ejecucion
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber un error, se corta la ejecucion y se devuelve como option
 fn interpretar_comandos(
@@ -80,6 +86,7 @@ fn interpretar_comandos(
     dicc_words: &mut HashMap<String, Palabra>,
     word_actual: Option<&String>,
     resultado: &mut String,
+    externas: &mut dyn PalabrasExternas,
 ) -> Option<Error> {
     for elemento in secuencia.iter() {
         if elemento.to_lowercase() == "if" {
@@ -96,6 +103,7 @@ fn interpretar_comandos(
                     dicc_words,
                     None,
                     resultado,
+                    externas,
                 );
                 if let Some(e) = interpretacion {
                     return Some(e);
@@ -107,10 +115,15 @@ fn interpretar_comandos(
                     dicc_words,
                     None,
                     resultado,
+                    externas,
                 );
             } else if let Err(e) = tope {
                 return Some(e);
             }
+        } else if let Some((palabra, texto)) = separar_palabra_con_texto(elemento) {
+            if let Some(e) = ejecutar_externa(&palabra, Some(texto), pila, externas, resultado) {
+                return Some(e);
+            }
         } else if !imprimir_texto(elemento, resultado) {
             let version = version(&(elemento.to_lowercase()), word_actual, dicc_words);
             if let Some(palabra) = dicc_words.get(&(elemento.to_lowercase())) {
@@ -124,11 +137,12 @@ fn interpretar_comandos(
                         dicc_words,
                         Some(&(elemento.to_lowercase())),
                         resultado,
+                        externas,
                     ) {
                         return Some(e);
                     }
                 }
-            } else if let Some(e) = operar(pila, elemento, dicc_operaciones, resultado) {
+            } else if let Some(e) = operar(pila, elemento, dicc_operaciones, resultado, externas) {
                 return Some(e);
             }
         }
@@ -147,6 +161,7 @@ fn interpretar_comandos(
 ///   que el interprete puede resolver y sus claves son funciones que a partir de la pila ejecutan
 ///   las mismas
 /// - 'resultado': string de resultado de la ejecucion
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber un error, se corta la ejecucion y se devuelve como option
 fn operar(
@@ -154,6 +169,7 @@ fn operar(
     elemento: &String,
     dicc_operaciones: &mut Operaciones,
     resultado: &mut String,
+    externas: &mut dyn PalabrasExternas,
 ) -> Option<Error> {
     match parsear_numero(elemento) {
         Ok(num) => {
@@ -174,13 +190,66 @@ fn operar(
                 }
             },
             _ => {
-                return Some(Error::WordNotFound);
+                return ejecutar_externa(&elemento.to_lowercase(), None, pila, externas, resultado);
             }
         },
     };
     None
 }
 
+/// Ejecuta una palabra provista por quien ejecuta el interprete
+///
+/// # Parametros
+/// - 'palabra': nombre de la palabra en minusculas
+/// - 'texto': texto del token, de ser de la forma `nombre" texto"`
+/// - 'pila': estructura de tipo Pila con los numeros apilados hasta el momento
+/// - 'externas': palabras provistas por quien ejecuta el interprete
+/// - 'resultado': string de resultado de la ejecucion
+///
+/// De no existir la palabra o haber un error, se devuelve como option
+fn ejecutar_externa(
+    palabra: &str,
+    texto: Option<&str>,
+    pila: &mut Pila<i16>,
+    externas: &mut dyn PalabrasExternas,
+    resultado: &mut String,
+) -> Option<Error> {
+    match externas.ejecutar(palabra, texto, pila) {
+        Some(Ok(res)) => {
+            resultado.push_str(&res);
+            if !res.is_empty() && res != "\n" {
+                resultado.push(' ');
+            }
+            None
+        }
+        Some(Err(e)) => Some(e),
+        None => Some(Error::WordNotFound),
+    }
+}
+
+/// Determina si un token es del tipo nombre" texto", distinto de ." ", y de
+/// serlo lo separa
+///
+///  # Parametros
+///  - 'elemento': token a analizar
+///
+/// Devuelve el nombre en minusculas, incluyendo la comilla de apertura, y el
+/// texto, o None si el token no es de ese tipo
+fn separar_palabra_con_texto(elemento: &str) -> Option<(String, &str)> {
+    let apertura = elemento.find('"')?;
+    let nombre = &elemento[..=apertura];
+    if nombre == ".\""
+        || apertura == 0
+        || elemento.len() < apertura + 3
+        || !elemento[apertura + 1..].starts_with(' ')
+        || !elemento.ends_with('"')
+    {
+        return None;
+    }
+    let texto = elemento.get(apertura + 2..elemento.len() - 1)?;
+    Some((nombre.to_lowercase(), texto))
+}
+
 /// Determina si un token es del tipo ." " y de serlo, imprime el texto correspondiente
 ///
 ///  # Parametros
@@ -254,13 +323,13 @@ fn version(
     word_actual: Option<&String>,
     dicc_words: &mut HashMap<String, Palabra>,
 ) -> isize {
-    if let Some(nombre_actual) = word_actual {
-        if let Some(palabra_actual) = dicc_words.get(nombre_actual) {
-            if let Some(version) = palabra_actual.version_referencia(elemento.to_string()) {
-                return version;
-            }
-            return palabra_actual.versiones;
+    if let Some(nombre_actual) = word_actual
+        && let Some(palabra_actual) = dicc_words.get(nombre_actual)
+    {
+        if let Some(version) = palabra_actual.version_referencia(elemento.to_string()) {
+            return version;
         }
+        return palabra_actual.versiones;
     }
     -1
 }
@@ -296,7 +365,12 @@ fn separar_clausulas(
             continue;
         }
 
-        actualizar_casos(&mut casos, elem, &dentro_else, &fin_if);
+        let instruccion = if separar_palabra_con_texto(elemento).is_some() {
+            elemento.to_string()
+        } else {
+            elem
+        };
+        actualizar_casos(&mut casos, instruccion, &dentro_else, &fin_if);
     }
     for caso in casos.iter_mut().take(3) {
         if !caso.is_empty() {
@@ -9,6 +9,7 @@ use crate::estructuras::errores::Error;
 use crate::estructuras::palabra::Palabra;
 use crate::estructuras::pila::Pila;
 use crate::operaciones::operaciones_mapeo::{Operaciones, crear_dicc_op};
+use crate::operaciones::palabras_externas::{PalabrasExternas, SinPalabrasExternas};
 use std::collections::HashMap;
 
 /// Llama a la procesion de cada linea, sin almacenarlas en memoria
@@ -17,30 +18,119 @@ use std::collections::HashMap;
 /// - 'text': lineas a ejecutar
 /// - 'armando_word': booleano indicando si en la linea anterior se estaba definiendo una
 ///   palabra, para continuar con la definicion de la misma
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber algun error, falla
-pub(crate) fn interpretar_archivo(text: Vec<String>, armando_word: &mut bool) -> (String, String) {
-    let tam_stack = TAM_PRED_STACK;
-    let (mut pila, mut operaciones, mut words) = crear_estructuras(tam_stack);
+pub(crate) fn interpretar_archivo(
+    text: Vec<String>,
+    armando_word: &mut bool,
+    externas: &mut dyn PalabrasExternas,
+) -> (String, String) {
+    let mut ejecucion = ejecutar_lineas(text, armando_word, externas);
+    if let Some(e) = ejecucion.error {
+        ejecucion.resultado.push_str(&format!("{e}"));
+    }
+    let pila_final = vaciar_pila(
+        &mut ejecucion.pila,
+        &TAM_PRED_STACK,
+        &mut ejecucion.resultado,
+    );
+    (ejecucion.resultado, pila_final)
+}
+
+/// Ejecuta las lineas de un programa, cortando ante el primer error
+///
+/// # Parametros
+/// - 'text': lineas a ejecutar
+/// - 'externas': palabras provistas por quien ejecuta el interprete
+///
+/// Devuelve el texto impreso y los elementos de la pila desde el fondo
+/// hasta el tope, o el error de la ejecucion
+pub(crate) fn ejecutar_programa(
+    text: Vec<String>,
+    externas: &mut dyn PalabrasExternas,
+) -> Result<(String, Vec<i16>), Error> {
+    let mut ejecucion = ejecutar_lineas(text, &mut false, externas);
+    if let Some(e) = ejecucion.error {
+        return Err(e);
+    }
+    let mut elementos = Vec::new();
+    while let Ok(elemento) = ejecucion.pila.desapilar() {
+        elementos.push(*elemento);
+    }
+    elementos.reverse();
+    Ok((ejecucion.resultado, elementos))
+}
+
+/// Ejecuta un programa sin palabras externas
+///
+/// # Parametros
+/// - 'text': lineas a ejecutar
+///
+/// Devuelve los nombres de las palabras definidas, ordenados, si el programa
+/// solo define palabras. Falla si el programa imprime texto, deja numeros en
+/// la pila o produce un error
+pub(crate) fn palabras_definidas(text: Vec<String>) -> Result<Vec<String>, Error> {
+    let mut ejecucion = ejecutar_lineas(text, &mut false, &mut SinPalabrasExternas);
+    if let Some(e) = ejecucion.error {
+        return Err(e);
+    }
+    if !ejecucion.resultado.is_empty() || ejecucion.pila.desapilar().is_ok() {
+        return Err(Error::InvalidWord);
+    }
+    let mut nombres: Vec<String> = ejecucion.words.into_keys().collect();
+    nombres.sort();
+    Ok(nombres)
+}
+
+/// Estado final de la ejecucion de un programa
+struct Ejecucion {
+    pila: Pila<i16>,
+    words: HashMap<String, Palabra>,
+    resultado: String,
+    error: Option<Error>,
+}
+
+/// Interpreta cada linea hasta el final del programa o hasta el primer error
+///
+/// Devuelve la pila final, las palabras definidas, el texto impreso y el
+/// error, de haberlo
+fn ejecutar_lineas(
+    text: Vec<String>,
+    armando_word: &mut bool,
+    externas: &mut dyn PalabrasExternas,
+) -> Ejecucion {
+    let (mut pila, mut operaciones, mut words) = crear_estructuras(TAM_PRED_STACK);
     let mut linea_final: Vec<String> = Vec::new();
     let mut resultado: String = String::new();
 
     for linea in text {
-        if let Some(e) = interpretar_linea(
-            linea.to_string(),
-            armando_word,
-            &mut linea_final,
-            &mut pila,
-            &mut operaciones,
-            &mut words,
-            &mut resultado,
-        ) {
-            resultado.push_str(&format!("{e}"));
-            break;
+        let mut error = separar_linea(linea, armando_word, &mut linea_final, externas);
+        if error.is_none() && !*armando_word {
+            error = interpretar(
+                &linea_final,
+                &mut pila,
+                &mut operaciones,
+                &mut words,
+                &mut resultado,
+                externas,
+            );
+        }
+        if error.is_some() {
+            return Ejecucion {
+                pila,
+                words,
+                resultado,
+                error,
+            };
         }
     }
-    let pila_final = vaciar_pila(&mut pila, &tam_stack, &mut resultado);
-    (resultado, pila_final)
+    Ejecucion {
+        pila,
+        words,
+        resultado,
+        error: None,
+    }
 }
 
 /// Llama a la procesion de cada linea, sin almacenarlas en memoria
@@ -57,42 +147,30 @@ fn crear_estructuras(tam_stack: usize) -> (Pila<i16>, Operaciones, HashMap<Strin
     (pila, operaciones, words)
 }
 
-/// Llama a la procesion de cada linea, sin almacenarlas en memoria
+/// Separa una linea en instrucciones y las agrega a las de las lineas
+/// anteriores si se estaba definiendo una palabra
 ///
 /// # Parametros
 /// - 'comando': linea del archivo .fth
 /// - 'armando_word': booleano indicando si en la linea anterior se estaba definiendo una
 ///   palabra, para continuar con la definicion de la misma
 /// - 'linea_final': lineas anteriores si se estaba definiendo una word, vector vacio en otro caso
-/// - 'pila': pila de i16 con los resultados acumulados
-/// - 'operaciones': diccionario cuyas claves son nombres de instrucciones
-///   que el interprete puede resolver y sus claves son funciones que a partir de la pila ejecutan
-///   las mismas
-/// - 'words': diccionatio de pares (nombre_de_palabra, palabra)
-/// - 'resultado': string de resultado de la ejecucion
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber algun error, lo devuelve
-fn interpretar_linea(
+fn separar_linea(
     comando: String,
     armando_word: &mut bool,
     linea_final: &mut Vec<String>,
-    pila: &mut Pila<i16>,
-    operaciones: &mut Operaciones,
-    words: &mut HashMap<String, Palabra>,
-    resultado: &mut String,
+    externas: &dyn PalabrasExternas,
 ) -> Option<Error> {
     if !*armando_word {
         linea_final.clear();
     }
     let mut linea_actual: Vec<String> = Vec::new();
-    if let Err(e) = separar_por_espacios(comando, &mut linea_actual, armando_word) {
+    if let Err(e) = separar_por_espacios(comando, &mut linea_actual, armando_word, externas) {
         return Some(e);
     }
     unir(linea_final, linea_actual);
-    if !*armando_word {
-        if let Some(e) = interpretar(linea_final, pila, operaciones, words, resultado) {
-            return Some(e);
-        }
-    }
     None
 }
@@ -2,6 +2,7 @@
 
 use crate::estructuras::errores::Error;
 use crate::estructuras::pila::Pila;
+use crate::operaciones::palabras_externas::PalabrasExternas;
 
 /// Llama a la procesion de cada linea, sin almacenarlas en memoria
 ///
@@ -10,12 +11,14 @@ use crate::estructuras::pila::Pila;
 /// - 'linea_final': lineas anteriores si se estaba definiendo una word, vector vacio en otro caso
 /// - 'armando_word': booleano indicando si en la linea anterior se estaba definiendo una
 ///   palabra, para continuar con la definicion de la misma
+/// - 'externas': palabras provistas por quien ejecuta el interprete
 ///
 /// De haber algun error, lo devuelve, sino, devuelve un vector de los tokens preprocesados
 pub fn separar_por_espacios(
     comando: String,
     linea_actual: &mut Vec<String>,
     armando_word: &mut bool,
+    externas: &dyn PalabrasExternas,
 ) -> Result<Option<Vec<String>>, Error> {
     let mut dentro_de_str = false;
     let mut expresion = String::new();
@@ -26,6 +29,7 @@ pub fn separar_por_espacios(
         &mut expresion,
         &mut dentro_de_str,
         linea_actual,
+        externas,
     ) {
         return Err(e);
     }
@@ -81,10 +85,10 @@ pub fn vaciar_pila(pila: &mut Pila<i16>, tam_stack: &usize, resultado: &mut Stri
 /// - 'resultado': string de resultado de la ejecucion
 fn llenar_pila(pila_1: &mut Pila<i16>, pila_2: &mut Pila<i16>, resultado: &mut String) {
     while !pila_1.esta_vacia() {
-        if let Ok(elem) = pila_1.desapilar() {
-            if let Err(e) = pila_2.apilar(*elem) {
-                resultado.push_str(&format!("{e}"));
-            }
+        if let Ok(elem) = pila_1.desapilar()
+            && let Err(e) = pila_2.apilar(*elem)
+        {
+            resultado.push_str(&format!("{e}"));
         }
     }
 }
@@ -98,6 +102,9 @@ fn llenar_pila(pila_1: &mut Pila<i16>, pila_2: &mut Pila<i16>, resultado: &mut S
 /// - 'expresion': formacion del token actual hasta el momento
 /// - 'dentro_de_str': determina si se esta analizando por dentro una expresion de tipo ." "
 /// - 'linea_actual': vector que almacena el resultados
+/// - 'externas': palabras provistas por quien ejecuta el interprete. Solo las
+///   que reciben texto abren una expresion de tipo nombre" ", dentro de la
+///   cual ':' y ';' no definen words
 ///
 /// De haber algun error, lo devuelve
 fn armar_expresion(
@@ -106,19 +113,29 @@ fn armar_expresion(
     expresion: &mut String,
     dentro_de_str: &mut bool,
     linea_actual: &mut Vec<String>,
+    externas: &dyn PalabrasExternas,
 ) -> Option<Error> {
+    let mut dentro_de_texto_externo = false;
     for caracter in comando.chars() {
-        if let Some(e) = verificar_armando_word(armando_word, &caracter) {
+        if !dentro_de_texto_externo && let Some(e) = verificar_armando_word(armando_word, &caracter)
+        {
             return Some(e);
         }
         if expresion == ".\"" && caracter == ' ' {
             *dentro_de_str = true;
+        } else if !*dentro_de_str
+            && caracter == ' '
+            && externas.recibe_texto(&expresion.to_lowercase())
+        {
+            *dentro_de_str = true;
+            dentro_de_texto_externo = true;
         }
         if es_espacio(&caracter) && !*dentro_de_str {
             actualizar_linea(expresion, linea_actual, armando_word);
             continue;
         } else if caracter == '"' && *dentro_de_str {
             cerrar_string(dentro_de_str, expresion, linea_actual, &caracter);
+            dentro_de_texto_externo = false;
             continue;
         }
         expresion.push(caracter);
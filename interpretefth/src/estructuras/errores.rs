@@ -11,6 +11,7 @@ pub enum Error {
     InvalidWord,
     DivisionByZero,
     WordNotFound,
+    ExternalError(String),
 }
 
 impl fmt::Display for Error {
@@ -22,6 +23,7 @@ impl fmt::Display for Error {
             Error::InvalidWord => write!(f, "invalid-word"),
             Error::DivisionByZero => write!(f, "division-by-zero"),
             Error::WordNotFound => write!(f, "?"),
+            Error::ExternalError(mensaje) => write!(f, "{mensaje}"),
         }
     }
 }
@@ -4,6 +4,40 @@ mod estructuras;
 mod operaciones;
 mod tests;
 
+pub use estructuras::errores::Error;
+pub use estructuras::pila::Pila;
+pub use operaciones::palabras_externas::PalabrasExternas;
+use operaciones::palabras_externas::SinPalabrasExternas;
+
 pub fn interpretar_texto(text: Vec<String>) -> (String, String) {
-    entrada_salida::lectura::interpretar_archivo(text, &mut false)
+    entrada_salida::lectura::interpretar_archivo(text, &mut false, &mut SinPalabrasExternas)
+}
+
+/// Ejecuta un programa que puede usar palabras provistas por quien lo
+/// ejecuta, cortando ante el primer error
+///
+/// # Parametros
+/// - 'text': lineas del programa
+/// - 'externas': palabras externas disponibles para el programa
+///
+/// Devuelve el texto impreso y la pila final, desde el fondo hasta el tope,
+/// o el error de la ejecucion
+pub fn ejecutar_programa(
+    text: Vec<String>,
+    externas: &mut dyn PalabrasExternas,
+) -> Result<(String, Vec<i16>), Error> {
+    entrada_salida::lectura::ejecutar_programa(text, externas)
+}
+
+/// Ejecuta un programa que solo define palabras, como una biblioteca de
+/// funciones
+///
+/// # Parametros
+/// - 'text': lineas del programa
+///
+/// Devuelve los nombres de las palabras definidas, ordenados, o el error de
+/// la ejecucion. Un programa que imprime texto o deja numeros en la pila es
+/// una palabra invalida
+pub fn palabras_definidas(text: Vec<String>) -> Result<Vec<String>, Error> {
+    entrada_salida::lectura::palabras_definidas(text)
 }
@@ -3,4 +3,5 @@ mod operaciones_logicas;
 pub mod operaciones_mapeo;
 mod operaciones_output;
 pub mod operaciones_stack;
+pub mod palabras_externas;
 pub mod words;
@@ -0,0 +1,51 @@
+//! Este modulo define las palabras que provee quien ejecuta el interprete,
+//! ademas de las operaciones predeterminadas y las words del programa
+
+use crate::estructuras::errores::Error;
+use crate::estructuras::pila::Pila;
+
+/// Palabras externas al interprete. Se consultan solo para los tokens que
+/// no son numeros, operaciones predeterminadas ni words definidas, y para
+/// los tokens de la forma `nombre" texto"`
+pub trait PalabrasExternas {
+    /// Ejecuta una palabra externa
+    ///
+    /// # Parametros
+    /// - 'palabra': nombre de la palabra en minusculas. En los tokens de la
+    ///   forma `nombre" texto"` incluye la comilla de apertura
+    /// - 'texto': texto del token, de ser de la forma `nombre" texto"`
+    /// - 'pila': pila de i16 con los resultados acumulados
+    ///
+    /// Devuelve None si la palabra no existe, sino el texto a imprimir o el
+    /// error de la ejecucion
+    fn ejecutar(
+        &mut self,
+        palabra: &str,
+        texto: Option<&str>,
+        pila: &mut Pila<i16>,
+    ) -> Option<Result<String, Error>>;
+
+    /// Determina si una palabra recibe texto, es decir, si los tokens de la
+    /// forma `nombre" texto"` se leen como un unico token
+    ///
+    /// # Parametros
+    /// - 'palabra': nombre de la palabra en minusculas, incluyendo la comilla
+    ///   de apertura
+    fn recibe_texto(&self, _palabra: &str) -> bool {
+        false
+    }
+}
+
+/// Palabras externas de un interprete que no provee ninguna
+pub struct SinPalabrasExternas;
+
+impl PalabrasExternas for SinPalabrasExternas {
+    fn ejecutar(
+        &mut self,
+        _palabra: &str,
+        _texto: Option<&str>,
+        _pila: &mut Pila<i16>,
+    ) -> Option<Result<String, Error>> {
+        None
+    }
+}
@@ -7,7 +7,10 @@ mod tests {
         io::{BufRead, BufReader},
     };
 
-    use crate::{estructuras::errores::Error, interpretar_texto};
+    use crate::{
+        PalabrasExternas, Pila, ejecutar_programa, estructuras::errores::Error, interpretar_texto,
+        palabras_definidas,
+    };
 
     fn interpretar_archivo(f: File) -> Vec<String> {
         let buffer = BufReader::new(f);
@@ -89,4 +92,101 @@ mod tests {
         let stack = "4 5 ".to_string();
         assert!(ejecutar_test(ruta, out.to_string(), stack));
     }
+
+    /// Palabras externas de prueba: `doble` duplica el tope y `largo"`
+    /// apila el largo de su texto
+    struct PalabrasDePrueba {
+        textos: Vec<String>,
+    }
+
+    impl PalabrasExternas for PalabrasDePrueba {
+        fn ejecutar(
+            &mut self,
+            palabra: &str,
+            texto: Option<&str>,
+            pila: &mut Pila<i16>,
+        ) -> Option<Result<String, Error>> {
+            match (palabra, texto) {
+                ("doble", None) => Some(match pila.desapilar() {
+                    Ok(n) => {
+                        let doble = *n * 2;
+                        pila.apilar(doble).map(|_| String::new())
+                    }
+                    Err(e) => Err(e),
+                }),
+                ("largo\"", Some(texto)) => {
+                    self.textos.push(texto.to_string());
+                    Some(pila.apilar(texto.len() as i16).map(|_| String::new()))
+                }
+                _ => None,
+            }
+        }
+
+        fn recibe_texto(&self, palabra: &str) -> bool {
+            palabra == "largo\""
+        }
+    }
+
+    #[test]
+    fn test_integracion_8_palabras_externas() {
+        let mut externas = PalabrasDePrueba { textos: Vec::new() };
+        let programa = vec![
+            ": cuadruple doble doble ;".to_string(),
+            ": dos 1 if largo\" Dos\" then ;".to_string(),
+            "3 cuadruple largo\" Clave:Uno\" dos .\" fin\"".to_string(),
+        ];
+        let (salida, pila) = ejecutar_programa(programa, &mut externas).unwrap();
+        assert_eq!(salida, "fin ");
+        assert_eq!(pila, vec![12, 9, 3]);
+        assert_eq!(externas.textos, vec!["Clave:Uno", "Dos"]);
+
+        let error = ejecutar_programa(vec!["1 triple".to_string()], &mut externas);
+        assert!(matches!(error, Err(Error::WordNotFound)));
+    }
+
+    #[test]
+    fn test_integracion_9_palabras_definidas() {
+        let biblioteca = vec![
+            ": Siguiente 1 + ;".to_string(),
+            ": anterior".to_string(),
+            "1 - ;".to_string(),
+        ];
+        let nombres = palabras_definidas(biblioteca).unwrap();
+        assert_eq!(nombres, vec!["anterior", "siguiente"]);
+
+        let con_pila = palabras_definidas(vec![": uno 1 ; uno".to_string()]);
+        assert!(matches!(con_pila, Err(Error::InvalidWord)));
+        let con_texto = palabras_definidas(vec![".\" hola\"".to_string()]);
+        assert!(matches!(con_texto, Err(Error::InvalidWord)));
+        let con_externas = palabras_definidas(vec!["doble".to_string()]);
+        assert!(matches!(con_externas, Err(Error::WordNotFound)));
+    }
+
+    #[test]
+    fn test_integracion_10_condicional_con_mayusculas() {
+        let texto = vec![
+            ": Doble 2 * ;".to_string(),
+            ": Elegir IF 3 DOBLE .\" Verdadero\" ELSE 4 Doble .\" Falso\" THEN ;".to_string(),
+            "1 elegir 0 ELEGIR".to_string(),
+        ];
+        let (output, stack) = interpretar_texto(texto);
+        assert_eq!(output, "verdadero falso ");
+        assert_eq!(stack, "6 8 ");
+    }
+
+    #[test]
+    fn test_integracion_11_palabras_con_comilla_sin_externas() {
+        let texto = vec![
+            ": cita\" 2 ;".to_string(),
+            ": otra\" cita\" ;".to_string(),
+            "cita\" otra\" +".to_string(),
+        ];
+        let (output, stack) = interpretar_texto(texto);
+        assert_eq!(output, "");
+        assert_eq!(stack, "4 ");
+
+        let (output, stack) = interpretar_texto(vec!["1 largo\" ab\"".to_string()]);
+        assert_eq!(output, "?");
+        assert_eq!(stack, "1 ");
+    }
 }
@@ -5,14 +5,61 @@ use std::{
     fmt::Display,
     fs::OpenOptions,
     io::Write,
-    sync::mpsc::{Receiver, Sender, channel},
+    sync::{
+        Arc,
+        atomic::{AtomicU8, Ordering},
+        mpsc::{Receiver, Sender, channel},
+    },
     thread::spawn,
 };
+
+/// Nivel minimo de los mensajes que registra el logger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NivelLog {
+    Debug = 0,
+    Info = 1,
+    Warning = 2,
+    Error = 3,
+}
+
+impl NivelLog {
+    /// Obtiene el nivel a partir de su nombre (ej: `warning`)
+    pub fn from_nombre(nombre: &str) -> Option<Self> {
+        match nombre.to_lowercase().as_str() {
+            "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warning" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+
+    fn from_u8(valor: u8) -> Self {
+        match valor {
+            0 => Self::Debug,
+            1 => Self::Info,
+            2 => Self::Warning,
+            _ => Self::Error,
+        }
+    }
+}
+
 /// Struct to hold the logger sender
 #[derive(Debug, Clone)]
 pub struct Logger {
     /// Sender to send log messages
     pub logger: Sender<String>,
+    /// Nivel minimo registrado, compartido entre los clones del logger
+    nivel: Arc<AtomicU8>,
 }
 
 impl Logger {
@@ -29,13 +76,35 @@ impl Logger {
             write_to_file(&filename, receiver);
         });
 
-        Logger { logger }
+        Logger {
+            logger,
+            nivel: Arc::new(AtomicU8::new(NivelLog::Debug as u8)),
+        }
     }
 
     pub fn null() -> Self {
         let (sender, receiver) = channel::<String>();
         spawn(move || for _ in receiver {});
-        Self { logger: sender }
+        Self {
+            logger: sender,
+            nivel: Arc::new(AtomicU8::new(NivelLog::Debug as u8)),
+        }
+    }
+
+    /// Cambia el nivel minimo de los mensajes registrados. El cambio aplica
+    /// a todos los clones del logger
+    /// # Parametros
+    /// * `nivel`: nuevo nivel minimo
+    pub fn set_nivel(&self, nivel: NivelLog) {
+        self.nivel.store(nivel as u8, Ordering::Relaxed);
+    }
+
+    pub fn nivel(&self) -> NivelLog {
+        NivelLog::from_u8(self.nivel.load(Ordering::Relaxed))
+    }
+
+    fn habilitado(&self, nivel: NivelLog) -> bool {
+        nivel >= self.nivel()
     }
 
     /// Registra un mensaje de tipo info
@@ -43,6 +112,9 @@ impl Logger {
     /// * `message`: mensaje a registrar
     /// * `module`: unidad logica del programa que emite la informacion
     pub fn info(&self, message: &str, module: &str) {
+        if !self.habilitado(NivelLog::Info) {
+            return;
+        }
         let now = chrono::Local::now();
         let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
         let log_message = format!("[INFO] - {timestamp} - {module}: {message}");
@@ -54,6 +126,9 @@ impl Logger {
     /// * `message`: mensaje a registrar
     /// * `module`: unidad logica del programa donde se produjo el error
     pub fn error(&self, message: &str, module: &str) {
+        if !self.habilitado(NivelLog::Error) {
+            return;
+        }
         let now = chrono::Local::now();
         let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
         let log_message = format!("[ERROR] - {timestamp} - {module}: {message}");
@@ -65,6 +140,9 @@ impl Logger {
     /// * `message`: mensaje a registrar
     /// * `module`: unidad logica del programa que emite la informacion
     pub fn warn(&self, message: &str, module: &str) {
+        if !self.habilitado(NivelLog::Warning) {
+            return;
+        }
         let now = chrono::Local::now();
         let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
         let log_message = format!("[WARNING] - {timestamp} - {module}: {message}");
@@ -77,6 +155,9 @@ impl Logger {
     /// * `module`: unidad logica del programa que emite la informacion
     /// * `data`: informacion del caso particular
     pub fn debug<T: Display>(&self, message: &str, module: &str, data: T) {
+        if !self.habilitado(NivelLog::Debug) {
+            return;
+        }
         let now = chrono::Local::now();
         let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
         let log_message = format!("[DEBUG] - {timestamp} - {module}: {message} - {data}");
@@ -183,4 +264,23 @@ mod tests {
         }
         remove_file("test_struct.log").unwrap_or_default();
     }
+
+    #[test]
+    fn test_nivel_filtra_mensajes() {
+        let logger = super::Logger::new("test_nivel.log");
+        let clon = logger.clone();
+        logger.set_nivel(super::NivelLog::Warning);
+        assert_eq!(clon.nivel(), super::NivelLog::Warning);
+
+        clon.info("This message is filtered", "test_module");
+        clon.warn("This is a warning message", "test_module");
+        sleep(time::Duration::from_millis(100));
+
+        let file = std::fs::File::open("test_nivel.log").unwrap();
+        let reader = std::io::BufReader::new(file);
+        let lines: Vec<String> = reader.lines().map_while(Result::ok).collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("[WARNING]"));
+        remove_file("test_nivel.log").unwrap_or_default();
+    }
 }
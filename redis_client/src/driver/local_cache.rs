@@ -0,0 +1,211 @@
+//! Este modulo contiene la cache local de respuestas del driver, que se
+//! mantiene consistente con los mensajes de invalidacion que el nodo
+//! publica, para las conexiones con CLIENT TRACKING activo, en el canal de
+//! invalidaciones de la conexion indicada con REDIRECT
+use std::collections::HashMap;
+
+use crate::tipos_datos::traits::DatoRedis;
+
+/// Channel in which the node publishes the invalidation messages
+pub const INVALIDATION_CHANNEL: &str = "__redis__:invalidate";
+
+/// Command that removes every key of the node
+const FLUSHALL: &str = "FLUSHALL";
+
+/// Read commands whose responses are cached. All of them read only the key
+/// given as their first argument
+const CACHEABLE_COMMANDS: [&str; 15] = [
+    "GET",
+    "STRLEN",
+    "GETBIT",
+    "BITCOUNT",
+    "LRANGE",
+    "LINDEX",
+    "LLEN",
+    "LPOS",
+    "SMEMBERS",
+    "SISMEMBER",
+    "TYPE",
+    "XLEN",
+    "XRANGE",
+    "XREVRANGE",
+    "GEOPOS",
+];
+
+/// Responses of read commands, grouped by the key they read
+#[derive(Debug, Default)]
+pub struct LocalCache {
+    entries: HashMap<String, HashMap<Vec<String>, DatoRedis>>,
+}
+
+impl LocalCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached response of a command, if there is one
+    pub fn get(&self, command: &[String]) -> Option<DatoRedis> {
+        let (key, command) = cache_entry(command)?;
+        self.entries.get(&key)?.get(&command).cloned()
+    }
+
+    /// Stores the response of a command, if the command is cacheable
+    pub fn insert(&mut self, command: &[String], response: DatoRedis) {
+        if let Some((key, command)) = cache_entry(command) {
+            self.entries
+                .entry(key)
+                .or_default()
+                .insert(command, response);
+        }
+    }
+
+    /// Discards the responses of the given keys, or every response if
+    /// `keys` is None
+    pub fn invalidate(&mut self, keys: Option<Vec<String>>) {
+        match keys {
+            Some(keys) => {
+                for key in keys {
+                    self.entries.remove(&key);
+                }
+            }
+            None => self.entries.clear(),
+        }
+    }
+
+    /// Discards the responses of the keys a command that is not cacheable
+    /// may modify, before sending it. Every argument is taken as a
+    /// possible key, and FLUSHALL discards every response. Otherwise a read
+    /// right after a write could be answered from the cache before the
+    /// node's invalidation arrives
+    pub fn invalidate_written(&mut self, command: &[String]) {
+        if cache_entry(command).is_some() {
+            return;
+        }
+        match command.first() {
+            Some(name) if name.eq_ignore_ascii_case(FLUSHALL) => self.invalidate(None),
+            _ => self.invalidate(Some(command.iter().skip(1).cloned().collect())),
+        }
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.entries.values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Returns the key read by a cacheable command and the command with its
+/// name in uppercase, None if the command is not cacheable
+fn cache_entry(command: &[String]) -> Option<(String, Vec<String>)> {
+    let name = command.first()?.to_uppercase();
+    let key = command.get(1)?.clone();
+    if !CACHEABLE_COMMANDS.contains(&name.as_str()) {
+        return None;
+    }
+    let mut normalized = command.to_vec();
+    normalized[0] = name;
+    Some((key, normalized))
+}
+
+/// Parses an invalidation message, a pub/sub message of the invalidation
+/// channel (`["message", "__redis__:invalidate", [key, ...]]`)
+///
+/// # Returns
+/// * None if the message is not an invalidation, Some with the invalidated
+///   keys otherwise, or Some(None) if every key was invalidated
+pub fn invalidated_keys(message: &DatoRedis) -> Option<Option<Vec<String>>> {
+    let DatoRedis::Arrays(message) = message else {
+        return None;
+    };
+    if message.len() != 3 {
+        return None;
+    }
+    match (message.get(0)?, message.get(1)?) {
+        (DatoRedis::BulkString(kind), DatoRedis::BulkString(channel))
+            if kind.contenido() == "message" && channel.contenido() == INVALIDATION_CHANNEL => {}
+        _ => return None,
+    }
+    match message.get(2)? {
+        DatoRedis::Null(_) => Some(None),
+        DatoRedis::Arrays(keys) => Some(Some(
+            keys.iter()
+                .filter_map(|key| match key {
+                    DatoRedis::BulkString(key) => Some(key.contenido()),
+                    _ => None,
+                })
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::protocol::dataencryption::encrypt_resp;
+    use crate::protocol::protocol_resp::resp_client_command_read;
+
+    fn tokens(command: &[&str]) -> Vec<String> {
+        command.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn bulk(value: &str) -> DatoRedis {
+        DatoRedis::new_bulk_string(value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_01_caches_only_read_commands_until_invalidated() {
+        let mut cache = LocalCache::new();
+        cache.insert(&tokens(&["get", "a"]), bulk("1"));
+        cache.insert(&tokens(&["LRANGE", "b", "0", "-1"]), bulk("2"));
+        cache.insert(&tokens(&["SET", "a", "2"]), bulk("OK"));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&tokens(&["GET", "a"])), Some(bulk("1")));
+        assert_eq!(cache.get(&tokens(&["LRANGE", "b", "0", "1"])), None);
+
+        cache.invalidate(Some(tokens(&["a"])));
+        assert_eq!(cache.get(&tokens(&["GET", "a"])), None);
+        cache.invalidate(None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_03_commands_that_are_not_cacheable_discard_their_keys() {
+        let mut cache = LocalCache::new();
+        cache.insert(&tokens(&["GET", "a"]), bulk("1"));
+        cache.insert(&tokens(&["GET", "b"]), bulk("2"));
+        cache.invalidate_written(&tokens(&["GET", "a"]));
+        assert_eq!(cache.len(), 2);
+
+        cache.invalidate_written(&tokens(&["SET", "a", "3"]));
+        assert_eq!(cache.get(&tokens(&["GET", "a"])), None);
+        assert_eq!(cache.get(&tokens(&["GET", "b"])), Some(bulk("2")));
+
+        cache.invalidate_written(&tokens(&["flushall"]));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_02_parses_invalidation_messages() {
+        let read = |resp: &str| {
+            let encrypted = encrypt_resp(resp).unwrap();
+            resp_client_command_read(&mut Cursor::new(encrypted)).unwrap()
+        };
+        let message = "*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n";
+        let keys = read(&format!("{message}*2\r\n$1\r\na\r\n$1\r\nb\r\n"));
+        assert_eq!(invalidated_keys(&keys), Some(Some(tokens(&["a", "b"]))));
+        let flush = read(&format!("{message}_\r\n"));
+        assert_eq!(invalidated_keys(&flush), Some(None));
+        // Replies shaped like the old invalidation arrays are not
+        // invalidations
+        let reply = read("*2\r\n$10\r\ninvalidate\r\n*1\r\n$1\r\na\r\n");
+        assert_eq!(invalidated_keys(&reply), None);
+        let other_channel = read("*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n_\r\n");
+        assert_eq!(invalidated_keys(&other_channel), None);
+    }
+}
@@ -1,5 +1,6 @@
 use redis_driver_error::RedisDriverError;
 
+pub mod local_cache;
 pub mod redis_driver;
 pub mod redis_driver_error;
 pub mod traits;
@@ -5,6 +5,7 @@ use std::{
 
 use super::RedisDriverResult;
 use crate::{
+    driver::local_cache::{INVALIDATION_CHANNEL, LocalCache, invalidated_keys},
     driver::redis_driver_error::{RedisDriverError, RedisDriverErrorKind},
     protocol::protocol_resp::{resp_client_command_read, resp_client_command_write},
     tipos_datos::traits::DatoRedis,
@@ -25,6 +26,12 @@ pub struct RedisDriver {
     user: Option<String>,
     /// Contains the password of the node
     password: Option<String>,
+    /// Contains the local cache of read responses, if enabled
+    cache: Option<LocalCache>,
+    /// Contains the connection subscribed to the invalidation channel,
+    /// which receives the invalidations of the keys read through
+    /// `connection` while the local cache is enabled
+    invalidations: Option<TcpStream>,
 }
 
 impl Deref for RedisDriver {
@@ -58,6 +65,8 @@ impl RedisDriver {
             connection: stream,
             user: None,
             password: None,
+            cache: None,
+            invalidations: None,
         };
 
         Ok(result)
@@ -86,6 +95,8 @@ impl RedisDriver {
             connection: stream,
             user: Some(user.to_string()),
             password: Some(password.to_string()),
+            cache: None,
+            invalidations: None,
         };
 
         Ok(result)
@@ -116,8 +127,40 @@ impl RedisDriver {
         Ok(response)
     }
 
+    /// Enables the local cache of read responses. The node is asked to
+    /// track the keys read through this connection and to redirect their
+    /// invalidations (`CLIENT TRACKING on REDIRECT id`) to a dedicated
+    /// connection subscribed to the invalidation channel. The cached
+    /// responses are discarded when it reports that their keys changed
+    /// # Returns
+    /// * `RedisDriverResult<()>`: Ok if tracking was enabled, Err if there was an error
+    pub fn enable_cache(&mut self) -> RedisDriverResult<()> {
+        self.start_tracking()?;
+        self.cache = Some(LocalCache::new());
+        Ok(())
+    }
+
+    /// Disables the local cache of read responses and the tracking of the
+    /// keys read through this connection
+    /// # Returns
+    /// * `RedisDriverResult<()>`: Ok if tracking was disabled, Err if there was an error
+    pub fn disable_cache(&mut self) -> RedisDriverResult<()> {
+        self.cache = None;
+        self.invalidations = None;
+        self.uncached_command(tracking_command("OFF"))?;
+        Ok(())
+    }
+
+    /// Returns the local cache of read responses, if enabled
+    pub fn cache(&self) -> Option<&LocalCache> {
+        self.cache.as_ref()
+    }
+
     /// Sends a command to the redis node and haStringndles MOVED errors
     /// by reconnecting to the new node and resending the command.
+    /// If the local cache is enabled, read commands are answered from it
+    /// when possible, and the responses of the keys other commands may
+    /// modify are discarded before sending them.
     /// # Arguments
     /// * `command`: command to send to the redis node
     /// # Returns
@@ -125,6 +168,26 @@ impl RedisDriver {
     /// # Errors
     /// * `RedisDriverError`: if there was an error reading the response or if the MOVED error format is invalid    
     pub fn safe_command(&mut self, command: Vec<String>) -> RedisDriverResult<DatoRedis> {
+        if self.cache.is_none() {
+            return self.uncached_command(command);
+        }
+        self.receive_pending_invalidations()?;
+        if let Some(response) = self.cache.as_ref().and_then(|cache| cache.get(&command)) {
+            return Ok(response);
+        }
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate_written(&command);
+        }
+        let response = self.uncached_command(command.clone())?;
+        if let Some(cache) = &mut self.cache {
+            cache.insert(&command, response.clone());
+        }
+        Ok(response)
+    }
+
+    /// Sends a command to the redis node, handling MOVED errors, without
+    /// going through the local cache
+    fn uncached_command(&mut self, command: Vec<String>) -> RedisDriverResult<DatoRedis> {
         self.command(command.clone())?;
         let response = self.receive_response();
         match response {
@@ -138,6 +201,11 @@ impl RedisDriver {
                         let new_connection =
                             auth_handshake_connect(addr.as_str(), &user, &password)?;
                         self.connection = new_connection;
+                        if let Some(cache) = &mut self.cache {
+                            // The new node does not track the keys read yet
+                            cache.invalidate(None);
+                            self.start_tracking()?;
+                        }
                         self.command(command)?;
                         self.receive_response()
                     } else {
@@ -155,6 +223,67 @@ impl RedisDriver {
             )),
         }
     }
+
+    /// Opens the connection that receives the invalidations, subscribed to
+    /// the invalidation channel of the node this driver is connected to,
+    /// and enables the tracking of this connection redirected to it
+    fn start_tracking(&mut self) -> RedisDriverResult<()> {
+        let addr = self.connection.peer_addr()?.to_string();
+        let mut invalidations = match (&self.user, &self.password) {
+            (Some(user), Some(password)) => auth_handshake_connect(&addr, user, password)?,
+            _ => no_auth_handshake_connnect(&addr)?,
+        };
+        resp_api_command_write(
+            vec!["CLIENT".to_string(), "ID".to_string()],
+            &mut invalidations,
+        )?;
+        let DatoRedis::Integer(id) = resp_client_command_read(&mut invalidations)? else {
+            return Err(RedisDriverError::new(
+                "Invalid CLIENT ID reply".to_string(),
+                RedisDriverErrorKind::ProtocolError,
+            ));
+        };
+        resp_api_command_write(
+            vec!["SUBSCRIBE".to_string(), INVALIDATION_CHANNEL.to_string()],
+            &mut invalidations,
+        )?;
+        resp_client_command_read(&mut invalidations)?;
+
+        let mut tracking = tracking_command("ON");
+        tracking.extend(["REDIRECT".to_string(), id.valor().to_string()]);
+        self.uncached_command(tracking)?;
+        self.invalidations = Some(invalidations);
+        Ok(())
+    }
+
+    /// Applies to the local cache the invalidation messages already
+    /// received, without waiting for new ones. If the invalidation
+    /// connection was closed the cache can no longer be kept consistent,
+    /// so it is disabled
+    fn receive_pending_invalidations(&mut self) -> RedisDriverResult<()> {
+        while let Some(invalidations) = &mut self.invalidations {
+            invalidations.set_nonblocking(true)?;
+            let mut buf = [0; 1];
+            let peeked = invalidations.peek(&mut buf);
+            invalidations.set_nonblocking(false)?;
+            match peeked {
+                Ok(0) => {
+                    self.cache = None;
+                    self.invalidations = None;
+                }
+                Ok(_) => {
+                    let message = resp_client_command_read(invalidations)?;
+                    if let (Some(cache), Some(keys)) = (&mut self.cache, invalidated_keys(&message))
+                    {
+                        cache.invalidate(keys);
+                    }
+                }
+                Err(_) => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+
     /// Receives a response from redis node and returns it as a redis driver result string
     pub fn receive_response_as_string(&mut self) -> RedisDriverResult<String> {
         self.connection.set_nonblocking(true)?; // Neeeded to avoid blocking states
@@ -177,6 +306,14 @@ impl RedisDriver {
     }
 }
 
+fn tracking_command(mode: &str) -> Vec<String> {
+    vec![
+        "CLIENT".to_string(),
+        "TRACKING".to_string(),
+        mode.to_string(),
+    ]
+}
+
 fn extract_addr_from_moved_err(mensaje: String) -> Result<String, RedisDriverError> {
     let (_slot, addr) = mensaje.split_once(" ").map(|s| (s.0, s.1)).ok_or_else(|| {
         RedisDriverError::new(
@@ -249,3 +386,78 @@ impl std::fmt::Display for RedisDriver {
         write!(f, "Connected to {}:{}", self.hostname, self.port)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread::spawn;
+
+    use super::*;
+    use crate::protocol::protocol_resp::{resp_server_command_read, resp_server_command_write};
+
+    /// Starts a node that answers GET and SET and never publishes
+    /// invalidations, so the driver can only keep its cache consistent on
+    /// its own. Returns its port
+    fn start_node() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let values = Arc::new(Mutex::new(HashMap::<Vec<u8>, Vec<u8>>::new()));
+        spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let values = values.clone();
+                spawn(move || {
+                    while let Ok(command) = resp_server_command_read(&mut stream) {
+                        let mut values = values.lock().unwrap();
+                        let response = match command[0].to_uppercase().as_str() {
+                            "GET" => match values.get(&command[1].to_vec()) {
+                                Some(value) => {
+                                    DatoRedis::new_bulk_string_desde_bytes(value.clone()).unwrap()
+                                }
+                                None => DatoRedis::new_null(),
+                            },
+                            "SET" => {
+                                values.insert(command[1].to_vec(), command[2].to_vec());
+                                DatoRedis::new_simple_string("OK".to_string()).unwrap()
+                            }
+                            "CLIENT" if command[1].eq_ignore_ascii_case("ID") => {
+                                DatoRedis::new_integer(1)
+                            }
+                            _ => DatoRedis::new_simple_string("OK".to_string()).unwrap(),
+                        };
+                        let response = response.convertir_a_protocolo_resp_bytes();
+                        if resp_server_command_write(&response, &mut stream).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    fn command(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_01_cached_key_is_read_back_after_writing_it() {
+        let mut driver = RedisDriver::connect("127.0.0.1", start_node()).unwrap();
+        driver.enable_cache().unwrap();
+        let bulk = |value: &str| DatoRedis::new_bulk_string(value.to_string()).unwrap();
+
+        driver.safe_command(command(&["SET", "key", "1"])).unwrap();
+        assert_eq!(
+            driver.safe_command(command(&["GET", "key"])).unwrap(),
+            bulk("1")
+        );
+        assert_eq!(driver.cache().map(LocalCache::len), Some(1));
+
+        driver.safe_command(command(&["SET", "key", "2"])).unwrap();
+        assert_eq!(
+            driver.safe_command(command(&["GET", "key"])).unwrap(),
+            bulk("2")
+        );
+    }
+}
@@ -41,7 +41,7 @@ fn to_hex_string(bytes: &[u8]) -> String {
 /// Un `Vec<u8>` con los bytes decodificados.
 fn from_hex_string(hex: &str) -> Result<Vec<u8>, String> {
     let bytes = hex.as_bytes();
-    if bytes.len() % 2 != 0 {
+    if !bytes.len().is_multiple_of(2) {
         return Err("Longitud impar en string hexadecimal".to_string());
     }
 
@@ -75,19 +75,19 @@ fn hex_char_to_value(c: u8) -> Result<u8, String> {
     }
 }
 
-/// Encripta un string y lo convierte a hexadecimal.
+/// Encripta una secuencia de bytes y la convierte a hexadecimal.
 ///
 /// Internamente usa AES-128-CBC con un IV aleatorio,
 /// y empaqueta la longitud total, el IV y el ciphertext.
 ///
 /// # Argumentos
-/// - `input`: String a encriptar.
+/// - `input`: Bytes a encriptar.
 ///
 /// # Retorna
 /// Un `Ok(String)` con el contenido hexadecimal del mensaje encriptado,
 /// o un `Err(String)` si falla la encriptación.
-pub fn encrypt_y_encode_hex(input: &str) -> Result<String, String> {
-    let encrypted = encrypt_resp(input).map_err(|_| "Error encriptando datos")?;
+pub fn encrypt_y_encode_hex(input: &[u8]) -> Result<String, String> {
+    let encrypted = encrypt_resp_bytes(input).map_err(|_| "Error encriptando datos")?;
     Ok(to_hex_string(&encrypted))
 }
 
@@ -97,31 +97,45 @@ pub fn encrypt_y_encode_hex(input: &str) -> Result<String, String> {
 /// - `input`: String hexadecimal previamente generado con [`encrypt_y_encode_hex`].
 ///
 /// # Retorna
-/// Un `Ok(String)` con el contenido desencriptado,
+/// Un `Ok(Vec<u8>)` con los bytes desencriptados,
 /// o un `Err(String)` si falla el parseo o la desencriptación.
-pub fn decrypt_from_hex(input: &str) -> Result<String, String> {
+pub fn decrypt_from_hex(input: &str) -> Result<Vec<u8>, String> {
     let bytes = from_hex_string(input)?;
     let mut cursor = Cursor::new(bytes);
 
-    decrypt_resp(&mut cursor).map_err(|e| format!("Error desencriptando: {e:?}"))
+    decrypt_resp_bytes(&mut cursor).map_err(|e| format!("Error desencriptando: {e:?}"))
 }
 
 /// Encripta una cadena RESP (como string plano) usando AES-128-CBC.
 ///
+/// # Argumentos
+/// - `resp_text`: Texto en formato RESP a encriptar.
+///
+/// # Retorna
+/// Un vector de bytes encriptados, con el formato de `encrypt_resp_bytes`.
+///
+/// # Errores
+/// Retorna un `DatoRedis` si ocurre un error con el cifrado.
+pub fn encrypt_resp(resp_text: &str) -> Result<Vec<u8>, DatoRedis> {
+    encrypt_resp_bytes(resp_text.as_bytes())
+}
+
+/// Encripta un mensaje RESP ya serializado en bytes usando AES-128-CBC.
+///
 /// El resultado incluye:
 /// - 2 bytes con la longitud total (u16 big endian)
 /// - 16 bytes de IV aleatorio
 /// - ciphertext
 ///
 /// # Argumentos
-/// - `resp_text`: Texto en formato RESP a encriptar.
+/// - `resp`: Bytes del mensaje RESP a encriptar.
 ///
 /// # Retorna
 /// Un vector de bytes encriptados, listo para enviarse o almacenarse.
 ///
 /// # Errores
 /// Retorna un `DatoRedis` si ocurre un error con el cifrado.
-pub fn encrypt_resp(resp_text: &str) -> Result<Vec<u8>, DatoRedis> {
+pub fn encrypt_resp_bytes(resp: &[u8]) -> Result<Vec<u8>, DatoRedis> {
     let mut iv = [0u8; IV_LEN];
     rand::thread_rng().fill(&mut iv);
 
@@ -132,7 +146,7 @@ pub fn encrypt_resp(resp_text: &str) -> Result<Vec<u8>, DatoRedis> {
         )
     })?;
 
-    let ciphertext = cipher.encrypt_vec(resp_text.as_bytes());
+    let ciphertext = cipher.encrypt_vec(resp);
 
     let total_len = (IV_LEN + ciphertext.len()) as u16;
     let mut result = Vec::with_capacity(2 + IV_LEN + ciphertext.len());
@@ -160,8 +174,26 @@ pub fn encrypt_resp(resp_text: &str) -> Result<Vec<u8>, DatoRedis> {
 /// Devuelve un `DatoRedis` con mensajes de error en caso de:
 /// - Error al leer del stream
 /// - Error al construir el cipher
-/// - Error al desencriptar o decodificar UTF-8.
+/// - Error al desencriptar.
+/// - El contenido desencriptado no es UTF-8 valido.
 pub fn decrypt_resp(stream: &mut dyn Read) -> Result<String, DatoRedis> {
+    String::from_utf8(decrypt_resp_bytes(stream)?).map_err(|_| {
+        DatoRedis::new_simple_error(
+            "SECURITY".to_string(),
+            "Decrypted message is not valid UTF-8.".to_string(),
+        )
+    })
+}
+
+/// Desencripta un mensaje previamente cifrado con `encrypt_resp`,
+/// retornando los bytes originales sin interpretarlos como texto.
+///
+/// # Argumentos
+/// - `stream`: Objeto que implementa `Read` desde el cual se leerán los datos encriptados.
+///
+/// # Errores
+/// Devuelve un `DatoRedis` si falla la lectura o la desencriptación.
+pub fn decrypt_resp_bytes(stream: &mut dyn Read) -> Result<Vec<u8>, DatoRedis> {
     let mut len_buf = [0u8; 2];
     stream.read_exact(&mut len_buf).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -189,28 +221,87 @@ pub fn decrypt_resp(stream: &mut dyn Read) -> Result<String, DatoRedis> {
         )
     })?;
 
-    let decrypted_data = cipher.decrypt_vec(ciphertext).map_err(|_| {
+    cipher.decrypt_vec(ciphertext).map_err(|_| {
         DatoRedis::new_simple_error(
             "SECURITY".to_string(),
             "Error decrypting message.".to_string(),
         )
+    })
+}
+
+/// Encripta un buffer de bytes arbitrario usando AES-128-CBC.
+///
+/// A diferencia de [`encrypt_resp`], no agrega prefijo de longitud: el
+/// resultado es el IV de 16 bytes seguido del ciphertext, y es responsabilidad
+/// de quien lo almacene registrar su largo.
+///
+/// # Argumentos
+/// - `datos`: Bytes a encriptar.
+///
+/// # Retorna
+/// Un vector con el IV y el ciphertext.
+///
+/// # Errores
+/// Retorna un `DatoRedis` si ocurre un error con el cifrado.
+pub fn encrypt_bytes(datos: &[u8]) -> Result<Vec<u8>, DatoRedis> {
+    let mut iv = [0u8; IV_LEN];
+    rand::thread_rng().fill(&mut iv);
+
+    let cipher = Aes128Cbc::new_from_slices(AES_KEY, &iv).map_err(|_| {
+        DatoRedis::new_simple_error(
+            "SECURITY".to_string(),
+            "Error creating cipher for encryption.".to_string(),
+        )
     })?;
 
-    let decrypted_string = String::from_utf8(decrypted_data).map_err(|_| {
+    let ciphertext = cipher.encrypt_vec(datos);
+    let mut result = Vec::with_capacity(IV_LEN + ciphertext.len());
+    result.extend_from_slice(&iv);
+    result.extend_from_slice(&ciphertext);
+    Ok(result)
+}
+
+/// Desencripta un buffer generado con [`encrypt_bytes`].
+///
+/// # Argumentos
+/// - `datos`: IV seguido del ciphertext.
+///
+/// # Retorna
+/// Los bytes originales.
+///
+/// # Errores
+/// Retorna un `DatoRedis` si el buffer es demasiado corto o no puede descifrarse.
+pub fn decrypt_bytes(datos: &[u8]) -> Result<Vec<u8>, DatoRedis> {
+    if datos.len() < IV_LEN {
+        return Err(DatoRedis::new_simple_error(
+            "SECURITY".to_string(),
+            "Error reading encrypted message.".to_string(),
+        ));
+    }
+    let (iv, ciphertext) = datos.split_at(IV_LEN);
+
+    let cipher = Aes128Cbc::new_from_slices(AES_KEY, iv).map_err(|_| {
         DatoRedis::new_simple_error(
             "SECURITY".to_string(),
-            "Decrypted message is not valid UTF-8.".to_string(),
+            "Error decrypting message.".to_string(),
         )
     })?;
 
-    Ok(decrypted_string)
+    cipher.decrypt_vec(ciphertext).map_err(|_| {
+        DatoRedis::new_simple_error(
+            "SECURITY".to_string(),
+            "Error decrypting message.".to_string(),
+        )
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
 
-    use crate::protocol::dataencryption::{decrypt_resp, encrypt_resp};
+    use crate::protocol::dataencryption::{
+        decrypt_bytes, decrypt_resp, encrypt_bytes, encrypt_resp,
+    };
 
     #[test]
     fn test_01_encrypt_and_decrypt_resp() {
@@ -232,4 +323,13 @@ mod tests {
         let decrypted_resp = decrypt_resp(&mut stream).unwrap();
         assert_eq!(resp, decrypted_resp);
     }
+
+    #[test]
+    fn test_03_encrypt_and_decrypt_bytes() {
+        let datos: Vec<u8> = (0..=255u8).chain(0..=255u8).collect();
+        let encrypted = encrypt_bytes(&datos).unwrap();
+        assert_ne!(encrypted, datos);
+        assert_eq!(decrypt_bytes(&encrypted).unwrap(), datos);
+        assert!(decrypt_bytes(&encrypted[..4]).is_err());
+    }
 }
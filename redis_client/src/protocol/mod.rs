@@ -1,3 +1,4 @@
 pub mod constants;
 pub mod dataencryption;
 pub mod protocol_resp;
+pub mod token;
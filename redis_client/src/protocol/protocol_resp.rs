@@ -1,7 +1,10 @@
-use crate::protocol::dataencryption::decrypt_resp;
+use crate::protocol::dataencryption::{decrypt_resp_bytes, encrypt_resp_bytes};
+use crate::protocol::token::Token;
+use crate::tipos_datos::constantes::*;
 use crate::tipos_datos::traits::DatoRedis;
-use crate::{protocol::dataencryption::encrypt_resp, tipos_datos::constantes::*};
 use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::iter::Peekable;
+use std::str::Chars;
 
 /// Serializa un comando en formato RESP y lo envía por el stream al servidor.
 ///
@@ -26,7 +29,7 @@ use std::io::{BufRead, BufReader, Cursor, Read, Write};
 /// - `Ok(())` si el comando fue enviado correctamente.
 /// - `Err(DatoRedis::SimpleError)` si hubo un error en la construcción del comando o al escribir en el stream.
 pub fn resp_client_command_write(comando: String, stream: &mut dyn Write) -> Result<(), DatoRedis> {
-    let entrada_split: Vec<String> = parsear_comando(comando);
+    let entrada_split = parsear_comando(comando);
 
     if entrada_split.is_empty() {
         return Err(DatoRedis::new_simple_error(
@@ -38,17 +41,30 @@ pub fn resp_client_command_write(comando: String, stream: &mut dyn Write) -> Res
     resp_api_command_write(entrada_split, stream)
 }
 
-/// Falta documentar
-pub fn resp_api_command_write(
-    comando: Vec<String>,
+/// Serializa un comando ya separado en tokens como array RESP de bulk
+/// strings y lo envía encriptado por el stream. Cada token se envía con
+/// sus bytes tal cual, por lo que puede contener datos binarios.
+///
+/// # Parámetros
+/// - `comando`: tokens del comando, como texto o como bytes.
+/// - `stream`: el stream donde se escribe el comando serializado.
+///
+/// # Retorna
+/// - `Ok(())` si el comando fue enviado correctamente, error de redis en
+///   otro caso.
+pub fn resp_api_command_write<T: AsRef<[u8]>>(
+    comando: Vec<T>,
     stream: &mut dyn Write,
 ) -> Result<(), DatoRedis> {
-    let mut comando_resp = format!("*{}\r\n", comando.len());
+    let mut comando_resp = format!("*{}\r\n", comando.len()).into_bytes();
     for token in comando {
-        comando_resp.push_str(&format!("${}\r\n{}\r\n", token.len(), token));
+        let token = token.as_ref();
+        comando_resp.extend_from_slice(format!("${}\r\n", token.len()).as_bytes());
+        comando_resp.extend_from_slice(token);
+        comando_resp.extend_from_slice(b"\r\n");
     }
 
-    let encrypted: Vec<u8> = encrypt_resp(&comando_resp)?;
+    let encrypted: Vec<u8> = encrypt_resp_bytes(&comando_resp)?;
 
     if stream.write_all(&encrypted).is_err() {
         return Err(DatoRedis::new_simple_error(
@@ -72,7 +88,7 @@ pub fn resp_api_command_write(
 /// * `comandos` - Un `String` que contiene el comando a parsear.
 ///
 /// # Retorna
-/// - Un `Vec<String>` que contiene cada componente del comando, en orden.
+/// - Un `Vec<Token>` que contiene cada componente del comando, en orden.
 ///
 /// # Comportamiento especial
 /// - Si un token comienza con `"` pero no termina con `"`, se considera el
@@ -80,6 +96,9 @@ pub fn resp_api_command_write(
 /// - Se sigue agregando texto a ese token hasta encontrar otro con `"` al final.
 /// - Las comillas no se eliminan ni escapan; se preservan tal como están.
 /// - Si hay un token entre comillas no cerradas, se incluye de todas formas.
+/// - `\xHH` representa el byte con valor hexadecimal `HH`, lo que permite
+///   ingresar datos binarios.
+/// - Un par de comillas sin contenido (`""`) representa un token vacio.
 ///
 /// # Ejemplos
 /// ```
@@ -92,22 +111,25 @@ pub fn resp_api_command_write(
 ///     "mi valor con espacios",
 /// ]);
 /// ```
-pub fn parsear_comando(line: String) -> Vec<String> {
+pub fn parsear_comando(line: String) -> Vec<Token> {
     let mut tokens = Vec::new();
-    let mut current = String::new();
+    let mut current = Vec::new();
     let mut in_quote: Option<char> = None;
+    let mut con_comillas = false;
     let mut escape = false;
+    let mut chars = line.chars().peekable();
 
-    for ch in line.chars() {
+    while let Some(ch) = chars.next() {
         if escape {
             match ch {
-                'n' => current.push('\n'),
-                't' => current.push('\t'),
-                'r' => current.push('\r'),
-                '\\' => current.push('\\'),
-                '\'' => current.push('\''),
-                '"' => current.push('"'),
-                _ => current.push(ch),
+                'n' => current.push(b'\n'),
+                't' => current.push(b'\t'),
+                'r' => current.push(b'\r'),
+                'x' => match leer_byte_hexadecimal(&mut chars) {
+                    Some(byte) => current.push(byte),
+                    None => agregar_caracter(&mut current, ch),
+                },
+                _ => agregar_caracter(&mut current, ch),
             }
             escape = false;
             continue;
@@ -122,28 +144,104 @@ pub fn parsear_comando(line: String) -> Vec<String> {
             if ch == quote_char {
                 in_quote = None;
             } else {
-                current.push(ch);
+                agregar_caracter(&mut current, ch);
             }
         } else if ch == '\'' || ch == '"' {
             in_quote = Some(ch);
+            con_comillas = true;
         } else if ch.is_whitespace() {
-            if !current.is_empty() {
-                tokens.push(current.clone());
-                current.clear();
+            if !current.is_empty() || con_comillas {
+                tokens.push(Token::new(std::mem::take(&mut current)));
+                con_comillas = false;
             }
         } else {
-            current.push(ch);
+            agregar_caracter(&mut current, ch);
         }
     }
 
-    if !current.is_empty() {
-        tokens.push(current);
+    if !current.is_empty() || con_comillas {
+        tokens.push(Token::new(current));
     }
 
     tokens
 }
 
-/// Lee desde el stream un comando RESP enviado por el cliente y lo parsea como un vector de strings.
+/// Agrega a un token los bytes UTF-8 de un caracter
+fn agregar_caracter(token: &mut Vec<u8>, ch: char) {
+    let mut buffer = [0u8; 4];
+    token.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+}
+
+/// Consume los dos digitos hexadecimales de una secuencia `\xHH`, de ser
+/// validos
+fn leer_byte_hexadecimal(chars: &mut Peekable<Chars>) -> Option<u8> {
+    let mut siguientes = chars.clone();
+    let digitos: String = [siguientes.next()?, siguientes.next()?].iter().collect();
+    let byte = u8::from_str_radix(&digitos, 16).ok()?;
+    chars.nth(1);
+    Some(byte)
+}
+
+/// Representa un token de forma que `parsear_comando` lo obtenga
+/// nuevamente: los tokens con espacios, comillas, caracteres de control o
+/// bytes que no son UTF-8 valido se escriben entre comillas dobles,
+/// escapando esos caracteres, al igual que los tokens vacios
+///
+/// # Parámetros
+/// * `token` - bytes del token a representar
+///
+/// # Retorna
+/// - Texto que representa al token
+pub fn escapar_token(token: &[u8]) -> String {
+    if let Ok(texto) = std::str::from_utf8(token)
+        && !texto.is_empty()
+        && texto
+            .chars()
+            .all(|c| !c.is_whitespace() && !c.is_control() && !matches!(c, '"' | '\'' | '\\'))
+    {
+        return texto.to_string();
+    }
+    entrecomillar_token(token)
+}
+
+/// Representa un token entre comillas dobles, escapando las comillas, las
+/// barras, los caracteres de control y los bytes que no son UTF-8 valido
+///
+/// # Parámetros
+/// * `token` - bytes del token a representar
+///
+/// # Retorna
+/// - Texto entre comillas que representa al token
+pub fn entrecomillar_token(token: &[u8]) -> String {
+    let mut escapado = String::from('"');
+    for fragmento in token.utf8_chunks() {
+        for c in fragmento.valid().chars() {
+            match c {
+                '"' => escapado.push_str("\\\""),
+                '\\' => escapado.push_str("\\\\"),
+                '\n' => escapado.push_str("\\n"),
+                '\r' => escapado.push_str("\\r"),
+                '\t' => escapado.push_str("\\t"),
+                _ if c.is_control() => {
+                    let mut buffer = [0u8; 4];
+                    for byte in c.encode_utf8(&mut buffer).bytes() {
+                        escapado.push_str(&format!("\\x{byte:02x}"));
+                    }
+                }
+                _ => escapado.push(c),
+            }
+        }
+        for byte in fragmento.invalid() {
+            escapado.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+    escapado.push('"');
+    escapado
+}
+
+/// Lee desde el stream un comando RESP enviado por el cliente y lo parsea
+/// como un vector de tokens, conservando los bytes de cada bulk string sin
+/// interpretarlos como texto.
 ///
 /// Espera que el formato recibido siga la especificación RESP con un array de bulk strings.
 ///
@@ -162,13 +260,11 @@ pub fn parsear_comando(line: String) -> Vec<String> {
 /// - `stream`: stream de lectura desde donde se recibe el comando.
 ///
 /// # Retorna
-/// - `Ok(Vec<String>)`: cada palabra del comando como un string.
+/// - `Ok(Vec<Token>)`: cada palabra del comando como un token.
 /// - `Err(DatoRedis::SimpleError)`: si el formato es inválido o hay errores de lectura.
-pub fn resp_server_command_read(stream: &mut dyn Read) -> Result<Vec<String>, DatoRedis> {
-    let desencrypted_resp = decrypt_resp(stream)?; // aca ya tengo el resp
-    let mut new_stream = Cursor::new(desencrypted_resp.into_bytes());
+pub fn resp_server_command_read(stream: &mut dyn Read) -> Result<Vec<Token>, DatoRedis> {
+    let mut new_stream = Cursor::new(decrypt_resp_bytes(stream)?);
 
-    // aca tengo que ver como usar el string...
     let header = read_line_custom(&mut new_stream)?;
     let cantidad = parse_array_len(&header)?;
     let mut resultado = Vec::with_capacity(cantidad);
@@ -176,7 +272,7 @@ pub fn resp_server_command_read(stream: &mut dyn Read) -> Result<Vec<String>, Da
     for _ in 0..cantidad {
         let bulk_header = read_line_custom(&mut new_stream)?;
         let bulk_len = parse_bulk_len(&bulk_header)?;
-        resultado.push(read_exact_bulk(&mut new_stream, bulk_len)?);
+        resultado.push(Token::new(read_exact_bulk(&mut new_stream, bulk_len)?));
     }
     Ok(resultado)
 }
@@ -207,8 +303,8 @@ fn parse_bulk_len(header: &str) -> Result<usize, DatoRedis> {
     })
 }
 
-/// Lee `len` bytes + `\r\n` del stream y los devuelve como `String`.
-fn read_exact_bulk(stream: &mut dyn Read, len: usize) -> Result<String, DatoRedis> {
+/// Lee `len` bytes + `\r\n` del stream y devuelve los `len` bytes.
+fn read_exact_bulk(stream: &mut dyn Read, len: usize) -> Result<Vec<u8>, DatoRedis> {
     let mut contenido = vec![0u8; len + 2]; // +2 por \r\n
     stream.read_exact(&mut contenido).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -216,7 +312,8 @@ fn read_exact_bulk(stream: &mut dyn Read, len: usize) -> Result<String, DatoRedi
             "No se pudo leer el contenido del bulk string.".into(),
         )
     })?;
-    Ok(String::from_utf8_lossy(&contenido[..len]).to_string())
+    contenido.truncate(len);
+    Ok(contenido)
 }
 
 /// Lee del stream hasta encontrar una secuencia `\r\n` y devuelve la línea
@@ -252,13 +349,13 @@ fn read_line_custom(stream: &mut dyn Read) -> Result<String, DatoRedis> {
 /// en formato RESP y la escribe en el stream de salida proporcionado.
 ///
 /// # Parámetros
-/// - `retorno`: la respuesta ya serializada en formato RESP (como string).
+/// - `retorno`: la respuesta ya serializada en formato RESP.
 /// - `stream`: stream de salida donde se escribe la respuesta.
 /// # Retorna
 /// - `Ok(())`: si la respuesta fue enviada correctamente.
 /// - `Err(DatoRedis::SimpleError)`: si hubo un error de escritura o flush.
-pub fn resp_server_command_write(retorno: &str, stream: &mut dyn Write) -> Result<(), DatoRedis> {
-    let encrypted = encrypt_resp(retorno)?;
+pub fn resp_server_command_write(retorno: &[u8], stream: &mut dyn Write) -> Result<(), DatoRedis> {
+    let encrypted = encrypt_resp_bytes(retorno)?;
 
     stream.write_all(&encrypted).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -316,8 +413,7 @@ pub fn resp_server_command_write(retorno: &str, stream: &mut dyn Write) -> Resul
 /// }
 /// ```
 pub fn resp_client_command_read(stream: &mut dyn Read) -> Result<DatoRedis, DatoRedis> {
-    let desencrypted_resp = decrypt_resp(stream)?; // aca ya tengo el resp
-    let new_stream = Cursor::new(desencrypted_resp.into_bytes());
+    let new_stream = Cursor::new(decrypt_resp_bytes(stream)?);
 
     let mut reader = BufReader::new(new_stream);
 
@@ -345,6 +441,7 @@ pub fn resp_client_command_read(stream: &mut dyn Read) -> Result<DatoRedis, Dato
         NULL_SIMBOL => read_and_convert_to_dato_redis(&mut reader, interpretar_nulls),
         ERROR_SIMBOL => read_and_convert_to_dato_redis(&mut reader, interpretar_simple_error),
         MAP_SYMBOL => read_and_convert_to_dato_redis(&mut reader, interpretar_map_reply),
+        PUSH_SIMBOL => read_and_convert_to_dato_redis(&mut reader, interpretar_push),
         _ => Err(DatoRedis::new_simple_error(
             "ERR Protocol".to_string(),
             "unknown data type".to_string(),
@@ -353,14 +450,14 @@ pub fn resp_client_command_read(stream: &mut dyn Read) -> Result<DatoRedis, Dato
 }
 
 /// Lee un dato a interpretar como simple string del reader recibido
-/// y lo devuelve String listo para convertir a dato redis
+/// y lo devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_simple_string<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_simple_string<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut simple_string = String::new();
     reader.read_line(&mut simple_string).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -369,18 +466,18 @@ fn interpretar_simple_string<R: BufRead>(reader: &mut R) -> Result<String, DatoR
         )
     })?;
 
-    Ok(SIMPLE_STRING_SIMBOL.to_string() + &simple_string)
+    Ok((SIMPLE_STRING_SIMBOL.to_string() + &simple_string).into_bytes())
 }
 
 /// Lee un dato del reader recibido a interpretar como bulk string y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_bulk_string<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_bulk_string<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut largo = String::new();
     reader.read_line(&mut largo).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -402,42 +499,36 @@ fn interpretar_bulk_string<R: BufRead>(reader: &mut R) -> Result<String, DatoRed
             "reading bulk string".to_string(),
         )
     })?;
-    let contenido = String::from_utf8(contenido).map_err(|_| {
-        DatoRedis::new_simple_error(
-            "ERR protocol".to_string(),
-            "parsing bulk string".to_string(),
-        )
-    })?;
 
-    Ok(BULK_STRING_SIMBOL.to_string() + &largo + &contenido)
+    Ok([BULK_STRING_SIMBOL.as_bytes(), largo.as_bytes(), &contenido].concat())
 }
 
 /// Lee un dato a interpretar como integer del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_integer<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_integer<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut integer = String::new();
     reader.read_line(&mut integer).map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "reading integer".to_string())
     })?;
 
-    Ok(INTEGER_SIMBOL.to_string() + &integer)
+    Ok((INTEGER_SIMBOL.to_string() + &integer).into_bytes())
 }
 
 /// Lee un dato a interpretar como array del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_arrays<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_arrays<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut cantidad_elementos = String::new();
     reader.read_line(&mut cantidad_elementos).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -446,7 +537,7 @@ fn interpretar_arrays<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
         )
     })?;
 
-    let mut resp = ARRAY_SIMBOL.to_string() + &cantidad_elementos;
+    let mut resp = (ARRAY_SIMBOL.to_string() + &cantidad_elementos).into_bytes();
 
     let cantidad_elementos = cantidad_elementos.trim().parse::<usize>().map_err(|_| {
         DatoRedis::new_simple_error(
@@ -471,6 +562,7 @@ fn interpretar_arrays<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
             INTEGER_SIMBOL => interpretar_integer(reader)?,
             ARRAY_SIMBOL => interpretar_arrays(reader)?,
             ERROR_SIMBOL => interpretar_simple_error(reader)?,
+            NULL_SIMBOL => interpretar_nulls(reader)?,
             _ => {
                 return Err(DatoRedis::new_simple_error(
                     "ERR protocol".to_string(),
@@ -479,26 +571,40 @@ fn interpretar_arrays<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
             }
         };
 
-        resp.push_str(&elemento_resp);
+        resp.extend(elemento_resp);
     }
 
     Ok(resp)
 }
 
+/// Lee un dato a interpretar como push del reader recibido y lo
+/// devuelve en bytes listos para convertir a dato redis. Su contenido se
+/// codifica igual que el de un array
+///
+/// # Parámetros
+/// * `reader`: de donde se lee el string a interpretar
+///
+/// # Retorna
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_push<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
+    let arreglo = interpretar_arrays(reader)?;
+    Ok([PUSH_SIMBOL.as_bytes(), &arreglo[ARRAY_SIMBOL.len()..]].concat())
+}
+
 /// Lee un dato a interpretar como set del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_sets<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_sets<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut cantidad_elementos = String::new();
     reader.read_line(&mut cantidad_elementos).map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "reading set length".to_string())
     })?;
-    let mut resp = SETS_SIMBOL.to_string() + &cantidad_elementos;
+    let mut resp = (SETS_SIMBOL.to_string() + &cantidad_elementos).into_bytes();
 
     let cantidad_elementos = cantidad_elementos.trim().parse::<usize>().map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "parsing set length".to_string())
@@ -524,38 +630,38 @@ fn interpretar_sets<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
             }
         };
 
-        resp.push_str(&elemento_resp);
+        resp.extend(elemento_resp);
     }
 
     Ok(resp)
 }
 
 /// Lee un dato a interpretar como null del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_nulls<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_nulls<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut string_null = String::new();
     reader.read_line(&mut string_null).map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "reading null".to_string())
     })?;
 
-    Ok(NULL_SIMBOL.to_string() + &string_null)
+    Ok((NULL_SIMBOL.to_string() + &string_null).into_bytes())
 }
 
 /// Lee un dato a interpretar como simple error del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_simple_error<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_simple_error<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut simple_error = String::new();
     reader.read_line(&mut simple_error).map_err(|_| {
         DatoRedis::new_simple_error(
@@ -563,23 +669,23 @@ fn interpretar_simple_error<R: BufRead>(reader: &mut R) -> Result<String, DatoRe
             "reading simple error".to_string(),
         )
     })?;
-    Ok(ERROR_SIMBOL.to_string() + &simple_error)
+    Ok((ERROR_SIMBOL.to_string() + &simple_error).into_bytes())
 }
 
 /// Lee un dato a interpretar como map del reader recibido y lo
-/// devuelve String listo para convertir a dato redis
+/// devuelve en bytes listos para convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn interpretar_map_reply<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn interpretar_map_reply<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut cantidad_elementos = String::new();
     reader.read_line(&mut cantidad_elementos).map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "reading map length".to_string())
     })?;
-    let mut resp = MAP_SYMBOL.to_string() + &cantidad_elementos;
+    let mut resp = (MAP_SYMBOL.to_string() + &cantidad_elementos).into_bytes();
 
     let cantidad_elementos = cantidad_elementos.trim().parse::<usize>().map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "parsing map length".to_string())
@@ -587,20 +693,20 @@ fn interpretar_map_reply<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis
     for _ in 0..cantidad_elementos {
         let key = get_element_as_resp_str(reader)?;
         let value = get_element_as_resp_str(reader)?;
-        resp.push_str(&key);
-        resp.push_str(&value);
+        resp.extend(key);
+        resp.extend(value);
     }
     Ok(resp)
 }
 
-/// Lee un dato del reader y lo devuelve como string a convertir a dato redis
+/// Lee un dato del reader y lo devuelve en bytes a convertir a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
-fn get_element_as_resp_str<R: BufRead>(reader: &mut R) -> Result<String, DatoRedis> {
+/// - Bytes del dato en caso de exito, error simple de redis en otro caso
+fn get_element_as_resp_str<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, DatoRedis> {
     let mut tipo_buf = vec![0u8; 1];
     reader.read_exact(&mut tipo_buf).map_err(|_| {
         DatoRedis::new_simple_error("ERR protocol".to_string(), "reading map data".to_string())
@@ -628,28 +734,28 @@ fn get_element_as_resp_str<R: BufRead>(reader: &mut R) -> Result<String, DatoRed
 }
 
 /// Lee un dato a interpretar del reader recibido y lo
-/// devuelve como String
+/// convierte a dato redis
 ///
 /// # Parámetros
 /// * `reader`: de donde se lee el string a interpretar
 /// * `interpretar`: funcion que recibe un reader y lo transforma en
-///   un String que puede parsearse a dato redis
-/// * `construir`: funcion que recibe un String y lo convierte a un tipo
-///   de dato redis especifico
+///   bytes que pueden parsearse a dato redis
 ///
 /// # Retorna
-/// - Un String en caso de exito, error simple de redis en otro caso
+/// - Dato redis en caso de exito, error simple de redis en otro caso
 fn read_and_convert_to_dato_redis<T: Read>(
     reader: &mut T,
-    interpretar: fn(&mut T) -> Result<String, DatoRedis>,
+    interpretar: fn(&mut T) -> Result<Vec<u8>, DatoRedis>,
 ) -> Result<DatoRedis, DatoRedis> {
     let contenido = interpretar(reader)?;
-    DatoRedis::from_bytes(contenido.as_bytes())
+    DatoRedis::from_bytes(&contenido)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::dataencryption::{decrypt_resp, encrypt_resp};
+    use crate::protocol::token::tokens;
     use crate::tipos_datos::traits::TipoDatoRedis;
     use std::io::Cursor;
 
@@ -742,14 +848,7 @@ mod tests {
 
         let result = resp_server_command_read(&mut cursor);
 
-        assert_eq!(
-            result,
-            Ok(vec![
-                "SET".to_string(),
-                "clave".to_string(),
-                "valor".to_string()
-            ])
-        );
+        assert_eq!(result, Ok(tokens(&["SET", "clave", "valor"])));
     }
 
     #[test]
@@ -762,11 +861,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Ok(vec![
-                "SET".to_string(),
-                "clave clave".to_string(),
-                "valor valor valor".to_string()
-            ])
+            Ok(tokens(&["SET", "clave clave", "valor valor valor"]))
         );
     }
 
@@ -777,7 +872,7 @@ mod tests {
         let mut cursor = Cursor::new(encrypted);
 
         let result = resp_server_command_read(&mut cursor);
-        assert_eq!(result, Ok(vec!["PING".to_string()]));
+        assert_eq!(result, Ok(tokens(&["PING"])));
     }
 
     #[test]
@@ -933,6 +1028,17 @@ mod tests {
         assert!(result.contains("-7"));
     }
 
+    #[test]
+    fn test_resp_client_read_push() {
+        let input = ">2\r\n$10\r\ninvalidate\r\n*1\r\n$5\r\nclave\r\n";
+        let encrypted = encrypt_resp(input).unwrap();
+        let mut cursor = Cursor::new(encrypted);
+
+        let result = resp_client_command_read(&mut cursor).unwrap();
+        assert!(matches!(result, DatoRedis::Push(_)));
+        assert_eq!(result.convertir_a_protocolo_resp(), input);
+    }
+
     #[test]
     fn test_basico() {
         let entrada = "hola mundo".to_string();
@@ -1004,7 +1110,7 @@ mod tests {
                 r#"set "clave con espacios" "valor con espacios""#,
                 vec!["set", "clave con espacios", "valor con espacios"],
             ),
-            ("echo \"\"", vec!["echo"]),
+            ("echo \"\"", vec!["echo", ""]),
             (r#"\"inicio sin cierre"#, vec!["\"inicio", "sin", "cierre"]),
             (
                 "espacios    múltiples   entre  tokens",
@@ -1028,4 +1134,63 @@ mod tests {
 
     #[test]
     fn parse_largo() {}
+
+    #[test]
+    fn test_parsear_comando_con_bytes_escapados() {
+        let tokens = parsear_comando(r#"set "\x00\xff" \x41\xc3\xa1 \xzz"#.to_string());
+        assert_eq!(tokens[1].bytes(), [0x00, 0xFF]);
+        assert_eq!(tokens[2], "Aá");
+        assert_eq!(tokens[3], "xzz");
+    }
+
+    #[test]
+    fn test_escapar_token_es_inverso_de_parsear_comando() {
+        let binario: &[u8] = &[0x00, 0xFF, b' ', 0x80, b'"'];
+        for token in [
+            "simple".as_bytes(),
+            "con espacios".as_bytes(),
+            "comillas \"y\" \\".as_bytes(),
+            "línea\r\n".as_bytes(),
+            binario,
+            b"",
+        ] {
+            let linea = format!("set {}", escapar_token(token));
+            assert_eq!(parsear_comando(linea), tokens(&["set".as_bytes(), token]));
+        }
+        assert_eq!(escapar_token(b"simple"), "simple");
+        assert_eq!(escapar_token(b""), "\"\"");
+    }
+
+    #[test]
+    fn test_parsear_comando_con_tokens_vacios() {
+        let tokens = parsear_comando(r#"set "" valor ''"#.to_string());
+        assert_eq!(tokens, vec!["set", "", "valor", ""]);
+    }
+
+    #[test]
+    fn test_comando_binario_llega_sin_modificaciones() {
+        let valor = vec![0x00, 0xFF, b'\r', b'\n', 0xC3];
+        let comando = tokens(&[b"SET".as_slice(), b"k", &valor]);
+        let mut buffer = Vec::new();
+        resp_api_command_write(comando.clone(), &mut buffer).unwrap();
+
+        let leidos = resp_server_command_read(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(leidos[2].len(), valor.len());
+        assert_eq!(leidos, comando);
+    }
+
+    #[test]
+    fn test_respuesta_binaria_llega_sin_modificaciones() {
+        let valor = vec![0xFF, b'$', b'\r', b'\n', 0x00];
+        let mut elementos = crate::tipos_datos::arrays::Arrays::new();
+        elementos.append(DatoRedis::new_bulk_string_desde_bytes(valor.clone()).unwrap());
+        elementos.append(DatoRedis::new_integer(1));
+        for dato in [
+            DatoRedis::new_bulk_string_desde_bytes(valor).unwrap(),
+            DatoRedis::new_array_con_contenido(elementos),
+        ] {
+            let leido = resp_client_command_read(&mut Cursor::new(dato.to_bytes())).unwrap();
+            assert_eq!(leido, dato);
+        }
+    }
 }
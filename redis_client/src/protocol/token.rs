@@ -0,0 +1,178 @@
+//! Este modulo contiene el tipo de los tokens de un comando.
+//!
+//! Un token conserva tal cual los bytes recibidos, por lo que las claves y
+//! los valores pueden ser datos binarios arbitrarios. El texto se obtiene
+//! solo cuando el comando lo necesita, por ejemplo para su nombre, sus
+//! opciones o sus argumentos numericos.
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// Token de un comando, con los bytes que lo componen
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Token(Vec<u8>);
+
+impl Token {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Token(bytes)
+    }
+
+    /// Retorna los bytes del token
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consume el token y retorna sus bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Retorna el token como texto. Los bytes que no son UTF-8 valido se
+    /// reemplazan por `U+FFFD`, por lo que solo debe usarse cuando el
+    /// token se interpreta como texto y no como dato
+    pub fn texto(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    /// Retorna el texto del token en mayusculas
+    pub fn to_uppercase(&self) -> String {
+        self.texto().to_uppercase()
+    }
+
+    /// Retorna el texto del token en minusculas
+    pub fn to_lowercase(&self) -> String {
+        self.texto().to_lowercase()
+    }
+
+    /// Compara el token con un texto sin distinguir mayusculas de
+    /// minusculas ASCII
+    pub fn eq_ignore_ascii_case(&self, otro: &str) -> bool {
+        self.0.eq_ignore_ascii_case(otro.as_bytes())
+    }
+
+    /// Interpreta el texto del token como un valor de tipo `F`
+    pub fn parse<F: FromStr>(&self) -> Result<F, F::Err> {
+        self.texto().parse()
+    }
+}
+
+impl Deref for Token {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Token {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for Token {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.texto())
+    }
+}
+
+impl From<Vec<u8>> for Token {
+    fn from(bytes: Vec<u8>) -> Self {
+        Token(bytes)
+    }
+}
+
+impl From<Token> for Vec<u8> {
+    fn from(token: Token) -> Self {
+        token.0
+    }
+}
+
+impl From<&[u8]> for Token {
+    fn from(bytes: &[u8]) -> Self {
+        Token(bytes.to_vec())
+    }
+}
+
+impl From<String> for Token {
+    fn from(texto: String) -> Self {
+        Token(texto.into_bytes())
+    }
+}
+
+impl From<&String> for Token {
+    fn from(texto: &String) -> Self {
+        Token(texto.as_bytes().to_vec())
+    }
+}
+
+impl From<&str> for Token {
+    fn from(texto: &str) -> Self {
+        Token(texto.as_bytes().to_vec())
+    }
+}
+
+impl PartialEq<str> for Token {
+    fn eq(&self, otro: &str) -> bool {
+        self.0 == otro.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for Token {
+    fn eq(&self, otro: &&str) -> bool {
+        self.0 == otro.as_bytes()
+    }
+}
+
+impl PartialEq<String> for Token {
+    fn eq(&self, otro: &String) -> bool {
+        self.0 == otro.as_bytes()
+    }
+}
+
+impl PartialEq<[u8]> for Token {
+    fn eq(&self, otro: &[u8]) -> bool {
+        self.0 == otro
+    }
+}
+
+impl PartialEq<Vec<u8>> for Token {
+    fn eq(&self, otro: &Vec<u8>) -> bool {
+        &self.0 == otro
+    }
+}
+
+/// Convierte una secuencia de textos en tokens
+pub fn tokens<T: AsRef<[u8]>>(textos: &[T]) -> Vec<Token> {
+    textos
+        .iter()
+        .map(|texto| Token::from(texto.as_ref()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_01_conserva_bytes_y_se_lee_como_texto() {
+        let binario = Token::new(vec![b'a', 0xFF, 0x00]);
+        assert_eq!(binario.len(), 3);
+        assert_eq!(binario.bytes(), [b'a', 0xFF, 0x00]);
+        assert_eq!(binario.texto(), "a\u{FFFD}\0");
+
+        let texto = Token::from("Set");
+        assert_eq!(texto, "Set");
+        assert!(texto.eq_ignore_ascii_case("SET"));
+        assert_eq!(texto.to_uppercase(), "SET");
+        assert_eq!(Token::from("-12").parse::<i64>(), Ok(-12));
+        assert!(binario.parse::<i64>().is_err());
+    }
+}
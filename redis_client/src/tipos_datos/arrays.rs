@@ -2,42 +2,45 @@
 
 use super::utils::obtener_elemento;
 use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+use std::collections::VecDeque;
 use std::mem::discriminant;
 
+/// Arreglo de datos redis. Se representa con una cola doble para que las
+/// listas admitan inserciones y eliminaciones en O(1) en ambos extremos
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Arrays {
-    contenido: Vec<DatoRedis>,
+    contenido: VecDeque<DatoRedis>,
     es_nulo: bool,
 }
 
 impl Arrays {
     pub fn new() -> Self {
         Arrays {
-            contenido: Vec::new(),
+            contenido: VecDeque::new(),
             es_nulo: false,
         }
     }
 
     pub fn new_con_contenido(contenido: Vec<DatoRedis>) -> Self {
         Arrays {
-            contenido,
+            contenido: VecDeque::from(contenido),
             es_nulo: false,
         }
     }
 
-    /// Crea un dato de redis Array a partir de un String en
+    /// Crea un dato de redis Array a partir de su representacion en
     /// formato RESP
     ///
     /// # Parametros
-    /// * `array_resp`: String resp a interpretar
+    /// * `array_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - Array en caso de exito, error simple de redis en otro caso
-    pub fn new_desde_resp(array_resp: String) -> Result<Self, DatoRedis> {
-        let elementos = Self::obtener_arreglo(array_resp)?;
+    pub fn new_desde_resp(array_resp: impl AsRef<[u8]>) -> Result<Self, DatoRedis> {
+        let elementos = Self::obtener_arreglo(array_resp.as_ref())?;
         let largo = elementos.len();
         Ok(Arrays {
-            contenido: elementos,
+            contenido: VecDeque::from(elementos),
             es_nulo: largo == 0,
         })
     }
@@ -52,7 +55,23 @@ impl Arrays {
     /// # Parametros
     /// * `dato`: Dato Redis a agregar
     pub fn append(&mut self, dato: DatoRedis) {
-        self.contenido.push(dato);
+        self.contenido.push_back(dato);
+    }
+
+    /// Agrega un elemento al inicio del arreglo
+    ///
+    /// # Parametros
+    /// * `dato`: Dato Redis a agregar
+    pub fn push_front(&mut self, dato: DatoRedis) {
+        self.contenido.push_front(dato);
+    }
+
+    /// Remueve el primer elemento del arreglo
+    ///
+    /// # Retorna
+    /// - option del elemento removido, de existir
+    pub fn pop_front(&mut self) -> Option<DatoRedis> {
+        self.contenido.pop_front()
     }
 
     /// Obtiene el elemento en una posicion del arreglo
@@ -117,7 +136,7 @@ impl Arrays {
                 "index out of range".to_string(),
             ));
         }
-        Ok(self.contenido.remove(index))
+        self.contenido.remove(index).ok_or_else(DatoRedis::new_null)
     }
 
     /// Remueve el ultimo elemento del arreglo
@@ -131,7 +150,7 @@ impl Arrays {
                 "index out of range".to_string(),
             ))
         } else {
-            if let Some(dato) = self.contenido.pop() {
+            if let Some(dato) = self.contenido.pop_back() {
                 return Ok(dato);
             }
             Err(DatoRedis::new_null())
@@ -154,7 +173,7 @@ impl Arrays {
     }
 
     /// Retorna el iterador del arreglo
-    pub fn iter(&self) -> std::slice::Iter<DatoRedis> {
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, DatoRedis> {
         self.contenido.iter()
     }
 
@@ -202,34 +221,34 @@ impl Arrays {
             .any(|d| matches!(d, dato if pred(dato)))
     }
 
-    /// A partir de un string en formato resp, sin el caracter inicial *,
-    /// retorna un vector de datos redis
+    /// A partir de los bytes de un arreglo en formato resp, retorna un
+    /// vector de datos redis
     ///
     /// # Parametros:
     /// * `arreglo_resp`: Representacion resp del arreglo
     ///
     /// # Retorna
     /// - un vector de datos redis en caso de exito, error simple en otro caso
-    fn obtener_arreglo(arreglo_resp: String) -> Result<Vec<DatoRedis>, DatoRedis> {
-        let largo_arreglo_str = Self::obtener_largo(&arreglo_resp, &mut String::new())?;
+    fn obtener_arreglo(arreglo_resp: &[u8]) -> Result<Vec<DatoRedis>, DatoRedis> {
+        let largo_arreglo_str = Self::obtener_largo(arreglo_resp)?;
         let digitos_largo = largo_arreglo_str.len();
         if let Ok(largo) = largo_arreglo_str.parse::<usize>() {
             let mut elementos: Vec<DatoRedis> = Vec::new();
             let mut indice_fin = digitos_largo + 3;
             for _ in 0..largo {
-                let resto = &arreglo_resp[indice_fin..].to_string();
+                let resto = arreglo_resp.get(indice_fin..).unwrap_or_default();
                 if let Ok((elemento, indice_final)) = obtener_elemento(resto) {
                     elementos.push(elemento);
                     indice_fin += indice_final;
-                } else if arreglo_resp.chars().nth(indice_fin) == Some('*') {
-                    let arr_anidado = Self::obtener_arreglo(resto.to_string())?;
+                } else if resto.first() == Some(&b'*') {
+                    let arr_anidado = Self::obtener_arreglo(resto)?;
                     let largo_arr_anidado = arr_anidado.len();
                     let arr = Arrays {
-                        contenido: arr_anidado,
+                        contenido: VecDeque::from(arr_anidado),
                         es_nulo: largo_arr_anidado == 0,
                     };
-                    let mut largo = arr.convertir_a_protocolo_resp().len();
-                    if resto.chars().nth(1) == Some('0') && resto.chars().nth(2) == Some('\r') {
+                    let mut largo = arr.convertir_a_protocolo_resp_bytes().len();
+                    if resto.get(1..3) == Some(b"0\r") {
                         largo = 0;
                     }
                     indice_fin += largo;
@@ -251,41 +270,27 @@ impl Arrays {
         ))
     }
 
-    /// Obtiene el largo de un arreglo a partir de una cadena que
-    /// lo representa
+    /// Obtiene el largo de un arreglo a partir de los bytes que lo
+    /// representan
     ///
     /// # Parametros
-    /// * `arreglo_resp`: String resp a interpretar
-    /// * `largo_entrada`: largo en chars de la entrada
+    /// * `arreglo_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - string indicando el largo del arreglo en caso de exito,
     ///   error simple de redis en otro caso
-    fn obtener_largo(
-        arreglo_resp: &str,
-        largo_arreglo_str: &mut String,
-    ) -> Result<String, DatoRedis> {
-        if arreglo_resp.is_empty() {
+    fn obtener_largo(arreglo_resp: &[u8]) -> Result<String, DatoRedis> {
+        if arreglo_resp.first() != Some(&b'*') {
             return Err(DatoRedis::new_simple_error(
                 "txt".to_string(),
                 "Array invalido\n".to_string(),
             ));
         }
-        if arreglo_resp.chars().nth(0) != Some('*') {
-            return Err(DatoRedis::new_simple_error(
-                "txt".to_string(),
-                "Array invalido\n".to_string(),
-            ));
-        }
-        let caracteres = &arreglo_resp[1..];
-        for caracter in caracteres.chars() {
-            if caracter.is_ascii_digit() {
-                largo_arreglo_str.push(caracter);
-            } else {
-                break;
-            }
-        }
-        Ok(largo_arreglo_str.to_string())
+        Ok(arreglo_resp[1..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .map(|byte| *byte as char)
+            .collect())
     }
 }
 
@@ -301,6 +306,17 @@ impl TipoDatoRedis for Arrays {
         resultado
     }
 
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        if self.es_nulo {
+            return b"*-1\r\n".to_vec();
+        }
+        let mut resultado = format!("*{}\r\n", self.contenido.len()).into_bytes();
+        for dato in &self.contenido {
+            resultado.extend(dato.convertir_a_protocolo_resp_bytes());
+        }
+        resultado
+    }
+
     fn convertir_resp_a_string(&self) -> String {
         self.convertir_con_indentacion(0)
     }
@@ -350,6 +366,7 @@ impl Default for Arrays {
 mod tests {
     use crate::tipos_datos::arrays::Arrays;
     use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+    use std::collections::VecDeque;
 
     #[test]
     fn test_01_arreglo_vacio_formato_valido() {
@@ -363,7 +380,7 @@ mod tests {
     #[test]
     fn test_02_arreglo_nulo_formato_valido() {
         let arreglo = Arrays {
-            contenido: Vec::new(),
+            contenido: VecDeque::new(),
             es_nulo: true,
         };
         let resultado_esperado = "*-1\r\n".to_string();
@@ -394,7 +411,7 @@ mod tests {
     #[test]
     fn test_03_arreglo_nulo_no_permite_append() {
         let mut arreglo = Arrays {
-            contenido: Vec::new(),
+            contenido: VecDeque::new(),
             es_nulo: true,
         };
         let elem = DatoRedis::new_bulk_string("dato".to_string()).unwrap();
@@ -515,7 +532,7 @@ mod tests {
 
     #[test]
     fn test_11_arreglo_int_a_partir_de_resp() {
-        let arreglo = Arrays::new_desde_resp("*3\r\n:1\r\n:3\r\n:334\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*3\r\n:1\r\n:3\r\n:334\r\n").unwrap();
         assert_eq!(arreglo.len(), 3);
         let resultado_esperado = "*3\r\n:1\r\n:3\r\n:334\r\n".to_string();
         let resultado_obtenido = arreglo.convertir_a_protocolo_resp();
@@ -524,7 +541,7 @@ mod tests {
 
     #[test]
     fn test_12_arreglo_bulk_str_a_partir_de_resp() {
-        let arreglo = Arrays::new_desde_resp("*2\r\n$3\r\nabc\r\n$1\r\nd\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*2\r\n$3\r\nabc\r\n$1\r\nd\r\n").unwrap();
         assert_eq!(arreglo.len(), 2);
         let resultado_esperado = "*2\r\n$3\r\nabc\r\n$1\r\nd\r\n".to_string();
         let resultado_obtenido = arreglo.convertir_a_protocolo_resp();
@@ -533,8 +550,7 @@ mod tests {
 
     #[test]
     fn test_13_arreglo_simple_str_a_partir_de_resp() {
-        let arreglo =
-            Arrays::new_desde_resp("*4\r\n+abc\r\n+123\r\n+qwert\r\n+aa\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*4\r\n+abc\r\n+123\r\n+qwert\r\n+aa\r\n").unwrap();
         assert_eq!(arreglo.len(), 4);
         let resultado_esperado = "*4\r\n+abc\r\n+123\r\n+qwert\r\n+aa\r\n".to_string();
         let resultado_obtenido = arreglo.convertir_a_protocolo_resp();
@@ -544,8 +560,7 @@ mod tests {
     #[test]
     fn test_14_arreglo_mixto_a_partir_de_resp() {
         let arreglo =
-            Arrays::new_desde_resp("*4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n".to_string())
-                .unwrap();
+            Arrays::new_desde_resp("*4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n").unwrap();
         assert_eq!(arreglo.len(), 4);
         let resultado_esperado = "*4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n".to_string();
         let resultado_obtenido = arreglo.convertir_a_protocolo_resp();
@@ -555,8 +570,7 @@ mod tests {
     #[test]
     fn test_15_arreglo_a_string() {
         let arreglo =
-            Arrays::new_desde_resp("*4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n".to_string())
-                .unwrap();
+            Arrays::new_desde_resp("*4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n").unwrap();
         let contenido = arreglo.convertir_resp_a_string();
         assert_eq!(
             contenido,
@@ -596,13 +610,11 @@ mod tests {
 
     #[test]
     fn test_17_arreglo_anidado_resp_a_array() {
-        let arreglo =
-            Arrays::new_desde_resp("*2\r\n*0\r\n*1\r\n$5\r\nhello\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*2\r\n*0\r\n*1\r\n$5\r\nhello\r\n").unwrap();
         assert_eq!(arreglo.len(), 2);
-        let arreglo = Arrays::new_desde_resp(
-            "*2\r\n*3\r\n$2\r\nab\r\n+abc\r\n:25\r\n*1\r\n$5\r\nhello\r\n".to_string(),
-        )
-        .unwrap();
+        let arreglo =
+            Arrays::new_desde_resp("*2\r\n*3\r\n$2\r\nab\r\n+abc\r\n:25\r\n*1\r\n$5\r\nhello\r\n")
+                .unwrap();
         assert_eq!(
             arreglo.convertir_a_protocolo_resp(),
             "*2\r\n*3\r\n$2\r\nab\r\n+abc\r\n:25\r\n*1\r\n$5\r\nhello\r\n".to_string()
@@ -612,8 +624,7 @@ mod tests {
     #[test]
     fn test_18_arreglo_resp_a_array() {
         let arreglo =
-            Arrays::new_desde_resp("*4\r\n+hola\r\n$7\r\n¡hello\r\n+¡a\r\n:2\r\n".to_string())
-                .unwrap();
+            Arrays::new_desde_resp("*4\r\n+hola\r\n$7\r\n¡hello\r\n+¡a\r\n:2\r\n").unwrap();
         assert_eq!(arreglo.len(), 4);
         if let Some(bs) = arreglo.get(1) {
             assert_eq!(bs.convertir_a_protocolo(), "$7\r\n¡hello\r\n");
@@ -634,20 +645,25 @@ mod tests {
 
     #[test]
     fn test_19_arreglo_con_simple_error() {
-        let arreglo =
-            Arrays::new_desde_resp("*2\r\n$4\r\nhola\r\n-MOVED 6539\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*2\r\n$4\r\nhola\r\n-MOVED 6539\r\n").unwrap();
         let result = arreglo.convertir_a_protocolo_resp();
         assert_eq!(result, "*2\r\n$4\r\nhola\r\n-MOVED 6539\r\n");
     }
 
     #[test]
     fn test_20_arreglo_contiene_simple_error() {
-        let arreglo =
-            Arrays::new_desde_resp("*2\r\n$4\r\nhola\r\n-MOVED 6539\r\n".to_string()).unwrap();
+        let arreglo = Arrays::new_desde_resp("*2\r\n$4\r\nhola\r\n-MOVED 6539\r\n").unwrap();
         let result = arreglo.contains_dato(&DatoRedis::new_simple_error(
             "-MOVED".to_string(),
             "6539".into(),
         ));
         assert!(result);
     }
+
+    #[test]
+    fn test_21_arreglo_resp_con_null() {
+        let arreglo = Arrays::new_desde_resp("*2\r\n_\r\n:2\r\n").unwrap();
+        assert_eq!(arreglo.get(0), Some(DatoRedis::new_null()));
+        assert_eq!(arreglo.get(1), Some(DatoRedis::new_integer(2)));
+    }
 }
@@ -9,12 +9,36 @@ use std::hash::Hash;
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub struct BulkString {
     largo: i32,
-    contenido: String,
+    contenido: Vec<u8>,
 }
 
 impl BulkString {
+    /// Crea un Bulk String a partir de su contenido como texto
+    ///
+    /// El contenido se guarda tal cual, incluidas las comillas de sus
+    /// extremos: las que delimitan un token las quita `parsear_comando`,
+    /// por lo que las que quedan forman parte del valor.
+    ///
+    /// # Parametros
+    /// * `contenido`: texto del bulk string
+    ///
+    /// # Retorna
+    /// - Bulk String en caso de exito, error simple de redis si supera el
+    ///   largo maximo
     pub fn new(contenido: String) -> Result<Self, DatoRedis> {
-        let contenido = contenido.trim_matches('"').to_string();
+        Self::new_desde_bytes(contenido.into_bytes())
+    }
+
+    /// Crea un Bulk String a partir de bytes arbitrarios, sin exigir que
+    /// sean UTF-8 valido
+    ///
+    /// # Parametros
+    /// * `contenido`: bytes del bulk string
+    ///
+    /// # Retorna
+    /// - Bulk String en caso de exito, error simple de redis si supera el
+    ///   largo maximo
+    pub fn new_desde_bytes(contenido: Vec<u8>) -> Result<Self, DatoRedis> {
         if contenido.len() > MAX_LEN_BULK_TYPE {
             return Err(DatoRedis::new_simple_error(
                 "ERR".to_string(),
@@ -27,55 +51,53 @@ impl BulkString {
         })
     }
 
-    /// Crea un dato de redis Bulk String a partir de un String en
-    /// formato RESP
+    /// Crea un dato de redis Bulk String a partir de su representacion
+    /// en formato RESP, cuyo contenido puede no ser UTF-8 valido
     ///
     /// # Parametros
-    /// * `bulk_string_resp`: String resp a interpretar
+    /// * `bulk_string_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - Bulk String en caso de exito, error simple de redis en otro caso
-    pub fn new_desde_resp(bulk_string_resp: String) -> Result<Self, DatoRedis> {
-        match Self::separar_bulk_string(bulk_string_resp) {
-            Ok((largo, contenido)) => Ok(BulkString { largo, contenido }),
+    pub fn new_desde_resp(bulk_string_resp: impl AsRef<[u8]>) -> Result<Self, DatoRedis> {
+        match Self::separar_bulk_string(bulk_string_resp.as_ref()) {
+            Ok((largo, contenido)) => Ok(BulkString {
+                largo,
+                contenido: contenido.to_vec(),
+            }),
             Err(e) => Err(e),
         }
     }
 
-    /// Detecta las partes de un Bulk String a partir de un String en
-    /// formato RESP
+    /// Detecta las partes de un Bulk String a partir de su representacion
+    /// en formato RESP
     ///
     /// # Parametros
-    /// * `bulk_string_resp`: String resp a interpretar
+    /// * `bulk_string_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - tupla de largo y contenido del bulk string en caso de exito,
     ///   error simple de redis en otro caso
-    fn separar_bulk_string(bulk_string_resp: String) -> Result<(i32, String), DatoRedis> {
-        let largo_entrada = bulk_string_resp.chars().count();
+    fn separar_bulk_string(bulk_string_resp: &[u8]) -> Result<(i32, &[u8]), DatoRedis> {
+        let largo_entrada = bulk_string_resp.len();
         // borrar numeros magicos
-        if bulk_string_resp == "$-1\r\n" {
+        if bulk_string_resp == b"$-1\r\n" {
             // Esto es para el null supongo
-            return Ok((-1, String::new()));
-        } else if largo_entrada < 6 || bulk_string_resp.chars().nth(0) != Some('$') {
+            return Ok((-1, &[]));
+        } else if largo_entrada < 6 || !bulk_string_resp.starts_with(BULK_STRING_SIMBOL.as_bytes())
+        {
             return Err(DatoRedis::new_simple_error(
                 "Protocol error".to_string(),
                 "invalid first byte".to_string(),
             ));
         }
-        let resultado_largo = Self::obtener_largo(&bulk_string_resp, &largo_entrada);
+        let resultado_largo = Self::obtener_largo(bulk_string_resp);
         if let Ok((largo, indice_inicio_contenido)) = resultado_largo {
-            let mut contenido = String::new();
-            for i in indice_inicio_contenido..largo_entrada - 2 {
-                if let Some(c) = bulk_string_resp.chars().nth(i) {
-                    contenido.push(c);
-                }
-            }
+            let contenido = bulk_string_resp
+                .get(indice_inicio_contenido..largo_entrada - 2)
+                .unwrap_or_default();
             // esta feo
-            if contenido.len() != largo
-                || bulk_string_resp.chars().nth(largo_entrada - 2) != Some('\r')
-                || bulk_string_resp.chars().nth(largo_entrada - 1) != Some('\n')
-            {
+            if contenido.len() != largo || !bulk_string_resp.ends_with(CRLF.as_bytes()) {
                 return Err(DatoRedis::new_simple_error(
                     "Protocol error".to_string(),
                     "expected '\r\n'".to_string(),
@@ -91,32 +113,23 @@ impl BulkString {
         ))
     }
 
-    /// Obtiene el largo de un bulk string a partir de una cadena que
-    /// lo representa
+    /// Obtiene el largo de un bulk string a partir de los bytes que
+    /// lo representan
     ///
     /// # Parametros
-    /// * `bulk_string_resp`: String resp a interpretar
-    /// * `largo_entrada`: largo en chars de la entrada
+    /// * `bulk_string_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - tupla de largo e indice de inicio del contendio del bulk string
     ///   en caso de exito, error simple de redis en otro caso
-    fn obtener_largo(
-        bulk_string_resp: &str,
-        largo_entrada: &usize,
-    ) -> Result<(usize, usize), DatoRedis> {
-        let mut largo_bulk_string = String::new();
-        for i in 1..*largo_entrada {
-            let caracter = bulk_string_resp.chars().nth(i);
-            if caracter == Some('\r') {
-                break;
-            }
-            if let Some(caracter_valido) = caracter {
-                largo_bulk_string.push(caracter_valido);
-            }
-        }
-        let inicio_texto = 3 + largo_bulk_string.len();
-        if let Ok(largo) = largo_bulk_string.trim().parse::<usize>() {
+    fn obtener_largo(bulk_string_resp: &[u8]) -> Result<(usize, usize), DatoRedis> {
+        let digitos: Vec<u8> = bulk_string_resp[1..]
+            .iter()
+            .take_while(|byte| **byte != b'\r')
+            .copied()
+            .collect();
+        let inicio_texto = 3 + digitos.len();
+        if let Ok(largo) = String::from_utf8_lossy(&digitos).trim().parse::<usize>() {
             return Ok((largo, inicio_texto));
         }
         Err(DatoRedis::new_null())
@@ -127,14 +140,30 @@ impl BulkString {
         self.largo
     }
 
-    /// Retorna el contenido del bulk error
+    /// Retorna el contenido del bulk string como texto. Los bytes que no
+    /// son UTF-8 valido se reemplazan, para obtenerlos usar `bytes`
     pub fn contenido(&self) -> String {
-        self.contenido.to_string()
+        String::from_utf8_lossy(&self.contenido).into_owned()
+    }
+
+    /// Retorna los bytes del contenido del bulk string
+    pub fn bytes(&self) -> &[u8] {
+        &self.contenido
+    }
+
+    /// Consume el bulk string y retorna los bytes de su contenido
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.contenido
     }
 
     /// Concatena un string al contenido del bulk error
     pub fn concatenar(&mut self, otro: String) {
-        self.contenido.push_str(&otro);
+        self.concatenar_bytes(otro.as_bytes());
+    }
+
+    /// Concatena bytes arbitrarios al contenido del bulk string
+    pub fn concatenar_bytes(&mut self, otro: &[u8]) {
+        self.contenido.extend_from_slice(otro);
         self.largo = self.contenido.len() as i32;
     }
 }
@@ -146,17 +175,30 @@ impl TipoDatoRedis for BulkString {
         respuesta.push_str(&self.largo.to_string());
         respuesta.push_str(CRLF);
         if self.largo > -1 {
-            respuesta.push_str(&self.contenido);
+            respuesta.push_str(&self.contenido());
             respuesta.push_str(CRLF);
         }
         respuesta
     }
 
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        let mut respuesta = format!("{BULK_STRING_SIMBOL}{}{CRLF}", self.largo).into_bytes();
+        if self.largo > -1 {
+            respuesta.extend_from_slice(&self.contenido);
+            respuesta.extend_from_slice(CRLF.as_bytes());
+        }
+        respuesta
+    }
+
     fn convertir_resp_a_string(&self) -> String {
         if self.largo == -1 {
             "nil".to_string()
         } else {
-            format!("\"{}\"{}", self.contenido, CRLF)
+            let visible = match std::str::from_utf8(&self.contenido) {
+                Ok(texto) => texto.to_string(),
+                Err(_) => self.contenido.escape_ascii().to_string(),
+            };
+            format!("\"{visible}\"{CRLF}")
         }
     }
 }
@@ -168,59 +210,59 @@ mod tests {
 
     #[test]
     fn test_01_resp_a_bulk_string_valido() {
-        let bulk_string = BulkString::new_desde_resp("$5\r\nhello\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$5\r\nhello\r\n");
         assert_eq!(bulk_string.unwrap().largo, 5);
-        let bulk_string_2 = BulkString::new_desde_resp("$5\r\nhello\r\n".to_string());
-        assert_eq!(bulk_string_2.unwrap().contenido, "hello".to_string());
-        let bulk_string_3 = BulkString::new_desde_resp("$6\r\nhello\n\r\n".to_string());
-        assert_eq!(bulk_string_3.unwrap().contenido, "hello\n".to_string());
-        let bulk_string_4 = BulkString::new_desde_resp("$7\r\nhello\r\n\r\n".to_string());
-        assert_eq!(bulk_string_4.unwrap().contenido, "hello\r\n".to_string());
-        let bulk_string_5 = BulkString::new_desde_resp("$12\r\nhello world\n\r\n".to_string());
+        let bulk_string_2 = BulkString::new_desde_resp("$5\r\nhello\r\n");
+        assert_eq!(bulk_string_2.unwrap().contenido(), "hello".to_string());
+        let bulk_string_3 = BulkString::new_desde_resp("$6\r\nhello\n\r\n");
+        assert_eq!(bulk_string_3.unwrap().contenido(), "hello\n".to_string());
+        let bulk_string_4 = BulkString::new_desde_resp("$7\r\nhello\r\n\r\n");
+        assert_eq!(bulk_string_4.unwrap().contenido(), "hello\r\n".to_string());
+        let bulk_string_5 = BulkString::new_desde_resp("$12\r\nhello world\n\r\n");
         assert_eq!(bulk_string_5.unwrap().largo, 12);
-        let bulk_string_6 = BulkString::new_desde_resp("$7\r\nhello¡\r\n".to_string());
+        let bulk_string_6 = BulkString::new_desde_resp("$7\r\nhello¡\r\n");
         assert_eq!(bulk_string_6.unwrap().largo, 7);
-        let bulk_string_7 = BulkString::new_desde_resp("$7\r\nhello¡\r\n".to_string());
-        assert_eq!(bulk_string_7.unwrap().contenido, "hello¡".to_string());
+        let bulk_string_7 = BulkString::new_desde_resp("$7\r\nhello¡\r\n");
+        assert_eq!(bulk_string_7.unwrap().contenido(), "hello¡".to_string());
     }
 
     #[test]
     fn test_02_resp_a_bulk_string_invalido() {
-        let bulk_string = BulkString::new_desde_resp("$5\r\nhello world\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$5\r\nhello world\r\n");
         assert!(bulk_string.is_err());
-        let bulk_string_2 = BulkString::new_desde_resp("$5\r\n\r\n".to_string());
+        let bulk_string_2 = BulkString::new_desde_resp("$5\r\n\r\n");
         assert!(bulk_string_2.is_err());
-        let bulk_string_3 = BulkString::new_desde_resp("$hola\r\nhello world\r\n".to_string());
+        let bulk_string_3 = BulkString::new_desde_resp("$hola\r\nhello world\r\n");
         assert!(bulk_string_3.is_err());
-        let bulk_string_4 = BulkString::new_desde_resp("+5\r\nhello\r\n".to_string());
+        let bulk_string_4 = BulkString::new_desde_resp("+5\r\nhello\r\n");
         assert!(bulk_string_4.is_err());
-        let bulk_string_4 = BulkString::new_desde_resp("$5hello\r\n".to_string());
+        let bulk_string_4 = BulkString::new_desde_resp("$5hello\r\n");
         assert!(bulk_string_4.is_err());
     }
 
     #[test]
     fn test_03_bulk_string_a_resp_valido() {
-        let bulk_string = BulkString::new_desde_resp("$5\r\nhello\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$5\r\nhello\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_a_protocolo_resp(),
             "$5\r\nhello\r\n".to_string()
         );
-        let bulk_string = BulkString::new_desde_resp("$-1\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$-1\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_a_protocolo_resp(),
             "$-1\r\n".to_string()
         );
-        let bulk_string = BulkString::new_desde_resp("$7\r\nhello\r\n\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$7\r\nhello\r\n\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_a_protocolo_resp(),
             "$7\r\nhello\r\n\r\n".to_string()
         );
-        let bulk_string = BulkString::new_desde_resp("$12\r\nhello world\n\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$12\r\nhello world\n\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_a_protocolo_resp(),
             "$12\r\nhello world\n\r\n".to_string()
         );
-        let bulk_string = BulkString::new_desde_resp("$0\r\n\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$0\r\n\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_a_protocolo_resp(),
             "$0\r\n\r\n".to_string()
@@ -247,7 +289,7 @@ mod tests {
 
     #[test]
     fn test_06_bulk_string_nulo_de_resp_a_string() {
-        let bulk_string = BulkString::new_desde_resp("$-1\r\n".to_string());
+        let bulk_string = BulkString::new_desde_resp("$-1\r\n");
         assert_eq!(
             bulk_string.unwrap().convertir_resp_a_string(),
             "nil".to_string()
@@ -258,7 +300,7 @@ mod tests {
     fn test_07_largo_y_contenido() {
         let bulk = BulkString {
             largo: 5,
-            contenido: "Hola!".to_string(),
+            contenido: b"Hola!".to_vec(),
         };
 
         assert_eq!(bulk.largo(), 5);
@@ -269,7 +311,7 @@ mod tests {
     fn test_08_concatenar() {
         let mut bulk = BulkString {
             largo: 4,
-            contenido: "Test".to_string(),
+            contenido: b"Test".to_vec(),
         };
 
         bulk.concatenar("123".to_string());
@@ -277,4 +319,25 @@ mod tests {
         assert_eq!(bulk.contenido(), "Test123");
         assert_eq!(bulk.largo(), 7);
     }
+
+    #[test]
+    fn test_09_contenido_binario() {
+        let mut bulk = BulkString::new_desde_bytes(vec![0x00, 0xFF, b'a']).unwrap();
+        assert_eq!(bulk.largo(), 3);
+        assert_eq!(bulk.bytes(), &[0x00, 0xFF, b'a']);
+        bulk.concatenar_bytes(&[0x80]);
+        assert_eq!(bulk.largo(), 4);
+        assert_eq!(bulk.into_bytes(), vec![0x00, 0xFF, b'a', 0x80]);
+    }
+
+    #[test]
+    fn test_10_conserva_comillas_de_los_extremos() {
+        let bulk_string = BulkString::new("\"hola\"".to_string()).unwrap();
+        assert_eq!(bulk_string.largo(), 6);
+        assert_eq!(bulk_string.bytes(), b"\"hola\"");
+        assert_eq!(
+            bulk_string.convertir_a_protocolo_resp(),
+            "$6\r\n\"hola\"\r\n"
+        );
+    }
 }
@@ -11,6 +11,7 @@ pub const NULL_SIMBOL: &str = "_";
 pub const VERBATIN_STRING_SIMBOL: &str = "=";
 pub const VERBATING_STRING_SIMBOL: &str = "=";
 pub const MAP_SYMBOL: &str = "%";
+pub const PUSH_SIMBOL: &str = ">";
 pub const CR: &str = "\r";
 pub const LF: &str = "\n";
 pub const CRLF: &str = "\r\n";
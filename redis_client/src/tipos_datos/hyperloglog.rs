@@ -0,0 +1,444 @@
+//! Este modulo contiene la implementacion del tipo de dato redis
+//! HyperLogLog, que estima la cantidad de elementos distintos agregados sin
+//! almacenarlos.
+//!
+//! Se usan 2^14 registros, cada uno con la mayor cantidad de ceros finales
+//! (mas uno) observada entre los hashes que le corresponden. Mientras pocos
+//! registros son distintos de cero se guardan solo esos (codificacion
+//! dispersa); al superar `MAXIMO_DISPERSOS` se pasa a guardar todos
+//! (codificacion densa), lo que no se revierte.
+use crate::tipos_datos::bulk_string::BulkString;
+use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+
+/// Bits del hash que eligen el registro
+const PRECISION: u32 = 14;
+/// Cantidad de registros
+pub const REGISTROS: usize = 1 << PRECISION;
+/// Bits del hash usados para contar ceros finales
+const BITS_RESTANTES: u32 = 64 - PRECISION;
+/// Mayor valor que puede tomar un registro
+const MAXIMO_VALOR: u8 = BITS_RESTANTES as u8 + 1;
+/// Cantidad de registros no nulos a partir de la cual se pasa a la
+/// codificacion densa
+pub const MAXIMO_DISPERSOS: usize = 750;
+/// Semilla del hash de los elementos
+const SEMILLA: u64 = 0xadc8_3b19;
+
+/// Prefijo de la representacion en bytes
+const MAGIC: &[u8; 4] = b"HYLL";
+const CODIFICACION_DENSA: u8 = 0;
+const CODIFICACION_DISPERSA: u8 = 1;
+/// Bits con los que se representa cada registro en la codificacion densa
+const BITS_REGISTRO: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Registros {
+    /// Registros no nulos como pares indice-valor, ordenados por indice
+    Dispersos(Vec<(u16, u8)>),
+    /// Valor de cada uno de los registros
+    Densos(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HyperLogLog {
+    registros: Registros,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    /// Crea un HyperLogLog vacio, con codificacion dispersa
+    pub fn new() -> Self {
+        HyperLogLog {
+            registros: Registros::Dispersos(Vec::new()),
+        }
+    }
+
+    /// Indica si el HyperLogLog usa la codificacion densa
+    pub fn es_denso(&self) -> bool {
+        matches!(self.registros, Registros::Densos(_))
+    }
+
+    /// Agrega un elemento
+    ///
+    /// # Parametros
+    /// * `elemento`: bytes del elemento a agregar
+    ///
+    /// # Retorna
+    /// - verdadero si se modifico algun registro
+    pub fn agregar(&mut self, elemento: &[u8]) -> bool {
+        let (indice, valor) = registro_de(murmur_hash_64a(elemento, SEMILLA));
+        self.actualizar(indice, valor)
+    }
+
+    /// Estima la cantidad de elementos distintos agregados
+    pub fn contar(&self) -> u64 {
+        let mut histograma = [0u32; MAXIMO_VALOR as usize + 1];
+        match &self.registros {
+            Registros::Dispersos(dispersos) => {
+                histograma[0] = (REGISTROS - dispersos.len()) as u32;
+                for &(_, valor) in dispersos {
+                    histograma[valor as usize] += 1;
+                }
+            }
+            Registros::Densos(densos) => {
+                for &valor in densos {
+                    histograma[valor as usize] += 1;
+                }
+            }
+        }
+        estimar(&histograma)
+    }
+
+    /// Incorpora los elementos de otro HyperLogLog, quedando cada
+    /// registro con el mayor de ambos valores
+    pub fn unir(&mut self, otro: &HyperLogLog) {
+        match &otro.registros {
+            Registros::Dispersos(dispersos) => {
+                for &(indice, valor) in dispersos {
+                    self.actualizar(indice, valor);
+                }
+            }
+            Registros::Densos(densos) => {
+                let mut registros = self.valores();
+                for (actual, &valor) in registros.iter_mut().zip(densos) {
+                    *actual = (*actual).max(valor);
+                }
+                self.registros = Registros::Densos(registros);
+            }
+        }
+    }
+
+    /// Cantidad aproximada de bytes que ocupan los registros
+    pub fn bytes_en_memoria(&self) -> usize {
+        match &self.registros {
+            Registros::Dispersos(dispersos) => dispersos.len() * 3,
+            Registros::Densos(densos) => densos.len(),
+        }
+    }
+
+    /// Representa el HyperLogLog como bytes: el prefijo `HYLL`, la
+    /// codificacion y los registros, empaquetados de a 6 bits si es densa
+    /// o como pares indice (u16) y valor (u8) si es dispersa
+    pub fn a_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        match &self.registros {
+            Registros::Densos(densos) => {
+                bytes.push(CODIFICACION_DENSA);
+                let mut empaquetados = vec![0u8; REGISTROS * BITS_REGISTRO / 8];
+                for (i, &valor) in densos.iter().enumerate() {
+                    for bit in 0..BITS_REGISTRO {
+                        if valor & (1 << bit) != 0 {
+                            let posicion = i * BITS_REGISTRO + bit;
+                            empaquetados[posicion / 8] |= 1 << (posicion % 8);
+                        }
+                    }
+                }
+                bytes.extend_from_slice(&empaquetados);
+            }
+            Registros::Dispersos(dispersos) => {
+                bytes.push(CODIFICACION_DISPERSA);
+                bytes.extend_from_slice(&(dispersos.len() as u16).to_be_bytes());
+                for &(indice, valor) in dispersos {
+                    bytes.extend_from_slice(&indice.to_be_bytes());
+                    bytes.push(valor);
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Crea un HyperLogLog a partir de su representacion en bytes
+    ///
+    /// # Parametros
+    /// * `bytes`: bytes generados con `a_bytes`
+    ///
+    /// # Retorna
+    /// - HyperLogLog en caso de exito, error simple de redis si los bytes
+    ///   no son una representacion valida
+    pub fn desde_bytes(bytes: &[u8]) -> Result<Self, DatoRedis> {
+        let error = || {
+            DatoRedis::new_simple_error(
+                "INVALIDOBJ".to_string(),
+                "Corrupted HLL object detected".to_string(),
+            )
+        };
+        let contenido = bytes.strip_prefix(MAGIC).ok_or_else(error)?;
+        let (&codificacion, registros) = contenido.split_first().ok_or_else(error)?;
+        match codificacion {
+            CODIFICACION_DENSA => {
+                if registros.len() != REGISTROS * BITS_REGISTRO / 8 {
+                    return Err(error());
+                }
+                let mut densos = vec![0u8; REGISTROS];
+                for (i, valor) in densos.iter_mut().enumerate() {
+                    for bit in 0..BITS_REGISTRO {
+                        let posicion = i * BITS_REGISTRO + bit;
+                        if registros[posicion / 8] & (1 << (posicion % 8)) != 0 {
+                            *valor |= 1 << bit;
+                        }
+                    }
+                    if *valor > MAXIMO_VALOR {
+                        return Err(error());
+                    }
+                }
+                Ok(HyperLogLog {
+                    registros: Registros::Densos(densos),
+                })
+            }
+            CODIFICACION_DISPERSA => {
+                let (cantidad, pares) = registros.split_at_checked(2).ok_or_else(error)?;
+                let cantidad = u16::from_be_bytes([cantidad[0], cantidad[1]]) as usize;
+                if pares.len() != cantidad * 3 {
+                    return Err(error());
+                }
+                let dispersos: Vec<(u16, u8)> = pares
+                    .chunks_exact(3)
+                    .map(|par| (u16::from_be_bytes([par[0], par[1]]), par[2]))
+                    .collect();
+                let validos = dispersos.iter().all(|&(indice, valor)| {
+                    (indice as usize) < REGISTROS && (1..=MAXIMO_VALOR).contains(&valor)
+                }) && dispersos.windows(2).all(|par| par[0].0 < par[1].0);
+                if !validos {
+                    return Err(error());
+                }
+                Ok(HyperLogLog {
+                    registros: Registros::Dispersos(dispersos),
+                })
+            }
+            _ => Err(error()),
+        }
+    }
+
+    /// Actualiza un registro si el valor es mayor al actual
+    ///
+    /// # Retorna
+    /// - verdadero si el registro fue modificado
+    fn actualizar(&mut self, indice: u16, valor: u8) -> bool {
+        match &mut self.registros {
+            Registros::Densos(densos) => {
+                let actual = &mut densos[indice as usize];
+                if *actual >= valor {
+                    return false;
+                }
+                *actual = valor;
+            }
+            Registros::Dispersos(dispersos) => {
+                match dispersos.binary_search_by_key(&indice, |&(i, _)| i) {
+                    Ok(posicion) if dispersos[posicion].1 >= valor => return false,
+                    Ok(posicion) => dispersos[posicion].1 = valor,
+                    Err(posicion) => dispersos.insert(posicion, (indice, valor)),
+                }
+                if dispersos.len() > MAXIMO_DISPERSOS {
+                    self.registros = Registros::Densos(self.valores());
+                }
+            }
+        }
+        true
+    }
+
+    /// Obtiene el valor de cada uno de los registros
+    fn valores(&self) -> Vec<u8> {
+        match &self.registros {
+            Registros::Densos(densos) => densos.clone(),
+            Registros::Dispersos(dispersos) => {
+                let mut valores = vec![0u8; REGISTROS];
+                for &(indice, valor) in dispersos {
+                    valores[indice as usize] = valor;
+                }
+                valores
+            }
+        }
+    }
+}
+
+/// Obtiene el registro que corresponde a un hash y el valor que aporta:
+/// la cantidad de ceros finales de los bits restantes, mas uno
+fn registro_de(hash: u64) -> (u16, u8) {
+    let indice = (hash & (REGISTROS as u64 - 1)) as u16;
+    let restantes = (hash >> PRECISION) | (1 << BITS_RESTANTES);
+    (indice, restantes.trailing_zeros() as u8 + 1)
+}
+
+/// Estima la cardinalidad a partir de la cantidad de registros con cada
+/// valor, con el estimador de Ertl ("New cardinality estimation algorithms
+/// for HyperLogLog sketches")
+fn estimar(histograma: &[u32]) -> u64 {
+    let m = REGISTROS as f64;
+    let q = BITS_RESTANTES as usize;
+    let mut z = m * tau((m - histograma[q + 1] as f64) / m);
+    for j in (1..=q).rev() {
+        z += histograma[j] as f64;
+        z *= 0.5;
+    }
+    z += m * sigma(histograma[0] as f64 / m);
+    let alfa = 0.5 / std::f64::consts::LN_2;
+    (alfa * m * m / z).round() as u64
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let anterior = z;
+        z += x * y;
+        y += y;
+        if anterior == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let anterior = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if anterior == z {
+            return z / 3.0;
+        }
+    }
+}
+
+/// Hash MurmurHash64A de Austin Appleby
+fn murmur_hash_64a(datos: &[u8], semilla: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+    let mut h = semilla ^ (datos.len() as u64).wrapping_mul(M);
+
+    let mut bloques = datos.chunks_exact(8);
+    for bloque in &mut bloques {
+        let mut k = u64::from_le_bytes(bloque.try_into().unwrap_or_default());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let resto = bloques.remainder();
+    if !resto.is_empty() {
+        for (i, &byte) in resto.iter().enumerate() {
+            h ^= (byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+impl TipoDatoRedis for HyperLogLog {
+    /// Se representa como el bulk string con sus bytes, igual que redis
+    fn convertir_a_protocolo_resp(&self) -> String {
+        match BulkString::new_desde_bytes(self.a_bytes()) {
+            Ok(bulk_string) => bulk_string.convertir_a_protocolo_resp(),
+            Err(e) => e.convertir_a_protocolo_resp(),
+        }
+    }
+
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        match BulkString::new_desde_bytes(self.a_bytes()) {
+            Ok(bulk_string) => bulk_string.convertir_a_protocolo_resp_bytes(),
+            Err(e) => e.convertir_a_protocolo_resp_bytes(),
+        }
+    }
+
+    fn convertir_resp_a_string(&self) -> String {
+        format!("(hyperloglog) ~{} elementos", self.contar())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn con_elementos(desde: usize, hasta: usize) -> HyperLogLog {
+        let mut hll = HyperLogLog::new();
+        for i in desde..hasta {
+            hll.agregar(format!("usuario:{i}").as_bytes());
+        }
+        hll
+    }
+
+    fn error_relativo(estimado: u64, real: usize) -> f64 {
+        (estimado as f64 - real as f64).abs() / real as f64
+    }
+
+    #[test]
+    fn test_01_hash_conocido() {
+        assert_eq!(murmur_hash_64a(b"", 0), 0);
+        assert_ne!(
+            murmur_hash_64a(b"a", SEMILLA),
+            murmur_hash_64a(b"b", SEMILLA)
+        );
+        let (_, valor) = registro_de(0);
+        assert_eq!(valor, MAXIMO_VALOR);
+    }
+
+    #[test]
+    fn test_02_elementos_repetidos_no_modifican_registros() {
+        let mut hll = HyperLogLog::new();
+        assert_eq!(hll.contar(), 0);
+        assert!(hll.agregar(b"a"));
+        assert!(!hll.agregar(b"a"));
+        assert_eq!(hll.contar(), 1);
+        assert!(!hll.es_denso());
+    }
+
+    #[test]
+    fn test_03_estimacion_en_ambas_codificaciones() {
+        let pocos = con_elementos(0, 100);
+        assert!(!pocos.es_denso());
+        assert!(error_relativo(pocos.contar(), 100) < 0.05);
+
+        let muchos = con_elementos(0, 50_000);
+        assert!(muchos.es_denso());
+        assert!(error_relativo(muchos.contar(), 50_000) < 0.03);
+    }
+
+    #[test]
+    fn test_04_union_de_codificaciones() {
+        let mut disperso = con_elementos(0, 300);
+        let denso = con_elementos(200, 20_000);
+        disperso.unir(&denso);
+        assert!(disperso.es_denso());
+        assert!(error_relativo(disperso.contar(), 20_000) < 0.03);
+
+        let mut otro = con_elementos(0, 10);
+        otro.unir(&con_elementos(5, 20));
+        assert!(!otro.es_denso());
+        assert_eq!(otro.contar(), 20);
+    }
+
+    #[test]
+    fn test_05_bytes_se_recuperan() {
+        for hll in [
+            con_elementos(0, 50),
+            con_elementos(0, 5_000),
+            HyperLogLog::new(),
+        ] {
+            let bytes = hll.a_bytes();
+            assert!(bytes.starts_with(MAGIC));
+            assert_eq!(HyperLogLog::desde_bytes(&bytes).unwrap(), hll);
+        }
+        assert!(HyperLogLog::desde_bytes(b"HYLL").is_err());
+        assert!(HyperLogLog::desde_bytes(b"HYLL\x01\x00\x01\xff\xff\x01").is_err());
+        assert!(HyperLogLog::desde_bytes(b"otro").is_err());
+    }
+}
@@ -57,38 +57,34 @@ impl MapReply {
         self.content.contains_key(key)
     }
 
-    /// Crea un dato de redis Map Reply a partir de un String en
+    /// Crea un dato de redis Map Reply a partir de su representacion en
     /// formato RESP
     ///
     /// # Parametros
-    /// * `map_resp`: String resp a interpretar
+    /// * `map_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - Map Reply de redis en caso de exito, error simple de redis en otro caso
-    pub fn new_desde_resp(map_resp: String) -> Result<Self, DatoRedis> {
+    pub fn new_desde_resp(map_resp: impl AsRef<[u8]>) -> Result<Self, DatoRedis> {
+        let map_resp = map_resp.as_ref();
         if map_resp.is_empty() {
             return Err(DatoRedis::new_simple_error(
                 "Protocol error".to_string(),
                 "wrong number of elements in map".to_string(),
             ));
         }
-        if map_resp.chars().nth(0) != Some('%') {
+        if map_resp.first() != Some(&b'%') {
             return Err(DatoRedis::new_simple_error(
                 "Protocol error".to_string(),
                 "invalid first byte".to_string(),
             ));
         }
 
-        let mut map_len = String::new();
-        let map_len_digits = &map_resp[1..];
-
-        for caracter in map_len_digits.chars() {
-            if caracter.is_ascii_digit() {
-                map_len.push(caracter);
-            } else {
-                break;
-            }
-        }
+        let map_len: String = map_resp[1..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .map(|byte| *byte as char)
+            .collect();
 
         let digits_len = map_len.len();
         if let Ok(len) = map_len.parse::<usize>() {
@@ -127,6 +123,18 @@ impl TipoDatoRedis for MapReply {
         result
     }
 
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        if self.null {
+            return b"\r\n".to_vec();
+        }
+        let mut result = format!("%{}\r\n", self.content.len()).into_bytes();
+        for (key, value) in &self.content {
+            result.extend(key.convertir_a_protocolo_resp_bytes());
+            result.extend(value.convertir_a_protocolo_resp_bytes());
+        }
+        result
+    }
+
     fn convertir_resp_a_string(&self) -> String {
         let mut result = String::new();
         for (index, (key, value)) in self.content.iter().enumerate() {
@@ -143,17 +151,17 @@ impl TipoDatoRedis for MapReply {
 }
 
 fn get_elements(
-    map_resp: String,
+    map_resp: &[u8],
     digits_len: usize,
     map_len: usize,
 ) -> Result<HashMap<DatoRedis, DatoRedis>, DatoRedis> {
     let mut content = HashMap::new();
     let mut current_index = digits_len + 3;
     for _ in 0..map_len {
-        let rest = &map_resp[current_index..].to_string();
+        let rest = map_resp.get(current_index..).unwrap_or_default();
         if let Ok((key, index_amount_to_increment)) = obtener_elemento(rest) {
             current_index += index_amount_to_increment;
-            let rest = &map_resp[current_index..].to_string();
+            let rest = map_resp.get(current_index..).unwrap_or_default();
             if let Ok((value, index_amount_to_increment)) = obtener_elemento(rest) {
                 content.insert(key, value);
                 current_index += index_amount_to_increment;
@@ -2,13 +2,17 @@ pub mod arrays;
 pub mod bulk_error;
 pub mod bulk_string;
 pub mod constantes;
+pub mod hyperloglog;
 pub mod integer;
 pub mod map_reply;
 pub mod moved_error;
 pub mod nulls;
+pub mod push;
 pub mod set;
 pub mod simple_error;
 pub mod simple_string;
+pub mod sorted_set;
+pub mod stream;
 pub mod traits;
 mod utils;
 pub mod verbatim_string;
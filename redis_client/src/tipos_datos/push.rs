@@ -0,0 +1,88 @@
+//! Este modulo contiene la implementacion del tipo de dato redis Push, que
+//! el servidor envia sin que el cliente lo pida, intercalado con las
+//! respuestas de la conexion
+use crate::tipos_datos::arrays::Arrays;
+use crate::tipos_datos::constantes::{ARRAY_SIMBOL, PUSH_SIMBOL};
+use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+
+/// Mensaje push de RESP3. Se codifica como un arreglo con `>` como
+/// caracter inicial, cuyo primer elemento indica el tipo de mensaje
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Push {
+    contenido: Arrays,
+}
+
+impl Push {
+    pub fn new(contenido: Arrays) -> Self {
+        Push { contenido }
+    }
+
+    /// Crea un dato de redis Push a partir de su representacion en
+    /// formato RESP
+    ///
+    /// # Parametros
+    /// * `push_resp`: bytes resp a interpretar
+    ///
+    /// # Retorna
+    /// - Push en caso de exito, error simple de redis en otro caso
+    pub fn new_desde_resp(push_resp: impl AsRef<[u8]>) -> Result<Self, DatoRedis> {
+        let Some(resto) = push_resp.as_ref().strip_prefix(PUSH_SIMBOL.as_bytes()) else {
+            return Err(DatoRedis::new_simple_error(
+                "Protocol error".to_string(),
+                "invalid first byte".to_string(),
+            ));
+        };
+        let contenido = Arrays::new_desde_resp([ARRAY_SIMBOL.as_bytes(), resto].concat())?;
+        Ok(Push { contenido })
+    }
+
+    /// Retorna los elementos del mensaje
+    pub fn contenido(&self) -> &Arrays {
+        &self.contenido
+    }
+}
+
+impl TipoDatoRedis for Push {
+    fn convertir_a_protocolo_resp(&self) -> String {
+        let arreglo = self.contenido.convertir_a_protocolo_resp();
+        PUSH_SIMBOL.to_string() + &arreglo[ARRAY_SIMBOL.len()..]
+    }
+
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        let arreglo = self.contenido.convertir_a_protocolo_resp_bytes();
+        [PUSH_SIMBOL.as_bytes(), &arreglo[ARRAY_SIMBOL.len()..]].concat()
+    }
+
+    fn convertir_resp_a_string(&self) -> String {
+        self.contenido.convertir_resp_a_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalidacion() -> Push {
+        Push::new(Arrays::new_con_contenido(vec![
+            DatoRedis::new_bulk_string("invalidate".to_string()).unwrap(),
+            DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![
+                DatoRedis::new_bulk_string("clave".to_string()).unwrap(),
+            ])),
+        ]))
+    }
+
+    #[test]
+    fn test_push_convertir_a_protocolo_resp() {
+        assert_eq!(
+            invalidacion().convertir_a_protocolo_resp(),
+            ">2\r\n$10\r\ninvalidate\r\n*1\r\n$5\r\nclave\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_new_desde_resp() {
+        let resp = invalidacion().convertir_a_protocolo_resp();
+        assert_eq!(Push::new_desde_resp(resp).unwrap(), invalidacion());
+        assert!(Push::new_desde_resp("*1\r\n:1\r\n").is_err());
+    }
+}
@@ -33,16 +33,16 @@ impl Set {
         }
     }
 
-    /// Crea un dato de redis Set a partir de un String en
+    /// Crea un dato de redis Set a partir de su representacion en
     /// formato RESP
     ///
     /// # Parametros
-    /// * `array_resp`: String resp a interpretar
+    /// * `array_resp`: bytes resp a interpretar
     ///
     /// # Retorna
     /// - Set en caso de exito, error simple de redis en otro caso
-    pub fn new_desde_resp(array_resp: String) -> Result<Self, DatoRedis> {
-        let elementos = Self::obtener_set(array_resp)?;
+    pub fn new_desde_resp(array_resp: impl AsRef<[u8]>) -> Result<Self, DatoRedis> {
+        let elementos = Self::obtener_set(array_resp.as_ref())?;
         let largo = elementos.len();
         Ok(Set {
             contenido: elementos,
@@ -89,37 +89,32 @@ impl Set {
         self.contenido.iter()
     }
 
-    /// A partir de un string en formato resp, sin el caracter inicial de set,
-    /// retorna un hashset de datos redis
+    /// A partir de los bytes de un set en formato resp, retorna un hashset
+    /// de datos redis
     ///
     /// # Parametros:
     /// * `set_resp`: Representacion resp del set
     ///
     /// # Retorna
     /// - un hashset de datos redis en caso de exito, error simple en otro caso
-    fn obtener_set(set_resp: String) -> Result<HashSet<DatoRedis>, DatoRedis> {
+    fn obtener_set(set_resp: &[u8]) -> Result<HashSet<DatoRedis>, DatoRedis> {
         if set_resp.is_empty() {
             return Err(DatoRedis::new_simple_error(
                 "Protocol error".to_string(),
                 "wrong number of elements in set".to_string(),
             ));
         }
-        if set_resp.chars().nth(0) != Some('~') {
+        if set_resp.first() != Some(&b'~') {
             return Err(DatoRedis::new_simple_error(
                 "Protocol error".to_string(),
                 "invalid first byte".to_string(),
             ));
         }
-        let mut largo_set_str = String::new();
-        let caracteres = &set_resp[1..];
-
-        for caracter in caracteres.chars() {
-            if caracter.is_ascii_digit() {
-                largo_set_str.push(caracter);
-            } else {
-                break;
-            }
-        }
+        let largo_set_str: String = set_resp[1..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .map(|byte| *byte as char)
+            .collect();
         let digitos_largo = largo_set_str.len();
         if let Ok(largo) = largo_set_str.parse::<usize>() {
             let set = Self::obtener_elementos(set_resp, digitos_largo, largo)?;
@@ -133,25 +128,25 @@ impl Set {
         ))
     }
 
-    /// Obtiene los elementos de un set a partir de una cadena que
-    /// lo representa
+    /// Obtiene los elementos de un set a partir de los bytes que
+    /// lo representan
     ///
     /// # Parametros
-    /// * `set_resp`: String resp a interpretar
+    /// * `set_resp`: bytes resp a interpretar
     /// * `digitos_largo`: cantidad de digitos del largo del arreglo
     /// * `largo_arreglo`: largo del set
     ///
     /// # Retorna
     /// - un hashset de datos redis en caso de exito, error simple en otro caso
     fn obtener_elementos(
-        set_resp: String,
+        set_resp: &[u8],
         digitos_largo: usize,
         largo_arreglo: usize,
     ) -> Result<HashSet<DatoRedis>, DatoRedis> {
         let mut elementos: HashSet<DatoRedis> = HashSet::new();
         let mut indice_fin = digitos_largo + 3;
         for _ in 0..largo_arreglo {
-            let resto = &set_resp[indice_fin..].to_string();
+            let resto = set_resp.get(indice_fin..).unwrap_or_default();
             if let Ok((elemento, indice_final)) = obtener_elemento(resto) {
                 elementos.insert(elemento);
                 indice_fin += indice_final;
@@ -189,6 +184,17 @@ impl TipoDatoRedis for Set {
         resultado
     }
 
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        if self.es_nulo {
+            return b"~-1\r\n".to_vec();
+        }
+        let mut resultado = format!("~{}\r\n", self.contenido.len()).into_bytes();
+        for valor in &self.contenido {
+            resultado.extend(valor.convertir_a_protocolo_resp_bytes());
+        }
+        resultado
+    }
+
     fn convertir_resp_a_string(&self) -> String {
         if self.contenido.is_empty() {
             return "(empty set)\r\n".to_string();
@@ -323,7 +329,7 @@ mod tests {
 
     #[test]
     fn test_07_set_int_a_partir_de_resp() {
-        let set = Set::new_desde_resp("~3\r\n:1\r\n:3\r\n:334\r\n".to_string()).unwrap();
+        let set = Set::new_desde_resp("~3\r\n:1\r\n:3\r\n:334\r\n").unwrap();
         assert_eq!(set.len(), 3);
         let resultado_obtenido = set.convertir_a_protocolo_resp();
         assert!(resultado_obtenido.starts_with("~3\r\n"));
@@ -334,7 +340,7 @@ mod tests {
 
     #[test]
     fn test_08_set_bulk_str_a_partir_de_resp() {
-        let set = Set::new_desde_resp("~2\r\n$3\r\nabc\r\n$1\r\nd\r\n".to_string()).unwrap();
+        let set = Set::new_desde_resp("~2\r\n$3\r\nabc\r\n$1\r\nd\r\n").unwrap();
         let resultado_obtenido = set.convertir_a_protocolo_resp();
         assert!(resultado_obtenido.starts_with("~2\r\n"));
         assert!(resultado_obtenido.contains("$3\r\nabc\r\n"));
@@ -343,8 +349,7 @@ mod tests {
 
     #[test]
     fn test_09_set_simple_str_a_partir_de_resp() {
-        let set =
-            Set::new_desde_resp("~4\r\n+abc\r\n+123\r\n+qwert\r\n+aa\r\n".to_string()).unwrap();
+        let set = Set::new_desde_resp("~4\r\n+abc\r\n+123\r\n+qwert\r\n+aa\r\n").unwrap();
         let resultado_obtenido = set.convertir_a_protocolo_resp();
         assert!(resultado_obtenido.starts_with("~4\r\n"));
         assert!(resultado_obtenido.contains("+abc\r\n"));
@@ -355,8 +360,7 @@ mod tests {
 
     #[test]
     fn test_10_set_mixto_a_partir_de_resp() {
-        let set = Set::new_desde_resp("~4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n".to_string())
-            .unwrap();
+        let set = Set::new_desde_resp("~4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n").unwrap();
         let resultado_obtenido = set.convertir_a_protocolo_resp();
         assert!(resultado_obtenido.starts_with("~4\r\n"));
         assert!(resultado_obtenido.contains("+abc\r\n"));
@@ -367,8 +371,7 @@ mod tests {
 
     #[test]
     fn test_11_set_a_string() {
-        let set = Set::new_desde_resp("~4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n".to_string())
-            .unwrap();
+        let set = Set::new_desde_resp("~4\r\n+abc\r\n:-123\r\n+qwert\r\n$2\r\nab\r\n").unwrap();
         let resultado = set.convertir_resp_a_string();
         assert_eq!(resultado.len(), 46);
         assert!(resultado.contains("\"ab\"\r\n"));
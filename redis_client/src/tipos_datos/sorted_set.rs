@@ -0,0 +1,188 @@
+//! Este modulo contiene la implementacion del tipo de dato redis Sorted
+//! Set: miembros unicos ordenados por un puntaje y, a igual puntaje, por
+//! su nombre
+use crate::tipos_datos::arrays::Arrays;
+use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
+
+/// Puntaje de un miembro, con orden total para poder usarse como clave
+#[derive(Debug, Clone, Copy)]
+pub struct Puntaje(pub f64);
+
+impl PartialEq for Puntaje {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Puntaje {}
+
+impl PartialOrd for Puntaje {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Puntaje {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for Puntaje {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SortedSet {
+    /// Miembros ordenados por puntaje y nombre
+    ordenados: BTreeSet<(Puntaje, Vec<u8>)>,
+    /// Puntaje de cada miembro
+    puntajes: BTreeMap<Vec<u8>, Puntaje>,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.puntajes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.puntajes.is_empty()
+    }
+
+    /// Obtiene el puntaje de un miembro, de existir
+    pub fn puntaje(&self, miembro: &[u8]) -> Option<f64> {
+        self.puntajes.get(miembro).map(|puntaje| puntaje.0)
+    }
+
+    /// Agrega un miembro o actualiza su puntaje
+    ///
+    /// # Parametros
+    /// * `miembro`: miembro a agregar
+    /// * `puntaje`: puntaje del miembro
+    ///
+    /// # Retorna
+    /// - verdadero si el miembro no existia
+    pub fn insertar(&mut self, miembro: Vec<u8>, puntaje: f64) -> bool {
+        let puntaje = Puntaje(puntaje);
+        match self.puntajes.insert(miembro.clone(), puntaje) {
+            Some(anterior) => {
+                self.ordenados.remove(&(anterior, miembro.clone()));
+                self.ordenados.insert((puntaje, miembro));
+                false
+            }
+            None => {
+                self.ordenados.insert((puntaje, miembro));
+                true
+            }
+        }
+    }
+
+    /// Elimina un miembro
+    ///
+    /// # Retorna
+    /// - verdadero si el miembro existia
+    pub fn eliminar(&mut self, miembro: &[u8]) -> bool {
+        match self.puntajes.remove(miembro) {
+            Some(puntaje) => self.ordenados.remove(&(puntaje, miembro.to_vec())),
+            None => false,
+        }
+    }
+
+    /// Itera los miembros y sus puntajes, ordenados por puntaje
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&[u8], f64)> {
+        self.ordenados
+            .iter()
+            .map(|(puntaje, miembro)| (miembro.as_slice(), puntaje.0))
+    }
+
+    /// Itera los miembros con puntaje en el rango `[minimo, maximo)`,
+    /// ordenados por puntaje
+    pub fn rango_por_puntaje(
+        &self,
+        minimo: f64,
+        maximo: f64,
+    ) -> impl DoubleEndedIterator<Item = (&[u8], f64)> {
+        // El menor miembro de cada puntaje es el de nombre vacio
+        let desde = Bound::Included((Puntaje(minimo), Vec::new()));
+        let hasta = Bound::Excluded((Puntaje(maximo.max(minimo)), Vec::new()));
+        self.ordenados
+            .range((desde, hasta))
+            .map(|(puntaje, miembro)| (miembro.as_slice(), puntaje.0))
+    }
+
+    /// Representa al sorted set como el arreglo de sus miembros, cada uno
+    /// seguido de su puntaje
+    fn como_arreglo(&self) -> Arrays {
+        let mut arreglo = Arrays::new();
+        for (miembro, puntaje) in self.iter() {
+            for bytes in [miembro.to_vec(), puntaje.to_string().into_bytes()] {
+                if let Ok(dato) = DatoRedis::new_bulk_string_desde_bytes(bytes) {
+                    arreglo.append(dato);
+                }
+            }
+        }
+        arreglo
+    }
+}
+
+impl TipoDatoRedis for SortedSet {
+    fn convertir_a_protocolo_resp(&self) -> String {
+        self.como_arreglo().convertir_a_protocolo_resp()
+    }
+
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        self.como_arreglo().convertir_a_protocolo_resp_bytes()
+    }
+
+    fn convertir_resp_a_string(&self) -> String {
+        self.como_arreglo().convertir_resp_a_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_01_miembros_se_ordenan_por_puntaje_y_nombre() {
+        let mut set = SortedSet::new();
+        assert!(set.insertar(b"b".to_vec(), 2.0));
+        assert!(set.insertar(b"c".to_vec(), 1.0));
+        assert!(set.insertar(b"a".to_vec(), 2.0));
+        assert!(!set.insertar(b"c".to_vec(), 3.0));
+
+        let miembros: Vec<_> = set.iter().collect();
+        assert_eq!(
+            miembros,
+            vec![(&b"a"[..], 2.0), (&b"b"[..], 2.0), (&b"c"[..], 3.0)]
+        );
+        assert_eq!(set.puntaje(b"c"), Some(3.0));
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn test_02_rango_por_puntaje_y_eliminacion() {
+        let mut set = SortedSet::new();
+        for (i, miembro) in ["a", "b", "c", "d"].iter().enumerate() {
+            set.insertar(miembro.as_bytes().to_vec(), i as f64);
+        }
+        let rango: Vec<_> = set.rango_por_puntaje(1.0, 3.0).map(|(m, _)| m).collect();
+        assert_eq!(rango, vec![b"b", b"c"]);
+
+        assert!(set.eliminar(b"b"));
+        assert!(!set.eliminar(b"b"));
+        assert_eq!(set.puntaje(b"b"), None);
+        let rango: Vec<_> = set.rango_por_puntaje(0.0, 10.0).map(|(m, _)| m).collect();
+        assert_eq!(rango, vec![b"a", b"c", b"d"]);
+    }
+}
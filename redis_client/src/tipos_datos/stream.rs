@@ -0,0 +1,575 @@
+//! Este modulo contiene la implementacion del tipo de dato redis Stream, un
+//! log de entradas ordenadas por id al que solo se agregan elementos al final
+use crate::tipos_datos::arrays::Arrays;
+use crate::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Pares campo-valor de una entrada del stream, con sus bytes tal como
+/// se recibieron
+pub type CamposStream = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Id de una entrada de un stream: milisegundos y numero de secuencia
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// Obtiene el menor id mayor al actual, de existir
+    pub fn siguiente(&self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_add(1).map(|ms| StreamId::new(ms, 0)),
+        }
+    }
+
+    /// Obtiene el mayor id menor al actual, de existir
+    pub fn anterior(&self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_sub(1).map(|ms| StreamId::new(ms, u64::MAX)),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = DatoRedis;
+
+    /// Interpreta un id con formato `ms-seq` o `ms` (secuencia 0)
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "Invalid stream ID specified as stream command argument".to_string(),
+            )
+        };
+        let (ms, seq) = match id.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse::<u64>().map_err(|_| error())?),
+            None => (id, 0),
+        };
+        Ok(StreamId::new(ms.parse::<u64>().map_err(|_| error())?, seq))
+    }
+}
+
+/// Entrada entregada a un consumidor de un grupo y aun no confirmada
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pendiente {
+    pub consumidor: String,
+    /// Instante de la ultima entrega, en milisegundos desde epoch
+    pub entregado_ms: u64,
+    /// Cantidad de veces que se entrego la entrada
+    pub entregas: u64,
+}
+
+/// Grupo de consumidores de un stream: cada entrada nueva se entrega a un
+/// unico consumidor del grupo y queda pendiente hasta ser confirmada
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct GrupoConsumidores {
+    ultimo_entregado: StreamId,
+    pendientes: BTreeMap<StreamId, Pendiente>,
+    /// Consumidores del grupo y el instante de su ultima actividad
+    consumidores: BTreeMap<String, u64>,
+}
+
+impl GrupoConsumidores {
+    pub fn new(ultimo_entregado: StreamId) -> Self {
+        GrupoConsumidores {
+            ultimo_entregado,
+            ..Self::default()
+        }
+    }
+
+    /// Crea un grupo a partir de su contenido, por ejemplo al leerlo de un
+    /// snapshot
+    pub fn new_con_contenido(
+        ultimo_entregado: StreamId,
+        pendientes: BTreeMap<StreamId, Pendiente>,
+        consumidores: BTreeMap<String, u64>,
+    ) -> Self {
+        GrupoConsumidores {
+            ultimo_entregado,
+            pendientes,
+            consumidores,
+        }
+    }
+
+    pub fn ultimo_entregado(&self) -> StreamId {
+        self.ultimo_entregado
+    }
+
+    pub fn set_ultimo_entregado(&mut self, id: StreamId) {
+        self.ultimo_entregado = id;
+    }
+
+    /// Entradas pendientes de confirmacion, ordenadas por id
+    pub fn pendientes(&self) -> &BTreeMap<StreamId, Pendiente> {
+        &self.pendientes
+    }
+
+    /// Consumidores del grupo junto con el instante de su ultima actividad
+    pub fn consumidores(&self) -> &BTreeMap<String, u64> {
+        &self.consumidores
+    }
+
+    /// Cantidad de entradas pendientes de un consumidor
+    pub fn pendientes_de(&self, consumidor: &str) -> usize {
+        self.pendientes
+            .values()
+            .filter(|p| p.consumidor == consumidor)
+            .count()
+    }
+
+    /// Registra la actividad de un consumidor, creandolo si no existe
+    ///
+    /// # Retorna
+    /// - verdadero si el consumidor fue creado
+    pub fn registrar_consumidor(&mut self, consumidor: &str, ahora_ms: u64) -> bool {
+        self.consumidores
+            .insert(consumidor.to_string(), ahora_ms)
+            .is_none()
+    }
+
+    /// Elimina un consumidor junto con sus entradas pendientes
+    ///
+    /// # Retorna
+    /// - cantidad de entradas pendientes que tenia el consumidor
+    pub fn eliminar_consumidor(&mut self, consumidor: &str) -> usize {
+        if self.consumidores.remove(consumidor).is_none() {
+            return 0;
+        }
+        let antes = self.pendientes.len();
+        self.pendientes.retain(|_, p| p.consumidor != consumidor);
+        antes - self.pendientes.len()
+    }
+
+    /// Entrega una entrada a un consumidor, avanzando el ultimo id
+    /// entregado del grupo
+    ///
+    /// # Parametros
+    /// * `id`: id de la entrada
+    /// * `consumidor`: consumidor que la recibe
+    /// * `ahora_ms`: instante de la entrega
+    /// * `requiere_confirmacion`: si la entrada queda pendiente hasta ser
+    ///   confirmada (falso con NOACK)
+    pub fn entregar(
+        &mut self,
+        id: StreamId,
+        consumidor: &str,
+        ahora_ms: u64,
+        requiere_confirmacion: bool,
+    ) {
+        self.ultimo_entregado = self.ultimo_entregado.max(id);
+        if requiere_confirmacion {
+            self.pendientes.insert(
+                id,
+                Pendiente {
+                    consumidor: consumidor.to_string(),
+                    entregado_ms: ahora_ms,
+                    entregas: 1,
+                },
+            );
+        }
+    }
+
+    /// Registra una entrada como pendiente sin modificar el ultimo id
+    /// entregado del grupo
+    pub fn agregar_pendiente(&mut self, id: StreamId, pendiente: Pendiente) {
+        self.pendientes.insert(id, pendiente);
+    }
+
+    /// Vuelve a entregar una entrada pendiente a un consumidor
+    ///
+    /// # Parametros
+    /// * `id`: id de la entrada pendiente
+    /// * `consumidor`: consumidor que pasa a ser dueño de la entrada
+    /// * `entregado_ms`: instante de la entrega
+    /// * `entregas`: cantidad de entregas a registrar, None para sumar una
+    ///
+    /// # Retorna
+    /// - verdadero si la entrada estaba pendiente
+    pub fn reasignar(
+        &mut self,
+        id: &StreamId,
+        consumidor: &str,
+        entregado_ms: u64,
+        entregas: Option<u64>,
+    ) -> bool {
+        match self.pendientes.get_mut(id) {
+            Some(pendiente) => {
+                pendiente.consumidor = consumidor.to_string();
+                pendiente.entregado_ms = entregado_ms;
+                pendiente.entregas = entregas.unwrap_or(pendiente.entregas + 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Confirma el procesamiento de una entrada, quitandola de las
+    /// pendientes
+    ///
+    /// # Retorna
+    /// - verdadero si la entrada estaba pendiente
+    pub fn confirmar(&mut self, id: &StreamId) -> bool {
+        self.pendientes.remove(id).is_some()
+    }
+}
+
+/// Stream de redis: entradas ordenadas por id junto con el ultimo id
+/// generado, que nunca decrece aunque se eliminen entradas
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Stream {
+    entradas: BTreeMap<StreamId, CamposStream>,
+    ultimo_id: StreamId,
+    /// Cantidad de entradas agregadas desde la creacion del stream
+    agregadas: u64,
+    grupos: BTreeMap<String, GrupoConsumidores>,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Crea un stream a partir de su contenido, por ejemplo al leerlo de
+    /// un snapshot
+    pub fn new_con_contenido(
+        entradas: BTreeMap<StreamId, CamposStream>,
+        ultimo_id: StreamId,
+        agregadas: u64,
+        grupos: BTreeMap<String, GrupoConsumidores>,
+    ) -> Self {
+        Stream {
+            entradas,
+            ultimo_id,
+            agregadas,
+            grupos,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entradas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entradas.is_empty()
+    }
+
+    pub fn ultimo_id(&self) -> StreamId {
+        self.ultimo_id
+    }
+
+    pub fn agregadas(&self) -> u64 {
+        self.agregadas
+    }
+
+    /// Genera el id de la proxima entrada a partir del instante actual
+    ///
+    /// # Parametros
+    /// * `ms`: milisegundos del id a generar
+    /// * `seq`: secuencia del id a generar, None para generarla
+    ///
+    /// # Retorna
+    /// - el id generado, None si no existe un id mayor al ultimo
+    pub fn generar_id(&self, ms: u64, seq: Option<u64>) -> Option<StreamId> {
+        match seq {
+            Some(seq) => Some(StreamId::new(ms, seq)),
+            None if ms > self.ultimo_id.ms => Some(StreamId::new(ms, 0)),
+            None if ms == self.ultimo_id.ms => self.ultimo_id.siguiente(),
+            None => None,
+        }
+    }
+
+    /// Agrega una entrada al final del stream
+    ///
+    /// # Parametros
+    /// * `id`: id de la entrada, debe ser mayor al ultimo id del stream
+    /// * `campos`: pares campo-valor de la entrada
+    ///
+    /// # Retorna
+    /// - () en caso de exito, error simple de redis si el id no es valido
+    pub fn agregar(&mut self, id: StreamId, campos: CamposStream) -> Result<(), DatoRedis> {
+        if id == StreamId::MIN {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "The ID specified in XADD must be greater than 0-0".to_string(),
+            ));
+        }
+        if id <= self.ultimo_id {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "The ID specified in XADD is equal or smaller than the target stream top item"
+                    .to_string(),
+            ));
+        }
+        self.entradas.insert(id, campos);
+        self.ultimo_id = id;
+        self.agregadas += 1;
+        Ok(())
+    }
+
+    /// Obtiene las entradas con id entre `desde` y `hasta`, inclusive, en
+    /// orden ascendente
+    pub fn rango(
+        &self,
+        desde: StreamId,
+        hasta: StreamId,
+    ) -> impl DoubleEndedIterator<Item = (&StreamId, &CamposStream)> {
+        // BTreeMap::range no admite rangos invertidos
+        (desde <= hasta)
+            .then(|| self.entradas.range(desde..=hasta))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Itera las entradas del stream en orden ascendente
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&StreamId, &CamposStream)> {
+        self.entradas.iter()
+    }
+
+    /// Obtiene una entrada del stream
+    pub fn entrada(&self, id: &StreamId) -> Option<&CamposStream> {
+        self.entradas.get(id)
+    }
+
+    /// Obtiene la entrada con menor id
+    pub fn primera(&self) -> Option<(&StreamId, &CamposStream)> {
+        self.entradas.first_key_value()
+    }
+
+    /// Obtiene la entrada con mayor id
+    pub fn ultima(&self) -> Option<(&StreamId, &CamposStream)> {
+        self.entradas.last_key_value()
+    }
+
+    /// Grupos de consumidores del stream, ordenados por nombre
+    pub fn grupos(&self) -> &BTreeMap<String, GrupoConsumidores> {
+        &self.grupos
+    }
+
+    pub fn grupo(&self, nombre: &str) -> Option<&GrupoConsumidores> {
+        self.grupos.get(nombre)
+    }
+
+    pub fn grupo_mutable(&mut self, nombre: &str) -> Option<&mut GrupoConsumidores> {
+        self.grupos.get_mut(nombre)
+    }
+
+    /// Crea un grupo de consumidores que entrega las entradas posteriores
+    /// a `ultimo_entregado`
+    ///
+    /// # Retorna
+    /// - falso si ya existia un grupo con ese nombre
+    pub fn crear_grupo(&mut self, nombre: &str, ultimo_entregado: StreamId) -> bool {
+        if self.grupos.contains_key(nombre) {
+            return false;
+        }
+        self.grupos
+            .insert(nombre.to_string(), GrupoConsumidores::new(ultimo_entregado));
+        true
+    }
+
+    /// Elimina un grupo de consumidores
+    ///
+    /// # Retorna
+    /// - verdadero si el grupo existia
+    pub fn eliminar_grupo(&mut self, nombre: &str) -> bool {
+        self.grupos.remove(nombre).is_some()
+    }
+
+    /// Elimina una entrada del stream
+    ///
+    /// # Retorna
+    /// - verdadero si la entrada existia
+    pub fn eliminar(&mut self, id: &StreamId) -> bool {
+        self.entradas.remove(id).is_some()
+    }
+
+    /// Elimina las entradas mas antiguas hasta que el stream tenga como
+    /// maximo `maxlen` entradas
+    ///
+    /// # Retorna
+    /// - cantidad de entradas eliminadas
+    pub fn recortar_maxlen(&mut self, maxlen: usize) -> usize {
+        let mut eliminadas = 0;
+        while self.entradas.len() > maxlen {
+            self.entradas.pop_first();
+            eliminadas += 1;
+        }
+        eliminadas
+    }
+
+    /// Elimina las entradas con id menor a `minid`
+    ///
+    /// # Retorna
+    /// - cantidad de entradas eliminadas
+    pub fn recortar_minid(&mut self, minid: StreamId) -> usize {
+        let conservadas = self.entradas.split_off(&minid);
+        let eliminadas = self.entradas.len();
+        self.entradas = conservadas;
+        eliminadas
+    }
+}
+
+/// Representa una entrada como un arreglo de dos elementos: el id y el
+/// arreglo de campos y valores
+pub fn entrada_a_dato(id: &StreamId, campos: &CamposStream) -> DatoRedis {
+    let mut valores = Arrays::new();
+    for (campo, valor) in campos {
+        for bytes in [campo, valor] {
+            if let Ok(dato) = DatoRedis::new_bulk_string_desde_bytes(bytes.clone()) {
+                valores.append(dato);
+            }
+        }
+    }
+    let mut entrada = Arrays::new();
+    if let Ok(id) = DatoRedis::new_bulk_string(id.to_string()) {
+        entrada.append(id);
+    }
+    entrada.append(DatoRedis::new_array_con_contenido(valores));
+    DatoRedis::new_array_con_contenido(entrada)
+}
+
+impl Stream {
+    /// Representa al stream como el arreglo de sus entradas
+    fn como_arreglo(&self) -> Arrays {
+        Arrays::new_con_contenido(
+            self.iter()
+                .map(|(id, campos)| entrada_a_dato(id, campos))
+                .collect(),
+        )
+    }
+}
+
+impl TipoDatoRedis for Stream {
+    fn convertir_a_protocolo_resp(&self) -> String {
+        self.como_arreglo().convertir_a_protocolo_resp()
+    }
+
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        self.como_arreglo().convertir_a_protocolo_resp_bytes()
+    }
+
+    fn convertir_resp_a_string(&self) -> String {
+        self.como_arreglo().convertir_resp_a_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn campos(pares: &[(&str, &str)]) -> CamposStream {
+        pares
+            .iter()
+            .map(|(c, v)| (c.as_bytes().to_vec(), v.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_01_ids_se_parsean_y_ordenan() {
+        assert_eq!("5-3".parse::<StreamId>().unwrap(), StreamId::new(5, 3));
+        assert_eq!("7".parse::<StreamId>().unwrap(), StreamId::new(7, 0));
+        assert!("a-1".parse::<StreamId>().is_err());
+        assert!("1-".parse::<StreamId>().is_err());
+        assert!(StreamId::new(1, 9) < StreamId::new(2, 0));
+        assert_eq!(
+            StreamId::new(1, u64::MAX).siguiente(),
+            Some(StreamId::new(2, 0))
+        );
+        assert_eq!(StreamId::MIN.anterior(), None);
+    }
+
+    #[test]
+    fn test_02_ids_generados_son_crecientes() {
+        let mut stream = Stream::new();
+        let id = stream.generar_id(10, None).unwrap();
+        stream.agregar(id, campos(&[("a", "1")])).unwrap();
+        assert_eq!(stream.generar_id(10, None), Some(StreamId::new(10, 1)));
+        // El reloj retrocedio: se sigue usando el ultimo instante
+        assert_eq!(stream.generar_id(5, None), None);
+
+        assert!(stream.agregar(StreamId::new(10, 0), campos(&[])).is_err());
+        assert!(stream.agregar(StreamId::MIN, campos(&[])).is_err());
+        assert_eq!(stream.len(), 1);
+    }
+
+    #[test]
+    fn test_03_rango_y_recortes() {
+        let mut stream = Stream::new();
+        for ms in 1..=5 {
+            stream
+                .agregar(StreamId::new(ms, 0), campos(&[("n", "x")]))
+                .unwrap();
+        }
+        let ids: Vec<StreamId> = stream
+            .rango(StreamId::new(2, 0), StreamId::new(4, 0))
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                StreamId::new(2, 0),
+                StreamId::new(3, 0),
+                StreamId::new(4, 0)
+            ]
+        );
+        assert_eq!(
+            stream
+                .rango(StreamId::new(4, 0), StreamId::new(2, 0))
+                .count(),
+            0
+        );
+
+        assert_eq!(stream.recortar_minid(StreamId::new(2, 0)), 1);
+        assert_eq!(stream.recortar_maxlen(2), 2);
+        assert_eq!(stream.len(), 2);
+        assert!(stream.eliminar(&StreamId::new(5, 0)));
+        assert_eq!(stream.ultimo_id(), StreamId::new(5, 0));
+        assert_eq!(stream.agregadas(), 5);
+    }
+
+    #[test]
+    fn test_04_grupo_entrega_y_confirma_entradas() {
+        let mut stream = Stream::new();
+        assert!(stream.crear_grupo("g", StreamId::MIN));
+        assert!(!stream.crear_grupo("g", StreamId::MAX));
+
+        let grupo = stream.grupo_mutable("g").unwrap();
+        assert!(grupo.registrar_consumidor("c1", 10));
+        assert!(!grupo.registrar_consumidor("c1", 20));
+        grupo.entregar(StreamId::new(1, 0), "c1", 20, true);
+        grupo.entregar(StreamId::new(2, 0), "c1", 20, false);
+        assert_eq!(grupo.ultimo_entregado(), StreamId::new(2, 0));
+        assert_eq!(grupo.pendientes_de("c1"), 1);
+
+        assert!(grupo.reasignar(&StreamId::new(1, 0), "c2", 30, None));
+        let pendiente = &grupo.pendientes()[&StreamId::new(1, 0)];
+        assert_eq!(pendiente.consumidor, "c2");
+        assert_eq!(pendiente.entregas, 2);
+
+        assert!(grupo.confirmar(&StreamId::new(1, 0)));
+        assert!(!grupo.confirmar(&StreamId::new(1, 0)));
+        assert!(stream.eliminar_grupo("g"));
+        assert!(stream.grupos().is_empty());
+    }
+}
@@ -1,15 +1,18 @@
 use super::map_reply::MapReply;
 use super::moved_error::MovedError;
-use crate::protocol::dataencryption::decrypt_resp;
-use crate::protocol::dataencryption::encrypt_resp;
+use crate::protocol::dataencryption::{decrypt_resp_bytes, encrypt_resp_bytes};
 use crate::tipos_datos::arrays::Arrays;
 use crate::tipos_datos::bulk_string::BulkString;
 use crate::tipos_datos::constantes::*;
+use crate::tipos_datos::hyperloglog::HyperLogLog;
 use crate::tipos_datos::integer::Integer;
 use crate::tipos_datos::nulls::Null;
+use crate::tipos_datos::push::Push;
 use crate::tipos_datos::set::Set;
 use crate::tipos_datos::simple_error::SimpleError;
 use crate::tipos_datos::simple_string::SimpleString;
+use crate::tipos_datos::sorted_set::SortedSet;
+use crate::tipos_datos::stream::Stream;
 use crate::tipos_datos::verbatim_string::VerbatimString;
 use std::fmt::Debug;
 use std::io::Cursor;
@@ -18,6 +21,13 @@ use std::io::Cursor;
 pub trait TipoDatoRedis {
     fn convertir_a_protocolo_resp(&self) -> String;
     fn convertir_resp_a_string(&self) -> String;
+
+    /// Convierte el dato a su representacion resp en bytes. Los tipos que
+    /// contienen bytes arbitrarios la redefinen para no perderlos al
+    /// pasar por un String
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        self.convertir_a_protocolo_resp().into_bytes()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -32,6 +42,10 @@ pub enum DatoRedis {
     SimpleError(SimpleError),
     Map(MapReply),
     MovedError(MovedError),
+    Stream(Stream),
+    HyperLogLog(HyperLogLog),
+    SortedSet(SortedSet),
+    Push(Push),
 }
 
 impl DatoRedis {
@@ -43,6 +57,12 @@ impl DatoRedis {
         Ok(DatoRedis::BulkString(BulkString::new(contenido)?))
     }
 
+    pub fn new_bulk_string_desde_bytes(contenido: Vec<u8>) -> Result<Self, DatoRedis> {
+        Ok(DatoRedis::BulkString(BulkString::new_desde_bytes(
+            contenido,
+        )?))
+    }
+
     pub fn new_array() -> Self {
         DatoRedis::Arrays(Arrays::new())
     }
@@ -84,56 +104,68 @@ impl DatoRedis {
         DatoRedis::Map(map)
     }
 
+    pub fn new_stream() -> Self {
+        DatoRedis::Stream(Stream::new())
+    }
+
+    pub fn new_sorted_set() -> Self {
+        DatoRedis::SortedSet(SortedSet::new())
+    }
+
+    pub fn new_hyperloglog() -> Self {
+        DatoRedis::HyperLogLog(HyperLogLog::new())
+    }
+
+    pub fn new_push_con_contenido(contenido: Arrays) -> Self {
+        DatoRedis::Push(Push::new(contenido))
+    }
+
     pub fn new_moved_error(slot: u16) -> Self {
         DatoRedis::MovedError(MovedError::new(slot))
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let resp: String = self.convertir_a_protocolo_resp();
-        encrypt_resp(&resp).unwrap_or_else(|_| vec![])
+        encrypt_resp_bytes(&self.convertir_a_protocolo_resp_bytes()).unwrap_or_else(|_| vec![])
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatoRedis> {
-        let contenido_resp =
-            String::from_utf8(bytes.to_vec()).map_err(|_e| DatoRedis::new_null())?;
         let tipo_dato = bytes
             .first()
             .ok_or_else(DatoRedis::new_null)
             .map(|b| *b as char)?;
         let tipo_dato = tipo_dato.to_string();
-        Self::new_dato_redis(&tipo_dato, contenido_resp)
+        Self::new_dato_redis(&tipo_dato, bytes)
     }
 
     pub fn from_encrypted_bytes(bytes: &[u8]) -> Result<Self, DatoRedis> {
         let mut cursor = Cursor::new(bytes);
 
-        let contenido_resp = decrypt_resp(&mut cursor)?;
+        let contenido_resp = decrypt_resp_bytes(&mut cursor)?;
 
-        let tipo_dato = contenido_resp
-            .chars()
-            .next()
-            .ok_or_else(DatoRedis::new_null)?
-            .to_string();
-
-        Self::new_dato_redis(&tipo_dato, contenido_resp)
+        Self::from_bytes(&contenido_resp)
     }
 
-    fn new_dato_redis(tipo: &str, contenido_resp: String) -> Result<Self, DatoRedis> {
+    /// Interpreta un dato en formato resp. Los tipos que pueden contener
+    /// bytes arbitrarios se leen como bytes, el resto debe ser UTF-8 valido
+    fn new_dato_redis(tipo: &str, contenido_resp: &[u8]) -> Result<Self, DatoRedis> {
+        let texto =
+            || String::from_utf8(contenido_resp.to_vec()).map_err(|_e| DatoRedis::new_null());
         match tipo {
             SIMPLE_STRING_SIMBOL => Ok(DatoRedis::SimpleString(SimpleString::new_desde_resp(
-                contenido_resp,
+                texto()?,
             )?)),
             BULK_STRING_SIMBOL => Ok(DatoRedis::BulkString(BulkString::new_desde_resp(
                 contenido_resp,
             )?)),
-            INTEGER_SIMBOL => Ok(DatoRedis::Integer(Integer::new_desde_resp(contenido_resp)?)),
+            INTEGER_SIMBOL => Ok(DatoRedis::Integer(Integer::new_desde_resp(texto()?)?)),
             ARRAY_SIMBOL => Ok(DatoRedis::Arrays(Arrays::new_desde_resp(contenido_resp)?)),
             SETS_SIMBOL => Ok(DatoRedis::Set(Set::new_desde_resp(contenido_resp)?)),
-            NULL_SIMBOL => Ok(DatoRedis::Null(Null::new_desde_resp(contenido_resp)?)),
+            NULL_SIMBOL => Ok(DatoRedis::Null(Null::new_desde_resp(texto()?)?)),
             ERROR_SIMBOL => Ok(DatoRedis::SimpleError(SimpleError::new_desde_resp(
-                contenido_resp,
+                texto()?
             )?)),
             MAP_SYMBOL => Ok(DatoRedis::Map(MapReply::new_desde_resp(contenido_resp)?)),
+            PUSH_SIMBOL => Ok(DatoRedis::Push(Push::new_desde_resp(contenido_resp)?)),
             //VERBATIM_STRING => leer_y_convertir(&mut reader, interpretar_verbatim_string, VerbatimString::new_desde_resp),
             _ => Err(DatoRedis::new_null()),
         }
@@ -156,6 +188,26 @@ impl TipoDatoRedis for DatoRedis {
             DatoRedis::SimpleError(simple_error) => simple_error.convertir_a_protocolo_resp(),
             DatoRedis::Map(map_reply) => map_reply.convertir_a_protocolo_resp(),
             DatoRedis::MovedError(moved_error) => moved_error.convertir_a_protocolo_resp(),
+            DatoRedis::Stream(stream) => stream.convertir_a_protocolo_resp(),
+            DatoRedis::HyperLogLog(hll) => hll.convertir_a_protocolo_resp(),
+            DatoRedis::SortedSet(sorted_set) => sorted_set.convertir_a_protocolo_resp(),
+            DatoRedis::Push(push) => push.convertir_a_protocolo_resp(),
+        }
+    }
+
+    /// Convierte un struct de tipo Dato Redis a su representacion resp en
+    /// bytes
+    fn convertir_a_protocolo_resp_bytes(&self) -> Vec<u8> {
+        match self {
+            DatoRedis::BulkString(bulk_string) => bulk_string.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::Arrays(arrays) => arrays.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::Set(set) => set.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::Map(map_reply) => map_reply.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::Stream(stream) => stream.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::HyperLogLog(hll) => hll.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::SortedSet(sorted_set) => sorted_set.convertir_a_protocolo_resp_bytes(),
+            DatoRedis::Push(push) => push.convertir_a_protocolo_resp_bytes(),
+            _ => self.convertir_a_protocolo_resp().into_bytes(),
         }
     }
 
@@ -172,6 +224,10 @@ impl TipoDatoRedis for DatoRedis {
             DatoRedis::SimpleError(simple_error) => simple_error.convertir_resp_a_string(),
             DatoRedis::Map(map_reply) => map_reply.convertir_resp_a_string(),
             DatoRedis::MovedError(moved_error) => moved_error.convertir_resp_a_string(),
+            DatoRedis::Stream(stream) => stream.convertir_resp_a_string(),
+            DatoRedis::HyperLogLog(hll) => hll.convertir_resp_a_string(),
+            DatoRedis::SortedSet(sorted_set) => sorted_set.convertir_resp_a_string(),
+            DatoRedis::Push(push) => push.convertir_resp_a_string(),
         }
     }
 }
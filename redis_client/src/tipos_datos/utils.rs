@@ -9,7 +9,7 @@ use crate::tipos_datos::moved_error::MovedError;
 use crate::tipos_datos::simple_error::SimpleError;
 use crate::tipos_errores::errores::Error;
 
-/// Para un string que representa un arreglo en formato resp, obtiene su
+/// Para los bytes que representan un arreglo en formato resp, obtiene su
 /// primer elemento (dato redis) y el indice del resp donde inicia el proximo
 /// elemento
 ///
@@ -19,15 +19,16 @@ use crate::tipos_errores::errores::Error;
 /// # Retorna
 /// - tupla de dato redis e indice de inicio del proximo elemento en caso de
 ///   exito, error en otro caso
-pub fn obtener_elemento(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    if let Some(token_inicio) = arreglo_resp.chars().next() {
-        match token_inicio.to_string().as_str() {
+pub fn obtener_elemento(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    if let Some(token_inicio) = arreglo_resp.first() {
+        match (*token_inicio as char).to_string().as_str() {
             INTEGER_SIMBOL => obtener_integer(arreglo_resp),
             BULK_STRING_SIMBOL => obtener_bulk_string(arreglo_resp),
             SIMPLE_STRING_SIMBOL => obtener_simple_string(arreglo_resp),
             ARRAY_SIMBOL => obtener_array(arreglo_resp),
             ERROR_SIMBOL => obtener_simple_error(arreglo_resp),
             MOVED_ERROR_SIMBOL => obtener_moved_error(arreglo_resp),
+            NULL_SIMBOL => obtener_null(arreglo_resp),
             _ => Err(Error::DatoIngresadoEsInvalido),
         }
     } else {
@@ -35,6 +36,26 @@ pub fn obtener_elemento(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error>
     }
 }
 
+/// Obtiene la primera linea de una cadena resp, sin su caracter inicial
+///
+/// # Parametros:
+/// * `arreglo_resp`: representacion resp
+///
+/// # Retorna
+/// - tupla del texto de la linea e indice donde termina (luego del CRLF)
+///   en caso de exito, error en otro caso
+fn obtener_linea(arreglo_resp: &[u8]) -> Result<(&str, usize), Error> {
+    let fin = arreglo_resp
+        .windows(CRLF.len())
+        .position(|par| par == CRLF.as_bytes())
+        .ok_or(Error::DatoIngresadoEsInvalido)?;
+    let linea = arreglo_resp
+        .get(1..fin)
+        .and_then(|linea| std::str::from_utf8(linea).ok())
+        .ok_or(Error::DatoIngresadoEsInvalido)?;
+    Ok((linea, fin + CRLF.len()))
+}
+
 /// Dado que el primer elemento de una cadena resp represene un entero,
 /// lo obtiene
 ///
@@ -44,26 +65,17 @@ pub fn obtener_elemento(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error>
 /// # Retorna
 /// - tupla de dato redis (integer) e indice de inicio del proximo elemento
 ///   en caso de exito, error en otro caso
-fn obtener_integer(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let mut numero_string = String::new();
-    let mut indice_fin = 0;
-    for i in 1..arreglo_resp.len() {
-        if arreglo_resp.chars().nth(i) == Some('\r')
-            && arreglo_resp.chars().nth(i + 1) == Some('\n')
-        {
-            indice_fin = i + 1;
-            break;
-        }
-        agregar_elemento(&mut numero_string, arreglo_resp, i);
-    }
+fn obtener_integer(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (numero_string, indice_fin) = obtener_linea(arreglo_resp)?;
     if let Ok(numero) = numero_string.parse::<i64>() {
-        return Ok((DatoRedis::Integer(Integer::new(numero)), indice_fin + 1));
+        return Ok((DatoRedis::Integer(Integer::new(numero)), indice_fin));
     }
     Err(Error::DatoIngresadoEsInvalido)
 }
 
 /// Dado que el primer elemento de una cadena resp represene un bulk string,
-/// lo obtiene
+/// lo obtiene. Su contenido se toma como bytes, sin exigir que sea UTF-8
+/// valido
 ///
 /// # Parametros:
 /// * `arreglo_resp`: representacion resp
@@ -71,37 +83,15 @@ fn obtener_integer(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
 /// # Retorna
 /// - tupla de dato redis (bulk string) e indice de inicio del proximo elemento
 ///   en caso de exito, error en otro caso
-fn obtener_bulk_string(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let mut numero_string = String::new();
-    let mut indice_fin = 0;
-    for i in 1..arreglo_resp.len() {
-        if arreglo_resp.chars().nth(i) == Some('\r')
-            && arreglo_resp.chars().nth(i + 1) == Some('\n')
-        {
-            indice_fin = i + 1;
-            break;
-        }
-        agregar_elemento(&mut numero_string, arreglo_resp, i);
-    }
+fn obtener_bulk_string(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (numero_string, indice_inicio) = obtener_linea(arreglo_resp)?;
     if let Ok(numero) = numero_string.parse::<usize>() {
-        let mut indice_final = indice_fin + 1;
-        let mut texto = String::new();
-        for i in indice_fin + 1..indice_fin + 1 + numero {
-            if indice_final == indice_fin + 1 + numero {
-                break;
-            }
-            if let Some(n) = arreglo_resp.chars().nth(i) {
-                texto.push(n);
-                indice_final += n.to_string().len();
-            }
-        }
-        let indice_acceso = indice_fin + 1 + texto.chars().count();
-        if arreglo_resp.chars().nth(indice_acceso) == Some('\r')
-            && arreglo_resp.chars().nth(indice_acceso + 1) == Some('\n')
+        let indice_fin = indice_inicio + numero;
+        if arreglo_resp.get(indice_fin..indice_fin + CRLF.len()) == Some(CRLF.as_bytes())
+            && let Ok(bulk_string) =
+                BulkString::new_desde_bytes(arreglo_resp[indice_inicio..indice_fin].to_vec())
         {
-            if let Ok(bulk_string) = BulkString::new(texto) {
-                return Ok((DatoRedis::BulkString(bulk_string), indice_final + 2));
-            }
+            return Ok((DatoRedis::BulkString(bulk_string), indice_fin + CRLF.len()));
         }
     }
     Err(Error::DatoIngresadoEsInvalido)
@@ -116,19 +106,27 @@ fn obtener_bulk_string(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error>
 /// # Retorna
 /// - tupla de dato redis (simple string) e indice de inicio del proximo elemento
 ///   en caso de exito, error en otro caso
-fn obtener_simple_string(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let mut texto = String::new();
-    for i in 1..arreglo_resp.len() {
-        if arreglo_resp.chars().nth(i) == Some('\r')
-            && arreglo_resp.chars().nth(i + 1) == Some('\n')
-        {
-            break;
-        }
-        agregar_elemento(&mut texto, arreglo_resp, i);
+fn obtener_simple_string(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (texto, indice_prox) = obtener_linea(arreglo_resp)?;
+    if let Ok(simple_string) = SimpleString::new(texto.to_string()) {
+        return Ok((DatoRedis::SimpleString(simple_string), indice_prox));
     }
-    let indice_prox = texto.len() + 2;
-    if let Ok(simple_string) = SimpleString::new(texto) {
-        return Ok((DatoRedis::SimpleString(simple_string), indice_prox + 1));
+    Err(Error::DatoIngresadoEsInvalido)
+}
+
+/// Dado que el primer elemento de una cadena resp represente un null,
+/// lo obtiene
+///
+/// # Parametros:
+/// * `arreglo_resp`: representacion resp
+///
+/// # Retorna
+/// - tupla de dato redis (null) e indice de inicio del proximo elemento
+///   en caso de exito, error en otro caso
+fn obtener_null(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let null_resp = format!("{NULL_SIMBOL}{CRLF}");
+    if arreglo_resp.starts_with(null_resp.as_bytes()) {
+        return Ok((DatoRedis::new_null(), null_resp.len()));
     }
     Err(Error::DatoIngresadoEsInvalido)
 }
@@ -142,21 +140,11 @@ fn obtener_simple_string(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error
 /// # Retorna
 /// - tupla de dato redis (arreglo) e indice de inicio del proximo elemento
 ///   en caso de exito, error en otro caso
-fn obtener_array(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let mut numero_string = String::new();
-    let mut indice_fin = 0;
-    for i in 1..arreglo_resp.len() {
-        if arreglo_resp.chars().nth(i) == Some('\r')
-            && arreglo_resp.chars().nth(i + 1) == Some('\n')
-        {
-            indice_fin = i + 1;
-            break;
-        }
-        agregar_elemento(&mut numero_string, arreglo_resp, i);
-    }
+fn obtener_array(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (numero_string, indice_inicio) = obtener_linea(arreglo_resp)?;
     if let Ok(numero) = numero_string.parse::<usize>() {
         let mut array = Vec::new();
-        let mut indice_acceso = indice_fin + 1;
+        let mut indice_acceso = indice_inicio;
         for _ in 0..numero {
             let (dato, nuevo_indice) = obtener_elemento(&arreglo_resp[indice_acceso..])?;
             array.push(dato);
@@ -170,11 +158,8 @@ fn obtener_array(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
     Err(Error::DatoIngresadoEsInvalido)
 }
 
-fn obtener_simple_error(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let end = arreglo_resp
-        .find("\r\n")
-        .ok_or(Error::DatoIngresadoEsInvalido)?;
-    let linea = &arreglo_resp[1..end];
+fn obtener_simple_error(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (linea, siguiente_indice) = obtener_linea(arreglo_resp)?;
 
     let (head, cuerpo) = match linea.find(' ') {
         Some(idx) => linea.split_at(idx),
@@ -182,20 +167,16 @@ fn obtener_simple_error(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error>
     };
     let cuerpo = &cuerpo[1..];
     let simple = SimpleError::new(head.to_string(), cuerpo.to_string());
-    let siguiente_indice = end + 2;
 
     Ok((DatoRedis::SimpleError(simple), siguiente_indice))
 }
 
-fn obtener_moved_error(arreglo_resp: &str) -> Result<(DatoRedis, usize), Error> {
-    let end = arreglo_resp
-        .find("\r\n")
-        .ok_or(Error::DatoIngresadoEsInvalido)?;
-    let linea = &arreglo_resp[1..end];
+fn obtener_moved_error(arreglo_resp: &[u8]) -> Result<(DatoRedis, usize), Error> {
+    let (linea, siguiente_indice) = obtener_linea(arreglo_resp)?;
     let slot: u16 = linea.parse().map_err(|_| Error::DatoIngresadoEsInvalido)?;
     let moved = MovedError::new(slot);
 
-    Ok((DatoRedis::MovedError(moved), end + 2))
+    Ok((DatoRedis::MovedError(moved), siguiente_indice))
 }
 
 /// Concatena un char a un string
@@ -0,0 +1,207 @@
+//! Compara el tráfico del bus del clúster al reenviar publicaciones a todos
+//! los nodos contra reenviarlas solo a los nodos que pueden tener
+//! suscriptores, según los resúmenes de suscripciones que comparten.
+//!
+//! Simula un clúster de 9 nodos (3 masters con 2 réplicas cada uno) y
+//! cuenta los mensajes y bytes que cada estrategia envía por el bus,
+//! incluyendo el costo de enviar los resúmenes.
+//!
+//! ```text
+//! cargo bench -p redis_node --bench pubsub_cluster
+//! ```
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use redis_client::protocol::token::Token;
+use redis_node::constantes::TOTAL_SLOTS;
+use redis_node::internal_protocol::header::{HeaderParameters, MessageHeader};
+use redis_node::internal_protocol::internal_protocol_msg::{ClusterMessage, ClusterMessagePayload};
+use redis_node::internal_protocol::internal_protocol_type::InternalProtocolType;
+use redis_node::internal_protocol::node_flags::{ClusterState, NodeFlags};
+use redis_node::internal_protocol::protocol_trait::SerializeRIP;
+use redis_node::internal_protocol::redis_cmd::RedisCMD;
+use redis_node::internal_protocol::resumen_pubsub::ResumenPubSub;
+use redis_node::node_id::NodeId;
+use redis_node::rutas_pubsub::{REFRESCO_RESUMEN, RutasPubSub};
+
+const NODOS: usize = 9;
+const MASTERS: usize = 3;
+/// Canales en uso en el clúster
+const CANALES: usize = 10_000;
+/// Canales con suscriptores en cada nodo
+const SUSCRIPCIONES_POR_NODO: usize = 300;
+const PUBLICACIONES: usize = 100_000;
+/// Porcentaje de publicaciones que son SPUBLISH
+const PORCENTAJE_SPUBLISH: u32 = 10;
+/// Duración simulada de la carga, para contar los refrescos de resúmenes
+const DURACION: Duration = Duration::from_secs(60);
+const SEMILLA: u64 = 49;
+
+struct NodoSimulado {
+    id: NodeId,
+    slots: Range<u16>,
+    rutas: RutasPubSub,
+    resumen: ResumenPubSub,
+}
+
+#[derive(Default)]
+struct Trafico {
+    mensajes: u64,
+    bytes: u64,
+}
+
+fn header(id: &NodeId, tipo: InternalProtocolType, slots: Range<u16>) -> MessageHeader {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 6379);
+    MessageHeader::new(HeaderParameters {
+        header_type: tipo,
+        node_id: id.clone(),
+        current_epoch: 1,
+        config_epoch: 1,
+        flags: NodeFlags::new(true, false, false, false),
+        hash_slots_bitmap: slots,
+        tcp_client_port: addr,
+        cluster_node_port: addr,
+        cluster_state: ClusterState::Ok,
+        master_id: None,
+    })
+}
+
+fn crear_nodos(rng: &mut StdRng) -> Vec<NodoSimulado> {
+    let por_master = TOTAL_SLOTS / MASTERS as u16;
+    (0..NODOS)
+        .map(|i| {
+            let master = (i % MASTERS) as u16;
+            let fin = if master as usize == MASTERS - 1 {
+                TOTAL_SLOTS
+            } else {
+                (master + 1) * por_master
+            };
+            let canales: Vec<Token> = sample(rng, CANALES, SUSCRIPCIONES_POR_NODO)
+                .iter()
+                .map(|c| Token::from(format!("documento:{c}")))
+                .collect();
+            let patrones = [Token::from(format!("alertas:{i}:*"))];
+            NodoSimulado {
+                id: NodeId::new(),
+                slots: master * por_master..fin,
+                rutas: RutasPubSub::new(),
+                resumen: ResumenPubSub::new(canales.iter(), patrones.iter()),
+            }
+        })
+        .collect()
+}
+
+/// Cada nodo envía su resumen al resto, al inicio y en cada refresco
+fn compartir_resumenes(nodos: &[NodoSimulado]) -> Trafico {
+    let envios = 1 + (DURACION.as_secs() / REFRESCO_RESUMEN.as_secs());
+    let mut trafico = Trafico::default();
+    for emisor in nodos {
+        let msg = ClusterMessage::new(
+            header(
+                &emisor.id,
+                InternalProtocolType::PubSubSummary,
+                emisor.slots.clone(),
+            ),
+            ClusterMessagePayload::PubSubSummary(emisor.resumen.clone()),
+        );
+        let bytes = msg.serialize().len() as u64;
+        for receptor in nodos.iter().filter(|n| n.id != emisor.id) {
+            receptor
+                .rutas
+                .registrar_remoto(emisor.id.clone(), emisor.resumen.clone());
+            trafico.mensajes += envios;
+            trafico.bytes += envios * bytes;
+        }
+    }
+    trafico
+}
+
+fn publicacion(rng: &mut StdRng) -> Vec<Token> {
+    let comando = if rng.random_range(0..100) < PORCENTAJE_SPUBLISH {
+        "SPUBLISH"
+    } else {
+        "PUBLISH"
+    };
+    let canal = format!("documento:{}", rng.random_range(0..CANALES));
+    vec![
+        Token::from(comando),
+        Token::from(canal),
+        Token::from("documento actualizado"),
+    ]
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(SEMILLA);
+    let nodos = crear_nodos(&mut rng);
+    let resumenes = compartir_resumenes(&nodos);
+
+    let mut difusion = Trafico::default();
+    let mut dirigido = Trafico::default();
+    let mut tiempo_ruteo = Duration::ZERO;
+    for _ in 0..PUBLICACIONES {
+        let emisor = &nodos[rng.random_range(0..NODOS)];
+        let tokens = publicacion(&mut rng);
+        let msg = ClusterMessage::new(
+            header(
+                &emisor.id,
+                InternalProtocolType::Publish,
+                emisor.slots.clone(),
+            ),
+            ClusterMessagePayload::PubSub(RedisCMD::new(tokens.clone())),
+        );
+        let bytes = msg.serialize().len() as u64;
+        let candidatos: Vec<(NodeId, Option<Range<u16>>)> = nodos
+            .iter()
+            .filter(|n| n.id != emisor.id)
+            .map(|n| (n.id.clone(), Some(n.slots.clone())))
+            .collect();
+
+        difusion.mensajes += candidatos.len() as u64;
+        difusion.bytes += candidatos.len() as u64 * bytes;
+
+        let inicio = Instant::now();
+        let destinos = emisor.rutas.destinos(&tokens, candidatos).len() as u64;
+        tiempo_ruteo += inicio.elapsed();
+        dirigido.mensajes += destinos;
+        dirigido.bytes += destinos * bytes;
+    }
+    let omitidos: u64 = nodos.iter().map(|n| n.rutas.omitidos()).sum();
+
+    println!(
+        "{NODOS} nodos, {CANALES} canales, {SUSCRIPCIONES_POR_NODO} canales suscriptos por nodo, \
+         {PUBLICACIONES} publicaciones ({PORCENTAJE_SPUBLISH}% SPUBLISH) en {}s",
+        DURACION.as_secs()
+    );
+    println!("{:<22}{:>12}{:>14}", "estrategia", "mensajes", "bytes");
+    println!(
+        "{:<22}{:>12}{:>14}",
+        "difusion", difusion.mensajes, difusion.bytes
+    );
+    println!(
+        "{:<22}{:>12}{:>14}",
+        "dirigido", dirigido.mensajes, dirigido.bytes
+    );
+    println!(
+        "{:<22}{:>12}{:>14}",
+        "resumenes", resumenes.mensajes, resumenes.bytes
+    );
+    let total = dirigido.bytes + resumenes.bytes;
+    println!(
+        "{:<22}{:>12}{:>14}",
+        "dirigido + resumenes",
+        dirigido.mensajes + resumenes.mensajes,
+        total
+    );
+    println!(
+        "reenvios omitidos: {omitidos}, bytes del bus: {:.1}% de la difusion",
+        total as f64 * 100.0 / difusion.bytes as f64
+    );
+    println!(
+        "ruteo: {:.2}us por publicacion",
+        tiempo_ruteo.as_secs_f64() * 1e6 / PUBLICACIONES as f64
+    );
+}
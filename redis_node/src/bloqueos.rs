@@ -0,0 +1,197 @@
+//! Este módulo contiene la cola de clientes bloqueados por los comandos
+//! BLPOP, BRPOP, BLMOVE y XREAD a la espera de que una lista o un stream
+//! tenga elementos
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use redis_client::protocol::token::Token;
+
+/// Tiempo máximo que un cliente bloqueado espera sin volver a verificar su
+/// conexión y la pertenencia de sus claves al nodo.
+pub const INTERVALO_BLOQUEO: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+struct EstadoBloqueos {
+    /// Tickets de los clientes bloqueados por cada clave, en orden de llegada.
+    esperando: HashMap<Token, VecDeque<u64>>,
+    siguiente_ticket: u64,
+    /// Se incrementa con cada modificación del storage que pueda
+    /// desbloquear a un cliente.
+    version: u64,
+}
+
+/// Cola de clientes bloqueados por clave. Los clientes se atienden en el
+/// orden en que se bloquearon: solo el primero de la cola de una clave
+/// puede extraer elementos de ella.
+#[derive(Debug, Default)]
+pub struct ColaBloqueos {
+    estado: Mutex<EstadoBloqueos>,
+    aviso: Condvar,
+}
+
+impl ColaBloqueos {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encola a un cliente en las colas de las claves indicadas
+    ///
+    /// # Retorna
+    /// - ticket que identifica al cliente en las colas
+    pub fn bloquear(&self, claves: &[Token]) -> u64 {
+        let Ok(mut estado) = self.estado.lock() else {
+            return 0;
+        };
+        estado.siguiente_ticket += 1;
+        let ticket = estado.siguiente_ticket;
+        for clave in claves {
+            let cola = estado.esperando.entry(clave.clone()).or_default();
+            if !cola.contains(&ticket) {
+                cola.push_back(ticket);
+            }
+        }
+        ticket
+    }
+
+    /// Quita a un cliente de las colas de sus claves y despierta al resto,
+    /// por si era el primero de alguna
+    pub fn desbloquear(&self, ticket: u64, claves: &[Token]) {
+        if let Ok(mut estado) = self.estado.lock() {
+            for clave in claves {
+                if let Some(cola) = estado.esperando.get_mut(clave) {
+                    cola.retain(|t| *t != ticket);
+                    if cola.is_empty() {
+                        estado.esperando.remove(clave);
+                    }
+                }
+            }
+            estado.version += 1;
+        }
+        self.aviso.notify_all();
+    }
+
+    /// Obtiene las claves, en el orden recibido, en las que el cliente es
+    /// el primero de la cola
+    pub fn claves_en_turno(&self, ticket: u64, claves: &[Token]) -> Vec<Token> {
+        let Ok(estado) = self.estado.lock() else {
+            return Vec::new();
+        };
+        claves
+            .iter()
+            .filter(|clave| {
+                estado
+                    .esperando
+                    .get(*clave)
+                    .and_then(|cola| cola.front())
+                    .is_some_and(|primero| *primero == ticket)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Versión actual de la cola, a utilizar con `esperar`
+    pub fn version(&self) -> u64 {
+        self.estado.lock().map(|e| e.version).unwrap_or(0)
+    }
+
+    /// Indica si hay clientes bloqueados
+    pub fn hay_bloqueados(&self) -> bool {
+        self.estado
+            .lock()
+            .map(|e| !e.esperando.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Despierta a los clientes bloqueados para que vuelvan a intentar
+    /// extraer elementos
+    pub fn notificar(&self) {
+        if let Ok(mut estado) = self.estado.lock() {
+            if estado.esperando.is_empty() {
+                return;
+            }
+            estado.version += 1;
+        }
+        self.aviso.notify_all();
+    }
+
+    /// Espera hasta que la versión cambie respecto de `version_vista` o
+    /// hasta el instante `hasta`, lo que ocurra primero
+    pub fn esperar(&self, version_vista: u64, hasta: Instant) {
+        let Ok(mut estado) = self.estado.lock() else {
+            return;
+        };
+        while estado.version == version_vista {
+            let ahora = Instant::now();
+            if ahora >= hasta {
+                return;
+            }
+            estado = match self.aviso.wait_timeout(estado, hasta - ahora) {
+                Ok((estado, _)) => estado,
+                Err(_) => return,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn claves(nombres: &[&str]) -> Vec<Token> {
+        nombres.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_01_clientes_atendidos_en_orden_de_llegada() {
+        let cola = ColaBloqueos::new();
+        let primero = cola.bloquear(&claves(&["a", "b"]));
+        let segundo = cola.bloquear(&claves(&["b"]));
+
+        assert_eq!(
+            cola.claves_en_turno(primero, &claves(&["a", "b"])),
+            claves(&["a", "b"])
+        );
+        assert!(cola.claves_en_turno(segundo, &claves(&["b"])).is_empty());
+
+        cola.desbloquear(primero, &claves(&["a", "b"]));
+        assert_eq!(
+            cola.claves_en_turno(segundo, &claves(&["b"])),
+            claves(&["b"])
+        );
+        cola.desbloquear(segundo, &claves(&["b"]));
+        assert!(!cola.hay_bloqueados());
+    }
+
+    #[test]
+    fn test_02_notificar_despierta_al_cliente_en_espera() {
+        let cola = Arc::new(ColaBloqueos::new());
+        let ticket = cola.bloquear(&claves(&["a"]));
+        let version = cola.version();
+
+        let notificadora = Arc::clone(&cola);
+        let hilo = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            notificadora.notificar();
+        });
+
+        let inicio = Instant::now();
+        cola.esperar(version, inicio + Duration::from_secs(5));
+        assert!(inicio.elapsed() < Duration::from_secs(5));
+        assert_ne!(cola.version(), version);
+
+        hilo.join().unwrap();
+        cola.desbloquear(ticket, &claves(&["a"]));
+    }
+
+    #[test]
+    fn test_03_esperar_respeta_el_limite() {
+        let cola = ColaBloqueos::new();
+        let version = cola.version();
+        let inicio = Instant::now();
+        cola.esperar(version, inicio + Duration::from_millis(30));
+        assert!(inicio.elapsed() >= Duration::from_millis(30));
+    }
+}
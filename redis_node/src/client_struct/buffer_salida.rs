@@ -0,0 +1,351 @@
+//! Este modulo contiene el buffer de salida de los clientes y los limites
+//! que se aplican a los suscriptores de pub/sub
+//! (`client_output_buffer_limit`).
+//!
+//! Cada mensaje encolado para un cliente suma sus bytes al buffer hasta que
+//! el hilo escritor lo envia por el socket. Un suscriptor que no lee sus
+//! mensajes hace crecer el buffer, por lo que al publicar se lo desconecta
+//! si supera el limite duro, o si permanece por encima del limite blando
+//! durante la cantidad de segundos configurada.
+use std::fmt;
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{SendError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use crate::memoria::parsear_memoria;
+
+/// Unica clase de clientes a la que se aplican los limites
+pub const CLASE_PUBSUB: &str = "pubsub";
+/// Limite duro por defecto: 32mb
+pub const LIMITE_DURO_DEFAULT: usize = 32 * 1024 * 1024;
+/// Limite blando por defecto: 8mb
+pub const LIMITE_BLANDO_DEFAULT: usize = 8 * 1024 * 1024;
+/// Segundos por defecto que se tolera superar el limite blando
+pub const SEGUNDOS_BLANDO_DEFAULT: u64 = 60;
+
+/// Limites del buffer de salida de los suscriptores. Un limite en 0 esta
+/// deshabilitado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitesBuffer {
+    pub duro: usize,
+    pub blando: usize,
+    pub segundos_blando: u64,
+}
+
+impl Default for LimitesBuffer {
+    fn default() -> Self {
+        LimitesBuffer {
+            duro: LIMITE_DURO_DEFAULT,
+            blando: LIMITE_BLANDO_DEFAULT,
+            segundos_blando: SEGUNDOS_BLANDO_DEFAULT,
+        }
+    }
+}
+
+impl LimitesBuffer {
+    /// Parsea los limites con el formato
+    /// `pubsub <limite duro> <limite blando> <segundos>`, donde los limites
+    /// aceptan los sufijos de memoria (`kb`, `mb`, `gb`)
+    ///
+    /// # Retorna
+    /// - Limites parseados, mensaje de error si el valor es invalido
+    pub fn parsear(valor: &str) -> Result<Self, String> {
+        let partes: Vec<&str> = valor.split_whitespace().collect();
+        let [clase, duro, blando, segundos] = partes[..] else {
+            return Err("wrong number of arguments".to_string());
+        };
+        if !clase.eq_ignore_ascii_case(CLASE_PUBSUB) {
+            return Err(format!("Invalid client class specified: '{clase}'"));
+        }
+        Ok(LimitesBuffer {
+            duro: parsear_memoria(duro)?,
+            blando: parsear_memoria(blando)?,
+            segundos_blando: segundos
+                .parse()
+                .map_err(|_| format!("Cantidad de segundos inválida: '{segundos}'"))?,
+        })
+    }
+}
+
+impl fmt::Display for LimitesBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{CLASE_PUBSUB} {} {} {}",
+            self.duro, self.blando, self.segundos_blando
+        )
+    }
+}
+
+/// Bytes encolados para un cliente que el hilo escritor todavia no envio
+#[derive(Debug)]
+pub struct BufferSalida {
+    pendientes: AtomicUsize,
+    /// Instante desde el que el buffer supera el limite blando
+    excedido_desde: Mutex<Option<Instant>>,
+    desconectado: AtomicBool,
+    /// Conexion del cliente, cerrada al desconectarlo por superar un limite
+    conexion: Option<TcpStream>,
+}
+
+impl BufferSalida {
+    pub fn new(conexion: Option<TcpStream>) -> Self {
+        BufferSalida {
+            pendientes: AtomicUsize::new(0),
+            excedido_desde: Mutex::new(None),
+            desconectado: AtomicBool::new(false),
+            conexion,
+        }
+    }
+
+    /// Cantidad de bytes pendientes de envio
+    pub fn pendientes(&self) -> usize {
+        self.pendientes.load(Ordering::Relaxed)
+    }
+
+    /// Descuenta los bytes de un mensaje ya enviado por el socket
+    pub fn liberar(&self, bytes: usize) {
+        let _ = self
+            .pendientes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| {
+                Some(p.saturating_sub(bytes))
+            });
+    }
+
+    /// Indica si el cliente fue desconectado por superar un limite
+    pub fn desconectado(&self) -> bool {
+        self.desconectado.load(Ordering::Relaxed)
+    }
+
+    /// Determina si encolar `bytes` mas supera los limites del buffer,
+    /// registrando el instante en que se supera el limite blando
+    fn excede(&self, limites: &LimitesBuffer, bytes: usize) -> bool {
+        let total = self.pendientes() + bytes;
+        if limites.duro > 0 && total > limites.duro {
+            return true;
+        }
+        let Ok(mut excedido_desde) = self.excedido_desde.lock() else {
+            return false;
+        };
+        if limites.blando == 0 || total <= limites.blando {
+            *excedido_desde = None;
+            return false;
+        }
+        let desde = excedido_desde.get_or_insert_with(Instant::now);
+        desde.elapsed().as_secs() >= limites.segundos_blando
+    }
+
+    /// Cierra la conexion del cliente. El hilo lector del nodo detecta el
+    /// cierre y lo desuscribe de sus canales
+    ///
+    /// # Retorna
+    /// - Verdadero si el cliente no habia sido desconectado antes
+    fn desconectar(&self) -> bool {
+        if self.desconectado.swap(true, Ordering::Relaxed) {
+            return false;
+        }
+        if let Some(conexion) = &self.conexion {
+            let _ = conexion.shutdown(Shutdown::Both);
+        }
+        true
+    }
+}
+
+/// Extremo por el que se encolan los mensajes de un cliente para su hilo
+/// escritor, contabilizando los bytes en su buffer de salida
+#[derive(Debug, Clone)]
+pub struct SalidaCliente {
+    sender: Sender<Vec<u8>>,
+    buffer: Arc<BufferSalida>,
+}
+
+impl SalidaCliente {
+    pub fn new(sender: Sender<Vec<u8>>, buffer: Arc<BufferSalida>) -> Self {
+        SalidaCliente { sender, buffer }
+    }
+
+    /// Encola un mensaje para el cliente
+    pub fn enviar(&self, mensaje: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        let bytes = mensaje.len();
+        self.buffer.pendientes.fetch_add(bytes, Ordering::Relaxed);
+        self.sender.send(mensaje).inspect_err(|_| {
+            self.buffer.liberar(bytes);
+        })
+    }
+
+    pub fn buffer(&self) -> &Arc<BufferSalida> {
+        &self.buffer
+    }
+}
+
+impl From<Sender<Vec<u8>>> for SalidaCliente {
+    /// Salida sin conexion asociada, que no puede cerrarse al superar un
+    /// limite
+    fn from(sender: Sender<Vec<u8>>) -> Self {
+        SalidaCliente::new(sender, Arc::new(BufferSalida::new(None)))
+    }
+}
+
+/// Resultado de entregar un mensaje a un suscriptor
+#[derive(Debug, PartialEq)]
+pub enum Entrega {
+    Encolado,
+    /// Se desconecto al suscriptor por superar los limites de su buffer
+    Desconectado,
+    /// El suscriptor ya habia sido desconectado
+    Descartado,
+}
+
+/// Limites vigentes de los buffers de salida de los suscriptores y
+/// cantidad de desconexiones que provocaron, compartidos entre el broker
+/// de pub/sub y el nodo (CONFIG e INFO)
+#[derive(Debug, Default)]
+pub struct ControlBuffers {
+    limites: RwLock<LimitesBuffer>,
+    desconexiones: AtomicU64,
+}
+
+impl ControlBuffers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn limites(&self) -> LimitesBuffer {
+        self.limites.read().map(|l| *l).unwrap_or_default()
+    }
+
+    pub fn set_limites(&self, limites: LimitesBuffer) {
+        if let Ok(mut actuales) = self.limites.write() {
+            *actuales = limites;
+        }
+    }
+
+    /// Cantidad de suscriptores desconectados por superar los limites
+    pub fn desconexiones(&self) -> u64 {
+        self.desconexiones.load(Ordering::Relaxed)
+    }
+
+    /// Reinicia el contador de desconexiones (CONFIG RESETSTAT)
+    pub fn reiniciar(&self) {
+        self.desconexiones.store(0, Ordering::Relaxed);
+    }
+
+    /// Encola un mensaje para un suscriptor, desconectandolo en lugar de
+    /// encolarlo si supera los limites de su buffer de salida
+    ///
+    /// # Retorna
+    /// - Resultado de la entrega, error si el hilo escritor del suscriptor
+    ///   ya no existe
+    pub fn entregar(
+        &self,
+        salida: &SalidaCliente,
+        mensaje: Vec<u8>,
+    ) -> Result<Entrega, SendError<Vec<u8>>> {
+        let buffer = salida.buffer();
+        if buffer.desconectado() {
+            return Ok(Entrega::Descartado);
+        }
+        if buffer.excede(&self.limites(), mensaje.len()) {
+            if !buffer.desconectar() {
+                return Ok(Entrega::Descartado);
+            }
+            self.desconexiones.fetch_add(1, Ordering::Relaxed);
+            return Ok(Entrega::Desconectado);
+        }
+        salida.enviar(mensaje)?;
+        Ok(Entrega::Encolado)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_01_parsear_limites() {
+        let limites = LimitesBuffer::parsear("PUBSUB 1mb 512kb 10").unwrap();
+        assert_eq!(
+            limites,
+            LimitesBuffer {
+                duro: 1024 * 1024,
+                blando: 512 * 1024,
+                segundos_blando: 10,
+            }
+        );
+        assert_eq!(limites.to_string(), "pubsub 1048576 524288 10");
+        assert_eq!(
+            LimitesBuffer::parsear(&LimitesBuffer::default().to_string()),
+            Ok(LimitesBuffer::default())
+        );
+        assert!(LimitesBuffer::parsear("normal 0 0 0").is_err());
+        assert!(LimitesBuffer::parsear("pubsub 1mb 1mb").is_err());
+        assert!(LimitesBuffer::parsear("pubsub 1mb 1mb -1").is_err());
+    }
+
+    #[test]
+    fn test_02_desconecta_al_superar_el_limite_duro() {
+        let control = ControlBuffers::new();
+        control.set_limites(LimitesBuffer {
+            duro: 10,
+            blando: 0,
+            segundos_blando: 0,
+        });
+        let (tx, rx) = channel();
+        let salida = SalidaCliente::from(tx);
+
+        assert_eq!(
+            control.entregar(&salida, "123456".into()),
+            Ok(Entrega::Encolado)
+        );
+        assert_eq!(salida.buffer().pendientes(), 6);
+        assert_eq!(
+            control.entregar(&salida, "123456".into()),
+            Ok(Entrega::Desconectado)
+        );
+        assert!(salida.buffer().desconectado());
+        assert_eq!(control.desconexiones(), 1);
+
+        // Un cliente desconectado no vuelve a contarse ni recibe mensajes
+        salida.buffer().liberar(6);
+        assert_eq!(
+            control.entregar(&salida, "1".into()),
+            Ok(Entrega::Descartado)
+        );
+        assert_eq!(control.desconexiones(), 1);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_03_limite_blando_tolera_excesos_temporales() {
+        let control = ControlBuffers::new();
+        control.set_limites(LimitesBuffer {
+            duro: 0,
+            blando: 4,
+            segundos_blando: 60,
+        });
+        let (tx, _rx) = channel();
+        let salida = SalidaCliente::from(tx);
+        assert_eq!(
+            control.entregar(&salida, "123456".into()),
+            Ok(Entrega::Encolado)
+        );
+        assert_eq!(
+            control.entregar(&salida, "123456".into()),
+            Ok(Entrega::Encolado)
+        );
+
+        control.set_limites(LimitesBuffer {
+            duro: 0,
+            blando: 4,
+            segundos_blando: 0,
+        });
+        assert_eq!(
+            control.entregar(&salida, "1".into()),
+            Ok(Entrega::Desconectado)
+        );
+        assert_eq!(control.desconexiones(), 1);
+    }
+}
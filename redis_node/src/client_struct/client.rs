@@ -1,4 +1,5 @@
 //! Este modulo contiene a la estructura cliente
+use crate::client_struct::buffer_salida::{BufferSalida, SalidaCliente};
 use crate::comandos::pub_sub_struct::Canal;
 use crate::log_msj::log_mensajes::{log_writer_error, log_writer_response_send};
 use crate::node_role::NodeRole;
@@ -7,12 +8,18 @@ use logger::logger::Logger;
 use redis_client::protocol::protocol_resp::resp_server_command_write;
 use redis_client::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
 use std::collections::HashSet;
-use std::net::TcpStream;
-use std::sync::mpsc::{Receiver, Sender};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, RwLock, mpsc};
 use std::thread::spawn;
+use std::time::Instant;
 // pub type Clients = HashMap<String, Arc<RwLock<Client>>>;
 
+/// Proximo identificador numerico a asignar a un cliente. Los ids son
+/// crecientes y no se reutilizan durante la vida del nodo.
+static SIGUIENTE_NUMERO: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Debug)]
 pub struct Client {
     id: String,
@@ -20,9 +27,16 @@ pub struct Client {
     channels: HashSet<Canal>,
     pchannels: HashSet<Canal>,
     schannels: HashSet<Canal>,
-    sender: Sender<String>,
+    salida: SalidaCliente,
     type_of_node_connections: Arc<RwLock<NodeRole>>,
     handshake: bool,
+    numero: u64,
+    nombre: Option<String>,
+    usuario: Option<String>,
+    creado: Instant,
+    ultima_interaccion: Instant,
+    ultimo_comando: Option<String>,
+    monitor: bool,
 }
 
 impl Client {
@@ -43,10 +57,16 @@ impl Client {
         node_role: Arc<RwLock<NodeRole>>,
     ) -> Client {
         let (tx_sender, rx_receiver) = mpsc::channel();
+        let buffer = Arc::new(BufferSalida::new(stream.try_clone().ok()));
 
         match intentar_clonar_stream(&stream, &logger) {
             Ok(stream_writer) => {
-                spawn_hilo_escritor(rx_receiver, logger.clone(), stream_writer);
+                spawn_hilo_escritor(
+                    rx_receiver,
+                    logger.clone(),
+                    stream_writer,
+                    Arc::clone(&buffer),
+                );
             }
             Err(_) => {
                 log_writer_error(&logger, "No se pudo clonar el stream del cliente");
@@ -60,18 +80,31 @@ impl Client {
             channels: HashSet::new(),
             pchannels: HashSet::new(),
             schannels: HashSet::new(),
-            sender: tx_sender,
+            salida: SalidaCliente::new(tx_sender, buffer),
             type_of_node_connections: node_role,
             handshake: false,
+            numero: SIGUIENTE_NUMERO.fetch_add(1, Ordering::Relaxed),
+            nombre: None,
+            usuario: None,
+            creado: Instant::now(),
+            ultima_interaccion: Instant::now(),
+            ultimo_comando: None,
+            monitor: false,
         }
     }
 
-    /// Obtiene el sender del cliente
+    /// Obtiene la salida del cliente, por la que se le encolan mensajes
     ///
     /// # Retorna
-    /// - Sender del cliente
-    pub fn get_sender(&mut self) -> Sender<String> {
-        self.sender.clone()
+    /// - Salida del cliente
+    pub fn get_salida(&self) -> SalidaCliente {
+        self.salida.clone()
+    }
+
+    /// Cantidad de bytes encolados para el cliente que todavia no se
+    /// enviaron
+    pub fn get_buffer_salida(&self) -> usize {
+        self.salida.buffer().pendientes()
     }
 
     /// Envia un mensaje al sender
@@ -81,9 +114,9 @@ impl Client {
     ///
     /// # Retorna
     /// - Sender del cliente
-    pub fn send_sender(&self, mensaje: String) -> Result<(), DatoRedis> {
-        self.sender
-            .send(mensaje)
+    pub fn send_sender(&self, mensaje: Vec<u8>) -> Result<(), DatoRedis> {
+        self.salida
+            .enviar(mensaje)
             .map_err(|e| DatoRedis::new_simple_error("Err".to_string(), format!("{e}")))?;
         Ok(())
     }
@@ -114,13 +147,13 @@ impl Client {
     ///
     /// # Retorna
     /// - canales del cliente
-    pub fn get_channels(&self) -> &HashSet<String> {
+    pub fn get_channels(&self) -> &HashSet<Canal> {
         &self.channels
     }
-    pub fn get_pchannels(&self) -> &HashSet<String> {
+    pub fn get_pchannels(&self) -> &HashSet<Canal> {
         &self.pchannels
     }
-    pub fn get_schannels(&self) -> &HashSet<String> {
+    pub fn get_schannels(&self) -> &HashSet<Canal> {
         &self.schannels
     }
 
@@ -174,6 +207,65 @@ impl Client {
     pub fn get_type_of_node_connections(&self) -> Arc<RwLock<NodeRole>> {
         self.type_of_node_connections.clone()
     }
+
+    /// Obtiene el identificador numerico unico del cliente (CLIENT ID)
+    pub fn get_numero(&self) -> u64 {
+        self.numero
+    }
+
+    pub fn get_nombre(&self) -> Option<&String> {
+        self.nombre.as_ref()
+    }
+
+    pub fn set_nombre(&mut self, nombre: Option<String>) {
+        self.nombre = nombre;
+    }
+
+    pub fn get_usuario(&self) -> Option<&String> {
+        self.usuario.as_ref()
+    }
+
+    pub fn set_usuario(&mut self, usuario: String) {
+        self.usuario = Some(usuario);
+    }
+
+    /// Indica si el cliente recibe el flujo de comandos de MONITOR
+    pub fn get_monitor(&self) -> bool {
+        self.monitor
+    }
+
+    pub fn set_monitor(&mut self, monitor: bool) {
+        self.monitor = monitor;
+    }
+
+    /// Registra el comando que el cliente se encuentra ejecutando
+    ///
+    /// # Parametros
+    /// * `comando`: nombre del comando en minusculas
+    pub fn registrar_comando(&mut self, comando: String) {
+        self.ultimo_comando = Some(comando);
+        self.ultima_interaccion = Instant::now();
+    }
+
+    pub fn get_ultimo_comando(&self) -> Option<&String> {
+        self.ultimo_comando.as_ref()
+    }
+
+    /// Segundos transcurridos desde la conexion del cliente
+    pub fn segundos_conectado(&self) -> u64 {
+        self.creado.elapsed().as_secs()
+    }
+
+    /// Segundos transcurridos desde el ultimo comando del cliente
+    pub fn segundos_inactivo(&self) -> u64 {
+        self.ultima_interaccion.elapsed().as_secs()
+    }
+
+    /// Cierra la conexion del cliente. El hilo lector del nodo detecta el
+    /// cierre y libera los recursos del cliente
+    pub fn cerrar_conexion(&self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
 }
 
 /// Intenta clonar un stream
@@ -197,15 +289,23 @@ fn intentar_clonar_stream(stream: &TcpStream, logger: &Logger) -> Result<TcpStre
 /// * `rx_receiver`: donde se recibiran las respuestas a escribir
 /// * `logger`: logger donde escribir en caso de ser necesario
 /// * `stream_writer`: stream donde escribir
+/// * `buffer`: buffer de salida del que se descuentan las respuestas escritas
 fn spawn_hilo_escritor(
-    rx_receiver: Receiver<String>,
+    rx_receiver: Receiver<Vec<u8>>,
     logger: Logger,
     mut stream_writer: TcpStream,
+    buffer: Arc<BufferSalida>,
 ) {
     spawn(move || {
         while let Ok(respuesta) = rx_receiver.recv() {
-            log_writer_response_send(&logger, &respuesta, stream_writer.peer_addr().ok());
-            if let Err(result) = resp_server_command_write(&respuesta, &mut stream_writer) {
+            log_writer_response_send(
+                &logger,
+                &String::from_utf8_lossy(&respuesta),
+                stream_writer.peer_addr().ok(),
+            );
+            let escrito = resp_server_command_write(&respuesta, &mut stream_writer);
+            buffer.liberar(respuesta.len());
+            if let Err(result) = escrito {
                 log_writer_error(&logger, &result.convertir_resp_a_string());
                 break;
             }
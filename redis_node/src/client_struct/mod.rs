@@ -1 +1,3 @@
+pub mod buffer_salida;
 pub mod client;
+pub mod registro_clientes;
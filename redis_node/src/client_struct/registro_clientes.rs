@@ -0,0 +1,157 @@
+//! Este modulo contiene el registro de los clientes conectados al nodo,
+//! utilizado por la familia de comandos CLIENT
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::client_struct::client::Client;
+
+/// Intervalo con el que un cliente pausado vuelve a verificar la pausa
+const INTERVALO_PAUSA: Duration = Duration::from_millis(10);
+
+/// Pausa de clientes activada con CLIENT PAUSE
+#[derive(Debug, Clone, Copy)]
+struct Pausa {
+    hasta: Instant,
+    solo_escritura: bool,
+}
+
+/// Registro de los clientes conectados al nodo, indexados por su id numerico
+#[derive(Debug, Default)]
+pub struct RegistroClientes {
+    clientes: RwLock<HashMap<u64, Arc<RwLock<Client>>>>,
+    monitores: RwLock<Vec<Arc<RwLock<Client>>>>,
+    pausa: RwLock<Option<Pausa>>,
+}
+
+impl RegistroClientes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Agrega un cliente recien conectado al registro
+    pub fn registrar(&self, client: Arc<RwLock<Client>>) {
+        let numero = match client.read() {
+            Ok(cli) => cli.get_numero(),
+            Err(_) => return,
+        };
+        if let Ok(mut clientes) = self.clientes.write() {
+            clientes.insert(numero, client);
+        }
+    }
+
+    /// Quita un cliente desconectado del registro
+    pub fn eliminar(&self, numero: u64) {
+        if let Ok(mut clientes) = self.clientes.write() {
+            clientes.remove(&numero);
+        }
+        self.quitar_monitor(numero);
+    }
+
+    /// Quita a un cliente de los que reciben el flujo de MONITOR
+    pub fn quitar_monitor(&self, numero: u64) {
+        if let Ok(mut monitores) = self.monitores.write() {
+            monitores.retain(|monitor| {
+                monitor
+                    .read()
+                    .map(|cli| cli.get_numero() != numero)
+                    .unwrap_or(false)
+            });
+        }
+    }
+
+    /// Obtiene un cliente conectado por su id numerico
+    pub fn obtener(&self, numero: u64) -> Option<Arc<RwLock<Client>>> {
+        self.clientes
+            .read()
+            .ok()
+            .and_then(|clientes| clientes.get(&numero).cloned())
+    }
+
+    /// Agrega un cliente a los que reciben el flujo de MONITOR
+    pub fn agregar_monitor(&self, client: Arc<RwLock<Client>>) {
+        if let Ok(mut monitores) = self.monitores.write() {
+            if monitores
+                .iter()
+                .any(|monitor| Arc::ptr_eq(monitor, &client))
+            {
+                return;
+            }
+            monitores.push(client);
+        }
+    }
+
+    /// Obtiene los clientes en modo MONITOR
+    pub fn monitores(&self) -> Vec<Arc<RwLock<Client>>> {
+        self.monitores
+            .read()
+            .map(|monitores| monitores.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn hay_monitores(&self) -> bool {
+        self.monitores
+            .read()
+            .map(|m| !m.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Obtiene los clientes conectados, ordenados por id
+    pub fn clientes(&self) -> Vec<Arc<RwLock<Client>>> {
+        let Ok(clientes) = self.clientes.read() else {
+            return Vec::new();
+        };
+        let mut ordenados: Vec<(u64, Arc<RwLock<Client>>)> = clientes
+            .iter()
+            .map(|(numero, client)| (*numero, client.clone()))
+            .collect();
+        ordenados.sort_by_key(|(numero, _)| *numero);
+        ordenados.into_iter().map(|(_, client)| client).collect()
+    }
+
+    /// Pausa a los clientes durante `duracion`
+    ///
+    /// # Parametros
+    /// * `duracion`: tiempo que dura la pausa
+    /// * `solo_escritura`: si es verdadero solo se pausan los comandos que
+    ///   modifican el storage
+    pub fn pausar(&self, duracion: Duration, solo_escritura: bool) {
+        if let Ok(mut pausa) = self.pausa.write() {
+            *pausa = Some(Pausa {
+                hasta: Instant::now() + duracion,
+                solo_escritura,
+            });
+        }
+    }
+
+    /// Finaliza la pausa de clientes, si la hubiera
+    pub fn reanudar(&self) {
+        if let Ok(mut pausa) = self.pausa.write() {
+            *pausa = None;
+        }
+    }
+
+    /// Indica si un comando debe esperar por una pausa activa
+    ///
+    /// # Parametros
+    /// * `es_escritura`: si el comando modifica el storage
+    pub fn pausado(&self, es_escritura: bool) -> bool {
+        match self.pausa.read() {
+            Ok(pausa) => pausa
+                .is_some_and(|p| p.hasta > Instant::now() && (es_escritura || !p.solo_escritura)),
+            Err(_) => false,
+        }
+    }
+
+    /// Bloquea el hilo del cliente mientras haya una pausa que aplique a su
+    /// comando
+    ///
+    /// # Parametros
+    /// * `es_escritura`: si el comando modifica el storage
+    pub fn esperar_pausa(&self, es_escritura: bool) {
+        while self.pausado(es_escritura) {
+            sleep(INTERVALO_PAUSA);
+        }
+    }
+}
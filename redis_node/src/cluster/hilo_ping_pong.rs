@@ -48,7 +48,7 @@ impl Node {
     ) {
         spawn(move || {
             loop {
-                sleep(Duration::from_millis(self.node_timeout / 10));
+                sleep(Duration::from_millis(self.get_node_timeout() / 10));
                 if let Err(e) = self.iniciar_ping_periodico(&outgoing_streams) {
                     self.logger.error(
                         &format!("Error en hilo de ping: {}", e.description),
@@ -115,6 +115,9 @@ impl Node {
         // Enviar pings a nodos que pasaron NODETIMEOUT / 2 sin ping
         self.enviar_pings_timeout(&ping, outgoing_streams, &ya_pingueados)?;
 
+        // Refrescar el resumen de suscripciones pub/sub en el resto del clúster
+        self.enviar_resumen_pubsub(outgoing_streams)?;
+
         Ok(())
     }
 
@@ -201,7 +204,9 @@ impl Node {
                     if ya_pingueados.contains(node_id) {
                         return None;
                     }
-                    if info.get_ping_sent_time() > Duration::from_millis(self.node_timeout / 2) {
+                    if info.get_ping_sent_time()
+                        > Duration::from_millis(self.get_node_timeout() / 2)
+                    {
                         Some(node_id.clone())
                     } else {
                         None
@@ -290,7 +295,7 @@ impl Node {
         };
 
         for (node_id, vecino) in info_guard.iter_mut() {
-            if vecino.is_suspected_failed(Duration::from_millis(self.node_timeout))
+            if vecino.is_suspected_failed(Duration::from_millis(self.get_node_timeout()))
                 && !marcados.contains(node_id)
             {
                 marcados.insert(node_id.clone());
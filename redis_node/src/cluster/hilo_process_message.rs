@@ -18,6 +18,7 @@ use crate::internal_protocol::moved::Moved;
 use crate::internal_protocol::moved_shard_pubsub::MovedShardPubSub;
 use crate::internal_protocol::node_flags::{ClusterState, NodeFlags};
 use crate::internal_protocol::redis_cmd::RedisCMD;
+use crate::latencia::EVENTO_CLUSTER;
 use crate::node::Node;
 use crate::node_id::NodeId;
 use crate::node_role::NodeRole;
@@ -26,6 +27,7 @@ use redis_client::tipos_datos::traits::DatoRedis;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::net::TcpStream;
+use std::ops::Range;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::{self, Receiver};
@@ -64,6 +66,7 @@ impl Node {
         spawn(move || {
             let mut estructuras_failover = EstructurasFailover::new();
             while let Ok(msj) = rx_connect.recv() {
+                let inicio = Instant::now();
                 let result_msj = match msj {
                     TipoMensajeNode::ClusterNode(msj) => self.clone().procesar_mensaje_out_node(
                         msj,
@@ -76,6 +79,7 @@ impl Node {
                         self.procesar_mensaje_inner_node(msj, outgoing_streams.clone())
                     }
                 };
+                self.latencia.registrar(EVENTO_CLUSTER, inicio.elapsed());
                 if let Err(e) = result_msj {
                     self.loggear_cluster_error(e);
                     self.marcar_node_fail("CLUSTER");
@@ -253,6 +257,7 @@ impl Node {
     /// - `RedisCommand`: Ejecuta un comando Redis recibido de otro nodo.
     /// - `Fail`: Marca a un nodo como fallado y propaga el estado.
     /// - `PubSub`: Ejecuta una operación de publicación o suscripción.
+    /// - `PubSubSummary`: Registra el resumen de suscripciones del emisor.
     /// - `FailNegotiation`: Recibe un offset de replicación en contexto de failover.
     /// - `FailAuthReq`: Evalúa una solicitud de voto para promover a un nuevo maestro.
     /// - `FailAuthAck`: Registra el voto recibido de otro nodo.
@@ -296,6 +301,12 @@ impl Node {
                 self.ejecutar_pubsub(cmd);
                 Ok(())
             }
+            ClusterMessagePayload::PubSubSummary(resumen) => {
+                self.pub_sub
+                    .rutas()
+                    .registrar_remoto(msj.header().node_id(), resumen);
+                Ok(())
+            }
             ClusterMessagePayload::FailNegotiation(failover_auth_req) => self.recibir_rep_offset(
                 msj.header().master_id(),
                 failover_auth_req,
@@ -327,7 +338,8 @@ impl Node {
     /// Este tipo de mensaje no viene de otro nodo sino que se genera como parte de una acción local
     /// que debe reflejarse en otros nodos (por ejemplo, replicación o publicación).
     ///
-    /// - `PubSub`: Reenvía el mensaje Pub/Sub a los demás nodos.
+    /// - `PubSub`: Reenvía el mensaje Pub/Sub a los nodos que pueden tener suscriptores.
+    /// - `ResumenPubSub`: Envía a los demás nodos el nuevo resumen de suscripciones.
     /// - `RedisCommand`: Replica el comando Redis a los nodos configurados como réplicas.
     /// - `Moved`: Maneja una redirección de cliente a otro nodo (cuando se detecta que una clave no pertenece al nodo actual).
     ///
@@ -349,6 +361,7 @@ impl Node {
             }
             InnerMensajeNode::Moved(moved) => self.redireccion_moved_hander(moved),
             InnerMensajeNode::MovedShard(resp) => self.redireccion_moved_pubsub_hander(resp),
+            InnerMensajeNode::ResumenPubSub => self.enviar_resumen_pubsub(&outgoing_streams),
         }
     }
 
@@ -367,10 +380,13 @@ impl Node {
         self.pub_sub.send_cmd(comando_pubsub);
     }
 
-    /// Propaga un comando Pub/Sub a todos los nodos del clúster.
+    /// Propaga un comando Pub/Sub a los nodos del clúster que pueden tener
+    /// suscriptores.
     ///
-    /// Crea un `ClusterMessage` de tipo `PubSub` y lo envía a través de todos
-    /// los streams salientes registrados en `outgoing_streams`.
+    /// Crea un `ClusterMessage` de tipo `PubSub` y lo envía a través de los
+    /// streams salientes de los nodos seleccionados por las rutas de pub/sub:
+    /// los que según su resumen pueden tener suscriptores del canal (PUBLISH)
+    /// o los que atienden el slot del canal (SPUBLISH).
     ///
     /// # Parámetros
     /// - `cmd`: Comando Pub/Sub (`RedisCMD`) a propagar.
@@ -397,8 +413,17 @@ impl Node {
             }
         };
 
+        let tokens = cmd.get_command();
         let msg = ClusterMessage::new(header, ClusterMessagePayload::PubSub(cmd));
 
+        let slots: HashMap<NodeId, Range<u16>> = self
+            .knows_nodes
+            .read()
+            .map_err(|_| ClusterError::new_lock_error("nodos conocidos", "PUBSUB CLUSTER"))?
+            .iter()
+            .map(|(id, nodo)| (id.clone(), nodo.get_slot_range()))
+            .collect();
+
         let mut streams = match outgoing_streams.write() {
             Ok(s) => s,
             Err(_) => {
@@ -409,7 +434,15 @@ impl Node {
             }
         };
 
-        for (node_id, stream) in streams.iter_mut() {
+        let candidatos = streams
+            .keys()
+            .map(|id| (id.clone(), slots.get(id).cloned()))
+            .collect();
+
+        for node_id in self.pub_sub.rutas().destinos(&tokens, candidatos) {
+            let Some(stream) = streams.get_mut(&node_id) else {
+                continue;
+            };
             if let Err(e) = send_cluster_message(stream, &msg) {
                 self.logger.warn(
                     &format!(
@@ -425,6 +458,44 @@ impl Node {
         Ok(())
     }
 
+    /// Envía el resumen de suscripciones pub/sub del nodo a todos los nodos
+    /// del clúster, si cambió desde el último envío o corresponde refrescarlo.
+    ///
+    /// # Parámetros
+    /// - `outgoing_streams`: Mapa compartido de conexiones salientes (`NodeId → TcpStream`).
+    ///
+    /// # Retorna
+    /// - `Ok(())` si no hubo que enviarlo o se envió.
+    /// - `Err(ClusterError)` si hubo un error al adquirir el lock de streams.
+    pub(crate) fn enviar_resumen_pubsub(
+        &self,
+        outgoing_streams: &MapaStreams,
+    ) -> Result<(), ClusterError> {
+        let Some(resumen) = self.pub_sub.rutas().resumen_a_enviar() else {
+            return Ok(());
+        };
+        let Some(header) = self.message_header_node(InternalProtocolType::PubSubSummary) else {
+            return Ok(());
+        };
+        let msg = ClusterMessage::new(header, ClusterMessagePayload::PubSubSummary(resumen));
+
+        let mut streams = outgoing_streams
+            .write()
+            .map_err(|_| ClusterError::new_lock_error("streams salientes", "PUBSUB CLUSTER"))?;
+        for (node_id, stream) in streams.iter_mut() {
+            if send_cluster_message(stream, &msg).is_err() {
+                self.logger.warn(
+                    &format!(
+                        "No se pudo enviar el resumen pubsub a nodo {}",
+                        node_id.get_id()
+                    ),
+                    "PUBSUB CLUSTER",
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Propaga un comando Redis a las réplicas de este nodo.
     ///
     /// Este método se utiliza para replicar operaciones que modifican el estado
@@ -496,7 +567,7 @@ impl Node {
     /// - El offset de replicación permite sincronizar el estado con las réplicas.
     fn ejecutar_redis_cmd(&self, comando_redis: RedisCMD, aof_arc: Option<Arc<RwLock<File>>>) {
         let comando = comando_redis.get_command();
-        self.ejecutar_comando_general_replica(&comando[0], &comando, &aof_arc);
+        self.ejecutar_comando_general_replica(&comando[0].texto(), &comando, &aof_arc);
         self.actualizar_replication_offset(&comando[0].to_uppercase());
     }
 
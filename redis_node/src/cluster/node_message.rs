@@ -23,4 +23,7 @@ pub enum InnerMensajeNode {
 
     /// Payload para redireccion en Shard PubSub
     MovedShard(MovedShardPubSub),
+
+    /// Aviso de que cambió el resumen de suscripciones pub/sub del nodo
+    ResumenPubSub,
 }
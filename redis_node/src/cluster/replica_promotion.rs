@@ -109,7 +109,7 @@ impl Node {
         }
 
         let resultado_master =
-            receiver_nuevo_master.recv_timeout(Duration::from_millis(self.node_timeout * 4));
+            receiver_nuevo_master.recv_timeout(Duration::from_millis(self.get_node_timeout() * 4));
         if self.debe_reiniciar_promocion(resultado_master, &id_master)? {
             self.clone().efectuar_replica_promotion(
                 id_master,
@@ -275,7 +275,7 @@ impl Node {
         let mut replication_offsets = HashSet::new();
         let replication_offset = self.replication_offset.load(Ordering::SeqCst) as u32;
         for _ in 0..replicas {
-            match rx.recv_timeout(Duration::from_millis(self.node_timeout)) {
+            match rx.recv_timeout(Duration::from_millis(self.get_node_timeout())) {
                 Ok(other_rep_offset) => {
                     let other = other_rep_offset.get_offset();
                     if other > replication_offset {
@@ -374,17 +374,17 @@ impl Node {
             .read()
             .map_err(|_| ClusterError::new_lock_error("nodos conocidos", "FAILOVER"))?;
         if let Some(master_fail) = master_id {
-            if let Some(master) = conocidos.get(&master_fail) {
-                if !master.get_flags().is_fail() {
-                    return Ok(());
-                }
+            if let Some(master) = conocidos.get(&master_fail)
+                && !master.get_flags().is_fail()
+            {
+                return Ok(());
             }
             let nuevo_voto = LastVote::new(node_id.clone(), Instant::now(), current_epoch);
             let ultimo_voto = votos.get(&master_fail);
             if nuevo_voto.es_pedido_valido(
                 self.current_epoch.load(Ordering::SeqCst),
                 ultimo_voto,
-                self.node_timeout,
+                self.get_node_timeout(),
             ) {
                 self.current_epoch
                     .store(nuevo_voto.current_epoch, Ordering::SeqCst);
@@ -464,7 +464,9 @@ impl Node {
     pub fn gana_votacion(&self, receiver_votos: &Receiver<NodeId>) -> bool {
         let mut votantes = HashSet::new();
         for _ in 0..(CANT_INICIAL_MASTERS - 1) {
-            if let Ok(id) = receiver_votos.recv_timeout(Duration::from_millis(self.node_timeout)) {
+            if let Ok(id) =
+                receiver_votos.recv_timeout(Duration::from_millis(self.get_node_timeout()))
+            {
                 votantes.insert(id);
             } else {
                 break;
@@ -588,15 +590,15 @@ impl Node {
     /// # Retorna
     /// - Verdadero si el mensaje se propaga con éxito, falso en otro caso
     fn propagar_rol(&self, outgoing_streams: &Arc<RwLock<HashMap<NodeId, TcpStream>>>) -> bool {
-        if let Some(mensaje) = self.armar_mensaje_update() {
-            if let Ok(mut streams) = outgoing_streams.write() {
-                for (_, stream) in streams.iter_mut() {
-                    if send_cluster_message(stream, &mensaje).is_err() {
-                        return false;
-                    }
+        if let Some(mensaje) = self.armar_mensaje_update()
+            && let Ok(mut streams) = outgoing_streams.write()
+        {
+            for (_, stream) in streams.iter_mut() {
+                if send_cluster_message(stream, &mensaje).is_err() {
+                    return false;
                 }
-                return true;
             }
+            return true;
         }
         false
     }
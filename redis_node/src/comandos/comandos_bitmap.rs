@@ -0,0 +1,841 @@
+//! Este modulo contiene la implementacion de los comandos de bitmaps y
+//! bitfields de redis. Operan sobre los bytes de un string, tomando el bit
+//! mas significativo de cada byte como el de menor offset
+use std::sync::{Arc, RwLock};
+
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+};
+use crate::storage::Storage;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use super::utils::{get_storage_read_lock, get_storage_write_lock};
+
+const OPCION_BYTE: &str = "BYTE";
+const OPCION_BIT: &str = "BIT";
+const OPERACION_AND: &str = "AND";
+const OPERACION_OR: &str = "OR";
+const OPERACION_XOR: &str = "XOR";
+const OPERACION_NOT: &str = "NOT";
+const SUBCOMANDO_GET: &str = "GET";
+const SUBCOMANDO_SET: &str = "SET";
+const SUBCOMANDO_INCRBY: &str = "INCRBY";
+const SUBCOMANDO_OVERFLOW: &str = "OVERFLOW";
+/// Offset maximo de un bit, correspondiente a un string de 512MB
+const OFFSET_MAXIMO: u64 = 512 * 1024 * 1024 * 8 - 1;
+
+/// Unidad en la que se expresan los rangos de BITCOUNT y BITPOS
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unidad {
+    Byte,
+    Bit,
+}
+
+/// Comportamiento de BITFIELD cuando un valor no entra en el tipo
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Overflow {
+    Wrap,
+    Sat,
+    Fail,
+}
+
+/// Tipo entero de un campo de BITFIELD
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TipoCampo {
+    con_signo: bool,
+    bits: u32,
+}
+
+/// Operacion de BITFIELD sobre un campo
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OperacionCampo {
+    Get,
+    Set(i64),
+    Incrby(i64),
+}
+
+/// Asigna un bit de un string, que se extiende con ceros de ser necesario
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, offset del
+///   bit y valor (0 o 1)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor previo del bit en caso de exito, error de redis en otro caso
+pub fn setbit(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let offset = parsear_offset(&tokens[2].texto())? as usize;
+    let valor = match tokens[3].bytes() {
+        b"0" => false,
+        b"1" => true,
+        _ => {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "bit is not an integer or out of range".to_string(),
+            ));
+        }
+    };
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let mut bytes = leer_bytes(&guard, &tokens[1])?.unwrap_or_default();
+    let previo = leer_bit(&bytes, offset);
+    escribir_bit(&mut bytes, offset, valor);
+    guard.set(&tokens[1], DatoRedis::new_bulk_string_desde_bytes(bytes)?)?;
+    Ok(DatoRedis::new_integer(previo as i64))
+}
+
+/// Obtiene un bit de un string. Los bits fuera del string valen 0
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y offset
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor del bit en caso de exito, error de redis en otro caso
+pub fn getbit(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let offset = parsear_offset(&tokens[2].texto())? as usize;
+    let guard = get_storage_read_lock(storage)?;
+
+    let bytes = leer_bytes(&guard, &tokens[1])?.unwrap_or_default();
+    Ok(DatoRedis::new_integer(leer_bit(&bytes, offset) as i64))
+}
+
+/// Cuenta los bits en 1 de un string, opcionalmente dentro de un rango
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y opcionalmente
+///   inicio, fin y unidad del rango (BYTE o BIT)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Cantidad de bits en 1 en caso de exito, error de redis en otro caso
+pub fn bitcount(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    if tokens.len() == 3 || tokens.len() > 5 {
+        return Err(error_sintaxis());
+    }
+    let rango = match tokens.get(2..4) {
+        Some(limites) => Some((
+            parsear_entero(&limites[0].texto())?,
+            parsear_entero(&limites[1].texto())?,
+            parsear_unidad(tokens.get(4))?,
+        )),
+        None => None,
+    };
+    let guard = get_storage_read_lock(storage)?;
+
+    let Some(bytes) = leer_bytes(&guard, &tokens[1])? else {
+        return Ok(DatoRedis::new_integer(0));
+    };
+    let (inicio, fin, unidad) = rango.unwrap_or((0, -1, Unidad::Byte));
+    let cantidad = match rango_de_bits(inicio, fin, unidad, bytes.len()) {
+        Some((desde, hasta)) => contar_bits(&bytes, desde, hasta),
+        None => 0,
+    };
+    Ok(DatoRedis::new_integer(cantidad as i64))
+}
+
+/// Busca el primer bit con el valor indicado en un string, opcionalmente
+/// dentro de un rango
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, bit buscado y
+///   opcionalmente inicio, fin y unidad del rango (BYTE o BIT)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Posicion del bit, o -1 si no se encuentra, en caso de exito. Al
+///   buscar un 0 sin indicar el fin del rango, el string se considera
+///   seguido de ceros. Error de redis en otro caso
+pub fn bitpos(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    if tokens.len() > 6 {
+        return Err(error_sintaxis());
+    }
+    let buscado = match tokens[2].bytes() {
+        b"0" => false,
+        b"1" => true,
+        _ => {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "The bit argument must be 1 or 0.".to_string(),
+            ));
+        }
+    };
+    let inicio = tokens
+        .get(3)
+        .map(|t| parsear_entero(&t.texto()))
+        .transpose()?;
+    let fin = tokens
+        .get(4)
+        .map(|t| parsear_entero(&t.texto()))
+        .transpose()?;
+    let unidad = parsear_unidad(tokens.get(5))?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let Some(bytes) = leer_bytes(&guard, &tokens[1])? else {
+        return Ok(DatoRedis::new_integer(if buscado { -1 } else { 0 }));
+    };
+    let Some((desde, hasta)) =
+        rango_de_bits(inicio.unwrap_or(0), fin.unwrap_or(-1), unidad, bytes.len())
+    else {
+        return Ok(DatoRedis::new_integer(-1));
+    };
+    let posicion = match (desde..=hasta).find(|&bit| leer_bit(&bytes, bit) == buscado) {
+        Some(posicion) => posicion as i64,
+        None if !buscado && fin.is_none() => hasta as i64 + 1,
+        None => -1,
+    };
+    Ok(DatoRedis::new_integer(posicion))
+}
+
+/// Aplica una operacion bit a bit entre strings y guarda el resultado en
+/// una clave destino. Los strings mas cortos se completan con ceros
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, operacion (AND, OR,
+///   XOR o NOT), clave destino y claves de origen
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Largo del string resultante en caso de exito, error de redis en otro
+///   caso. Si el resultado es vacio se elimina la clave destino
+pub fn bitop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let operacion = tokens[1].to_uppercase();
+    let destino = &tokens[2];
+    let origenes = &tokens[3..];
+    match operacion.as_str() {
+        OPERACION_AND | OPERACION_OR | OPERACION_XOR => {}
+        OPERACION_NOT if origenes.len() == 1 => {}
+        OPERACION_NOT => {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "BITOP NOT must be called with a single source key.".to_string(),
+            ));
+        }
+        _ => return Err(error_sintaxis()),
+    }
+    let mut guard = get_storage_write_lock(storage)?;
+    for origen in origenes {
+        verificar_mismo_slot(&guard, destino, origen)?;
+    }
+
+    let mut valores = Vec::with_capacity(origenes.len());
+    for origen in origenes {
+        valores.push(leer_bytes(&guard, origen)?.unwrap_or_default());
+    }
+    let largo = valores.iter().map(Vec::len).max().unwrap_or(0);
+    let byte = |valor: &Vec<u8>, i: usize| valor.get(i).copied().unwrap_or(0);
+    let resultado: Vec<u8> = (0..largo)
+        .map(|i| {
+            let mut bytes = valores.iter().map(|valor| byte(valor, i));
+            let primero = bytes.next().unwrap_or(0);
+            match operacion.as_str() {
+                OPERACION_AND => bytes.fold(primero, |acumulado, b| acumulado & b),
+                OPERACION_OR => bytes.fold(primero, |acumulado, b| acumulado | b),
+                OPERACION_XOR => bytes.fold(primero, |acumulado, b| acumulado ^ b),
+                _ => !primero,
+            }
+        })
+        .collect();
+
+    if resultado.is_empty() {
+        match guard.remove(destino) {
+            Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+            _ => return Ok(DatoRedis::new_integer(0)),
+        }
+    }
+    guard.set(destino, DatoRedis::new_bulk_string_desde_bytes(resultado)?)?;
+    guard.set_expiracion(destino, None)?;
+    Ok(DatoRedis::new_integer(largo as i64))
+}
+
+/// Lee y modifica enteros de ancho arbitrario dentro de un string
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y una
+///   secuencia de subcomandos GET, SET, INCRBY y OVERFLOW
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Arreglo con el resultado de cada GET, SET e INCRBY (null si el
+///   overflow es FAIL y el valor no entra en el campo) en caso de exito,
+///   error de redis en otro caso
+pub fn bitfield(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let operaciones = parsear_operaciones_bitfield(&tokens[2..])?;
+    let escribe = operaciones
+        .iter()
+        .any(|(_, _, _, operacion)| *operacion != OperacionCampo::Get);
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let mut bytes = leer_bytes(&guard, &tokens[1])?.unwrap_or_default();
+    let mut resultados = Arrays::new();
+    for (tipo, offset, overflow, operacion) in operaciones {
+        let actual = leer_campo(&bytes, offset, tipo);
+        let nuevo = match operacion {
+            OperacionCampo::Get => {
+                resultados.append(DatoRedis::new_integer(actual as i64));
+                continue;
+            }
+            OperacionCampo::Set(valor) => ajustar_campo(valor as i128, tipo, overflow),
+            OperacionCampo::Incrby(incremento) => {
+                ajustar_campo(actual + incremento as i128, tipo, overflow)
+            }
+        };
+        let fin = (offset + tipo.bits as usize).div_ceil(8);
+        if bytes.len() < fin {
+            bytes.resize(fin, 0);
+        }
+        let Some(nuevo) = nuevo else {
+            resultados.append(DatoRedis::new_null());
+            continue;
+        };
+        escribir_campo(&mut bytes, offset, tipo, nuevo);
+        let respuesta = match operacion {
+            OperacionCampo::Set(_) => actual,
+            _ => nuevo,
+        };
+        resultados.append(DatoRedis::new_integer(respuesta as i64));
+    }
+
+    if escribe {
+        guard.set(&tokens[1], DatoRedis::new_bulk_string_desde_bytes(bytes)?)?;
+    }
+    Ok(DatoRedis::new_array_con_contenido(resultados))
+}
+
+/// Obtiene los bytes del string de una clave
+///
+/// # Retorna
+/// - None si la clave no existe, error de redis si no almacena un string
+///   o pertenece a otro nodo
+fn leer_bytes(storage: &Storage, clave: &[u8]) -> Result<Option<Vec<u8>>, DatoRedis> {
+    match storage.get(clave) {
+        Ok(DatoRedis::BulkString(valor)) => Ok(Some(valor.into_bytes())),
+        Ok(_) => Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => Err(DatoRedis::MovedError(e)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn leer_bit(bytes: &[u8], offset: usize) -> bool {
+    bytes
+        .get(offset / 8)
+        .is_some_and(|byte| byte & (0x80 >> (offset % 8)) != 0)
+}
+
+fn escribir_bit(bytes: &mut Vec<u8>, offset: usize, valor: bool) {
+    let indice = offset / 8;
+    if bytes.len() <= indice {
+        bytes.resize(indice + 1, 0);
+    }
+    let mascara = 0x80 >> (offset % 8);
+    if valor {
+        bytes[indice] |= mascara;
+    } else {
+        bytes[indice] &= !mascara;
+    }
+}
+
+/// Cuenta los bits en 1 entre dos posiciones, ambas incluidas
+fn contar_bits(bytes: &[u8], desde: usize, hasta: usize) -> u64 {
+    let mut cantidad = 0;
+    let mut bit = desde;
+    while bit <= hasta {
+        if bit.is_multiple_of(8) && bit + 7 <= hasta {
+            cantidad += bytes[bit / 8].count_ones() as u64;
+            bit += 8;
+        } else {
+            cantidad += leer_bit(bytes, bit) as u64;
+            bit += 1;
+        }
+    }
+    cantidad
+}
+
+/// Convierte un rango con indices negativos desde el final, expresado en
+/// bytes o bits, en el rango de bits que abarca dentro de un string de
+/// `largo` bytes
+///
+/// # Retorna
+/// - Primer y ultimo bit del rango, o None si el rango es vacio
+fn rango_de_bits(inicio: i64, fin: i64, unidad: Unidad, largo: usize) -> Option<(usize, usize)> {
+    let total = match unidad {
+        Unidad::Byte => largo as i64,
+        Unidad::Bit => largo as i64 * 8,
+    };
+    let normalizar = |indice: i64| if indice < 0 { total + indice } else { indice };
+    let inicio = normalizar(inicio).max(0);
+    let fin = normalizar(fin).max(0).min(total - 1);
+    if total == 0 || inicio > fin {
+        return None;
+    }
+    match unidad {
+        Unidad::Byte => Some((inicio as usize * 8, fin as usize * 8 + 7)),
+        Unidad::Bit => Some((inicio as usize, fin as usize)),
+    }
+}
+
+/// Parsea los subcomandos de BITFIELD
+///
+/// # Retorna
+/// - Tipo, offset en bits, overflow vigente y operacion de cada campo
+fn parsear_operaciones_bitfield(
+    tokens: &[Token],
+) -> Result<Vec<(TipoCampo, usize, Overflow, OperacionCampo)>, DatoRedis> {
+    let mut operaciones = Vec::new();
+    let mut overflow = Overflow::Wrap;
+    let mut i = 0;
+    while i < tokens.len() {
+        let subcomando = tokens[i].to_uppercase();
+        if subcomando == SUBCOMANDO_OVERFLOW {
+            overflow = match tokens.get(i + 1).map(|t| t.to_uppercase()).as_deref() {
+                Some("WRAP") => Overflow::Wrap,
+                Some("SAT") => Overflow::Sat,
+                Some("FAIL") => Overflow::Fail,
+                _ => {
+                    return Err(DatoRedis::new_simple_error(
+                        "ERR".to_string(),
+                        "Invalid OVERFLOW type specified".to_string(),
+                    ));
+                }
+            };
+            i += 2;
+            continue;
+        }
+        let argumentos = match subcomando.as_str() {
+            SUBCOMANDO_GET => 2,
+            SUBCOMANDO_SET | SUBCOMANDO_INCRBY => 3,
+            _ => return Err(error_sintaxis()),
+        };
+        let Some(argumentos) = tokens.get(i + 1..i + 1 + argumentos) else {
+            return Err(error_sintaxis());
+        };
+        let tipo = parsear_tipo(&argumentos[0].texto())?;
+        let offset = parsear_offset_campo(&argumentos[1].texto(), tipo)?;
+        let operacion = match subcomando.as_str() {
+            SUBCOMANDO_GET => OperacionCampo::Get,
+            SUBCOMANDO_SET => OperacionCampo::Set(parsear_entero(&argumentos[2].texto())?),
+            _ => OperacionCampo::Incrby(parsear_entero(&argumentos[2].texto())?),
+        };
+        operaciones.push((tipo, offset, overflow, operacion));
+        i += 1 + argumentos.len();
+    }
+    Ok(operaciones)
+}
+
+/// Parsea el tipo de un campo: `i1` a `i64` o `u1` a `u63`
+fn parsear_tipo(token: &str) -> Result<TipoCampo, DatoRedis> {
+    let error = || {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.".to_string(),
+        )
+    };
+    let con_signo = match token.chars().next() {
+        Some('i') | Some('I') => true,
+        Some('u') | Some('U') => false,
+        _ => return Err(error()),
+    };
+    let bits = token[1..].parse::<u32>().map_err(|_| error())?;
+    let maximo = if con_signo { 64 } else { 63 };
+    if bits == 0 || bits > maximo {
+        return Err(error());
+    }
+    Ok(TipoCampo { con_signo, bits })
+}
+
+/// Parsea el offset de un campo, en bits o, con el prefijo `#`, en
+/// multiplos del ancho del tipo
+fn parsear_offset_campo(token: &str, tipo: TipoCampo) -> Result<usize, DatoRedis> {
+    let offset = match token.strip_prefix('#') {
+        Some(indice) => parsear_offset(indice)?.checked_mul(tipo.bits as u64),
+        None => Some(parsear_offset(token)?),
+    };
+    match offset {
+        Some(offset) if offset.saturating_add(tipo.bits as u64 - 1) <= OFFSET_MAXIMO => {
+            Ok(offset as usize)
+        }
+        _ => Err(error_offset()),
+    }
+}
+
+fn limites_campo(tipo: TipoCampo) -> (i128, i128) {
+    if tipo.con_signo {
+        let limite = 1i128 << (tipo.bits - 1);
+        (-limite, limite - 1)
+    } else {
+        (0, (1i128 << tipo.bits) - 1)
+    }
+}
+
+/// Ajusta un valor al rango del tipo segun el overflow
+///
+/// # Retorna
+/// - Valor a escribir, o None si no entra y el overflow es FAIL
+fn ajustar_campo(valor: i128, tipo: TipoCampo, overflow: Overflow) -> Option<i128> {
+    let (minimo, maximo) = limites_campo(tipo);
+    if (minimo..=maximo).contains(&valor) {
+        return Some(valor);
+    }
+    match overflow {
+        Overflow::Wrap => Some((valor - minimo).rem_euclid(1i128 << tipo.bits) + minimo),
+        Overflow::Sat => Some(valor.clamp(minimo, maximo)),
+        Overflow::Fail => None,
+    }
+}
+
+fn leer_campo(bytes: &[u8], offset: usize, tipo: TipoCampo) -> i128 {
+    let sin_signo = (0..tipo.bits as usize).fold(0i128, |valor, i| {
+        (valor << 1) | leer_bit(bytes, offset + i) as i128
+    });
+    if tipo.con_signo && sin_signo >> (tipo.bits - 1) == 1 {
+        sin_signo - (1i128 << tipo.bits)
+    } else {
+        sin_signo
+    }
+}
+
+fn escribir_campo(bytes: &mut Vec<u8>, offset: usize, tipo: TipoCampo, valor: i128) {
+    let bits = valor.rem_euclid(1i128 << tipo.bits);
+    for i in 0..tipo.bits as usize {
+        let bit = (bits >> (tipo.bits as usize - 1 - i)) & 1 == 1;
+        escribir_bit(bytes, offset + i, bit);
+    }
+}
+
+/// Parsea el offset de un bit, que debe ser menor a 2^32
+fn parsear_offset(token: &str) -> Result<u64, DatoRedis> {
+    match token.parse::<u64>() {
+        Ok(offset) if offset <= OFFSET_MAXIMO => Ok(offset),
+        _ => Err(error_offset()),
+    }
+}
+
+fn parsear_unidad(token: Option<&Token>) -> Result<Unidad, DatoRedis> {
+    match token.map(|t| t.to_uppercase()).as_deref() {
+        None | Some(OPCION_BYTE) => Ok(Unidad::Byte),
+        Some(OPCION_BIT) => Ok(Unidad::Bit),
+        Some(_) => Err(error_sintaxis()),
+    }
+}
+
+fn parsear_entero(token: &str) -> Result<i64, DatoRedis> {
+    token.parse::<i64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not an integer or out of range".to_string(),
+        )
+    })
+}
+
+fn error_offset() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        "bit offset is not an integer or out of range".to_string(),
+    )
+}
+
+fn error_tipo() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "WRONGTYPE".to_string(),
+        "Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn entero(resultado: Result<DatoRedis, DatoRedis>) -> i64 {
+        match resultado {
+            Ok(DatoRedis::Integer(valor)) => valor.valor(),
+            otro => panic!("se esperaba un entero: {otro:?}"),
+        }
+    }
+
+    fn valor(storage: &Arc<RwLock<Storage>>, clave: &str) -> Vec<u8> {
+        leer_bytes(&storage.read().unwrap(), clave.as_bytes())
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Busca una clave que pertenezca al mismo slot que `key`
+    fn clave_vecina(storage: &Arc<RwLock<Storage>>, key: &str) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(key);
+        (0..)
+            .map(|i| format!("{key}{i}"))
+            .find(|candidata| guard.calculate_slot_for_key(candidata) == slot)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_01_setbit_y_getbit() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(
+            entero(setbit(&tokens(&["setbit", "k", "7", "1"]), &storage)),
+            0
+        );
+        assert_eq!(
+            entero(setbit(&tokens(&["setbit", "k", "7", "0"]), &storage)),
+            1
+        );
+        assert_eq!(
+            entero(setbit(&tokens(&["setbit", "k", "9", "1"]), &storage)),
+            0
+        );
+        assert_eq!(valor(&storage, "k"), vec![0x00, 0x40]);
+        assert_eq!(entero(getbit(&tokens(&["getbit", "k", "9"]), &storage)), 1);
+        assert_eq!(
+            entero(getbit(&tokens(&["getbit", "k", "1000"]), &storage)),
+            0
+        );
+        assert_eq!(
+            entero(getbit(&tokens(&["getbit", "nada", "0"]), &storage)),
+            0
+        );
+
+        assert!(setbit(&tokens(&["setbit", "k", "1", "2"]), &storage).is_err());
+        assert!(setbit(&tokens(&["setbit", "k", "-1", "1"]), &storage).is_err());
+        assert!(setbit(&tokens(&["setbit", "k", "4294967296", "1"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_02_bitcount_con_rangos() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        storage
+            .write()
+            .unwrap()
+            .set(
+                "k",
+                DatoRedis::new_bulk_string("foobar".to_string()).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(entero(bitcount(&tokens(&["bitcount", "k"]), &storage)), 26);
+        assert_eq!(
+            entero(bitcount(&tokens(&["bitcount", "k", "0", "0"]), &storage)),
+            4
+        );
+        assert_eq!(
+            entero(bitcount(&tokens(&["bitcount", "k", "1", "1"]), &storage)),
+            6
+        );
+        assert_eq!(
+            entero(bitcount(&tokens(&["bitcount", "k", "-2", "-1"]), &storage)),
+            7
+        );
+        assert_eq!(
+            entero(bitcount(
+                &tokens(&["bitcount", "k", "5", "30", "BIT"]),
+                &storage
+            )),
+            17
+        );
+        assert_eq!(
+            entero(bitcount(&tokens(&["bitcount", "k", "3", "1"]), &storage)),
+            0
+        );
+        assert_eq!(
+            entero(bitcount(&tokens(&["bitcount", "nada"]), &storage)),
+            0
+        );
+        assert!(bitcount(&tokens(&["bitcount", "k", "0"]), &storage).is_err());
+        assert!(bitcount(&tokens(&["bitcount", "k", "0", "1", "WORD"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_03_bitpos() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        storage
+            .write()
+            .unwrap()
+            .set(
+                "k",
+                DatoRedis::new_bulk_string_desde_bytes(vec![0xFF, 0xF0, 0x00]).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(entero(bitpos(&tokens(&["bitpos", "k", "0"]), &storage)), 12);
+        assert_eq!(
+            entero(bitpos(&tokens(&["bitpos", "k", "1", "2"]), &storage)),
+            -1
+        );
+        assert_eq!(
+            entero(bitpos(
+                &tokens(&["bitpos", "k", "1", "7", "15", "BIT"]),
+                &storage
+            )),
+            7
+        );
+
+        storage
+            .write()
+            .unwrap()
+            .set(
+                "unos",
+                DatoRedis::new_bulk_string_desde_bytes(vec![0xFF, 0xFF]).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            entero(bitpos(&tokens(&["bitpos", "unos", "0"]), &storage)),
+            16
+        );
+        assert_eq!(
+            entero(bitpos(
+                &tokens(&["bitpos", "unos", "0", "0", "-1"]),
+                &storage
+            )),
+            -1
+        );
+        assert_eq!(
+            entero(bitpos(&tokens(&["bitpos", "nada", "0"]), &storage)),
+            0
+        );
+        assert_eq!(
+            entero(bitpos(&tokens(&["bitpos", "nada", "1"]), &storage)),
+            -1
+        );
+        assert!(bitpos(&tokens(&["bitpos", "k", "2"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_04_bitop() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let a = clave_vecina(&storage, "dest");
+        let b = clave_vecina(&storage, &a);
+        {
+            let mut guard = storage.write().unwrap();
+            guard
+                .set(
+                    a.clone(),
+                    DatoRedis::new_bulk_string_desde_bytes(vec![0b1100, 0xFF]).unwrap(),
+                )
+                .unwrap();
+            guard
+                .set(
+                    b.clone(),
+                    DatoRedis::new_bulk_string_desde_bytes(vec![0b1010]).unwrap(),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            entero(bitop(&tokens(&["bitop", "AND", "dest", &a, &b]), &storage)),
+            2
+        );
+        assert_eq!(valor(&storage, "dest"), vec![0b1000, 0x00]);
+        assert_eq!(
+            entero(bitop(&tokens(&["bitop", "or", "dest", &a, &b]), &storage)),
+            2
+        );
+        assert_eq!(valor(&storage, "dest"), vec![0b1110, 0xFF]);
+        assert_eq!(
+            entero(bitop(&tokens(&["bitop", "XOR", "dest", &a, &b]), &storage)),
+            2
+        );
+        assert_eq!(valor(&storage, "dest"), vec![0b0110, 0xFF]);
+        assert_eq!(
+            entero(bitop(&tokens(&["bitop", "NOT", "dest", &b]), &storage)),
+            1
+        );
+        assert_eq!(valor(&storage, "dest"), vec![0b1111_0101]);
+
+        let ausente = clave_vecina(&storage, &b);
+        assert_eq!(
+            entero(bitop(
+                &tokens(&["bitop", "AND", "dest", &ausente]),
+                &storage
+            )),
+            0
+        );
+        assert!(storage.read().unwrap().get("dest").is_err());
+
+        assert!(bitop(&tokens(&["bitop", "NOT", "dest", &a, &b]), &storage).is_err());
+        assert!(bitop(&tokens(&["bitop", "NAND", "dest", &a]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_05_bitfield_get_set_e_incrby() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let resultado = bitfield(
+            &tokens(&[
+                "bitfield", "k", "SET", "u8", "0", "255", "GET", "u4", "0", "GET", "i8", "0",
+                "INCRBY", "u8", "#1", "10", "GET", "u16", "0",
+            ]),
+            &storage,
+        )
+        .unwrap();
+        let mut esperado = Arrays::new();
+        for valor in [0, 15, -1, 10, 0xFF0A] {
+            esperado.append(DatoRedis::new_integer(valor));
+        }
+        assert_eq!(resultado, DatoRedis::new_array_con_contenido(esperado));
+        assert_eq!(valor(&storage, "k"), vec![0xFF, 0x0A]);
+
+        // Solo lectura no crea la clave
+        bitfield(&tokens(&["bitfield", "nada", "GET", "i64", "0"]), &storage).unwrap();
+        assert!(storage.read().unwrap().get("nada").is_err());
+    }
+
+    #[test]
+    fn test_06_bitfield_overflow() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let resultado = bitfield(
+            &tokens(&[
+                "bitfield", "k", "SET", "u8", "0", "250", "INCRBY", "u8", "0", "10", "OVERFLOW",
+                "SAT", "INCRBY", "i8", "8", "200", "OVERFLOW", "FAIL", "INCRBY", "u8", "0", "300",
+            ]),
+            &storage,
+        )
+        .unwrap();
+        let mut esperado = Arrays::new();
+        esperado.append(DatoRedis::new_integer(0));
+        esperado.append(DatoRedis::new_integer(4));
+        esperado.append(DatoRedis::new_integer(127));
+        esperado.append(DatoRedis::new_null());
+        assert_eq!(resultado, DatoRedis::new_array_con_contenido(esperado));
+        assert_eq!(valor(&storage, "k"), vec![4, 127]);
+    }
+
+    #[test]
+    fn test_07_bitfield_argumentos_invalidos() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        for invalido in [
+            vec!["bitfield", "k", "GET", "u64", "0"],
+            vec!["bitfield", "k", "GET", "i0", "0"],
+            vec!["bitfield", "k", "GET", "i8", "-1"],
+            vec!["bitfield", "k", "SET", "i8", "0"],
+            vec!["bitfield", "k", "OVERFLOW", "NUNCA"],
+            vec!["bitfield", "k", "DEL", "i8", "0"],
+        ] {
+            assert!(bitfield(&tokens(&invalido), &storage).is_err());
+        }
+        storage
+            .write()
+            .unwrap()
+            .set("lista", DatoRedis::new_array())
+            .unwrap();
+        assert!(bitfield(&tokens(&["bitfield", "lista", "GET", "u8", "0"]), &storage).is_err());
+        assert!(getbit(&tokens(&["getbit", "lista", "0"]), &storage).is_err());
+    }
+}
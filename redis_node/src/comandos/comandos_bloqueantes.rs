@@ -0,0 +1,296 @@
+//! Este modulo contiene la ejecucion de los comandos bloqueantes de listas
+//! (BLPOP, BRPOP y BLMOVE) y de XREAD y XREADGROUP con BLOCK, que dejan al cliente en
+//! espera hasta que una lista o stream tenga elementos o venza el timeout
+use std::io::ErrorKind;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::bloqueos::INTERVALO_BLOQUEO;
+use crate::client_struct::client::Client;
+use crate::comandos::comandos_list::parametros_bloqueo;
+use crate::comandos::comandos_stream::{preparar_xread_bloqueante, xread_bloqueante};
+use crate::comandos::comandos_stream_grupos::{
+    preparar_xreadgroup_bloqueante, xreadgroup_bloqueante,
+};
+use crate::comandos::const_cmd::{
+    CMD_BLMOVE, CMD_BLPOP, CMD_BRPOP, CMD_LMOVE, CMD_LPOP, CMD_RPOP, CMD_XREAD, CMD_XREADGROUP,
+};
+use crate::node::Node;
+use crate::node_role::NodeRole;
+use crate::utils::utils_functions::CommandFunction;
+
+/// Tiempo que se espera una respuesta al verificar si el cliente sigue
+/// conectado
+const ESPERA_VERIFICACION_CONEXION: Duration = Duration::from_millis(1);
+
+/// Indica si un comando puede bloquear al cliente
+///
+/// # Parámetros
+/// * `comando`: nombre del comando en mayusculas
+/// * `tokens`: secuencia de strings que conforman el comando
+pub(crate) fn es_comando_bloqueante(comando: &str, tokens: &[Token]) -> bool {
+    match comando {
+        CMD_BLPOP | CMD_BRPOP | CMD_BLMOVE => true,
+        CMD_XREAD => xread_bloqueante(tokens),
+        CMD_XREADGROUP => xreadgroup_bloqueante(tokens),
+        _ => false,
+    }
+}
+
+impl Node {
+    /// Ejecuta un comando bloqueante. El cliente espera en la cola de sus
+    /// claves hasta ser el primero de alguna que tenga elementos, hasta que
+    /// venza el timeout o hasta que las claves dejen de pertenecer al nodo
+    ///
+    /// # Parámetros
+    /// * `funcion`: intento no bloqueante del comando
+    /// * `tokens`: secuencia de strings que conforman el comando
+    /// * `client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - la respuesta y el comando no bloqueante equivalente, que es el que
+    ///   se guarda en el AOF y se envia a las replicas. Null si vence el
+    ///   timeout, MOVED si el slot cambia de dueño, error de redis en otros
+    ///   casos
+    pub(crate) fn ejecutar_comando_bloqueante(
+        &self,
+        funcion: CommandFunction,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<(DatoRedis, Vec<Token>), DatoRedis> {
+        let comando = tokens[0].to_uppercase();
+        if comando == CMD_XREAD || comando == CMD_XREADGROUP {
+            return self.ejecutar_xread_bloqueante(funcion, tokens, client);
+        }
+        let (claves, timeout) = parametros_bloqueo(tokens)?;
+        let limite = timeout.map(|t| Instant::now() + t);
+        let ticket = self.bloqueos.bloquear(&claves);
+        let resultado = self.esperar_elementos(funcion, tokens, &claves, ticket, limite, client);
+        self.bloqueos.desbloquear(ticket, &claves);
+        resultado
+    }
+
+    fn esperar_elementos(
+        &self,
+        funcion: CommandFunction,
+        tokens: &[Token],
+        claves: &[Token],
+        ticket: u64,
+        limite: Option<Instant>,
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<(DatoRedis, Vec<Token>), DatoRedis> {
+        loop {
+            let version = self.bloqueos.version();
+            self.verificar_dueño_claves(claves)?;
+
+            let en_turno = self.bloqueos.claves_en_turno(ticket, claves);
+            if !en_turno.is_empty() {
+                let intento = tokens_con_claves(tokens, &en_turno);
+                match funcion(&intento, &self.storage) {
+                    Ok(respuesta) => {
+                        let propagado = comando_equivalente(&intento, &respuesta);
+                        return Ok((respuesta, propagado));
+                    }
+                    Err(DatoRedis::Null(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let ahora = Instant::now();
+            if limite.is_some_and(|limite| ahora >= limite) || !cliente_conectado(client) {
+                return Err(DatoRedis::new_null());
+            }
+            let proxima_verificacion = ahora + INTERVALO_BLOQUEO;
+            let hasta = limite.map_or(proxima_verificacion, |l| l.min(proxima_verificacion));
+            self.bloqueos.esperar(version, hasta);
+        }
+    }
+
+    /// Ejecuta un XREAD o XREADGROUP con BLOCK. A diferencia de las listas,
+    /// leer un stream no consume sus entradas, por lo que todos los clientes
+    /// bloqueados en un stream reintentan la lectura. En XREADGROUP cada
+    /// entrada se entrega a un unico consumidor, ya que cada intento se
+    /// ejecuta con el lock de escritura del storage
+    ///
+    /// # Retorna
+    /// - la respuesta y el comando sin BLOCK, que es el que se guarda en el
+    ///   AOF y se envia a las replicas. Null si vence el timeout
+    fn ejecutar_xread_bloqueante(
+        &self,
+        funcion: CommandFunction,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<(DatoRedis, Vec<Token>), DatoRedis> {
+        // XREADGROUP modifica el grupo, por lo que solo lo ejecuta el dueño
+        // de las claves
+        let es_xreadgroup = tokens[0].to_uppercase() == CMD_XREADGROUP;
+        let (claves, timeout, intento) = if es_xreadgroup {
+            preparar_xreadgroup_bloqueante(tokens)?
+        } else {
+            preparar_xread_bloqueante(tokens, &self.storage)?
+        };
+        let limite = timeout.map(|t| Instant::now() + t);
+        let ticket = self.bloqueos.bloquear(&claves);
+        let resultado =
+            self.esperar_entradas(funcion, &intento, &claves, es_xreadgroup, limite, client);
+        self.bloqueos.desbloquear(ticket, &claves);
+        resultado.map(|respuesta| (respuesta, intento))
+    }
+
+    fn esperar_entradas(
+        &self,
+        funcion: CommandFunction,
+        intento: &[Token],
+        claves: &[Token],
+        verificar_dueño: bool,
+        limite: Option<Instant>,
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        loop {
+            let version = self.bloqueos.version();
+            if verificar_dueño {
+                self.verificar_dueño_claves(claves)?;
+            }
+            match funcion(intento, &self.storage)? {
+                DatoRedis::Null(_) => {}
+                respuesta => return Ok(respuesta),
+            }
+
+            let ahora = Instant::now();
+            if limite.is_some_and(|limite| ahora >= limite) || !cliente_conectado(client) {
+                return Ok(DatoRedis::new_null());
+            }
+            let proxima_verificacion = ahora + INTERVALO_BLOQUEO;
+            let hasta = limite.map_or(proxima_verificacion, |l| l.min(proxima_verificacion));
+            self.bloqueos.esperar(version, hasta);
+        }
+    }
+
+    /// Verifica que las claves sigan perteneciendo al nodo: si el nodo pasó
+    /// a ser replica o el slot ya no esta en su rango, el cliente debe ser
+    /// redirigido
+    fn verificar_dueño_claves(&self, claves: &[Token]) -> Result<(), DatoRedis> {
+        let es_replica = self
+            .role
+            .read()
+            .map(|rol| *rol == NodeRole::Replica)
+            .unwrap_or(false);
+        let storage = self.storage.read().map_err(|_| {
+            DatoRedis::new_simple_error("ERR".to_string(), "storage lock error".to_string())
+        })?;
+        for clave in claves {
+            let slot = storage.calculate_slot_for_key(clave);
+            if es_replica || !storage.get_slot_range().contains(&slot) {
+                return Err(DatoRedis::new_moved_error(slot));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reemplaza las claves de un comando bloqueante por las indicadas,
+/// conservando el resto de los argumentos
+fn tokens_con_claves(tokens: &[Token], claves: &[Token]) -> Vec<Token> {
+    if tokens[0].to_uppercase() == CMD_BLMOVE {
+        return tokens.to_vec();
+    }
+    let mut intento = vec![tokens[0].clone()];
+    intento.extend(claves.iter().cloned());
+    intento.push(tokens[tokens.len() - 1].clone());
+    intento
+}
+
+/// Obtiene el comando no bloqueante equivalente al efecto de un comando
+/// bloqueante exitoso
+///
+/// # Parámetros
+/// * `tokens`: comando bloqueante ejecutado
+/// * `respuesta`: respuesta del comando
+fn comando_equivalente(tokens: &[Token], respuesta: &DatoRedis) -> Vec<Token> {
+    let comando = tokens[0].to_uppercase();
+    if comando == CMD_BLMOVE {
+        let mut equivalente = vec![Token::from(CMD_LMOVE)];
+        equivalente.extend(tokens[1..5].iter().cloned());
+        return equivalente;
+    }
+    let pop = if comando == CMD_BLPOP {
+        CMD_LPOP
+    } else {
+        CMD_RPOP
+    };
+    let clave = match respuesta {
+        DatoRedis::Arrays(arr) => match arr.get(0) {
+            Some(DatoRedis::BulkString(clave)) => Token::from(clave.bytes()),
+            _ => tokens[1].clone(),
+        },
+        _ => tokens[1].clone(),
+    };
+    vec![Token::from(pop), clave]
+}
+
+/// Verifica si el cliente sigue conectado, para no dejar en la cola de
+/// espera a un cliente que cerro la conexion
+fn cliente_conectado(client: &Arc<RwLock<Client>>) -> bool {
+    let Ok(cli) = client.read() else {
+        return false;
+    };
+    let stream = cli.get_stream();
+    if stream
+        .set_read_timeout(Some(ESPERA_VERIFICACION_CONEXION))
+        .is_err()
+    {
+        return true;
+    }
+    let mut buffer = [0u8; 1];
+    let conectado = match stream.peek(&mut buffer) {
+        Ok(0) => false,
+        Ok(_) => true,
+        Err(e) => matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut),
+    };
+    let _ = stream.set_read_timeout(None);
+    conectado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redis_client::protocol::token::tokens;
+    use redis_client::tipos_datos::arrays::Arrays;
+
+    #[test]
+    fn test_01_intento_solo_con_claves_en_turno() {
+        assert_eq!(
+            tokens_con_claves(&tokens(&["BLPOP", "a", "b", "c", "0"]), &tokens(&["b"])),
+            tokens(&["BLPOP", "b", "0"])
+        );
+        let blmove = tokens(&["BLMOVE", "a", "b", "LEFT", "RIGHT", "1.5"]);
+        assert_eq!(tokens_con_claves(&blmove, &tokens(&["a"])), blmove);
+    }
+
+    #[test]
+    fn test_02_se_propaga_el_comando_no_bloqueante() {
+        let mut respuesta = Arrays::new();
+        respuesta.append(DatoRedis::new_bulk_string("b".to_string()).unwrap());
+        respuesta.append(DatoRedis::new_bulk_string("valor".to_string()).unwrap());
+        let respuesta = DatoRedis::new_array_con_contenido(respuesta);
+
+        assert_eq!(
+            comando_equivalente(&tokens(&["blpop", "a", "b", "0"]), &respuesta),
+            tokens(&["LPOP", "b"])
+        );
+        assert_eq!(
+            comando_equivalente(&tokens(&["brpop", "a", "b", "0"]), &respuesta),
+            tokens(&["RPOP", "b"])
+        );
+        assert_eq!(
+            comando_equivalente(
+                &tokens(&["blmove", "a", "b", "left", "right", "0"]),
+                &DatoRedis::new_bulk_string("valor".to_string()).unwrap()
+            ),
+            tokens(&["LMOVE", "a", "b", "left", "right"])
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! Este modulo contiene la implementacion de la familia de comandos CLIENT,
+//! que permiten inspeccionar y administrar las conexiones del nodo
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::*;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido,
+};
+use crate::node::Node;
+use crate::tracking::OpcionesTracking;
+
+const FILTRO_ID: &str = "ID";
+const FILTRO_ADDR: &str = "ADDR";
+const FILTRO_USER: &str = "USER";
+const FILTRO_SKIPME: &str = "SKIPME";
+const MODO_PAUSA_WRITE: &str = "WRITE";
+const MODO_PAUSA_ALL: &str = "ALL";
+const OPCION_REDIRECT: &str = "REDIRECT";
+const OPCION_PREFIX: &str = "PREFIX";
+const OPCION_BCAST: &str = "BCAST";
+const OPCION_OPTIN: &str = "OPTIN";
+const OPCION_OPTOUT: &str = "OPTOUT";
+const OPCION_NOLOOP: &str = "NOLOOP";
+
+/// Criterios de seleccion de CLIENT KILL
+#[derive(Debug, Default)]
+struct FiltroKill {
+    id: Option<u64>,
+    addr: Option<String>,
+    usuario: Option<String>,
+    incluir_propio: bool,
+}
+
+impl FiltroKill {
+    /// Indica si un cliente cumple con todos los criterios del filtro
+    ///
+    /// # Parámetros
+    /// * `client`: cliente a evaluar
+    /// * `es_propio`: si el cliente es quien ejecuta el comando
+    fn coincide(&self, client: &Client, es_propio: bool) -> bool {
+        (self.incluir_propio || !es_propio)
+            && self.id.is_none_or(|id| id == client.get_numero())
+            && self
+                .addr
+                .as_ref()
+                .is_none_or(|addr| *addr == client.client_id())
+            && self
+                .usuario
+                .as_ref()
+                .is_none_or(|usuario| Some(usuario) == client.get_usuario())
+    }
+}
+
+impl Node {
+    /// Ejecuta un subcomando de CLIENT
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos
+    /// * `client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - Respuesta del subcomando, error de redis si el subcomando no existe
+    ///   o sus argumentos son invalidos
+    pub(crate) fn client(
+        &self,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CLIENT.to_lowercase(), 2, tokens.len())?;
+        let subcomando = tokens[1].to_uppercase();
+        match subcomando.as_str() {
+            CMD_CLIENT_ID => Ok(DatoRedis::new_integer(
+                leer_cliente(client)?.get_numero() as i64
+            )),
+            CMD_CLIENT_SETNAME => client_setname(tokens, client),
+            CMD_CLIENT_GETNAME => match leer_cliente(client)?.get_nombre() {
+                Some(nombre) => DatoRedis::new_bulk_string(nombre.clone()),
+                None => Ok(DatoRedis::new_null()),
+            },
+            CMD_CLIENT_LIST => self.client_list(tokens),
+            CMD_CLIENT_INFO => DatoRedis::new_bulk_string(describir_cliente(client)? + "\n"),
+            CMD_CLIENT_KILL => self.client_kill(tokens, client),
+            CMD_CLIENT_PAUSE => self.client_pause(tokens),
+            CMD_CLIENT_UNPAUSE => {
+                self.clientes.reanudar();
+                DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+            }
+            CMD_CLIENT_NO_EVICT => client_no_evict(tokens),
+            CMD_CLIENT_TRACKING => self.client_tracking(tokens, client),
+            CMD_CLIENT_CACHING => self.client_caching(tokens, client),
+            CMD_CLIENT_GETREDIRECT => {
+                let numero = leer_cliente(client)?.get_numero();
+                let redirect = match self.tracking.opciones(numero) {
+                    Some(opciones) => opciones.redirect.map_or(0, |id| id as i64),
+                    None => -1,
+                };
+                Ok(DatoRedis::new_integer(redirect))
+            }
+            _ => Err(error_subcomando_desconocido(&tokens[1].texto())),
+        }
+    }
+
+    /// Lista los clientes conectados, uno por linea, opcionalmente filtrando
+    /// por id (`CLIENT LIST [ID id ...]`)
+    fn client_list(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        let ids = match tokens.get(2) {
+            None => None,
+            Some(filtro) if filtro.to_uppercase() == FILTRO_ID && tokens.len() > 3 => Some(
+                tokens[3..]
+                    .iter()
+                    .map(parsear_id)
+                    .collect::<Result<Vec<u64>, DatoRedis>>()?,
+            ),
+            Some(_) => return Err(error_sintaxis()),
+        };
+
+        let mut lineas = String::new();
+        for cliente in self.clientes.clientes() {
+            let numero = leer_cliente(&cliente)?.get_numero();
+            if ids.as_ref().is_none_or(|ids| ids.contains(&numero)) {
+                lineas.push_str(&describir_cliente(&cliente)?);
+                lineas.push('\n');
+            }
+        }
+        DatoRedis::new_bulk_string(lineas)
+    }
+
+    /// Cierra conexiones de clientes. Soporta la forma `CLIENT KILL addr`,
+    /// que responde OK o error si no existe, y la forma con filtros
+    /// `CLIENT KILL [ID id] [ADDR addr] [USER user] [SKIPME yes|no]`, que
+    /// responde la cantidad de clientes cerrados
+    fn client_kill(
+        &self,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CLIENT_KILL.to_lowercase(), 3, tokens.len())?;
+        if tokens.len() == 3 {
+            let filtro = FiltroKill {
+                addr: Some(tokens[2].texto().into_owned()),
+                incluir_propio: true,
+                ..FiltroKill::default()
+            };
+            return match self.matar_clientes(&filtro, client)? {
+                0 => Err(DatoRedis::new_simple_error(
+                    "ERR".to_string(),
+                    "No such client".to_string(),
+                )),
+                _ => DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string()),
+            };
+        }
+
+        let filtro = parsear_filtro_kill(&tokens[2..])?;
+        let cerrados = self.matar_clientes(&filtro, client)?;
+        Ok(DatoRedis::new_integer(cerrados as i64))
+    }
+
+    /// Cierra la conexion de los clientes que cumplen con el filtro
+    ///
+    /// # Retorna
+    /// - Cantidad de clientes cerrados
+    fn matar_clientes(
+        &self,
+        filtro: &FiltroKill,
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<usize, DatoRedis> {
+        let mut cerrados = 0;
+        for cliente in self.clientes.clientes() {
+            let es_propio = Arc::ptr_eq(&cliente, client);
+            let guard = leer_cliente(&cliente)?;
+            if filtro.coincide(&guard, es_propio) {
+                guard.cerrar_conexion();
+                cerrados += 1;
+            }
+        }
+        Ok(cerrados)
+    }
+
+    /// Pausa a los clientes durante `timeout` milisegundos
+    /// (`CLIENT PAUSE timeout [WRITE|ALL]`). Con WRITE solo se demoran los
+    /// comandos que modifican el storage
+    fn client_pause(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CLIENT_PAUSE.to_lowercase(), 3, tokens.len())?;
+        let timeout = tokens[2].parse::<u64>().map_err(|_| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "timeout is not an integer or out of range".to_string(),
+            )
+        })?;
+        let solo_escritura = match tokens.get(3).map(|s| s.to_uppercase()) {
+            None => false,
+            Some(modo) if modo == MODO_PAUSA_ALL && tokens.len() == 4 => false,
+            Some(modo) if modo == MODO_PAUSA_WRITE && tokens.len() == 4 => true,
+            Some(_) => return Err(error_sintaxis()),
+        };
+        self.clientes
+            .pausar(Duration::from_millis(timeout), solo_escritura);
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Activa o desactiva el client side caching de la conexion
+    /// (`CLIENT TRACKING on|off [REDIRECT id] [PREFIX prefijo ...] [BCAST]
+    /// [OPTIN] [OPTOUT] [NOLOOP]`). Con tracking activo la conexion recibe
+    /// como push la invalidacion de las claves que leyo, o de las que
+    /// empiezan con alguno de los prefijos en modo BCAST, cuando cambian o
+    /// expiran. Con REDIRECT la recibe en cambio el cliente indicado, si
+    /// esta suscripto a `__redis__:invalidate`
+    fn client_tracking(
+        &self,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CLIENT_TRACKING.to_lowercase(), 3, tokens.len())?;
+        let numero = leer_cliente(client)?.get_numero();
+        match tokens[2].to_lowercase().as_str() {
+            "on" => {
+                let opciones = parsear_opciones_tracking(&tokens[3..])?;
+                if let Some(redirect) = opciones.redirect
+                    && self.clientes.obtener(redirect).is_none()
+                {
+                    return Err(error("The client ID you want redirect to does not exist"));
+                }
+                if self
+                    .tracking
+                    .opciones(numero)
+                    .is_some_and(|previas| previas.bcast != opciones.bcast)
+                {
+                    return Err(error(
+                        "You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.",
+                    ));
+                }
+                self.tracking.activar(numero, opciones);
+            }
+            "off" if tokens.len() == 3 => self.tracking.desactivar(numero),
+            _ => return Err(error_sintaxis()),
+        }
+        self.actualizar_registro_modificaciones();
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Indica si se registran las claves que lee el proximo comando del
+    /// cliente (`CLIENT CACHING yes|no`), segun el modo OPTIN u OPTOUT
+    fn client_caching(
+        &self,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_CLIENT_CACHING.to_lowercase(), 3, tokens.len())?;
+        let numero = leer_cliente(client)?.get_numero();
+        let opciones = self
+            .tracking
+            .opciones(numero)
+            .filter(|opciones| opciones.optin || opciones.optout)
+            .ok_or_else(|| {
+                error(
+                    "CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled",
+                )
+            })?;
+        let caching = match tokens[2].to_lowercase().as_str() {
+            "yes" if opciones.optin => true,
+            "yes" => {
+                return Err(error(
+                    "CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode.",
+                ));
+            }
+            "no" if opciones.optout => false,
+            "no" => {
+                return Err(error(
+                    "CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode.",
+                ));
+            }
+            _ => return Err(error_sintaxis()),
+        };
+        self.tracking.set_caching(numero, caching);
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Desactiva el tracking de un cliente desconectado
+    pub(crate) fn desactivar_tracking(&self, numero: u64) {
+        if self.tracking.opciones(numero).is_some() {
+            self.tracking.desactivar(numero);
+            self.actualizar_registro_modificaciones();
+        }
+    }
+
+    /// Habilita en el storage el registro de las claves modificadas solo si
+    /// algun cliente tiene tracking activo
+    fn actualizar_registro_modificaciones(&self) {
+        if let Ok(mut storage) = self.storage.write() {
+            storage.set_registrar_modificaciones(self.tracking.hay_clientes());
+        }
+    }
+}
+
+/// Asigna un nombre a la conexion (`CLIENT SETNAME nombre`). Un nombre
+/// vacio elimina el nombre asignado
+fn client_setname(tokens: &[Token], client: &Arc<RwLock<Client>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&CMD_CLIENT_SETNAME.to_lowercase(), 3, tokens.len())?;
+    let nombre = &tokens[2];
+    if nombre.iter().any(|&c| c <= b' ' || c > b'~') {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Client names cannot contain spaces, newlines or special characters.".to_string(),
+        ));
+    }
+    let nombre = (!nombre.is_empty()).then(|| nombre.texto().into_owned());
+    escribir_cliente(client)?.set_nombre(nombre);
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Responde a `CLIENT NO-EVICT on|off`. El nodo nunca desaloja clientes
+/// por memoria (solo desaloja claves y desconecta a los que superan
+/// `client_output_buffer_limit`, de lo que NO-EVICT no exime), por lo que
+/// solo se acepta `off` y se rechaza `on` en lugar de ignorarlo
+fn client_no_evict(tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&CMD_CLIENT_NO_EVICT.to_lowercase(), 3, tokens.len())?;
+    match tokens[2].to_lowercase().as_str() {
+        "off" => DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string()),
+        "on" => Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "CLIENT NO-EVICT on is not supported, clients are never evicted".to_string(),
+        )),
+        _ => Err(error_sintaxis()),
+    }
+}
+
+/// Parsea los pares `filtro valor` de la forma nueva de CLIENT KILL
+fn parsear_filtro_kill(argumentos: &[Token]) -> Result<FiltroKill, DatoRedis> {
+    if !argumentos.len().is_multiple_of(2) {
+        return Err(error_sintaxis());
+    }
+    let mut filtro = FiltroKill {
+        incluir_propio: false,
+        ..FiltroKill::default()
+    };
+    for par in argumentos.chunks(2) {
+        let valor = &par[1];
+        match par[0].to_uppercase().as_str() {
+            FILTRO_ID => filtro.id = Some(parsear_id(valor)?),
+            FILTRO_ADDR => filtro.addr = Some(valor.texto().into_owned()),
+            FILTRO_USER => filtro.usuario = Some(valor.texto().into_owned()),
+            FILTRO_SKIPME => {
+                filtro.incluir_propio = match valor.to_lowercase().as_str() {
+                    "yes" => false,
+                    "no" => true,
+                    _ => return Err(error_sintaxis()),
+                }
+            }
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    Ok(filtro)
+}
+
+/// Describe un cliente en el formato de una linea de CLIENT LIST
+fn describir_cliente(client: &Arc<RwLock<Client>>) -> Result<String, DatoRedis> {
+    let cliente = leer_cliente(client)?;
+    let mut flags = if cliente.get_modo_pub_sub() {
+        "P".to_string()
+    } else {
+        "N".to_string()
+    };
+    if cliente.get_monitor() {
+        flags = "O".to_string();
+    }
+    Ok(format!(
+        "id={} addr={} name={} age={} idle={} flags={} sub={} psub={} ssub={} omem={} cmd={} user={}",
+        cliente.get_numero(),
+        cliente.client_id(),
+        cliente.get_nombre().map(String::as_str).unwrap_or(""),
+        cliente.segundos_conectado(),
+        cliente.segundos_inactivo(),
+        flags,
+        cliente.get_channels().len(),
+        cliente.get_pchannels().len(),
+        cliente.get_schannels().len(),
+        cliente.get_buffer_salida(),
+        cliente
+            .get_ultimo_comando()
+            .map(String::as_str)
+            .unwrap_or("NULL"),
+        cliente
+            .get_usuario()
+            .map(String::as_str)
+            .unwrap_or("default"),
+    ))
+}
+
+/// Parsea las opciones de CLIENT TRACKING on
+fn parsear_opciones_tracking(argumentos: &[Token]) -> Result<OpcionesTracking, DatoRedis> {
+    let mut opciones = OpcionesTracking::default();
+    let mut argumentos = argumentos.iter();
+    while let Some(argumento) = argumentos.next() {
+        match argumento.to_uppercase().as_str() {
+            OPCION_REDIRECT => {
+                let id = argumentos.next().ok_or_else(error_sintaxis)?;
+                opciones.redirect = Some(parsear_id(id)?);
+            }
+            OPCION_PREFIX => {
+                let prefijo = argumentos.next().ok_or_else(error_sintaxis)?;
+                opciones.prefijos.push(prefijo.clone());
+            }
+            OPCION_BCAST => opciones.bcast = true,
+            OPCION_OPTIN => opciones.optin = true,
+            OPCION_OPTOUT => opciones.optout = true,
+            OPCION_NOLOOP => opciones.noloop = true,
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    if !opciones.bcast && !opciones.prefijos.is_empty() {
+        return Err(error("PREFIX option requires BCAST mode to be enabled"));
+    }
+    if opciones.optin && opciones.optout {
+        return Err(error("You can't use both OPTIN and OPTOUT"));
+    }
+    if opciones.bcast && (opciones.optin || opciones.optout) {
+        return Err(error("OPTIN and OPTOUT are not compatible with BCAST"));
+    }
+    Ok(opciones)
+}
+
+fn parsear_id(id: &Token) -> Result<u64, DatoRedis> {
+    id.parse::<u64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!("Invalid client ID '{}'", id.texto()),
+        )
+    })
+}
+
+fn error_sintaxis() -> DatoRedis {
+    error("syntax error")
+}
+
+fn error(mensaje: &str) -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), mensaje.to_string())
+}
+
+fn leer_cliente(
+    client: &Arc<RwLock<Client>>,
+) -> Result<std::sync::RwLockReadGuard<'_, Client>, DatoRedis> {
+    client.read().map_err(|_| {
+        DatoRedis::new_simple_error("ERR".to_string(), "client lock error".to_string())
+    })
+}
+
+fn escribir_cliente(
+    client: &Arc<RwLock<Client>>,
+) -> Result<std::sync::RwLockWriteGuard<'_, Client>, DatoRedis> {
+    client.write().map_err(|_| {
+        DatoRedis::new_simple_error("ERR".to_string(), "client lock error".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_role::NodeRole;
+    use logger::logger::Logger;
+    use std::net::{TcpListener, TcpStream};
+
+    fn make_node() -> Node {
+        NodeBuilder::para_pruebas().build().unwrap()
+    }
+
+    fn make_client(addr: &str) -> Arc<RwLock<Client>> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let cliente = Client::new(
+            addr.to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        );
+        Arc::new(RwLock::new(cliente))
+    }
+
+    fn ejecutar(node: &Node, client: &Arc<RwLock<Client>>, tokens: &[&str]) -> DatoRedis {
+        let tokens: Vec<Token> = tokens.iter().map(|s| Token::from(*s)).collect();
+        node.client(&tokens, client).unwrap_or_else(|e| e)
+    }
+
+    fn texto(dato: DatoRedis) -> String {
+        match dato {
+            DatoRedis::BulkString(bulk) => bulk.contenido(),
+            otro => panic!("Se esperaba un bulk string: {otro:?}"),
+        }
+    }
+
+    #[test]
+    fn test_01_setname_y_getname() {
+        let node = make_node();
+        let client = make_client("127.0.0.1:5000");
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "getname"]),
+            DatoRedis::new_null()
+        );
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "setname", "worker"]),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "getname"]),
+            DatoRedis::new_bulk_string("worker".to_string()).unwrap()
+        );
+        assert!(matches!(
+            ejecutar(&node, &client, &["client", "setname", "con espacio"]),
+            DatoRedis::SimpleError(_)
+        ));
+    }
+
+    #[test]
+    fn test_02_list_muestra_los_clientes_registrados() {
+        let node = make_node();
+        let primero = make_client("127.0.0.1:5001");
+        let segundo = make_client("127.0.0.1:5002");
+        node.clientes.registrar(primero.clone());
+        node.clientes.registrar(segundo.clone());
+        primero
+            .write()
+            .unwrap()
+            .registrar_comando("client".to_string());
+
+        let lista = texto(ejecutar(&node, &primero, &["client", "list"]));
+        assert_eq!(lista.lines().count(), 2);
+        assert!(lista.contains("addr=127.0.0.1:5001"));
+        assert!(lista.contains("cmd=client"));
+
+        let id = segundo.read().unwrap().get_numero().to_string();
+        let filtrada = texto(ejecutar(&node, &primero, &["client", "list", "id", &id]));
+        assert_eq!(filtrada.lines().count(), 1);
+        assert!(filtrada.contains("addr=127.0.0.1:5002"));
+    }
+
+    #[test]
+    fn test_03_kill_por_addr_y_por_filtros() {
+        let node = make_node();
+        let propio = make_client("127.0.0.1:5003");
+        let otro = make_client("127.0.0.1:5004");
+        node.clientes.registrar(propio.clone());
+        node.clientes.registrar(otro.clone());
+
+        assert_eq!(
+            ejecutar(&node, &propio, &["client", "kill", "127.0.0.1:5004"]),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert!(matches!(
+            ejecutar(&node, &propio, &["client", "kill", "127.0.0.1:9999"]),
+            DatoRedis::SimpleError(_)
+        ));
+        assert_eq!(
+            ejecutar(
+                &node,
+                &propio,
+                &["client", "kill", "addr", "127.0.0.1:5003"]
+            ),
+            DatoRedis::new_integer(0)
+        );
+        assert_eq!(
+            ejecutar(
+                &node,
+                &propio,
+                &["client", "kill", "addr", "127.0.0.1:5003", "skipme", "no"]
+            ),
+            DatoRedis::new_integer(1)
+        );
+    }
+
+    #[test]
+    fn test_04_pause_de_escritura() {
+        let node = make_node();
+        let client = make_client("127.0.0.1:5005");
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "pause", "10000", "write"]),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert!(node.clientes.pausado(true));
+        assert!(!node.clientes.pausado(false));
+        ejecutar(&node, &client, &["client", "unpause"]);
+        assert!(!node.clientes.pausado(true));
+    }
+
+    #[test]
+    fn test_05_tracking_valida_opciones() {
+        let node = make_node();
+        let client = make_client("127.0.0.1:5006");
+        let otro = make_client("127.0.0.1:5007");
+        node.clientes.registrar(otro.clone());
+        let ok = DatoRedis::new_simple_string("OK".to_string()).unwrap();
+        let es_error =
+            |tokens: &[&str]| matches!(ejecutar(&node, &client, tokens), DatoRedis::SimpleError(_));
+
+        assert!(es_error(&["client", "tracking", "on", "prefix", "a"]));
+        assert!(es_error(&["client", "tracking", "on", "optin", "optout"]));
+        assert!(es_error(&["client", "tracking", "on", "bcast", "optin"]));
+        assert!(es_error(&[
+            "client", "tracking", "on", "redirect", "999999"
+        ]));
+        assert!(es_error(&["client", "caching", "yes"]));
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "getredirect"]),
+            DatoRedis::new_integer(-1)
+        );
+        assert_eq!(ejecutar(&node, &client, &["client", "tracking", "on"]), ok);
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "getredirect"]),
+            DatoRedis::new_integer(0)
+        );
+
+        let id = otro.read().unwrap().get_numero().to_string();
+        assert_eq!(
+            ejecutar(
+                &node,
+                &client,
+                &["client", "tracking", "on", "redirect", &id, "optin"]
+            ),
+            ok
+        );
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "getredirect"]),
+            DatoRedis::new_integer(id.parse().unwrap())
+        );
+        assert_eq!(ejecutar(&node, &client, &["client", "caching", "yes"]), ok);
+        assert!(es_error(&["client", "caching", "no"]));
+        assert!(es_error(&[
+            "client", "tracking", "on", "bcast", "redirect", &id
+        ]));
+
+        assert_eq!(ejecutar(&node, &client, &["client", "tracking", "off"]), ok);
+        assert!(!node.tracking.hay_clientes());
+    }
+
+    #[test]
+    fn test_06_no_evict_on_se_rechaza() {
+        let node = make_node();
+        let client = make_client("127.0.0.1:5008");
+        assert_eq!(
+            ejecutar(&node, &client, &["client", "no-evict", "off"]),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert!(matches!(
+            ejecutar(&node, &client, &["client", "no-evict", "on"]),
+            DatoRedis::SimpleError(_)
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+//! Este modulo contiene la implementacion del comando COMMAND, que describe
+//! los comandos soportados por el nodo a partir de la tabla de comandos
+use std::sync::{Arc, RwLock};
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::map_reply::MapReply;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::*;
+use crate::comandos::tabla_comandos::{
+    FLAG_MOVABLEKEYS, InfoComando, TABLA_COMANDOS, buscar_comando,
+};
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido,
+};
+use crate::node::Node;
+
+impl Node {
+    /// Describe los comandos soportados por el nodo
+    /// (`COMMAND`, `COMMAND COUNT`, `COMMAND INFO [comando ...]`,
+    /// `COMMAND DOCS [comando ...]`, `COMMAND GETKEYS comando [arg ...]`)
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - La descripcion de los comandos pedidos (todos si no se indica
+    ///   ninguno), su cantidad, su documentacion o las claves de un comando,
+    ///   segun el subcomando
+    pub(crate) fn command(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        let Some(subcomando) = tokens.get(1) else {
+            return describir_comandos(TABLA_COMANDOS.iter().map(Some));
+        };
+        match subcomando.to_uppercase().as_str() {
+            CMD_COMMAND_COUNT => {
+                assert_number_of_arguments_distinct(
+                    &CMD_COMMAND_COUNT.to_lowercase(),
+                    2,
+                    tokens.len(),
+                )?;
+                Ok(DatoRedis::new_integer(TABLA_COMANDOS.len() as i64))
+            }
+            CMD_COMMAND_INFO if tokens.len() == 2 => {
+                describir_comandos(TABLA_COMANDOS.iter().map(Some))
+            }
+            CMD_COMMAND_INFO => describir_comandos(
+                tokens[2..]
+                    .iter()
+                    .map(|nombre| buscar_comando(&nombre.texto())),
+            ),
+            CMD_COMMAND_DOCS => {
+                let comandos: Vec<&InfoComando> = if tokens.len() == 2 {
+                    TABLA_COMANDOS.iter().collect()
+                } else {
+                    tokens[2..]
+                        .iter()
+                        .filter_map(|nombre| buscar_comando(&nombre.texto()))
+                        .collect()
+                };
+                documentar_comandos(comandos)
+            }
+            CMD_COMMAND_GETKEYS => {
+                assert_correct_arguments_quantity(
+                    &CMD_COMMAND_GETKEYS.to_lowercase(),
+                    3,
+                    tokens.len(),
+                )?;
+                obtener_claves(&tokens[2..])
+            }
+            _ => Err(error_subcomando_desconocido(&subcomando.texto())),
+        }
+    }
+}
+
+/// Describe cada comando con el formato de COMMAND INFO: nombre, aridad,
+/// flags, primera clave, ultima clave, paso y categorias. Los comandos
+/// desconocidos se describen como null
+fn describir_comandos<'a>(
+    comandos: impl Iterator<Item = Option<&'a InfoComando>>,
+) -> Result<DatoRedis, DatoRedis> {
+    let descripciones = comandos
+        .map(|info| match info {
+            Some(info) => describir_comando(info),
+            None => Ok(DatoRedis::new_null()),
+        })
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    Ok(array_de(descripciones))
+}
+
+fn describir_comando(info: &InfoComando) -> Result<DatoRedis, DatoRedis> {
+    let mut flags = info
+        .flags
+        .iter()
+        .map(|flag| DatoRedis::new_simple_string(flag.to_string()))
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    if info.tiene_claves_movibles() {
+        flags.push(DatoRedis::new_simple_string(FLAG_MOVABLEKEYS.to_string())?);
+    }
+    Ok(array_de(vec![
+        DatoRedis::new_bulk_string(info.nombre.to_lowercase())?,
+        DatoRedis::new_integer(info.aridad),
+        array_de(flags),
+        DatoRedis::new_integer(info.primera_clave),
+        DatoRedis::new_integer(info.ultima_clave),
+        DatoRedis::new_integer(info.paso),
+        array_de(vec![DatoRedis::new_simple_string(format!(
+            "@{}",
+            info.grupo
+        ))?]),
+    ]))
+}
+
+/// Documenta los comandos con el formato de COMMAND DOCS: un mapa del
+/// nombre de cada comando a su resumen y su grupo
+fn documentar_comandos(comandos: Vec<&InfoComando>) -> Result<DatoRedis, DatoRedis> {
+    let mut documentacion = MapReply::new();
+    for info in comandos {
+        let mut campos = MapReply::new();
+        campos.insert(
+            DatoRedis::new_bulk_string("summary".to_string())?,
+            DatoRedis::new_bulk_string(info.resumen.to_string())?,
+        );
+        campos.insert(
+            DatoRedis::new_bulk_string("group".to_string())?,
+            DatoRedis::new_bulk_string(info.grupo.to_string())?,
+        );
+        documentacion.insert(
+            DatoRedis::new_bulk_string(info.nombre.to_lowercase())?,
+            DatoRedis::new_map_reply_with_content(campos),
+        );
+    }
+    Ok(DatoRedis::new_map_reply_with_content(documentacion))
+}
+
+/// Obtiene las claves de un comando completo (COMMAND GETKEYS)
+///
+/// # Retorna
+/// - Array con las claves, error de redis si el comando no existe, no
+///   respeta su aridad o no recibe claves
+fn obtener_claves(comando: &[Token]) -> Result<DatoRedis, DatoRedis> {
+    let info = buscar_comando(&comando[0].texto()).ok_or_else(|| {
+        DatoRedis::new_simple_error("ERR".to_string(), "Invalid command specified".to_string())
+    })?;
+    if !info.aridad_valida(comando.len()) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Invalid number of arguments specified for command".to_string(),
+        ));
+    }
+    let claves = info
+        .indices_claves(comando)
+        .into_iter()
+        .filter_map(|indice| comando.get(indice))
+        .map(|clave| DatoRedis::new_bulk_string_desde_bytes(clave.to_vec()))
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    if claves.is_empty() {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "The command has no key arguments".to_string(),
+        ));
+    }
+    Ok(array_de(claves))
+}
+
+fn array_de(contenido: Vec<DatoRedis>) -> DatoRedis {
+    DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(contenido))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+
+    use logger::logger::Logger;
+
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_role::NodeRole;
+
+    fn ejecutar(comando: &[&str]) -> Result<DatoRedis, DatoRedis> {
+        let node = NodeBuilder::para_pruebas().build().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let client = Arc::new(RwLock::new(Client::new(
+            "127.0.0.1:5000".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        )));
+        let tokens: Vec<Token> = comando.iter().map(|s| Token::from(*s)).collect();
+        node.command(&tokens, &client)
+    }
+
+    fn bulks(valores: &[&str]) -> DatoRedis {
+        array_de(
+            valores
+                .iter()
+                .map(|v| DatoRedis::new_bulk_string(v.to_string()).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test01_count_e_info() {
+        assert_eq!(
+            ejecutar(&["COMMAND", "COUNT"]).unwrap(),
+            DatoRedis::new_integer(TABLA_COMANDOS.len() as i64)
+        );
+
+        let DatoRedis::Arrays(info) = ejecutar(&["COMMAND", "INFO", "get", "noexiste"]).unwrap()
+        else {
+            panic!("Se esperaba un array");
+        };
+        let simple = |s: &str| DatoRedis::new_simple_string(s.to_string()).unwrap();
+        assert_eq!(
+            info.get(0),
+            Some(array_de(vec![
+                DatoRedis::new_bulk_string("get".to_string()).unwrap(),
+                DatoRedis::new_integer(2),
+                array_de(vec![simple("readonly"), simple("fast")]),
+                DatoRedis::new_integer(1),
+                DatoRedis::new_integer(1),
+                DatoRedis::new_integer(1),
+                array_de(vec![simple("@string")]),
+            ]))
+        );
+        assert_eq!(info.get(1), Some(DatoRedis::new_null()));
+    }
+
+    #[test]
+    fn test02_getkeys() {
+        assert_eq!(
+            ejecutar(&["COMMAND", "GETKEYS", "MSET", "a", "1", "b", "2"]).unwrap(),
+            bulks(&["a", "b"])
+        );
+        assert_eq!(
+            ejecutar(&["COMMAND", "GETKEYS", "EVAL", "return 1", "1", "k", "arg"]).unwrap(),
+            bulks(&["k"])
+        );
+        assert!(ejecutar(&["COMMAND", "GETKEYS", "GET"]).is_err());
+        assert!(ejecutar(&["COMMAND", "GETKEYS", "PING"]).is_err());
+        assert!(ejecutar(&["COMMAND", "GETKEYS", "NOEXISTE", "a"]).is_err());
+    }
+
+    #[test]
+    fn test03_docs() {
+        let DatoRedis::Map(docs) = ejecutar(&["COMMAND", "DOCS", "ping", "noexiste"]).unwrap()
+        else {
+            panic!("Se esperaba un mapa");
+        };
+        let entradas: Vec<_> = docs.iter().collect();
+        assert_eq!(entradas.len(), 1);
+        assert_eq!(
+            entradas[0].0,
+            &DatoRedis::new_bulk_string("ping".to_string()).unwrap()
+        );
+    }
+}
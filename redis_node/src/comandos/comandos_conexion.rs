@@ -0,0 +1,186 @@
+//! Este modulo contiene la implementacion de los comandos de conexion
+//! PING, ECHO, QUIT y la parte de RESET que restablece el estado del
+//! cliente en el nodo
+use std::sync::{Arc, RwLock};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::{CMD_ECHO, CMD_PING, OPERACION_EXITOSA};
+use crate::comandos::utils::assert_number_of_arguments_distinct;
+use crate::node::Node;
+
+/// Respuesta de PING sin argumentos
+const RESPUESTA_PING: &str = "PONG";
+/// Usuario con el que queda autenticada una conexion luego de RESET
+const USUARIO_DEFAULT: &str = "default";
+/// Tiempo maximo que QUIT espera a que se envie la respuesta antes de
+/// cerrar la conexion
+const ESPERA_QUIT: Duration = Duration::from_secs(1);
+/// Intervalo con el que QUIT verifica si la respuesta ya se envio
+const INTERVALO_QUIT: Duration = Duration::from_millis(1);
+
+impl Node {
+    /// Verifica que el nodo responda
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando y opcionalmente un
+    ///   mensaje
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - PONG, o el mensaje recibido como bulk string, error de redis si se
+    ///   reciben mas argumentos
+    pub(crate) fn ping(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        match tokens {
+            [_] => DatoRedis::new_simple_string(RESPUESTA_PING.to_string()),
+            [_, mensaje] => DatoRedis::new_bulk_string_desde_bytes(mensaje.to_vec()),
+            _ => Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                format!(
+                    "wrong number of arguments for '{}' command",
+                    CMD_PING.to_lowercase()
+                ),
+            )),
+        }
+    }
+
+    /// Devuelve el mensaje recibido
+    ///
+    /// # Retorna
+    /// - Bulk string con el mensaje, error de redis si la cantidad de
+    ///   argumentos es incorrecta
+    pub(crate) fn echo(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_ECHO.to_lowercase(), 2, tokens.len())?;
+        DatoRedis::new_bulk_string_desde_bytes(tokens[1].to_vec())
+    }
+
+    /// Responde OK a un pedido de cierre de la conexion. El cierre lo
+    /// realiza `cerrar_tras_responder` una vez encolada la respuesta
+    pub(crate) fn quit(
+        &self,
+        _tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Cierra la conexion de un cliente luego de que el hilo escritor envie
+    /// las respuestas pendientes, esperando como maximo `ESPERA_QUIT`. El
+    /// hilo lector detecta el cierre y libera los recursos del cliente
+    pub(crate) fn cerrar_tras_responder(&self, client: &Arc<RwLock<Client>>) {
+        let Ok(salida) = client.read().map(|cli| cli.get_salida()) else {
+            return;
+        };
+        let inicio = Instant::now();
+        while salida.buffer().pendientes() > 0 && inicio.elapsed() < ESPERA_QUIT {
+            sleep(INTERVALO_QUIT);
+        }
+        if let Ok(cli) = client.read() {
+            cli.cerrar_conexion();
+        }
+    }
+
+    /// Restablece el estado de la conexion que mantiene el nodo para RESET:
+    /// quita el nombre, desactiva el tracking y MONITOR, y vuelve
+    /// al usuario por defecto. Las suscripciones las quita el broker de
+    /// pub/sub
+    pub(crate) fn reiniciar_conexion(&self, client: &Arc<RwLock<Client>>) {
+        let Ok(mut cli) = client.write() else {
+            return;
+        };
+        cli.set_nombre(None);
+        cli.set_monitor(false);
+        cli.set_usuario(USUARIO_DEFAULT.to_string());
+        let numero = cli.get_numero();
+        drop(cli);
+        self.clientes.quitar_monitor(numero);
+        self.desactivar_tracking(numero);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+
+    use logger::logger::Logger;
+
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_role::NodeRole;
+
+    fn crear_nodo() -> Node {
+        NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .build()
+            .unwrap()
+    }
+
+    fn cliente() -> (Arc<RwLock<Client>>, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (remoto, _) = listener.accept().unwrap();
+        let cliente = Client::new(
+            "127.0.0.1:5000".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        );
+        (Arc::new(RwLock::new(cliente)), remoto)
+    }
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test01_ping_y_echo() {
+        let node = crear_nodo();
+        let (client, _remoto) = cliente();
+        assert_eq!(
+            node.ping(&tokens(&["PING"]), &client).unwrap(),
+            DatoRedis::new_simple_string("PONG".to_string()).unwrap()
+        );
+        assert_eq!(
+            node.ping(&tokens(&["PING", "hola"]), &client).unwrap(),
+            DatoRedis::new_bulk_string("hola".to_string()).unwrap()
+        );
+        assert!(node.ping(&tokens(&["PING", "a", "b"]), &client).is_err());
+        assert_eq!(
+            node.echo(&tokens(&["ECHO", "hola"]), &client).unwrap(),
+            DatoRedis::new_bulk_string("hola".to_string()).unwrap()
+        );
+        assert!(node.echo(&tokens(&["ECHO"]), &client).is_err());
+    }
+
+    #[test]
+    fn test02_reiniciar_conexion() {
+        let node = crear_nodo();
+        let (client, _remoto) = cliente();
+        {
+            let mut cli = client.write().unwrap();
+            cli.set_nombre(Some("conexion".to_string()));
+            cli.set_usuario("otro".to_string());
+            cli.set_monitor(true);
+        }
+        node.clientes.agregar_monitor(client.clone());
+
+        node.reiniciar_conexion(&client);
+        let cli = client.read().unwrap();
+        assert_eq!(cli.get_nombre(), None);
+        assert!(!cli.get_monitor());
+        assert_eq!(cli.get_usuario().map(String::as_str), Some(USUARIO_DEFAULT));
+        assert!(!node.clientes.hay_monitores());
+    }
+}
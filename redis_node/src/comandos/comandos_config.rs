@@ -0,0 +1,545 @@
+//! Este modulo contiene la implementacion del comando CONFIG, que permite
+//! consultar y modificar en ejecucion la configuracion del nodo
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+
+use glob::Pattern;
+use logger::logger::NivelLog;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::map_reply::MapReply;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::buffer_salida::LimitesBuffer;
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::*;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido, get_storage_write_lock,
+};
+use crate::config::config_parser::Config;
+use crate::memoria::{PoliticaEviction, parsear_memoria};
+use crate::node::Node;
+use crate::notificaciones::parsear_flags;
+
+const PARAM_IP: &str = "ip";
+const PARAM_PORT: &str = "port";
+const PARAM_SLOT_RANGE_START: &str = "slot_range_start";
+const PARAM_SLOT_RANGE_END: &str = "slot_range_end";
+const PARAM_SAVE: &str = "save";
+const PARAM_NODE_TIMEOUT: &str = "node_timeout";
+const PARAM_MAX_CLIENTS: &str = "max_clients";
+const PARAM_LOGLEVEL: &str = "loglevel";
+const PARAM_APPENDONLY: &str = "appendonly";
+const PARAM_MAXMEMORY: &str = "maxmemory";
+const PARAM_MAXMEMORY_POLICY: &str = "maxmemory_policy";
+const PARAM_SLOWLOG_LOG_SLOWER_THAN: &str = "slowlog_log_slower_than";
+const PARAM_SLOWLOG_MAX_LEN: &str = "slowlog_max_len";
+const PARAM_LATENCY_MONITOR_THRESHOLD: &str = "latency_monitor_threshold";
+const PARAM_NOTIFY_KEYSPACE_EVENTS: &str = "notify_keyspace_events";
+const PARAM_CLIENT_OUTPUT_BUFFER_LIMIT: &str = "client_output_buffer_limit";
+
+/// Parametros que pueden modificarse en ejecucion con CONFIG SET. Son
+/// tambien los que CONFIG REWRITE escribe en el archivo de configuracion
+const PARAMETROS_MODIFICABLES: [&str; 12] = [
+    PARAM_SAVE,
+    PARAM_NODE_TIMEOUT,
+    PARAM_MAX_CLIENTS,
+    PARAM_LOGLEVEL,
+    PARAM_APPENDONLY,
+    PARAM_MAXMEMORY,
+    PARAM_MAXMEMORY_POLICY,
+    PARAM_SLOWLOG_LOG_SLOWER_THAN,
+    PARAM_SLOWLOG_MAX_LEN,
+    PARAM_LATENCY_MONITOR_THRESHOLD,
+    PARAM_NOTIFY_KEYSPACE_EVENTS,
+    PARAM_CLIENT_OUTPUT_BUFFER_LIMIT,
+];
+
+/// Parametros que solo pueden consultarse
+const PARAMETROS_INMUTABLES: [&str; 4] = [
+    PARAM_IP,
+    PARAM_PORT,
+    PARAM_SLOT_RANGE_START,
+    PARAM_SLOT_RANGE_END,
+];
+
+/// Valor ya validado de un parametro recibido en CONFIG SET
+enum ValorConfig {
+    Save(u64),
+    NodeTimeout(u64),
+    MaxClients(usize),
+    LogLevel(NivelLog),
+    AppendOnly(bool),
+    MaxMemory(usize),
+    MaxMemoryPolicy(PoliticaEviction),
+    SlowlogLogSlowerThan(i64),
+    SlowlogMaxLen(usize),
+    LatencyMonitorThreshold(u64),
+    NotifyKeyspaceEvents(String),
+    ClientOutputBufferLimit(LimitesBuffer),
+}
+
+impl Node {
+    /// Ejecuta un subcomando de CONFIG
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - Respuesta del subcomando, error de redis si el subcomando no existe
+    ///   o sus argumentos son invalidos
+    pub(crate) fn config(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CONFIG.to_lowercase(), 2, tokens.len())?;
+        match tokens[1].to_uppercase().as_str() {
+            CMD_CONFIG_GET => self.config_get(tokens),
+            CMD_CONFIG_SET => self.config_set(tokens),
+            CMD_CONFIG_REWRITE => self.config_rewrite(tokens),
+            CMD_CONFIG_RESETSTAT => self.config_resetstat(tokens),
+            _ => Err(error_subcomando_desconocido(&tokens[1].texto())),
+        }
+    }
+
+    /// Devuelve los parametros cuyo nombre coincide con alguno de los
+    /// patrones glob recibidos (`CONFIG GET patron [patron ...]`)
+    fn config_get(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_CONFIG_GET.to_lowercase(), 3, tokens.len())?;
+        let patrones = tokens[2..]
+            .iter()
+            .map(|patron| Pattern::new(&patron.to_lowercase()))
+            .collect::<Result<Vec<Pattern>, _>>()
+            .map_err(|_| {
+                DatoRedis::new_simple_error("ERR".to_string(), "invalid pattern".to_string())
+            })?;
+
+        let mut reply = MapReply::new();
+        for nombre in PARAMETROS_INMUTABLES
+            .iter()
+            .chain(PARAMETROS_MODIFICABLES.iter())
+        {
+            if patrones.iter().any(|patron| patron.matches(nombre)) {
+                reply.insert(
+                    DatoRedis::new_bulk_string(nombre.to_string())?,
+                    DatoRedis::new_bulk_string(self.valor_parametro(nombre)?)?,
+                );
+            }
+        }
+        Ok(DatoRedis::new_map_reply_with_content(reply))
+    }
+
+    /// Modifica uno o mas parametros (`CONFIG SET parametro valor ...`). Los
+    /// valores se validan todos antes de aplicar alguno
+    fn config_set(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        let argumentos = &tokens[2..];
+        if argumentos.is_empty() || !argumentos.len().is_multiple_of(2) {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "wrong number of arguments for 'config|set' command".to_string(),
+            ));
+        }
+
+        let valores = argumentos
+            .chunks(2)
+            .map(|par| parsear_valor(&par[0].to_lowercase(), &par[1].texto()))
+            .collect::<Result<Vec<ValorConfig>, DatoRedis>>()?;
+        for valor in valores {
+            self.aplicar_valor(valor)?;
+        }
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Escribe la configuracion efectiva en el archivo del que se cargo el
+    /// nodo, preservando sus comentarios
+    fn config_rewrite(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_CONFIG_REWRITE.to_lowercase(), 2, tokens.len())?;
+        let path = self
+            .config_path
+            .read()
+            .ok()
+            .and_then(|path| path.clone())
+            .ok_or_else(|| {
+                DatoRedis::new_simple_error(
+                    "ERR".to_string(),
+                    "The server is running without a config file".to_string(),
+                )
+            })?;
+
+        let valores = PARAMETROS_MODIFICABLES
+            .iter()
+            .map(|nombre| Ok((nombre.to_string(), self.valor_parametro(nombre)?)))
+            .collect::<Result<Vec<(String, String)>, DatoRedis>>()?;
+        Config::reescribir(&path, &valores)
+            .map_err(|e| DatoRedis::new_simple_error("ERR".to_string(), e))?;
+        self.logger.info("Configuración reescrita", "Config");
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Reinicia las estadisticas que se muestran en INFO
+    fn config_resetstat(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_CONFIG_RESETSTAT.to_lowercase(), 2, tokens.len())?;
+        self.estadisticas.reiniciar();
+        self.pub_sub.buffers().reiniciar();
+        self.pub_sub.rutas().reiniciar();
+        get_storage_write_lock(&self.storage)?.reiniciar_estadisticas();
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Obtiene el valor actual de un parametro de configuracion
+    fn valor_parametro(&self, nombre: &str) -> Result<String, DatoRedis> {
+        let valor = match nombre {
+            PARAM_IP => self.cli_addr.ip().to_string(),
+            PARAM_PORT => self.cli_addr.port().to_string(),
+            PARAM_SLOT_RANGE_START => self.slot_range.start.to_string(),
+            PARAM_SLOT_RANGE_END => self.slot_range.end.to_string(),
+            PARAM_SAVE => self.save_interval.load(Ordering::SeqCst).to_string(),
+            PARAM_NODE_TIMEOUT => self.get_node_timeout().to_string(),
+            PARAM_MAX_CLIENTS => self.max_client_capacity.load(Ordering::SeqCst).to_string(),
+            PARAM_LOGLEVEL => self.logger.nivel().nombre().to_string(),
+            PARAM_APPENDONLY => si_no(self.appendonly.load(Ordering::SeqCst)).to_string(),
+            PARAM_MAXMEMORY => get_storage_write_lock(&self.storage)?
+                .get_maxmemory()
+                .to_string(),
+            PARAM_MAXMEMORY_POLICY => get_storage_write_lock(&self.storage)?
+                .get_politica()
+                .nombre()
+                .to_string(),
+            PARAM_SLOWLOG_LOG_SLOWER_THAN => self.slowlog.umbral_us().to_string(),
+            PARAM_SLOWLOG_MAX_LEN => self.slowlog.max_len().to_string(),
+            PARAM_LATENCY_MONITOR_THRESHOLD => self.latencia.umbral_ms().to_string(),
+            PARAM_NOTIFY_KEYSPACE_EVENTS => self.notificaciones.flags(),
+            PARAM_CLIENT_OUTPUT_BUFFER_LIMIT => self.pub_sub.buffers().limites().to_string(),
+            _ => return Err(error_parametro_desconocido(nombre)),
+        };
+        Ok(valor)
+    }
+
+    /// Aplica un valor ya validado a la configuracion en ejecucion
+    fn aplicar_valor(&self, valor: ValorConfig) -> Result<(), DatoRedis> {
+        match valor {
+            ValorConfig::Save(ms) => self.save_interval.store(ms, Ordering::SeqCst),
+            ValorConfig::NodeTimeout(ms) => self.node_timeout.store(ms, Ordering::SeqCst),
+            ValorConfig::MaxClients(max) => self.max_client_capacity.store(max, Ordering::SeqCst),
+            ValorConfig::LogLevel(nivel) => self.logger.set_nivel(nivel),
+            ValorConfig::AppendOnly(habilitado) => {
+                self.appendonly.store(habilitado, Ordering::SeqCst)
+            }
+            ValorConfig::MaxMemory(maxmemory) => {
+                let mut storage = get_storage_write_lock(&self.storage)?;
+                let politica = storage.get_politica();
+                storage.configurar_memoria(maxmemory, politica);
+            }
+            ValorConfig::MaxMemoryPolicy(politica) => {
+                let mut storage = get_storage_write_lock(&self.storage)?;
+                let maxmemory = storage.get_maxmemory();
+                storage.configurar_memoria(maxmemory, politica);
+            }
+            ValorConfig::SlowlogLogSlowerThan(umbral) => self.slowlog.set_umbral_us(umbral),
+            ValorConfig::SlowlogMaxLen(max_len) => self.slowlog.set_max_len(max_len),
+            ValorConfig::LatencyMonitorThreshold(umbral) => self.latencia.set_umbral_ms(umbral),
+            ValorConfig::NotifyKeyspaceEvents(flags) => self
+                .configurar_notificaciones(&flags)
+                .map_err(|e| DatoRedis::new_simple_error("ERR".to_string(), e))?,
+            ValorConfig::ClientOutputBufferLimit(limites) => {
+                self.pub_sub.buffers().set_limites(limites)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Valida el valor recibido para un parametro de CONFIG SET
+///
+/// # Parámetros
+/// * `nombre`: nombre del parametro en minusculas
+/// * `valor`: valor recibido
+///
+/// # Retorna
+/// - Valor parseado, error de redis si el parametro no existe, no puede
+///   modificarse o el valor es invalido
+fn parsear_valor(nombre: &str, valor: &str) -> Result<ValorConfig, DatoRedis> {
+    let error_valor = |motivo: &str| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!("CONFIG SET failed (possibly related to argument '{nombre}') - {motivo}"),
+        )
+    };
+    let entero = |minimo: u64| {
+        valor
+            .parse::<u64>()
+            .ok()
+            .filter(|n| *n >= minimo)
+            .ok_or_else(|| error_valor("argument couldn't be parsed into an integer"))
+    };
+
+    match nombre {
+        PARAM_SAVE => entero(1).map(ValorConfig::Save),
+        // Mismo minimo que se exige al leer el archivo de configuracion
+        PARAM_NODE_TIMEOUT => entero(1001).map(ValorConfig::NodeTimeout),
+        PARAM_MAX_CLIENTS => entero(1).map(|n| ValorConfig::MaxClients(n as usize)),
+        PARAM_LOGLEVEL => NivelLog::from_nombre(valor)
+            .map(ValorConfig::LogLevel)
+            .ok_or_else(|| {
+                error_valor("argument(s) must be one of the following: debug, info, warning, error")
+            }),
+        PARAM_APPENDONLY => match valor.to_lowercase().as_str() {
+            "yes" => Ok(ValorConfig::AppendOnly(true)),
+            "no" => Ok(ValorConfig::AppendOnly(false)),
+            _ => Err(error_valor("argument must be 'yes' or 'no'")),
+        },
+        PARAM_MAXMEMORY => parsear_memoria(valor)
+            .map(ValorConfig::MaxMemory)
+            .map_err(|_| error_valor("argument must be a memory value")),
+        PARAM_MAXMEMORY_POLICY => PoliticaEviction::from_nombre(valor)
+            .map(ValorConfig::MaxMemoryPolicy)
+            .ok_or_else(|| error_valor("argument is not a valid eviction policy")),
+        PARAM_SLOWLOG_LOG_SLOWER_THAN => valor
+            .parse::<i64>()
+            .map(ValorConfig::SlowlogLogSlowerThan)
+            .map_err(|_| error_valor("argument couldn't be parsed into an integer")),
+        PARAM_SLOWLOG_MAX_LEN => entero(0).map(|n| ValorConfig::SlowlogMaxLen(n as usize)),
+        PARAM_LATENCY_MONITOR_THRESHOLD => entero(0).map(ValorConfig::LatencyMonitorThreshold),
+        PARAM_NOTIFY_KEYSPACE_EVENTS => parsear_flags(valor)
+            .map(|_| ValorConfig::NotifyKeyspaceEvents(valor.to_string()))
+            .map_err(|e| error_valor(&e)),
+        PARAM_CLIENT_OUTPUT_BUFFER_LIMIT => LimitesBuffer::parsear(valor)
+            .map(ValorConfig::ClientOutputBufferLimit)
+            .map_err(|e| error_valor(&e)),
+        _ if PARAMETROS_INMUTABLES.contains(&nombre) => {
+            Err(error_valor("can't set immutable config"))
+        }
+        _ => Err(error_parametro_desconocido(nombre)),
+    }
+}
+
+fn error_parametro_desconocido(nombre: &str) -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        format!("Unknown option or number of arguments for CONFIG SET - '{nombre}'"),
+    )
+}
+
+fn si_no(valor: bool) -> &'static str {
+    if valor { "yes" } else { "no" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_id::NodeId;
+    use crate::node_role::NodeRole;
+    use logger::logger::Logger;
+    use std::net::{TcpListener, TcpStream};
+    use std::path::PathBuf;
+
+    fn make_node() -> Node {
+        NodeBuilder::para_pruebas()
+            .save_interval(30000)
+            .max_client_capacity(10)
+            .logger(Logger::null())
+            .build()
+            .unwrap()
+    }
+
+    /// Archivo en el directorio temporal del sistema, con un nombre unico
+    /// por proceso y prueba, que se elimina al salir de alcance
+    struct ArchivoTemporal(PathBuf);
+
+    impl ArchivoTemporal {
+        fn nuevo(prefijo: &str) -> Self {
+            let nombre = format!("{prefijo}_{}_{}.conf", std::process::id(), NodeId::new());
+            ArchivoTemporal(std::env::temp_dir().join(nombre))
+        }
+    }
+
+    impl Drop for ArchivoTemporal {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn make_client() -> Arc<RwLock<Client>> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let cliente = Client::new(
+            "1234".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        );
+        Arc::new(RwLock::new(cliente))
+    }
+
+    fn ejecutar(node: &Node, tokens: &[&str]) -> DatoRedis {
+        let tokens: Vec<Token> = tokens.iter().map(|s| Token::from(*s)).collect();
+        node.config(&tokens, &make_client()).unwrap_or_else(|e| e)
+    }
+
+    fn valor_de(respuesta: &DatoRedis, nombre: &str) -> Option<String> {
+        let DatoRedis::Map(map) = respuesta else {
+            panic!("Se esperaba un map reply: {respuesta:?}");
+        };
+        map.iter().find_map(|(clave, valor)| match (clave, valor) {
+            (DatoRedis::BulkString(clave), DatoRedis::BulkString(valor))
+                if clave.contenido() == nombre =>
+            {
+                Some(valor.contenido())
+            }
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_01_get_con_patrones() {
+        let node = make_node();
+        let respuesta = ejecutar(&node, &["config", "get", "max*"]);
+        assert_eq!(valor_de(&respuesta, "max_clients"), Some("10".to_string()));
+        assert_eq!(valor_de(&respuesta, "maxmemory"), Some("0".to_string()));
+        assert_eq!(valor_de(&respuesta, "save"), None);
+
+        let respuesta = ejecutar(&node, &["config", "get", "port", "save"]);
+        let puerto = node.cli_addr.port().to_string();
+        assert_eq!(valor_de(&respuesta, "port"), Some(puerto));
+        assert_eq!(valor_de(&respuesta, "save"), Some("30000".to_string()));
+    }
+
+    #[test]
+    fn test_02_set_modifica_la_configuracion_en_ejecucion() {
+        let node = make_node();
+        let ok = DatoRedis::new_simple_string("OK".to_string()).unwrap();
+        assert_eq!(
+            ejecutar(
+                &node,
+                &[
+                    "config",
+                    "set",
+                    "save",
+                    "1000",
+                    "loglevel",
+                    "warning",
+                    "appendonly",
+                    "yes"
+                ]
+            ),
+            ok
+        );
+        assert_eq!(node.save_interval.load(Ordering::SeqCst), 1000);
+        assert_eq!(node.logger.nivel(), NivelLog::Warning);
+        assert!(node.appendonly.load(Ordering::SeqCst));
+
+        assert_eq!(ejecutar(&node, &["config", "set", "maxmemory", "1mb"]), ok);
+        assert_eq!(node.storage.read().unwrap().get_maxmemory(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_03_set_invalido_no_aplica_ningun_valor() {
+        let node = make_node();
+        for tokens in [
+            ["config", "set", "save", "0"],
+            ["config", "set", "port", "7001"],
+            ["config", "set", "inexistente", "1"],
+        ] {
+            assert!(matches!(
+                ejecutar(&node, &tokens),
+                DatoRedis::SimpleError(_)
+            ));
+        }
+        assert!(matches!(
+            ejecutar(
+                &node,
+                &["config", "set", "max_clients", "50", "save", "nan"]
+            ),
+            DatoRedis::SimpleError(_)
+        ));
+        assert_eq!(node.max_client_capacity.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn test_04_rewrite_y_resetstat() {
+        let node = make_node();
+        assert!(matches!(
+            ejecutar(&node, &["config", "rewrite"]),
+            DatoRedis::SimpleError(_)
+        ));
+
+        let archivo = ArchivoTemporal::nuevo("config_rewrite");
+        let path = archivo.0.to_string_lossy().to_string();
+        std::fs::write(&path, "# nodo de prueba\nsave=5\nmax_clients=3\n").unwrap();
+        *node.config_path.write().unwrap() = Some(path.clone());
+        ejecutar(&node, &["config", "set", "max_clients", "20"]);
+        ejecutar(&node, &["config", "rewrite"]);
+        let contenido = std::fs::read_to_string(&path).unwrap();
+        assert!(contenido.starts_with("# nodo de prueba\nsave=30000\nmax_clients=20\n"));
+        assert!(contenido.contains("maxmemory_policy=noeviction\n"));
+
+        node.estadisticas.registrar_comando();
+        ejecutar(&node, &["config", "resetstat"]);
+        assert_eq!(node.estadisticas.comandos_procesados(), 0);
+    }
+
+    #[test]
+    fn test_05_notify_keyspace_events() {
+        let node = make_node();
+        assert!(matches!(
+            ejecutar(&node, &["config", "set", "notify_keyspace_events", "KEq"]),
+            DatoRedis::SimpleError(_)
+        ));
+
+        ejecutar(&node, &["config", "set", "notify_keyspace_events", "KEA"]);
+        let respuesta = ejecutar(&node, &["config", "get", "notify_keyspace_events"]);
+        assert_eq!(
+            valor_de(&respuesta, "notify_keyspace_events"),
+            Some("AKE".to_string())
+        );
+
+        let mut storage = node.storage.write().unwrap();
+        let _ = storage.set("a", DatoRedis::new_null());
+        let _ = storage.remove("a");
+        assert_eq!(storage.tomar_eventos().len(), 1);
+    }
+
+    #[test]
+    fn test_06_client_output_buffer_limit() {
+        let node = make_node();
+        let respuesta = ejecutar(&node, &["config", "get", "client_output_buffer_limit"]);
+        assert_eq!(
+            valor_de(&respuesta, "client_output_buffer_limit"),
+            Some("pubsub 33554432 8388608 60".to_string())
+        );
+
+        ejecutar(
+            &node,
+            &[
+                "config",
+                "set",
+                "client_output_buffer_limit",
+                "pubsub 1mb 0 0",
+            ],
+        );
+        assert_eq!(
+            node.pub_sub.buffers().limites(),
+            LimitesBuffer {
+                duro: 1024 * 1024,
+                blando: 0,
+                segundos_blando: 0,
+            }
+        );
+        assert!(matches!(
+            ejecutar(
+                &node,
+                &[
+                    "config",
+                    "set",
+                    "client_output_buffer_limit",
+                    "normal 0 0 0"
+                ]
+            ),
+            DatoRedis::SimpleError(_)
+        ));
+    }
+}
@@ -0,0 +1,366 @@
+//! Este modulo contiene la implementacion de los comandos de funciones:
+//! FUNCTION administra las bibliotecas de funciones del nodo y FCALL y
+//! FCALL_RO ejecutan sus funciones.
+//!
+//! Las bibliotecas se guardan en el storage, por lo que se persisten en el
+//! snapshot y, al igual que FCALL, FUNCTION LOAD, DELETE y FLUSH se guardan
+//! en el AOF y se propagan a las replicas.
+use std::sync::{Arc, RwLock};
+
+use glob::Pattern;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::comandos_scripting::{
+    ResultadoScript, ejecutar_atomico, indices_datos_script,
+};
+use crate::comandos::const_cmd::{
+    CMD_FCALL_RO, CMD_FUNCTION, CMD_FUNCTION_DELETE, CMD_FUNCTION_FLUSH, CMD_FUNCTION_LIST,
+    CMD_FUNCTION_LOAD, OPERACION_EXITOSA,
+};
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido, get_storage_read_lock, get_storage_write_lock,
+};
+use crate::funciones::Biblioteca;
+use crate::storage::Storage;
+
+const MOTOR: &str = "FORTH";
+const OPCION_REEMPLAZAR: &str = "REPLACE";
+const OPCION_NOMBRE_BIBLIOTECA: &str = "LIBRARYNAME";
+const OPCION_CON_CODIGO: &str = "WITHCODE";
+
+/// Administra las bibliotecas de funciones del nodo
+/// (`FUNCTION LOAD [REPLACE] codigo`, `FUNCTION DELETE biblioteca`,
+/// `FUNCTION FLUSH [ASYNC|SYNC]`,
+/// `FUNCTION LIST [LIBRARYNAME patron] [WITHCODE]`)
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+///   argumentos
+/// * `storage`: storage del nodo donde se guardan las bibliotecas
+///
+/// # Retorna
+/// - Nombre de la biblioteca cargada, OK al eliminarlas o la lista de
+///   bibliotecas; error de redis en otro caso
+pub fn function(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&CMD_FUNCTION.to_lowercase(), 2, tokens.len())?;
+    let nombre = format!("{}|{}", CMD_FUNCTION, tokens[1].texto()).to_lowercase();
+    match tokens[1].to_uppercase().as_str() {
+        CMD_FUNCTION_LOAD => {
+            assert_correct_arguments_quantity(&nombre, 3, tokens.len())?;
+            let reemplazar = match tokens.len() {
+                3 => false,
+                4 if tokens[2].eq_ignore_ascii_case(OPCION_REEMPLAZAR) => true,
+                _ => {
+                    return Err(error(&format!(
+                        "Unknown option given: {}",
+                        tokens[2].texto()
+                    )));
+                }
+            };
+            let codigo = &tokens[tokens.len() - 1];
+            let biblioteca = get_storage_write_lock(storage)?
+                .funciones_mut()
+                .cargar(codigo, reemplazar)?;
+            DatoRedis::new_bulk_string(biblioteca)
+        }
+        CMD_FUNCTION_DELETE => {
+            assert_number_of_arguments_distinct(&nombre, 3, tokens.len())?;
+            get_storage_write_lock(storage)?
+                .funciones_mut()
+                .eliminar(&tokens[2].texto())?;
+            DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+        }
+        CMD_FUNCTION_FLUSH => {
+            match tokens.get(2).map(|modo| modo.to_uppercase()).as_deref() {
+                None | Some("ASYNC") | Some("SYNC") if tokens.len() <= 3 => {}
+                _ => return Err(error("FUNCTION FLUSH only supports SYNC|ASYNC option")),
+            }
+            get_storage_write_lock(storage)?.funciones_mut().vaciar();
+            DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+        }
+        CMD_FUNCTION_LIST => listar(&tokens[2..], storage),
+        _ => Err(error_subcomando_desconocido(&tokens[1].texto())),
+    }
+}
+
+/// Ejecuta una funcion de forma atomica (`FCALL funcion cantidad_claves
+/// [clave ...] [argumento ...]`). FCALL_RO solo permite llamar a comandos
+/// que no modifican el storage
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, funcion, cantidad de
+///   claves, claves y argumentos
+/// * `storage`: storage del nodo donde se encuentran las claves y las
+///   bibliotecas
+///
+/// # Retorna
+/// - Respuesta de la funcion en caso de exito, error de redis en otro caso
+pub fn fcall(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    ejecutar_fcall(tokens, storage).0
+}
+
+/// Ejecuta una funcion de una biblioteca cargada, como `fcall`
+///
+/// # Retorna
+/// - Respuesta de la funcion y los comandos de escritura que llamo, que se
+///   propagan en lugar del FCALL aunque la funcion falle
+pub(crate) fn ejecutar_fcall(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> ResultadoScript {
+    if let Err(e) = assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len()) {
+        return (Err(e), Vec::new());
+    }
+    let solo_lectura = tokens[0].eq_ignore_ascii_case(CMD_FCALL_RO);
+    let mut guard = match get_storage_write_lock(storage) {
+        Ok(guard) => guard,
+        Err(e) => return (Err(e), Vec::new()),
+    };
+    let funcion = tokens[1].texto();
+    let programa = guard
+        .funciones()
+        .biblioteca_de_funcion(&funcion)
+        .map(|biblioteca| biblioteca.programa(&funcion));
+    match programa {
+        Some(programa) => ejecutar_atomico(&mut guard, programa, tokens, solo_lectura),
+        None => (Err(error("Function not found")), Vec::new()),
+    }
+}
+
+/// Obtiene los indices de los tokens de FUNCTION y FCALL que se encriptan
+/// en el AOF: el codigo de las bibliotecas y los argumentos de las
+/// funciones
+pub fn indices_datos_funciones(tokens: &[Token]) -> Vec<usize> {
+    let es_load = tokens
+        .get(1)
+        .is_some_and(|subcomando| subcomando.eq_ignore_ascii_case(CMD_FUNCTION_LOAD));
+    match tokens[0].to_uppercase().as_str() {
+        CMD_FUNCTION if es_load && tokens.len() > 2 => vec![tokens.len() - 1],
+        CMD_FUNCTION => vec![],
+        _ => indices_datos_script(tokens),
+    }
+}
+
+/// Determina si un comando FUNCTION modifica las bibliotecas del nodo
+pub fn es_mutacion_de_funciones(tokens: &[Token]) -> bool {
+    !tokens
+        .get(1)
+        .is_some_and(|subcomando| subcomando.eq_ignore_ascii_case(CMD_FUNCTION_LIST))
+}
+
+/// Determina si un comando FUNCTION carga una biblioteca, lo que aumenta
+/// la memoria usada
+pub fn es_carga_de_funciones(tokens: &[Token]) -> bool {
+    tokens
+        .get(1)
+        .is_some_and(|subcomando| subcomando.eq_ignore_ascii_case(CMD_FUNCTION_LOAD))
+}
+
+/// Lista las bibliotecas del nodo, con sus funciones y opcionalmente su
+/// codigo
+fn listar(opciones: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    let mut patron = None;
+    let mut con_codigo = false;
+    let mut opciones = opciones.iter();
+    while let Some(opcion) = opciones.next() {
+        match opcion.to_uppercase().as_str() {
+            OPCION_CON_CODIGO => con_codigo = true,
+            OPCION_NOMBRE_BIBLIOTECA => {
+                let valor = opciones
+                    .next()
+                    .ok_or_else(|| error("library name argument was not given"))?;
+                let invalido = || error("Invalid library name pattern");
+                patron = Some(Pattern::new(&valor.texto()).map_err(|_| invalido())?);
+            }
+            _ => return Err(error(&format!("Unknown argument {}", opcion.texto()))),
+        }
+    }
+
+    let guard = get_storage_read_lock(storage)?;
+    let bibliotecas = guard
+        .funciones()
+        .bibliotecas()
+        .filter(|biblioteca| {
+            patron
+                .as_ref()
+                .is_none_or(|patron| patron.matches(biblioteca.nombre()))
+        })
+        .map(|biblioteca| descripcion_biblioteca(biblioteca, con_codigo))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(arreglo(bibliotecas))
+}
+
+/// Describe una biblioteca como en FUNCTION LIST: nombre, version (null si
+/// el header no la declara), motor, funciones y, si se pide, codigo
+fn descripcion_biblioteca(
+    biblioteca: &Biblioteca,
+    con_codigo: bool,
+) -> Result<DatoRedis, DatoRedis> {
+    let funciones = biblioteca
+        .funciones()
+        .iter()
+        .map(|funcion| {
+            Ok(arreglo(vec![
+                DatoRedis::new_bulk_string("name".to_string())?,
+                DatoRedis::new_bulk_string(funcion.clone())?,
+                DatoRedis::new_bulk_string("description".to_string())?,
+                DatoRedis::new_null(),
+                DatoRedis::new_bulk_string("flags".to_string())?,
+                arreglo(vec![]),
+            ]))
+        })
+        .collect::<Result<Vec<_>, DatoRedis>>()?;
+    let mut descripcion = vec![
+        DatoRedis::new_bulk_string("library_name".to_string())?,
+        DatoRedis::new_bulk_string(biblioteca.nombre().to_string())?,
+        DatoRedis::new_bulk_string("library_version".to_string())?,
+        match biblioteca.version() {
+            Some(version) => DatoRedis::new_bulk_string(version.to_string())?,
+            None => DatoRedis::new_null(),
+        },
+        DatoRedis::new_bulk_string("engine".to_string())?,
+        DatoRedis::new_bulk_string(MOTOR.to_string())?,
+        DatoRedis::new_bulk_string("functions".to_string())?,
+        arreglo(funciones),
+    ];
+    if con_codigo {
+        descripcion.push(DatoRedis::new_bulk_string("library_code".to_string())?);
+        descripcion.push(DatoRedis::new_bulk_string_desde_bytes(
+            biblioteca.codigo().to_vec(),
+        )?);
+    }
+    Ok(arreglo(descripcion))
+}
+
+fn arreglo(elementos: Vec<DatoRedis>) -> DatoRedis {
+    DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(elementos))
+}
+
+fn error(mensaje: &str) -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), mensaje.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use redis_client::tipos_datos::traits::TipoDatoRedis;
+
+    use super::*;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+    const DOCUMENTOS: &str = "#!forth name=documentos version=2.1\n: nuevo_id call\" INCR KEYS[1]\" ;\n: leer call\" GET KEYS[1]\" ;";
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn mensaje_error(resultado: Result<DatoRedis, DatoRedis>) -> String {
+        match resultado {
+            Err(DatoRedis::SimpleError(error)) => error.mensaje().to_string(),
+            otro => panic!("se esperaba un error: {otro:?}"),
+        }
+    }
+
+    fn enteros(valores: &[i64]) -> DatoRedis {
+        arreglo(valores.iter().map(|v| DatoRedis::new_integer(*v)).collect())
+    }
+
+    #[test]
+    fn test_01_fcall_ejecuta_funciones_de_la_biblioteca() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let cargada = function(&tokens(&["function", "load", DOCUMENTOS]), &storage);
+        assert_eq!(
+            cargada,
+            DatoRedis::new_bulk_string("documentos".to_string())
+        );
+
+        let nuevo_id = tokens(&["fcall", "NUEVO_ID", "1", "documentos:id"]);
+        assert_eq!(fcall(&nuevo_id, &storage).unwrap(), enteros(&[1]));
+        assert_eq!(fcall(&nuevo_id, &storage).unwrap(), enteros(&[2]));
+
+        let leer = tokens(&["fcall_ro", "leer", "1", "documentos:id"]);
+        assert_eq!(fcall(&leer, &storage).unwrap(), enteros(&[2]));
+        let escritura = fcall(&tokens(&["fcall_ro", "nuevo_id", "1", "a"]), &storage);
+        assert!(mensaje_error(escritura).contains("Write commands are not allowed"));
+        let inexistente = fcall(&tokens(&["fcall", "borrar", "0"]), &storage);
+        assert_eq!(mensaje_error(inexistente), "Function not found");
+    }
+
+    #[test]
+    fn test_02_function_list_delete_y_flush() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        function(&tokens(&["function", "load", DOCUMENTOS]), &storage).unwrap();
+        let repetida = function(&tokens(&["function", "load", DOCUMENTOS]), &storage);
+        assert!(mensaje_error(repetida).contains("already exists"));
+        let reemplazo = tokens(&["function", "load", "replace", DOCUMENTOS]);
+        assert!(function(&reemplazo, &storage).is_ok());
+        let otra = "#!forth name=usuarios\n: alta call\" SADD KEYS[1] ARGV[1]\" ;";
+        function(&tokens(&["function", "load", otra]), &storage).unwrap();
+
+        let Ok(DatoRedis::Arrays(lista)) = function(
+            &tokens(&["function", "list", "withcode", "libraryname", "doc*"]),
+            &storage,
+        ) else {
+            panic!("se esperaba la lista de bibliotecas");
+        };
+        let esperado = arreglo(vec![
+            DatoRedis::new_bulk_string("library_name".to_string()).unwrap(),
+            DatoRedis::new_bulk_string("documentos".to_string()).unwrap(),
+            DatoRedis::new_bulk_string("library_version".to_string()).unwrap(),
+            DatoRedis::new_bulk_string("2.1".to_string()).unwrap(),
+            DatoRedis::new_bulk_string("engine".to_string()).unwrap(),
+            DatoRedis::new_bulk_string(MOTOR.to_string()).unwrap(),
+            DatoRedis::new_bulk_string("functions".to_string()).unwrap(),
+            arreglo(
+                ["leer", "nuevo_id"]
+                    .into_iter()
+                    .map(|nombre| {
+                        arreglo(vec![
+                            DatoRedis::new_bulk_string("name".to_string()).unwrap(),
+                            DatoRedis::new_bulk_string(nombre.to_string()).unwrap(),
+                            DatoRedis::new_bulk_string("description".to_string()).unwrap(),
+                            DatoRedis::new_null(),
+                            DatoRedis::new_bulk_string("flags".to_string()).unwrap(),
+                            arreglo(vec![]),
+                        ])
+                    })
+                    .collect(),
+            ),
+            DatoRedis::new_bulk_string("library_code".to_string()).unwrap(),
+            DatoRedis::new_bulk_string(DOCUMENTOS.to_string()).unwrap(),
+        ]);
+        assert_eq!(DatoRedis::Arrays(lista), arreglo(vec![esperado]));
+
+        function(&tokens(&["function", "delete", "documentos"]), &storage).unwrap();
+        let borrada = function(&tokens(&["function", "delete", "documentos"]), &storage);
+        assert_eq!(mensaje_error(borrada), "Library not found");
+        let listar = || function(&tokens(&["function", "list"]), &storage).unwrap();
+        assert_eq!(
+            listar()
+                .convertir_resp_a_string()
+                .matches("usuarios")
+                .count(),
+            1
+        );
+        assert!(function(&tokens(&["function", "flush", "lazy"]), &storage).is_err());
+        function(&tokens(&["function", "flush"]), &storage).unwrap();
+        assert_eq!(listar(), arreglo(vec![]));
+        assert!(function(&tokens(&["function", "list", "otro"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_03_metadata_de_funciones() {
+        let load = tokens(&["function", "load", "replace", DOCUMENTOS]);
+        assert_eq!(indices_datos_funciones(&load), vec![3]);
+        assert!(es_mutacion_de_funciones(&load));
+        let list = tokens(&["FUNCTION", "LIST"]);
+        assert!(indices_datos_funciones(&list).is_empty());
+        assert!(!es_mutacion_de_funciones(&list));
+        let fcall = tokens(&["fcall", "nuevo_id", "1", "clave", "valor"]);
+        assert_eq!(indices_datos_funciones(&fcall), vec![1, 4]);
+    }
+}
@@ -0,0 +1,864 @@
+//! Este modulo contiene la implementacion de los comandos geoespaciales de
+//! redis. Las posiciones se guardan en un sorted set cuyo puntaje es el
+//! geohash de cada miembro, lo que permite buscar por area recorriendo
+//! solo los rangos de geohashes que la cubren
+use std::sync::{Arc, RwLock};
+
+use crate::comandos::geohash::{
+    self, LATITUD_MAXIMA, LATITUD_MINIMA, LONGITUD_MAXIMA, LONGITUD_MINIMA,
+};
+use crate::comandos::utils::assert_correct_arguments_quantity;
+use crate::storage::Storage;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::sorted_set::SortedSet;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use super::utils::{get_storage_read_lock, get_storage_write_lock};
+
+const OPCION_NX: &str = "NX";
+const OPCION_XX: &str = "XX";
+const OPCION_CH: &str = "CH";
+const OPCION_FROMMEMBER: &str = "FROMMEMBER";
+const OPCION_FROMLONLAT: &str = "FROMLONLAT";
+const OPCION_BYRADIUS: &str = "BYRADIUS";
+const OPCION_BYBOX: &str = "BYBOX";
+const OPCION_ASC: &str = "ASC";
+const OPCION_DESC: &str = "DESC";
+const OPCION_COUNT: &str = "COUNT";
+const OPCION_ANY: &str = "ANY";
+const OPCION_WITHCOORD: &str = "WITHCOORD";
+const OPCION_WITHDIST: &str = "WITHDIST";
+const OPCION_WITHHASH: &str = "WITHHASH";
+
+/// Unidades de distancia aceptadas y su equivalencia en metros
+const UNIDADES: [(&str, f64); 4] = [("M", 1.0), ("KM", 1000.0), ("FT", 0.3048), ("MI", 1609.34)];
+
+/// Origen de una busqueda
+#[derive(Debug, Clone, PartialEq)]
+enum Origen {
+    Miembro(Vec<u8>),
+    Posicion(f64, f64),
+}
+
+/// Area de una busqueda alrededor del origen, con sus medidas en metros
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Forma {
+    Radio(f64),
+    Caja { ancho: f64, alto: f64 },
+}
+
+impl Forma {
+    /// Distancia en metros desde el centro a un punto, si el punto esta
+    /// dentro del area
+    fn distancia_si_contiene(&self, centro: (f64, f64), punto: (f64, f64)) -> Option<f64> {
+        let distancia = geohash::distancia(centro.0, centro.1, punto.0, punto.1);
+        let contiene = match *self {
+            Forma::Radio(radio) => distancia <= radio,
+            Forma::Caja { ancho, alto } => {
+                geohash::distancia_latitud(centro.1, punto.1) <= alto / 2.0
+                    && geohash::distancia(centro.0, punto.1, punto.0, punto.1) <= ancho / 2.0
+            }
+        };
+        contiene.then_some(distancia)
+    }
+
+    /// Distancia en metros que cubre el area hacia cada lado del centro,
+    /// en longitud y en latitud
+    fn extension(&self) -> (f64, f64) {
+        match *self {
+            Forma::Radio(radio) => (radio, radio),
+            Forma::Caja { ancho, alto } => (ancho / 2.0, alto / 2.0),
+        }
+    }
+}
+
+/// Opciones de GEOSEARCH
+#[derive(Debug, Clone, PartialEq)]
+struct Busqueda {
+    origen: Origen,
+    forma: Forma,
+    /// Metros que representa la unidad indicada
+    unidad: f64,
+    /// Orden por distancia, verdadero si es ascendente
+    ascendente: Option<bool>,
+    cantidad: Option<usize>,
+    /// Si alcanza con encontrar `cantidad` miembros cualesquiera
+    cualquiera: bool,
+    con_coordenadas: bool,
+    con_distancia: bool,
+    con_hash: bool,
+}
+
+/// Miembro encontrado por una busqueda
+struct Resultado<'a> {
+    miembro: &'a [u8],
+    distancia: f64,
+    hash: u64,
+    posicion: (f64, f64),
+}
+
+/// Agrega miembros con su posicion a un indice geoespacial, o actualiza
+/// su posicion si ya existian
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, opciones NX,
+///   XX y CH, y ternas de longitud, latitud y miembro
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Cantidad de miembros agregados (o agregados y actualizados con CH) en
+///   caso de exito, error de redis en otro caso
+pub fn geoadd(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 5, tokens.len())?;
+    let inicio = inicio_posiciones_geoadd(tokens);
+    let opciones: Vec<String> = tokens[2..inicio].iter().map(|t| t.to_uppercase()).collect();
+    let nx = opciones.iter().any(|o| o == OPCION_NX);
+    let xx = opciones.iter().any(|o| o == OPCION_XX);
+    let ch = opciones.iter().any(|o| o == OPCION_CH);
+    if nx && xx {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "XX and NX options at the same time are not compatible".to_string(),
+        ));
+    }
+    let posiciones = &tokens[inicio..];
+    if posiciones.is_empty() || !posiciones.len().is_multiple_of(3) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "syntax error. Try GEOADD key [x1] [y1] [name1] [x2] [y2] [name2] ... ".to_string(),
+        ));
+    }
+    let mut miembros = Vec::with_capacity(posiciones.len() / 3);
+    for terna in posiciones.chunks_exact(3) {
+        let (longitud, latitud) = parsear_posicion(&terna[0], &terna[1])?;
+        miembros.push((terna[2].to_vec(), geohash::codificar(longitud, latitud)));
+    }
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let mut indice = leer_indice(&guard, &tokens[1])?.unwrap_or_default();
+    let (mut agregados, mut actualizados) = (0, 0);
+    for (miembro, hash) in miembros {
+        let anterior = indice.puntaje(&miembro);
+        if (nx && anterior.is_some()) || (xx && anterior.is_none()) {
+            continue;
+        }
+        if anterior == Some(hash as f64) {
+            continue;
+        }
+        match anterior {
+            Some(_) => actualizados += 1,
+            None => agregados += 1,
+        }
+        indice.insertar(miembro, hash as f64);
+    }
+    if agregados + actualizados > 0 {
+        guard.set(&tokens[1], DatoRedis::SortedSet(indice))?;
+    }
+    let respuesta = if ch {
+        agregados + actualizados
+    } else {
+        agregados
+    };
+    Ok(DatoRedis::new_integer(respuesta))
+}
+
+/// Obtiene la distancia entre dos miembros de un indice geoespacial
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, ambos
+///   miembros y opcionalmente la unidad (M, KM, FT o MI)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Distancia con 4 decimales en caso de exito, null si algun miembro no
+///   existe, error de redis en otro caso
+pub fn geodist(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    if tokens.len() > 5 {
+        return Err(error_sintaxis());
+    }
+    let unidad = match tokens.get(4) {
+        Some(unidad) => parsear_unidad(unidad)?,
+        None => 1.0,
+    };
+    let guard = get_storage_read_lock(storage)?;
+
+    let Some(indice) = leer_indice(&guard, &tokens[1])? else {
+        return Ok(DatoRedis::new_null());
+    };
+    match (posicion(&indice, &tokens[2]), posicion(&indice, &tokens[3])) {
+        (Some(a), Some(b)) => {
+            let metros = geohash::distancia(a.0, a.1, b.0, b.1);
+            DatoRedis::new_bulk_string(format!("{:.4}", metros / unidad))
+        }
+        _ => Ok(DatoRedis::new_null()),
+    }
+}
+
+/// Obtiene la posicion de miembros de un indice geoespacial
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y miembros
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Arreglo con la longitud y latitud de cada miembro, o null si no
+///   existe, en caso de exito, error de redis en otro caso
+pub fn geopos(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let indice = leer_indice(&guard, &tokens[1])?.unwrap_or_default();
+    let mut posiciones = Arrays::new();
+    for miembro in &tokens[2..] {
+        match posicion(&indice, miembro) {
+            Some(posicion) => posiciones.append(coordenadas(posicion)?),
+            None => posiciones.append(DatoRedis::new_null()),
+        }
+    }
+    Ok(DatoRedis::new_array_con_contenido(posiciones))
+}
+
+/// Busca los miembros de un indice geoespacial dentro de un radio o de un
+/// rectangulo alrededor de un miembro o de una posicion
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, origen
+///   (FROMMEMBER o FROMLONLAT), forma (BYRADIUS o BYBOX) y opcionalmente
+///   orden (ASC o DESC), COUNT con ANY y los datos a incluir (WITHCOORD,
+///   WITHDIST y WITHHASH)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Arreglo con los miembros encontrados, cada uno acompañado por los
+///   datos pedidos, en caso de exito, error de redis en otro caso
+pub fn geosearch(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 6, tokens.len())?;
+    let busqueda = parsear_busqueda(&tokens[2..])?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let Some(indice) = leer_indice(&guard, &tokens[1])? else {
+        return Ok(DatoRedis::new_array());
+    };
+    let centro = match &busqueda.origen {
+        Origen::Posicion(longitud, latitud) => (*longitud, *latitud),
+        Origen::Miembro(miembro) => posicion(&indice, miembro).ok_or_else(|| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "could not decode requested zset member".to_string(),
+            )
+        })?,
+    };
+
+    let mut resultados = buscar(&indice, centro, &busqueda);
+    if let Some(ascendente) = busqueda.ascendente {
+        resultados.sort_by(|a, b| a.distancia.total_cmp(&b.distancia));
+        if !ascendente {
+            resultados.reverse();
+        }
+    }
+    if let Some(cantidad) = busqueda.cantidad {
+        resultados.truncate(cantidad);
+    }
+
+    let mut respuesta = Arrays::new();
+    for resultado in resultados {
+        respuesta.append(resultado_a_dato(&resultado, &busqueda)?);
+    }
+    Ok(DatoRedis::new_array_con_contenido(respuesta))
+}
+
+/// Obtiene la posicion desde la que comienzan las ternas de longitud,
+/// latitud y miembro de GEOADD, salteando las opciones
+pub fn inicio_posiciones_geoadd(tokens: &[Token]) -> usize {
+    let mut inicio = 2.min(tokens.len());
+    while tokens.get(inicio).is_some_and(|token| {
+        [OPCION_NX, OPCION_XX, OPCION_CH]
+            .iter()
+            .any(|opcion| token.eq_ignore_ascii_case(opcion))
+    }) {
+        inicio += 1;
+    }
+    inicio
+}
+
+/// Recorre los rangos de geohashes que cubren el area buscada, quedandose
+/// con los miembros que efectivamente estan dentro de ella
+fn buscar<'a>(
+    indice: &'a SortedSet,
+    centro: (f64, f64),
+    busqueda: &Busqueda,
+) -> Vec<Resultado<'a>> {
+    let (metros_longitud, metros_latitud) = busqueda.forma.extension();
+    let (grados_longitud, grados_latitud) =
+        geohash::extension_en_grados(centro.1, metros_longitud, metros_latitud);
+    // Con ANY y sin orden alcanza con los primeros encontrados
+    let limite = match (busqueda.cualquiera, busqueda.cantidad) {
+        (true, Some(cantidad)) => cantidad,
+        _ => usize::MAX,
+    };
+
+    let mut resultados = Vec::new();
+    for (desde, hasta) in
+        geohash::rangos_a_buscar(centro.0, centro.1, grados_longitud, grados_latitud)
+    {
+        for (miembro, puntaje) in indice.rango_por_puntaje(desde as f64, hasta as f64) {
+            let hash = puntaje as u64;
+            let posicion = geohash::decodificar(hash);
+            if let Some(distancia) = busqueda.forma.distancia_si_contiene(centro, posicion) {
+                resultados.push(Resultado {
+                    miembro,
+                    distancia,
+                    hash,
+                    posicion,
+                });
+                if resultados.len() >= limite {
+                    return resultados;
+                }
+            }
+        }
+    }
+    resultados
+}
+
+fn resultado_a_dato(resultado: &Resultado, busqueda: &Busqueda) -> Result<DatoRedis, DatoRedis> {
+    let miembro = DatoRedis::new_bulk_string_desde_bytes(resultado.miembro.to_vec())?;
+    if !busqueda.con_distancia && !busqueda.con_hash && !busqueda.con_coordenadas {
+        return Ok(miembro);
+    }
+    let mut dato = Arrays::new();
+    dato.append(miembro);
+    if busqueda.con_distancia {
+        let distancia = format!("{:.4}", resultado.distancia / busqueda.unidad);
+        dato.append(DatoRedis::new_bulk_string(distancia)?);
+    }
+    if busqueda.con_hash {
+        dato.append(DatoRedis::new_integer(resultado.hash as i64));
+    }
+    if busqueda.con_coordenadas {
+        dato.append(coordenadas(resultado.posicion)?);
+    }
+    Ok(DatoRedis::new_array_con_contenido(dato))
+}
+
+/// Parsea las opciones de GEOSEARCH, a partir de la clave
+fn parsear_busqueda(opciones: &[Token]) -> Result<Busqueda, DatoRedis> {
+    let mut origen = None;
+    let mut forma_y_unidad = None;
+    let mut cantidad_origenes = 0;
+    let mut cantidad_formas = 0;
+    let mut ascendente = None;
+    let mut cantidad = None;
+    let (mut cualquiera, mut con_coordenadas, mut con_distancia, mut con_hash) =
+        (false, false, false, false);
+
+    let argumento = |i: usize| opciones.get(i).ok_or_else(error_sintaxis);
+    let mut i = 0;
+    while i < opciones.len() {
+        let opcion = opciones[i].to_uppercase();
+        match opcion.as_str() {
+            OPCION_FROMMEMBER => {
+                origen = Some(Origen::Miembro(argumento(i + 1)?.to_vec()));
+                cantidad_origenes += 1;
+                i += 1;
+            }
+            OPCION_FROMLONLAT => {
+                let (longitud, latitud) = parsear_posicion(argumento(i + 1)?, argumento(i + 2)?)?;
+                origen = Some(Origen::Posicion(longitud, latitud));
+                cantidad_origenes += 1;
+                i += 2;
+            }
+            OPCION_BYRADIUS => {
+                let radio = parsear_medida(argumento(i + 1)?, "radius")?;
+                let unidad = parsear_unidad(argumento(i + 2)?)?;
+                forma_y_unidad = Some((Forma::Radio(radio * unidad), unidad));
+                cantidad_formas += 1;
+                i += 2;
+            }
+            OPCION_BYBOX => {
+                let ancho = parsear_medida(argumento(i + 1)?, "width")?;
+                let alto = parsear_medida(argumento(i + 2)?, "height")?;
+                let unidad = parsear_unidad(argumento(i + 3)?)?;
+                let forma = Forma::Caja {
+                    ancho: ancho * unidad,
+                    alto: alto * unidad,
+                };
+                forma_y_unidad = Some((forma, unidad));
+                cantidad_formas += 1;
+                i += 3;
+            }
+            OPCION_ASC => ascendente = Some(true),
+            OPCION_DESC => ascendente = Some(false),
+            OPCION_COUNT => {
+                let valor = argumento(i + 1)?
+                    .parse::<i64>()
+                    .map_err(|_| error_entero())?;
+                if valor <= 0 {
+                    return Err(DatoRedis::new_simple_error(
+                        "ERR".to_string(),
+                        "COUNT must be > 0".to_string(),
+                    ));
+                }
+                cantidad = Some(valor as usize);
+                i += 1;
+                if opciones
+                    .get(i + 1)
+                    .is_some_and(|t| t.eq_ignore_ascii_case(OPCION_ANY))
+                {
+                    cualquiera = true;
+                    i += 1;
+                }
+            }
+            OPCION_WITHCOORD => con_coordenadas = true,
+            OPCION_WITHDIST => con_distancia = true,
+            OPCION_WITHHASH => con_hash = true,
+            _ => return Err(error_sintaxis()),
+        }
+        i += 1;
+    }
+
+    let (Some(origen), 1) = (origen, cantidad_origenes) else {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH".to_string(),
+        ));
+    };
+    let (Some((forma, unidad)), 1) = (forma_y_unidad, cantidad_formas) else {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "exactly one of BYRADIUS and BYBOX can be specified for GEOSEARCH".to_string(),
+        ));
+    };
+    // Sin ANY, COUNT devuelve los mas cercanos
+    if cantidad.is_some() && !cualquiera && ascendente.is_none() {
+        ascendente = Some(true);
+    }
+    Ok(Busqueda {
+        origen,
+        forma,
+        unidad,
+        ascendente,
+        cantidad,
+        cualquiera,
+        con_coordenadas,
+        con_distancia,
+        con_hash,
+    })
+}
+
+/// Obtiene el indice geoespacial de una clave
+///
+/// # Retorna
+/// - None si la clave no existe, error de redis si no almacena un sorted
+///   set o pertenece a otro nodo
+fn leer_indice(storage: &Storage, clave: &[u8]) -> Result<Option<SortedSet>, DatoRedis> {
+    match storage.get(clave) {
+        Ok(DatoRedis::SortedSet(indice)) => Ok(Some(indice)),
+        Ok(_) => Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => Err(DatoRedis::MovedError(e)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Posicion de un miembro, decodificada de su geohash
+fn posicion(indice: &SortedSet, miembro: &[u8]) -> Option<(f64, f64)> {
+    indice
+        .puntaje(miembro)
+        .map(|puntaje| geohash::decodificar(puntaje as u64))
+}
+
+/// Representa una posicion como el arreglo de su longitud y latitud
+fn coordenadas(posicion: (f64, f64)) -> Result<DatoRedis, DatoRedis> {
+    let mut arreglo = Arrays::new();
+    arreglo.append(DatoRedis::new_bulk_string(posicion.0.to_string())?);
+    arreglo.append(DatoRedis::new_bulk_string(posicion.1.to_string())?);
+    Ok(DatoRedis::new_array_con_contenido(arreglo))
+}
+
+fn parsear_posicion(longitud: &Token, latitud: &Token) -> Result<(f64, f64), DatoRedis> {
+    let longitud = parsear_float(longitud)?;
+    let latitud = parsear_float(latitud)?;
+    if !(LONGITUD_MINIMA..=LONGITUD_MAXIMA).contains(&longitud)
+        || !(LATITUD_MINIMA..=LATITUD_MAXIMA).contains(&latitud)
+    {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!("invalid longitude,latitude pair {longitud:.6},{latitud:.6}"),
+        ));
+    }
+    Ok((longitud, latitud))
+}
+
+/// Parsea una medida de la forma de busqueda, que no puede ser negativa
+fn parsear_medida(token: &Token, nombre: &str) -> Result<f64, DatoRedis> {
+    let valor = parsear_float(token)?;
+    if valor < 0.0 {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!("{nombre} cannot be negative"),
+        ));
+    }
+    Ok(valor)
+}
+
+/// Obtiene cuantos metros representa una unidad
+fn parsear_unidad(token: &Token) -> Result<f64, DatoRedis> {
+    UNIDADES
+        .iter()
+        .find(|(nombre, _)| token.eq_ignore_ascii_case(nombre))
+        .map(|(_, metros)| *metros)
+        .ok_or_else(|| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "unsupported unit provided. please use M, KM, FT, MI".to_string(),
+            )
+        })
+}
+
+fn parsear_float(token: &Token) -> Result<f64, DatoRedis> {
+    match token.parse::<f64>() {
+        Ok(valor) if valor.is_finite() => Ok(valor),
+        _ => Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not a valid float".to_string(),
+        )),
+    }
+}
+
+fn error_entero() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        "value is not an integer or out of range".to_string(),
+    )
+}
+
+fn error_tipo() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "WRONGTYPE".to_string(),
+        "Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn entero(resultado: Result<DatoRedis, DatoRedis>) -> i64 {
+        match resultado {
+            Ok(DatoRedis::Integer(valor)) => valor.valor(),
+            otro => panic!("se esperaba un entero: {otro:?}"),
+        }
+    }
+
+    fn texto(dato: &DatoRedis) -> String {
+        match dato {
+            DatoRedis::BulkString(bulk) => bulk.contenido(),
+            otro => panic!("se esperaba un bulk string: {otro:?}"),
+        }
+    }
+
+    fn elementos(resultado: Result<DatoRedis, DatoRedis>) -> Vec<DatoRedis> {
+        match resultado {
+            Ok(DatoRedis::Arrays(arreglo)) => arreglo.iter().cloned().collect(),
+            otro => panic!("se esperaba un arreglo: {otro:?}"),
+        }
+    }
+
+    fn sicilia() -> Arc<RwLock<Storage>> {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let comando = tokens(&[
+            "geoadd",
+            "sicilia",
+            "13.361389",
+            "38.115556",
+            "Palermo",
+            "15.087269",
+            "37.502669",
+            "Catania",
+            "12.758489",
+            "38.788135",
+            "edge1",
+            "17.241510",
+            "38.788135",
+            "edge2",
+        ]);
+        assert_eq!(entero(geoadd(&comando, &storage)), 4);
+        storage
+    }
+
+    #[test]
+    fn test_01_geoadd_con_opciones() {
+        let storage = sicilia();
+        let comando = tokens(&["geoadd", "sicilia", "NX", "1", "1", "Palermo"]);
+        assert_eq!(entero(geoadd(&comando, &storage)), 0);
+        let comando = tokens(&[
+            "geoadd", "sicilia", "XX", "CH", "1", "1", "Palermo", "2", "2", "x",
+        ]);
+        assert_eq!(entero(geoadd(&comando, &storage)), 1);
+        let comando = tokens(&["geoadd", "nueva", "XX", "1", "1", "a"]);
+        assert_eq!(entero(geoadd(&comando, &storage)), 0);
+        assert!(storage.read().unwrap().get("nueva").is_err());
+
+        assert!(
+            geoadd(
+                &tokens(&["geoadd", "k", "NX", "XX", "1", "1", "a"]),
+                &storage
+            )
+            .is_err()
+        );
+        assert!(geoadd(&tokens(&["geoadd", "k", "1", "1"]), &storage).is_err());
+        assert!(geoadd(&tokens(&["geoadd", "k", "1", "86", "a"]), &storage).is_err());
+        assert!(geoadd(&tokens(&["geoadd", "k", "181", "0", "a"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_02_geodist_y_geopos() {
+        let storage = sicilia();
+        let distancia = geodist(
+            &tokens(&["geodist", "sicilia", "Palermo", "Catania"]),
+            &storage,
+        );
+        assert_eq!(texto(&distancia.unwrap()), "166274.1516");
+        let distancia = geodist(
+            &tokens(&["geodist", "sicilia", "Palermo", "Catania", "km"]),
+            &storage,
+        );
+        assert_eq!(texto(&distancia.unwrap()), "166.2742");
+        assert_eq!(
+            geodist(
+                &tokens(&["geodist", "sicilia", "Palermo", "nada"]),
+                &storage
+            )
+            .unwrap(),
+            DatoRedis::new_null()
+        );
+        assert!(geodist(&tokens(&["geodist", "sicilia", "a", "b", "yd"]), &storage).is_err());
+
+        let posiciones = elementos(geopos(
+            &tokens(&["geopos", "sicilia", "Palermo", "nada"]),
+            &storage,
+        ));
+        let DatoRedis::Arrays(palermo) = &posiciones[0] else {
+            panic!("se esperaba un arreglo");
+        };
+        let longitud: f64 = texto(&palermo.get(0).unwrap()).parse().unwrap();
+        assert!((longitud - 13.361389).abs() < 1e-5);
+        assert_eq!(posiciones[1], DatoRedis::new_null());
+    }
+
+    #[test]
+    fn test_03_geosearch_por_radio_con_orden_y_cantidad() {
+        let storage = sicilia();
+        let nombres = |comando: &[&str]| -> Vec<String> {
+            elementos(geosearch(&tokens(comando), &storage))
+                .iter()
+                .map(texto)
+                .collect()
+        };
+        assert_eq!(
+            nombres(&[
+                "geosearch",
+                "sicilia",
+                "FROMLONLAT",
+                "15",
+                "37",
+                "BYRADIUS",
+                "200",
+                "km",
+                "ASC"
+            ]),
+            vec!["Catania", "Palermo"]
+        );
+        assert_eq!(
+            nombres(&[
+                "geosearch",
+                "sicilia",
+                "FROMLONLAT",
+                "15",
+                "37",
+                "BYRADIUS",
+                "200",
+                "km",
+                "DESC"
+            ]),
+            vec!["Palermo", "Catania"]
+        );
+        assert_eq!(
+            nombres(&[
+                "geosearch",
+                "sicilia",
+                "FROMMEMBER",
+                "Palermo",
+                "BYRADIUS",
+                "400",
+                "km",
+                "COUNT",
+                "2"
+            ]),
+            vec!["Palermo", "edge1"]
+        );
+        assert_eq!(
+            nombres(&[
+                "geosearch",
+                "sicilia",
+                "FROMLONLAT",
+                "15",
+                "37",
+                "BYRADIUS",
+                "400",
+                "km",
+                "COUNT",
+                "1",
+                "ANY"
+            ])
+            .len(),
+            1
+        );
+        assert!(
+            nombres(&[
+                "geosearch",
+                "nada",
+                "FROMLONLAT",
+                "0",
+                "0",
+                "BYRADIUS",
+                "1",
+                "m"
+            ])
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_04_geosearch_por_caja_con_datos() {
+        let storage = sicilia();
+        let comando = tokens(&[
+            "geosearch",
+            "sicilia",
+            "FROMLONLAT",
+            "15",
+            "37",
+            "BYBOX",
+            "400",
+            "400",
+            "km",
+            "ASC",
+            "WITHCOORD",
+            "WITHDIST",
+            "WITHHASH",
+        ]);
+        let resultados = elementos(geosearch(&comando, &storage));
+        let nombres: Vec<String> = resultados
+            .iter()
+            .map(|r| match r {
+                DatoRedis::Arrays(datos) => texto(&datos.get(0).unwrap()),
+                otro => panic!("se esperaba un arreglo: {otro:?}"),
+            })
+            .collect();
+        assert_eq!(nombres, vec!["Catania", "Palermo", "edge2", "edge1"]);
+        let DatoRedis::Arrays(catania) = &resultados[0] else {
+            panic!("se esperaba un arreglo");
+        };
+        assert_eq!(texto(&catania.get(1).unwrap()), "56.4413");
+        assert_eq!(
+            catania.get(2).unwrap(),
+            DatoRedis::new_integer(3479447370796909)
+        );
+        assert!(matches!(catania.get(3), Some(DatoRedis::Arrays(_))));
+    }
+
+    #[test]
+    fn test_05_geosearch_errores() {
+        let storage = sicilia();
+        let error = |comando: &[&str]| geosearch(&tokens(comando), &storage).is_err();
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "BYRADIUS",
+            "1",
+            "km",
+            "ASC"
+        ]));
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "FROMLONLAT",
+            "0",
+            "0",
+            "COUNT",
+            "1"
+        ]));
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "FROMMEMBER",
+            "nada",
+            "BYRADIUS",
+            "1",
+            "km"
+        ]));
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "FROMLONLAT",
+            "0",
+            "0",
+            "BYRADIUS",
+            "-1",
+            "km"
+        ]));
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "FROMLONLAT",
+            "0",
+            "0",
+            "BYRADIUS",
+            "1",
+            "km",
+            "COUNT",
+            "0"
+        ]));
+        assert!(error(&[
+            "geosearch",
+            "sicilia",
+            "FROMLONLAT",
+            "0",
+            "0",
+            "BYBOX",
+            "1",
+            "km"
+        ]));
+
+        storage
+            .write()
+            .unwrap()
+            .set("lista", DatoRedis::new_array())
+            .unwrap();
+        assert!(error(&[
+            "geosearch",
+            "lista",
+            "FROMLONLAT",
+            "0",
+            "0",
+            "BYRADIUS",
+            "1",
+            "km"
+        ]));
+    }
+}
@@ -0,0 +1,229 @@
+//! Este modulo contiene la implementacion de los comandos de HyperLogLog
+//! de redis, que estiman la cantidad de elementos distintos de un conjunto
+//! sin almacenarlos
+use std::sync::{Arc, RwLock};
+
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::const_cmd::OPERACION_EXITOSA;
+use crate::comandos::utils::assert_correct_arguments_quantity;
+use crate::storage::Storage;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::hyperloglog::HyperLogLog;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use super::utils::{get_storage_read_lock, get_storage_write_lock};
+
+/// Agrega elementos al HyperLogLog de una clave, creandolo si no existe
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y elementos a
+///   agregar
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - 1 si la clave fue creada o se modifico algun registro, 0 en otro
+///   caso, error de redis si la clave no almacena un HyperLogLog
+pub fn pfadd(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let (mut hll, mut modificado) = match leer_hll(&guard, &tokens[1])? {
+        Some(hll) => (hll, false),
+        None => (HyperLogLog::new(), true),
+    };
+    for elemento in &tokens[2..] {
+        modificado |= hll.agregar(elemento);
+    }
+    if modificado {
+        guard.set(&tokens[1], DatoRedis::HyperLogLog(hll))?;
+    }
+    Ok(DatoRedis::new_integer(modificado as i64))
+}
+
+/// Estima la cantidad de elementos distintos agregados a una clave o, si
+/// se indican varias, a la union de todas ellas
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y claves a contar
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Cardinalidad estimada en caso de exito, error de redis en otro caso.
+///   Las claves inexistentes se consideran vacias
+pub fn pfcount(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    for clave in &tokens[2..] {
+        verificar_mismo_slot(&guard, &tokens[1], clave)?;
+    }
+
+    let union = unir_claves(&guard, &tokens[1..])?;
+    Ok(DatoRedis::new_integer(union.contar() as i64))
+}
+
+/// Une los HyperLogLog de varias claves en una clave destino, incluyendo
+/// el contenido previo de la misma
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave destino y
+///   claves de origen
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - OK en caso de exito, error de redis en otro caso
+pub fn pfmerge(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+    for clave in &tokens[2..] {
+        verificar_mismo_slot(&guard, &tokens[1], clave)?;
+    }
+
+    let union = unir_claves(&guard, &tokens[1..])?;
+    guard.set(&tokens[1], DatoRedis::HyperLogLog(union))?;
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Obtiene la union de los HyperLogLog de varias claves
+fn unir_claves(storage: &Storage, claves: &[Token]) -> Result<HyperLogLog, DatoRedis> {
+    let mut union = HyperLogLog::new();
+    for clave in claves {
+        if let Some(hll) = leer_hll(storage, clave)? {
+            union.unir(&hll);
+        }
+    }
+    Ok(union)
+}
+
+/// Obtiene el HyperLogLog de una clave
+///
+/// # Retorna
+/// - None si la clave no existe, error de redis si no almacena un
+///   HyperLogLog o pertenece a otro nodo
+fn leer_hll(storage: &Storage, clave: &[u8]) -> Result<Option<HyperLogLog>, DatoRedis> {
+    match storage.get(clave) {
+        Ok(DatoRedis::HyperLogLog(hll)) => Ok(Some(hll)),
+        Ok(_) => Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => Err(DatoRedis::MovedError(e)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn error_tipo() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "WRONGTYPE".to_string(),
+        "Key is not a valid HyperLogLog string value.".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn entero(resultado: Result<DatoRedis, DatoRedis>) -> i64 {
+        match resultado {
+            Ok(DatoRedis::Integer(valor)) => valor.valor(),
+            otro => panic!("se esperaba un entero: {otro:?}"),
+        }
+    }
+
+    /// Busca una clave que pertenezca al mismo slot que `key`
+    fn clave_vecina(storage: &Arc<RwLock<Storage>>, key: &str) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(key);
+        (0..)
+            .map(|i| format!("{key}{i}"))
+            .find(|candidata| guard.calculate_slot_for_key(candidata) == slot)
+            .unwrap()
+    }
+
+    fn agregar_usuarios(storage: &Arc<RwLock<Storage>>, clave: &str, usuarios: Range<usize>) {
+        let mut comando = tokens(&["pfadd", clave]);
+        comando.extend(usuarios.map(|i| Token::from(format!("usuario:{i}"))));
+        pfadd(&comando, storage).unwrap();
+    }
+
+    #[test]
+    fn test_01_pfadd_y_pfcount() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(entero(pfadd(&tokens(&["pfadd", "doc"]), &storage)), 1);
+        assert_eq!(entero(pfadd(&tokens(&["pfadd", "doc"]), &storage)), 0);
+        assert_eq!(
+            entero(pfadd(&tokens(&["pfadd", "doc", "ana", "luis"]), &storage)),
+            1
+        );
+        assert_eq!(
+            entero(pfadd(&tokens(&["pfadd", "doc", "ana"]), &storage)),
+            0
+        );
+        assert_eq!(entero(pfcount(&tokens(&["pfcount", "doc"]), &storage)), 2);
+        assert_eq!(entero(pfcount(&tokens(&["pfcount", "nada"]), &storage)), 0);
+    }
+
+    #[test]
+    fn test_02_pfcount_de_varias_claves_cuenta_la_union() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let otra = clave_vecina(&storage, "doc");
+        agregar_usuarios(&storage, "doc", 0..600);
+        agregar_usuarios(&storage, &otra, 400..1000);
+
+        let union = entero(pfcount(&tokens(&["pfcount", "doc", &otra]), &storage));
+        assert!((970..=1030).contains(&union), "estimado {union}");
+        // Contar la union no modifica las claves
+        let solo = entero(pfcount(&tokens(&["pfcount", "doc"]), &storage));
+        assert!((580..=620).contains(&solo), "estimado {solo}");
+    }
+
+    #[test]
+    fn test_03_pfmerge_incluye_el_destino() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let origen = clave_vecina(&storage, "destino");
+        agregar_usuarios(&storage, "destino", 0..10);
+        agregar_usuarios(&storage, &origen, 5..20);
+
+        assert!(pfmerge(&tokens(&["pfmerge", "destino", &origen]), &storage).is_ok());
+        assert_eq!(
+            entero(pfcount(&tokens(&["pfcount", "destino"]), &storage)),
+            20
+        );
+        assert!(pfmerge(&tokens(&["pfmerge", "vacio"]), &storage).is_ok());
+        assert_eq!(entero(pfcount(&tokens(&["pfcount", "vacio"]), &storage)), 0);
+    }
+
+    #[test]
+    fn test_04_errores() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        storage
+            .write()
+            .unwrap()
+            .set(
+                "texto",
+                DatoRedis::new_bulk_string("hola".to_string()).unwrap(),
+            )
+            .unwrap();
+        assert!(pfadd(&tokens(&["pfadd", "texto", "a"]), &storage).is_err());
+        assert!(pfcount(&tokens(&["pfcount", "texto"]), &storage).is_err());
+        assert!(pfcount(&tokens(&["pfcount"]), &storage).is_err());
+
+        let lejana = (0..)
+            .map(|i| format!("clave{i}"))
+            .find(|clave| {
+                let guard = storage.read().unwrap();
+                guard.calculate_slot_for_key(clave) != guard.calculate_slot_for_key("texto")
+            })
+            .unwrap();
+        assert!(matches!(
+            pfmerge(&tokens(&["pfmerge", "texto", &lejana]), &storage),
+            Err(DatoRedis::SimpleError(_))
+        ));
+    }
+}
@@ -0,0 +1,983 @@
+//! Este modulo contiene la implementacion de los comandos genericos sobre
+//! claves de redis, independientes del tipo de dato almacenado
+use std::sync::{Arc, RwLock};
+
+use crate::comandos::const_cmd::{CMD_MEMORY_USAGE, CMD_OBJECT_FREQ, CMD_OBJECT_IDLETIME};
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido, parsear_cursor, parsear_opciones_scan, respuesta_scan,
+};
+use crate::memoria::ahora_ms;
+use crate::persistence::snapshot::{deserializar_dump, serializar_dump};
+use crate::{comandos::const_cmd::OPERACION_EXITOSA, storage::Storage};
+use glob::Pattern;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use super::utils::{get_storage_read_lock, get_storage_write_lock};
+
+const OPCION_REPLACE: &str = "REPLACE";
+const OPCION_ABSTTL: &str = "ABSTTL";
+const OPCION_DB: &str = "DB";
+const OPCION_ASYNC: &str = "ASYNC";
+const OPCION_SYNC: &str = "SYNC";
+
+const TIPO_STRING: &str = "string";
+const TIPO_LIST: &str = "list";
+const TIPO_SET: &str = "set";
+const TIPO_HASH: &str = "hash";
+const TIPO_STREAM: &str = "stream";
+const TIPO_ZSET: &str = "zset";
+const TIPO_NONE: &str = "none";
+
+/// Serializa el valor de una clave en el formato de DUMP
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y clave a serializar
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Payload en bytes (tag, valor, version y crc32) en caso de exito, null
+///   si la clave no existe
+pub fn dump(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+
+    match guard.get(&tokens[1]) {
+        Ok(valor) => {
+            let payload = serializar_dump(&valor)?;
+            DatoRedis::new_bulk_string_desde_bytes(payload)
+        }
+        Err(e) => match e {
+            DatoRedis::MovedError(_) => Err(e),
+            _ => Ok(DatoRedis::new_null()),
+        },
+    }
+}
+
+/// Crea una clave a partir de un payload generado por DUMP
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, ttl, payload
+///   y opcionalmente REPLACE y ABSTTL (el ttl es un instante unix en
+///   milisegundos)
+/// * `storage`: storage del nodo donde se guardara la clave
+///
+/// # Retorna
+/// - OK en caso de exito, error de redis si la clave ya existe (sin
+///   REPLACE) o el payload es invalido
+pub fn restore(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
+
+    let mut reemplazar = false;
+    let mut absoluto = false;
+    for opcion in &tokens[4..] {
+        match opcion.to_uppercase().as_str() {
+            OPCION_REPLACE => reemplazar = true,
+            OPCION_ABSTTL => absoluto = true,
+            _ => {
+                return Err(DatoRedis::new_simple_error(
+                    "ERR".to_string(),
+                    "syntax error".to_string(),
+                ));
+            }
+        }
+    }
+
+    let ttl = tokens[2].parse::<i64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not an integer or out of range".to_string(),
+        )
+    })?;
+    if ttl < 0 {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Invalid TTL value, must be >= 0".to_string(),
+        ));
+    }
+
+    let valor = deserializar_dump(tokens[3].bytes())?;
+
+    let mut guard = get_storage_write_lock(storage)?;
+    match guard.get(&tokens[1]) {
+        Ok(_) if !reemplazar => {
+            return Err(DatoRedis::new_simple_error(
+                "BUSYKEY".to_string(),
+                "Target key name already exists.".to_string(),
+            ));
+        }
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        _ => {}
+    }
+    guard.set(&tokens[1], valor)?;
+    let expira_en = match ttl {
+        0 => None,
+        ttl if absoluto => Some(ttl as u64),
+        ttl => Some(ahora_ms() + ttl as u64),
+    };
+    guard.set_expiracion(&tokens[1], expira_en)?;
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Reescribe un RESTORE con ttl relativo como un RESTORE con ABSTTL, para
+/// que el AOF y las replicas expiren la clave en el mismo instante que el
+/// master
+///
+/// # Retorna
+/// - Los tokens con el ttl absoluto, o los mismos tokens si no tienen ttl
+///   relativo valido
+pub(crate) fn restore_con_expiracion_absoluta(tokens: &[Token]) -> Vec<Token> {
+    let mut absoluto = tokens.to_vec();
+    let ya_absoluto = tokens
+        .iter()
+        .skip(4)
+        .any(|opcion| opcion.eq_ignore_ascii_case(OPCION_ABSTTL));
+    if let Some(ttl) = tokens.get(2).and_then(|ttl| ttl.parse::<u64>().ok())
+        && ttl > 0
+        && !ya_absoluto
+    {
+        absoluto[2] = Token::from((ahora_ms() + ttl).to_string());
+        absoluto.push(Token::from(OPCION_ABSTTL));
+    }
+    absoluto
+}
+
+/// Ejecuta los subcomandos de MEMORY. Soporta `MEMORY USAGE key [SAMPLES n]`,
+/// que estima los bytes ocupados por una clave y su valor
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, subcomando y clave
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Cantidad de bytes estimada, null si la clave no existe
+pub fn memory(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+    if tokens[1].to_uppercase() != CMD_MEMORY_USAGE {
+        return Err(error_subcomando_desconocido(&tokens[1].texto()));
+    }
+    let mut guard = get_storage_write_lock(storage)?;
+
+    match guard.uso_memoria(&tokens[2]) {
+        Ok(bytes) => Ok(DatoRedis::new_integer(bytes as i64)),
+        Err(e) => match e {
+            DatoRedis::MovedError(_) => Err(e),
+            _ => Ok(DatoRedis::new_null()),
+        },
+    }
+}
+
+/// Ejecuta los subcomandos de OBJECT: `OBJECT FREQ key` devuelve el
+/// contador de frecuencia LFU y `OBJECT IDLETIME key` los segundos desde el
+/// último acceso. Ninguno de los dos registra un acceso a la clave
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, subcomando y clave
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor solicitado, null si la clave no existe, error de redis si la
+///   politica de desalojo no lleva registro del dato pedido
+pub fn object(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    let es_lfu = guard.get_politica().es_lfu();
+    let key = &tokens[2];
+
+    let resultado = match tokens[1].to_uppercase().as_str() {
+        CMD_OBJECT_FREQ if !es_lfu => {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "An LFU maxmemory policy is not selected, access frequency not tracked."
+                    .to_string(),
+            ));
+        }
+        CMD_OBJECT_IDLETIME if es_lfu => {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "An LFU maxmemory policy is selected, idle time not tracked.".to_string(),
+            ));
+        }
+        CMD_OBJECT_FREQ => guard.frecuencia(key).map(|f| f as i64),
+        CMD_OBJECT_IDLETIME => guard.segundos_inactiva(key).map(|s| s as i64),
+        _ => return Err(error_subcomando_desconocido(&tokens[1].texto())),
+    };
+
+    match resultado {
+        Ok(valor) => Ok(DatoRedis::new_integer(valor)),
+        Err(e) => match e {
+            DatoRedis::MovedError(_) => Err(e),
+            _ => Ok(DatoRedis::new_null()),
+        },
+    }
+}
+
+/// Cuenta cuantas de las claves recibidas existen. Una clave repetida se
+/// cuenta tantas veces como aparezca
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y claves a verificar
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Cantidad de claves existentes, error de redis si las claves no
+///   pertenecen al mismo slot o a los slots del nodo
+pub fn exists(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    verificar_claves_mismo_slot(&guard, &tokens[1..])?;
+
+    let mut existentes = 0;
+    for key in &tokens[1..] {
+        match guard.get(key) {
+            Ok(_) => existentes += 1,
+            Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+            Err(_) => continue,
+        }
+    }
+    Ok(DatoRedis::new_integer(existentes))
+}
+
+/// Obtiene el tipo del valor almacenado en una clave
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y clave
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Nombre del tipo (`string`, `list`, `set`, `hash`), o `none` si la
+///   clave no existe
+pub fn key_type(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let tipo = match guard.get(&tokens[1]) {
+        Ok(valor) => nombre_tipo(&valor),
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        Err(_) => TIPO_NONE,
+    };
+    DatoRedis::new_simple_string(tipo.to_string())
+}
+
+/// Obtiene las claves que coinciden con un patron glob
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y patron
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Claves coincidentes ordenadas, error de redis si el patron es invalido
+pub fn keys(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 2, tokens.len())?;
+    let patron = Pattern::new(&tokens[1].texto()).map_err(|_| {
+        DatoRedis::new_simple_error("ERR".to_string(), "invalid pattern".to_string())
+    })?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let (_, claves) = guard.escanear(0, usize::MAX);
+    let mut coincidentes: Vec<&Vec<u8>> = claves
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| patron.matches(&String::from_utf8_lossy(key)))
+        .collect();
+    coincidentes.sort();
+    let claves = coincidentes
+        .into_iter()
+        .map(|key| DatoRedis::new_bulk_string_desde_bytes(key.to_vec()))
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    Ok(DatoRedis::new_array_con_contenido(
+        Arrays::new_con_contenido(claves),
+    ))
+}
+
+/// Itera las claves del nodo mediante un cursor (`SCAN cursor [MATCH
+/// patron] [COUNT cantidad] [TYPE tipo]`). Toda clave presente durante la
+/// iteracion completa se devuelve al menos una vez
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, cursor y opciones
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Cursor siguiente (0 al terminar) y claves obtenidas, error de redis
+///   si el cursor o las opciones son invalidas
+pub fn scan(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let cursor = parsear_cursor(&tokens[1])?;
+    let opciones = parsear_opciones_scan(&tokens[2..], true)?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let (siguiente, claves) = guard.escanear(cursor, opciones.count);
+    let claves = claves
+        .into_iter()
+        .filter(|(key, valor)| {
+            opciones.coincide(key)
+                && opciones
+                    .tipo
+                    .as_ref()
+                    .is_none_or(|tipo| tipo == nombre_tipo(valor))
+        })
+        .map(|(key, _)| DatoRedis::new_bulk_string_desde_bytes(key.to_vec()))
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    respuesta_scan(siguiente, claves)
+}
+
+/// Renombra una clave. Si la clave destino existe, se sobrescribe
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y nuevo nombre
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - OK en caso de exito, error de redis si la clave no existe o ambas
+///   claves no pertenecen al mismo slot
+pub fn rename(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 3, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+    verificar_mismo_slot(&guard, &tokens[1], &tokens[2])?;
+
+    guard
+        .renombrar(&tokens[1], &tokens[2])
+        .map_err(error_clave_inexistente)?;
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Renombra una clave solo si la clave destino no existe
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y nuevo nombre
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - 1 si se renombro la clave, 0 si el destino ya existia, error de
+///   redis si la clave no existe o ambas claves no pertenecen al mismo slot
+pub fn renamenx(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 3, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+    verificar_mismo_slot(&guard, &tokens[1], &tokens[2])?;
+
+    guard.get(&tokens[1]).map_err(error_clave_inexistente)?;
+    if guard.get(&tokens[2]).is_ok() {
+        return Ok(DatoRedis::new_integer(0));
+    }
+    guard
+        .renombrar(&tokens[1], &tokens[2])
+        .map_err(error_clave_inexistente)?;
+    Ok(DatoRedis::new_integer(1))
+}
+
+/// Copia el valor y la expiracion de una clave en otra. Soporta las
+/// opciones `REPLACE` y `DB 0`
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave origen, clave
+///   destino y opciones
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - 1 si se copio la clave, 0 si el origen no existe o el destino ya
+///   existia (sin REPLACE), error de redis si las claves no pertenecen al
+///   mismo slot o las opciones son invalidas
+pub fn copy(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+
+    let mut reemplazar = false;
+    let mut opciones = tokens[3..].iter();
+    while let Some(opcion) = opciones.next() {
+        match opcion.to_uppercase().as_str() {
+            OPCION_REPLACE => reemplazar = true,
+            OPCION_DB => match opciones.next() {
+                Some(db) if db == "0" => {}
+                Some(_) => {
+                    return Err(DatoRedis::new_simple_error(
+                        "ERR".to_string(),
+                        "Copying to another database is not allowed in cluster mode".to_string(),
+                    ));
+                }
+                None => return Err(error_sintaxis()),
+            },
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    if tokens[1] == tokens[2] {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "source and destination objects are the same".to_string(),
+        ));
+    }
+
+    let mut guard = get_storage_write_lock(storage)?;
+    verificar_mismo_slot(&guard, &tokens[1], &tokens[2])?;
+    match guard.copiar(&tokens[1], &tokens[2], reemplazar) {
+        Ok(copiada) => Ok(DatoRedis::new_integer(copiada as i64)),
+        Err(DatoRedis::MovedError(e)) => Err(DatoRedis::MovedError(e)),
+        Err(_) => Ok(DatoRedis::new_integer(0)),
+    }
+}
+
+/// Elimina las claves recibidas
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y claves a eliminar
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Cantidad de claves eliminadas, error de redis si las claves no
+///   pertenecen al mismo slot o a los slots del nodo, en cuyo caso no se
+///   elimina ninguna
+pub fn unlink(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+    verificar_claves_mismo_slot(&guard, &tokens[1..])?;
+
+    let mut eliminadas = 0;
+    for key in &tokens[1..] {
+        match guard.remove(key) {
+            Ok(_) => eliminadas += 1,
+            Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+            Err(_) => continue,
+        }
+    }
+    Ok(DatoRedis::new_integer(eliminadas))
+}
+
+/// Obtiene la cantidad de claves almacenadas en el nodo
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo unicamente el nombre del comando
+/// * `storage`: storage del nodo
+///
+/// # Retorna
+/// - Cantidad de claves
+pub fn dbsize(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 1, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    Ok(DatoRedis::new_integer(guard.cantidad_claves() as i64))
+}
+
+/// Elimina todas las claves del nodo. Acepta `ASYNC` y `SYNC`, que se
+/// comportan igual
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y opcionalmente el modo
+/// * `storage`: storage del nodo
+///
+/// # Retorna
+/// - OK en caso de exito, error de redis si el modo es invalido
+pub fn flushall(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    match tokens.get(1).map(|modo| modo.to_uppercase()) {
+        None => {}
+        Some(modo) if tokens.len() == 2 && (modo == OPCION_ASYNC || modo == OPCION_SYNC) => {}
+        Some(_) => return Err(error_sintaxis()),
+    }
+    get_storage_write_lock(storage)?.vaciar();
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Obtiene una clave al azar entre las almacenadas en el nodo
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo unicamente el nombre del comando
+/// * `storage`: storage del nodo
+///
+/// # Retorna
+/// - Nombre de la clave, null si el nodo no tiene claves
+pub fn randomkey(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 1, tokens.len())?;
+    let mut guard = get_storage_write_lock(storage)?;
+    match guard.clave_aleatoria() {
+        Some(key) => DatoRedis::new_bulk_string_desde_bytes(key),
+        None => Ok(DatoRedis::new_null()),
+    }
+}
+
+/// Obtiene el nombre del tipo de un valor, como lo informan TYPE y la
+/// opcion TYPE de SCAN
+pub(crate) fn nombre_tipo(valor: &DatoRedis) -> &'static str {
+    match valor {
+        DatoRedis::Arrays(_) => TIPO_LIST,
+        DatoRedis::Set(_) => TIPO_SET,
+        DatoRedis::Map(_) => TIPO_HASH,
+        DatoRedis::Stream(_) => TIPO_STREAM,
+        DatoRedis::SortedSet(_) => TIPO_ZSET,
+        _ => TIPO_STRING,
+    }
+}
+
+/// Verifica que dos claves pertenezcan al mismo slot, requisito de los
+/// comandos que operan sobre mas de una clave
+pub(crate) fn verificar_mismo_slot(
+    storage: &Storage,
+    origen: &[u8],
+    destino: &[u8],
+) -> Result<(), DatoRedis> {
+    if storage.calculate_slot_for_key(origen) != storage.calculate_slot_for_key(destino) {
+        return Err(DatoRedis::new_simple_error(
+            "CROSSSLOT".to_string(),
+            "Keys in request don't hash to the same slot".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Verifica que todas las claves pertenezcan al mismo slot y que ese slot
+/// sea del nodo, antes de operar sobre alguna de ellas
+fn verificar_claves_mismo_slot(storage: &Storage, claves: &[Token]) -> Result<(), DatoRedis> {
+    for clave in claves.iter().skip(1) {
+        verificar_mismo_slot(storage, &claves[0], clave)?;
+    }
+    let slot = storage.calculate_slot_for_key(&claves[0]);
+    if !storage.get_slot_range().contains(&slot) {
+        return Err(DatoRedis::new_moved_error(slot));
+    }
+    Ok(())
+}
+
+/// Convierte el error de una clave inexistente en el error de redis
+/// correspondiente, conservando los errores MOVED
+fn error_clave_inexistente(error: DatoRedis) -> DatoRedis {
+    match error {
+        DatoRedis::MovedError(_) => error,
+        _ => DatoRedis::new_simple_error("ERR".to_string(), "no such key".to_string()),
+    }
+}
+
+fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use crate::comandos::comandos_keyspace::*;
+    use crate::comandos::comandos_list::rpush;
+    use crate::comandos::comandos_set::sadd;
+    use crate::comandos::comandos_string::{get, set};
+    use crate::memoria::{LFU_INIT_VAL, PoliticaEviction};
+    use crate::utils::utils_functions::CommandFunction;
+    use redis_client::tipos_datos::traits::TipoDatoRedis;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn to_tokens(tokens: &[&str]) -> Vec<Token> {
+        tokens.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn obtener_payload(storage: &Arc<RwLock<Storage>>, key: &str) -> Vec<u8> {
+        match dump(&to_tokens(&["dump", key]), storage).unwrap() {
+            DatoRedis::BulkString(bulk) => bulk.into_bytes(),
+            otro => panic!("Se esperaba un bulk string: {otro:?}"),
+        }
+    }
+
+    fn tokens_restore(key: &str, ttl: &str, payload: &[u8], opciones: &[&str]) -> Vec<Token> {
+        let mut tokens = to_tokens(&["restore", key, ttl]);
+        tokens.push(Token::from(payload));
+        tokens.extend(to_tokens(opciones));
+        tokens
+    }
+
+    #[test]
+    fn test_01_dump_clave_inexistente_devuelve_null() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let resultado = dump(&to_tokens(&["dump", "key1"]), &storage).unwrap();
+        assert_eq!(resultado, DatoRedis::new_null());
+    }
+
+    #[test]
+    fn test_02_dump_y_restore_de_string() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+        let payload = obtener_payload(&storage, "key1");
+        assert!(payload.windows(5).any(|bytes| bytes == b"valor"));
+
+        restore(&tokens_restore("key2", "0", &payload, &[]), &storage).unwrap();
+        let valor = get(&to_tokens(&["get", "key2"]), &storage).unwrap();
+        assert_eq!(
+            valor,
+            DatoRedis::new_bulk_string("valor".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_03_dump_y_restore_de_lista_y_set() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        rpush(&to_tokens(&["rpush", "lista", "a", "b", "c"]), &storage).unwrap();
+        sadd(&to_tokens(&["sadd", "conjunto", "x", "y"]), &storage).unwrap();
+
+        let payload_lista = obtener_payload(&storage, "lista");
+        let payload_set = obtener_payload(&storage, "conjunto");
+        restore(
+            &tokens_restore("lista2", "0", &payload_lista, &[]),
+            &storage,
+        )
+        .unwrap();
+        restore(
+            &tokens_restore("conjunto2", "0", &payload_set, &[]),
+            &storage,
+        )
+        .unwrap();
+
+        let guard = storage.read().unwrap();
+        assert_eq!(guard.get("lista").unwrap(), guard.get("lista2").unwrap());
+        assert_eq!(
+            guard.get("conjunto").unwrap(),
+            guard.get("conjunto2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_04_restore_sobre_clave_existente_requiere_replace() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "nuevo"]), &storage).unwrap();
+        set(&to_tokens(&["set", "key2", "viejo"]), &storage).unwrap();
+        let payload = obtener_payload(&storage, "key1");
+
+        let error = restore(&tokens_restore("key2", "0", &payload, &[]), &storage);
+        assert!(matches!(error, Err(DatoRedis::SimpleError(_))));
+
+        restore(
+            &tokens_restore("key2", "0", &payload, &["REPLACE"]),
+            &storage,
+        )
+        .unwrap();
+        let valor = get(&to_tokens(&["get", "key2"]), &storage).unwrap();
+        assert_eq!(
+            valor,
+            DatoRedis::new_bulk_string("nuevo".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_05_restore_con_payload_corrupto_falla() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+        let mut payload = obtener_payload(&storage, "key1");
+        payload[0] = 0xff;
+
+        assert!(restore(&tokens_restore("key2", "0", &payload, &[]), &storage).is_err());
+        assert!(restore(&tokens_restore("key2", "0", b"zz", &[]), &storage).is_err());
+        assert!(restore(&tokens_restore("key2", "-1", &payload, &[]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_06_restore_con_ttl_expira_la_clave() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+        let payload = obtener_payload(&storage, "key1");
+
+        restore(&tokens_restore("key2", "1", &payload, &[]), &storage).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let resultado = dump(&to_tokens(&["dump", "key2"]), &storage).unwrap();
+        assert_eq!(resultado, DatoRedis::new_null());
+    }
+
+    #[test]
+    fn test_07_memory_usage() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "chica", "a"]), &storage).unwrap();
+        set(&to_tokens(&["set", "grande", &"a".repeat(1000)]), &storage).unwrap();
+
+        let uso = |key: &str| match memory(&to_tokens(&["memory", "usage", key]), &storage) {
+            Ok(DatoRedis::Integer(entero)) => entero.valor(),
+            otro => panic!("Se esperaba un entero: {otro:?}"),
+        };
+        assert!(uso("grande") > uso("chica") + 900);
+        assert_eq!(
+            memory(&to_tokens(&["memory", "usage", "inexistente"]), &storage).unwrap(),
+            DatoRedis::new_null()
+        );
+        assert!(memory(&to_tokens(&["memory", "doctor", "chica"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_08_object_freq_requiere_politica_lfu() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+
+        assert!(object(&to_tokens(&["object", "freq", "key1"]), &storage).is_err());
+        assert_eq!(
+            object(&to_tokens(&["object", "idletime", "key1"]), &storage).unwrap(),
+            DatoRedis::new_integer(0)
+        );
+
+        storage
+            .write()
+            .unwrap()
+            .configurar_memoria(0, PoliticaEviction::AllKeysLfu);
+        let frecuencia = object(&to_tokens(&["object", "freq", "key1"]), &storage).unwrap();
+        assert!(matches!(frecuencia, DatoRedis::Integer(f) if f.valor() >= LFU_INIT_VAL as i64));
+        assert!(object(&to_tokens(&["object", "idletime", "key1"]), &storage).is_err());
+    }
+
+    fn entero(resultado: Result<DatoRedis, DatoRedis>) -> i64 {
+        match resultado {
+            Ok(DatoRedis::Integer(entero)) => entero.valor(),
+            otro => panic!("Se esperaba un entero: {otro:?}"),
+        }
+    }
+
+    #[test]
+    fn test_09_exists_type_y_dbsize() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let lista = clave_vecina(&storage, "cadena", true);
+        let otra = clave_vecina(&storage, &lista, true);
+        set(&to_tokens(&["set", "cadena", "valor"]), &storage).unwrap();
+        rpush(&to_tokens(&["rpush", &lista, "a"]), &storage).unwrap();
+        sadd(&to_tokens(&["sadd", "conjunto", "x"]), &storage).unwrap();
+
+        let existentes = exists(
+            &to_tokens(&["exists", "cadena", &lista, "cadena", &otra]),
+            &storage,
+        );
+        assert_eq!(entero(existentes), 3);
+        set(&to_tokens(&["set", "expirada", "valor"]), &storage).unwrap();
+        storage
+            .write()
+            .unwrap()
+            .set_expiracion("expirada", Some(ahora_ms() - 1))
+            .unwrap();
+        assert_eq!(entero(dbsize(&to_tokens(&["dbsize"]), &storage)), 4);
+        storage.write().unwrap().expirar_claves(&["expirada"]);
+        assert_eq!(entero(dbsize(&to_tokens(&["dbsize"]), &storage)), 3);
+
+        for (key, tipo) in [
+            ("cadena", "string"),
+            (lista.as_str(), "list"),
+            ("conjunto", "set"),
+            (otra.as_str(), "none"),
+        ] {
+            assert_eq!(
+                key_type(&to_tokens(&["type", key]), &storage).unwrap(),
+                DatoRedis::new_simple_string(tipo.to_string()).unwrap()
+            );
+        }
+    }
+
+    /// Busca una clave que pertenezca al mismo slot que `key`, o a otro
+    /// distinto, segun `mismo_slot`
+    fn clave_vecina(storage: &Arc<RwLock<Storage>>, key: &str, mismo_slot: bool) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(key);
+        (0..)
+            .map(|i| format!("{key}{i}"))
+            .find(|candidata| (guard.calculate_slot_for_key(candidata) == slot) == mismo_slot)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_10_rename_conserva_valor_y_expiracion() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let destino = clave_vecina(&storage, "origen", true);
+        set(&to_tokens(&["set", "origen", "valor"]), &storage).unwrap();
+        storage
+            .write()
+            .unwrap()
+            .set_expiracion("origen", Some(ahora_ms() + 60_000))
+            .unwrap();
+
+        rename(&to_tokens(&["rename", "origen", &destino]), &storage).unwrap();
+        {
+            let guard = storage.read().unwrap();
+            assert!(guard.get("origen").is_err());
+            assert_eq!(
+                guard.get(&destino).unwrap(),
+                DatoRedis::new_bulk_string("valor".to_string()).unwrap()
+            );
+            assert_eq!(guard.cantidad_claves_volatiles(), 1);
+        }
+
+        assert!(rename(&to_tokens(&["rename", "origen", &destino]), &storage).is_err());
+        set(&to_tokens(&["set", "origen", "otro"]), &storage).unwrap();
+        let renombrada = renamenx(&to_tokens(&["renamenx", "origen", &destino]), &storage);
+        assert_eq!(entero(renombrada), 0);
+    }
+
+    #[test]
+    fn test_11_comandos_de_dos_claves_en_distinto_slot_fallan() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let otra = clave_vecina(&storage, "key", false);
+        set(&to_tokens(&["set", "key", "valor"]), &storage).unwrap();
+
+        let comandos: [(&str, CommandFunction); 5] = [
+            ("rename", rename),
+            ("renamenx", renamenx),
+            ("copy", copy),
+            ("exists", exists),
+            ("unlink", unlink),
+        ];
+        for (nombre, comando) in comandos {
+            let error = comando(&to_tokens(&[nombre, "key", &otra]), &storage);
+            assert!(matches!(
+                error,
+                Err(DatoRedis::SimpleError(e)) if e.tipo() == "CROSSSLOT"
+            ));
+        }
+        assert!(storage.read().unwrap().get("key").is_ok());
+        assert!(storage.read().unwrap().get(otra).is_err());
+    }
+
+    #[test]
+    fn test_12_copy_respeta_replace() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let destino = clave_vecina(&storage, "origen", true);
+        set(&to_tokens(&["set", "origen", "nuevo"]), &storage).unwrap();
+
+        let copiar = |opciones: &[&str]| {
+            let mut tokens = vec!["copy", "origen", destino.as_str()];
+            tokens.extend_from_slice(opciones);
+            copy(&to_tokens(&tokens), &storage)
+        };
+        assert_eq!(entero(copiar(&[])), 1);
+        set(&to_tokens(&["set", "origen", "otro"]), &storage).unwrap();
+        assert_eq!(entero(copiar(&[])), 0);
+        assert_eq!(entero(copiar(&["DB", "0", "REPLACE"])), 1);
+        assert!(copiar(&["DB", "1"]).is_err());
+        assert!(copiar(&["ahora"]).is_err());
+
+        let guard = storage.read().unwrap();
+        assert_eq!(guard.get(&destino).unwrap(), guard.get("origen").unwrap());
+    }
+
+    #[test]
+    fn test_13_unlink_flushall_y_randomkey() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(
+            randomkey(&to_tokens(&["randomkey"]), &storage).unwrap(),
+            DatoRedis::new_null()
+        );
+        let otra = clave_vecina(&storage, "key1", true);
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+        set(&to_tokens(&["set", "key2", "valor"]), &storage).unwrap();
+        set(&to_tokens(&["set", "key3", "valor"]), &storage).unwrap();
+
+        assert_eq!(
+            entero(unlink(&to_tokens(&["unlink", "key1", &otra]), &storage)),
+            1
+        );
+        let fuera_de_rango = Arc::new(RwLock::new(Storage::new(0..1)));
+        assert!(matches!(
+            unlink(&to_tokens(&["unlink", "key1", &otra]), &fuera_de_rango),
+            Err(DatoRedis::MovedError(_))
+        ));
+        let aleatoria = randomkey(&to_tokens(&["randomkey"]), &storage).unwrap();
+        assert!(
+            [
+                DatoRedis::new_bulk_string("key2".to_string()).unwrap(),
+                DatoRedis::new_bulk_string("key3".to_string()).unwrap()
+            ]
+            .contains(&aleatoria)
+        );
+
+        assert!(flushall(&to_tokens(&["flushall", "lazy"]), &storage).is_err());
+        flushall(&to_tokens(&["flushall", "async"]), &storage).unwrap();
+        assert_eq!(entero(dbsize(&to_tokens(&["dbsize"]), &storage)), 0);
+        assert_eq!(storage.write().unwrap().memoria_usada(), 0);
+    }
+
+    #[test]
+    fn test_14_keys_con_patron() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        for key in ["user:1", "user:2", "doc:1"] {
+            set(&to_tokens(&["set", key, "valor"]), &storage).unwrap();
+        }
+        let esperado = DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![
+            DatoRedis::new_bulk_string("user:1".to_string()).unwrap(),
+            DatoRedis::new_bulk_string("user:2".to_string()).unwrap(),
+        ]));
+        assert_eq!(
+            keys(&to_tokens(&["keys", "user:*"]), &storage).unwrap(),
+            esperado
+        );
+    }
+
+    #[test]
+    fn test_15_scan_devuelve_toda_clave_presente_durante_la_iteracion() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let claves: Vec<String> = (0..50).map(|i| format!("key{i}")).collect();
+        for key in &claves {
+            set(&to_tokens(&["set", key, "valor"]), &storage).unwrap();
+        }
+        rpush(&to_tokens(&["rpush", "lista", "a"]), &storage).unwrap();
+
+        let mut cursor = "0".to_string();
+        let mut vistas = Vec::new();
+        let mut llamadas = 0;
+        loop {
+            let respuesta = scan(&to_tokens(&["scan", &cursor, "COUNT", "5"]), &storage).unwrap();
+            let DatoRedis::Arrays(respuesta) = respuesta else {
+                panic!("Se esperaba un array");
+            };
+            let Some(DatoRedis::BulkString(siguiente)) = respuesta.get(0) else {
+                panic!("Se esperaba un cursor");
+            };
+            let Some(DatoRedis::Arrays(elementos)) = respuesta.get(1) else {
+                panic!("Se esperaba un array de claves");
+            };
+            vistas.extend(elementos.iter().map(|dato| match dato {
+                DatoRedis::BulkString(bulk) => bulk.contenido(),
+                otro => panic!("Se esperaba un bulk string: {otro:?}"),
+            }));
+            // Las claves agregadas durante la iteracion pueden o no aparecer
+            set(
+                &to_tokens(&["set", &format!("nueva{llamadas}"), "v"]),
+                &storage,
+            )
+            .unwrap();
+            llamadas += 1;
+            cursor = siguiente.contenido();
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert!(llamadas > 1);
+        assert!(claves.iter().all(|key| vistas.contains(key)));
+        assert!(vistas.contains(&"lista".to_string()));
+
+        let solo_listas = scan(
+            &to_tokens(&["scan", "0", "COUNT", "1000", "TYPE", "list"]),
+            &storage,
+        )
+        .unwrap()
+        .convertir_resp_a_string();
+        assert!(solo_listas.contains("lista") && !solo_listas.contains("key1"));
+        assert!(scan(&to_tokens(&["scan", "abc"]), &storage).is_err());
+        assert!(scan(&to_tokens(&["scan", "0", "COUNT", "0"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_16_restore_reescrito_con_absttl_conserva_la_expiracion() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&to_tokens(&["set", "key1", "valor"]), &storage).unwrap();
+        let payload = obtener_payload(&storage, "key1");
+
+        let antes = ahora_ms();
+        let tokens = restore_con_expiracion_absoluta(&tokens_restore(
+            "key2",
+            "60000",
+            &payload,
+            &["REPLACE"],
+        ));
+        assert_eq!(tokens[4], "REPLACE");
+        assert_eq!(tokens[5], OPCION_ABSTTL);
+        let instante = tokens[2].parse::<u64>().unwrap();
+        assert!(instante >= antes + 60000);
+        assert_eq!(restore_con_expiracion_absoluta(&tokens), tokens);
+
+        restore(&tokens, &storage).unwrap();
+        assert_eq!(
+            get_storage_read_lock(&storage).unwrap().expiracion("key2"),
+            Some(instante)
+        );
+        assert_eq!(
+            restore_con_expiracion_absoluta(&tokens_restore("key3", "0", &payload, &[])),
+            tokens_restore("key3", "0", &payload, &[])
+        );
+    }
+}
@@ -3,14 +3,25 @@
 use std::{
     cmp::Ordering,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use crate::comandos::utils::assert_correct_arguments_quantity;
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::const_cmd::{CMD_BLMOVE, CMD_LMOVE};
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+};
+use crate::memoria::VariacionMemoria;
 use crate::storage::Storage;
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::{arrays::Arrays, bulk_string::BulkString, traits::DatoRedis};
 
 use super::utils::{get_storage_read_lock, get_storage_write_lock};
 
+const OPCION_COUNT: &str = "COUNT";
+const OPCION_RANK: &str = "RANK";
+const OPCION_MAXLEN: &str = "MAXLEN";
+
 /// Inserta un elemento en una lista antes o despues
 /// de un elemento de referencia (pivote)
 ///
@@ -21,33 +32,45 @@ use super::utils::{get_storage_read_lock, get_storage_write_lock};
 ///
 /// # Retorna
 /// - en caso de insercion exitosa, el nuevo largo del arreglo, error simple de redis en otros casos
-pub fn linsert(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 5, tokens.len())?;
+pub fn linsert(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 5, tokens.len())?;
 
     let posicion = tokens[2].to_uppercase();
     if posicion != "BEFORE" && posicion != "AFTER" {
         return Err(DatoRedis::new_simple_error(
             "ERR".to_string(),
-            "Syntax error: expected BEFORE or AFTER".to_string(),
+            "syntax error".to_string(),
         ));
     }
 
-    let pivot = BulkString::new(tokens[3].to_string())?;
-    let nuevo_elemento = DatoRedis::new_bulk_string(tokens[4].to_string())?;
+    let pivot = BulkString::new_desde_bytes(tokens[3].to_vec())?;
+    let nuevo_elemento = DatoRedis::new_bulk_string_desde_bytes(tokens[4].to_vec())?;
 
     let mut guard = get_storage_write_lock(storage)?;
 
-    let valor = guard.get_mutable(tokens[1].to_string())?;
+    // Una clave inexistente se considera una lista vacia
+    let valor = match guard.get_mutable(&tokens[1]) {
+        Ok(valor) => valor,
+        Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+        Err(_) => return Ok(DatoRedis::new_integer(0)),
+    };
 
-    match valor {
-        DatoRedis::Arrays(arrays) => {
-            procesar_insercion(arrays, posicion.as_str(), pivot, nuevo_elemento)
-        }
+    let mut variacion = VariacionMemoria::default();
+    let respuesta = match valor {
+        DatoRedis::Arrays(arrays) => procesar_insercion(
+            arrays,
+            posicion.as_str(),
+            pivot,
+            nuevo_elemento,
+            &mut variacion,
+        ),
         _ => Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
             "Operation against a key holding the wrong kind of value".to_string(),
         )),
-    }
+    };
+    guard.registrar_variacion(&tokens[1], variacion);
+    respuesta
 }
 
 /// Inserta los elementos recibidos al inicio de la lista
@@ -61,9 +84,10 @@ pub fn linsert(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<Dato
 /// # Retorna
 /// - en caso de insercion exitosa, el nuevo largo del arreglo,
 ///   error simple de redis en otros casos
-pub fn lpush(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    push_elements(tokens, storage, |arrays, element| {
-        arrays.insert(0, element).map_err(|_| DatoRedis::new_null())
+pub fn lpush(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    push_elements(tokens, storage, true, |arrays, element| {
+        arrays.push_front(element);
+        Ok(())
     })
 }
 
@@ -78,8 +102,46 @@ pub fn lpush(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
 /// # Retorna
 /// - en caso de insercion exitosa, el nuevo largo del arreglo,
 ///   error simple de redis en otros casos
-pub fn rpush(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    push_elements(tokens, storage, |arrays, element| {
+pub fn rpush(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    push_elements(tokens, storage, true, |arrays, element| {
+        arrays.append(element);
+        Ok(())
+    })
+}
+
+/// Inserta los elementos recibidos al inicio de la lista, solo si la
+/// lista ya existe
+///
+/// # Parámetros
+/// * tokens: lista conteniendo nombre del comando, nombre de la lista donde insertar
+///   y elementos a insertar
+/// * storage: storage del nodo donde se encuentra la lista donde
+///   insertar
+///
+/// # Retorna
+/// - el nuevo largo del arreglo, 0 si la lista no existe, error simple de
+///   redis en otros casos
+pub fn lpushx(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    push_elements(tokens, storage, false, |arrays, element| {
+        arrays.push_front(element);
+        Ok(())
+    })
+}
+
+/// Inserta los elementos recibidos al final de la lista, solo si la
+/// lista ya existe
+///
+/// # Parámetros
+/// * tokens: lista conteniendo nombre del comando, lista donde insertar
+///   y elementos a insertar
+/// * storage: storage del nodo donde se encuentra la lista donde
+///   insertar
+///
+/// # Retorna
+/// - el nuevo largo del arreglo, 0 si la lista no existe, error simple de
+///   redis en otros casos
+pub fn rpushx(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    push_elements(tokens, storage, false, |arrays, element| {
         arrays.append(element);
         Ok(())
     })
@@ -95,12 +157,12 @@ pub fn rpush(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
 /// # Retorna
 /// - en caso de calculo exitoso, el largo del arreglo,
 ///   error simple de redis en otros casos
-pub fn llen(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+pub fn llen(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
 
     let guard = get_storage_read_lock(storage)?;
 
-    match guard.get(tokens[1].to_string()) {
+    match guard.get(&tokens[1]) {
         Ok(DatoRedis::Arrays(list)) => Ok(DatoRedis::new_integer(list.len() as i64)),
         Ok(_) => Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
@@ -124,7 +186,7 @@ pub fn llen(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// # Retorna
 /// - en caso de calculo exitoso, el nuevo largo del arreglo,
 ///   error simple de redis en otros casos
-pub fn lpop(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+pub fn lpop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
     pop_elements_with_action(tokens, storage, |list: &mut Arrays| {
         if list.is_empty() {
             return Err(DatoRedis::new_null());
@@ -150,7 +212,7 @@ pub fn lpop(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// # Retorna
 /// - en caso de calculo exitoso, el nuevo largo del arreglo,
 ///   error simple de redis en otros casos
-pub fn rpop(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+pub fn rpop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
     pop_elements_with_action(tokens, storage, |list: &mut Arrays| {
         if let Some(e) = list.get(list.len() - 1) {
             let _ = list
@@ -173,16 +235,16 @@ pub fn rpop(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// # Retorna
 /// - en caso de exito, los elementos, en el rango indicado, arreglo
 ///   vacio en otros casos (por ejemplo, indices invalidos)
-pub fn lrange(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 4, tokens.len())?;
+pub fn lrange(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let start = parse_index(&tokens[2])?;
     let stop = parse_index(&tokens[3])?;
 
     let guard = get_storage_read_lock(storage)?;
 
-    match guard.get(key.clone()) {
+    match guard.get(key) {
         Ok(DatoRedis::Arrays(arrays)) => obtener_rango(arrays, start, stop),
         Ok(_) => Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
@@ -206,16 +268,16 @@ pub fn lrange(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 /// # Retorna
 /// - en caso de insercion exitosa, OK,
 ///   error simple de redis en otros casos
-pub fn lset(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 4, tokens.len())?;
+pub fn lset(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let index = parse_index(&tokens[2])?;
-    let nuevo_elemento = DatoRedis::new_bulk_string(tokens[3].to_string())?;
+    let nuevo_elemento = DatoRedis::new_bulk_string_desde_bytes(tokens[3].to_vec())?;
 
     let mut guard = get_storage_write_lock(storage)?;
 
-    let lista = match guard.get_mutable(key.clone()) {
+    let lista = match guard.get_mutable(key) {
         Ok(valor) => valor,
         Err(e) => match e {
             DatoRedis::MovedError(_) => return Err(e),
@@ -228,14 +290,17 @@ pub fn lset(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
         },
     };
 
-    if let DatoRedis::Arrays(arrays) = &mut *lista {
-        setear_elemento(arrays, index, nuevo_elemento)
+    let mut variacion = VariacionMemoria::default();
+    let respuesta = if let DatoRedis::Arrays(arrays) = &mut *lista {
+        setear_elemento(arrays, index, nuevo_elemento, &mut variacion)
     } else {
         Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
             "Operation against a key holding the wrong kind of value".to_string(),
         ))
-    }
+    };
+    guard.registrar_variacion(key, variacion);
+    respuesta
 }
 
 /// Reduce una lista a un rango determinado
@@ -248,16 +313,16 @@ pub fn lset(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// # Retorna
 /// - en caso de modificacion exitosa, OK, lista vacia en caso de
 ///   error (por ejemplo, indices invalidos)
-pub fn ltrim(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 4, tokens.len())?;
+pub fn ltrim(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let start = parse_index(&tokens[2])?;
     let end = parse_index(&tokens[3])?;
 
     let mut guard = get_storage_write_lock(storage)?;
 
-    let lista = match guard.get_mutable(key.clone()) {
+    let lista = match guard.get_mutable(key) {
         Ok(valor) => valor,
         Err(e) => match e {
             DatoRedis::MovedError(_) => return Err(e),
@@ -268,15 +333,18 @@ pub fn ltrim(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
         },
     };
 
-    if let DatoRedis::Arrays(arrays) = &mut *lista {
-        recortar_lista(arrays, start, end)?;
-        Ok(DatoRedis::new_simple_string("OK".to_string())?)
+    let mut variacion = VariacionMemoria::default();
+    let respuesta = if let DatoRedis::Arrays(arrays) = &mut *lista {
+        recortar_lista(arrays, start, end, &mut variacion)?;
+        DatoRedis::new_simple_string("OK".to_string())
     } else {
         Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
             "Operation against a key holding the wrong kind of value".to_string(),
         ))
-    }
+    };
+    guard.registrar_variacion(key, variacion);
+    respuesta
 }
 
 /// Retorna el elemento de una posicion determinada de una lista
@@ -291,13 +359,13 @@ pub fn ltrim(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
 /// - en caso de insercion exitosa, el elemento, nil para indices
 ///   invalidos y error simple en caso de que no haya una lista en la key
 ///   indicada
-pub fn lindex(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
+pub fn lindex(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
 
     let index = parse_index(&tokens[2])?;
     let guard = get_storage_read_lock(storage)?;
 
-    let lista = guard.get(tokens[1].to_string())?;
+    let lista = guard.get(&tokens[1])?;
 
     if let DatoRedis::Arrays(arrays) = lista {
         obtener_elemento(&arrays, index)
@@ -324,30 +392,33 @@ pub fn lindex(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 /// - en caso de eliminacion exitosa, la cantidad de elementos
 ///   removidos y error simple en caso de que no haya una lista en la key
 ///   indicada
-pub fn lrem(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 4, tokens.len())?;
+pub fn lrem(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let count = parse_index(&tokens[2])?;
-    let elemento = DatoRedis::new_bulk_string(tokens[3].to_string())?;
+    let elemento = DatoRedis::new_bulk_string_desde_bytes(tokens[3].to_vec())?;
 
     let mut guard = get_storage_write_lock(storage)?;
 
-    let valor = match guard.get_mutable(key.clone()) {
+    let valor = match guard.get_mutable(key) {
         Ok(v) => v,
         Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
         Err(_) => return Ok(DatoRedis::new_integer(0)),
     };
 
-    if let DatoRedis::Arrays(arrays) = valor {
-        let removed = remover_elementos(arrays, &elemento, count);
+    let mut variacion = VariacionMemoria::default();
+    let respuesta = if let DatoRedis::Arrays(arrays) = valor {
+        let removed = remover_elementos(arrays, &elemento, count, &mut variacion);
         Ok(DatoRedis::new_integer(removed))
     } else {
         Err(DatoRedis::new_simple_error(
             "WRONGTYPE".to_string(),
             "Operation against a key holding the wrong kind of value".to_string(),
         ))
-    }
+    };
+    guard.registrar_variacion(key, variacion);
+    respuesta
 }
 
 /// Mueve un elemento de una lista a otra
@@ -367,8 +438,8 @@ pub fn lrem(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// # Retorna
 /// - en caso de movimiento exitosa, el elemento y error simple en
 ///   otros casos
-pub fn lmove(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].clone(), 5, tokens.len())?;
+pub fn lmove(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 5, tokens.len())?;
 
     let source_key = tokens[1].clone();
     let dest_key = tokens[2].clone();
@@ -378,7 +449,7 @@ pub fn lmove(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
     let mut guard = get_storage_write_lock(storage)?;
 
     // Obtener lista origen
-    let array_origen = match guard.get_mutable(source_key.clone()) {
+    let array_origen = match guard.get_mutable(&source_key) {
         Ok(DatoRedis::Arrays(arr)) => arr,
         Ok(_) => {
             return Err(DatoRedis::new_simple_error(
@@ -396,10 +467,12 @@ pub fn lmove(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
         return Err(DatoRedis::new_null());
     }
 
-    let elemento = extraer_elemento(array_origen, &wherefrom)?;
+    let mut variacion = VariacionMemoria::default();
+    let elemento = extraer_elemento(array_origen, &wherefrom, &mut variacion)?;
+    guard.registrar_variacion(&source_key, variacion);
 
     // Obtener o crear lista destino
-    let array_destino = match guard.get_mutable(dest_key.clone()) {
+    let array_destino = match guard.get_mutable(&dest_key) {
         Ok(DatoRedis::Arrays(arr)) => arr,
         Ok(_) => {
             return Err(DatoRedis::new_simple_error(
@@ -409,8 +482,8 @@ pub fn lmove(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
         }
         Err(e @ DatoRedis::MovedError(_)) => return Err(e),
         Err(_) => {
-            guard.set(dest_key.clone(), DatoRedis::new_array())?;
-            match guard.get_mutable(dest_key.clone()) {
+            guard.set(&dest_key, DatoRedis::new_array())?;
+            match guard.get_mutable(&dest_key) {
                 Ok(DatoRedis::Arrays(arr)) => arr,
                 Ok(_) => {
                     return Err(DatoRedis::new_simple_error(
@@ -429,21 +502,410 @@ pub fn lmove(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
         }
     };
 
-    insertar_elemento(array_destino, &whereto, elemento.clone())?;
+    let mut variacion = VariacionMemoria::default();
+    insertar_elemento(array_destino, &whereto, elemento.clone(), &mut variacion)?;
+    guard.registrar_variacion(&dest_key, variacion);
     Ok(elemento)
 }
 
+/// Equivalente a LMOVE origen destino RIGHT LEFT
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, lista origen y lista
+///   destino
+/// * `storage`: storage del nodo donde se encuentran las listas
+///
+/// # Retorna
+/// - el elemento movido, null si la lista origen esta vacia o no existe,
+///   error simple de redis en otros casos
+pub fn rpoplpush(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let equivalente = [
+        Token::from(CMD_LMOVE),
+        tokens[1].clone(),
+        tokens[2].clone(),
+        Token::from("RIGHT"),
+        Token::from("LEFT"),
+    ];
+    lmove(&equivalente, storage)
+}
+
+/// Elimina hasta count elementos de un extremo de la primera lista no
+/// vacia entre las indicadas
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, cantidad de claves,
+///   las claves, LEFT o RIGHT y opcionalmente COUNT count
+/// * `storage`: storage del nodo donde se encuentran las listas
+///
+/// # Retorna
+/// - arreglo con la clave y los elementos eliminados, null si todas las
+///   listas estan vacias o no existen, error simple de redis en otros casos
+pub fn lmpop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let numkeys = tokens[1]
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "numkeys should be greater than 0".to_string(),
+            )
+        })?;
+    if tokens.len() < numkeys + 3 {
+        return Err(error_sintaxis());
+    }
+    let claves = &tokens[2..numkeys + 2];
+    let wherefrom = tokens[numkeys + 2].to_uppercase();
+    if wherefrom != "LEFT" && wherefrom != "RIGHT" {
+        return Err(error_sintaxis());
+    }
+    let count = match &tokens[numkeys + 3..] {
+        [] => 1,
+        [opcion, valor] if opcion.to_uppercase() == OPCION_COUNT => valor
+            .parse::<usize>()
+            .ok()
+            .filter(|c| *c > 0)
+            .ok_or_else(|| {
+                DatoRedis::new_simple_error(
+                    "ERR".to_string(),
+                    "count should be greater than 0".to_string(),
+                )
+            })?,
+        _ => return Err(error_sintaxis()),
+    };
+
+    let mut guard = get_storage_write_lock(storage)?;
+    for clave in claves.iter().skip(1) {
+        verificar_mismo_slot(&guard, &claves[0], clave)?;
+    }
+    for clave in claves {
+        let mut variacion = VariacionMemoria::default();
+        let elementos = match guard.get_mutable(clave) {
+            Ok(DatoRedis::Arrays(lista)) if !lista.is_empty() => {
+                let mut elementos = Arrays::new();
+                for _ in 0..count.min(lista.len()) {
+                    elementos.append(extraer_elemento(lista, &wherefrom, &mut variacion)?);
+                }
+                elementos
+            }
+            Ok(DatoRedis::Arrays(_)) => continue,
+            Ok(_) => {
+                return Err(DatoRedis::new_simple_error(
+                    "WRONGTYPE".to_string(),
+                    "Operation against a key holding the wrong kind of value".to_string(),
+                ));
+            }
+            Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+            Err(_) => continue,
+        };
+        guard.registrar_variacion(clave, variacion);
+        let mut respuesta = Arrays::new();
+        respuesta.append(DatoRedis::new_bulk_string_desde_bytes(clave.to_vec())?);
+        respuesta.append(DatoRedis::new_array_con_contenido(elementos));
+        return Ok(DatoRedis::new_array_con_contenido(respuesta));
+    }
+    Err(DatoRedis::new_null())
+}
+
+/// Opciones del comando LPOS
+#[derive(Debug, PartialEq)]
+struct OpcionesLpos {
+    /// Numero de coincidencia a partir de la cual se reportan posiciones,
+    /// negativo para recorrer la lista desde el final
+    rank: i64,
+    /// Cantidad de posiciones a reportar (0 para todas), None si no se
+    /// recibio COUNT
+    count: Option<usize>,
+    /// Cantidad maxima de elementos a comparar, 0 para toda la lista
+    maxlen: usize,
+}
+
+/// Busca las posiciones de un elemento en una lista
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, la lista, el elemento
+///   a buscar y opcionalmente RANK rank, COUNT count y MAXLEN len
+/// * `storage`: storage del nodo donde se encuentra la lista
+///
+/// # Retorna
+/// - sin COUNT, la posicion de la coincidencia o null; con COUNT, el
+///   arreglo de posiciones. Error simple de redis en otros casos
+pub fn lpos(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let opciones = parsear_opciones_lpos(&tokens[3..])?;
+    let elemento = DatoRedis::new_bulk_string_desde_bytes(tokens[2].to_vec())?;
+
+    let guard = get_storage_read_lock(storage)?;
+    let posiciones = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::Arrays(lista)) => buscar_posiciones(&lista, &elemento, &opciones),
+        Ok(_) => {
+            return Err(DatoRedis::new_simple_error(
+                "WRONGTYPE".to_string(),
+                "Operation against a key holding the wrong kind of value".to_string(),
+            ));
+        }
+        Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+        Err(_) => Vec::new(),
+    };
+
+    if opciones.count.is_some() {
+        let posiciones = posiciones
+            .into_iter()
+            .map(|p| DatoRedis::new_integer(p as i64))
+            .collect();
+        return Ok(DatoRedis::new_array_con_contenido(
+            Arrays::new_con_contenido(posiciones),
+        ));
+    }
+    Ok(posiciones
+        .first()
+        .map(|p| DatoRedis::new_integer(*p as i64))
+        .unwrap_or_else(DatoRedis::new_null))
+}
+
+/// Interpreta las opciones RANK, COUNT y MAXLEN de LPOS
+fn parsear_opciones_lpos(opciones: &[Token]) -> Result<OpcionesLpos, DatoRedis> {
+    let mut resultado = OpcionesLpos {
+        rank: 1,
+        count: None,
+        maxlen: 0,
+    };
+    for par in opciones.chunks(2) {
+        let [opcion, valor] = par else {
+            return Err(error_sintaxis());
+        };
+        let valor = valor.parse::<i64>().map_err(|_| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "value is not an integer or out of range".to_string(),
+            )
+        })?;
+        match opcion.to_uppercase().as_str() {
+            OPCION_RANK => {
+                if valor == 0 || valor == i64::MIN {
+                    return Err(DatoRedis::new_simple_error(
+                        "ERR".to_string(),
+                        "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string(),
+                    ));
+                }
+                resultado.rank = valor;
+            }
+            OPCION_COUNT => resultado.count = Some(no_negativo(valor, OPCION_COUNT)?),
+            OPCION_MAXLEN => resultado.maxlen = no_negativo(valor, OPCION_MAXLEN)?,
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    Ok(resultado)
+}
+
+fn no_negativo(valor: i64, opcion: &str) -> Result<usize, DatoRedis> {
+    usize::try_from(valor).map_err(|_| {
+        DatoRedis::new_simple_error("ERR".to_string(), format!("{opcion} can't be negative"))
+    })
+}
+
+/// Obtiene las posiciones de las coincidencias de un elemento en una lista,
+/// segun las opciones de LPOS
+fn buscar_posiciones(lista: &Arrays, elemento: &DatoRedis, opciones: &OpcionesLpos) -> Vec<usize> {
+    let limite = match opciones.maxlen {
+        0 => lista.len(),
+        maxlen => maxlen,
+    };
+    let buscadas = match opciones.count {
+        Some(0) => usize::MAX,
+        Some(count) => count,
+        None => 1,
+    };
+    let recorrido: Vec<(usize, &DatoRedis)> = if opciones.rank > 0 {
+        lista.iter().enumerate().take(limite).collect()
+    } else {
+        lista.iter().enumerate().rev().take(limite).collect()
+    };
+    recorrido
+        .into_iter()
+        .filter(|(_, dato)| *dato == elemento)
+        .skip((opciones.rank.unsigned_abs() - 1) as usize)
+        .take(buscadas)
+        .map(|(posicion, _)| posicion)
+        .collect()
+}
+
+fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
+}
+
+/// Intento no bloqueante de BLPOP: elimina el primer elemento de la primera
+/// lista no vacia entre las indicadas
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el nombre de una o mas
+///   listas y el timeout en segundos
+/// * `storage`: storage del nodo donde se encuentran las listas
+///
+/// # Retorna
+/// - arreglo con la clave y el elemento eliminado, null si todas las
+///   listas estan vacias o no existen, error simple de redis en otros casos
+pub fn blpop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    pop_primera_lista(tokens, storage, "LEFT")
+}
+
+/// Intento no bloqueante de BRPOP: elimina el ultimo elemento de la primera
+/// lista no vacia entre las indicadas
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el nombre de una o mas
+///   listas y el timeout en segundos
+/// * `storage`: storage del nodo donde se encuentran las listas
+///
+/// # Retorna
+/// - arreglo con la clave y el elemento eliminado, null si todas las
+///   listas estan vacias o no existen, error simple de redis en otros casos
+pub fn brpop(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    pop_primera_lista(tokens, storage, "RIGHT")
+}
+
+/// Intento no bloqueante de BLMOVE: equivale a LMOVE con un timeout
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, lista origen, lista
+///   destino, wherefrom, whereto y el timeout en segundos
+/// * `storage`: storage del nodo donde se encuentran las listas
+///
+/// # Retorna
+/// - el elemento movido, null si la lista origen esta vacia o no existe,
+///   error simple de redis en otros casos
+pub fn blmove(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    parametros_bloqueo(tokens)?;
+    lmove(&tokens[..5], storage)
+}
+
+/// Obtiene las claves por las que espera un comando bloqueante (BLPOP,
+/// BRPOP o BLMOVE) y su timeout
+///
+/// # Parámetros
+/// * `tokens`: secuencia de strings que conforman el comando
+///
+/// # Retorna
+/// - claves a esperar y timeout (None si es 0, es decir, sin limite),
+///   error de redis si los argumentos son invalidos
+pub(crate) fn parametros_bloqueo(
+    tokens: &[Token],
+) -> Result<(Vec<Token>, Option<Duration>), DatoRedis> {
+    let comando = tokens[0].to_uppercase();
+    let claves = if comando == CMD_BLMOVE {
+        assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 6, tokens.len())?;
+        if tokens[3..5]
+            .iter()
+            .any(|direccion| !matches!(direccion.to_uppercase().as_str(), "LEFT" | "RIGHT"))
+        {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "syntax error".to_string(),
+            ));
+        }
+        vec![tokens[1].clone()]
+    } else {
+        assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+        tokens[1..tokens.len() - 1].to_vec()
+    };
+    let timeout = parsear_timeout(&tokens[tokens.len() - 1])?;
+    Ok((claves, timeout))
+}
+
+/// Convierte el timeout en segundos de un comando bloqueante
+///
+/// # Retorna
+/// - None si el timeout es 0, la duracion en otro caso, error de redis
+///   si no es un numero o es negativo
+fn parsear_timeout(token: &Token) -> Result<Option<Duration>, DatoRedis> {
+    let segundos = token
+        .parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite())
+        .ok_or_else(|| {
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "timeout is not a float or out of range".to_string(),
+            )
+        })?;
+    if segundos < 0.0 {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "timeout is negative".to_string(),
+        ));
+    }
+    if segundos == 0.0 {
+        return Ok(None);
+    }
+    Ok(Some(Duration::from_secs_f64(segundos)))
+}
+
+/// Elimina un elemento del extremo indicado de la primera lista no vacia
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el nombre de una o mas
+///   listas y el timeout en segundos
+/// * `storage`: storage del nodo donde se encuentran las listas
+/// * `wherefrom`: LEFT para eliminar el primer elemento, RIGHT para el ultimo
+///
+/// # Retorna
+/// - arreglo con la clave y el elemento eliminado, null si todas las
+///   listas estan vacias o no existen, error simple de redis en otros casos
+fn pop_primera_lista(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+    wherefrom: &str,
+) -> Result<DatoRedis, DatoRedis> {
+    let (claves, _) = parametros_bloqueo(tokens)?;
+    let mut guard = get_storage_write_lock(storage)?;
+    for clave in claves.iter().skip(1) {
+        verificar_mismo_slot(&guard, &claves[0], clave)?;
+    }
+
+    for clave in claves {
+        let mut variacion = VariacionMemoria::default();
+        let elemento = match guard.get_mutable(&clave) {
+            Ok(DatoRedis::Arrays(lista)) if !lista.is_empty() => {
+                extraer_elemento(lista, wherefrom, &mut variacion)?
+            }
+            Ok(DatoRedis::Arrays(_)) => continue,
+            Ok(_) => {
+                return Err(DatoRedis::new_simple_error(
+                    "WRONGTYPE".to_string(),
+                    "Operation against a key holding the wrong kind of value".to_string(),
+                ));
+            }
+            Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+            Err(_) => continue,
+        };
+        guard.registrar_variacion(&clave, variacion);
+        let mut respuesta = Arrays::new();
+        respuesta.append(DatoRedis::new_bulk_string_desde_bytes(clave.into_bytes())?);
+        respuesta.append(elemento);
+        return Ok(DatoRedis::new_array_con_contenido(respuesta));
+    }
+    Err(DatoRedis::new_null())
+}
+
 /// Extrae un elemento del inicio o el final de un arreglo
 ///
 /// # Parámetros
 /// * `arrays`: array de donde se remueve el elemento
 /// * `wherefrom`: determina si se remueve el elemento del inicio
 ///   (LEFT) o del final (RIGHT)
+/// * `variacion`: variacion de memoria donde se registra el elemento
+///   removido
 ///
 /// # Retorna
 /// - en caso de movimiento exitosa, el elemento y error simple en
 ///   otros casos
-fn extraer_elemento(arrays: &mut Arrays, wherefrom: &str) -> Result<DatoRedis, DatoRedis> {
+fn extraer_elemento(
+    arrays: &mut Arrays,
+    wherefrom: &str,
+    variacion: &mut VariacionMemoria,
+) -> Result<DatoRedis, DatoRedis> {
     let index = match wherefrom {
         "LEFT" => 0,
         "RIGHT" => arrays.len() - 1,
@@ -455,18 +917,12 @@ fn extraer_elemento(arrays: &mut Arrays, wherefrom: &str) -> Result<DatoRedis, D
         }
     };
 
-    let elemento = arrays.get(index).ok_or_else(DatoRedis::new_null)?;
-
-    match wherefrom {
-        "LEFT" => {
-            arrays.remove(0)?;
-        }
-        "RIGHT" => {
-            arrays.pop()?;
-        }
-        _ => unreachable!(),
+    let elemento = match index {
+        0 => arrays.pop_front(),
+        _ => arrays.pop().ok(),
     };
-
+    let elemento = elemento.ok_or_else(DatoRedis::new_null)?;
+    variacion.liberar(&elemento);
     Ok(elemento)
 }
 
@@ -477,6 +933,8 @@ fn extraer_elemento(arrays: &mut Arrays, wherefrom: &str) -> Result<DatoRedis, D
 /// * `whereto`: determina si se inserrta el elemento al inicio
 ///   (LEFT) o al final (RIGHT)
 /// * `elemento`: elemento a insertar
+/// * `variacion`: variacion de memoria donde se registra el elemento
+///   insertado
 ///
 /// # Retorna
 /// - unit en caso de exito, error simple en otros casos
@@ -484,9 +942,14 @@ fn insertar_elemento(
     arrays: &mut Arrays,
     whereto: &str,
     elemento: DatoRedis,
+    variacion: &mut VariacionMemoria,
 ) -> Result<(), DatoRedis> {
+    variacion.agregar(&elemento);
     match whereto {
-        "LEFT" => arrays.insert(0, elemento),
+        "LEFT" => {
+            arrays.push_front(elemento);
+            Ok(())
+        }
         "RIGHT" => {
             arrays.append(elemento);
             Ok(())
@@ -507,6 +970,8 @@ fn insertar_elemento(
 /// * `posicion`: BEFORE/AFTER relativa al elemento pivote
 /// * `pivot`: un elemento del arreglo
 /// * `nuevo_elemento`: elemento a insertar
+/// * `variacion`: variacion de memoria donde se registra el elemento
+///   insertado
 ///
 /// # Retorna
 /// - nuevo largo del arreglo en caso de exito, error simple en
@@ -516,6 +981,7 @@ fn procesar_insercion(
     posicion: &str,
     pivot: BulkString,
     nuevo_elemento: DatoRedis,
+    variacion: &mut VariacionMemoria,
 ) -> Result<DatoRedis, DatoRedis> {
     if arrays.is_empty() {
         return Ok(DatoRedis::new_integer(0));
@@ -523,6 +989,7 @@ fn procesar_insercion(
 
     if let Some(index) = encontrar_pivot(arrays, &pivot) {
         let insert_index = calcular_indice_insercion(posicion, index)?;
+        variacion.agregar(&nuevo_elemento);
         arrays.insert(insert_index, nuevo_elemento)?;
         Ok(DatoRedis::new_integer(arrays.len() as i64))
     } else {
@@ -570,7 +1037,7 @@ fn calcular_indice_insercion(posicion: &str, index: usize) -> Result<usize, Dato
     }
 }
 
-/// Transforma un token (&str) en un indice del arreglo (i32)
+/// Transforma un token en un indice del arreglo (i32)
 ///
 /// # Parámetros
 /// * `token`: string a transformar
@@ -578,7 +1045,7 @@ fn calcular_indice_insercion(posicion: &str, index: usize) -> Result<usize, Dato
 /// # Retorna
 /// - Posicion correspondiente al parametro pasado en el arreglo,
 ///   error simple de redis en otro caso
-fn parse_index(token: &str) -> Result<i32, DatoRedis> {
+fn parse_index(token: &Token) -> Result<i32, DatoRedis> {
     token.parse::<i32>().map_err(|_| {
         DatoRedis::new_simple_error(
             "ERR".to_string(),
@@ -645,7 +1112,7 @@ fn ajustar_indice(indice: i32, len: i32) -> i32 {
 /// # Retorna
 /// - el valor del parametro buscado como usize en caso de exito,
 ///   error simple de redis en otros casos
-fn parse_count(tokens: &[String]) -> Result<usize, DatoRedis> {
+fn parse_count(tokens: &[Token]) -> Result<usize, DatoRedis> {
     if tokens.len() > 2 {
         tokens[2].parse::<usize>().map_err(|_| {
             DatoRedis::new_simple_error(
@@ -664,37 +1131,49 @@ fn parse_count(tokens: &[String]) -> Result<usize, DatoRedis> {
 /// * `tokens`: lista incluyendo nombre del comando, nombre de la lista
 ///   donde insertar y secuencia de elementos a insertar
 /// * `storage`: storage del nodo donde se encuentra la lista indicada
+/// * `crear`: si es falso y la lista no existe no se inserta nada
 /// * `push`: funcion que inserta un elemento a un arreglo redis
 ///
 /// # Retorna
-/// - El nuevo largo del arreglo en caso de exito, error de redis en
-///   otro caso
+/// - El nuevo largo del arreglo en caso de exito (0 si no existe y no se
+///   debe crear), error de redis en otro caso
 fn push_elements(
-    tokens: &[String],
+    tokens: &[Token],
     storage: &Arc<RwLock<Storage>>,
+    crear: bool,
     push: impl Fn(&mut Arrays, DatoRedis) -> Result<(), DatoRedis>,
 ) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
 
     let key = &tokens[1];
     let mut guard = get_storage_write_lock(storage)?;
 
-    let lista = match guard.get_mutable(key.to_string()) {
+    let lista = match guard.get_mutable(key) {
         Ok(valor) => valor,
+        Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+        Err(_) if !crear => return Ok(DatoRedis::new_integer(0)),
         Err(_) => {
-            guard.set(key.to_string(), DatoRedis::new_array())?;
-            guard.get_mutable(key.to_string())?
+            guard.set(key, DatoRedis::new_array())?;
+            guard.get_mutable(key)?
         }
     };
 
     if let DatoRedis::Arrays(arrays) = &mut *lista {
+        let mut variacion = VariacionMemoria::default();
         for token in tokens.iter().skip(2) {
-            let nuevo_elemento = DatoRedis::new_bulk_string(token.to_string()).map_err(|_| {
-                DatoRedis::new_simple_error("ERR".to_string(), "invalid bulk string".to_string())
-            })?;
+            let nuevo_elemento =
+                DatoRedis::new_bulk_string_desde_bytes(token.to_vec()).map_err(|_| {
+                    DatoRedis::new_simple_error(
+                        "ERR".to_string(),
+                        "invalid bulk string".to_string(),
+                    )
+                })?;
+            variacion.agregar(&nuevo_elemento);
             push(arrays, nuevo_elemento)?;
         }
-        return Ok(DatoRedis::new_integer(arrays.len() as i64));
+        let largo = arrays.len();
+        guard.registrar_variacion(key, variacion);
+        return Ok(DatoRedis::new_integer(largo as i64));
     }
 
     Err(DatoRedis::new_simple_error(
@@ -715,16 +1194,18 @@ fn push_elements(
 /// - El nuevo largo del arreglo en caso de exito, error de redis en
 ///   otro caso
 fn pop_elements_with_action(
-    tokens: &[String],
+    tokens: &[Token],
     storage: &Arc<RwLock<Storage>>,
     action: impl Fn(&mut Arrays) -> Result<Option<DatoRedis>, DatoRedis>,
 ) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].clone(), 2, tokens.len())?;
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
     let mut guard = get_storage_write_lock(storage)?;
-    let lista = guard.get_mutable(tokens[1].clone())?;
+    let lista = guard.get_mutable(&tokens[1])?;
     let count = parse_count(tokens)?;
-    let elements = pop_elements(lista, count, action)?;
-    Ok(DatoRedis::new_array_con_contenido(elements))
+    let mut variacion = VariacionMemoria::default();
+    let elements = pop_elements(lista, count, action, &mut variacion);
+    guard.registrar_variacion(&tokens[1], variacion);
+    Ok(DatoRedis::new_array_con_contenido(elements?))
 }
 
 /// Devuelve los elementos de un arreglo que cumplen con la funcion de
@@ -734,6 +1215,8 @@ fn pop_elements_with_action(
 /// * `lista`: arreglo de donde eliminar los elementos
 /// * `count`: cantidad de elementos donde aplicar la funcion
 /// * `action`: funcion a evaluar con los elementos a eliminar
+/// * `variacion`: variacion de memoria donde se registran los elementos
+///   eliminados
 ///
 /// # Retorna
 /// - Nuevo array con los elementos restantes en caso de exito, error
@@ -742,6 +1225,7 @@ fn pop_elements(
     lista: &mut DatoRedis,
     count: usize,
     action: impl Fn(&mut Arrays) -> Result<Option<DatoRedis>, DatoRedis>,
+    variacion: &mut VariacionMemoria,
 ) -> Result<Arrays, DatoRedis> {
     if let DatoRedis::Arrays(list) = lista {
         if list.is_empty() {
@@ -755,6 +1239,7 @@ fn pop_elements(
             let element = action(list)?;
 
             if let Some(e) = element {
+                variacion.liberar(&e);
                 elements_to_return.append(e);
             } else {
                 return Err(DatoRedis::new_null());
@@ -782,6 +1267,7 @@ fn setear_elemento(
     arrays: &mut Arrays,
     index: i32,
     nuevo_elemento: DatoRedis,
+    variacion: &mut VariacionMemoria,
 ) -> Result<DatoRedis, DatoRedis> {
     let len = arrays.len() as i32;
     let real_index = ajustar_indice(index, len);
@@ -793,6 +1279,10 @@ fn setear_elemento(
         ));
     }
 
+    if let Some(anterior) = arrays.get(real_index as usize) {
+        variacion.liberar(&anterior);
+    }
+    variacion.agregar(&nuevo_elemento);
     let _ = arrays.set(real_index as usize, nuevo_elemento);
     DatoRedis::new_simple_string("OK".to_string())
 }
@@ -804,11 +1294,18 @@ fn setear_elemento(
 /// * `arrays`: arreglo de donde recortar los elementos
 /// * `start`: indice de inicio del recorte
 /// * `end`: indice de fin del recorte
+/// * `variacion`: variacion de memoria donde se registran los elementos
+///   eliminados
 ///
 /// # Retorna
 /// - Nuevo array con los elementos restantes en caso de exito, error
 ///   simple de redis en otros casos
-fn recortar_lista(arrays: &mut Arrays, start: i32, end: i32) -> Result<DatoRedis, DatoRedis> {
+fn recortar_lista(
+    arrays: &mut Arrays,
+    start: i32,
+    end: i32,
+    variacion: &mut VariacionMemoria,
+) -> Result<DatoRedis, DatoRedis> {
     let len = arrays.len() as i32;
     if len == 0 {
         return Ok(DatoRedis::new_null());
@@ -818,6 +1315,9 @@ fn recortar_lista(arrays: &mut Arrays, start: i32, end: i32) -> Result<DatoRedis
     let real_end = ajustar_indice(end, len).clamp(0, len - 1);
 
     if real_start > real_end {
+        arrays
+            .iter()
+            .for_each(|elemento| variacion.liberar(elemento));
         arrays.clear();
     } else {
         let mut start_usize = real_start as usize;
@@ -828,12 +1328,16 @@ fn recortar_lista(arrays: &mut Arrays, start: i32, end: i32) -> Result<DatoRedis
         }
 
         while start_usize > 0 {
-            let _ = arrays.remove(0);
+            if let Some(elemento) = arrays.pop_front() {
+                variacion.liberar(&elemento);
+            }
             start_usize -= 1;
         }
 
         while end_usize < arrays.len() - 1 {
-            let _ = arrays.remove(arrays.len() - 1);
+            if let Ok(elemento) = arrays.pop() {
+                variacion.liberar(&elemento);
+            }
         }
     }
 
@@ -873,22 +1377,29 @@ fn obtener_elemento(arrays: &Arrays, index: i32) -> Result<DatoRedis, DatoRedis>
 /// * `arrays`: arreglo de donde eliminar el elemento
 /// * `elemento`: elemento a eliminar
 /// * `count`: cantidad de apariciones a borrar
+/// * `variacion`: variacion de memoria donde se registran los elementos
+///   eliminados
 ///
 /// # Retorna
 /// - Cantidad de elementos eliminados
-fn remover_elementos(arrays: &mut Arrays, elemento: &DatoRedis, mut count: i32) -> i64 {
+fn remover_elementos(
+    arrays: &mut Arrays,
+    elemento: &DatoRedis,
+    mut count: i32,
+    variacion: &mut VariacionMemoria,
+) -> i64 {
     let mut removed = 0;
 
     match count.cmp(&0) {
         Ordering::Less => {
             let mut i = arrays.len() as i32 - 1;
             while count < 0 && i >= 0 {
-                if let Some(e) = arrays.get(i as usize) {
-                    if e == *elemento {
-                        arrays.remove(i as usize).ok();
-                        count += 1;
-                        removed += 1;
-                    }
+                if let Some(e) = arrays.get(i as usize)
+                    && e == *elemento
+                {
+                    arrays.remove(i as usize).ok();
+                    count += 1;
+                    removed += 1;
                 }
                 i -= 1;
             }
@@ -896,16 +1407,16 @@ fn remover_elementos(arrays: &mut Arrays, elemento: &DatoRedis, mut count: i32)
         Ordering::Greater => {
             let mut i = 0;
             while i < arrays.len() {
-                if let Some(e) = arrays.get(i) {
-                    if e == *elemento {
-                        arrays.remove(i).ok();
-                        count -= 1;
-                        removed += 1;
-                        if count == 0 {
-                            break;
-                        }
-                        continue;
+                if let Some(e) = arrays.get(i)
+                    && e == *elemento
+                {
+                    arrays.remove(i).ok();
+                    count -= 1;
+                    removed += 1;
+                    if count == 0 {
+                        break;
                     }
+                    continue;
                 }
                 i += 1;
             }
@@ -913,24 +1424,26 @@ fn remover_elementos(arrays: &mut Arrays, elemento: &DatoRedis, mut count: i32)
         Ordering::Equal => {
             let mut i = 0;
             while i < arrays.len() {
-                if let Some(e) = arrays.get(i) {
-                    if e == *elemento {
-                        arrays.remove(i).ok();
-                        removed += 1;
-                        continue;
-                    }
+                if let Some(e) = arrays.get(i)
+                    && e == *elemento
+                {
+                    arrays.remove(i).ok();
+                    removed += 1;
+                    continue;
                 }
                 i += 1;
             }
         }
     }
 
+    (0..removed).for_each(|_| variacion.liberar(elemento));
     removed
 }
 
 #[cfg(test)]
 mod tests {
     use crate::comandos::comandos_list::*;
+    use crate::memoria::estimar_memoria;
     use redis_client::tipos_datos::integer::Integer;
     use redis_client::tipos_datos::simple_string::SimpleString;
     use std::ops::Range;
@@ -940,14 +1453,14 @@ mod tests {
         end: 16378,
     };
 
-    fn obtener_tokens(vector: Vec<&str>) -> Vec<String> {
-        vector.into_iter().map(|s| s.to_string()).collect()
+    fn obtener_tokens(vector: Vec<&str>) -> Vec<Token> {
+        vector.into_iter().map(Token::from).collect()
     }
 
     #[test]
     fn test_llen() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         lpush(&tokens, &storage).unwrap();
@@ -959,14 +1472,14 @@ mod tests {
     #[test]
     fn test_lpush_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         if let DatoRedis::Integer(numero) = lpush(&tokens, &storage).unwrap() {
             assert_eq!(numero, Integer::new(1));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
-                if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
-                    assert_eq!(bulk, BulkString::new("element".to_string()).unwrap());
-                }
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap()
+                && let DatoRedis::BulkString(bulk) = array.get(0).unwrap()
+            {
+                assert_eq!(bulk, BulkString::new("element".to_string()).unwrap());
             }
         }
     }
@@ -974,14 +1487,14 @@ mod tests {
     #[test]
     fn test_rpush_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["rpush", "key", "element2"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["rpush", "key", "element2"]);
 
         if let DatoRedis::Integer(numero) = rpush(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 2);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("element".to_string()).unwrap());
@@ -996,13 +1509,13 @@ mod tests {
     #[test]
     fn test_lpush_elements() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         if let DatoRedis::Integer(numero) = lpush(&tokens, &storage).unwrap() {
             assert_eq!(numero, Integer::new(4));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 4);
                 let esperados = ["element4", "element3", "element2", "element"];
                 for (i, esperado) in esperados.iter().enumerate() {
@@ -1017,15 +1530,15 @@ mod tests {
     #[test]
     fn test_rpush_elements() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens1: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens1: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens1, &storage).unwrap();
-        let tokens2: Vec<String> =
+        let tokens2: Vec<Token> =
             obtener_tokens(vec!["rpush", "key", "element2", "element3", "element4"]);
 
         if let DatoRedis::Integer(numero) = rpush(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(4));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 4);
                 let esperados = ["element", "element2", "element3", "element4"];
                 for (i, esperado) in esperados.iter().enumerate() {
@@ -1040,15 +1553,15 @@ mod tests {
     #[test]
     fn test_lset() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
 
-        let tokens2: Vec<String> = obtener_tokens(vec!["lset", "key", "0", "new_element"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lset", "key", "0", "new_element"]);
 
         if let DatoRedis::SimpleString(check) = lset(&tokens2, &storage).unwrap() {
             assert_eq!(check, SimpleString::new("OK".to_string()).unwrap());
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("new_element".to_string()).unwrap());
                 }
@@ -1060,9 +1573,9 @@ mod tests {
     #[test]
     fn test_lindex() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lindex", "key", "0"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lindex", "key", "0"]);
 
         if let DatoRedis::BulkString(bulk) = lindex(&tokens2, &storage).unwrap() {
             assert_eq!(bulk, BulkString::new("element".to_string()).unwrap());
@@ -1072,18 +1585,18 @@ mod tests {
     #[test]
     fn test_lindex_out_of_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lindex", "key", "1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lindex", "key", "1"]);
         assert!(lindex(&tokens2, &storage).is_err());
     }
 
     #[test]
     fn test_lindex_negative_index() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lindex", "key", "-1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lindex", "key", "-1"]);
 
         if let DatoRedis::BulkString(bulk) = lindex(&tokens2, &storage).unwrap() {
             assert_eq!(bulk, BulkString::new("element".to_string()).unwrap());
@@ -1093,18 +1606,18 @@ mod tests {
     #[test]
     fn test_linsert_with_after() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> =
+        let tokens2: Vec<Token> =
             obtener_tokens(vec!["linsert", "key", "AFTER", "element", "new_element"]);
 
         if let DatoRedis::Integer(numero) = linsert(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
-                if let DatoRedis::BulkString(bulk) = array.get(1).unwrap() {
-                    assert_eq!(bulk, BulkString::new("new_element".to_string()).unwrap());
-                }
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap()
+                && let DatoRedis::BulkString(bulk) = array.get(1).unwrap()
+            {
+                assert_eq!(bulk, BulkString::new("new_element".to_string()).unwrap());
             }
         }
     }
@@ -1112,18 +1625,18 @@ mod tests {
     #[test]
     fn test_linsert_with_before() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> =
+        let tokens2: Vec<Token> =
             obtener_tokens(vec!["linsert", "key", "BEFORE", "element", "new_element"]);
 
         if let DatoRedis::Integer(numero) = linsert(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
-                if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
-                    assert_eq!(bulk, BulkString::new("new_element".to_string()).unwrap());
-                }
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap()
+                && let DatoRedis::BulkString(bulk) = array.get(0).unwrap()
+            {
+                assert_eq!(bulk, BulkString::new("new_element".to_string()).unwrap());
             }
         }
     }
@@ -1131,9 +1644,9 @@ mod tests {
     #[test]
     fn test_linsert_with_nonexistent_pivot() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec![
+        let tokens2: Vec<Token> = obtener_tokens(vec![
             "linsert",
             "key",
             "AFTER",
@@ -1144,7 +1657,7 @@ mod tests {
         if let DatoRedis::Integer(numero) = linsert(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(-1));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 1);
             }
         }
@@ -1153,9 +1666,9 @@ mod tests {
     #[test]
     fn test_lpop_with_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lpop", "key"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lpop", "key"]);
 
         if let DatoRedis::Arrays(array) = lpop(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 1);
@@ -1165,7 +1678,7 @@ mod tests {
         }
 
         let guard = storage.read().unwrap();
-        if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
             assert_eq!(array.len(), 0);
         }
     }
@@ -1173,11 +1686,11 @@ mod tests {
     #[test]
     fn test_lpop_with_elements() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lpop", "key", "2"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lpop", "key", "2"]);
 
         if let DatoRedis::Arrays(array) = lpop(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 2);
@@ -1189,7 +1702,7 @@ mod tests {
             }
         }
         let guard = storage.read().unwrap();
-        if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
             assert_eq!(array.len(), 2);
         }
     }
@@ -1197,11 +1710,11 @@ mod tests {
     #[test]
     fn test_lrange_index_in_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrange", "key", "0", "3"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrange", "key", "0", "3"]);
 
         if let DatoRedis::Arrays(array) = lrange(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 4);
@@ -1223,11 +1736,11 @@ mod tests {
     #[test]
     fn test_lrange_neg_index_end() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrange", "key", "0", "-1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrange", "key", "0", "-1"]);
 
         if let DatoRedis::Arrays(array) = lrange(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 4);
@@ -1249,9 +1762,9 @@ mod tests {
     #[test]
     fn test_lrange_neg_index_star() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrange", "key", "-3", "2"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrange", "key", "-3", "2"]);
 
         if let DatoRedis::Arrays(array) = lrange(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 3);
@@ -1270,9 +1783,9 @@ mod tests {
     #[test]
     fn test_lrange_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrange", "key", "0", "0"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrange", "key", "0", "0"]);
 
         if let DatoRedis::Arrays(array) = lrange(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 1);
@@ -1285,9 +1798,9 @@ mod tests {
     #[test]
     fn test_lrange_off_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrange", "key", "5", "100"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrange", "key", "5", "100"]);
 
         if let DatoRedis::Arrays(array) = lrange(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 0);
@@ -1297,12 +1810,12 @@ mod tests {
     #[test]
     fn test_linsert_with_empty_list() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens: Vec<String> = obtener_tokens(vec!["lpop", "key"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpop", "key"]);
         lpop(&tokens, &storage).unwrap();
 
-        let tokens2: Vec<String> = obtener_tokens(vec![
+        let tokens2: Vec<Token> = obtener_tokens(vec![
             "linsert",
             "key",
             "AFTER",
@@ -1313,7 +1826,7 @@ mod tests {
         if let DatoRedis::Integer(numero) = linsert(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(0));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 0);
             }
         }
@@ -1322,14 +1835,14 @@ mod tests {
     #[test]
     fn test_ltrim_index_in_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["ltrim", "key", "0", "1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["ltrim", "key", "0", "1"]);
 
         if let DatoRedis::SimpleString(ok) = ltrim(&tokens2, &storage).unwrap() {
             assert_eq!(ok, SimpleString::new("OK".to_string()).unwrap());
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 2);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("one".to_string()).unwrap());
@@ -1344,14 +1857,14 @@ mod tests {
     #[test]
     fn test_ltrim_neg_index_end() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["ltrim", "key", "1", "-1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["ltrim", "key", "1", "-1"]);
 
         if let DatoRedis::SimpleString(ok) = ltrim(&tokens2, &storage).unwrap() {
             assert_eq!(ok, SimpleString::new("OK".to_string()).unwrap());
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 2);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("two".to_string()).unwrap());
@@ -1366,14 +1879,14 @@ mod tests {
     #[test]
     fn test_ltrim_neg_index_start() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["ltrim", "key", "-1", "1"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["ltrim", "key", "-1", "1"]);
 
         if let DatoRedis::SimpleString(ok) = ltrim(&tokens2, &storage).unwrap() {
             assert_eq!(ok, SimpleString::new("OK".to_string()).unwrap());
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 0);
             }
         }
@@ -1382,9 +1895,9 @@ mod tests {
     #[test]
     fn test_rpop_with_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["lpush", "key", "element"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["lpush", "key", "element"]);
         lpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["rpop", "key"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["rpop", "key"]);
 
         if let DatoRedis::Arrays(array) = rpop(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 1);
@@ -1393,7 +1906,7 @@ mod tests {
             }
         }
         let guard = storage.read().unwrap();
-        if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
             assert_eq!(array.len(), 0);
         }
     }
@@ -1401,11 +1914,11 @@ mod tests {
     #[test]
     fn test_rpop_with_elements() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec![
+        let tokens: Vec<Token> = obtener_tokens(vec![
             "lpush", "key", "element", "element2", "element3", "element4",
         ]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["rpop", "key", "2"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["rpop", "key", "2"]);
 
         if let DatoRedis::Arrays(array) = rpop(&tokens2, &storage).unwrap() {
             assert_eq!(array.len(), 2);
@@ -1417,7 +1930,7 @@ mod tests {
             }
         }
         let guard = storage.read().unwrap();
-        if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
             assert_eq!(array.len(), 2);
         }
     }
@@ -1425,14 +1938,14 @@ mod tests {
     #[test]
     fn test_lrem_neg_count() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrem", "key", "-2", "hello"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrem", "key", "-2", "hello"]);
 
         if let DatoRedis::Integer(numero) = lrem(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 1);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("foo".to_string()).unwrap());
@@ -1444,14 +1957,14 @@ mod tests {
     #[test]
     fn test_lrem_neg_count_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrem", "key", "-1", "hello"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrem", "key", "-1", "hello"]);
 
         if let DatoRedis::Integer(numero) = lrem(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(1));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 2);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("hello".to_string()).unwrap());
@@ -1466,14 +1979,14 @@ mod tests {
     #[test]
     fn test_lrem_pos_count_one_element() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrem", "key", "1", "hello"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrem", "key", "1", "hello"]);
 
         if let DatoRedis::Integer(numero) = lrem(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(1));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 2);
                 if let DatoRedis::BulkString(bulk) = array.get(1).unwrap() {
                     assert_eq!(bulk, BulkString::new("hello".to_string()).unwrap());
@@ -1488,14 +2001,14 @@ mod tests {
     #[test]
     fn test_lrem_pos_count() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrem", "key", "2", "hello"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrem", "key", "2", "hello"]);
 
         if let DatoRedis::Integer(numero) = lrem(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 1);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("foo".to_string()).unwrap());
@@ -1507,14 +2020,14 @@ mod tests {
     #[test]
     fn test_lrem_remove_all() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key", "hello", "foo", "hello"]);
         rpush(&tokens, &storage).unwrap();
-        let tokens2: Vec<String> = obtener_tokens(vec!["lrem", "key", "0", "hello"]);
+        let tokens2: Vec<Token> = obtener_tokens(vec!["lrem", "key", "0", "hello"]);
 
         if let DatoRedis::Integer(numero) = lrem(&tokens2, &storage).unwrap() {
             assert_eq!(numero, Integer::new(2));
             let guard = storage.read().unwrap();
-            if let DatoRedis::Arrays(array) = guard.get("key".to_string()).unwrap() {
+            if let DatoRedis::Arrays(array) = guard.get("key").unwrap() {
                 assert_eq!(array.len(), 1);
                 if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                     assert_eq!(bulk, BulkString::new("foo".to_string()).unwrap());
@@ -1526,27 +2039,27 @@ mod tests {
     #[test]
     fn test_lmove() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = obtener_tokens(vec!["rpush", "key1", "one", "two", "three"]);
+        let tokens: Vec<Token> = obtener_tokens(vec!["rpush", "key1", "one", "two", "three"]);
         rpush(&tokens, &storage).unwrap();
 
-        let tokens3: Vec<String> = obtener_tokens(vec!["lmove", "key1", "key2", "RIGHT", "LEFT"]);
+        let tokens3: Vec<Token> = obtener_tokens(vec!["lmove", "key1", "key2", "RIGHT", "LEFT"]);
         if let DatoRedis::BulkString(element) = lmove(&tokens3, &storage).unwrap() {
             assert_eq!(element, BulkString::new("three".to_string()).unwrap());
         }
 
-        let tokens3: Vec<String> = obtener_tokens(vec!["lmove", "key1", "key2", "LEFT", "RIGHT"]);
+        let tokens3: Vec<Token> = obtener_tokens(vec!["lmove", "key1", "key2", "LEFT", "RIGHT"]);
         if let DatoRedis::BulkString(element) = lmove(&tokens3, &storage).unwrap() {
             assert_eq!(element, BulkString::new("one".to_string()).unwrap());
         }
         let guard = storage.read().unwrap();
-        if let DatoRedis::Arrays(array) = guard.get("key1".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key1").unwrap() {
             assert_eq!(array.len(), 1);
             if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                 assert_eq!(bulk, BulkString::new("two".to_string()).unwrap());
             }
         }
 
-        if let DatoRedis::Arrays(array) = guard.get("key2".to_string()).unwrap() {
+        if let DatoRedis::Arrays(array) = guard.get("key2").unwrap() {
             assert_eq!(array.len(), 2);
             if let DatoRedis::BulkString(bulk) = array.get(0).unwrap() {
                 assert_eq!(bulk, BulkString::new("three".to_string()).unwrap());
@@ -1556,4 +2069,280 @@ mod tests {
             }
         }
     }
+
+    /// Busca una clave del mismo slot que `clave`
+    fn clave_mismo_slot(storage: &Arc<RwLock<Storage>>, clave: &str) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(clave);
+        (0..)
+            .map(|i| format!("{clave}-{i}"))
+            .find(|candidata| guard.calculate_slot_for_key(candidata) == slot)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_blpop_pops_from_first_non_empty_list() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let vecina = clave_mismo_slot(&storage, "cola");
+        rpush(
+            &obtener_tokens(vec!["rpush", &vecina, "uno", "dos"]),
+            &storage,
+        )
+        .unwrap();
+
+        let tokens = obtener_tokens(vec!["blpop", "cola", &vecina, "0"]);
+        let mut esperado = Arrays::new();
+        esperado.append(DatoRedis::new_bulk_string(vecina.clone()).unwrap());
+        esperado.append(DatoRedis::new_bulk_string("uno".to_string()).unwrap());
+        assert_eq!(
+            blpop(&tokens, &storage).unwrap(),
+            DatoRedis::new_array_con_contenido(esperado)
+        );
+
+        let tokens = obtener_tokens(vec!["brpop", &vecina, "1.5"]);
+        if let DatoRedis::Arrays(respuesta) = brpop(&tokens, &storage).unwrap() {
+            assert_eq!(
+                respuesta.get(1).unwrap(),
+                DatoRedis::new_bulk_string("dos".to_string()).unwrap()
+            );
+        }
+
+        // Todas las listas vacias: el cliente debe bloquearse
+        assert_eq!(blpop(&tokens, &storage), Err(DatoRedis::new_null()));
+    }
+
+    #[test]
+    fn test_blocking_pops_validate_arguments() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let vecina = clave_mismo_slot(&storage, "cola");
+        let otra_slot = (0..)
+            .map(|i| format!("otra-{i}"))
+            .find(|c| {
+                let guard = storage.read().unwrap();
+                guard.calculate_slot_for_key(c) != guard.calculate_slot_for_key("cola")
+            })
+            .unwrap();
+
+        let tokens = obtener_tokens(vec!["blpop", "cola", &vecina, "-1"]);
+        assert!(blpop(&tokens, &storage).is_err_and(|e| e != DatoRedis::new_null()));
+        let tokens = obtener_tokens(vec!["blpop", "cola", "nan"]);
+        assert!(blpop(&tokens, &storage).is_err_and(|e| e != DatoRedis::new_null()));
+        let tokens = obtener_tokens(vec!["blpop", "cola", &otra_slot, "0"]);
+        assert!(blpop(&tokens, &storage).is_err_and(|e| e != DatoRedis::new_null()));
+        let tokens = obtener_tokens(vec!["blmove", "cola", "destino", "UP", "LEFT", "0"]);
+        assert!(blmove(&tokens, &storage).is_err_and(|e| e != DatoRedis::new_null()));
+
+        assert_eq!(
+            parametros_bloqueo(&obtener_tokens(vec!["brpop", "a", "b", "0"])).unwrap(),
+            (obtener_tokens(vec!["a", "b"]), None)
+        );
+        assert_eq!(
+            parametros_bloqueo(&obtener_tokens(vec![
+                "blmove", "a", "b", "left", "right", "0.5"
+            ]))
+            .unwrap(),
+            (
+                obtener_tokens(vec!["a"]),
+                Some(std::time::Duration::from_millis(500))
+            )
+        );
+    }
+
+    #[test]
+    fn test_blmove_moves_element_when_source_has_elements() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let tokens = obtener_tokens(vec!["blmove", "origen", "destino", "RIGHT", "LEFT", "0"]);
+        assert_eq!(blmove(&tokens, &storage), Err(DatoRedis::new_null()));
+
+        rpush(&obtener_tokens(vec!["rpush", "origen", "a", "b"]), &storage).unwrap();
+        assert_eq!(
+            blmove(&tokens, &storage).unwrap(),
+            DatoRedis::new_bulk_string("b".to_string()).unwrap()
+        );
+        let guard = storage.read().unwrap();
+        if let DatoRedis::Arrays(destino) = guard.get("destino").unwrap() {
+            assert_eq!(destino.len(), 1);
+        }
+    }
+
+    fn enteros(valores: &[i64]) -> DatoRedis {
+        DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(
+            valores.iter().map(|v| DatoRedis::new_integer(*v)).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_lpos_with_rank_count_and_maxlen() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        rpush(
+            &obtener_tokens(vec!["rpush", "key", "a", "b", "c", "1", "2", "3", "c", "c"]),
+            &storage,
+        )
+        .unwrap();
+        let lpos_con = |opciones: Vec<&str>| {
+            let mut tokens = vec!["lpos", "key", "c"];
+            tokens.extend(opciones);
+            lpos(&obtener_tokens(tokens), &storage)
+        };
+
+        assert_eq!(lpos_con(vec![]), Ok(DatoRedis::new_integer(2)));
+        assert_eq!(lpos_con(vec!["RANK", "2"]), Ok(DatoRedis::new_integer(6)));
+        assert_eq!(lpos_con(vec!["RANK", "-1"]), Ok(DatoRedis::new_integer(7)));
+        assert_eq!(lpos_con(vec!["COUNT", "2"]), Ok(enteros(&[2, 6])));
+        assert_eq!(lpos_con(vec!["COUNT", "0"]), Ok(enteros(&[2, 6, 7])));
+        assert_eq!(
+            lpos_con(vec!["RANK", "-1", "COUNT", "2"]),
+            Ok(enteros(&[7, 6]))
+        );
+        assert_eq!(
+            lpos_con(vec!["COUNT", "0", "MAXLEN", "7"]),
+            Ok(enteros(&[2, 6]))
+        );
+        assert_eq!(lpos_con(vec!["RANK", "4"]), Ok(DatoRedis::new_null()));
+        assert!(lpos_con(vec!["RANK", "0"]).is_err());
+        assert!(lpos_con(vec!["COUNT", "-1"]).is_err());
+        assert!(lpos_con(vec!["MAXLEN"]).is_err());
+
+        let tokens = obtener_tokens(vec!["lpos", "missing", "c", "COUNT", "1"]);
+        assert_eq!(lpos(&tokens, &storage), Ok(enteros(&[])));
+    }
+
+    #[test]
+    fn test_lmpop_pops_count_elements_from_first_non_empty_list() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let vecina = clave_mismo_slot(&storage, "cola");
+        rpush(
+            &obtener_tokens(vec!["rpush", &vecina, "a", "b", "c"]),
+            &storage,
+        )
+        .unwrap();
+
+        let tokens = obtener_tokens(vec!["lmpop", "2", "cola", &vecina, "RIGHT", "COUNT", "2"]);
+        let mut elementos = Arrays::new();
+        elementos.append(DatoRedis::new_bulk_string("c".to_string()).unwrap());
+        elementos.append(DatoRedis::new_bulk_string("b".to_string()).unwrap());
+        let mut esperado = Arrays::new();
+        esperado.append(DatoRedis::new_bulk_string(vecina.clone()).unwrap());
+        esperado.append(DatoRedis::new_array_con_contenido(elementos));
+        assert_eq!(
+            lmpop(&tokens, &storage),
+            Ok(DatoRedis::new_array_con_contenido(esperado))
+        );
+
+        let tokens = obtener_tokens(vec!["lmpop", "1", &vecina, "LEFT", "COUNT", "5"]);
+        if let Ok(DatoRedis::Arrays(respuesta)) = lmpop(&tokens, &storage) {
+            assert_eq!(
+                respuesta.get(1),
+                Some(DatoRedis::new_array_con_contenido(
+                    Arrays::new_con_contenido(vec![
+                        DatoRedis::new_bulk_string("a".to_string()).unwrap()
+                    ])
+                ))
+            );
+        }
+        assert_eq!(lmpop(&tokens, &storage), Err(DatoRedis::new_null()));
+
+        for invalido in [
+            vec!["lmpop", "0", "cola", "LEFT"],
+            vec!["lmpop", "2", "cola", "LEFT"],
+            vec!["lmpop", "1", "cola", "UP"],
+            vec!["lmpop", "1", "cola", "LEFT", "COUNT", "0"],
+        ] {
+            assert!(
+                lmpop(&obtener_tokens(invalido), &storage)
+                    .is_err_and(|e| e != DatoRedis::new_null())
+            );
+        }
+    }
+
+    #[test]
+    fn test_pushx_only_pushes_to_existing_lists() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let tokens = obtener_tokens(vec!["lpushx", "key", "a"]);
+        assert_eq!(lpushx(&tokens, &storage), Ok(DatoRedis::new_integer(0)));
+        assert!(storage.read().unwrap().get("key").is_err());
+
+        rpush(&obtener_tokens(vec!["rpush", "key", "b"]), &storage).unwrap();
+        assert_eq!(lpushx(&tokens, &storage), Ok(DatoRedis::new_integer(2)));
+        let tokens = obtener_tokens(vec!["rpushx", "key", "c", "d"]);
+        assert_eq!(rpushx(&tokens, &storage), Ok(DatoRedis::new_integer(4)));
+
+        let tokens = obtener_tokens(vec!["lrange", "key", "0", "-1"]);
+        let contenido: Vec<String> = match lrange(&tokens, &storage).unwrap() {
+            DatoRedis::Arrays(lista) => lista
+                .iter()
+                .map(|dato| match dato {
+                    DatoRedis::BulkString(b) => b.contenido(),
+                    _ => String::new(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(contenido, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_rpoplpush_and_linsert_on_missing_key() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let tokens = obtener_tokens(vec!["rpoplpush", "origen", "destino"]);
+        assert_eq!(rpoplpush(&tokens, &storage), Err(DatoRedis::new_null()));
+
+        rpush(&obtener_tokens(vec!["rpush", "origen", "a", "b"]), &storage).unwrap();
+        rpush(&obtener_tokens(vec!["rpush", "destino", "x"]), &storage).unwrap();
+        assert_eq!(
+            rpoplpush(&tokens, &storage),
+            Ok(DatoRedis::new_bulk_string("b".to_string()).unwrap())
+        );
+        let tokens = obtener_tokens(vec!["lindex", "destino", "0"]);
+        assert_eq!(
+            lindex(&tokens, &storage),
+            Ok(DatoRedis::new_bulk_string("b".to_string()).unwrap())
+        );
+
+        let tokens = obtener_tokens(vec!["linsert", "missing", "BEFORE", "a", "b"]);
+        assert_eq!(linsert(&tokens, &storage), Ok(DatoRedis::new_integer(0)));
+        let tokens = obtener_tokens(vec!["linsert", "origen", "BEFORE", "a", "b", "c"]);
+        assert!(linsert(&tokens, &storage).is_err());
+        let tokens = obtener_tokens(vec!["linsert", "origen", "NEAR", "a", "b"]);
+        assert!(linsert(&tokens, &storage).is_err());
+    }
+
+    #[test]
+    fn test_memoria_se_actualiza_con_cada_modificacion() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let comandos = [
+            vec!["rpush", "a", "uno", "dos", "tres", "cuatro", "cinco"],
+            vec!["lpush", "a", "cero"],
+            vec!["linsert", "a", "BEFORE", "dos", "uno_y_medio"],
+            vec!["lset", "a", "0", "un_valor_mas_largo"],
+            vec!["lrem", "a", "1", "tres"],
+            vec!["rpop", "a", "2"],
+            vec!["ltrim", "a", "1", "-1"],
+            vec!["lmove", "a", "b", "LEFT", "RIGHT"],
+            vec!["lmpop", "1", "a", "LEFT", "COUNT", "1"],
+        ];
+        for comando in comandos {
+            let tokens = obtener_tokens(comando);
+            let funcion = match &*tokens[0].texto() {
+                "rpush" => rpush,
+                "lpush" => lpush,
+                "linsert" => linsert,
+                "lset" => lset,
+                "lrem" => lrem,
+                "rpop" => rpop,
+                "ltrim" => ltrim,
+                "lmove" => lmove,
+                _ => lmpop,
+            };
+            funcion(&tokens, &storage).unwrap();
+
+            let guard = storage.read().unwrap();
+            let esperada: usize = guard
+                .iter()
+                .flat_map(|(_, mapa)| mapa.iter())
+                .map(|(key, dato)| estimar_memoria(key, dato))
+                .sum();
+            assert_eq!(guard.memoria_contabilizada(), esperada, "{tokens:?}");
+        }
+    }
 }
@@ -0,0 +1,155 @@
+//! Este modulo contiene la implementacion del comando MONITOR, que envia a
+//! los clientes suscriptos cada comando procesado por el nodo
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use redis_client::protocol::protocol_resp::entrecomillar_token;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::{CMD_AUTH, CMD_HELLO, CMD_MONITOR, OPERACION_EXITOSA};
+use crate::comandos::utils::{assert_number_of_arguments_distinct, send_msj};
+use crate::node::Node;
+
+/// Texto con el que se reemplazan las contraseñas en el flujo de MONITOR
+const REDACTADO: &str = "(redacted)";
+
+impl Node {
+    /// Pone al cliente en modo MONITOR: a partir de ese momento recibe una
+    /// linea por cada comando que procese el nodo
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo unicamente el nombre del comando
+    /// * `client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - OK, error de redis si se reciben argumentos
+    pub(crate) fn monitor(
+        &self,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_MONITOR.to_lowercase(), 1, tokens.len())?;
+        client
+            .write()
+            .map_err(|_| {
+                DatoRedis::new_simple_error("ERR".to_string(), "client lock error".to_string())
+            })?
+            .set_monitor(true);
+        self.clientes.agregar_monitor(client.clone());
+        DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    }
+
+    /// Envia a los clientes en modo MONITOR el comando recibido, con las
+    /// contraseñas de AUTH y HELLO ocultas
+    ///
+    /// # Parámetros
+    /// * `client`: cliente que envio el comando
+    /// * `tokens`: secuencia de strings que conforman el comando
+    pub(crate) fn notificar_monitores(&self, client: &Arc<RwLock<Client>>, tokens: &[Token]) {
+        if !self.clientes.hay_monitores() {
+            return;
+        }
+        let addr = match client.read() {
+            Ok(cli) => cli.client_id(),
+            Err(_) => return,
+        };
+        let Ok(linea) = DatoRedis::new_simple_string(formatear_linea_monitor(&addr, tokens)) else {
+            return;
+        };
+        for monitor in self.clientes.monitores() {
+            if !Arc::ptr_eq(&monitor, client) {
+                send_msj(monitor, linea.clone(), &self.logger);
+            }
+        }
+    }
+}
+
+/// Formatea un comando como una linea de MONITOR:
+/// `timestamp [0 direccion] "arg1" "arg2" ...`
+///
+/// # Parámetros
+/// * `addr`: direccion del cliente que envio el comando
+/// * `tokens`: secuencia de strings que conforman el comando
+fn formatear_linea_monitor(addr: &str, tokens: &[Token]) -> String {
+    let ahora = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut linea = format!(
+        "{}.{:06} [0 {addr}]",
+        ahora.as_secs(),
+        ahora.subsec_micros()
+    );
+    for token in redactar(tokens) {
+        linea.push(' ');
+        linea.push_str(&entrecomillar_token(&token));
+    }
+    linea
+}
+
+/// Reemplaza las contraseñas de AUTH y HELLO ... AUTH por un texto fijo
+fn redactar(tokens: &[Token]) -> Vec<Token> {
+    let mut redactados = tokens.to_vec();
+    let Some(comando) = tokens.first().map(|t| t.to_uppercase()) else {
+        return redactados;
+    };
+    let desde = if comando == CMD_AUTH {
+        // AUTH contraseña o AUTH usuario contraseña
+        Some(if tokens.len() > 2 { 2 } else { 1 })
+    } else if comando == CMD_HELLO {
+        // HELLO protover AUTH usuario contraseña
+        tokens
+            .iter()
+            .position(|t| t.to_uppercase() == CMD_AUTH)
+            .map(|i| i + 2)
+    } else {
+        None
+    };
+    if let Some(desde) = desde {
+        for token in redactados.iter_mut().skip(desde) {
+            *token = Token::from(REDACTADO);
+        }
+    }
+    redactados
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_01_linea_con_direccion_y_argumentos() {
+        let linea = formatear_linea_monitor("127.0.0.1:5000", &tokens(&["set", "clave", "a b"]));
+        let (timestamp, resto) = linea.split_once(' ').unwrap();
+        assert!(timestamp.contains('.'));
+        assert_eq!(resto, "[0 127.0.0.1:5000] \"set\" \"clave\" \"a b\"");
+    }
+
+    #[test]
+    fn test_02_contraseñas_redactadas() {
+        assert_eq!(
+            redactar(&tokens(&["auth", "usuario", "secreto"])),
+            tokens(&["auth", "usuario", REDACTADO])
+        );
+        assert_eq!(
+            redactar(&tokens(&["HELLO", "3", "AUTH", "usuario", "secreto"])),
+            tokens(&["HELLO", "3", "AUTH", "usuario", REDACTADO])
+        );
+        assert_eq!(
+            redactar(&tokens(&["get", "auth"])),
+            tokens(&["get", "auth"])
+        );
+    }
+
+    #[test]
+    fn test_03_caracteres_especiales_escapados() {
+        let tokens = [Token::from(&b"a\"b\r\n"[..]), Token::from(&b"\x01\xff"[..])];
+        let linea = formatear_linea_monitor("127.0.0.1:5000", &tokens);
+        assert!(linea.ends_with(" \"a\\\"b\\r\\n\" \"\\x01\\xff\""));
+    }
+}
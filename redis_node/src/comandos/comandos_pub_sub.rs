@@ -1,9 +1,10 @@
 //! Este modulo contiene los comandos de tipo pub sub de los nodos
 
+use crate::client_struct::buffer_salida::{Entrega, SalidaCliente};
 use crate::client_struct::client::Client;
 use crate::comandos::const_cmd::{
     CMD_PSUBSCRIBE, CMD_PUBSUB_CHANNELS, CMD_PUBSUB_NUMPAT, CMD_PUBSUB_NUMSUB,
-    CMD_PUBSUB_SHARDCHANNELS, CMD_PUBSUB_SHARDNUMSUB, CMD_PUNSUBSCRIBE, CMD_SPUBLISH,
+    CMD_PUBSUB_SHARDCHANNELS, CMD_PUBSUB_SHARDNUMSUB, CMD_PUNSUBSCRIBE, CMD_RESET, CMD_SPUBLISH,
     CMD_SSUBSCRIBE, CMD_SUBSCRIBE, CMD_SUNSUBSCRIBE, CMD_UNSUBSCRIBE,
 };
 use crate::comandos::pub_sub_struct::{Canal, Channels, PubSubCore};
@@ -13,9 +14,10 @@ use crate::comandos::utils::{
 use crate::constantes::TOTAL_SLOTS;
 use common::cr16::crc16;
 use glob::Pattern;
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::arrays::Arrays;
 use redis_client::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 impl PubSubCore {
@@ -45,7 +47,7 @@ impl PubSubCore {
     /// * Actualiza la lista interna de suscripciones del cliente.
     pub(crate) fn subscribe(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         let cmd = tokens[0].to_uppercase();
@@ -82,10 +84,10 @@ impl PubSubCore {
     /// - Actualiza los canales del cliente.
     fn subscribe_normal(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
-        assert_correct_arguments_quantity(tokens[0].clone(), 2, tokens.len())?;
+        assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
 
         let mut cli = client
             .write()
@@ -96,12 +98,12 @@ impl PubSubCore {
             self.channels
                 .entry(canal.clone())
                 .or_default()
-                .insert(cli.client_id(), cli.get_sender());
+                .insert(cli.client_id(), cli.get_salida());
 
             cli.add_channel(canal.clone());
 
             resp.append(DatoRedis::new_bulk_string("subscribe".into())?);
-            resp.append(DatoRedis::new_bulk_string(canal.clone())?);
+            resp.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
             resp.append(DatoRedis::new_integer(cli.get_channels().len() as i64));
         }
         Ok(DatoRedis::new_array_con_contenido(resp))
@@ -129,10 +131,10 @@ impl PubSubCore {
     /// - Actualiza los patrones del cliente.
     fn subscribe_pattern(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
-        assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+        assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
 
         let mut cli = client.write().map_err(|e| {
             DatoRedis::new_simple_error("ERR".to_string(), format!("client lock error: {e}"))
@@ -141,19 +143,17 @@ impl PubSubCore {
         let mut respuesta = Arrays::new();
 
         for patron in tokens.iter().skip(1) {
-            let patron: Canal = patron.to_string();
-
             // Agrego el sender a pchannels
-            let canal_sub = self.pchannels.entry(patron.to_string()).or_default();
+            let canal_sub = self.pchannels.entry(patron.clone()).or_default();
 
-            canal_sub.insert(cli.client_id(), cli.get_sender());
+            canal_sub.insert(cli.client_id(), cli.get_salida());
 
             // Al cliente le agrego su nuevo patrón
-            cli.add_pchannel(patron.to_string());
+            cli.add_pchannel(patron.clone());
 
             // Formato de respuesta RESP para PSUBSCRIBE
             respuesta.append(DatoRedis::new_bulk_string("psubscribe".to_string())?);
-            respuesta.append(DatoRedis::new_bulk_string(patron)?);
+            respuesta.append(DatoRedis::new_bulk_string_desde_bytes(patron.to_vec())?);
             respuesta.append(DatoRedis::new_integer(cli.get_pchannels().len() as i64));
         }
 
@@ -183,10 +183,10 @@ impl PubSubCore {
     /// * Añade el shard a `client.schannels`.
     fn subscribe_shard(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
-        assert_correct_arguments_quantity(tokens[0].clone(), 2, tokens.len())?;
+        assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
 
         let mut cli = client
             .write()
@@ -196,7 +196,7 @@ impl PubSubCore {
         for shard in tokens.iter().skip(1) {
             let (slot_valido, slot) = self.validar_slots(shard);
             if !slot_valido {
-                resp.append(DatoRedis::new_bulk_string(shard.clone())?);
+                resp.append(DatoRedis::new_bulk_string_desde_bytes(shard.to_vec())?);
                 resp.append(DatoRedis::new_moved_error(slot));
                 continue;
             }
@@ -204,12 +204,12 @@ impl PubSubCore {
             self.schannels
                 .entry(shard.clone())
                 .or_default()
-                .insert(cli.client_id(), cli.get_sender());
+                .insert(cli.client_id(), cli.get_salida());
 
             cli.add_schannel(shard.clone());
 
             resp.append(DatoRedis::new_bulk_string("ssubscribe".into())?);
-            resp.append(DatoRedis::new_bulk_string(shard.clone())?);
+            resp.append(DatoRedis::new_bulk_string_desde_bytes(shard.to_vec())?);
             resp.append(DatoRedis::new_integer(cli.get_schannels().len() as i64));
         }
         Ok(DatoRedis::new_array_con_contenido(resp))
@@ -234,7 +234,7 @@ impl PubSubCore {
     /// - Si falla el envío a algún cliente.
     pub(crate) fn publish(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         _client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         self.publish_internal(tokens)
@@ -252,8 +252,8 @@ impl PubSubCore {
     ///
     /// # Retorno
     /// `DatoRedis::Integer` con el total de entregas exitosas.
-    pub(crate) fn publish_internal(&mut self, tokens: &[String]) -> Result<DatoRedis, DatoRedis> {
-        assert_number_of_arguments_distinct(tokens[0].clone(), 3, tokens.len())?;
+    pub(crate) fn publish_internal(&mut self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&tokens[0].texto(), 3, tokens.len())?;
         let tipo = &tokens[0].to_uppercase();
         let canal = &tokens[1];
         let mensaje = &tokens[2];
@@ -290,22 +290,17 @@ impl PubSubCore {
     /// $<canal>
     /// $<mensaje>
     /// ```
-    fn publish_normal(&mut self, canal: &str, mensaje: &str) -> Result<i64, DatoRedis> {
+    fn publish_normal(&mut self, canal: &[u8], mensaje: &[u8]) -> Result<i64, DatoRedis> {
         let mut entregados = 0;
 
         if let Some(clientes) = self.channels.get(canal) {
             let mut arr = Arrays::new();
             arr.append(DatoRedis::new_bulk_string("message".into())?);
-            arr.append(DatoRedis::new_bulk_string(canal.into())?);
-            arr.append(DatoRedis::new_bulk_string(mensaje.into())?);
+            arr.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
+            arr.append(DatoRedis::new_bulk_string_desde_bytes(mensaje.to_vec())?);
             let resp = DatoRedis::new_array_con_contenido(arr);
 
-            for sender in clientes.values() {
-                sender
-                    .send(resp.convertir_a_protocolo_resp())
-                    .map_err(|e| DatoRedis::new_simple_error("ERR".into(), e.to_string()))?;
-                entregados += 1;
-            }
+            entregados += self.entregar(clientes, &resp)?;
         }
         Ok(entregados)
     }
@@ -329,24 +324,19 @@ impl PubSubCore {
     /// $<canal>
     /// $<mensaje>
     /// ```
-    fn publish_pattern(&mut self, canal: &str, mensaje: &str) -> Result<i64, DatoRedis> {
+    fn publish_pattern(&mut self, canal: &[u8], mensaje: &[u8]) -> Result<i64, DatoRedis> {
         let mut entregados = 0;
 
         for (patron, clientes) in &self.pchannels {
             if matches_pattern(canal, patron) {
                 let mut arr = Arrays::new();
                 arr.append(DatoRedis::new_bulk_string("pmessage".into())?);
-                arr.append(DatoRedis::new_bulk_string(patron.into())?);
-                arr.append(DatoRedis::new_bulk_string(canal.into())?);
-                arr.append(DatoRedis::new_bulk_string(mensaje.into())?);
+                arr.append(DatoRedis::new_bulk_string_desde_bytes(patron.to_vec())?);
+                arr.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
+                arr.append(DatoRedis::new_bulk_string_desde_bytes(mensaje.to_vec())?);
                 let resp = DatoRedis::new_array_con_contenido(arr);
 
-                for sender in clientes.values() {
-                    sender
-                        .send(resp.convertir_a_protocolo_resp())
-                        .map_err(|e| DatoRedis::new_simple_error("ERR".into(), e.to_string()))?;
-                    entregados += 1;
-                }
+                entregados += self.entregar(clientes, &resp)?;
             }
         }
         Ok(entregados)
@@ -370,7 +360,7 @@ impl PubSubCore {
     /// $<canal>
     /// $<mensaje>
     /// ```
-    fn publish_shard(&mut self, canal: &str, mensaje: &str) -> Result<i64, DatoRedis> {
+    fn publish_shard(&mut self, canal: &[u8], mensaje: &[u8]) -> Result<i64, DatoRedis> {
         let mut entregados = 0;
 
         if !self.validar_slots(canal).0 {
@@ -380,21 +370,50 @@ impl PubSubCore {
         if let Some(clientes) = self.schannels.get(canal) {
             let mut arr = Arrays::new();
             arr.append(DatoRedis::new_bulk_string("smessage".into())?);
-            arr.append(DatoRedis::new_bulk_string(canal.into())?);
-            arr.append(DatoRedis::new_bulk_string(mensaje.into())?);
+            arr.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
+            arr.append(DatoRedis::new_bulk_string_desde_bytes(mensaje.to_vec())?);
             let resp = DatoRedis::new_array_con_contenido(arr);
 
-            for sender in clientes.values() {
-                sender
-                    .send(resp.convertir_a_protocolo_resp())
-                    .map_err(|e| DatoRedis::new_simple_error("ERR".into(), e.to_string()))?;
-                entregados += 1;
-            }
+            entregados += self.entregar(clientes, &resp)?;
         }
 
         Ok(entregados)
     }
 
+    /// Encola un mensaje publicado para cada suscriptor, respetando los
+    /// limites de su buffer de salida. Los suscriptores que los superan se
+    /// desconectan y no reciben el mensaje.
+    ///
+    /// # Parámetros
+    /// - `clientes`: suscriptores del canal o patrón.
+    /// - `resp`: mensaje a entregar.
+    ///
+    /// # Retorno
+    /// - `Ok(i64)`: Cantidad de clientes a los que se les encoló el mensaje.
+    /// - `Err(DatoRedis)`: Error al enviar el mensaje por un sender roto.
+    fn entregar(
+        &self,
+        clientes: &HashMap<String, SalidaCliente>,
+        resp: &DatoRedis,
+    ) -> Result<i64, DatoRedis> {
+        let mensaje = resp.convertir_a_protocolo_resp_bytes();
+        let mut entregados = 0;
+        for (id, salida) in clientes {
+            match self.buffers.entregar(salida, mensaje.clone()) {
+                Ok(Entrega::Encolado) => entregados += 1,
+                Ok(Entrega::Desconectado) => self.logger.warn(
+                    &format!(
+                        "Cliente {id} desconectado por superar el límite del buffer de salida"
+                    ),
+                    "PubSub",
+                ),
+                Ok(Entrega::Descartado) => {}
+                Err(e) => return Err(DatoRedis::new_simple_error("ERR".into(), e.to_string())),
+            }
+        }
+        Ok(entregados)
+    }
+
     /// Procesa la desuscripción (`UNSUBSCRIBE`, `PUNSUBSCRIBE` o `SUNSUBSCRIBE`) del cliente
     /// de uno o más canales o patrones.
     ///
@@ -406,7 +425,7 @@ impl PubSubCore {
     /// Respuesta RESP con confirmaciones para cada canal o patrón desuscrito..
     pub(crate) fn unsubscribe(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         match tokens[0].to_uppercase().as_str() {
@@ -420,12 +439,31 @@ impl PubSubCore {
         }
     }
 
+    /// Maneja el comando `RESET`: desuscribe al cliente de todos sus
+    /// canales, patrones y *shard‑channels* sin enviar una respuesta por
+    /// cada uno. Se ejecuta en el hilo del broker para que el cliente no
+    /// reciba mensajes publicados luego de la respuesta.
+    ///
+    /// # Retorno
+    /// `RESET`, o error si se reciben argumentos
+    pub(crate) fn reset(
+        &mut self,
+        tokens: &[Token],
+        client: Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_RESET.to_lowercase(), 1, tokens.len())?;
+        for comando in [CMD_UNSUBSCRIBE, CMD_PUNSUBSCRIBE, CMD_SUNSUBSCRIBE] {
+            self.unsubscribe(&[Token::from(comando)], client.clone())?;
+        }
+        DatoRedis::new_simple_string(CMD_RESET.to_string())
+    }
+
     /// Desuscribe el cliente de canales regulares (`UNSUBSCRIBE`).
     ///
     /// Llama a `unsubscribe_generico` con funciones específicas para canales normales.
     fn unsubscribe_normal(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         Self::unsubscribe_generico(
@@ -443,7 +481,7 @@ impl PubSubCore {
     /// Llama a `unsubscribe_generico` con funciones específicas para patrones.
     fn unsubscribe_pattern(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         Self::unsubscribe_generico(
@@ -461,7 +499,7 @@ impl PubSubCore {
     /// Llama a `unsubscribe_generico` con funciones específicas para shard-channels.
     fn sunsubscribe_shard(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
         Self::unsubscribe_generico(
@@ -489,11 +527,11 @@ impl PubSubCore {
     /// # Retorno
     /// Respuesta RESP con confirmaciones de desuscripción para cada canal o patrón..
     fn unsubscribe_generico(
-        tokens: &[String],
+        tokens: &[Token],
         mapa_canales: &mut Channels,
         client: Arc<RwLock<Client>>,
         remover_del_cliente: fn(&mut Client, &Canal),
-        obtener_del_cliente: fn(&Client) -> &HashSet<String>,
+        obtener_del_cliente: fn(&Client) -> &HashSet<Canal>,
         nombre_comando: &str,
     ) -> Result<DatoRedis, DatoRedis> {
         let mut cli = client
@@ -501,7 +539,7 @@ impl PubSubCore {
             .map_err(|e| DatoRedis::new_simple_error("ERR".into(), format!("client lock: {e}")))?;
 
         // canales a eliminar
-        let canales_a_borrar: Vec<String> = if tokens.len() == 1 {
+        let canales_a_borrar: Vec<Canal> = if tokens.len() == 1 {
             obtener_del_cliente(&cli).iter().cloned().collect()
         } else {
             tokens.iter().skip(1).cloned().collect()
@@ -519,7 +557,7 @@ impl PubSubCore {
             remover_del_cliente(&mut cli, canal);
 
             resp.append(DatoRedis::new_bulk_string(nombre_comando.to_lowercase())?);
-            resp.append(DatoRedis::new_bulk_string(canal.clone())?);
+            resp.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
             resp.append(DatoRedis::new_integer(
                 obtener_del_cliente(&cli).len() as i64
             ));
@@ -560,10 +598,10 @@ impl PubSubCore {
     ///   o la cantidad de argumentos es incorrecta.
     pub(crate) fn pub_sub(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         _client: Arc<RwLock<Client>>,
     ) -> Result<DatoRedis, DatoRedis> {
-        assert_correct_arguments_quantity(tokens[0].clone(), 1, tokens.len())?;
+        assert_correct_arguments_quantity(&tokens[0].texto(), 1, tokens.len())?;
 
         match tokens[1].to_uppercase().as_str() {
             CMD_PUBSUB_NUMSUB => self.pubsub_numsub(tokens),
@@ -588,19 +626,19 @@ impl PubSubCore {
     /// - `Ok(DatoRedis)` con array RESP `[canal, cantidad, canal, cantidad, ...]`.
     fn obtener_numsub(
         canales: &Channels,
-        canales_consultar: Option<&[String]>,
+        canales_consultar: Option<&[Token]>,
     ) -> Result<DatoRedis, DatoRedis> {
         let mut resultado = Arrays::new();
 
         if let Some(lista) = canales_consultar {
             for canal in lista {
-                resultado.append(DatoRedis::new_bulk_string(canal.clone())?);
+                resultado.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
                 let cantidad = canales.get(canal).map(|m| m.len()).unwrap_or(0);
                 resultado.append(DatoRedis::new_integer(cantidad as i64));
             }
         } else {
             for (canal, mapa) in canales.iter() {
-                resultado.append(DatoRedis::new_bulk_string(canal.clone())?);
+                resultado.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
                 resultado.append(DatoRedis::new_integer(mapa.len() as i64));
             }
         }
@@ -609,7 +647,7 @@ impl PubSubCore {
     }
 
     /// Implementa el subcomando `PUBSUB NUMSUB [canal ...]` para canales normales.
-    fn pubsub_numsub(&self, tokens: &[String]) -> Result<DatoRedis, DatoRedis> {
+    fn pubsub_numsub(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
         let canales_consultar = if tokens.len() > 2 {
             Some(&tokens[2..])
         } else {
@@ -619,7 +657,7 @@ impl PubSubCore {
     }
 
     /// Implementa el subcomando `PUBSUB SNUMSUB [canal ...]` para shard channels.
-    fn pubsub_snumsub(&self, tokens: &[String]) -> Result<DatoRedis, DatoRedis> {
+    fn pubsub_snumsub(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
         let canales_consultar = if tokens.len() > 2 {
             Some(&tokens[2..])
         } else {
@@ -638,7 +676,7 @@ impl PubSubCore {
     /// - `Ok(DatoRedis)` con array de nombres de canales.
     fn obtener_canales_con_suscriptores(
         canales: &Channels,
-        patron_opt: Option<&str>,
+        patron_opt: Option<&[u8]>,
     ) -> Result<DatoRedis, DatoRedis> {
         let mut resultado = Arrays::new();
 
@@ -650,7 +688,7 @@ impl PubSubCore {
                 .map(|pat| matches_pattern(canal, pat))
                 .unwrap_or(true)
             {
-                resultado.append(DatoRedis::new_bulk_string(canal.clone())?);
+                resultado.append(DatoRedis::new_bulk_string_desde_bytes(canal.to_vec())?);
             }
         }
 
@@ -660,16 +698,16 @@ impl PubSubCore {
     /// Implementa el subcomando `PUBSUB CHANNELS [pattern]` para canales normales.
     ///
     /// Devuelve los canales con suscriptores que coincidan opcionalmente con el patrón.
-    fn pubsub_channels(&self, tokens: &[String]) -> Result<DatoRedis, DatoRedis> {
-        let patron_opt = tokens.get(2).map(|s| s.as_str());
+    fn pubsub_channels(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        let patron_opt = tokens.get(2).map(|s| s.bytes());
         Self::obtener_canales_con_suscriptores(&self.channels, patron_opt)
     }
 
     /// Implementa `PUBSUB SCHANNELS [pattern]` para shard channels.
     ///
     /// Devuelve shard channels con suscriptores que coincidan opcionalmente con el patrón.
-    fn pubsub_schannels(&self, tokens: &[String]) -> Result<DatoRedis, DatoRedis> {
-        let patron_opt = tokens.get(2).map(|s| s.as_str());
+    fn pubsub_schannels(&self, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+        let patron_opt = tokens.get(2).map(|s| s.bytes());
         Self::obtener_canales_con_suscriptores(&self.schannels, patron_opt)
     }
 
@@ -687,13 +725,14 @@ impl PubSubCore {
     ///
     /// # Retorno
     /// - `(bool, u16)` indicando si el slot es válido y el valor del slot.
-    fn validar_slots(&self, canal: &str) -> (bool, u16) {
-        let slot = crc16(canal.as_bytes()) % TOTAL_SLOTS;
+    fn validar_slots(&self, canal: &[u8]) -> (bool, u16) {
+        let slot = crc16(canal) % TOTAL_SLOTS;
         (self.slot_range.contains(&slot), slot)
     }
 }
 
-/// Determina si el nombre de un canal cumple un determinado patron
+/// Determina si el nombre de un canal cumple un determinado patron.
+/// Los bytes que no son UTF-8 válido se comparan en su forma lossy.
 ///
 /// # Parámetros
 /// * `canal`: nombre del canal a analizar
@@ -701,15 +740,16 @@ impl PubSubCore {
 ///
 /// # Retorna
 /// - verdadero de cumplirse el patron, falso en otro caso
-fn matches_pattern(canal: &str, patron: &str) -> bool {
-    Pattern::new(patron)
-        .map(|p| p.matches(canal))
+fn matches_pattern(canal: &[u8], patron: &[u8]) -> bool {
+    Pattern::new(&String::from_utf8_lossy(patron))
+        .map(|p| p.matches(&String::from_utf8_lossy(canal)))
         .unwrap_or(false)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client_struct::buffer_salida::LimitesBuffer;
     use crate::node_role::NodeRole;
     use logger::logger::Logger;
     use std::net::{TcpListener, TcpStream};
@@ -727,9 +767,11 @@ mod tests {
         (client, server)
     }
 
-    fn setup_cliente(nombre: &str) -> (Arc<RwLock<Client>>, Sender<String>, Receiver<String>) {
+    type ClienteDePrueba = (Arc<RwLock<Client>>, Sender<Vec<u8>>, Receiver<Vec<u8>>);
+
+    fn setup_cliente(nombre: &str) -> ClienteDePrueba {
         // Crear un canal para la comunicación
-        let (tx, rx) = channel::<String>();
+        let (tx, rx) = channel::<Vec<u8>>();
         let looger = Logger::null();
         // Crear el cliente con el nombre proporcionado
         let (client_stream, _server_stream) = dummy_tcp_pair();
@@ -756,9 +798,9 @@ mod tests {
         let mut pubsub = setup_pubsub_core(0..0);
 
         let tokens = vec![
-            "SUBSCRIBE".to_string(),
-            "canal1".to_string(),
-            "canal2".to_string(),
+            Token::from("SUBSCRIBE"),
+            Token::from("canal1"),
+            Token::from("canal2"),
         ];
 
         // 5. Ejecutar subscribe
@@ -766,14 +808,14 @@ mod tests {
 
         // 6. Verificaciones
         let cliente_guard = cliente.read().unwrap();
-        assert!(cliente_guard.get_channels().contains("canal1"));
-        assert!(cliente_guard.get_channels().contains("canal2"));
+        assert!(cliente_guard.get_channels().contains("canal1".as_bytes()));
+        assert!(cliente_guard.get_channels().contains("canal2".as_bytes()));
         assert!(cliente_guard.get_modo_pub_sub());
 
-        assert!(pubsub.channels.contains_key("canal1"));
-        assert!(pubsub.channels.contains_key("canal2"));
-        assert!(pubsub.channels["canal1"].contains_key("cliente1"));
-        assert!(pubsub.channels["canal2"].contains_key("cliente1"));
+        assert!(pubsub.channels.contains_key("canal1".as_bytes()));
+        assert!(pubsub.channels.contains_key("canal2".as_bytes()));
+        assert!(pubsub.channels["canal1".as_bytes()].contains_key("cliente1"));
+        assert!(pubsub.channels["canal2".as_bytes()].contains_key("cliente1"));
 
         // 7. Verificar respuesta RESP
         let expected = "1) \"subscribe\"\r\n\
@@ -793,7 +835,7 @@ mod tests {
         let (cliente, _tx, _rx) = setup_cliente("cliente1");
         let mut pubsub = setup_pubsub_core(0..0);
 
-        let tokens = vec!["SUBSCRIBE".to_string()];
+        let tokens = vec![Token::from("SUBSCRIBE")];
 
         assert!(pubsub.subscribe(&tokens, cliente.clone()).is_err());
     }
@@ -808,19 +850,19 @@ mod tests {
             .channels
             .entry("canal1".into())
             .or_default()
-            .insert("cliente1".into(), tx.clone());
+            .insert("cliente1".into(), tx.clone().into());
 
         let tokens = vec![
-            "PUBLISH".to_string(),
-            "canal1".to_string(),
-            "¡Hola mundo!".to_string(),
+            Token::from("PUBLISH"),
+            Token::from("canal1"),
+            Token::from("¡Hola mundo!"),
         ];
 
         let result = pubsub.publish(&tokens, cliente.clone()).unwrap();
         assert_eq!(result, DatoRedis::new_integer(1));
         assert_eq!(
             rx.try_recv().unwrap(),
-            "*3\r\n$7\r\nmessage\r\n$6\r\ncanal1\r\n$13\r\n¡Hola mundo!\r\n"
+            "*3\r\n$7\r\nmessage\r\n$6\r\ncanal1\r\n$13\r\n¡Hola mundo!\r\n".as_bytes()
         );
     }
 
@@ -831,9 +873,9 @@ mod tests {
         let mut pubsub = setup_pubsub_core(0..0);
 
         let tokens = vec![
-            "PUBLISH".to_string(),
-            "canal1".to_string(),
-            "¡Hola mundo!".to_string(),
+            Token::from("PUBLISH"),
+            Token::from("canal1"),
+            Token::from("¡Hola mundo!"),
         ];
 
         let result = pubsub.publish(&tokens, cliente.clone()).unwrap();
@@ -852,23 +894,23 @@ mod tests {
             .channels
             .entry("canal1".into())
             .or_default()
-            .insert("cliente1".into(), tx1.clone());
+            .insert("cliente1".into(), tx1.clone().into());
         pubsub
             .channels
-            .get_mut("canal1")
+            .get_mut("canal1".as_bytes())
             .unwrap()
-            .insert("cliente2".into(), tx2.clone());
+            .insert("cliente2".into(), tx2.clone().into());
 
         let tokens = vec![
-            "PUBLISH".to_string(),
-            "canal1".to_string(),
-            "¡Hola mundo!".to_string(),
+            Token::from("PUBLISH"),
+            Token::from("canal1"),
+            Token::from("¡Hola mundo!"),
         ];
         let result = pubsub.publish(&tokens, cliente1.clone()).unwrap();
         assert_eq!(result, DatoRedis::new_integer(2));
         assert_eq!(
             rx1.try_recv().unwrap(),
-            "*3\r\n$7\r\nmessage\r\n$6\r\ncanal1\r\n$13\r\n¡Hola mundo!\r\n"
+            "*3\r\n$7\r\nmessage\r\n$6\r\ncanal1\r\n$13\r\n¡Hola mundo!\r\n".as_bytes()
         );
     }
 
@@ -882,10 +924,10 @@ mod tests {
             .channels
             .entry("canal1".into())
             .or_default()
-            .insert("cliente1".into(), tx.clone());
+            .insert("cliente1".into(), tx.clone().into());
         {
             let mut g = cliente.write().unwrap();
-            g.add_channel("canal1".parse().unwrap());
+            g.add_channel(Token::from("canal1"));
         }
 
         let tokens = vec!["UNSUBSCRIBE".into(), "canal1".into()];
@@ -894,7 +936,7 @@ mod tests {
             result.convertir_a_protocolo_resp(),
             "*3\r\n$11\r\nunsubscribe\r\n$6\r\ncanal1\r\n:0\r\n"
         );
-        assert!(!pubsub.channels.contains_key("canal1"));
+        assert!(!pubsub.channels.contains_key("canal1".as_bytes()));
         assert!(rx.try_recv().is_err());
     }
 
@@ -909,12 +951,12 @@ mod tests {
                 .channels
                 .entry((*nombre).into())
                 .or_default()
-                .insert("cliente1".into(), tx.clone());
+                .insert("cliente1".into(), tx.clone().into());
         }
         {
             let mut g = cliente.write().unwrap();
             for n in &["canal1", "canal2", "canal3"] {
-                g.add_channel((*n).parse().unwrap());
+                g.add_channel(Token::from(*n));
             }
         }
 
@@ -925,7 +967,7 @@ mod tests {
         assert!(res.contains("canal2"));
         assert!(res.contains("canal3"));
         for nombre in &["canal1", "canal2", "canal3"] {
-            assert!(!pubsub.channels.contains_key(*nombre));
+            assert!(!pubsub.channels.contains_key(nombre.as_bytes()));
         }
         assert!(rx.try_recv().is_err());
     }
@@ -942,17 +984,17 @@ mod tests {
                 .channels
                 .entry((*nombre).into())
                 .or_default()
-                .insert("cliente1".into(), tx1.clone());
+                .insert("cliente1".into(), tx1.clone().into());
             pubsub
                 .channels
-                .get_mut(*nombre)
+                .get_mut(nombre.as_bytes())
                 .unwrap()
-                .insert("cliente2".into(), tx2.clone());
+                .insert("cliente2".into(), tx2.clone().into());
         }
         {
             let mut g = cliente.write().unwrap();
             for n in &["canal1", "canal2", "canal3"] {
-                g.add_channel((*n).parse().unwrap());
+                g.add_channel(Token::from(*n));
             }
         }
 
@@ -962,7 +1004,7 @@ mod tests {
             result.convertir_a_protocolo_resp(),
             "*3\r\n$11\r\nunsubscribe\r\n$6\r\ncanal1\r\n:2\r\n"
         );
-        assert!(!pubsub.channels["canal1"].contains_key("cliente1"));
+        assert!(!pubsub.channels["canal1".as_bytes()].contains_key("cliente1"));
         assert!(rx.try_recv().is_err());
     }
 
@@ -977,17 +1019,17 @@ mod tests {
             .channels
             .entry("canal1".into())
             .or_default()
-            .insert("cliente1".into(), tx1.clone());
+            .insert("cliente1".into(), tx1.clone().into());
         pubsub
             .channels
-            .get_mut("canal1")
+            .get_mut("canal1".as_bytes())
             .unwrap()
-            .insert("cliente2".into(), tx2.clone());
+            .insert("cliente2".into(), tx2.clone().into());
         pubsub
             .channels
             .entry("canal2".into())
             .or_default()
-            .insert("cliente3".into(), tx3.clone());
+            .insert("cliente3".into(), tx3.clone().into());
         pubsub.channels.entry("canal3".into()).or_default();
 
         let tokens = vec![
@@ -1017,18 +1059,18 @@ mod tests {
             .channels
             .entry("hello".into())
             .or_default()
-            .insert("cliente1".into(), tx1.clone());
+            .insert("cliente1".into(), tx1.clone().into());
         pubsub
             .channels
             .entry("hallo".into())
             .or_default()
-            .insert("cliente2".into(), tx2.clone());
+            .insert("cliente2".into(), tx2.clone().into());
         for ch in &["hxllo", "heeeello", "hllo", "hillo"] {
             pubsub
                 .channels
                 .entry((*ch).into())
                 .or_default()
-                .insert("cliente1".into(), tx1.clone());
+                .insert("cliente1".into(), tx1.clone().into());
         }
 
         let tokens = vec!["PUBSUB".into(), "CHANNELS".into()];
@@ -1110,8 +1152,8 @@ mod tests {
             result.unwrap().convertir_resp_a_string(),
             "1) \"psubscribe\"\r\n2) \"canal prueba*\"\r\n3) (integer) 1\r\n"
         );
-        assert!(pubsub.pchannels.contains_key("canal prueba*"));
-        assert!(pubsub.pchannels["canal prueba*"].contains_key("cliente1"));
+        assert!(pubsub.pchannels.contains_key("canal prueba*".as_bytes()));
+        assert!(pubsub.pchannels["canal prueba*".as_bytes()].contains_key("cliente1"));
         assert!(pubsub.channels.is_empty());
     }
 
@@ -1134,8 +1176,8 @@ mod tests {
             "1) \"psubscribe\"\r\n2) \"canal prueba*\"\r\n3) (integer) 1\r\n\
 4) \"psubscribe\"\r\n5) \"otro canal*\"\r\n6) (integer) 2\r\n"
         );
-        assert!(pubsub.pchannels["canal prueba*"].contains_key("cliente1"));
-        assert!(pubsub.pchannels["otro canal*"].contains_key("cliente1"));
+        assert!(pubsub.pchannels["canal prueba*".as_bytes()].contains_key("cliente1"));
+        assert!(pubsub.pchannels["otro canal*".as_bytes()].contains_key("cliente1"));
         assert!(pubsub.channels.is_empty());
     }
 
@@ -1160,8 +1202,8 @@ mod tests {
             result.unwrap().convertir_resp_a_string(),
             "1) \"punsubscribe\"\r\n2) \"canal prueba*\"\r\n3) (integer) 1\r\n"
         );
-        assert!(!pubsub.pchannels.contains_key("canal prueba*"));
-        assert!(pubsub.pchannels["otro canal*"].contains_key("cliente1"));
+        assert!(!pubsub.pchannels.contains_key("canal prueba*".as_bytes()));
+        assert!(pubsub.pchannels["otro canal*".as_bytes()].contains_key("cliente1"));
         assert!(pubsub.channels.is_empty());
     }
 
@@ -1274,8 +1316,8 @@ mod tests {
             )
             .unwrap();
 
-        assert!(core.schannels.contains_key("sch1"));
-        assert!(core.schannels.contains_key("sch2"));
+        assert!(core.schannels.contains_key("sch1".as_bytes()));
+        assert!(core.schannels.contains_key("sch2".as_bytes()));
         assert_eq!(
             resp.convertir_resp_a_string(),
             "1) \"ssubscribe\"\r\n2) \"sch1\"\r\n3) (integer) 1\r\n\
@@ -1307,7 +1349,7 @@ mod tests {
         core.schannels
             .entry("sch1".into())
             .or_default()
-            .insert("cliente1".into(), tx.clone());
+            .insert("cliente1".into(), tx.clone().into());
 
         let resp = core
             .publish(
@@ -1319,7 +1361,7 @@ mod tests {
         assert_eq!(resp.convertir_resp_a_string(), "(integer) 1\r\n");
         assert_eq!(
             rx.try_recv().unwrap(),
-            "*3\r\n$8\r\nsmessage\r\n$4\r\nsch1\r\n$5\r\nHola!\r\n"
+            "*3\r\n$8\r\nsmessage\r\n$4\r\nsch1\r\n$5\r\nHola!\r\n".as_bytes()
         );
     }
 
@@ -1332,7 +1374,7 @@ mod tests {
         core.schannels
             .entry("sch1".into())
             .or_default()
-            .insert("cliente1".into(), tx.clone());
+            .insert("cliente1".into(), tx.clone().into());
 
         let _ = core.subscribe(&["SSUBSCRIBE".into(), "sch1".into()], cliente.clone());
 
@@ -1356,11 +1398,11 @@ mod tests {
         core.schannels
             .entry("sch1".into())
             .or_default()
-            .insert("cli1".into(), tx1.clone());
+            .insert("cli1".into(), tx1.clone().into());
         core.schannels
-            .get_mut("sch1")
+            .get_mut("sch1".as_bytes())
             .unwrap()
-            .insert("cli2".into(), tx2.clone());
+            .insert("cli2".into(), tx2.clone().into());
         core.schannels.entry("sch2".into()).or_default();
 
         let resp = core
@@ -1391,7 +1433,7 @@ mod tests {
             core.schannels
                 .entry((*c).into())
                 .or_default()
-                .insert("cli1".into(), tx1.clone());
+                .insert("cli1".into(), tx1.clone().into());
         }
 
         let all = core
@@ -1417,4 +1459,40 @@ mod tests {
         assert!(!pat.contains("beta"));
         assert!(!pat.contains("gamma"));
     }
+
+    #[test]
+    fn test24_publish_desconecta_suscriptores_lentos() {
+        let (cliente, _tx, _rx) = setup_cliente("cliente1");
+        let (cliente2, _tx2, _rx2) = setup_cliente("cliente2");
+        let mut pubsub = setup_pubsub_core(0..0);
+        pubsub
+            .subscribe(&["SUBSCRIBE".into(), "canal1".into()], cliente.clone())
+            .unwrap();
+        pubsub
+            .subscribe(&["SUBSCRIBE".into(), "canal1".into()], cliente2.clone())
+            .unwrap();
+        let salida = cliente.read().unwrap().get_salida();
+
+        // El primer cliente tiene encolada una respuesta que no pudo enviar.
+        // Se encola por un canal sin hilo escritor para que siga pendiente
+        let (tx_pendiente, _rx_pendiente) = channel::<Vec<u8>>();
+        SalidaCliente::new(tx_pendiente, salida.buffer().clone())
+            .enviar(vec![b'x'; 100])
+            .unwrap();
+        pubsub.buffers.set_limites(LimitesBuffer {
+            duro: 120,
+            blando: 0,
+            segundos_blando: 0,
+        });
+        let tokens = vec!["PUBLISH".into(), "canal1".into(), "hola".into()];
+
+        let result = pubsub.publish(&tokens, cliente.clone()).unwrap();
+        assert_eq!(result, DatoRedis::new_integer(1));
+        assert!(salida.buffer().desconectado());
+        assert_eq!(pubsub.buffers.desconexiones(), 1);
+
+        let result = pubsub.publish(&tokens, cliente.clone()).unwrap();
+        assert_eq!(result, DatoRedis::new_integer(1));
+        assert_eq!(pubsub.buffers.desconexiones(), 1);
+    }
 }
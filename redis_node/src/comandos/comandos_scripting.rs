@@ -0,0 +1,621 @@
+//! Este modulo contiene la implementacion de los comandos de scripting.
+//! EVAL y EVALSHA ejecutan programas de Forth que pueden llamar a los
+//! comandos del nodo y SCRIPT administra la cache de scripts.
+//!
+//! Ademas de las palabras de Forth, un script dispone de:
+//! - `call" COMANDO args"`: ejecuta un comando del nodo, reemplazando los
+//!   tokens `KEYS[i]` y `ARGV[i]` por la clave o el argumento i-esimo, y
+//!   apila su respuesta como numero (nil es 0 y las respuestas no
+//!   numericas son -1)
+//! - `.reply`: imprime la ultima respuesta obtenida con `call"`
+//! - `#keys` y `#argv`: apilan la cantidad de claves y de argumentos
+//!
+//! La respuesta del script es el texto que imprimio o, si no imprimio
+//! nada, el arreglo de numeros que quedaron en la pila. Al AOF y a las
+//! replicas se propagan los comandos de escritura que llamo el script, no
+//! su texto
+use std::ops::Range;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+
+use interpretefth::{Error, PalabrasExternas, Pila, ejecutar_programa};
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
+
+use crate::client_struct::client::Client;
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::const_cmd::{
+    CMD_EVAL, CMD_EVALSHA, CMD_FCALL, CMD_FCALL_RO, CMD_FLUSHALL, CMD_FUNCTION, CMD_SCRIPT,
+    CMD_SCRIPT_EXISTS, CMD_SCRIPT_FLUSH, CMD_SCRIPT_LOAD, OPERACION_EXITOSA,
+};
+use crate::comandos::procesamiento_comandos::{
+    comando_propagado, con_expiraciones_absolutas, es_operacion_no_mutable,
+};
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido, get_storage_write_lock,
+};
+use crate::node::Node;
+use crate::storage::Storage;
+use crate::utils::utils_functions::obtener_fn_normal;
+
+const PALABRA_CALL: &str = "call\"";
+const PALABRA_REPLY: &str = ".reply";
+const PALABRA_CANTIDAD_CLAVES: &str = "#keys";
+const PALABRA_CANTIDAD_ARGUMENTOS: &str = "#argv";
+const PREFIJO_CLAVE: &str = "KEYS[";
+const PREFIJO_ARGUMENTO: &str = "ARGV[";
+/// Valores con los que se apilan las respuestas no numericas
+const VERDADERO: i16 = -1;
+const FALSO: i16 = 0;
+/// Respuesta de un script y comandos de escritura que llamo, reescritos
+/// como se propagan al AOF y a las replicas
+pub(crate) type ResultadoScript = (Result<DatoRedis, DatoRedis>, Vec<Vec<Token>>);
+/// Claves, argumentos y slot de las claves de un script
+type ClavesScript<'a> = (&'a [Token], &'a [Token], Option<u16>);
+/// Comandos que no pueden llamarse desde un script
+const COMANDOS_PROHIBIDOS: [&str; 6] = [
+    CMD_EVAL,
+    CMD_EVALSHA,
+    CMD_FCALL,
+    CMD_FCALL_RO,
+    CMD_FUNCTION,
+    CMD_FLUSHALL,
+];
+
+/// Ejecuta un script de Forth de forma atomica: ningun otro comando se
+/// intercala con los que llama el script
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, script, cantidad de
+///   claves, claves y argumentos
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Respuesta del script en caso de exito, error de redis en otro caso.
+///   Las claves deben pertenecer a un mismo slot y el script solo puede
+///   acceder a claves de ese slot
+pub fn eval(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    ejecutar_eval(tokens, storage).0
+}
+
+/// Ejecuta un script de Forth de forma atomica, como `eval`
+///
+/// # Retorna
+/// - Respuesta del script y los comandos de escritura que llamo, que se
+///   propagan en lugar del EVAL aunque el script falle
+pub(crate) fn ejecutar_eval(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> ResultadoScript {
+    if let Err(e) = assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len()) {
+        return (Err(e), Vec::new());
+    }
+    let lineas = tokens[1].texto().lines().map(String::from).collect();
+    match get_storage_write_lock(storage) {
+        Ok(mut guard) => ejecutar_atomico(&mut guard, lineas, tokens, false),
+        Err(e) => (Err(e), Vec::new()),
+    }
+}
+
+/// Ejecuta un programa de Forth sobre el storage del nodo, cuyo write lock
+/// impide que se intercalen otros comandos
+///
+/// # Parámetros
+/// * `guard`: write lock del storage del nodo
+/// * `lineas`: lineas del programa
+/// * `tokens`: tokens del comando, con la cantidad de claves en la
+///   posicion 2 seguida de las claves y los argumentos
+/// * `solo_lectura`: si el programa solo puede llamar a comandos que no
+///   modifican el storage
+///
+/// # Retorna
+/// - Respuesta del programa en caso de exito, error de redis en otro caso,
+///   junto a los comandos de escritura que llamo antes de terminar
+pub(crate) fn ejecutar_atomico(
+    guard: &mut RwLockWriteGuard<'_, Storage>,
+    lineas: Vec<String>,
+    tokens: &[Token],
+    solo_lectura: bool,
+) -> ResultadoScript {
+    let (claves, argumentos, slot) = match claves_del_script(guard, tokens) {
+        Ok(claves) => claves,
+        Err(e) => return (Err(e), Vec::new()),
+    };
+
+    // El programa opera sobre el storage del nodo, limitado al slot de sus
+    // claves
+    let rango = guard.get_slot_range();
+    let aislado = Arc::new(RwLock::new(Storage::new(0..0)));
+    let slot_del_script: Range<u16> = slot.map_or(0..0, |slot| slot..slot + 1);
+    guard.restringir_slots(slot_del_script);
+    intercambiar(guard, &aislado);
+    let mut contexto = ContextoScript {
+        claves,
+        argumentos,
+        storage: &aislado,
+        solo_lectura,
+        ultima_respuesta: DatoRedis::new_null(),
+        error: None,
+        escrituras: Vec::new(),
+    };
+    let resultado = contexto.ejecutar(lineas);
+    intercambiar(guard, &aislado);
+    guard.restringir_slots(rango);
+    (resultado, contexto.escrituras)
+}
+
+/// Separa las claves y los argumentos de un script y obtiene el slot de
+/// sus claves
+///
+/// # Retorna
+/// - Claves, argumentos y slot (None si no recibe claves), error de redis
+///   si las claves pertenecen a distintos slots o a un slot de otro nodo
+fn claves_del_script<'a>(
+    guard: &RwLockWriteGuard<'_, Storage>,
+    tokens: &'a [Token],
+) -> Result<ClavesScript<'a>, DatoRedis> {
+    let cantidad_claves = cantidad_claves(tokens)?;
+    let claves = &tokens[3..3 + cantidad_claves];
+    let argumentos = &tokens[3 + cantidad_claves..];
+
+    for clave in claves.iter().skip(1) {
+        verificar_mismo_slot(guard, &claves[0], clave)?;
+    }
+    let slot = claves
+        .first()
+        .map(|clave| guard.calculate_slot_for_key(clave));
+    if let Some(slot) = slot
+        && !guard.get_slot_range().contains(&slot)
+    {
+        return Err(DatoRedis::new_moved_error(slot));
+    }
+    Ok((claves, argumentos, slot))
+}
+
+/// Obtiene los indices de los tokens de EVAL y EVALSHA que se encriptan
+/// en el AOF: el script y los argumentos
+pub fn indices_datos_script(tokens: &[Token]) -> Vec<usize> {
+    let inicio_argumentos = match tokens.len() {
+        ..3 => tokens.len(),
+        _ => cantidad_claves(tokens).map_or(tokens.len(), |cantidad| 3 + cantidad),
+    };
+    std::iter::once(1)
+        .chain(inicio_argumentos..tokens.len())
+        .filter(|indice| *indice < tokens.len())
+        .collect()
+}
+
+impl Node {
+    /// Administra la cache de scripts del nodo (`SCRIPT LOAD script`,
+    /// `SCRIPT EXISTS sha [sha ...]`, `SCRIPT FLUSH [ASYNC|SYNC]`)
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - SHA1 del script cargado, arreglo indicando con 1 o 0 si existe
+    ///   cada script, u OK al vaciar la cache; error de redis en otro caso
+    pub(crate) fn script(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_SCRIPT.to_lowercase(), 2, tokens.len())?;
+        let nombre = format!("{}|{}", CMD_SCRIPT, tokens[1].texto()).to_lowercase();
+        match tokens[1].to_uppercase().as_str() {
+            CMD_SCRIPT_LOAD => {
+                assert_number_of_arguments_distinct(&nombre, 3, tokens.len())?;
+                DatoRedis::new_bulk_string(self.scripts.cargar(&tokens[2]))
+            }
+            CMD_SCRIPT_EXISTS => {
+                assert_correct_arguments_quantity(&nombre, 3, tokens.len())?;
+                let existentes = tokens[2..]
+                    .iter()
+                    .map(|sha| DatoRedis::new_integer(self.scripts.existe(&sha.texto()) as i64))
+                    .collect();
+                Ok(DatoRedis::new_array_con_contenido(
+                    Arrays::new_con_contenido(existentes),
+                ))
+            }
+            CMD_SCRIPT_FLUSH => {
+                match tokens.get(2).map(|modo| modo.to_uppercase()).as_deref() {
+                    None | Some("ASYNC") | Some("SYNC") if tokens.len() <= 3 => {}
+                    _ => {
+                        return Err(DatoRedis::new_simple_error(
+                            "ERR".to_string(),
+                            "SCRIPT FLUSH only support SYNC|ASYNC option".to_string(),
+                        ));
+                    }
+                }
+                self.scripts.vaciar();
+                DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+            }
+            _ => Err(error_subcomando_desconocido(&tokens[1].texto())),
+        }
+    }
+}
+
+/// Contexto de ejecucion de un script, que provee al interprete las
+/// palabras para acceder al nodo
+struct ContextoScript<'a> {
+    claves: &'a [Token],
+    argumentos: &'a [Token],
+    storage: &'a Arc<RwLock<Storage>>,
+    /// Si el script solo puede llamar a comandos que no modifican el
+    /// storage
+    solo_lectura: bool,
+    ultima_respuesta: DatoRedis,
+    /// Error de redis que interrumpio el script, de haberlo
+    error: Option<DatoRedis>,
+    /// Comandos de escritura ejecutados, en el orden en que se llamaron
+    escrituras: Vec<Vec<Token>>,
+}
+
+impl PalabrasExternas for ContextoScript<'_> {
+    fn ejecutar(
+        &mut self,
+        palabra: &str,
+        texto: Option<&str>,
+        pila: &mut Pila<i16>,
+    ) -> Option<Result<String, Error>> {
+        let resultado = match (palabra, texto) {
+            (PALABRA_CALL, Some(texto)) => self.llamar(texto, pila),
+            (PALABRA_REPLY, None) => Ok(texto_de_respuesta(&self.ultima_respuesta)),
+            (PALABRA_CANTIDAD_CLAVES, None) => apilar_cantidad(pila, self.claves.len()),
+            (PALABRA_CANTIDAD_ARGUMENTOS, None) => apilar_cantidad(pila, self.argumentos.len()),
+            _ => return None,
+        };
+        Some(resultado)
+    }
+
+    fn recibe_texto(&self, palabra: &str) -> bool {
+        palabra == PALABRA_CALL
+    }
+}
+
+impl ContextoScript<'_> {
+    /// Ejecuta las lineas de un script y construye su respuesta
+    fn ejecutar(&mut self, lineas: Vec<String>) -> Result<DatoRedis, DatoRedis> {
+        match ejecutar_programa(lineas, self) {
+            Ok((salida, pila)) => respuesta_de_script(salida.trim_end(), pila),
+            Err(Error::WordNotFound) => Err(error_script("unknown word")),
+            Err(e) => Err(self
+                .error
+                .take()
+                .unwrap_or_else(|| error_script(&e.to_string()))),
+        }
+    }
+
+    /// Ejecuta un comando del nodo y apila su respuesta
+    fn llamar(&mut self, texto: &str, pila: &mut Pila<i16>) -> Result<String, Error> {
+        let tokens = self.expandir(texto).map_err(|e| self.fallar(e))?;
+        let (respuesta, propagado) =
+            ejecutar_comando_de_script(&tokens, self.storage, self.solo_lectura)
+                .map_err(|e| self.fallar(e))?;
+        self.escrituras.extend(propagado);
+        let valor = valor_en_pila(&respuesta).map_err(|e| self.fallar(e))?;
+        self.ultima_respuesta = respuesta;
+        pila.apilar(valor).map(|_| String::new())
+    }
+
+    /// Separa el texto de un `call"` en tokens, reemplazando las
+    /// referencias a claves y argumentos
+    fn expandir(&self, texto: &str) -> Result<Vec<Token>, DatoRedis> {
+        texto
+            .split_whitespace()
+            .map(|token| {
+                for (prefijo, valores) in [
+                    (PREFIJO_CLAVE, self.claves),
+                    (PREFIJO_ARGUMENTO, self.argumentos),
+                ] {
+                    if let Some(indice) = token
+                        .strip_prefix(prefijo)
+                        .and_then(|resto| resto.strip_suffix(']'))
+                    {
+                        return indice
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|indice| valores.get(indice.checked_sub(1)?))
+                            .cloned()
+                            .ok_or_else(|| error_script(&format!("{token} is out of range")));
+                    }
+                }
+                Ok(Token::from(token))
+            })
+            .collect()
+    }
+
+    /// Registra el error de redis que interrumpe el script
+    fn fallar(&mut self, error: DatoRedis) -> Error {
+        let mensaje = error.convertir_resp_a_string();
+        self.error = Some(error);
+        Error::ExternalError(mensaje)
+    }
+}
+
+/// Ejecuta un comando llamado desde un script
+///
+/// # Retorna
+/// - Respuesta del comando y, si modifica el storage, los tokens con los
+///   que se propaga; error de redis en otro caso
+fn ejecutar_comando_de_script(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+    solo_lectura: bool,
+) -> Result<(DatoRedis, Option<Vec<Token>>), DatoRedis> {
+    let Some(nombre) = tokens.first().map(|nombre| nombre.to_uppercase()) else {
+        return Err(error_script(
+            "Please specify at least one argument for this call",
+        ));
+    };
+    if COMANDOS_PROHIBIDOS.contains(&nombre.as_str()) {
+        return Err(error_script(
+            "This Redis command is not allowed from script",
+        ));
+    }
+    let Some(funcion) = obtener_fn_normal(&nombre) else {
+        return Err(error_script("Unknown Redis command called from script"));
+    };
+    if solo_lectura && !es_operacion_no_mutable(&nombre) {
+        return Err(error_script(
+            "Write commands are not allowed from read-only scripts",
+        ));
+    }
+    let tokens = con_expiraciones_absolutas(&nombre, tokens);
+    match funcion(&tokens, storage) {
+        Ok(respuesta) => {
+            let propagado = (!es_operacion_no_mutable(&nombre))
+                .then(|| comando_propagado(&nombre, &tokens, &respuesta));
+            Ok((respuesta, propagado))
+        }
+        Err(DatoRedis::Null(null)) => Ok((DatoRedis::Null(null), None)),
+        Err(DatoRedis::MovedError(_)) => Err(error_script(
+            "Script attempted to access a key that does not hash to the slot of its keys",
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// Intercambia el contenido del storage del nodo con el del storage aislado
+/// donde se ejecuta el script
+fn intercambiar(guard: &mut RwLockWriteGuard<'_, Storage>, aislado: &Arc<RwLock<Storage>>) {
+    // Un comando del script que entra en panico no debe hacer perder el
+    // contenido del nodo
+    let mut aislado = match aislado.write() {
+        Ok(aislado) => aislado,
+        Err(envenenado) => envenenado.into_inner(),
+    };
+    std::mem::swap(&mut **guard, &mut *aislado);
+}
+
+/// Obtiene la cantidad de claves de un EVAL y verifica que no supere a la
+/// cantidad de tokens restantes
+pub(crate) fn cantidad_claves(tokens: &[Token]) -> Result<usize, DatoRedis> {
+    let cantidad = tokens[2].parse::<i64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not an integer or out of range".to_string(),
+        )
+    })?;
+    if cantidad < 0 {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Number of keys can't be negative".to_string(),
+        ));
+    }
+    if cantidad as usize > tokens.len() - 3 {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    Ok(cantidad as usize)
+}
+
+/// Obtiene el valor con el que se apila la respuesta de un comando
+fn valor_en_pila(respuesta: &DatoRedis) -> Result<i16, DatoRedis> {
+    match respuesta {
+        DatoRedis::Integer(entero) => i16::try_from(entero.valor())
+            .map_err(|_| error_script("integer reply out of range for the script stack")),
+        DatoRedis::Null(_) => Ok(FALSO),
+        DatoRedis::BulkString(bulk) => Ok(bulk.contenido().parse().unwrap_or(VERDADERO)),
+        _ => Ok(VERDADERO),
+    }
+}
+
+/// Obtiene el texto con el que `.reply` imprime una respuesta
+fn texto_de_respuesta(respuesta: &DatoRedis) -> String {
+    match respuesta {
+        DatoRedis::BulkString(bulk) => bulk.contenido(),
+        DatoRedis::SimpleString(simple) => simple.contenido().to_string(),
+        DatoRedis::Integer(entero) => entero.valor().to_string(),
+        DatoRedis::Null(_) => String::new(),
+        otro => otro.convertir_resp_a_string().trim_end().to_string(),
+    }
+}
+
+fn apilar_cantidad(pila: &mut Pila<i16>, cantidad: usize) -> Result<String, Error> {
+    let cantidad = i16::try_from(cantidad).map_err(|_| Error::StackOverflow)?;
+    pila.apilar(cantidad).map(|_| String::new())
+}
+
+/// Construye la respuesta de un script: su salida si imprimio algo o los
+/// numeros de la pila en otro caso
+fn respuesta_de_script(salida: &str, pila: Vec<i16>) -> Result<DatoRedis, DatoRedis> {
+    if !salida.is_empty() {
+        return DatoRedis::new_bulk_string(salida.to_string());
+    }
+    let elementos = pila
+        .into_iter()
+        .map(|valor| DatoRedis::new_integer(valor as i64))
+        .collect();
+    Ok(DatoRedis::new_array_con_contenido(
+        Arrays::new_con_contenido(elementos),
+    ))
+}
+
+fn error_script(mensaje: &str) -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        format!("Error running script: {mensaje}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::ops::Range;
+
+    use logger::logger::Logger;
+
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_role::NodeRole;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn mensaje_error(resultado: Result<DatoRedis, DatoRedis>) -> String {
+        match resultado {
+            Err(DatoRedis::SimpleError(error)) => format!("{} {}", error.tipo(), error.mensaje()),
+            otro => panic!("se esperaba un error: {otro:?}"),
+        }
+    }
+
+    /// Busca una clave que pertenezca a otro slot que `key`
+    fn clave_lejana(storage: &Arc<RwLock<Storage>>, key: &str) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(key);
+        (0..)
+            .map(|i| format!("{key}{i}"))
+            .find(|candidata| guard.calculate_slot_for_key(candidata) != slot)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_01_eval_llama_comandos_con_claves_y_argumentos() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let script = "call\" SET KEYS[1] ARGV[1]\"\ncall\" GET KEYS[1]\" .reply";
+        let respuesta = eval(
+            &tokens(&["eval", script, "1", "saludo", "hola mundo"]),
+            &storage,
+        );
+        assert_eq!(
+            respuesta,
+            DatoRedis::new_bulk_string("hola mundo".to_string())
+        );
+        assert_eq!(
+            storage.read().unwrap().get("saludo").unwrap(),
+            DatoRedis::new_bulk_string("hola mundo".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_02_eval_sin_salida_devuelve_la_pila() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let script = ": sumar call\" INCRBY KEYS[1] ARGV[1]\" call\" INCR KEYS[1]\" + ;\ncall\" GET KEYS[1]\" sumar #keys #argv";
+        let respuesta = eval(&tokens(&["eval", script, "1", "contador", "5"]), &storage).unwrap();
+        let esperado: Vec<DatoRedis> = [0, 11, 1, 1]
+            .into_iter()
+            .map(DatoRedis::new_integer)
+            .collect();
+        assert_eq!(
+            respuesta,
+            DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(esperado))
+        );
+    }
+
+    #[test]
+    fn test_03_eval_rechaza_claves_de_otros_slots() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let lejana = clave_lejana(&storage, "clave");
+
+        let cruzadas = eval(&tokens(&["eval", "1", "2", "clave", &lejana]), &storage);
+        assert!(mensaje_error(cruzadas).starts_with("CROSSSLOT"));
+
+        let script = format!("call\" SET {lejana} 1\"");
+        let no_declarada = eval(&tokens(&["eval", &script, "1", "clave"]), &storage);
+        assert!(mensaje_error(no_declarada).contains("slot"));
+        let sin_claves = eval(&tokens(&["eval", "call\" GET clave\"", "0"]), &storage);
+        assert!(mensaje_error(sin_claves).contains("slot"));
+
+        // El storage recupera su rango y su contenido
+        let guard = storage.read().unwrap();
+        assert_eq!(guard.get_slot_range(), RANGE);
+        assert!(guard.get(lejana).is_err());
+    }
+
+    #[test]
+    fn test_04_errores_del_script() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let ejecutar = |script: &str, resto: &[&str]| {
+            let mut comando = tokens(&["eval", script]);
+            comando.extend(tokens(resto));
+            eval(&comando, &storage)
+        };
+
+        assert!(ejecutar("1", &["-1"]).is_err());
+        assert!(ejecutar("1", &["2", "clave"]).is_err());
+        assert!(mensaje_error(ejecutar("+", &["0"])).contains("stack-underflow"));
+        assert!(mensaje_error(ejecutar("nada", &["0"])).contains("unknown word"));
+        assert!(mensaje_error(ejecutar("call\" FLUSHALL\"", &["0"])).contains("not allowed"));
+        assert!(mensaje_error(ejecutar("call\" GET KEYS[2]\"", &["1", "a"])).contains("range"));
+
+        ejecutar("call\" LPUSH KEYS[1] x\"", &["1", "lista"]).unwrap();
+        let tipo = ejecutar("call\" INCR KEYS[1]\" 1", &["1", "lista"]);
+        assert!(mensaje_error(tipo).starts_with("WRONGTYPE"));
+        assert!(ejecutar("call\" SET KEYS[1] 1\"", &["1", "lista"]).is_ok());
+    }
+
+    #[test]
+    fn test_05_indices_encriptados() {
+        let comando = tokens(&["eval", "script", "2", "a", "b", "x", "y"]);
+        assert_eq!(indices_datos_script(&comando), vec![1, 5, 6]);
+        assert_eq!(indices_datos_script(&tokens(&["eval", "script"])), vec![1]);
+    }
+
+    #[test]
+    fn test_06_script_load_exists_y_flush() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(RANGE)
+            .storage(Arc::new(RwLock::new(Storage::new(RANGE))))
+            .max_client_capacity(10)
+            .build()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let cliente = Arc::new(RwLock::new(Client::new(
+            "1234".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        )));
+
+        let Ok(DatoRedis::BulkString(sha)) =
+            node.script(&tokens(&["script", "load", "1 2 +"]), &cliente)
+        else {
+            panic!("se esperaba el sha del script");
+        };
+        let existe = |sha: &str| node.script(&tokens(&["script", "exists", sha, "abc"]), &cliente);
+        let esperado = |valor: i64| {
+            DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![
+                DatoRedis::new_integer(valor),
+                DatoRedis::new_integer(0),
+            ]))
+        };
+        assert_eq!(existe(&sha.contenido()).unwrap(), esperado(1));
+        assert!(
+            node.script(&tokens(&["script", "flush", "lazy"]), &cliente)
+                .is_err()
+        );
+        assert!(node.script(&tokens(&["script", "flush"]), &cliente).is_ok());
+        assert_eq!(existe(&sha.contenido()).unwrap(), esperado(0));
+        assert!(node.script(&tokens(&["script", "nada"]), &cliente).is_err());
+    }
+}
@@ -0,0 +1,636 @@
+//! Este modulo contiene la implementacion de los comandos de administracion
+//! del servidor, que consultan el estado del nodo en lugar del storage
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::client_struct::client::Client;
+use crate::comandos::const_cmd::*;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    error_subcomando_desconocido, get_storage_write_lock,
+};
+use crate::internal_protocol::node_flags::ClusterState;
+use crate::latencia::EntradaSlowlog;
+use crate::node::Node;
+use crate::node_role::NodeRole;
+
+const SECCION_SERVER: &str = "server";
+const SECCION_CLIENTS: &str = "clients";
+const SECCION_MEMORY: &str = "memory";
+const SECCION_PERSISTENCE: &str = "persistence";
+const SECCION_STATS: &str = "stats";
+const SECCION_REPLICATION: &str = "replication";
+const SECCION_CLUSTER: &str = "cluster";
+const SECCION_KEYSPACE: &str = "keyspace";
+
+/// Secciones devueltas por INFO cuando no se especifica ninguna
+const SECCIONES_DEFAULT: [&str; 8] = [
+    SECCION_SERVER,
+    SECCION_CLIENTS,
+    SECCION_MEMORY,
+    SECCION_PERSISTENCE,
+    SECCION_STATS,
+    SECCION_REPLICATION,
+    SECCION_CLUSTER,
+    SECCION_KEYSPACE,
+];
+
+/// Cantidad de entradas que devuelve SLOWLOG GET sin argumento
+const SLOWLOG_GET_DEFAULT: usize = 10;
+
+type CamposInfo = Vec<(String, String)>;
+
+impl Node {
+    /// Devuelve informacion y estadisticas del nodo en el formato de texto
+    /// de INFO: una cabecera `# Seccion` seguida de lineas `campo:valor`
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando y opcionalmente las
+    ///   secciones pedidas (`all`, `everything` y `default` incluyen todas)
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - Bulk string con las secciones pedidas, vacio si ninguna existe
+    pub(crate) fn info(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        let pedidas: Vec<String> = tokens[1..].iter().map(|s| s.to_lowercase()).collect();
+        let todas = pedidas.is_empty()
+            || pedidas
+                .iter()
+                .any(|s| matches!(s.as_str(), "all" | "everything" | "default"));
+
+        let mut secciones = Vec::new();
+        for seccion in SECCIONES_DEFAULT {
+            if todas || pedidas.iter().any(|s| s == seccion) {
+                let campos = self.campos_seccion_info(seccion)?;
+                secciones.push(formatear_seccion(seccion, campos));
+            }
+        }
+        DatoRedis::new_bulk_string(secciones.join("\r\n"))
+    }
+
+    /// Obtiene los campos de una seccion de INFO
+    ///
+    /// # Parámetros
+    /// * `seccion`: nombre de la seccion en minusculas
+    ///
+    /// # Retorna
+    /// - Pares campo/valor de la seccion, error de redis si no se pudo
+    ///   acceder al storage
+    fn campos_seccion_info(&self, seccion: &str) -> Result<CamposInfo, DatoRedis> {
+        let estadisticas = &self.estadisticas;
+        let campos = match seccion {
+            SECCION_SERVER => {
+                let segundos = estadisticas.segundos_activo();
+                vec![
+                    campo("redis_version", env!("CARGO_PKG_VERSION")),
+                    campo("redis_mode", "cluster"),
+                    campo("process_id", std::process::id()),
+                    campo("run_id", self.id.get_id()),
+                    campo("tcp_port", self.cli_addr.port()),
+                    campo("uptime_in_seconds", segundos),
+                    campo("uptime_in_days", segundos / 86_400),
+                ]
+            }
+            SECCION_CLIENTS => {
+                let (suscriptores, max_buffer_salida) = self.resumen_buffers_salida();
+                vec![
+                    campo(
+                        "connected_clients",
+                        self.act_client_active.load(Ordering::SeqCst),
+                    ),
+                    campo(
+                        "maxclients",
+                        self.max_client_capacity.load(Ordering::SeqCst),
+                    ),
+                    campo("pubsub_clients", suscriptores),
+                    campo("client_recent_max_output_buffer", max_buffer_salida),
+                ]
+            }
+            SECCION_MEMORY => {
+                let mut storage = get_storage_write_lock(&self.storage)?;
+                let usada = storage.memoria_usada();
+                let maxima = storage.get_maxmemory();
+                vec![
+                    campo("used_memory", usada),
+                    campo("used_memory_human", formatear_bytes(usada)),
+                    campo("maxmemory", maxima),
+                    campo("maxmemory_human", formatear_bytes(maxima)),
+                    campo("maxmemory_policy", storage.get_politica().nombre()),
+                ]
+            }
+            SECCION_PERSISTENCE => vec![
+                campo("aof_enabled", self.appendonly.load(Ordering::SeqCst) as u8),
+                campo("rdb_saves", estadisticas.guardados_realizados()),
+                campo("rdb_last_save_time", estadisticas.ultimo_guardado()),
+                campo(
+                    "rdb_last_bgsave_status",
+                    if estadisticas.ultimo_guardado_ok() {
+                        "ok"
+                    } else {
+                        "err"
+                    },
+                ),
+                campo(
+                    "rdb_save_interval_ms",
+                    self.save_interval.load(Ordering::SeqCst),
+                ),
+            ],
+            SECCION_STATS => {
+                let storage = get_storage_write_lock(&self.storage)?;
+                vec![
+                    campo(
+                        "total_connections_received",
+                        estadisticas.conexiones_recibidas(),
+                    ),
+                    campo(
+                        "total_commands_processed",
+                        estadisticas.comandos_procesados(),
+                    ),
+                    campo("evicted_keys", storage.claves_desalojadas()),
+                    campo(
+                        "client_output_buffer_limit_disconnections",
+                        self.pub_sub.buffers().desconexiones(),
+                    ),
+                ]
+            }
+            SECCION_REPLICATION => self.campos_replicacion(),
+            SECCION_CLUSTER => self.campos_cluster(),
+            SECCION_KEYSPACE => {
+                let storage = get_storage_write_lock(&self.storage)?;
+                let mut campos = vec![campo(
+                    "db0",
+                    format!(
+                        "keys={},expires={}",
+                        storage.cantidad_claves(),
+                        storage.cantidad_claves_volatiles()
+                    ),
+                )];
+                for (slot, claves) in storage.claves_por_slot() {
+                    campos.push(campo(&format!("slot{slot}"), format!("keys={claves}")));
+                }
+                campos
+            }
+            _ => Vec::new(),
+        };
+        Ok(campos)
+    }
+
+    /// Obtiene la cantidad de clientes en modo pub/sub y el mayor buffer
+    /// de salida entre los clientes conectados
+    fn resumen_buffers_salida(&self) -> (usize, usize) {
+        let mut suscriptores = 0;
+        let mut max_buffer_salida = 0;
+        for client in self.clientes.clientes() {
+            if let Ok(cliente) = client.read() {
+                if !cliente.get_channels().is_empty()
+                    || !cliente.get_pchannels().is_empty()
+                    || !cliente.get_schannels().is_empty()
+                {
+                    suscriptores += 1;
+                }
+                max_buffer_salida = max_buffer_salida.max(cliente.get_buffer_salida());
+            }
+        }
+        (suscriptores, max_buffer_salida)
+    }
+
+    /// Consulta o reinicia el registro de comandos lentos
+    /// (`SLOWLOG GET [cantidad]`, `SLOWLOG LEN`, `SLOWLOG RESET`)
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos. Una cantidad de -1 devuelve todas las entradas
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - Entradas del slowlog de la mas nueva a la mas vieja (cada una con
+    ///   id, timestamp, duracion en microsegundos, argumentos, direccion y
+    ///   nombre del cliente), su largo u OK, segun el subcomando
+    pub(crate) fn slowlog(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_SLOWLOG.to_lowercase(), 2, tokens.len())?;
+        match tokens[1].to_uppercase().as_str() {
+            CMD_SLOWLOG_GET => {
+                let cantidad = match tokens.get(2) {
+                    None => SLOWLOG_GET_DEFAULT,
+                    Some(cantidad) => match cantidad.parse::<i64>() {
+                        Ok(-1) => usize::MAX,
+                        Ok(n) if n >= 0 => n as usize,
+                        _ => {
+                            return Err(DatoRedis::new_simple_error(
+                                "ERR".to_string(),
+                                "count should be greater than or equal to -1".to_string(),
+                            ));
+                        }
+                    },
+                };
+                let entradas = self
+                    .slowlog
+                    .obtener(cantidad)
+                    .iter()
+                    .map(entrada_slowlog_a_redis)
+                    .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+                Ok(array_de(entradas))
+            }
+            CMD_SLOWLOG_LEN => Ok(DatoRedis::new_integer(self.slowlog.largo() as i64)),
+            CMD_SLOWLOG_RESET => {
+                self.slowlog.reiniciar();
+                DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+            }
+            _ => Err(error_subcomando_desconocido(&tokens[1].texto())),
+        }
+    }
+
+    /// Consulta o reinicia el monitor de picos de latencia
+    /// (`LATENCY LATEST`, `LATENCY HISTORY evento`, `LATENCY RESET [evento ...]`)
+    ///
+    /// # Parámetros
+    /// * `tokens`: lista conteniendo nombre del comando, subcomando y sus
+    ///   argumentos
+    /// * `_client`: cliente que ejecuta el comando
+    ///
+    /// # Retorna
+    /// - Ultimo pico de cada evento (nombre, timestamp, ultima y maxima
+    ///   latencia en ms), las muestras de un evento (timestamp y latencia) o
+    ///   la cantidad de eventos reiniciados, segun el subcomando
+    pub(crate) fn latency(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_correct_arguments_quantity(&CMD_LATENCY.to_lowercase(), 2, tokens.len())?;
+        let filas = match tokens[1].to_uppercase().as_str() {
+            CMD_LATENCY_LATEST => self
+                .latencia
+                .ultimos()
+                .into_iter()
+                .map(|pico| {
+                    Ok(array_de(vec![
+                        DatoRedis::new_bulk_string(pico.evento)?,
+                        DatoRedis::new_integer(pico.timestamp as i64),
+                        DatoRedis::new_integer(pico.ultimo_ms as i64),
+                        DatoRedis::new_integer(pico.maximo_ms as i64),
+                    ]))
+                })
+                .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?,
+            CMD_LATENCY_HISTORY => {
+                assert_number_of_arguments_distinct(
+                    &CMD_LATENCY_HISTORY.to_lowercase(),
+                    3,
+                    tokens.len(),
+                )?;
+                self.latencia
+                    .historial(&tokens[2].to_lowercase())
+                    .into_iter()
+                    .map(|(timestamp, ms)| {
+                        array_de(vec![
+                            DatoRedis::new_integer(timestamp as i64),
+                            DatoRedis::new_integer(ms as i64),
+                        ])
+                    })
+                    .collect()
+            }
+            CMD_LATENCY_RESET => {
+                let eventos: Vec<String> = tokens[2..].iter().map(|e| e.to_lowercase()).collect();
+                let reiniciados = self.latencia.reiniciar(&eventos);
+                return Ok(DatoRedis::new_integer(reiniciados as i64));
+            }
+            _ => return Err(error_subcomando_desconocido(&tokens[1].texto())),
+        };
+        Ok(array_de(filas))
+    }
+
+    /// Devuelve la hora del servidor
+    ///
+    /// # Retorna
+    /// - Array con los segundos desde UNIX epoch y los microsegundos
+    ///   transcurridos del segundo actual, error de redis si se reciben
+    ///   argumentos
+    pub(crate) fn time(
+        &self,
+        tokens: &[Token],
+        _client: &Arc<RwLock<Client>>,
+    ) -> Result<DatoRedis, DatoRedis> {
+        assert_number_of_arguments_distinct(&CMD_TIME.to_lowercase(), 1, tokens.len())?;
+        let ahora = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(array_de(vec![
+            DatoRedis::new_bulk_string(ahora.as_secs().to_string())?,
+            DatoRedis::new_bulk_string(ahora.subsec_micros().to_string())?,
+        ]))
+    }
+
+    /// Campos de la seccion de replicacion de INFO
+    fn campos_replicacion(&self) -> CamposInfo {
+        let es_master = self
+            .role
+            .read()
+            .map(|rol| *rol == NodeRole::Master)
+            .unwrap_or(true);
+        let mut campos = vec![campo("role", if es_master { "master" } else { "slave" })];
+
+        if es_master {
+            let replicas = self
+                .replicas
+                .read()
+                .map(|r| r.as_ref().map(|v| v.len()).unwrap_or(0))
+                .unwrap_or(0);
+            campos.push(campo("connected_slaves", replicas));
+        } else if let Ok(master) = self.master.read() {
+            let id_master = master
+                .as_ref()
+                .map(|id| id.get_id().to_string())
+                .unwrap_or_default();
+            campos.push(campo("master_node_id", id_master));
+        }
+        campos.push(campo(
+            "master_repl_offset",
+            self.replication_offset.load(Ordering::SeqCst),
+        ));
+        campos
+    }
+
+    /// Campos de la seccion de cluster de INFO
+    fn campos_cluster(&self) -> CamposInfo {
+        let estado = match self.cluster_state.read().map(|e| e.clone()) {
+            Ok(ClusterState::Ok) => "ok",
+            _ => "fail",
+        };
+        let conocidos = self.knows_nodes.read().map(|n| n.len()).unwrap_or(0);
+        vec![
+            campo("cluster_enabled", 1),
+            campo("cluster_state", estado),
+            campo(
+                "cluster_current_epoch",
+                self.current_epoch.load(Ordering::SeqCst),
+            ),
+            campo("cluster_my_epoch", self.config_epoch.load(Ordering::SeqCst)),
+            campo("cluster_known_nodes", conocidos + 1),
+            campo(
+                "cluster_slot_range",
+                format!("{}-{}", self.slot_range.start, self.slot_range.end),
+            ),
+            campo(
+                "cluster_pubsub_messages_sent",
+                self.pub_sub.rutas().enviados(),
+            ),
+            campo(
+                "cluster_pubsub_messages_skipped",
+                self.pub_sub.rutas().omitidos(),
+            ),
+        ]
+    }
+}
+
+/// Convierte una entrada del slowlog al formato de respuesta de SLOWLOG GET
+fn entrada_slowlog_a_redis(entrada: &EntradaSlowlog) -> Result<DatoRedis, DatoRedis> {
+    let argumentos = entrada
+        .argumentos
+        .iter()
+        .map(|argumento| DatoRedis::new_bulk_string(argumento.clone()))
+        .collect::<Result<Vec<DatoRedis>, DatoRedis>>()?;
+    Ok(array_de(vec![
+        DatoRedis::new_integer(entrada.id as i64),
+        DatoRedis::new_integer(entrada.timestamp as i64),
+        DatoRedis::new_integer(entrada.duracion_us as i64),
+        array_de(argumentos),
+        DatoRedis::new_bulk_string(entrada.addr.clone())?,
+        DatoRedis::new_bulk_string(entrada.nombre.clone())?,
+    ]))
+}
+
+fn array_de(contenido: Vec<DatoRedis>) -> DatoRedis {
+    DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(contenido))
+}
+
+/// Crea un par campo/valor de INFO
+fn campo(nombre: &str, valor: impl ToString) -> (String, String) {
+    (nombre.to_string(), valor.to_string())
+}
+
+/// Formatea una seccion de INFO con su cabecera y sus campos
+///
+/// # Parámetros
+/// * `seccion`: nombre de la seccion en minusculas
+/// * `campos`: pares campo/valor de la seccion
+///
+/// # Retorna
+/// - Texto de la seccion terminado en CRLF
+fn formatear_seccion(seccion: &str, campos: CamposInfo) -> String {
+    let mut titulo = seccion.to_string();
+    if let Some(primera) = titulo.get_mut(0..1) {
+        primera.make_ascii_uppercase();
+    }
+    let mut texto = format!("# {titulo}\r\n");
+    for (campo, valor) in campos {
+        texto.push_str(&format!("{campo}:{valor}\r\n"));
+    }
+    texto
+}
+
+/// Formatea una cantidad de bytes en unidades legibles (B, K, M, G)
+fn formatear_bytes(bytes: usize) -> String {
+    const UNIDADES: [&str; 3] = ["K", "M", "G"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut valor = bytes as f64 / 1024.0;
+    let mut unidad = 0;
+    while valor >= 1024.0 && unidad < UNIDADES.len() - 1 {
+        valor /= 1024.0;
+        unidad += 1;
+    }
+    format!("{valor:.2}{}", UNIDADES[unidad])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+
+    use logger::logger::Logger;
+    use redis_client::tipos_datos::traits::DatoRedis;
+
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::storage::Storage;
+
+    fn make_node() -> Node {
+        NodeBuilder::para_pruebas()
+            .slot_range(0..16378)
+            .storage(Arc::new(RwLock::new(Storage::new(0..16378))))
+            .max_client_capacity(10)
+            .build()
+            .unwrap()
+    }
+
+    fn make_client() -> Arc<RwLock<Client>> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let cliente = Client::new(
+            "1234".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        );
+        Arc::new(RwLock::new(cliente))
+    }
+
+    fn info_texto(node: &Node, tokens: &[&str]) -> String {
+        let tokens: Vec<Token> = tokens.iter().map(|s| Token::from(*s)).collect();
+        match node.info(&tokens, &make_client()).unwrap() {
+            DatoRedis::BulkString(bulk) => bulk.contenido(),
+            otro => panic!("Se esperaba un bulk string: {otro:?}"),
+        }
+    }
+
+    #[test]
+    fn test_01_info_sin_argumentos_devuelve_todas_las_secciones() {
+        let node = make_node();
+        let texto = info_texto(&node, &["info"]);
+        for titulo in [
+            "# Server",
+            "# Clients",
+            "# Memory",
+            "# Persistence",
+            "# Stats",
+            "# Replication",
+            "# Cluster",
+            "# Keyspace",
+        ] {
+            assert!(texto.contains(titulo), "falta la seccion {titulo}");
+        }
+        assert!(texto.contains("role:master\r\n"));
+        assert!(texto.contains("maxclients:10\r\n"));
+    }
+
+    #[test]
+    fn test_02_info_de_una_seccion() {
+        let node = make_node();
+        node.estadisticas.registrar_comando();
+        let texto = info_texto(&node, &["info", "STATS"]);
+        assert!(texto.starts_with("# Stats\r\n"));
+        assert!(texto.contains("total_commands_processed:1\r\n"));
+        assert!(!texto.contains("# Server"));
+        assert_eq!(info_texto(&node, &["info", "inexistente"]), "");
+    }
+
+    #[test]
+    fn test_03_info_keyspace_cuenta_claves_por_slot() {
+        let node = make_node();
+        {
+            let mut storage = node.storage.write().unwrap();
+            for key in ["a", "b", "c"] {
+                storage
+                    .set(key, DatoRedis::new_bulk_string("v".to_string()).unwrap())
+                    .unwrap();
+            }
+        }
+        let texto = info_texto(&node, &["info", "keyspace"]);
+        assert!(texto.contains("db0:keys=3,expires=0\r\n"));
+        assert_eq!(texto.matches(":keys=1\r\n").count(), 3);
+    }
+
+    #[test]
+    fn test_04_formatear_bytes() {
+        assert_eq!(formatear_bytes(512), "512B");
+        assert_eq!(formatear_bytes(2048), "2.00K");
+        assert_eq!(formatear_bytes(3 * 1024 * 1024), "3.00M");
+    }
+
+    #[test]
+    fn test_05_slowlog_get_len_y_reset() {
+        let node = make_node();
+        let client = make_client();
+        let tokens = |t: &[&str]| t.iter().map(|s| Token::from(*s)).collect::<Vec<Token>>();
+        node.slowlog.set_umbral_us(0);
+        node.slowlog.registrar(
+            std::time::Duration::from_millis(3),
+            &tokens(&["set", "a", "b"]),
+            "127.0.0.1:5000",
+            "worker",
+        );
+
+        assert_eq!(
+            node.slowlog(&tokens(&["slowlog", "len"]), &client),
+            Ok(DatoRedis::new_integer(1))
+        );
+        let DatoRedis::Arrays(entradas) =
+            node.slowlog(&tokens(&["slowlog", "get"]), &client).unwrap()
+        else {
+            panic!("Se esperaba un array");
+        };
+        let Some(DatoRedis::Arrays(entrada)) = entradas.get(0) else {
+            panic!("Se esperaba una entrada");
+        };
+        assert_eq!(entrada.get(2), Some(DatoRedis::new_integer(3000)));
+        assert_eq!(
+            entrada.get(5),
+            Some(DatoRedis::new_bulk_string("worker".to_string()).unwrap())
+        );
+
+        node.slowlog(&tokens(&["slowlog", "reset"]), &client)
+            .unwrap();
+        assert_eq!(node.slowlog.largo(), 0);
+    }
+
+    #[test]
+    fn test_06_latency_latest_y_reset() {
+        let node = make_node();
+        let client = make_client();
+        let tokens = |t: &[&str]| t.iter().map(|s| Token::from(*s)).collect::<Vec<Token>>();
+        node.latencia.set_umbral_ms(1);
+        node.latencia.registrar(
+            crate::latencia::EVENTO_AOF,
+            std::time::Duration::from_millis(5),
+        );
+
+        let DatoRedis::Arrays(ultimos) = node
+            .latency(&tokens(&["latency", "latest"]), &client)
+            .unwrap()
+        else {
+            panic!("Se esperaba un array");
+        };
+        let Some(DatoRedis::Arrays(pico)) = ultimos.get(0) else {
+            panic!("Se esperaba un pico");
+        };
+        assert_eq!(
+            pico.get(0),
+            Some(DatoRedis::new_bulk_string("aof-write".to_string()).unwrap())
+        );
+        assert_eq!(pico.get(3), Some(DatoRedis::new_integer(5)));
+        assert_eq!(
+            node.latency(&tokens(&["latency", "reset"]), &client),
+            Ok(DatoRedis::new_integer(1))
+        );
+    }
+
+    #[test]
+    fn test_07_time() {
+        let node = make_node();
+        let client = make_client();
+        let tokens = |t: &[&str]| t.iter().map(|s| Token::from(*s)).collect::<Vec<Token>>();
+        let DatoRedis::Arrays(hora) = node.time(&tokens(&["time"]), &client).unwrap() else {
+            panic!("Se esperaba un array");
+        };
+        let entero = |i: usize| match hora.get(i) {
+            Some(DatoRedis::BulkString(bulk)) => bulk.contenido().parse::<u64>().unwrap(),
+            otro => panic!("Se esperaba un bulk string: {otro:?}"),
+        };
+        assert!(entero(0) > 1_600_000_000);
+        assert!(entero(1) < 1_000_000);
+        assert!(node.time(&tokens(&["time", "extra"]), &client).is_err());
+    }
+}
@@ -1,12 +1,15 @@
 //! Este modulo contiene la implementacion de los comandos
 //! del tipo set de redis
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
 use std::sync::RwLock;
 
 use crate::comandos::utils::{
-    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct, parsear_cursor,
+    parsear_opciones_scan, respuesta_scan,
 };
 use crate::storage::Storage;
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::traits::DatoRedis;
 
 use super::utils::get_storage_read_lock;
@@ -24,15 +27,15 @@ use super::utils::get_storage_write_lock;
 /// # Retorna
 /// - en caso de insercion exitosa, la cantidad de valores insertados,
 ///   error simple de redis en otros casos
-pub fn sadd(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
+pub fn sadd(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
 
     let mut guard = get_storage_write_lock(storage)?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let elementos = tokens[2..].to_vec();
 
-    let set_ref = match guard.get_mutable(key.clone()) {
+    let set_ref = match guard.get_mutable(key) {
         Ok(DatoRedis::Set(set)) => set,
         Ok(_) => {
             return Err(DatoRedis::new_simple_error(
@@ -44,8 +47,8 @@ pub fn sadd(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
             DatoRedis::MovedError(_) => return Err(e),
             _ => {
                 let nuevo_set = DatoRedis::new_set();
-                guard.set(key.clone(), nuevo_set)?;
-                match guard.get_mutable(key.clone()) {
+                guard.set(key, nuevo_set)?;
+                match guard.get_mutable(key) {
                     Ok(DatoRedis::Set(set)) => set,
                     _ => {
                         return Err(DatoRedis::new_simple_error(
@@ -60,7 +63,7 @@ pub fn sadd(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 
     let mut nuevos_insertados = 0;
     for valor in elementos {
-        set_ref.insert(DatoRedis::new_bulk_string(valor)?);
+        set_ref.insert(DatoRedis::new_bulk_string_desde_bytes(valor.into_bytes())?);
         nuevos_insertados += 1;
     }
 
@@ -76,12 +79,12 @@ pub fn sadd(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 ///
 /// # Retorna
 /// - cardinalidad del set, 0 de estar vacio o no existir
-pub fn scard(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_number_of_arguments_distinct(tokens[0].to_string(), 2, tokens.len())?;
+pub fn scard(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 2, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let mut guard = get_storage_write_lock(storage)?;
-    match guard.get_mutable(key.to_string()) {
+    match guard.get_mutable(key) {
         Ok(DatoRedis::Set(set)) => Ok(DatoRedis::new_integer(set.len() as i64)),
         Ok(_) => Ok(DatoRedis::new_integer(0)),
         Err(e) => {
@@ -102,20 +105,17 @@ pub fn scard(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRe
 ///
 /// # Retorna
 /// - 1 si el elemento pertenece al set, 0 en otro caso
-pub fn sismember(
-    tokens: &[String],
-    storage: &Arc<RwLock<Storage>>,
-) -> Result<DatoRedis, DatoRedis> {
-    assert_number_of_arguments_distinct(tokens[0].to_string(), 3, tokens.len())?;
+pub fn sismember(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 3, tokens.len())?;
 
     let guard = get_storage_read_lock(storage)?;
-    let key = tokens[1].to_string();
-    let element = tokens[2].to_string();
+    let key = &tokens[1];
+    let element = &tokens[2];
     let set_ref = guard.get(key).map_err(|_| DatoRedis::new_integer(0))?;
 
     // I don't like this, but it will return 0 either if the key or the element doesn't exist
     if let DatoRedis::Set(set) = set_ref {
-        if set.contains_member(&DatoRedis::new_bulk_string(element)?) {
+        if set.contains_member(&DatoRedis::new_bulk_string_desde_bytes(element.to_vec())?) {
             Ok(DatoRedis::new_integer(1))
         } else {
             Ok(DatoRedis::new_integer(0))
@@ -134,12 +134,12 @@ pub fn sismember(
 ///
 /// # Retorna
 /// - cantidad de elementos removidos del set
-pub fn srem(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
+pub fn srem(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
     let mut guard = get_storage_write_lock(storage)?;
 
-    let key = tokens[1].to_string();
-    let set_ref = guard.get_mutable(key.to_string()).map_err(|e| {
+    let key = &tokens[1];
+    let set_ref = guard.get_mutable(key).map_err(|e| {
         if let DatoRedis::MovedError(_) = e {
             e
         } else {
@@ -151,7 +151,7 @@ pub fn srem(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
         if set.is_empty() {
             return Ok(DatoRedis::new_integer(0));
         }
-        let element_to_remove = DatoRedis::new_bulk_string(tokens[2].to_string())?;
+        let element_to_remove = DatoRedis::new_bulk_string_desde_bytes(tokens[2].to_vec())?;
         let response = set.remove_member(&element_to_remove);
         Ok(DatoRedis::new_integer(response as i64))
     } else {
@@ -166,10 +166,10 @@ pub fn srem(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 /// * `storage`: storage del nodo donde se encuentra el set
 /// # Retorna
 /// - Una copia del ser original, o un error si el set no existe
-pub fn smembers(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_number_of_arguments_distinct(tokens[0].to_string(), 2, tokens.len())?;
+pub fn smembers(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].texto(), 2, tokens.len())?;
 
-    let key = tokens[1].to_string();
+    let key = &tokens[1];
     let guard = storage.read().map_err(|_| {
         DatoRedis::new_simple_error(
             "ERR".to_string(),
@@ -188,10 +188,82 @@ pub fn smembers(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<Dat
     }
 }
 
+/// Itera los elementos de un set mediante un cursor (`SSCAN clave cursor
+/// [MATCH patron] [COUNT cantidad]`). Los elementos se recorren ordenados
+/// por un hash estable, por lo que todo elemento presente durante la
+/// iteracion completa se devuelve al menos una vez
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave, cursor y
+///   opciones
+/// * `storage`: storage del nodo donde se encuentra el set
+///
+/// # Retorna
+/// - Cursor siguiente (0 al terminar) y elementos obtenidos, error de
+///   redis si la clave no contiene un set o las opciones son invalidas
+pub fn sscan(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+    let cursor = parsear_cursor(&tokens[2])?;
+    let opciones = parsear_opciones_scan(&tokens[3..], false)?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let set = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::Set(set)) => set,
+        Ok(_) => {
+            return Err(DatoRedis::new_simple_error(
+                "WRONGTYPE".to_string(),
+                "Operation against a key holding the wrong kind of value".to_string(),
+            ));
+        }
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        Err(_) => return respuesta_scan(0, Vec::new()),
+    };
+
+    let mut pendientes: Vec<(u64, &DatoRedis)> = set
+        .iter()
+        .map(|elemento| (hash_estable(elemento), elemento))
+        .filter(|(hash, _)| *hash >= cursor)
+        .collect();
+    pendientes.sort_by_key(|(hash, _)| *hash);
+
+    // Se incluyen todos los elementos que comparten el hash del ultimo
+    // devuelto, para que el cursor siguiente no saltee ninguno
+    let mut cantidad = pendientes.len().min(opciones.count);
+    while cantidad > 0
+        && cantidad < pendientes.len()
+        && pendientes[cantidad].0 == pendientes[cantidad - 1].0
+    {
+        cantidad += 1;
+    }
+    let siguiente = match (pendientes.get(cantidad), cantidad.checked_sub(1)) {
+        (Some(_), Some(ultimo)) => pendientes[ultimo].0.checked_add(1).unwrap_or(0),
+        _ => 0,
+    };
+
+    let elementos = pendientes[..cantidad]
+        .iter()
+        .map(|(_, elemento)| (*elemento).clone())
+        .filter(|elemento| match elemento {
+            DatoRedis::BulkString(bulk) => opciones.coincide(bulk.bytes()),
+            _ => opciones.patron.is_none(),
+        })
+        .collect();
+    respuesta_scan(siguiente, elementos)
+}
+
+/// Calcula un hash de un elemento que no varia entre llamadas, usado como
+/// cursor de SSCAN
+fn hash_estable(elemento: &DatoRedis) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    elemento.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{sadd, scard, sismember, smembers, srem};
+    use super::{sadd, scard, sismember, smembers, srem, sscan};
     use crate::storage::Storage;
+    use redis_client::protocol::token::Token;
     use redis_client::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
     use std::{
         ops::Range,
@@ -203,14 +275,14 @@ mod tests {
         end: 16378,
     };
 
-    fn convert_to_tokens(vector: Vec<&str>) -> Vec<String> {
-        vector.into_iter().map(|s| s.to_string()).collect()
+    fn convert_to_tokens(vector: Vec<&str>) -> Vec<Token> {
+        vector.into_iter().map(Token::from).collect()
     }
 
     #[test]
     fn test_sadd_with_one_argument() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = convert_to_tokens(["sadd", "key1", "value1"].to_vec());
+        let tokens: Vec<Token> = convert_to_tokens(["sadd", "key1", "value1"].to_vec());
 
         let response = sadd(&tokens, &storage).unwrap();
         assert_eq!(response, DatoRedis::new_integer(1));
@@ -219,7 +291,7 @@ mod tests {
     #[test]
     fn test_sadd_with_n_arguments() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> =
+        let tokens: Vec<Token> =
             convert_to_tokens(["sadd", "key1", "value1", "value2", "value3"].to_vec());
 
         let response = sadd(&tokens, &storage).unwrap();
@@ -323,4 +395,48 @@ mod tests {
         assert!(response.convertir_resp_a_string().contains("value1"));
         assert!(response.convertir_resp_a_string().contains("value2"));
     }
+
+    /// Ejecuta un SSCAN y devuelve el cursor siguiente y los elementos
+    fn sscan_pagina(storage: &Arc<RwLock<Storage>>, cursor: &str) -> (String, Vec<String>) {
+        let tokens = convert_to_tokens(["sscan", "key1", cursor, "COUNT", "3"].to_vec());
+        let DatoRedis::Arrays(respuesta) = sscan(&tokens, storage).unwrap() else {
+            panic!("Se esperaba un array");
+        };
+        let contenido = |dato: DatoRedis| match dato {
+            DatoRedis::BulkString(bulk) => bulk.contenido(),
+            otro => panic!("Se esperaba un bulk string: {otro:?}"),
+        };
+        let Some(DatoRedis::Arrays(elementos)) = respuesta.get(1) else {
+            panic!("Se esperaba un array de elementos");
+        };
+        (
+            contenido(respuesta.get(0).unwrap()),
+            elementos.iter().cloned().map(contenido).collect(),
+        )
+    }
+
+    #[test]
+    fn test_sscan_returns_every_member_present_during_iteration() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let miembros: Vec<String> = (0..20).map(|i| format!("value{i}")).collect();
+        let mut tokens = convert_to_tokens(vec!["sadd", "key1"]);
+        tokens.extend(miembros.iter().map(Token::from));
+        sadd(&tokens, &storage).unwrap();
+
+        let (mut cursor, mut vistos) = sscan_pagina(&storage, "0");
+        let tokens = convert_to_tokens(["sadd", "key1", "extra1", "extra2"].to_vec());
+        sadd(&tokens, &storage).unwrap();
+        while cursor != "0" {
+            let (siguiente, elementos) = sscan_pagina(&storage, &cursor);
+            vistos.extend(elementos);
+            cursor = siguiente;
+        }
+        assert!(miembros.iter().all(|miembro| vistos.contains(miembro)));
+
+        let tokens =
+            convert_to_tokens(["sscan", "key1", "0", "MATCH", "extra*", "COUNT", "100"].to_vec());
+        let response = sscan(&tokens, &storage).unwrap();
+        assert!(response.convertir_resp_a_string().contains("extra1"));
+        assert!(!response.convertir_resp_a_string().contains("value1"));
+    }
 }
@@ -0,0 +1,874 @@
+//! Este modulo contiene la implementacion de los comandos de redis para
+//! streams: XADD, XLEN, XRANGE, XREVRANGE, XTRIM, XDEL y XREAD
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::stream::{CamposStream, Stream, StreamId, entrada_a_dato};
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::const_cmd::CMD_XREAD;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct, get_storage_read_lock,
+    get_storage_write_lock,
+};
+use crate::memoria::{VariacionMemoria, ahora_ms};
+use crate::storage::Storage;
+
+const OPCION_NOMKSTREAM: &str = "NOMKSTREAM";
+const OPCION_MAXLEN: &str = "MAXLEN";
+const OPCION_MINID: &str = "MINID";
+const OPCION_LIMIT: &str = "LIMIT";
+pub(crate) const OPCION_COUNT: &str = "COUNT";
+pub(crate) const OPCION_BLOCK: &str = "BLOCK";
+pub(crate) const OPCION_STREAMS: &str = "STREAMS";
+/// Id generado automaticamente por XADD
+const ID_AUTOMATICO: &str = "*";
+/// Ultimo id del stream al ejecutarse XREAD
+pub(crate) const ID_ULTIMO: &str = "$";
+const ID_MINIMO: &str = "-";
+const ID_MAXIMO: &str = "+";
+/// Prefijo de los limites exclusivos de XRANGE y XREVRANGE
+const PREFIJO_EXCLUSIVO: char = '(';
+
+/// Criterio de recorte de un stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Recorte {
+    /// Cantidad maxima de entradas
+    MaxLen(usize),
+    /// Id minimo de las entradas a conservar
+    MinId(StreamId),
+}
+
+impl Recorte {
+    /// Aplica el recorte al stream, registrando la memoria de las entradas
+    /// eliminadas
+    ///
+    /// # Retorna
+    /// - cantidad de entradas eliminadas
+    fn aplicar(self, stream: &mut Stream, variacion: &mut VariacionMemoria) -> usize {
+        let eliminadas = match self {
+            Recorte::MaxLen(maxlen) => stream.len().saturating_sub(maxlen),
+            Recorte::MinId(minid) => stream.iter().take_while(|(id, _)| **id < minid).count(),
+        };
+        stream
+            .iter()
+            .take(eliminadas)
+            .for_each(|(_, campos)| variacion.liberar_entrada(campos));
+        match self {
+            Recorte::MaxLen(maxlen) => stream.recortar_maxlen(maxlen),
+            Recorte::MinId(minid) => stream.recortar_minid(minid),
+        }
+    }
+}
+
+/// Agrega una entrada a un stream, creandolo si no existe
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, opciones
+///   NOMKSTREAM y MAXLEN/MINID, el id de la entrada (`*` para generarlo) y
+///   los pares campo-valor
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - el id de la entrada agregada, null si no existe el stream y se
+///   recibio NOMKSTREAM, error simple de redis en otros casos
+pub fn xadd(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 5, tokens.len())?;
+    let (nomkstream, recorte, posicion_id) = parsear_opciones_xadd(tokens)?;
+    let campos = parsear_campos(&tokens[0].texto(), &tokens[posicion_id + 1..])?;
+    let id_pedido = &tokens[posicion_id].texto();
+
+    let mut guard = get_storage_write_lock(storage)?;
+    let existente = match guard.get_mutable(&tokens[1]) {
+        Ok(DatoRedis::Stream(stream)) => Some(stream),
+        Ok(_) => return Err(error_tipo()),
+        Err(e @ DatoRedis::MovedError(_)) => return Err(e),
+        Err(_) => None,
+    };
+    let mut variacion = VariacionMemoria::default();
+    let id = match existente {
+        Some(stream) => {
+            let id = agregar_entrada(stream, id_pedido, campos, recorte, &mut variacion);
+            guard.registrar_variacion(&tokens[1], variacion);
+            id?
+        }
+        None if nomkstream => return Ok(DatoRedis::new_null()),
+        None => {
+            let mut stream = Stream::new();
+            let id = agregar_entrada(&mut stream, id_pedido, campos, recorte, &mut variacion)?;
+            guard.set(&tokens[1], DatoRedis::Stream(stream))?;
+            id
+        }
+    };
+    DatoRedis::new_bulk_string(id.to_string())
+}
+
+/// Reemplaza el id pedido en un XADD por el id efectivamente agregado, para
+/// que las replicas y el AOF agreguen la entrada con el mismo id
+///
+/// # Parámetros
+/// * `tokens`: comando XADD ejecutado
+/// * `respuesta`: respuesta del comando
+pub(crate) fn xadd_con_id(tokens: &[Token], respuesta: &DatoRedis) -> Vec<Token> {
+    let mut propagado = tokens.to_vec();
+    if let DatoRedis::BulkString(id) = respuesta
+        && let Ok((_, _, posicion_id)) = parsear_opciones_xadd(tokens)
+    {
+        propagado[posicion_id] = Token::from(id.bytes());
+    }
+    propagado
+}
+
+/// Obtiene las posiciones de los campos y valores de un XADD, que son los
+/// datos que se cifran al guardarlo en el AOF
+pub(crate) fn indices_campos_xadd(tokens: &[Token]) -> Vec<usize> {
+    match parsear_opciones_xadd(tokens) {
+        Ok((_, _, posicion_id)) => (posicion_id + 1..tokens.len()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Devuelve la cantidad de entradas de un stream
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y el stream
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - cantidad de entradas, 0 si no existe, error simple de redis si la
+///   clave no es un stream
+pub fn xlen(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    let largo = con_stream(&guard, &tokens[1], |stream| stream.len())?.unwrap_or(0);
+    Ok(DatoRedis::new_integer(largo as i64))
+}
+
+/// Devuelve las entradas de un stream entre dos ids, en orden ascendente
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, el id
+///   inicial, el id final y opcionalmente COUNT count
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - arreglo de entradas, error simple de redis en otros casos
+pub fn xrange(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    rango_entradas(tokens, storage, false)
+}
+
+/// Devuelve las entradas de un stream entre dos ids, en orden descendente
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, el id
+///   final, el id inicial y opcionalmente COUNT count
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - arreglo de entradas, error simple de redis en otros casos
+pub fn xrevrange(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    rango_entradas(tokens, storage, true)
+}
+
+/// Elimina las entradas mas antiguas de un stream
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream y el criterio
+///   de recorte: MAXLEN o MINID, opcionalmente `=` o `~`, el umbral y
+///   opcionalmente LIMIT count
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - cantidad de entradas eliminadas, error simple de redis en otros casos
+pub fn xtrim(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let mut posicion = 2;
+    let recorte = parsear_recorte(tokens, &mut posicion)?.ok_or_else(error_sintaxis)?;
+    if posicion != tokens.len() {
+        return Err(error_sintaxis());
+    }
+
+    let mut guard = get_storage_write_lock(storage)?;
+    let mut variacion = VariacionMemoria::default();
+    let eliminadas = con_stream_mutable(&mut guard, &tokens[1], |stream| {
+        recorte.aplicar(stream, &mut variacion)
+    })?;
+    guard.registrar_variacion(&tokens[1], variacion);
+    Ok(DatoRedis::new_integer(eliminadas.unwrap_or(0) as i64))
+}
+
+/// Elimina entradas de un stream
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream y los ids
+///   de las entradas a eliminar
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - cantidad de entradas eliminadas, error simple de redis en otros casos
+pub fn xdel(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let ids = tokens[2..]
+        .iter()
+        .map(|id| id.parse::<StreamId>())
+        .collect::<Result<Vec<StreamId>, DatoRedis>>()?;
+
+    let mut guard = get_storage_write_lock(storage)?;
+    let eliminadas = con_stream_mutable(&mut guard, &tokens[1], |stream| {
+        ids.iter().filter(|id| stream.eliminar(id)).count()
+    })?;
+    Ok(DatoRedis::new_integer(eliminadas.unwrap_or(0) as i64))
+}
+
+/// Streams a esperar, timeout y comando a reintentar de un XREAD con BLOCK
+pub(crate) type EsperaXread = (Vec<Token>, Option<Duration>, Vec<Token>);
+
+/// Parametros de XREAD
+#[derive(Debug, PartialEq)]
+struct ParametrosXread {
+    count: Option<usize>,
+    /// Milisegundos de espera, None si no se recibio BLOCK
+    block: Option<u64>,
+    claves: Vec<Token>,
+    ids: Vec<Token>,
+}
+
+/// Lee las entradas de uno o mas streams con id mayor a los indicados. La
+/// espera de BLOCK la realiza el nodo, reintentando este comando
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, opcionalmente
+///   COUNT count y BLOCK milisegundos, STREAMS, los streams y un id por
+///   stream (`$` para el ultimo id del stream)
+/// * `storage`: storage del nodo donde se encuentran los streams
+///
+/// # Retorna
+/// - arreglo con un par (stream, entradas) por cada stream con entradas
+///   nuevas, null si no hay ninguna, error simple de redis en otros casos
+pub fn xread(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    let parametros = parsear_xread(tokens)?;
+    let guard = get_storage_read_lock(storage)?;
+    for clave in parametros.claves.iter().skip(1) {
+        verificar_mismo_slot(&guard, &parametros.claves[0], clave)?;
+    }
+
+    let mut respuesta = Arrays::new();
+    for (clave, id) in parametros.claves.iter().zip(&parametros.ids) {
+        let entradas = con_stream(&guard, clave, |stream| -> Result<Arrays, DatoRedis> {
+            if id == ID_ULTIMO {
+                return Ok(Arrays::new());
+            }
+            let Some(desde) = id.parse::<StreamId>()?.siguiente() else {
+                return Ok(Arrays::new());
+            };
+            Ok(Arrays::new_con_contenido(
+                stream
+                    .rango(desde, StreamId::MAX)
+                    .take(parametros.count.unwrap_or(usize::MAX))
+                    .map(|(id, campos)| entrada_a_dato(id, campos))
+                    .collect(),
+            ))
+        })?
+        .transpose()?;
+
+        if let Some(entradas) = entradas.filter(|e| !e.is_empty()) {
+            let mut par = Arrays::new();
+            par.append(DatoRedis::new_bulk_string_desde_bytes(clave.to_vec())?);
+            par.append(DatoRedis::new_array_con_contenido(entradas));
+            respuesta.append(DatoRedis::new_array_con_contenido(par));
+        }
+    }
+
+    if respuesta.is_empty() {
+        return Ok(DatoRedis::new_null());
+    }
+    Ok(DatoRedis::new_array_con_contenido(respuesta))
+}
+
+/// Indica si un XREAD debe bloquear al cliente, es decir, si recibio BLOCK
+pub(crate) fn xread_bloqueante(tokens: &[Token]) -> bool {
+    parsear_xread(tokens).is_ok_and(|parametros| parametros.block.is_some())
+}
+
+/// Prepara la espera de un XREAD con BLOCK, reemplazando los ids `$` por el
+/// ultimo id de cada stream al momento de bloquearse
+///
+/// # Parámetros
+/// * `tokens`: comando XREAD recibido
+/// * `storage`: storage del nodo donde se encuentran los streams
+///
+/// # Retorna
+/// - los streams a esperar, el timeout (None si es 0, es decir, sin
+///   limite) y el XREAD sin BLOCK a reintentar, error de redis si los
+///   argumentos son invalidos
+pub(crate) fn preparar_xread_bloqueante(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+) -> Result<EsperaXread, DatoRedis> {
+    let parametros = parsear_xread(tokens)?;
+    let guard = get_storage_read_lock(storage)?;
+
+    let mut intento = vec![Token::from(CMD_XREAD)];
+    if let Some(count) = parametros.count {
+        intento.extend([Token::from(OPCION_COUNT), Token::from(count.to_string())]);
+    }
+    intento.push(Token::from(OPCION_STREAMS));
+    intento.extend(parametros.claves.iter().cloned());
+    for (clave, id) in parametros.claves.iter().zip(&parametros.ids) {
+        let id = if id == ID_ULTIMO {
+            let ultimo = con_stream(&guard, clave, |stream| stream.ultimo_id())?;
+            Token::from(ultimo.unwrap_or(StreamId::MIN).to_string())
+        } else {
+            id.clone()
+        };
+        intento.push(id);
+    }
+
+    let timeout = parametros
+        .block
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis);
+    Ok((parametros.claves, timeout, intento))
+}
+
+/// Interpreta los argumentos de XREAD
+fn parsear_xread(tokens: &[Token]) -> Result<ParametrosXread, DatoRedis> {
+    let mut parametros = ParametrosXread {
+        count: None,
+        block: None,
+        claves: Vec::new(),
+        ids: Vec::new(),
+    };
+    let mut posicion = 1;
+    loop {
+        let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) else {
+            return Err(error_sintaxis());
+        };
+        match opcion.as_str() {
+            OPCION_STREAMS => break,
+            OPCION_COUNT => {
+                parametros.count = Some(parsear_entero(tokens.get(posicion + 1))? as usize)
+            }
+            OPCION_BLOCK => parametros.block = Some(parsear_entero(tokens.get(posicion + 1))?),
+            _ => return Err(error_sintaxis()),
+        }
+        posicion += 2;
+    }
+
+    let streams = &tokens[posicion + 1..];
+    if streams.is_empty() || !streams.len().is_multiple_of(2) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified."
+                .to_string(),
+        ));
+    }
+    let (claves, ids) = streams.split_at(streams.len() / 2);
+    for id in ids.iter().filter(|id| *id != ID_ULTIMO) {
+        id.parse::<StreamId>()?;
+    }
+    parametros.claves = claves.to_vec();
+    parametros.ids = ids.to_vec();
+    Ok(parametros)
+}
+
+/// Interpreta las opciones de XADD
+///
+/// # Retorna
+/// - si se recibio NOMKSTREAM, el recorte a aplicar y la posicion del id de
+///   la entrada en el comando, error de redis si las opciones son invalidas
+fn parsear_opciones_xadd(tokens: &[Token]) -> Result<(bool, Option<Recorte>, usize), DatoRedis> {
+    let mut nomkstream = false;
+    let mut recorte = None;
+    let mut posicion = 2;
+    while let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) {
+        if opcion == OPCION_NOMKSTREAM {
+            nomkstream = true;
+            posicion += 1;
+        } else if let Some(nuevo) = parsear_recorte(tokens, &mut posicion)? {
+            recorte = Some(nuevo);
+        } else {
+            break;
+        }
+    }
+    if posicion >= tokens.len() {
+        return Err(error_sintaxis());
+    }
+    Ok((nomkstream, recorte, posicion))
+}
+
+/// Interpreta un criterio de recorte (MAXLEN o MINID) a partir de
+/// `posicion`, avanzandola hasta el siguiente argumento
+///
+/// # Retorna
+/// - el recorte, None si en `posicion` no comienza un criterio de recorte,
+///   error de redis si el criterio es invalido
+fn parsear_recorte(tokens: &[Token], posicion: &mut usize) -> Result<Option<Recorte>, DatoRedis> {
+    let Some(opcion) = tokens.get(*posicion).map(|t| t.to_uppercase()) else {
+        return Ok(None);
+    };
+    if opcion != OPCION_MAXLEN && opcion != OPCION_MINID {
+        return Ok(None);
+    }
+    let mut actual = *posicion + 1;
+    // El recorte aproximado (~) se realiza de forma exacta
+    if tokens.get(actual).is_some_and(|t| t == "=" || t == "~") {
+        actual += 1;
+    }
+    let umbral = tokens.get(actual).ok_or_else(error_sintaxis)?;
+    let recorte = if opcion == OPCION_MAXLEN {
+        Recorte::MaxLen(parsear_entero(Some(umbral))? as usize)
+    } else {
+        Recorte::MinId(umbral.parse::<StreamId>()?)
+    };
+    actual += 1;
+    if tokens
+        .get(actual)
+        .is_some_and(|t| t.to_uppercase() == OPCION_LIMIT)
+    {
+        parsear_entero(tokens.get(actual + 1))?;
+        actual += 2;
+    }
+    *posicion = actual;
+    Ok(Some(recorte))
+}
+
+/// Agrega una entrada a un stream y aplica el recorte indicado
+///
+/// # Retorna
+/// - el id de la entrada, error de redis si el id no es valido
+fn agregar_entrada(
+    stream: &mut Stream,
+    id_pedido: &str,
+    campos: CamposStream,
+    recorte: Option<Recorte>,
+    variacion: &mut VariacionMemoria,
+) -> Result<StreamId, DatoRedis> {
+    let id = generar_id(stream, id_pedido)?;
+    let mut agregada = *variacion;
+    agregada.agregar_entrada(&campos);
+    stream.agregar(id, campos)?;
+    *variacion = agregada;
+    if let Some(recorte) = recorte {
+        recorte.aplicar(stream, variacion);
+    }
+    Ok(id)
+}
+
+/// Obtiene el id de una nueva entrada: `*` lo genera a partir del instante
+/// actual, `ms-*` genera solo la secuencia y `ms-seq` es un id explicito
+fn generar_id(stream: &Stream, id_pedido: &str) -> Result<StreamId, DatoRedis> {
+    let ultimo = stream.ultimo_id();
+    let generado = if id_pedido == ID_AUTOMATICO {
+        // Si el reloj retrocedio se sigue usando el instante del ultimo id
+        stream.generar_id(ahora_ms().max(ultimo.ms), None)
+    } else if let Some(ms) = id_pedido.strip_suffix("-*") {
+        let ms = ms.parse::<u64>().map_err(|_| error_id_invalido())?;
+        stream.generar_id(ms, None)
+    } else {
+        Some(id_pedido.parse::<StreamId>()?)
+    };
+    generado.ok_or_else(|| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "The ID specified in XADD is equal or smaller than the target stream top item"
+                .to_string(),
+        )
+    })
+}
+
+/// Interpreta los pares campo-valor de una entrada, conservando sus bytes
+fn parsear_campos(comando: &str, tokens: &[Token]) -> Result<CamposStream, DatoRedis> {
+    if tokens.is_empty() || !tokens.len().is_multiple_of(2) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!(
+                "wrong number of arguments for '{}' command",
+                comando.to_lowercase()
+            ),
+        ));
+    }
+    Ok(tokens
+        .chunks(2)
+        .map(|par| (par[0].to_vec(), par[1].to_vec()))
+        .collect())
+}
+
+/// Ejecuta XRANGE o XREVRANGE
+///
+/// # Parámetros
+/// * `tokens`: comando recibido
+/// * `storage`: storage del nodo donde se encuentra el stream
+/// * `inverso`: verdadero para XREVRANGE, cuyo primer limite es el final
+fn rango_entradas(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+    inverso: bool,
+) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let (inicio, fin) = if inverso {
+        (&tokens[3], &tokens[2])
+    } else {
+        (&tokens[2], &tokens[3])
+    };
+    let desde = parsear_limite(inicio, true)?;
+    let hasta = parsear_limite(fin, false)?;
+    let count = match &tokens[4..] {
+        [] => usize::MAX,
+        [opcion, valor] if opcion.to_uppercase() == OPCION_COUNT => {
+            parsear_entero(Some(valor))? as usize
+        }
+        _ => return Err(error_sintaxis()),
+    };
+
+    let guard = get_storage_read_lock(storage)?;
+    let entradas = con_stream(&guard, &tokens[1], |stream| {
+        let (Some(desde), Some(hasta)) = (desde, hasta) else {
+            return Vec::new();
+        };
+        let rango = stream.rango(desde, hasta);
+        let entradas: Box<dyn Iterator<Item = _>> = if inverso {
+            Box::new(rango.rev())
+        } else {
+            Box::new(rango)
+        };
+        entradas
+            .take(count)
+            .map(|(id, campos)| entrada_a_dato(id, campos))
+            .collect()
+    })?
+    .unwrap_or_default();
+    Ok(DatoRedis::new_array_con_contenido(
+        Arrays::new_con_contenido(entradas),
+    ))
+}
+
+/// Interpreta un limite de XRANGE: `-`, `+`, un id completo, un id sin
+/// secuencia o un id exclusivo precedido por `(`
+///
+/// # Parámetros
+/// * `limite`: limite recibido
+/// * `es_inicio`: si es el limite inferior del rango
+///
+/// # Retorna
+/// - el id inclusivo correspondiente, None si el rango resulta vacio,
+///   error de redis si el id es invalido
+pub(crate) fn parsear_limite(
+    limite: &Token,
+    es_inicio: bool,
+) -> Result<Option<StreamId>, DatoRedis> {
+    let limite = limite.texto();
+    let limite = limite.as_ref();
+    match limite {
+        ID_MINIMO => return Ok(Some(StreamId::MIN)),
+        ID_MAXIMO => return Ok(Some(StreamId::MAX)),
+        _ => {}
+    }
+    let (exclusivo, id) = match limite.strip_prefix(PREFIJO_EXCLUSIVO) {
+        Some(id) => (true, id),
+        None => (false, limite),
+    };
+    let mut valor = id.parse::<StreamId>()?;
+    if !es_inicio && !id.contains('-') {
+        valor.seq = u64::MAX;
+    }
+    if !exclusivo {
+        return Ok(Some(valor));
+    }
+    Ok(if es_inicio {
+        valor.siguiente()
+    } else {
+        valor.anterior()
+    })
+}
+
+/// Aplica una funcion al stream de una clave
+///
+/// # Retorna
+/// - el resultado de la funcion, None si la clave no existe, error de
+///   redis si la clave no es un stream o no pertenece al nodo
+pub(crate) fn con_stream<T>(
+    storage: &Storage,
+    clave: &[u8],
+    funcion: impl FnOnce(&Stream) -> T,
+) -> Result<Option<T>, DatoRedis> {
+    match storage.get(clave) {
+        Ok(DatoRedis::Stream(stream)) => Ok(Some(funcion(&stream))),
+        Ok(_) => Err(error_tipo()),
+        Err(e @ DatoRedis::MovedError(_)) => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Aplica una funcion que modifica el stream de una clave
+///
+/// # Retorna
+/// - el resultado de la funcion, None si la clave no existe, error de
+///   redis si la clave no es un stream o no pertenece al nodo
+pub(crate) fn con_stream_mutable<T>(
+    storage: &mut Storage,
+    clave: &[u8],
+    funcion: impl FnOnce(&mut Stream) -> T,
+) -> Result<Option<T>, DatoRedis> {
+    match storage.get_mutable(clave) {
+        Ok(DatoRedis::Stream(stream)) => Ok(Some(funcion(stream))),
+        Ok(_) => Err(error_tipo()),
+        Err(e @ DatoRedis::MovedError(_)) => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+pub(crate) fn parsear_entero(token: Option<&Token>) -> Result<u64, DatoRedis> {
+    let token = token.ok_or_else(error_sintaxis)?;
+    token.parse::<u64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not an integer or out of range".to_string(),
+        )
+    })
+}
+
+pub(crate) fn error_tipo() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "WRONGTYPE".to_string(),
+        "Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_id_invalido() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        "Invalid stream ID specified as stream command argument".to_string(),
+    )
+}
+
+pub(crate) fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memoria::estimar_memoria;
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn storage_con_stream(ids: &[&str]) -> Arc<RwLock<Storage>> {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        for id in ids {
+            xadd(&tokens(&["XADD", "s", id, "campo", id]), &storage).unwrap();
+        }
+        storage
+    }
+
+    /// Obtiene los ids de un arreglo de entradas
+    fn ids(respuesta: DatoRedis) -> Vec<String> {
+        let DatoRedis::Arrays(entradas) = respuesta else {
+            panic!("se esperaba un arreglo: {respuesta:?}");
+        };
+        entradas
+            .iter()
+            .map(|entrada| match entrada {
+                DatoRedis::Arrays(entrada) => match entrada.get(0) {
+                    Some(DatoRedis::BulkString(id)) => id.contenido(),
+                    _ => panic!("entrada sin id"),
+                },
+                _ => panic!("entrada invalida"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_01_xadd_genera_ids_crecientes() {
+        let storage = storage_con_stream(&["5-1"]);
+        let id = xadd(&tokens(&["XADD", "s", "5-*", "a", "1"]), &storage).unwrap();
+        assert_eq!(id, DatoRedis::new_bulk_string("5-2".to_string()).unwrap());
+
+        let DatoRedis::BulkString(auto) =
+            xadd(&tokens(&["XADD", "s", "*", "a", "1"]), &storage).unwrap()
+        else {
+            panic!("se esperaba un id");
+        };
+        assert!(auto.contenido().parse::<StreamId>().unwrap() > StreamId::new(5, 2));
+
+        assert!(xadd(&tokens(&["XADD", "s", "5-0", "a", "1"]), &storage).is_err());
+        assert!(xadd(&tokens(&["XADD", "s", "*", "a"]), &storage).is_err());
+        assert_eq!(
+            xlen(&tokens(&["XLEN", "s"]), &storage).unwrap(),
+            DatoRedis::new_integer(3)
+        );
+    }
+
+    #[test]
+    fn test_02_xadd_nomkstream_y_maxlen() {
+        let storage = storage_con_stream(&[]);
+        assert_eq!(
+            xadd(
+                &tokens(&["XADD", "s", "NOMKSTREAM", "*", "a", "1"]),
+                &storage
+            )
+            .unwrap(),
+            DatoRedis::new_null()
+        );
+        assert_eq!(
+            xlen(&tokens(&["XLEN", "s"]), &storage).unwrap(),
+            DatoRedis::new_integer(0)
+        );
+
+        for id in ["1", "2", "3"] {
+            xadd(
+                &tokens(&["XADD", "s", "MAXLEN", "~", "2", id, "a", "1"]),
+                &storage,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            ids(xrange(&tokens(&["XRANGE", "s", "-", "+"]), &storage).unwrap()),
+            ["2-0", "3-0"]
+        );
+    }
+
+    #[test]
+    fn test_03_xrange_y_xrevrange() {
+        let storage = storage_con_stream(&["1-0", "1-1", "2-0", "3-0"]);
+        assert_eq!(
+            ids(xrange(&tokens(&["XRANGE", "s", "1", "2"]), &storage).unwrap()),
+            ["1-0", "1-1", "2-0"]
+        );
+        assert_eq!(
+            ids(xrange(
+                &tokens(&["XRANGE", "s", "(1-0", "+", "COUNT", "2"]),
+                &storage
+            )
+            .unwrap()),
+            ["1-1", "2-0"]
+        );
+        assert_eq!(
+            ids(xrevrange(&tokens(&["XREVRANGE", "s", "+", "(2-0"]), &storage).unwrap()),
+            ["3-0"]
+        );
+        assert_eq!(
+            ids(xrevrange(
+                &tokens(&["XREVRANGE", "s", "+", "-", "COUNT", "2"]),
+                &storage
+            )
+            .unwrap()),
+            ["3-0", "2-0"]
+        );
+        assert!(ids(xrange(&tokens(&["XRANGE", "otro", "-", "+"]), &storage).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_04_xtrim_y_xdel() {
+        let storage = storage_con_stream(&["1", "2", "3", "4"]);
+        assert_eq!(
+            xtrim(&tokens(&["XTRIM", "s", "MINID", "2"]), &storage).unwrap(),
+            DatoRedis::new_integer(1)
+        );
+        assert_eq!(
+            xtrim(&tokens(&["XTRIM", "s", "MAXLEN", "=", "2"]), &storage).unwrap(),
+            DatoRedis::new_integer(1)
+        );
+        assert_eq!(
+            xdel(&tokens(&["XDEL", "s", "3-0", "9-0"]), &storage).unwrap(),
+            DatoRedis::new_integer(1)
+        );
+        assert!(xtrim(&tokens(&["XTRIM", "s", "LEN", "2"]), &storage).is_err());
+        // Los ids nuevos siguen siendo mayores a los eliminados
+        assert!(xadd(&tokens(&["XADD", "s", "3-0", "a", "1"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_05_xread_devuelve_entradas_nuevas() {
+        let storage = storage_con_stream(&["1", "2"]);
+        let respuesta = xread(&tokens(&["XREAD", "STREAMS", "s", "1-0"]), &storage).unwrap();
+        let DatoRedis::Arrays(streams) = respuesta else {
+            panic!("se esperaba un arreglo");
+        };
+        let Some(DatoRedis::Arrays(par)) = streams.get(0) else {
+            panic!("se esperaba un par");
+        };
+        assert_eq!(
+            par.get(0),
+            Some(DatoRedis::new_bulk_string("s".to_string()).unwrap())
+        );
+        assert_eq!(ids(par.get(1).unwrap()), ["2-0"]);
+
+        assert_eq!(
+            xread(&tokens(&["XREAD", "STREAMS", "s", "$"]), &storage).unwrap(),
+            DatoRedis::new_null()
+        );
+        assert!(xread(&tokens(&["XREAD", "STREAMS", "s", "t", "0"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_06_xread_bloqueante_resuelve_el_ultimo_id() {
+        let storage = storage_con_stream(&["7-3"]);
+        let xread = tokens(&["XREAD", "COUNT", "1", "BLOCK", "0", "STREAMS", "s", "$"]);
+        assert!(xread_bloqueante(&xread));
+        assert!(!xread_bloqueante(&tokens(&["XREAD", "STREAMS", "s", "$"])));
+
+        let (claves, timeout, intento) = preparar_xread_bloqueante(&xread, &storage).unwrap();
+        assert_eq!(claves, tokens(&["s"]));
+        assert_eq!(timeout, None);
+        assert_eq!(
+            intento,
+            tokens(&["XREAD", "COUNT", "1", "STREAMS", "s", "7-3"])
+        );
+    }
+
+    #[test]
+    fn test_07_xadd_propaga_el_id_generado() {
+        let comando = tokens(&["XADD", "s", "MAXLEN", "10", "*", "a", "1"]);
+        let respuesta = DatoRedis::new_bulk_string("9-0".to_string()).unwrap();
+        assert_eq!(
+            xadd_con_id(&comando, &respuesta),
+            tokens(&["XADD", "s", "MAXLEN", "10", "9-0", "a", "1"])
+        );
+        assert_eq!(indices_campos_xadd(&comando), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_08_wrongtype() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        storage
+            .write()
+            .unwrap()
+            .set(
+                "s",
+                DatoRedis::new_bulk_string("texto".to_string()).unwrap(),
+            )
+            .unwrap();
+        assert!(xadd(&tokens(&["XADD", "s", "*", "a", "1"]), &storage).is_err());
+        assert!(xlen(&tokens(&["XLEN", "s"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_09_xadd_y_xtrim_actualizan_la_memoria() {
+        let storage = storage_con_stream(&["1-1", "1-2", "1-3"]);
+        xadd(
+            &tokens(&["XADD", "s", "MAXLEN", "2", "2-1", "campo", "valor largo"]),
+            &storage,
+        )
+        .unwrap();
+        xtrim(&tokens(&["XTRIM", "s", "MINID", "2-0"]), &storage).unwrap();
+
+        let guard = storage.read().unwrap();
+        let stream = guard.get("s").unwrap();
+        assert_eq!(
+            guard.memoria_contabilizada(),
+            estimar_memoria(b"s", &stream)
+        );
+    }
+}
@@ -0,0 +1,1143 @@
+//! Este modulo contiene la implementacion de los comandos de redis para
+//! grupos de consumidores de streams: XGROUP, XREADGROUP, XACK, XPENDING,
+//! XCLAIM y XINFO
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::stream::{
+    CamposStream, GrupoConsumidores, Pendiente, Stream, StreamId, entrada_a_dato,
+};
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::comandos_stream::{
+    EsperaXread, ID_ULTIMO, OPCION_BLOCK, OPCION_COUNT, OPCION_STREAMS, con_stream,
+    con_stream_mutable, error_sintaxis, parsear_entero, parsear_limite,
+};
+use crate::comandos::const_cmd::CMD_XREADGROUP;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct, get_storage_read_lock,
+    get_storage_write_lock,
+};
+use crate::memoria::ahora_ms;
+use crate::storage::Storage;
+
+const SUBCOMANDO_CREATE: &str = "CREATE";
+const SUBCOMANDO_SETID: &str = "SETID";
+const SUBCOMANDO_DESTROY: &str = "DESTROY";
+const SUBCOMANDO_CREATECONSUMER: &str = "CREATECONSUMER";
+const SUBCOMANDO_DELCONSUMER: &str = "DELCONSUMER";
+const SUBCOMANDO_STREAM: &str = "STREAM";
+const SUBCOMANDO_GROUPS: &str = "GROUPS";
+const SUBCOMANDO_CONSUMERS: &str = "CONSUMERS";
+const OPCION_MKSTREAM: &str = "MKSTREAM";
+const OPCION_ENTRIESREAD: &str = "ENTRIESREAD";
+const OPCION_GROUP: &str = "GROUP";
+const OPCION_NOACK: &str = "NOACK";
+const OPCION_IDLE: &str = "IDLE";
+const OPCION_TIME: &str = "TIME";
+const OPCION_RETRYCOUNT: &str = "RETRYCOUNT";
+const OPCION_FORCE: &str = "FORCE";
+const OPCION_JUSTID: &str = "JUSTID";
+const OPCION_LASTID: &str = "LASTID";
+
+/// Id de XREADGROUP que pide entradas nunca entregadas al grupo
+const ID_NUEVAS: &str = ">";
+
+/// Determina si un comando XGROUP agrega datos al stream (CREATE y
+/// CREATECONSUMER), a diferencia de los que solo eliminan o actualizan
+pub fn xgroup_agrega_datos(tokens: &[Token]) -> bool {
+    tokens.get(1).is_some_and(|subcomando| {
+        subcomando.eq_ignore_ascii_case(SUBCOMANDO_CREATE)
+            || subcomando.eq_ignore_ascii_case(SUBCOMANDO_CREATECONSUMER)
+    })
+}
+
+/// Administra los grupos de consumidores de un stream
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el subcomando
+///   (CREATE, SETID, DESTROY, CREATECONSUMER o DELCONSUMER), el stream, el
+///   grupo y los argumentos del subcomando
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - OK al crear el grupo o cambiar su ultimo id entregado, la cantidad de
+///   grupos o consumidores creados o eliminados en DESTROY y
+///   CREATECONSUMER, la cantidad de entradas pendientes del consumidor
+///   eliminado en DELCONSUMER, error simple de redis en otros casos
+pub fn xgroup(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let subcomando = tokens[1].to_uppercase();
+    let (clave, grupo) = (&tokens[2], &*tokens[3].texto());
+    let mut guard = get_storage_write_lock(storage)?;
+
+    match subcomando.as_str() {
+        SUBCOMANDO_CREATE => {
+            let (id, mkstream) = parsear_opciones_xgroup(tokens, true)?;
+            if mkstream && con_stream(&guard, clave, |_| ())?.is_none() {
+                guard.set(clave, DatoRedis::Stream(Stream::new()))?;
+            }
+            let creado = con_stream_mutable(&mut guard, clave, |stream| {
+                let id = id.unwrap_or(stream.ultimo_id());
+                stream.crear_grupo(grupo, id)
+            })?
+            .ok_or_else(error_requiere_clave)?;
+            if !creado {
+                return Err(DatoRedis::new_simple_error(
+                    "BUSYGROUP".to_string(),
+                    "Consumer Group name already exists".to_string(),
+                ));
+            }
+            DatoRedis::new_simple_string("OK".to_string())
+        }
+        SUBCOMANDO_SETID => {
+            let (id, _) = parsear_opciones_xgroup(tokens, false)?;
+            con_grupo_mutable(&mut guard, clave, grupo, |stream, nombre| {
+                let id = id.unwrap_or(stream.ultimo_id());
+                if let Some(grupo) = stream.grupo_mutable(nombre) {
+                    grupo.set_ultimo_entregado(id);
+                }
+            })?;
+            DatoRedis::new_simple_string("OK".to_string())
+        }
+        SUBCOMANDO_DESTROY => {
+            assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 4, tokens.len())?;
+            let eliminado =
+                con_stream_mutable(&mut guard, clave, |stream| stream.eliminar_grupo(grupo))?
+                    .ok_or_else(error_requiere_clave)?;
+            Ok(DatoRedis::new_integer(eliminado as i64))
+        }
+        SUBCOMANDO_CREATECONSUMER | SUBCOMANDO_DELCONSUMER => {
+            assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 5, tokens.len())?;
+            let consumidor = &*tokens[4].texto();
+            let resultado = con_grupo_mutable(&mut guard, clave, grupo, |stream, nombre| {
+                let Some(grupo) = stream.grupo_mutable(nombre) else {
+                    return 0;
+                };
+                if subcomando == SUBCOMANDO_DELCONSUMER {
+                    grupo.eliminar_consumidor(consumidor)
+                } else if grupo.consumidores().contains_key(consumidor) {
+                    0
+                } else {
+                    grupo.registrar_consumidor(consumidor, ahora_ms()) as usize
+                }
+            })?;
+            Ok(DatoRedis::new_integer(resultado as i64))
+        }
+        _ => Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!(
+                "unknown subcommand '{}'. Try XGROUP HELP.",
+                tokens[1].to_lowercase()
+            ),
+        )),
+    }
+}
+
+/// Parametros de XREADGROUP
+#[derive(Debug, PartialEq)]
+struct ParametrosXreadgroup {
+    grupo: String,
+    consumidor: String,
+    count: Option<usize>,
+    /// Milisegundos de espera, None si no se recibio BLOCK
+    block: Option<u64>,
+    noack: bool,
+    claves: Vec<Token>,
+    ids: Vec<Token>,
+}
+
+/// Lee entradas de uno o mas streams como consumidor de un grupo. Con el id
+/// `>` entrega al consumidor las entradas que el grupo aun no entrego, que
+/// quedan pendientes hasta ser confirmadas con XACK. Con otro id devuelve
+/// las entradas pendientes del consumidor posteriores a ese id. La espera
+/// de BLOCK la realiza el nodo, reintentando este comando
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, GROUP, el grupo, el
+///   consumidor, opcionalmente COUNT count, BLOCK milisegundos y NOACK,
+///   STREAMS, los streams y un id por stream
+/// * `storage`: storage del nodo donde se encuentran los streams
+///
+/// # Retorna
+/// - arreglo con un par (stream, entradas) por cada stream leido, null si
+///   no hay entradas nuevas, error simple de redis en otros casos
+pub fn xreadgroup(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+) -> Result<DatoRedis, DatoRedis> {
+    let parametros = parsear_xreadgroup(tokens)?;
+    let mut guard = get_storage_write_lock(storage)?;
+    for clave in parametros.claves.iter().skip(1) {
+        verificar_mismo_slot(&guard, &parametros.claves[0], clave)?;
+    }
+
+    let ahora = ahora_ms();
+    let mut respuesta = Arrays::new();
+    for (clave, id) in parametros.claves.iter().zip(&parametros.ids) {
+        let entradas = con_stream_mutable(&mut guard, clave, |stream| {
+            stream
+                .grupo(&parametros.grupo)
+                .is_some()
+                .then(|| leer_para_consumidor(stream, &parametros, id, ahora))
+        })?
+        .flatten()
+        .ok_or_else(|| {
+            DatoRedis::new_simple_error(
+                "NOGROUP".to_string(),
+                format!(
+                    "No such key '{}' or consumer group '{}' in XREADGROUP with GROUP option",
+                    String::from_utf8_lossy(clave),
+                    parametros.grupo
+                ),
+            )
+        })??;
+
+        // El historial de pendientes se informa aunque este vacio
+        if !entradas.is_empty() || id != ID_NUEVAS {
+            let mut par = Arrays::new();
+            par.append(DatoRedis::new_bulk_string_desde_bytes(clave.to_vec())?);
+            par.append(DatoRedis::new_array_con_contenido(
+                Arrays::new_con_contenido(entradas),
+            ));
+            respuesta.append(DatoRedis::new_array_con_contenido(par));
+        }
+    }
+
+    if respuesta.is_empty() {
+        return Ok(DatoRedis::new_null());
+    }
+    Ok(DatoRedis::new_array_con_contenido(respuesta))
+}
+
+/// Indica si un XREADGROUP debe bloquear al cliente: si recibio BLOCK y
+/// solo pide entradas nuevas
+pub(crate) fn xreadgroup_bloqueante(tokens: &[Token]) -> bool {
+    parsear_xreadgroup(tokens).is_ok_and(|parametros| {
+        parametros.block.is_some() && parametros.ids.iter().all(|id| id == ID_NUEVAS)
+    })
+}
+
+/// Prepara la espera de un XREADGROUP con BLOCK
+///
+/// # Retorna
+/// - los streams a esperar, el timeout (None si es 0, es decir, sin
+///   limite) y el XREADGROUP sin BLOCK a reintentar, error de redis si los
+///   argumentos son invalidos
+pub(crate) fn preparar_xreadgroup_bloqueante(tokens: &[Token]) -> Result<EsperaXread, DatoRedis> {
+    let parametros = parsear_xreadgroup(tokens)?;
+    let mut intento = vec![
+        Token::from(CMD_XREADGROUP),
+        Token::from(OPCION_GROUP),
+        Token::from(parametros.grupo.as_str()),
+        Token::from(parametros.consumidor.as_str()),
+    ];
+    if let Some(count) = parametros.count {
+        intento.extend([Token::from(OPCION_COUNT), Token::from(count.to_string())]);
+    }
+    if parametros.noack {
+        intento.push(Token::from(OPCION_NOACK));
+    }
+    intento.push(Token::from(OPCION_STREAMS));
+    intento.extend(parametros.claves.iter().cloned());
+    intento.extend(parametros.ids.iter().cloned());
+
+    let timeout = parametros
+        .block
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis);
+    Ok((parametros.claves, timeout, intento))
+}
+
+/// Confirma el procesamiento de entradas pendientes de un grupo
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, el grupo y
+///   los ids de las entradas a confirmar
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - cantidad de entradas confirmadas, error simple de redis en otros casos
+pub fn xack(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let ids = parsear_ids(&tokens[3..])?;
+    let mut guard = get_storage_write_lock(storage)?;
+    let confirmadas = con_stream_mutable(&mut guard, &tokens[1], |stream| {
+        stream.grupo_mutable(&tokens[2].texto()).map_or(0, |grupo| {
+            ids.iter().filter(|id| grupo.confirmar(id)).count()
+        })
+    })?;
+    Ok(DatoRedis::new_integer(confirmadas.unwrap_or(0) as i64))
+}
+
+/// Informa las entradas pendientes de confirmacion de un grupo. Sin rango
+/// devuelve un resumen, con rango devuelve el detalle de cada entrada
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, el grupo y
+///   opcionalmente IDLE min-idle-time, el id inicial, el id final, la
+///   cantidad de entradas y el consumidor
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - el resumen (cantidad, menor id, mayor id y pendientes por consumidor)
+///   o el arreglo de entradas (id, consumidor, milisegundos desde la ultima
+///   entrega y cantidad de entregas), error simple de redis en otros casos
+pub fn xpending(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let (clave, nombre) = (&tokens[1], &*tokens[2].texto());
+    let guard = get_storage_read_lock(storage)?;
+    con_stream(&guard, clave, |stream| {
+        stream
+            .grupo(nombre)
+            .map(|grupo| pendientes_de_grupo(grupo, &tokens[3..]))
+    })?
+    .flatten()
+    .ok_or_else(|| {
+        DatoRedis::new_simple_error(
+            "NOGROUP".to_string(),
+            format!(
+                "No such key '{}' or consumer group '{nombre}'",
+                String::from_utf8_lossy(clave)
+            ),
+        )
+    })?
+}
+
+/// Opciones de XCLAIM
+#[derive(Debug, Default, PartialEq)]
+struct OpcionesXclaim {
+    /// Instante de entrega a registrar, por defecto el actual
+    entregado_ms: Option<u64>,
+    entregas: Option<u64>,
+    force: bool,
+    justid: bool,
+    lastid: Option<StreamId>,
+}
+
+/// Transfiere a un consumidor entradas pendientes de otros consumidores que
+/// no fueron confirmadas en al menos `min-idle-time` milisegundos, por
+/// ejemplo porque su consumidor dejo de funcionar
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el stream, el grupo,
+///   el consumidor, el tiempo minimo sin confirmacion, los ids de las
+///   entradas y opcionalmente IDLE ms, TIME ms, RETRYCOUNT count, FORCE,
+///   JUSTID y LASTID id
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - arreglo de entradas transferidas, o de sus ids con JUSTID, error
+///   simple de redis en otros casos
+pub fn xclaim(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 6, tokens.len())?;
+    let (clave, nombre, consumidor) = (&tokens[1], &*tokens[2].texto(), &*tokens[3].texto());
+    let idle_minimo = parsear_entero(Some(&tokens[4]))?;
+    let (ids, opciones) = parsear_xclaim(&tokens[5..])?;
+
+    let ahora = ahora_ms();
+    let entregado_ms = opciones.entregado_ms.unwrap_or(ahora);
+    let mut guard = get_storage_write_lock(storage)?;
+    let reclamadas = con_grupo_mutable(&mut guard, clave, nombre, |stream, nombre| {
+        let mut reclamadas = Vec::new();
+        for id in &ids {
+            let campos = stream.entrada(id).cloned();
+            let Some(grupo) = stream.grupo_mutable(nombre) else {
+                break;
+            };
+            // Las entradas eliminadas del stream dejan de estar pendientes
+            let Some(campos) = campos else {
+                grupo.confirmar(id);
+                continue;
+            };
+            let actual = grupo.pendientes().get(id);
+            let entregas_previas = match actual.map(|p| (p.entregado_ms, p.entregas)) {
+                Some((entregado, _)) if ahora.saturating_sub(entregado) < idle_minimo => {
+                    continue;
+                }
+                Some((_, entregas)) => entregas,
+                None if opciones.force => {
+                    grupo.agregar_pendiente(
+                        *id,
+                        Pendiente {
+                            consumidor: consumidor.to_string(),
+                            entregado_ms,
+                            entregas: 0,
+                        },
+                    );
+                    0
+                }
+                None => continue,
+            };
+            // JUSTID no cuenta como una nueva entrega
+            let entregas = opciones.entregas.unwrap_or(if opciones.justid {
+                entregas_previas
+            } else {
+                entregas_previas + 1
+            });
+            grupo.reasignar(id, consumidor, entregado_ms, Some(entregas));
+            reclamadas.push((*id, campos));
+        }
+        if let Some(grupo) = stream.grupo_mutable(nombre) {
+            grupo.registrar_consumidor(consumidor, ahora);
+            if let Some(lastid) = opciones.lastid
+                && lastid > grupo.ultimo_entregado()
+            {
+                grupo.set_ultimo_entregado(lastid);
+            }
+        }
+        reclamadas
+    })?;
+
+    let mut respuesta = Arrays::new();
+    for (id, campos) in &reclamadas {
+        if opciones.justid {
+            respuesta.append(DatoRedis::new_bulk_string(id.to_string())?);
+        } else {
+            respuesta.append(entrada_a_dato(id, campos));
+        }
+    }
+    Ok(DatoRedis::new_array_con_contenido(respuesta))
+}
+
+/// Informa el estado de un stream, de sus grupos o de los consumidores de
+/// un grupo
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, el subcomando
+///   (STREAM, GROUPS o CONSUMERS), el stream y, para CONSUMERS, el grupo
+/// * `storage`: storage del nodo donde se encuentra el stream
+///
+/// # Retorna
+/// - arreglo de pares campo-valor con la informacion pedida, o un arreglo
+///   de ellos por cada grupo o consumidor, error simple de redis en otros
+///   casos
+pub fn xinfo(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    con_stream(&guard, &tokens[2], |stream| info_de_stream(stream, tokens))?
+        .ok_or_else(|| DatoRedis::new_simple_error("ERR".to_string(), "no such key".to_string()))?
+}
+
+/// Ejecuta XINFO sobre un stream existente
+fn info_de_stream(stream: &Stream, tokens: &[Token]) -> Result<DatoRedis, DatoRedis> {
+    let clave = &tokens[2];
+    match tokens[1].to_uppercase().as_str() {
+        SUBCOMANDO_STREAM => info_stream(stream),
+        SUBCOMANDO_GROUPS => {
+            let mut grupos = Arrays::new();
+            for (nombre, grupo) in stream.grupos() {
+                grupos.append(pares(vec![
+                    ("name", DatoRedis::new_bulk_string(nombre.to_string())?),
+                    (
+                        "consumers",
+                        DatoRedis::new_integer(grupo.consumidores().len() as i64),
+                    ),
+                    (
+                        "pending",
+                        DatoRedis::new_integer(grupo.pendientes().len() as i64),
+                    ),
+                    (
+                        "last-delivered-id",
+                        DatoRedis::new_bulk_string(grupo.ultimo_entregado().to_string())?,
+                    ),
+                ])?);
+            }
+            Ok(DatoRedis::new_array_con_contenido(grupos))
+        }
+        SUBCOMANDO_CONSUMERS => {
+            assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 4, tokens.len())?;
+            let grupo = stream
+                .grupo(&tokens[3].texto())
+                .ok_or_else(|| error_nogroup(clave, &tokens[3].texto()))?;
+            let ahora = ahora_ms();
+            let mut consumidores = Arrays::new();
+            for (consumidor, visto_ms) in grupo.consumidores() {
+                consumidores.append(pares(vec![
+                    ("name", DatoRedis::new_bulk_string(consumidor.to_string())?),
+                    (
+                        "pending",
+                        DatoRedis::new_integer(grupo.pendientes_de(consumidor) as i64),
+                    ),
+                    (
+                        "idle",
+                        DatoRedis::new_integer(ahora.saturating_sub(*visto_ms) as i64),
+                    ),
+                ])?);
+            }
+            Ok(DatoRedis::new_array_con_contenido(consumidores))
+        }
+        _ => Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!(
+                "unknown subcommand '{}'. Try XINFO HELP.",
+                tokens[1].to_lowercase()
+            ),
+        )),
+    }
+}
+
+/// Obtiene las entradas de un stream que lee un consumidor con XREADGROUP,
+/// registrando la entrega en el grupo
+///
+/// # Parámetros
+/// * `stream`: stream leido, que contiene al grupo
+/// * `parametros`: parametros del comando
+/// * `id`: `>` para entradas nuevas, otro id para el historial de
+///   entradas pendientes del consumidor
+/// * `ahora`: instante de la entrega
+///
+/// # Retorna
+/// - las entradas entregadas, con los campos en null si la entrada
+///   pendiente fue eliminada del stream, error de redis si el id es invalido
+fn leer_para_consumidor(
+    stream: &mut Stream,
+    parametros: &ParametrosXreadgroup,
+    id: &Token,
+    ahora: u64,
+) -> Result<Vec<DatoRedis>, DatoRedis> {
+    let count = parametros.count.unwrap_or(usize::MAX);
+    let Some(grupo) = stream.grupo(&parametros.grupo) else {
+        return Ok(Vec::new());
+    };
+    let nuevas = id == ID_NUEVAS;
+    let entradas: Vec<(StreamId, Option<CamposStream>)> = if nuevas {
+        match grupo.ultimo_entregado().siguiente() {
+            Some(desde) => stream
+                .rango(desde, StreamId::MAX)
+                .take(count)
+                .map(|(id, campos)| (*id, Some(campos.clone())))
+                .collect(),
+            None => Vec::new(),
+        }
+    } else {
+        match id.parse::<StreamId>()?.siguiente() {
+            Some(desde) => grupo
+                .pendientes()
+                .range(desde..)
+                .filter(|(_, p)| p.consumidor == parametros.consumidor)
+                .take(count)
+                .map(|(id, _)| (*id, stream.entrada(id).cloned()))
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    let Some(grupo) = stream.grupo_mutable(&parametros.grupo) else {
+        return Ok(Vec::new());
+    };
+    grupo.registrar_consumidor(&parametros.consumidor, ahora);
+    let mut datos = Vec::new();
+    for (id, campos) in entradas {
+        if nuevas {
+            grupo.entregar(id, &parametros.consumidor, ahora, !parametros.noack);
+        } else {
+            grupo.reasignar(&id, &parametros.consumidor, ahora, None);
+        }
+        datos.push(match campos {
+            Some(campos) => entrada_a_dato(&id, &campos),
+            None => {
+                let mut entrada = Arrays::new();
+                entrada.append(DatoRedis::new_bulk_string(id.to_string())?);
+                entrada.append(DatoRedis::new_null());
+                DatoRedis::new_array_con_contenido(entrada)
+            }
+        });
+    }
+    Ok(datos)
+}
+
+/// Interpreta los argumentos de XREADGROUP
+fn parsear_xreadgroup(tokens: &[Token]) -> Result<ParametrosXreadgroup, DatoRedis> {
+    if tokens.len() < 4 || tokens[1].to_uppercase() != OPCION_GROUP {
+        return Err(error_sintaxis());
+    }
+    let mut parametros = ParametrosXreadgroup {
+        grupo: tokens[2].texto().into_owned(),
+        consumidor: tokens[3].texto().into_owned(),
+        count: None,
+        block: None,
+        noack: false,
+        claves: Vec::new(),
+        ids: Vec::new(),
+    };
+    let mut posicion = 4;
+    loop {
+        let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) else {
+            return Err(error_sintaxis());
+        };
+        match opcion.as_str() {
+            OPCION_STREAMS => break,
+            OPCION_NOACK => {
+                parametros.noack = true;
+                posicion += 1;
+                continue;
+            }
+            OPCION_COUNT => {
+                parametros.count = Some(parsear_entero(tokens.get(posicion + 1))? as usize)
+            }
+            OPCION_BLOCK => parametros.block = Some(parsear_entero(tokens.get(posicion + 1))?),
+            _ => return Err(error_sintaxis()),
+        }
+        posicion += 2;
+    }
+
+    let streams = &tokens[posicion + 1..];
+    if streams.is_empty() || !streams.len().is_multiple_of(2) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "Unbalanced 'xreadgroup' list of streams: for each stream key an ID or '>' must be specified."
+                .to_string(),
+        ));
+    }
+    let (claves, ids) = streams.split_at(streams.len() / 2);
+    for id in ids.iter().filter(|id| *id != ID_NUEVAS) {
+        if id == ID_ULTIMO {
+            return Err(DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "The $ ID is meaningless in the context of XREADGROUP: you want to read the history of this consumer by specifying a proper ID, or use the > ID to get new messages. The $ ID would just return an empty result set."
+                    .to_string(),
+            ));
+        }
+        id.parse::<StreamId>()?;
+    }
+    parametros.claves = claves.to_vec();
+    parametros.ids = ids.to_vec();
+    Ok(parametros)
+}
+
+/// Interpreta el id y las opciones de XGROUP CREATE y XGROUP SETID
+///
+/// # Parámetros
+/// * `tokens`: comando recibido
+/// * `admite_mkstream`: si el subcomando admite la opcion MKSTREAM
+///
+/// # Retorna
+/// - el id indicado (None para `$`, el ultimo id del stream) y si se
+///   recibio MKSTREAM, error de redis si los argumentos son invalidos
+fn parsear_opciones_xgroup(
+    tokens: &[Token],
+    admite_mkstream: bool,
+) -> Result<(Option<StreamId>, bool), DatoRedis> {
+    let id = match tokens.get(4) {
+        Some(id) if id == ID_ULTIMO => None,
+        Some(id) => Some(id.parse::<StreamId>()?),
+        None => return Err(error_sintaxis()),
+    };
+    let mut mkstream = false;
+    let mut posicion = 5;
+    while let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) {
+        match opcion.as_str() {
+            OPCION_MKSTREAM if admite_mkstream => {
+                mkstream = true;
+                posicion += 1;
+            }
+            // La cantidad de entradas leidas solo se usa para estimar el
+            // lag del grupo, que no se informa
+            OPCION_ENTRIESREAD => {
+                parsear_entero(tokens.get(posicion + 1))?;
+                posicion += 2;
+            }
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    Ok((id, mkstream))
+}
+
+/// Interpreta los ids y las opciones de XCLAIM
+fn parsear_xclaim(tokens: &[Token]) -> Result<(Vec<StreamId>, OpcionesXclaim), DatoRedis> {
+    let cantidad_ids = tokens
+        .iter()
+        .position(|t| {
+            matches!(
+                t.to_uppercase().as_str(),
+                OPCION_IDLE
+                    | OPCION_TIME
+                    | OPCION_RETRYCOUNT
+                    | OPCION_FORCE
+                    | OPCION_JUSTID
+                    | OPCION_LASTID
+            )
+        })
+        .unwrap_or(tokens.len());
+    if cantidad_ids == 0 {
+        return Err(error_sintaxis());
+    }
+    let ids = parsear_ids(&tokens[..cantidad_ids])?;
+
+    let mut opciones = OpcionesXclaim::default();
+    let mut posicion = cantidad_ids;
+    while let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) {
+        match opcion.as_str() {
+            OPCION_FORCE => opciones.force = true,
+            OPCION_JUSTID => opciones.justid = true,
+            OPCION_IDLE => {
+                let idle = parsear_entero(tokens.get(posicion + 1))?;
+                opciones.entregado_ms = Some(ahora_ms().saturating_sub(idle));
+                posicion += 1;
+            }
+            OPCION_TIME => {
+                opciones.entregado_ms = Some(parsear_entero(tokens.get(posicion + 1))?);
+                posicion += 1;
+            }
+            OPCION_RETRYCOUNT => {
+                opciones.entregas = Some(parsear_entero(tokens.get(posicion + 1))?);
+                posicion += 1;
+            }
+            OPCION_LASTID => {
+                let lastid = tokens.get(posicion + 1).ok_or_else(error_sintaxis)?;
+                opciones.lastid = Some(lastid.parse::<StreamId>()?);
+                posicion += 1;
+            }
+            _ => return Err(error_sintaxis()),
+        }
+        posicion += 1;
+    }
+    Ok((ids, opciones))
+}
+
+fn parsear_ids(tokens: &[Token]) -> Result<Vec<StreamId>, DatoRedis> {
+    tokens.iter().map(|id| id.parse::<StreamId>()).collect()
+}
+
+/// Aplica una funcion que modifica el stream de una clave, verificando que
+/// exista el grupo indicado
+///
+/// # Retorna
+/// - el resultado de la funcion, que recibe el stream y el nombre del
+///   grupo, error de redis si no existe la clave o el grupo
+fn con_grupo_mutable<T>(
+    storage: &mut Storage,
+    clave: &[u8],
+    grupo: &str,
+    funcion: impl FnOnce(&mut Stream, &str) -> T,
+) -> Result<T, DatoRedis> {
+    con_stream_mutable(storage, clave, |stream| {
+        stream
+            .grupo(grupo)
+            .is_some()
+            .then(|| funcion(stream, grupo))
+    })?
+    .flatten()
+    .ok_or_else(|| error_nogroup(clave, grupo))
+}
+
+/// Ejecuta XPENDING sobre un grupo
+///
+/// # Parámetros
+/// * `grupo`: grupo consultado
+/// * `argumentos`: argumentos posteriores al grupo, vacio para el resumen
+fn pendientes_de_grupo(
+    grupo: &GrupoConsumidores,
+    argumentos: &[Token],
+) -> Result<DatoRedis, DatoRedis> {
+    if argumentos.is_empty() {
+        return resumen_pendientes(grupo);
+    }
+    let mut idle_minimo = 0;
+    let mut argumentos = argumentos;
+    if argumentos[0].to_uppercase() == OPCION_IDLE {
+        idle_minimo = parsear_entero(argumentos.get(1))?;
+        argumentos = &argumentos[2.min(argumentos.len())..];
+    }
+    if argumentos.len() != 3 && argumentos.len() != 4 {
+        return Err(error_sintaxis());
+    }
+    let desde = parsear_limite(&argumentos[0], true)?;
+    let hasta = parsear_limite(&argumentos[1], false)?;
+    let count = parsear_entero(Some(&argumentos[2]))? as usize;
+    let consumidor = argumentos.get(3).map(|c| c.texto());
+
+    let ahora = ahora_ms();
+    let mut detalle = Arrays::new();
+    if let (Some(desde), Some(hasta)) = (desde, hasta)
+        && desde <= hasta
+    {
+        let pendientes = grupo
+            .pendientes()
+            .range(desde..=hasta)
+            .filter(|(_, p)| consumidor.as_ref().is_none_or(|c| *c == p.consumidor))
+            .filter(|(_, p)| ahora.saturating_sub(p.entregado_ms) >= idle_minimo)
+            .take(count);
+        for (id, pendiente) in pendientes {
+            let mut entrada = Arrays::new();
+            entrada.append(DatoRedis::new_bulk_string(id.to_string())?);
+            entrada.append(DatoRedis::new_bulk_string(
+                pendiente.consumidor.to_string(),
+            )?);
+            entrada.append(DatoRedis::new_integer(
+                ahora.saturating_sub(pendiente.entregado_ms) as i64,
+            ));
+            entrada.append(DatoRedis::new_integer(pendiente.entregas as i64));
+            detalle.append(DatoRedis::new_array_con_contenido(entrada));
+        }
+    }
+    Ok(DatoRedis::new_array_con_contenido(detalle))
+}
+
+/// Resumen de XPENDING: cantidad de pendientes, menor y mayor id pendiente
+/// y cantidad de pendientes de cada consumidor
+fn resumen_pendientes(grupo: &GrupoConsumidores) -> Result<DatoRedis, DatoRedis> {
+    let pendientes = grupo.pendientes();
+    let mut resumen = Arrays::new();
+    resumen.append(DatoRedis::new_integer(pendientes.len() as i64));
+    let extremos = [pendientes.first_key_value(), pendientes.last_key_value()];
+    for extremo in extremos {
+        resumen.append(match extremo {
+            Some((id, _)) => DatoRedis::new_bulk_string(id.to_string())?,
+            None => DatoRedis::new_null(),
+        });
+    }
+
+    let mut por_consumidor: Vec<(&String, usize)> = Vec::new();
+    for Pendiente { consumidor, .. } in pendientes.values() {
+        match por_consumidor.iter_mut().find(|(c, _)| *c == consumidor) {
+            Some((_, cantidad)) => *cantidad += 1,
+            None => por_consumidor.push((consumidor, 1)),
+        }
+    }
+    if por_consumidor.is_empty() {
+        resumen.append(DatoRedis::new_null());
+    } else {
+        por_consumidor.sort();
+        let mut consumidores = Arrays::new();
+        for (consumidor, cantidad) in por_consumidor {
+            let mut par = Arrays::new();
+            par.append(DatoRedis::new_bulk_string(consumidor.to_string())?);
+            par.append(DatoRedis::new_bulk_string(cantidad.to_string())?);
+            consumidores.append(DatoRedis::new_array_con_contenido(par));
+        }
+        resumen.append(DatoRedis::new_array_con_contenido(consumidores));
+    }
+    Ok(DatoRedis::new_array_con_contenido(resumen))
+}
+
+/// Informacion de XINFO STREAM
+fn info_stream(stream: &Stream) -> Result<DatoRedis, DatoRedis> {
+    let entrada = |entrada: Option<(&StreamId, &CamposStream)>| match entrada {
+        Some((id, campos)) => entrada_a_dato(id, campos),
+        None => DatoRedis::new_null(),
+    };
+    pares(vec![
+        ("length", DatoRedis::new_integer(stream.len() as i64)),
+        (
+            "last-generated-id",
+            DatoRedis::new_bulk_string(stream.ultimo_id().to_string())?,
+        ),
+        (
+            "entries-added",
+            DatoRedis::new_integer(stream.agregadas() as i64),
+        ),
+        (
+            "groups",
+            DatoRedis::new_integer(stream.grupos().len() as i64),
+        ),
+        ("first-entry", entrada(stream.primera())),
+        ("last-entry", entrada(stream.ultima())),
+    ])
+}
+
+/// Arma un arreglo alternando nombres de campos y valores
+fn pares(campos: Vec<(&str, DatoRedis)>) -> Result<DatoRedis, DatoRedis> {
+    let mut arreglo = Arrays::new();
+    for (campo, valor) in campos {
+        arreglo.append(DatoRedis::new_bulk_string(campo.to_string())?);
+        arreglo.append(valor);
+    }
+    Ok(DatoRedis::new_array_con_contenido(arreglo))
+}
+
+fn error_nogroup(clave: &[u8], grupo: &str) -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "NOGROUP".to_string(),
+        format!(
+            "No such consumer group '{grupo}' for key name '{}'",
+            String::from_utf8_lossy(clave)
+        ),
+    )
+}
+
+fn error_requiere_clave() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        "The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically."
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comandos::comandos_stream::{xadd, xdel};
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    /// Storage con un stream `s` con las entradas indicadas y el grupo `g`
+    /// creado antes de agregarlas
+    fn storage_con_grupo(ids: &[&str]) -> Arc<RwLock<Storage>> {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        xgroup(
+            &tokens(&["XGROUP", "CREATE", "s", "g", "$", "MKSTREAM"]),
+            &storage,
+        )
+        .unwrap();
+        for id in ids {
+            xadd(&tokens(&["XADD", "s", id, "campo", id]), &storage).unwrap();
+        }
+        storage
+    }
+
+    /// Obtiene los ids entregados a un consumidor por XREADGROUP
+    fn ids_leidos(respuesta: DatoRedis) -> Vec<String> {
+        let DatoRedis::Arrays(streams) = respuesta else {
+            return Vec::new();
+        };
+        let Some(DatoRedis::Arrays(par)) = streams.get(0) else {
+            panic!("se esperaba un par");
+        };
+        let Some(DatoRedis::Arrays(entradas)) = par.get(1) else {
+            panic!("se esperaban entradas");
+        };
+        entradas
+            .iter()
+            .map(|entrada| match entrada {
+                DatoRedis::Arrays(entrada) => match entrada.get(0) {
+                    Some(DatoRedis::BulkString(id)) => id.contenido(),
+                    _ => panic!("entrada sin id"),
+                },
+                DatoRedis::BulkString(id) => id.contenido(),
+                _ => panic!("entrada invalida"),
+            })
+            .collect()
+    }
+
+    fn leer(consumidor: &str, id: &str, storage: &Arc<RwLock<Storage>>) -> DatoRedis {
+        xreadgroup(
+            &tokens(&[
+                "XREADGROUP",
+                "GROUP",
+                "g",
+                consumidor,
+                "COUNT",
+                "2",
+                "STREAMS",
+                "s",
+                id,
+            ]),
+            storage,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_01_xgroup_create() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert!(xgroup(&tokens(&["XGROUP", "CREATE", "s", "g", "$"]), &storage).is_err());
+        assert_eq!(
+            xgroup(
+                &tokens(&["XGROUP", "CREATE", "s", "g", "0", "MKSTREAM"]),
+                &storage
+            )
+            .unwrap(),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert!(xgroup(&tokens(&["XGROUP", "CREATE", "s", "g", "0"]), &storage).is_err());
+        assert_eq!(
+            xgroup(&tokens(&["XGROUP", "DESTROY", "s", "g"]), &storage).unwrap(),
+            DatoRedis::new_integer(1)
+        );
+        assert!(xgroup(&tokens(&["XGROUP", "SETID", "s", "g", "0"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_02_cada_entrada_se_entrega_a_un_consumidor() {
+        let storage = storage_con_grupo(&["1", "2", "3"]);
+        assert_eq!(ids_leidos(leer("c1", ">", &storage)), ["1-0", "2-0"]);
+        assert_eq!(ids_leidos(leer("c2", ">", &storage)), ["3-0"]);
+        assert_eq!(leer("c2", ">", &storage), DatoRedis::new_null());
+
+        // Historial de pendientes del consumidor
+        assert_eq!(ids_leidos(leer("c1", "0", &storage)), ["1-0", "2-0"]);
+        assert_eq!(
+            xack(&tokens(&["XACK", "s", "g", "1-0", "9-0"]), &storage).unwrap(),
+            DatoRedis::new_integer(1)
+        );
+        assert_eq!(ids_leidos(leer("c1", "0", &storage)), ["2-0"]);
+        assert!(
+            xreadgroup(
+                &tokens(&["XREADGROUP", "GROUP", "otro", "c", "STREAMS", "s", ">"]),
+                &storage
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_03_xpending_resumen_y_detalle() {
+        let storage = storage_con_grupo(&["1", "2", "3"]);
+        leer("c1", ">", &storage);
+        leer("c2", ">", &storage);
+
+        let resumen = xpending(&tokens(&["XPENDING", "s", "g"]), &storage).unwrap();
+        let DatoRedis::Arrays(resumen) = resumen else {
+            panic!("se esperaba un arreglo");
+        };
+        assert_eq!(resumen.get(0), Some(DatoRedis::new_integer(3)));
+        assert_eq!(
+            resumen.get(1),
+            Some(DatoRedis::new_bulk_string("1-0".to_string()).unwrap())
+        );
+        assert_eq!(
+            resumen.get(2),
+            Some(DatoRedis::new_bulk_string("3-0".to_string()).unwrap())
+        );
+
+        let detalle = xpending(
+            &tokens(&["XPENDING", "s", "g", "-", "+", "10", "c2"]),
+            &storage,
+        )
+        .unwrap();
+        let DatoRedis::Arrays(detalle) = detalle else {
+            panic!("se esperaba un arreglo");
+        };
+        assert_eq!(detalle.len(), 1);
+        let Some(DatoRedis::Arrays(entrada)) = detalle.get(0) else {
+            panic!("se esperaba una entrada");
+        };
+        assert_eq!(
+            entrada.get(1),
+            Some(DatoRedis::new_bulk_string("c2".to_string()).unwrap())
+        );
+        assert_eq!(entrada.get(3), Some(DatoRedis::new_integer(1)));
+        assert!(xpending(&tokens(&["XPENDING", "s", "otro"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_04_xclaim_transfiere_entradas_inactivas() {
+        let storage = storage_con_grupo(&["1", "2", "3"]);
+        leer("c1", ">", &storage);
+
+        let reclamar = |min_idle: &str, opciones: &[&str]| {
+            let mut comando = tokens(&["XCLAIM", "s", "g", "c2", min_idle, "1-0", "2-0"]);
+            comando.extend(tokens(opciones));
+            xclaim(&comando, &storage).unwrap()
+        };
+        // Las entradas fueron entregadas recien
+        assert_eq!(
+            reclamar("60000", &["JUSTID"]),
+            DatoRedis::new_array_con_contenido(Arrays::new())
+        );
+
+        xdel(&tokens(&["XDEL", "s", "2-0"]), &storage).unwrap();
+        let reclamadas = reclamar("0", &["JUSTID"]);
+        let DatoRedis::Arrays(reclamadas) = reclamadas else {
+            panic!("se esperaba un arreglo");
+        };
+        assert_eq!(
+            reclamadas.get(0),
+            Some(DatoRedis::new_bulk_string("1-0".to_string()).unwrap())
+        );
+        assert_eq!(reclamadas.len(), 1);
+
+        // La entrada eliminada deja de estar pendiente
+        assert_eq!(ids_leidos(leer("c1", "0", &storage)), Vec::<String>::new());
+        assert_eq!(ids_leidos(leer("c2", "0", &storage)), ["1-0"]);
+    }
+
+    #[test]
+    fn test_05_xinfo_y_delconsumer() {
+        let storage = storage_con_grupo(&["1", "2"]);
+        leer("c1", ">", &storage);
+
+        let DatoRedis::Arrays(grupos) =
+            xinfo(&tokens(&["XINFO", "GROUPS", "s"]), &storage).unwrap()
+        else {
+            panic!("se esperaba un arreglo");
+        };
+        let Some(DatoRedis::Arrays(grupo)) = grupos.get(0) else {
+            panic!("se esperaba un grupo");
+        };
+        assert_eq!(grupo.get(5), Some(DatoRedis::new_integer(2)));
+        assert_eq!(
+            grupo.get(7),
+            Some(DatoRedis::new_bulk_string("2-0".to_string()).unwrap())
+        );
+
+        let DatoRedis::Arrays(info) = xinfo(&tokens(&["XINFO", "STREAM", "s"]), &storage).unwrap()
+        else {
+            panic!("se esperaba un arreglo");
+        };
+        assert_eq!(info.get(1), Some(DatoRedis::new_integer(2)));
+        assert!(xinfo(&tokens(&["XINFO", "STREAM", "otro"]), &storage).is_err());
+
+        assert_eq!(
+            xgroup(
+                &tokens(&["XGROUP", "DELCONSUMER", "s", "g", "c1"]),
+                &storage
+            )
+            .unwrap(),
+            DatoRedis::new_integer(2)
+        );
+        assert_eq!(
+            xinfo(&tokens(&["XINFO", "CONSUMERS", "s", "g"]), &storage).unwrap(),
+            DatoRedis::new_array_con_contenido(Arrays::new())
+        );
+    }
+
+    #[test]
+    fn test_06_xreadgroup_bloqueante_sin_block() {
+        let comando = tokens(&[
+            "XREADGROUP",
+            "GROUP",
+            "g",
+            "c",
+            "BLOCK",
+            "100",
+            "NOACK",
+            "STREAMS",
+            "s",
+            ">",
+        ]);
+        assert!(xreadgroup_bloqueante(&comando));
+        let (claves, timeout, intento) = preparar_xreadgroup_bloqueante(&comando).unwrap();
+        assert_eq!(claves, tokens(&["s"]));
+        assert_eq!(timeout, Some(Duration::from_millis(100)));
+        assert_eq!(
+            intento,
+            tokens(&[
+                "XREADGROUP",
+                "GROUP",
+                "g",
+                "c",
+                "NOACK",
+                "STREAMS",
+                "s",
+                ">"
+            ])
+        );
+        // La lectura del historial no bloquea
+        let historial = tokens(&[
+            "XREADGROUP",
+            "GROUP",
+            "g",
+            "c",
+            "BLOCK",
+            "0",
+            "STREAMS",
+            "s",
+            "0",
+        ]);
+        assert!(!xreadgroup_bloqueante(&historial));
+    }
+}
@@ -1,12 +1,52 @@
 //! Este modulo contiene la implementacion de los comandos para strings de redis
 use std::sync::{Arc, RwLock};
 
-use crate::comandos::utils::assert_correct_arguments_quantity;
+use crate::comandos::comandos_keyspace::verificar_mismo_slot;
+use crate::comandos::const_cmd::CMD_SET;
+use crate::comandos::utils::{
+    assert_correct_arguments_quantity, assert_number_of_arguments_distinct,
+};
+use crate::memoria::ahora_ms;
 use crate::{comandos::const_cmd::OPERACION_EXITOSA, storage::Storage};
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
 use redis_client::tipos_datos::traits::DatoRedis;
 
 use super::utils::{get_storage_read_lock, get_storage_write_lock};
 
+const OPCION_NX: &str = "NX";
+const OPCION_XX: &str = "XX";
+const OPCION_GET: &str = "GET";
+const OPCION_EX: &str = "EX";
+const OPCION_PX: &str = "PX";
+const OPCION_EXAT: &str = "EXAT";
+const OPCION_PXAT: &str = "PXAT";
+const OPCION_KEEPTTL: &str = "KEEPTTL";
+const OPCION_PERSIST: &str = "PERSIST";
+/// Largo maximo de un string, en bytes
+const LARGO_MAXIMO_STRING: usize = 512 * 1024 * 1024;
+
+/// Condicion para que SET escriba el valor
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CondicionSet {
+    Siempre,
+    /// NX: solo si la clave no existe
+    SiNoExiste,
+    /// XX: solo si la clave existe
+    SiExiste,
+}
+
+/// Expiracion a aplicar a una clave al modificarla
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expiracion {
+    /// La clave pasa a ser persistente
+    Persistir,
+    /// Se conserva la expiracion que tuviera la clave
+    Conservar,
+    /// Instante de expiracion en milisegundos desde epoch
+    En(u64),
+}
+
 /// Obtiene el valor (string) de una clave
 ///
 /// # Parámetros
@@ -14,17 +54,18 @@ use super::utils::{get_storage_read_lock, get_storage_write_lock};
 /// * `storage`: storage del nodo donde se encuentra la clave
 ///
 /// # Retorna
-/// - Valor de la clave en caso de exito, null en otro caso
-pub fn get(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+/// - Valor de la clave en caso de exito, error de redis si la clave no
+///   almacena un string, null en otro caso
+pub fn get(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
     let guard = get_storage_read_lock(storage)?;
 
-    match guard.get(tokens[1].to_string()) {
+    match guard.get(&tokens[1]) {
         Ok(val) => {
             if let DatoRedis::BulkString(_) = val {
                 Ok(val)
             } else {
-                Err(DatoRedis::new_null())
+                Err(error_tipo())
             }
         }
         Err(e) => Err(e),
@@ -40,12 +81,12 @@ pub fn get(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedi
 ///
 /// # Retorna
 /// - Cantidad de elementos eliminados, error de redis en otro caso
-pub fn del(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+pub fn del(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
     let mut eliminados = 0;
     let mut guard = get_storage_write_lock(storage)?;
     for token in tokens.iter() {
-        match guard.remove(token.to_string()) {
+        match guard.remove(token) {
             Ok(_) => eliminados += 1,
             Err(e) => match e {
                 DatoRedis::MovedError(_) => return Err(e),
@@ -56,31 +97,172 @@ pub fn del(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedi
     Ok(DatoRedis::new_integer(eliminados))
 }
 
-/// Asigna un valor (string) de una clave. De existir la clave, modifica
-/// su valor si la misma ya almacenaba un string
+/// Asigna un valor (string) a una clave, reemplazando el valor que
+/// tuviera. Salvo con KEEPTTL, la clave pierde la expiracion previa
 ///
 /// # Parámetros
-/// * `tokens`: lista conteniendo nombre del comando, clave y elemento
-///   a insertar
+/// * `tokens`: lista conteniendo nombre del comando, clave, elemento
+///   a insertar y opcionalmente NX o XX, GET y una expiracion (EX, PX,
+///   EXAT, PXAT o KEEPTTL)
 /// * `storage`: storage del nodo donde se encuentra la clave
 ///
 /// # Retorna
+/// - OK en caso de exito, o con GET el valor previo de la clave. Null si
+///   no se cumple la condicion NX o XX, error de redis en otro caso
+pub fn set(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+    let (condicion, devolver_anterior, expiracion) = parsear_opciones_set(tokens)?;
+    let valor_a_guardar = DatoRedis::new_bulk_string_desde_bytes(tokens[2].to_vec())?;
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let anterior = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::BulkString(valor)) => Some(DatoRedis::BulkString(valor)),
+        Ok(_) if devolver_anterior => return Err(error_tipo()),
+        Ok(valor) => Some(valor),
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        Err(_) => None,
+    };
+    let escribir = match condicion {
+        CondicionSet::Siempre => true,
+        CondicionSet::SiNoExiste => anterior.is_none(),
+        CondicionSet::SiExiste => anterior.is_some(),
+    };
+    if escribir {
+        guard.set(&tokens[1], valor_a_guardar)?;
+        aplicar_expiracion(&mut guard, &tokens[1], expiracion)?;
+    }
+
+    if devolver_anterior {
+        return Ok(anterior.unwrap_or_else(DatoRedis::new_null));
+    }
+    if !escribir {
+        return Err(DatoRedis::new_null());
+    }
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
+
+/// Asigna valores (strings) a varias claves, que deben pertenecer al
+/// mismo slot
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y pares de clave y
+///   elemento a insertar
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
 /// - OK en caso de exito, error de redis en otro caso
-pub fn set(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
-    let valor_a_guardar = DatoRedis::new_bulk_string(tokens[2].to_string())?;
+pub fn mset(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
+    if tokens.len().is_multiple_of(2) {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!(
+                "wrong number of arguments for '{}' command",
+                tokens[0].to_lowercase()
+            ),
+        ));
+    }
     let mut guard = get_storage_write_lock(storage)?;
+    for par in tokens[1..].chunks(2).skip(1) {
+        verificar_mismo_slot(&guard, &tokens[1], &par[0])?;
+    }
+    for par in tokens[1..].chunks(2) {
+        guard.set(
+            &par[0],
+            DatoRedis::new_bulk_string_desde_bytes(par[1].to_vec())?,
+        )?;
+        guard.set_expiracion(&par[0], None)?;
+    }
+    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+}
 
-    if let Ok(valor_existente) = guard.get(tokens[1].to_string()) {
-        if !matches!(valor_existente, DatoRedis::BulkString(_)) {
-            return Err(DatoRedis::new_simple_error(
-                "WRONGTYPE".to_string(),
-                "Operation against a key holding the wrong kind of value".to_string(),
-            ));
+/// Obtiene los valores (strings) de varias claves, que deben pertenecer
+/// al mismo slot
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando y las claves
+/// * `storage`: storage del nodo donde se encuentran las claves
+///
+/// # Retorna
+/// - Arreglo con el valor de cada clave, null para las claves que no
+///   existen o no almacenan un string, error de redis en otro caso
+pub fn mget(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let guard = get_storage_read_lock(storage)?;
+    for clave in tokens.iter().skip(2) {
+        verificar_mismo_slot(&guard, &tokens[1], clave)?;
+    }
+    let mut valores = Arrays::new();
+    for clave in &tokens[1..] {
+        match guard.get(clave) {
+            Ok(DatoRedis::BulkString(valor)) => valores.append(DatoRedis::BulkString(valor)),
+            Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+            _ => valores.append(DatoRedis::new_null()),
         }
     }
-    guard.set(tokens[1].to_string(), valor_a_guardar)?;
-    DatoRedis::new_simple_string(OPERACION_EXITOSA.to_string())
+    Ok(DatoRedis::new_array_con_contenido(valores))
+}
+
+/// Obtiene el valor (string) de una clave y modifica su expiracion
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave y
+///   opcionalmente la nueva expiracion (EX, PX, EXAT, PXAT o PERSIST)
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor de la clave en caso de exito, null si la clave no existe,
+///   error de redis en otro caso
+pub fn getex(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
+    let expiracion = match &tokens[2..] {
+        [] => Expiracion::Conservar,
+        [opcion] if opcion.to_uppercase() == OPCION_PERSIST => Expiracion::Persistir,
+        [opcion, valor] => Expiracion::En(instante_expiracion(
+            &tokens[0],
+            &opcion.to_uppercase(),
+            valor,
+        )?),
+        _ => return Err(error_sintaxis()),
+    };
+    let mut guard = get_storage_write_lock(storage)?;
+
+    match guard.get(&tokens[1]) {
+        Ok(DatoRedis::BulkString(valor)) => {
+            aplicar_expiracion(&mut guard, &tokens[1], expiracion)?;
+            Ok(DatoRedis::BulkString(valor))
+        }
+        Ok(_) => Err(error_tipo()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reemplaza las expiraciones relativas (EX y PX) y en segundos (EXAT) de
+/// SET y GETEX por su instante absoluto en milisegundos (PXAT), para que
+/// las replicas y el AOF asignen la misma expiracion que el nodo
+///
+/// # Parámetros
+/// * `tokens`: comando SET o GETEX recibido
+pub(crate) fn con_expiracion_absoluta(tokens: &[Token]) -> Vec<Token> {
+    let mut absoluto = tokens.to_vec();
+    let mut posicion = if tokens[0].to_uppercase() == CMD_SET {
+        3
+    } else {
+        2
+    };
+    while posicion + 1 < absoluto.len() {
+        let opcion = absoluto[posicion].to_uppercase();
+        if !matches!(opcion.as_str(), OPCION_EX | OPCION_PX | OPCION_EXAT) {
+            posicion += 1;
+            continue;
+        }
+        if let Ok(instante) = instante_expiracion(&tokens[0], &opcion, &absoluto[posicion + 1]) {
+            absoluto[posicion] = Token::from(OPCION_PXAT);
+            absoluto[posicion + 1] = Token::from(instante.to_string());
+        }
+        posicion += 2;
+    }
+    absoluto
 }
 
 /// Borra una clave y devuelve su valor (string)
@@ -91,11 +273,11 @@ pub fn set(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedi
 ///
 /// # Retorna
 /// - Valor borrado en caso de exito, null en otro caso
-pub fn getdel(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+pub fn getdel(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
     let mut guard = get_storage_write_lock(storage)?;
 
-    match guard.remove(tokens[1].to_string()) {
+    match guard.remove(&tokens[1]) {
         Ok(val) => Ok(val),
         Err(e) => match e {
             DatoRedis::MovedError(_) => Err(e),
@@ -113,14 +295,14 @@ pub fn getdel(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 ///
 /// # Retorna
 /// - Nuevo largo del valor de la clave en caso de exito, 0 en otro caso
-pub fn append(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 3, tokens.len())?;
+pub fn append(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 3, tokens.len())?;
     let mut guard = get_storage_write_lock(storage)?;
 
-    match guard.get_mutable(tokens[1].to_string()) {
+    match guard.get_mutable(&tokens[1]) {
         Ok(valor) => {
             if let DatoRedis::BulkString(bulk_string) = &mut *valor {
-                bulk_string.concatenar(tokens[2].to_string());
+                bulk_string.concatenar_bytes(&tokens[2]);
                 let longitud_cadena = DatoRedis::new_integer(bulk_string.largo() as i64);
                 return Ok(longitud_cadena);
             }
@@ -128,15 +310,10 @@ pub fn append(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
         Err(e) => match e {
             DatoRedis::MovedError(_) => return Err(e),
             _ => {
-                set(
-                    &[
-                        "set".to_string(),
-                        tokens[1].to_string(),
-                        tokens[2].to_string(),
-                    ],
-                    storage,
-                )?;
-                return Ok(DatoRedis::new_integer(tokens[2].len() as i64));
+                let bytes = tokens[2].to_vec();
+                let largo = bytes.len();
+                guard.set(&tokens[1], DatoRedis::new_bulk_string_desde_bytes(bytes)?)?;
+                return Ok(DatoRedis::new_integer(largo as i64));
             }
         },
     }
@@ -152,18 +329,15 @@ pub fn append(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 /// # Retorna
 /// - Largo del valor de la clave en caso de exito, error de redis
 ///   en caso de tipo invalido, 0 en otro caso
-pub fn strlen(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
+pub fn strlen(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 2, tokens.len())?;
     let guard = get_storage_read_lock(storage)?;
 
-    match guard.get(tokens[1].to_string()) {
+    match guard.get(&tokens[1]) {
         Ok(DatoRedis::BulkString(bulk_string)) => {
             Ok(DatoRedis::new_integer(bulk_string.largo() as i64))
         }
-        Ok(_) => Err(DatoRedis::new_simple_error(
-            "WRONGTYPE".to_string(),
-            "Operation against a key holding the wrong kind of value".to_string(),
-        )),
+        Ok(_) => Err(error_tipo()),
         Err(e) => match e {
             DatoRedis::MovedError(_) => Err(e),
             _ => Ok(DatoRedis::new_integer(0)),
@@ -183,30 +357,26 @@ pub fn strlen(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 /// # Retorna
 /// - Largo del valor de la clave en caso de exito, error de redis
 ///   en caso de tipo invalido, 0 en otro caso
-pub fn substr(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 4, tokens.len())?;
+pub fn substr(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_correct_arguments_quantity(&tokens[0].texto(), 4, tokens.len())?;
     let guard = get_storage_read_lock(storage)?;
 
-    match guard.get(tokens[1].to_string()) {
+    match guard.get(&tokens[1]) {
         Ok(DatoRedis::BulkString(bulk_string)) => {
             let len = bulk_string.largo() as isize;
 
-            let inicio = parse_index(tokens[2].to_string())?;
-            let fin = parse_index(tokens[3].to_string())?;
+            let inicio = parse_index(&tokens[2])?;
+            let fin = parse_index(&tokens[3])?;
             let (inicio, fin) = adjust_indices(inicio, fin, len);
 
             if inicio > fin {
                 return DatoRedis::new_bulk_string("".to_string());
             }
 
-            let slice_cadena = bulk_string.contenido()[inicio..fin].to_string();
-            DatoRedis::new_bulk_string(slice_cadena)
+            DatoRedis::new_bulk_string_desde_bytes(bulk_string.bytes()[inicio..fin].to_vec())
         }
 
-        Ok(_) => Err(DatoRedis::new_simple_error(
-            "WRONGTYPE".to_string(),
-            "Operation against a key holding the wrong kind of value".to_string(),
-        )),
+        Ok(_) => Err(error_tipo()),
 
         Err(e) => match e {
             DatoRedis::MovedError(_) => Err(e), // propagás el MOVED
@@ -225,8 +395,9 @@ pub fn substr(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoR
 ///
 /// # Retorna
 /// - Valor incrementado en caso de exito, error de redis en otro caso
-pub fn incr(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    modificar_valor(tokens, storage, |x| x + 1)
+pub fn incr(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    modificar_valor(tokens, storage, |x| x.checked_add(1))
 }
 
 /// Decrementa el valor de una clave en 1 unidad, dada que la misma
@@ -239,77 +410,295 @@ pub fn incr(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRed
 ///
 /// # Retorna
 /// - Valor decrementado en caso de exito, error de redis en otro caso
-pub fn decr(tokens: &[String], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
-    modificar_valor(tokens, storage, |x| x - 1)
+pub fn decr(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 2, tokens.len())?;
+    modificar_valor(tokens, storage, |x| x.checked_sub(1))
+}
+
+/// Incrementa el valor de una clave en la cantidad indicada
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave a
+///   analizar e incremento
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor incrementado en caso de exito, error de redis en otro caso
+pub fn incrby(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let incremento = parsear_entero(&tokens[2].texto())?;
+    modificar_valor(tokens, storage, |x| x.checked_add(incremento))
+}
+
+/// Decrementa el valor de una clave en la cantidad indicada
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave a
+///   analizar y decremento
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor decrementado en caso de exito, error de redis en otro caso
+pub fn decrby(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let decremento = parsear_entero(&tokens[2].texto())?;
+    modificar_valor(tokens, storage, |x| x.checked_sub(decremento))
+}
+
+/// Incrementa el valor de una clave en la cantidad de punto flotante
+/// indicada
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave a
+///   analizar e incremento
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Valor incrementado en caso de exito, error de redis en otro caso
+pub fn incrbyfloat(
+    tokens: &[Token],
+    storage: &Arc<RwLock<Storage>>,
+) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 3, tokens.len())?;
+    let incremento = parsear_float(&tokens[2].texto())?;
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let actual = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::BulkString(valor)) => parsear_float(&valor.contenido())?,
+        Ok(_) => return Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        Err(_) => 0.0,
+    };
+    let nuevo = actual + incremento;
+    if !nuevo.is_finite() {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "increment would produce NaN or Infinity".to_string(),
+        ));
+    }
+    // Evita guardar el cero negativo como "-0"
+    let nuevo = if nuevo == 0.0 { 0.0 } else { nuevo };
+    let valor = DatoRedis::new_bulk_string(nuevo.to_string())?;
+    guard.set(&tokens[1], valor.clone())?;
+    Ok(valor)
+}
+
+/// Sobrescribe parte del valor (string) de una clave a partir de un
+/// offset, completando con bytes nulos si el valor es mas corto
+///
+/// # Parámetros
+/// * `tokens`: lista conteniendo nombre del comando, clave a
+///   modificar, offset y valor a escribir
+/// * `storage`: storage del nodo donde se encuentra la clave
+///
+/// # Retorna
+/// - Nuevo largo del valor de la clave en caso de exito, error de redis
+///   en otro caso
+pub fn setrange(tokens: &[Token], storage: &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis> {
+    assert_number_of_arguments_distinct(&tokens[0].to_lowercase(), 4, tokens.len())?;
+    let error_offset =
+        || DatoRedis::new_simple_error("ERR".to_string(), "offset is out of range".to_string());
+    let offset =
+        usize::try_from(parsear_entero(&tokens[2].texto())?).map_err(|_| error_offset())?;
+    let valor = &tokens[3];
+    if offset.saturating_add(valor.len()) > LARGO_MAXIMO_STRING {
+        return Err(DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
+        ));
+    }
+    let mut guard = get_storage_write_lock(storage)?;
+
+    let mut bytes = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::BulkString(actual)) => actual.into_bytes(),
+        Ok(_) => return Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        // Un valor vacio no crea la clave
+        Err(_) if valor.is_empty() => return Ok(DatoRedis::new_integer(0)),
+        Err(_) => Vec::new(),
+    };
+    if valor.is_empty() {
+        return Ok(DatoRedis::new_integer(bytes.len() as i64));
+    }
+    let fin = offset + valor.len();
+    if bytes.len() < fin {
+        bytes.resize(fin, 0);
+    }
+    bytes[offset..fin].copy_from_slice(valor);
+    let largo = bytes.len();
+    guard.set(&tokens[1], DatoRedis::new_bulk_string_desde_bytes(bytes)?)?;
+    Ok(DatoRedis::new_integer(largo as i64))
 }
 
-/// Aplica una operacion numerica sobre una clave, de ser posible
+/// Aplica una operacion numerica sobre una clave, de ser posible. Una
+/// clave inexistente se considera con valor 0
 ///
 /// # Parámetros
 /// * `tokens`: lista conteniendo nombre del comando y clave a
 ///   analizar
 /// * `storage`: storage del nodo donde se encuentra la clave
-/// * `operacion`: funcion a aplicar
+/// * `operacion`: funcion a aplicar, que devuelve None si el resultado
+///   no puede representarse
 ///
 /// # Retorna
 /// - Valor modificado en caso de exito, error de redis en otro caso
 fn modificar_valor(
-    tokens: &[String],
+    tokens: &[Token],
     storage: &Arc<RwLock<Storage>>,
-    operacion: impl Fn(i64) -> i64,
+    operacion: impl Fn(i64) -> Option<i64>,
 ) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity(tokens[0].to_string(), 2, tokens.len())?;
-    let error = DatoRedis::new_simple_error(
-        "ERR".to_string(),
-        "value is not an integer or out of range".to_string(),
-    );
-
     let mut guard = get_storage_write_lock(storage)?;
 
-    match guard.get(tokens[1].to_string()) {
-        Ok(DatoRedis::BulkString(valor_bstring)) => {
-            let valor_actual = valor_bstring
-                .contenido()
-                .parse::<i64>()
-                .map_err(|_| error.clone())?;
-            let nuevo_valor = operacion(valor_actual);
-
-            if let Ok(valor_a_guardar) = DatoRedis::new_bulk_string(nuevo_valor.to_string()) {
-                let _ = guard.set(tokens[1].to_string(), valor_a_guardar);
-                Ok(DatoRedis::new_integer(nuevo_valor))
-            } else {
-                Err(error)
+    let valor_actual = match guard.get(&tokens[1]) {
+        Ok(DatoRedis::BulkString(valor_bstring)) => parsear_entero(&valor_bstring.contenido())?,
+        Ok(_) => return Err(error_tipo()),
+        Err(DatoRedis::MovedError(e)) => return Err(DatoRedis::MovedError(e)),
+        Err(_) => 0,
+    };
+    let nuevo_valor = operacion(valor_actual).ok_or_else(|| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "increment or decrement would overflow".to_string(),
+        )
+    })?;
+    guard.set(
+        &tokens[1],
+        DatoRedis::new_bulk_string(nuevo_valor.to_string())?,
+    )?;
+    Ok(DatoRedis::new_integer(nuevo_valor))
+}
+
+/// Interpreta las opciones de SET
+///
+/// # Retorna
+/// - la condicion de escritura, si se recibio GET y la expiracion a
+///   aplicar, error de redis si las opciones son invalidas
+fn parsear_opciones_set(tokens: &[Token]) -> Result<(CondicionSet, bool, Expiracion), DatoRedis> {
+    let mut condicion = CondicionSet::Siempre;
+    let mut devolver_anterior = false;
+    let mut expiracion = None;
+    let mut posicion = 3;
+    while let Some(opcion) = tokens.get(posicion).map(|t| t.to_uppercase()) {
+        match opcion.as_str() {
+            OPCION_NX | OPCION_XX if condicion != CondicionSet::Siempre => {
+                return Err(error_sintaxis());
             }
-        }
-        Ok(_) => Err(DatoRedis::new_simple_error(
-            "WRONGTYPE".to_string(),
-            "Operation against a key holding the wrong kind of value".to_string(),
-        )),
-        Err(e) => match e {
-            DatoRedis::MovedError(_) => Err(e),
-            _ => {
-                if let Ok(valor_cero) = DatoRedis::new_bulk_string("0".to_string()) {
-                    let _ = guard.set(tokens[1].to_string(), valor_cero);
-                    drop(guard);
-                    modificar_valor(tokens, storage, operacion)
-                } else {
-                    Err(error)
-                }
+            OPCION_NX => condicion = CondicionSet::SiNoExiste,
+            OPCION_XX => condicion = CondicionSet::SiExiste,
+            OPCION_GET => devolver_anterior = true,
+            _ if expiracion.is_some() => return Err(error_sintaxis()),
+            OPCION_KEEPTTL => expiracion = Some(Expiracion::Conservar),
+            OPCION_EX | OPCION_PX | OPCION_EXAT | OPCION_PXAT => {
+                let valor = tokens.get(posicion + 1).ok_or_else(error_sintaxis)?;
+                expiracion = Some(Expiracion::En(instante_expiracion(
+                    &tokens[0], &opcion, valor,
+                )?));
+                posicion += 1;
             }
-        },
+            _ => return Err(error_sintaxis()),
+        }
+        posicion += 1;
     }
+    Ok((
+        condicion,
+        devolver_anterior,
+        expiracion.unwrap_or(Expiracion::Persistir),
+    ))
+}
+
+/// Obtiene el instante de expiracion indicado por una opcion EX, PX, EXAT
+/// o PXAT
+///
+/// # Parámetros
+/// * `comando`: nombre del comando, para el mensaje de error
+/// * `opcion`: opcion en mayusculas
+/// * `valor`: valor de la opcion
+///
+/// # Retorna
+/// - instante de expiracion en milisegundos desde epoch, error de redis
+///   si el valor no es un entero positivo
+fn instante_expiracion(comando: &Token, opcion: &str, valor: &Token) -> Result<u64, DatoRedis> {
+    let error = || {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            format!(
+                "invalid expire time in '{}' command",
+                comando.to_lowercase()
+            ),
+        )
+    };
+    let valor = parsear_entero(&valor.texto())?;
+    let valor = u64::try_from(valor)
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(error)?;
+    let instante = match opcion {
+        OPCION_EX => valor
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(ahora_ms())),
+        OPCION_PX => valor.checked_add(ahora_ms()),
+        OPCION_EXAT => valor.checked_mul(1000),
+        OPCION_PXAT => Some(valor),
+        _ => return Err(error_sintaxis()),
+    };
+    instante
+        .filter(|ms| *ms <= i64::MAX as u64)
+        .ok_or_else(error)
+}
+
+/// Asigna a una clave existente la expiracion indicada
+fn aplicar_expiracion(
+    storage: &mut Storage,
+    clave: &[u8],
+    expiracion: Expiracion,
+) -> Result<(), DatoRedis> {
+    match expiracion {
+        Expiracion::Persistir => storage.set_expiracion(clave, None),
+        Expiracion::Conservar => Ok(()),
+        Expiracion::En(instante) => storage.set_expiracion(clave, Some(instante)),
+    }
+}
+
+fn parsear_entero(valor: &str) -> Result<i64, DatoRedis> {
+    valor.parse::<i64>().map_err(|_| {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "value is not an integer or out of range".to_string(),
+        )
+    })
+}
+
+fn parsear_float(valor: &str) -> Result<f64, DatoRedis> {
+    valor
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| {
+            DatoRedis::new_simple_error("ERR".to_string(), "value is not a valid float".to_string())
+        })
+}
+
+fn error_tipo() -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "WRONGTYPE".to_string(),
+        "Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_sintaxis() -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string())
 }
 
 /// Obtiene el valor numerico de un string, transformandolo en un
 /// posible indice
 ///
 /// # Parámetros
-/// * `index_str`: String a transformar
+/// * `index`: token a transformar
 ///
 /// # Retorna
 /// - Valor indexable en caso de exito, null en otro caso
-fn parse_index(index_str: String) -> Result<isize, DatoRedis> {
-    index_str.parse().map_err(|_| DatoRedis::new_null())
+fn parse_index(index: &Token) -> Result<isize, DatoRedis> {
+    index.parse().map_err(|_| DatoRedis::new_null())
 }
 
 /// Obtiene valores indexables para un string a partir de los
@@ -356,9 +745,9 @@ mod tests {
     #[test]
     fn test_set() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let result = set(&tokens, &storage).unwrap();
         if let DatoRedis::BulkString(bulk_string) = result {
@@ -371,16 +760,13 @@ mod tests {
     fn test_get() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
 
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["get", "key1"]
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
+        let tokens: Vec<Token> = vec!["get", "key1"].into_iter().map(Token::from).collect();
         let value = get(&tokens, &storage).unwrap();
 
         if let DatoRedis::BulkString(bulk_string) = value {
@@ -393,16 +779,13 @@ mod tests {
     fn test_del() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
 
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["del", "key1"]
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
+        let tokens: Vec<Token> = vec!["del", "key1"].into_iter().map(Token::from).collect();
         let res = del(&tokens.clone(), &storage).unwrap();
 
         if let DatoRedis::Integer(valor) = res {
@@ -417,15 +800,15 @@ mod tests {
     fn test_getdel() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
 
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["getdel", "key1"]
+        let tokens: Vec<Token> = vec!["getdel", "key1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = getdel(&tokens.clone(), &storage).unwrap();
 
@@ -440,14 +823,14 @@ mod tests {
     #[test]
     fn test_append() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
-        let tokens: Vec<String> = vec!["append", "key1", "value2"]
+        let tokens: Vec<Token> = vec!["append", "key1", "value2"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = append(&tokens.clone(), &storage).unwrap();
         if let DatoRedis::Integer(integer) = res {
@@ -463,13 +846,13 @@ mod tests {
     #[test]
     fn test_strlen() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens, &storage);
 
-        let res = strlen(&["strlen".to_string(), "key1".to_string()], &storage).unwrap();
+        let res = strlen(&obtener_tokens(&["strlen", "key1"]), &storage).unwrap();
         if let DatoRedis::Integer(long) = res {
             assert_eq!(long.valor(), 6);
         }
@@ -478,15 +861,15 @@ mod tests {
     #[test]
     fn test_substr_in_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "0", "3"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "0", "3"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -498,15 +881,15 @@ mod tests {
     #[test]
     fn test_substr_off_range() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "0", "7"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "0", "7"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -518,15 +901,15 @@ mod tests {
     #[test]
     fn test_substr_neg_index_init() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "-1", "3"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "-1", "3"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -538,15 +921,15 @@ mod tests {
     #[test]
     fn test_substr_neg_index_end() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "0", "-1"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "0", "-1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -558,15 +941,15 @@ mod tests {
     #[test]
     fn test_substr_index() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "0", "2"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "0", "2"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -578,15 +961,15 @@ mod tests {
     #[test]
     fn test_substr_ini_neg_and_fin_neg_index() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "value1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "value1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["substr", "key1", "-2", "-1"]
+        let tokens: Vec<Token> = vec!["substr", "key1", "-2", "-1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let res = substr(&tokens, &storage).unwrap();
 
@@ -598,20 +981,17 @@ mod tests {
     #[test]
     fn test_incr() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["incr", "key1"]
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
+        let tokens: Vec<Token> = vec!["incr", "key1"].into_iter().map(Token::from).collect();
         let res = incr(&tokens, &storage);
         assert!(res.is_ok());
 
-        let valor = get(&["get".to_string(), "key1".to_string()], &storage).unwrap();
+        let valor = get(&obtener_tokens(&["get", "key1"]), &storage).unwrap();
         if let DatoRedis::BulkString(valor_bstring) = valor {
             assert_eq!(valor_bstring.contenido(), "2");
         }
@@ -620,22 +1000,259 @@ mod tests {
     #[test]
     fn test_decr() {
         let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
-        let tokens_a_guardar: Vec<String> = vec!["set", "key1", "1"]
+        let tokens_a_guardar: Vec<Token> = vec!["set", "key1", "1"]
             .into_iter()
-            .map(|s| s.to_string())
+            .map(Token::from)
             .collect();
         let _ = set(&tokens_a_guardar, &storage);
 
-        let tokens: Vec<String> = vec!["incr", "key1"]
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
+        let tokens: Vec<Token> = vec!["incr", "key1"].into_iter().map(Token::from).collect();
         let res = decr(&tokens, &storage);
         assert!(res.is_ok());
 
-        let valor = get(&["get".to_string(), "key1".to_string()], &storage).unwrap();
+        let valor = get(&obtener_tokens(&["get", "key1"]), &storage).unwrap();
         if let DatoRedis::BulkString(valor_bstring) = valor {
             assert_eq!(valor_bstring.contenido(), "0");
         }
     }
+
+    fn obtener_tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn bulk(valor: &str) -> DatoRedis {
+        DatoRedis::new_bulk_string(valor.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_set_nx_xx_y_get() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert!(set(&obtener_tokens(&["set", "k", "a", "XX"]), &storage).is_err());
+        assert!(set(&obtener_tokens(&["set", "k", "a", "NX"]), &storage).is_ok());
+        assert!(set(&obtener_tokens(&["set", "k", "b", "NX"]), &storage).is_err());
+        assert_eq!(
+            set(&obtener_tokens(&["set", "k", "c", "XX", "GET"]), &storage).unwrap(),
+            bulk("a")
+        );
+        assert_eq!(
+            get(&obtener_tokens(&["get", "k"]), &storage).unwrap(),
+            bulk("c")
+        );
+        assert!(set(&obtener_tokens(&["set", "k", "c", "NX", "XX"]), &storage).is_err());
+        assert!(set(&obtener_tokens(&["set", "k", "c", "EX", "0"]), &storage).is_err());
+        assert!(
+            set(
+                &obtener_tokens(&["set", "k", "c", "EX", "1", "KEEPTTL"]),
+                &storage
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_set_con_expiracion_y_keepttl() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        set(&obtener_tokens(&["set", "k", "a", "PX", "20"]), &storage).unwrap();
+        set(&obtener_tokens(&["set", "k", "b", "KEEPTTL"]), &storage).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(40));
+        assert!(get(&obtener_tokens(&["get", "k"]), &storage).is_err());
+
+        // Sin KEEPTTL la clave pierde la expiracion
+        set(&obtener_tokens(&["set", "k", "a", "PX", "20"]), &storage).unwrap();
+        set(&obtener_tokens(&["set", "k", "b"]), &storage).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(40));
+        assert_eq!(
+            get(&obtener_tokens(&["get", "k"]), &storage).unwrap(),
+            bulk("b")
+        );
+    }
+
+    #[test]
+    fn test_expiracion_relativa_se_propaga_absoluta() {
+        let antes = ahora_ms();
+        let tokens =
+            con_expiracion_absoluta(&obtener_tokens(&["set", "k", "EX", "EX", "10", "NX"]));
+        assert_eq!(tokens[3], "PXAT");
+        let instante = tokens[4].parse::<u64>().unwrap();
+        assert!(instante >= antes + 10_000 && instante <= ahora_ms() + 10_000);
+        assert_eq!(tokens[5], "NX");
+
+        let getex = obtener_tokens(&["getex", "k", "PERSIST"]);
+        assert_eq!(con_expiracion_absoluta(&getex), getex);
+    }
+
+    /// Busca una clave que pertenezca al mismo slot que `key`, o a otro
+    /// distinto, segun `mismo_slot`
+    fn clave_vecina(storage: &Arc<RwLock<Storage>>, key: &str, mismo_slot: bool) -> String {
+        let guard = storage.read().unwrap();
+        let slot = guard.calculate_slot_for_key(key);
+        (0..)
+            .map(|i| format!("{key}{i}"))
+            .find(|candidata| (guard.calculate_slot_for_key(candidata) == slot) == mismo_slot)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mset_y_mget() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let vecina = clave_vecina(&storage, "key", true);
+        let ausente = clave_vecina(&storage, &vecina, true);
+        let lejana = clave_vecina(&storage, "key", false);
+        assert!(
+            mset(
+                &obtener_tokens(&["mset", "key", "a", &vecina, "b"]),
+                &storage
+            )
+            .is_ok()
+        );
+        assert!(mset(&obtener_tokens(&["mset", "key", "a", &vecina]), &storage).is_err());
+        assert!(matches!(
+            mset(&obtener_tokens(&["mset", "key", "a", &lejana, "b"]), &storage),
+            Err(DatoRedis::SimpleError(e)) if e.tipo() == "CROSSSLOT"
+        ));
+
+        let mut esperado = Arrays::new();
+        esperado.append(bulk("a"));
+        esperado.append(DatoRedis::new_null());
+        esperado.append(bulk("b"));
+        assert_eq!(
+            mget(
+                &obtener_tokens(&["mget", "key", &ausente, &vecina]),
+                &storage
+            )
+            .unwrap(),
+            DatoRedis::new_array_con_contenido(esperado)
+        );
+        assert!(mget(&obtener_tokens(&["mget", "key", &lejana]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_incrby_decrby_y_overflow() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(
+            incrby(&obtener_tokens(&["incrby", "k", "10"]), &storage).unwrap(),
+            DatoRedis::new_integer(10)
+        );
+        assert_eq!(
+            decrby(&obtener_tokens(&["decrby", "k", "15"]), &storage).unwrap(),
+            DatoRedis::new_integer(-5)
+        );
+        assert!(incrby(&obtener_tokens(&["incrby", "k", "uno"]), &storage).is_err());
+
+        set(
+            &obtener_tokens(&["set", "max", &i64::MAX.to_string()]),
+            &storage,
+        )
+        .unwrap();
+        assert!(incr(&obtener_tokens(&["incr", "max"]), &storage).is_err());
+        assert!(
+            decrby(
+                &obtener_tokens(&["decrby", "nueva", &i64::MIN.to_string()]),
+                &storage
+            )
+            .is_err()
+        );
+        // Un incremento fallido no crea la clave
+        assert!(get(&obtener_tokens(&["get", "nueva"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_incrbyfloat() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(
+            incrbyfloat(&obtener_tokens(&["incrbyfloat", "k", "10.5"]), &storage).unwrap(),
+            bulk("10.5")
+        );
+        assert_eq!(
+            incrbyfloat(&obtener_tokens(&["incrbyfloat", "k", "-0.5"]), &storage).unwrap(),
+            bulk("10")
+        );
+        assert!(incrbyfloat(&obtener_tokens(&["incrbyfloat", "k", "inf"]), &storage).is_err());
+        assert!(incrbyfloat(&obtener_tokens(&["incrbyfloat", "k", "x"]), &storage).is_err());
+        assert_eq!(
+            incrby(&obtener_tokens(&["incrby", "k", "1"]), &storage).unwrap(),
+            DatoRedis::new_integer(11)
+        );
+    }
+
+    #[test]
+    fn test_setrange_y_getex() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        assert_eq!(
+            setrange(&obtener_tokens(&["setrange", "k", "0", ""]), &storage).unwrap(),
+            DatoRedis::new_integer(0)
+        );
+        set(&obtener_tokens(&["set", "k", "Hello World"]), &storage).unwrap();
+        assert_eq!(
+            setrange(&obtener_tokens(&["setrange", "k", "6", "Redis"]), &storage).unwrap(),
+            DatoRedis::new_integer(11)
+        );
+        assert_eq!(
+            setrange(&obtener_tokens(&["setrange", "p", "2", "x"]), &storage).unwrap(),
+            DatoRedis::new_integer(3)
+        );
+        assert_eq!(
+            get(&obtener_tokens(&["get", "p"]), &storage).unwrap(),
+            bulk("\0\0x")
+        );
+        assert!(setrange(&obtener_tokens(&["setrange", "k", "-1", "x"]), &storage).is_err());
+
+        assert_eq!(
+            getex(&obtener_tokens(&["getex", "k", "PX", "20"]), &storage).unwrap(),
+            bulk("Hello Redis")
+        );
+        std::thread::sleep(std::time::Duration::from_millis(40));
+        assert!(getex(&obtener_tokens(&["getex", "k"]), &storage).is_err());
+    }
+
+    #[test]
+    fn test_tipo_incorrecto() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        storage
+            .write()
+            .unwrap()
+            .set("lista", DatoRedis::new_array())
+            .unwrap();
+        assert!(matches!(
+            get(&obtener_tokens(&["get", "lista"]), &storage),
+            Err(DatoRedis::SimpleError(_))
+        ));
+        assert!(incrby(&obtener_tokens(&["incrby", "lista", "1"]), &storage).is_err());
+        assert!(set(&obtener_tokens(&["set", "lista", "x", "GET"]), &storage).is_err());
+        // SET sin GET reemplaza valores de cualquier tipo
+        assert!(set(&obtener_tokens(&["set", "lista", "x"]), &storage).is_ok());
+    }
+
+    #[test]
+    fn test_valores_binarios() {
+        let storage = Arc::new(RwLock::new(Storage::new(RANGE)));
+        let binario = Token::from(&[0xff, 0x00, 0xc3][..]);
+        let mut tokens = obtener_tokens(&["append", "k"]);
+        tokens.push(binario.clone());
+        assert_eq!(
+            append(&tokens, &storage).unwrap(),
+            DatoRedis::new_integer(3)
+        );
+        let mut tokens = obtener_tokens(&["append", "k"]);
+        tokens.push(binario);
+        assert_eq!(
+            append(&tokens, &storage).unwrap(),
+            DatoRedis::new_integer(6)
+        );
+        let mut tokens = obtener_tokens(&["setrange", "k", "1"]);
+        tokens.push(Token::from(&[0xfe][..]));
+        assert_eq!(
+            setrange(&tokens, &storage).unwrap(),
+            DatoRedis::new_integer(6)
+        );
+
+        let Ok(DatoRedis::BulkString(valor)) = get(&obtener_tokens(&["get", "k"]), &storage) else {
+            panic!("se esperaba un bulk string");
+        };
+        assert_eq!(valor.bytes(), [0xff, 0xfe, 0xc3, 0xff, 0x00, 0xc3]);
+        assert_eq!(
+            strlen(&obtener_tokens(&["strlen", "k"]), &storage).unwrap(),
+            DatoRedis::new_integer(6)
+        );
+    }
 }
@@ -1,6 +1,73 @@
 // comandos_persistencia.rs
 pub const CMD_SAVE: &str = "SAVE";
 
+// comandos_server.rs
+pub const CMD_INFO: &str = "INFO";
+pub const CMD_SLOWLOG: &str = "SLOWLOG";
+pub const CMD_SLOWLOG_GET: &str = "GET";
+pub const CMD_SLOWLOG_LEN: &str = "LEN";
+pub const CMD_SLOWLOG_RESET: &str = "RESET";
+pub const CMD_LATENCY: &str = "LATENCY";
+pub const CMD_LATENCY_LATEST: &str = "LATEST";
+pub const CMD_LATENCY_HISTORY: &str = "HISTORY";
+pub const CMD_LATENCY_RESET: &str = "RESET";
+pub const CMD_TIME: &str = "TIME";
+
+// comandos_command.rs
+pub const CMD_COMMAND: &str = "COMMAND";
+pub const CMD_COMMAND_COUNT: &str = "COUNT";
+pub const CMD_COMMAND_INFO: &str = "INFO";
+pub const CMD_COMMAND_DOCS: &str = "DOCS";
+pub const CMD_COMMAND_GETKEYS: &str = "GETKEYS";
+
+// comandos_conexion.rs
+pub const CMD_PING: &str = "PING";
+pub const CMD_ECHO: &str = "ECHO";
+pub const CMD_QUIT: &str = "QUIT";
+pub const CMD_RESET: &str = "RESET";
+
+// comandos_config.rs
+pub const CMD_CONFIG: &str = "CONFIG";
+pub const CMD_CONFIG_GET: &str = "GET";
+pub const CMD_CONFIG_SET: &str = "SET";
+pub const CMD_CONFIG_REWRITE: &str = "REWRITE";
+pub const CMD_CONFIG_RESETSTAT: &str = "RESETSTAT";
+
+// comandos_client.rs
+pub const CMD_CLIENT: &str = "CLIENT";
+pub const CMD_CLIENT_ID: &str = "ID";
+pub const CMD_CLIENT_SETNAME: &str = "SETNAME";
+pub const CMD_CLIENT_GETNAME: &str = "GETNAME";
+pub const CMD_CLIENT_LIST: &str = "LIST";
+pub const CMD_CLIENT_INFO: &str = "INFO";
+pub const CMD_CLIENT_KILL: &str = "KILL";
+pub const CMD_CLIENT_PAUSE: &str = "PAUSE";
+pub const CMD_CLIENT_UNPAUSE: &str = "UNPAUSE";
+pub const CMD_CLIENT_NO_EVICT: &str = "NO-EVICT";
+pub const CMD_CLIENT_TRACKING: &str = "TRACKING";
+pub const CMD_CLIENT_CACHING: &str = "CACHING";
+pub const CMD_CLIENT_GETREDIRECT: &str = "GETREDIRECT";
+
+// comandos_monitor.rs
+pub const CMD_MONITOR: &str = "MONITOR";
+
+// comandos_scripting.rs
+pub const CMD_EVAL: &str = "EVAL";
+pub const CMD_EVALSHA: &str = "EVALSHA";
+pub const CMD_SCRIPT: &str = "SCRIPT";
+pub const CMD_SCRIPT_LOAD: &str = "LOAD";
+pub const CMD_SCRIPT_EXISTS: &str = "EXISTS";
+pub const CMD_SCRIPT_FLUSH: &str = "FLUSH";
+
+// comandos_funciones.rs
+pub const CMD_FUNCTION: &str = "FUNCTION";
+pub const CMD_FUNCTION_LOAD: &str = "LOAD";
+pub const CMD_FUNCTION_DELETE: &str = "DELETE";
+pub const CMD_FUNCTION_FLUSH: &str = "FLUSH";
+pub const CMD_FUNCTION_LIST: &str = "LIST";
+pub const CMD_FCALL: &str = "FCALL";
+pub const CMD_FCALL_RO: &str = "FCALL_RO";
+
 // handshake.rs
 pub const CMD_HELLO: &str = "HELLO";
 pub const CMD_AUTH: &str = "AUTH";
@@ -33,6 +100,32 @@ pub const CMD_SUBSTR: &str = "SUBSTR";
 pub const CMD_GETRANGE: &str = "GETRANGE";
 pub const CMD_INCR: &str = "INCR";
 pub const CMD_DECR: &str = "DECR";
+pub const CMD_INCRBY: &str = "INCRBY";
+pub const CMD_DECRBY: &str = "DECRBY";
+pub const CMD_INCRBYFLOAT: &str = "INCRBYFLOAT";
+pub const CMD_MSET: &str = "MSET";
+pub const CMD_MGET: &str = "MGET";
+pub const CMD_SETRANGE: &str = "SETRANGE";
+pub const CMD_GETEX: &str = "GETEX";
+
+// Comandos bitmap
+pub const CMD_SETBIT: &str = "SETBIT";
+pub const CMD_GETBIT: &str = "GETBIT";
+pub const CMD_BITCOUNT: &str = "BITCOUNT";
+pub const CMD_BITPOS: &str = "BITPOS";
+pub const CMD_BITOP: &str = "BITOP";
+pub const CMD_BITFIELD: &str = "BITFIELD";
+
+// Comandos hyperloglog
+pub const CMD_PFADD: &str = "PFADD";
+pub const CMD_PFCOUNT: &str = "PFCOUNT";
+pub const CMD_PFMERGE: &str = "PFMERGE";
+
+// Comandos geoespaciales
+pub const CMD_GEOADD: &str = "GEOADD";
+pub const CMD_GEODIST: &str = "GEODIST";
+pub const CMD_GEOPOS: &str = "GEOPOS";
+pub const CMD_GEOSEARCH: &str = "GEOSEARCH";
 
 // Comandos listas
 pub const CMD_LINSERT: &str = "LINSERT";
@@ -47,6 +140,14 @@ pub const CMD_LREM: &str = "LREM";
 pub const CMD_LTRIM: &str = "LTRIM";
 pub const CMD_LINDEX: &str = "LINDEX";
 pub const CMD_LMOVE: &str = "LMOVE";
+pub const CMD_BLPOP: &str = "BLPOP";
+pub const CMD_BRPOP: &str = "BRPOP";
+pub const CMD_BLMOVE: &str = "BLMOVE";
+pub const CMD_LPOS: &str = "LPOS";
+pub const CMD_LMPOP: &str = "LMPOP";
+pub const CMD_LPUSHX: &str = "LPUSHX";
+pub const CMD_RPUSHX: &str = "RPUSHX";
+pub const CMD_RPOPLPUSH: &str = "RPOPLPUSH";
 
 // Comandos set
 pub const CMD_SADD: &str = "SADD";
@@ -54,5 +155,41 @@ pub const CMD_SCARD: &str = "SCARD";
 pub const CMD_SISMEMBER: &str = "SISMEMBER";
 pub const CMD_SREM: &str = "SREM";
 pub const CMD_SMEMBERS: &str = "SMEMBERS";
+pub const CMD_SSCAN: &str = "SSCAN";
+
+// Comandos stream
+pub const CMD_XADD: &str = "XADD";
+pub const CMD_XLEN: &str = "XLEN";
+pub const CMD_XRANGE: &str = "XRANGE";
+pub const CMD_XREVRANGE: &str = "XREVRANGE";
+pub const CMD_XTRIM: &str = "XTRIM";
+pub const CMD_XDEL: &str = "XDEL";
+pub const CMD_XREAD: &str = "XREAD";
+pub const CMD_XGROUP: &str = "XGROUP";
+pub const CMD_XREADGROUP: &str = "XREADGROUP";
+pub const CMD_XACK: &str = "XACK";
+pub const CMD_XPENDING: &str = "XPENDING";
+pub const CMD_XCLAIM: &str = "XCLAIM";
+pub const CMD_XINFO: &str = "XINFO";
+
+// Comandos keyspace
+pub const CMD_DUMP: &str = "DUMP";
+pub const CMD_RESTORE: &str = "RESTORE";
+pub const CMD_MEMORY: &str = "MEMORY";
+pub const CMD_MEMORY_USAGE: &str = "USAGE";
+pub const CMD_OBJECT: &str = "OBJECT";
+pub const CMD_OBJECT_FREQ: &str = "FREQ";
+pub const CMD_OBJECT_IDLETIME: &str = "IDLETIME";
+pub const CMD_EXISTS: &str = "EXISTS";
+pub const CMD_TYPE: &str = "TYPE";
+pub const CMD_RENAME: &str = "RENAME";
+pub const CMD_RENAMENX: &str = "RENAMENX";
+pub const CMD_COPY: &str = "COPY";
+pub const CMD_UNLINK: &str = "UNLINK";
+pub const CMD_DBSIZE: &str = "DBSIZE";
+pub const CMD_FLUSHALL: &str = "FLUSHALL";
+pub const CMD_RANDOMKEY: &str = "RANDOMKEY";
+pub const CMD_KEYS: &str = "KEYS";
+pub const CMD_SCAN: &str = "SCAN";
 
 pub const OPERACION_EXITOSA: &str = "OK";
@@ -0,0 +1,228 @@
+//! Este modulo contiene la codificacion geohash usada por los indices
+//! geoespaciales. Una posicion se representa con 26 bits de latitud y 26 de
+//! longitud intercalados en un entero de 52 bits, de modo que las
+//! posiciones cercanas comparten prefijo y quedan proximas al ordenarlas
+//! por ese entero
+use std::collections::BTreeSet;
+
+pub const LONGITUD_MINIMA: f64 = -180.0;
+pub const LONGITUD_MAXIMA: f64 = 180.0;
+/// Limites de latitud de la proyeccion de Mercator (EPSG:900913)
+pub const LATITUD_MINIMA: f64 = -85.05112878;
+pub const LATITUD_MAXIMA: f64 = 85.05112878;
+/// Bits de cada coordenada en un geohash
+const PASOS: u32 = 26;
+/// Radio de la tierra en metros, el mismo que usa redis
+const RADIO_TIERRA_M: f64 = 6372797.560856;
+
+/// Codifica una posicion como geohash de 52 bits
+///
+/// # Parámetros
+/// * `longitud`, `latitud`: posicion, dentro de los limites validos
+pub fn codificar(longitud: f64, latitud: f64) -> u64 {
+    intercalar(
+        indice_celda(latitud, LATITUD_MINIMA, LATITUD_MAXIMA, PASOS),
+        indice_celda(longitud, LONGITUD_MINIMA, LONGITUD_MAXIMA, PASOS),
+    )
+}
+
+/// Obtiene la posicion del centro de la celda de un geohash
+///
+/// # Retorna
+/// - longitud y latitud del centro de la celda
+pub fn decodificar(hash: u64) -> (f64, f64) {
+    let (indice_latitud, indice_longitud) = separar(hash);
+    let centro = |indice: u32, minimo: f64, maximo: f64| {
+        let ancho = (maximo - minimo) / (1u64 << PASOS) as f64;
+        (minimo + (indice as f64 + 0.5) * ancho).clamp(minimo, maximo)
+    };
+    (
+        centro(indice_longitud, LONGITUD_MINIMA, LONGITUD_MAXIMA),
+        centro(indice_latitud, LATITUD_MINIMA, LATITUD_MAXIMA),
+    )
+}
+
+/// Distancia en metros entre dos posiciones, con la formula del haversine
+pub fn distancia(longitud1: f64, latitud1: f64, longitud2: f64, latitud2: f64) -> f64 {
+    let (latitud1, latitud2) = (latitud1.to_radians(), latitud2.to_radians());
+    let u = ((latitud2 - latitud1) / 2.0).sin();
+    let v = ((longitud2 - longitud1).to_radians() / 2.0).sin();
+    let a = u * u + latitud1.cos() * latitud2.cos() * v * v;
+    2.0 * RADIO_TIERRA_M * a.sqrt().asin()
+}
+
+/// Distancia en metros entre dos latitudes, sobre un meridiano
+pub fn distancia_latitud(latitud1: f64, latitud2: f64) -> f64 {
+    RADIO_TIERRA_M * (latitud2.to_radians() - latitud1.to_radians()).abs()
+}
+
+/// Obtiene cuantos grados de longitud y de latitud hay que recorrer desde
+/// una posicion para cubrir la distancia indicada en cada eje
+///
+/// # Parámetros
+/// * `latitud`: latitud de la posicion
+/// * `metros_longitud`, `metros_latitud`: distancia a cubrir en cada eje
+pub fn extension_en_grados(latitud: f64, metros_longitud: f64, metros_latitud: f64) -> (f64, f64) {
+    let grados_latitud = (metros_latitud / RADIO_TIERRA_M).to_degrees();
+    // Los grados de longitud son mas cortos cuanto mas lejos del ecuador
+    let latitud_extrema = (latitud.abs() + grados_latitud).min(90.0).to_radians();
+    let metros_por_radian = RADIO_TIERRA_M * latitud_extrema.cos();
+    let grados_longitud = if metros_por_radian <= 0.0 {
+        LONGITUD_MAXIMA
+    } else {
+        (metros_longitud / metros_por_radian).to_degrees()
+    };
+    (grados_longitud.min(LONGITUD_MAXIMA), grados_latitud)
+}
+
+/// Calcula los rangos de geohashes que cubren el area rectangular
+/// alrededor de una posicion. Se elige el tamaño de celda mas chico que no
+/// sea menor a la extension buscada, por lo que alcanzan a lo sumo 3
+/// celdas por eje
+///
+/// # Parámetros
+/// * `longitud`, `latitud`: centro del area
+/// * `grados_longitud`, `grados_latitud`: extension del area hacia cada
+///   lado del centro
+///
+/// # Retorna
+/// - rangos `[desde, hasta)` de geohashes, ordenados y sin solaparse
+pub fn rangos_a_buscar(
+    longitud: f64,
+    latitud: f64,
+    grados_longitud: f64,
+    grados_latitud: f64,
+) -> Vec<(u64, u64)> {
+    let rango_latitud = LATITUD_MAXIMA - LATITUD_MINIMA;
+    let rango_longitud = LONGITUD_MAXIMA - LONGITUD_MINIMA;
+    let tamano_celda = |rango: f64, pasos: u32| rango / (1u64 << pasos) as f64;
+    let mut pasos = PASOS;
+    while pasos > 1
+        && (tamano_celda(rango_latitud, pasos) < grados_latitud
+            || tamano_celda(rango_longitud, pasos) < grados_longitud)
+    {
+        pasos -= 1;
+    }
+    let celdas = 1i64 << pasos;
+
+    let latitudes = indice_celda(
+        (latitud - grados_latitud).max(LATITUD_MINIMA),
+        LATITUD_MINIMA,
+        LATITUD_MAXIMA,
+        pasos,
+    )
+        ..=indice_celda(
+            (latitud + grados_latitud).min(LATITUD_MAXIMA),
+            LATITUD_MINIMA,
+            LATITUD_MAXIMA,
+            pasos,
+        );
+    // La longitud se extiende mas alla de ±180 y se ajusta al rango valido
+    let indice_longitud =
+        |grados: f64| ((grados - LONGITUD_MINIMA) / rango_longitud * celdas as f64).floor() as i64;
+    let desde = indice_longitud(longitud - grados_longitud);
+    let hasta = indice_longitud(longitud + grados_longitud).min(desde + celdas - 1);
+
+    let desplazamiento = 2 * (PASOS - pasos);
+    let mut bases = BTreeSet::new();
+    for indice_latitud in latitudes {
+        for indice_longitud in desde..=hasta {
+            let indice_longitud = indice_longitud.rem_euclid(celdas) as u32;
+            bases.insert(intercalar(indice_latitud, indice_longitud) << desplazamiento);
+        }
+    }
+
+    let mut rangos: Vec<(u64, u64)> = Vec::new();
+    for base in bases {
+        let hasta = base + (1u64 << desplazamiento);
+        match rangos.last_mut() {
+            Some(ultimo) if ultimo.1 == base => ultimo.1 = hasta,
+            _ => rangos.push((base, hasta)),
+        }
+    }
+    rangos
+}
+
+/// Indice de la celda que contiene un valor al dividir `[minimo, maximo]`
+/// en 2^pasos celdas
+fn indice_celda(valor: f64, minimo: f64, maximo: f64, pasos: u32) -> u32 {
+    let celdas = (1u64 << pasos) as f64;
+    let indice = ((valor - minimo) / (maximo - minimo) * celdas).floor();
+    indice.clamp(0.0, celdas - 1.0) as u32
+}
+
+/// Intercala los bits de ambos indices: la latitud en las posiciones pares
+/// y la longitud en las impares
+fn intercalar(latitud: u32, longitud: u32) -> u64 {
+    (0..PASOS).fold(0u64, |hash, bit| {
+        hash | (((latitud as u64 >> bit) & 1) << (2 * bit))
+            | (((longitud as u64 >> bit) & 1) << (2 * bit + 1))
+    })
+}
+
+/// Separa un geohash en sus indices de latitud y longitud
+fn separar(hash: u64) -> (u32, u32) {
+    (0..PASOS).fold((0u32, 0u32), |(latitud, longitud), bit| {
+        (
+            latitud | (((hash >> (2 * bit)) & 1) as u32) << bit,
+            longitud | (((hash >> (2 * bit + 1)) & 1) as u32) << bit,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_01_codificar_y_decodificar() {
+        // Valores de referencia de redis para Palermo
+        let hash = codificar(13.361389, 38.115556);
+        assert_eq!(hash, 3479099956230698);
+        let (longitud, latitud) = decodificar(hash);
+        assert!((longitud - 13.361389).abs() < 1e-5);
+        assert!((latitud - 38.115556).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_02_distancia_entre_ciudades() {
+        let palermo = decodificar(codificar(13.361389, 38.115556));
+        let catania = decodificar(codificar(15.087269, 37.502669));
+        let metros = distancia(palermo.0, palermo.1, catania.0, catania.1);
+        assert!((metros - 166274.1516).abs() < 0.01, "{metros}");
+        assert_eq!(distancia_latitud(10.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_03_rangos_cubren_el_area() {
+        let (grados_longitud, grados_latitud) = extension_en_grados(38.0, 200_000.0, 200_000.0);
+        let rangos = rangos_a_buscar(15.0, 37.0, grados_longitud, grados_latitud);
+        assert!(!rangos.is_empty() && rangos.len() <= 9);
+        for (longitud, latitud) in [(13.361389, 38.115556), (15.087269, 37.502669)] {
+            let hash = codificar(longitud, latitud);
+            assert!(
+                rangos
+                    .iter()
+                    .any(|(desde, hasta)| (*desde..*hasta).contains(&hash))
+            );
+        }
+        let lejos = codificar(-58.38, -34.6);
+        assert!(
+            !rangos
+                .iter()
+                .any(|(desde, hasta)| (*desde..*hasta).contains(&lejos))
+        );
+    }
+
+    #[test]
+    fn test_04_rangos_cruzan_el_antimeridiano() {
+        let (grados_longitud, grados_latitud) = extension_en_grados(0.0, 50_000.0, 50_000.0);
+        let rangos = rangos_a_buscar(179.9, 0.0, grados_longitud, grados_latitud);
+        let del_otro_lado = codificar(-179.9, 0.0);
+        assert!(
+            rangos
+                .iter()
+                .any(|(desde, hasta)| (*desde..*hasta).contains(&del_otro_lado))
+        );
+    }
+}
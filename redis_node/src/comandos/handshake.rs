@@ -3,12 +3,13 @@ use std::{
     sync::{Arc, RwLock, RwLockReadGuard},
 };
 
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::{map_reply::MapReply, traits::DatoRedis};
 
 use crate::{client_struct::client::Client, comandos::utils::assert_correct_arguments_quantity};
 
 pub fn hello(
-    vec: &[String],
+    vec: &[Token],
     client: &mut Arc<RwLock<Client>>,
     users: &HashMap<String, String>,
 ) -> Result<DatoRedis, DatoRedis> {
@@ -30,6 +31,7 @@ pub fn hello(
             DatoRedis::new_simple_error("HANDSHAKE".to_string(), "client lock error".to_string())
         })?;
         guard.set_handshake(true);
+        guard.set_usuario("default".to_string());
         Ok(reply)
     } else if vec.len() == 5 {
         if vec[1] != "3" {
@@ -110,23 +112,28 @@ fn obtener_id(guard: &RwLockReadGuard<Client>) -> Result<i64, DatoRedis> {
 }
 
 pub fn auth(
-    vec: &[String],
+    vec: &[Token],
     client: &mut Arc<RwLock<Client>>,
     users_dict: &HashMap<String, String>,
 ) -> Result<DatoRedis, DatoRedis> {
-    assert_correct_arguments_quantity("AUTH".to_string(), 3, vec.len())?;
-    if users_dict.contains_key(&vec[1]) && users_dict[&vec[1]] == vec[2] {
+    assert_correct_arguments_quantity("AUTH", 3, vec.len())?;
+    let usuario = vec[1].texto();
+    if users_dict
+        .get(&*usuario)
+        .is_some_and(|password| vec[2] == *password)
+    {
         let mut guard = client.write().map_err(|_| {
             DatoRedis::new_simple_error("HANDSHAKE".to_string(), "client lock error".to_string())
         })?;
         guard.set_handshake(true);
+        guard.set_usuario(usuario.into_owned());
         Ok(DatoRedis::new_bulk_string(
             "User authenticated".to_string(),
         )?)
     } else {
         Err(DatoRedis::new_simple_error(
             "AUTH".to_string(),
-            format!("Authentication failed for user: {}", vec[1]),
+            format!("Authentication failed for user: {usuario}"),
         ))
     }
 }
@@ -198,7 +205,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["HELLO", "2", "AUTH", DEFAULT_USER, DEFAULT_PASSWORD]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         assert!(hello(&v, &mut cli, &users_map()).is_err());
     }
@@ -208,7 +215,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["HELLO", "3", "FOO", DEFAULT_USER, DEFAULT_PASSWORD]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         let res = hello(&v, &mut cli, &users_map());
         assert!(res.is_err());
@@ -219,7 +226,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["HELLO", "3", "AUTH", "bad", "creds"]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         assert!(hello(&v, &mut cli, &users_map()).is_err());
     }
@@ -229,7 +236,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["HELLO", "3", "AUTH", DEFAULT_USER, DEFAULT_PASSWORD]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         let res = hello(&v, &mut cli, &users_map());
         assert!(res.is_ok());
@@ -241,7 +248,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["HELLO", "3", "EXTRA"]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         assert!(hello(&v, &mut cli, &users_map()).is_err());
     }
@@ -260,7 +267,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["AUTH", "rust", "badpass"]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         assert!(auth(&v, &mut cli, &users_map()).is_err());
     }
@@ -270,7 +277,7 @@ mod tests {
         let mut cli = make_client();
         let v = vec!["AUTH", "rust", "secret"]
             .into_iter()
-            .map(String::from)
+            .map(Token::from)
             .collect::<Vec<_>>();
         let res = auth(&v, &mut cli, &users_map());
         assert!(res.is_ok());
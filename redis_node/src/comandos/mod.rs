@@ -1,9 +1,26 @@
+pub mod comandos_bitmap;
+pub mod comandos_bloqueantes;
+pub mod comandos_client;
+pub mod comandos_command;
+pub mod comandos_conexion;
+pub mod comandos_config;
+pub mod comandos_funciones;
+pub mod comandos_geo;
+pub mod comandos_hyperloglog;
+pub mod comandos_keyspace;
 pub mod comandos_list;
+pub mod comandos_monitor;
 pub mod comandos_pub_sub;
+pub mod comandos_scripting;
+pub mod comandos_server;
 pub mod comandos_set;
+pub mod comandos_stream;
+pub mod comandos_stream_grupos;
 pub mod comandos_string;
 pub mod const_cmd;
+pub mod geohash;
 pub mod handshake;
 pub mod procesamiento_comandos;
 pub mod pub_sub_struct;
+pub mod tabla_comandos;
 pub mod utils;
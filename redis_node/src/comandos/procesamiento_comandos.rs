@@ -1,34 +1,60 @@
 //! Este módulo contiene las funciones de procesamiento de comandos del nodo
 
 use super::{
+    comandos_bloqueantes::es_comando_bloqueante,
+    comandos_funciones::ejecutar_fcall,
+    comandos_keyspace::restore_con_expiracion_absoluta,
+    comandos_scripting::ejecutar_eval,
+    comandos_stream::xadd_con_id,
+    comandos_string::con_expiracion_absoluta,
     const_cmd::{
-        CMD_GET, CMD_LINDEX, CMD_LLEN, CMD_LRANGE, CMD_PUBLISH, CMD_SISMEMBER, CMD_SMEMBERS,
-        CMD_STRLEN,
+        CMD_CLIENT, CMD_CLIENT_CACHING, CMD_EVAL, CMD_EVALSHA, CMD_FCALL, CMD_FCALL_RO, CMD_GETEX,
+        CMD_PUBLISH, CMD_QUIT, CMD_RESET, CMD_RESTORE, CMD_SET, CMD_XADD,
     },
     pub_sub_struct::{BrokerCommand, PubSubBroker},
-    utils::{do_handshake, leer_comando, send_msj, send_msj_to_logger},
+    tabla_comandos::{FLAG_READONLY, buscar_comando},
+    utils::{
+        do_handshake, get_storage_write_lock, leer_comando, puede_aumentar_memoria, send_msj,
+        send_msj_to_logger,
+    },
 };
 use crate::comandos::const_cmd::CMD_SPUBLISH;
 use crate::internal_protocol::moved::Moved;
 use crate::{
     client_struct::client::Client,
     internal_protocol::redis_cmd::RedisCMD,
+    latencia::{EVENTO_AOF, EVENTO_COMANDO},
     log_msj::log_mensajes::log_cli_send_cmd_info,
     node_role::NodeRole,
+    notificaciones::eventos_de_comando,
     persistence::persistencia::guardar_operacion,
-    utils::utils_functions::{obtener_fn_normal, obtener_stream},
+    tracking::{CANAL_INVALIDACION, claves_leidas},
+    utils::utils_functions::{CommandFunction, obtener_fn_nodo, obtener_fn_normal, obtener_stream},
 };
 use crate::{
     cluster::node_message::{InnerMensajeNode, TipoMensajeNode},
     node::Node,
 };
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::{simple_error::SimpleError, traits::DatoRedis};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     fs::File,
     sync::{Arc, RwLock, atomic::Ordering, mpsc::Sender},
+    time::{Duration, Instant},
 };
 
+/// Error para un comando que un cliente suscripto no puede ejecutar
+fn error_en_modo_pubsub(comando: &str) -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        format!(
+            "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+            comando.to_lowercase()
+        ),
+    )
+}
+
 impl Node {
     /// Procesa los comandos de un cliente mientras los haya
     ///
@@ -74,7 +100,7 @@ impl Node {
     fn procesar_un_comando(
         &self,
         aofile: &Option<Arc<RwLock<File>>>,
-        comando_tokens: &[String],
+        comando_tokens: &[Token],
         client: &Arc<RwLock<Client>>,
         tx_connect: Sender<TipoMensajeNode>,
         users: &HashMap<String, String>,
@@ -88,25 +114,87 @@ impl Node {
             return;
         }
 
+        self.notificar_monitores(client, comando_tokens);
+
         if !self.handshake_realizado(client) {
             do_handshake(client, comando_tokens, &self.logger, users);
             return;
         }
 
         self.log_comando_cliente(client, comando_tokens);
+        self.estadisticas.registrar_comando();
+        if let Ok(mut cli) = client.write() {
+            cli.registrar_comando(comando_tokens[0].to_lowercase());
+        }
 
+        let inicio = Instant::now();
+        self.despachar_comando(aofile, comando_tokens, client, tx_connect);
+        self.finalizar_comando_tracking(client, comando_tokens);
+        // El tiempo que un comando bloqueante pasa esperando no es tiempo
+        // de ejecucion
+        if !es_comando_bloqueante(&comando_tokens[0].to_uppercase(), comando_tokens) {
+            self.registrar_duracion_comando(client, comando_tokens, inicio.elapsed());
+        }
+    }
+
+    /// Ejecuta un comando de un cliente que ya realizo el handshake,
+    /// delegando segun se trate de un comando pub/sub, del nodo o general
+    ///
+    /// # Parametros
+    /// * `aofile`: file de persistencia
+    /// * `comando_tokens`: secuencia de tokens que conforman el comando
+    /// * `client`: cliente cuyo comando se procesa
+    /// * `tx_connect`: canal hacia el hilo de procesamiento del cluster
+    fn despachar_comando(
+        &self,
+        aofile: &Option<Arc<RwLock<File>>>,
+        comando_tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+        tx_connect: Sender<TipoMensajeNode>,
+    ) {
         if self.procesar_pubsub_y_reenviar_si_es_publish(comando_tokens, client, &tx_connect) {
             return;
         }
 
-        if self.cliente_en_modo_pubsub(client) {
+        let comando = comando_tokens[0].to_uppercase();
+        if self.cliente_en_modo_pubsub(client)
+            && !buscar_comando(&comando).is_some_and(|info| info.es_permitido_en_pubsub())
+        {
+            send_msj(
+                client.clone(),
+                error_en_modo_pubsub(&comando_tokens[0].texto()),
+                &self.logger,
+            );
             return;
         }
 
-        let comando = comando_tokens[0].to_uppercase();
+        if let Some(funcion) = obtener_fn_nodo(&comando) {
+            let respuesta = funcion(self, comando_tokens, client).unwrap_or_else(|e| e);
+            send_msj(client.clone(), respuesta, &self.logger);
+            if comando == CMD_QUIT {
+                self.cerrar_tras_responder(client);
+            }
+            return;
+        }
         self.procesar_comando_general(&comando, comando_tokens, client, aofile, tx_connect);
     }
 
+    /// Registra la duracion de un comando en el slowlog y en el monitor de
+    /// latencia
+    fn registrar_duracion_comando(
+        &self,
+        client: &Arc<RwLock<Client>>,
+        comando_tokens: &[Token],
+        duracion: Duration,
+    ) {
+        self.latencia.registrar(EVENTO_COMANDO, duracion);
+        if let Ok(cli) = client.read() {
+            let nombre = cli.get_nombre().map(String::as_str).unwrap_or("");
+            self.slowlog
+                .registrar(duracion, comando_tokens, &cli.client_id(), nombre);
+        }
+    }
+
     fn handshake_realizado(&self, client: &Arc<RwLock<Client>>) -> bool {
         client.read().map(|c| c.get_handshake()).unwrap_or(false)
     }
@@ -115,7 +203,7 @@ impl Node {
         client.read().map(|c| c.get_modo_pub_sub()).unwrap_or(false)
     }
 
-    fn log_comando_cliente(&self, client: &Arc<RwLock<Client>>, comando_tokens: &[String]) {
+    fn log_comando_cliente(&self, client: &Arc<RwLock<Client>>, comando_tokens: &[Token]) {
         if let Ok(cli) = client.read() {
             log_cli_send_cmd_info(&self.logger, cli, comando_tokens);
         }
@@ -123,7 +211,7 @@ impl Node {
 
     fn procesar_pubsub_y_reenviar_si_es_publish(
         &self,
-        comando_tokens: &[String],
+        comando_tokens: &[Token],
         client: &Arc<RwLock<Client>>,
         tx_connect: &Sender<TipoMensajeNode>,
     ) -> bool {
@@ -131,11 +219,11 @@ impl Node {
             return false;
         }
 
-        if let Some(cmd) = comando_tokens.first().map(|s| s.to_uppercase()) {
-            if cmd == CMD_PUBLISH || cmd == CMD_SPUBLISH {
-                let mensaje = InnerMensajeNode::PubSub(RedisCMD::new(comando_tokens.to_vec()));
-                let _ = tx_connect.send(TipoMensajeNode::InnerNode(mensaje));
-            }
+        if let Some(cmd) = comando_tokens.first().map(|s| s.to_uppercase())
+            && (cmd == CMD_PUBLISH || cmd == CMD_SPUBLISH)
+        {
+            let mensaje = InnerMensajeNode::PubSub(RedisCMD::new(comando_tokens.to_vec()));
+            let _ = tx_connect.send(TipoMensajeNode::InnerNode(mensaje));
         }
 
         true
@@ -154,7 +242,7 @@ impl Node {
     /// - Verdadero si se encuentra el comando, falso en otro caso
     fn procesar_pub_sub(
         &self,
-        tokens: &[String],
+        tokens: &[Token],
         client: &Arc<RwLock<Client>>,
         tx_connect: Sender<TipoMensajeNode>,
     ) -> bool {
@@ -162,6 +250,9 @@ impl Node {
         if !PubSubBroker::es_comando_pub_sub(&comando) {
             return false;
         }
+        if comando == CMD_RESET && tokens.len() == 1 {
+            self.reiniciar_conexion(client);
+        }
         let dummy =
             BrokerCommand::new_client_cmd(tokens.to_vec(), client.clone(), Some(tx_connect));
         self.pub_sub.send_cmd(dummy);
@@ -183,15 +274,14 @@ impl Node {
     fn procesar_comando_general(
         &self,
         comando: &str,
-        tokens: &[String],
+        tokens: &[Token],
         client: &Arc<RwLock<Client>>,
         aofile: &Option<Arc<RwLock<File>>>,
         tx_connect: Sender<TipoMensajeNode>,
     ) {
         let logger = &self.logger;
-        if *self.role.read().unwrap() == NodeRole::Replica
-            && !es_operacion_no_mutable(&comando.to_uppercase())
-        {
+        let es_lectura = es_comando_de_lectura(comando, tokens);
+        if *self.role.read().unwrap() == NodeRole::Replica && !es_lectura {
             send_msj(
                 client.clone(),
                 DatoRedis::SimpleError(SimpleError::new(
@@ -202,35 +292,124 @@ impl Node {
             );
             return;
         }
-        match obtener_fn_normal(comando) {
-            Some(funcion) => match funcion(tokens, &self.storage) {
-                Ok(respuesta) => {
-                    send_msj(client.clone(), respuesta, logger);
+        self.clientes.esperar_pausa(!es_lectura);
+        if puede_aumentar_memoria(comando, tokens)
+            && let Err(e) = self.liberar_memoria()
+        {
+            send_msj(client.clone(), e, logger);
+            return;
+        }
+        let Some(funcion) = obtener_fn_normal(comando) else {
+            send_msj(client.clone(), DatoRedis::new_null(), logger);
+            return;
+        };
+        if es_lectura {
+            self.expirar_claves_leidas(tokens);
+        }
+        let (resultado, propagados) = self.ejecutar_comando(funcion, comando, tokens, client);
+        match resultado {
+            Ok(respuesta) => {
+                // La respuesta de un script no corresponde a los comandos
+                // que propaga
+                let tokens_eventos = match propagados.as_slice() {
+                    [propagado] if !es_script(comando) => propagado.as_slice(),
+                    _ => tokens,
+                };
+                self.publicar_eventos_keyspace(tokens_eventos, Some(&respuesta));
+                if es_lectura {
+                    self.registrar_lectura_tracking(client, comando, tokens);
+                }
+                send_msj(client.clone(), respuesta, logger);
+                self.propagar(propagados, aofile, &tx_connect);
+            }
+            Err(e) => {
+                self.publicar_eventos_keyspace(tokens, None);
+                // Un script que falla conserva las escrituras que hizo
+                // antes del error, por lo que tambien se propagan
+                self.propagar(propagados, aofile, &tx_connect);
+                if let DatoRedis::MovedError(e) = e.clone() {
+                    let moved = Moved::new(e.get_slot(), client.clone());
+                    let msj = InnerMensajeNode::Moved(moved);
+                    let _ = tx_connect.send(TipoMensajeNode::InnerNode(msj));
+                } else {
+                    send_msj(client.clone(), e, logger);
+                }
+            }
+        }
+    }
+
+    /// Guarda en el AOF y envia a las replicas los comandos ejecutados,
+    /// despertando a los comandos bloqueados si alguno modifica el storage
+    ///
+    /// # Parametros
+    /// * `propagados`: tokens de los comandos a propagar, en orden
+    /// * `aofile`: file de persistencia
+    /// * `tx_connect`: canal hacia el hilo de procesamiento del cluster
+    fn propagar(
+        &self,
+        propagados: Vec<Vec<Token>>,
+        aofile: &Option<Arc<RwLock<File>>>,
+        tx_connect: &Sender<TipoMensajeNode>,
+    ) {
+        let logger = &self.logger;
+        for propagado in propagados {
+            if let Some(aofile) = aofile
+                && self.appendonly.load(Ordering::SeqCst)
+            {
+                let inicio = Instant::now();
+                guardar_operacion(aofile, propagado.clone())
+                    .map_err(|e| logger.error(&e.to_string(), "AOF"))
+                    .ok();
+                self.latencia.registrar(EVENTO_AOF, inicio.elapsed());
 
-                    if let Some(aofile) = aofile {
-                        guardar_operacion(aofile, tokens.to_vec())
-                            .map_err(|e| logger.error(&e.to_string(), "AOF"))
-                            .ok();
+                logger.info(&format!("Operación {propagado:?} guardada en AOF"), "AOF");
+            }
 
-                        logger.info(&format!("Operación {tokens:?} guardada en AOF"), "AOF");
-                    }
+            let comando = propagado[0].to_uppercase();
+            self.actualizar_replication_offset(&comando);
+            if !es_operacion_no_mutable(&comando) {
+                self.bloqueos.notificar();
+            }
+            let msj = InnerMensajeNode::RedisCommand(RedisCMD::new(propagado));
+            let _ = tx_connect.send(TipoMensajeNode::InnerNode(msj));
+        }
+    }
 
-                    self.actualizar_replication_offset(&comando.to_uppercase());
-                    let msj = InnerMensajeNode::RedisCommand(RedisCMD::new(tokens.to_vec()));
-                    let _ = tx_connect.send(TipoMensajeNode::InnerNode(msj));
-                }
-                Err(e) => {
-                    if let DatoRedis::MovedError(e) = e.clone() {
-                        let moved = Moved::new(e.get_slot(), client.clone());
-                        let msj = InnerMensajeNode::Moved(moved);
-                        let _ = tx_connect.send(TipoMensajeNode::InnerNode(msj));
-                    } else {
-                        send_msj(client.clone(), e, logger);
+    /// Ejecuta un comando general, esperando si se trata de un comando
+    /// bloqueante
+    ///
+    /// # Retorna
+    /// - la respuesta del comando, o error de redis, y los tokens de los
+    ///   comandos a guardar en el AOF y enviar a las replicas. Los scripts
+    ///   propagan las escrituras que ejecutaron en lugar de su texto
+    fn ejecutar_comando(
+        &self,
+        funcion: CommandFunction,
+        comando: &str,
+        tokens: &[Token],
+        client: &Arc<RwLock<Client>>,
+    ) -> (Result<DatoRedis, DatoRedis>, Vec<Vec<Token>>) {
+        if es_comando_bloqueante(comando, tokens) {
+            return match self.ejecutar_comando_bloqueante(funcion, tokens, client) {
+                Ok((respuesta, propagado)) => (Ok(respuesta), vec![propagado]),
+                Err(e) => (Err(e), Vec::new()),
+            };
+        }
+        match comando {
+            CMD_EVAL | CMD_EVALSHA => match self.scripts.como_eval(tokens) {
+                Ok(eval) => ejecutar_eval(&eval, &self.storage),
+                Err(e) => (Err(e), Vec::new()),
+            },
+            CMD_FCALL | CMD_FCALL_RO => ejecutar_fcall(tokens, &self.storage),
+            _ => {
+                let tokens = con_expiraciones_absolutas(comando, tokens);
+                match funcion(&tokens, &self.storage) {
+                    Ok(respuesta) => {
+                        let propagado = comando_propagado(comando, &tokens, &respuesta);
+                        (Ok(respuesta), vec![propagado])
                     }
+                    Err(e) => (Err(e), Vec::new()),
                 }
-            },
-            None => {
-                send_msj(client.clone(), DatoRedis::new_null(), logger);
             }
         }
     }
@@ -247,40 +426,442 @@ impl Node {
     pub fn ejecutar_comando_general_replica(
         &self,
         comando: &str,
-        tokens: &[String],
+        tokens: &[Token],
         aofile: &Option<Arc<RwLock<File>>>,
     ) {
-        if let Some(funcion) = obtener_fn_normal(&comando.to_uppercase()) {
-            if funcion(tokens, &self.storage).is_ok() {
-                if let Some(aof) = aofile {
-                    match guardar_operacion(aof, tokens.to_vec()) {
-                        Ok(_) => self
-                            .logger
-                            .info(&format!("Operación {tokens:?} guardada en AOF"), "AOF"),
-                        Err(e) => self.logger.error(&e.to_string(), "AOF"),
-                    }
-                }
+        let Some(funcion) = obtener_fn_normal(&comando.to_uppercase()) else {
+            return;
+        };
+        let respuesta = funcion(tokens, &self.storage);
+        self.publicar_eventos_keyspace(tokens, respuesta.as_ref().ok());
+        self.enviar_invalidaciones(None);
+        if respuesta.is_ok() {
+            self.bloqueos.notificar();
+        }
+        if respuesta.is_ok()
+            && let Some(aof) = aofile
+            && self.appendonly.load(Ordering::SeqCst)
+        {
+            let inicio = Instant::now();
+            let resultado = guardar_operacion(aof, tokens.to_vec());
+            self.latencia.registrar(EVENTO_AOF, inicio.elapsed());
+            match resultado {
+                Ok(_) => self
+                    .logger
+                    .info(&format!("Operación {tokens:?} guardada en AOF"), "AOF"),
+                Err(e) => self.logger.error(&e.to_string(), "AOF"),
+            }
+        }
+    }
+
+    /// Publica por pub/sub las notificaciones de keyspace de un comando y
+    /// de las claves que el storage eliminó, expiró o desalojó mientras
+    /// se ejecutaba
+    ///
+    /// # Parametros
+    /// * `tokens`: secuencia de strings que conforman el comando
+    /// * `respuesta`: respuesta del comando, None si falló
+    fn publicar_eventos_keyspace(&self, tokens: &[Token], respuesta: Option<&DatoRedis>) {
+        if !self.notificaciones.habilitadas() {
+            return;
+        }
+        let mut eventos = match self.storage.write() {
+            Ok(mut storage) => storage.tomar_eventos(),
+            Err(_) => Vec::new(),
+        };
+        if let Some(respuesta) = respuesta {
+            eventos.extend(eventos_de_comando(tokens, respuesta));
+        }
+        for evento in eventos {
+            for (canal, mensaje) in self.notificaciones.mensajes(&evento) {
+                let publish = vec![Token::from(CMD_PUBLISH), canal, mensaje];
+                self.pub_sub
+                    .send_cmd(BrokerCommand::new_node_cmd(publish, None));
+            }
+        }
+    }
+
+    /// Elimina las claves expiradas que recibe un comando de lectura, que
+    /// el storage solo oculta al leerlas, para que se notifique su
+    /// expiración. Cualquier argumento que nombre una clave expirada se
+    /// elimina, lo que no cambia el resultado de ningún comando
+    ///
+    /// # Parametros
+    /// * `tokens`: secuencia de strings que conforman el comando
+    fn expirar_claves_leidas(&self, tokens: &[Token]) {
+        let argumentos = &tokens[1..];
+        let hay_expiradas = self
+            .storage
+            .read()
+            .is_ok_and(|storage| storage.hay_claves_expiradas(argumentos));
+        if hay_expiradas && let Ok(mut storage) = self.storage.write() {
+            storage.expirar_claves(argumentos);
+        }
+    }
+
+    /// Registra las claves leídas por un comando, si el cliente que lo
+    /// ejecutó tiene tracking activo
+    fn registrar_lectura_tracking(
+        &self,
+        client: &Arc<RwLock<Client>>,
+        comando: &str,
+        tokens: &[Token],
+    ) {
+        if !self.tracking.hay_clientes() {
+            return;
+        }
+        if let Ok(cli) = client.read() {
+            self.tracking
+                .registrar_lectura(cli.get_numero(), &claves_leidas(comando, tokens));
+        }
+    }
+
+    /// Envía las invalidaciones de las claves que cambiaron tras un comando
+    /// y descarta la elección de CLIENT CACHING, que solo aplica al comando
+    /// siguiente al propio CLIENT CACHING
+    fn finalizar_comando_tracking(&self, client: &Arc<RwLock<Client>>, tokens: &[Token]) {
+        if !self.tracking.hay_clientes() {
+            return;
+        }
+        let Ok(numero) = client.read().map(|cli| cli.get_numero()) else {
+            return;
+        };
+        self.enviar_invalidaciones(Some(numero));
+        let es_client_caching = tokens[0].eq_ignore_ascii_case(CMD_CLIENT)
+            && tokens
+                .get(1)
+                .is_some_and(|s| s.eq_ignore_ascii_case(CMD_CLIENT_CACHING));
+        if !es_client_caching {
+            self.tracking.finalizar_comando(numero);
+        }
+    }
+
+    /// Envía a los clientes con tracking, o a sus clientes de REDIRECT, la
+    /// invalidación de las claves que cambiaron en el storage desde el
+    /// último envío
+    ///
+    /// # Parametros
+    /// * `origen`: cliente que ejecutó el comando, None si es un comando
+    ///   propagado por el master
+    fn enviar_invalidaciones(&self, origen: Option<u64>) {
+        if !self.tracking.hay_clientes() {
+            return;
+        }
+        let modificaciones = match self.storage.write() {
+            Ok(mut storage) => storage.tomar_modificaciones(),
+            Err(_) => return,
+        };
+        for invalidacion in self.tracking.invalidar(modificaciones, origen) {
+            // Solo un cliente de REDIRECT suscripto al canal de
+            // invalidaciones espera mensajes de pub/sub entre sus respuestas
+            let Some(destino) = self
+                .clientes
+                .obtener(invalidacion.destino)
+                .filter(|destino| {
+                    !invalidacion.redirigida
+                        || destino.read().is_ok_and(|cli| {
+                            cli.get_channels().contains(CANAL_INVALIDACION.as_bytes())
+                        })
+                })
+            else {
+                continue;
+            };
+            match invalidacion.mensaje() {
+                Ok(mensaje) => send_msj(destino, mensaje, &self.logger),
+                Err(e) => self.logger.error(&format!("{e:?}"), "Tracking"),
             }
         }
     }
 
-    pub fn actualizar_replication_offset(&self, comando: &String) {
+    /// Ejecuta un ciclo activo de expiración sobre el storage y publica las
+    /// notificaciones e invalidaciones de las claves expiradas
+    pub(crate) fn expirar_claves_activamente(&self) {
+        let expiradas = match self.storage.write() {
+            Ok(mut storage) => storage.expirar_claves_activamente(),
+            Err(_) => return,
+        };
+        if expiradas > 0 {
+            self.logger
+                .info(&format!("{expiradas} claves expiradas"), "Expiration");
+            self.publicar_eventos_keyspace(&[], None);
+            self.enviar_invalidaciones(None);
+        }
+    }
+
+    /// Desaloja claves del storage si se superó `maxmemory`, según la
+    /// política configurada
+    ///
+    /// # Retorna
+    /// - () si hay memoria disponible, error OOM de redis en otro caso
+    fn liberar_memoria(&self) -> Result<(), DatoRedis> {
+        let desalojadas = get_storage_write_lock(&self.storage)?.liberar_memoria()?;
+        if desalojadas > 0 {
+            self.logger.info(
+                &format!("{desalojadas} claves desalojadas por maxmemory"),
+                "Eviction",
+            );
+        }
+        Ok(())
+    }
+
+    pub fn actualizar_replication_offset(&self, comando: &str) {
         if !es_operacion_no_mutable(comando) {
             self.replication_offset.fetch_add(1, Ordering::SeqCst);
         }
     }
 }
 
-fn es_operacion_no_mutable(cmd: &String) -> bool {
-    let operaciones_no_mutables = HashSet::from([
-        CMD_LRANGE.to_string(),
-        CMD_LINDEX.to_string(),
-        CMD_LLEN.to_string(),
-        CMD_GET.to_string(),
-        CMD_STRLEN.to_string(),
-        CMD_SISMEMBER.to_string(),
-        CMD_SMEMBERS.to_string(),
-    ]);
-
-    operaciones_no_mutables.contains(cmd)
+/// Determina si un comando ejecuta un script o una funcion
+fn es_script(comando: &str) -> bool {
+    matches!(comando, CMD_EVAL | CMD_EVALSHA | CMD_FCALL | CMD_FCALL_RO)
+}
+
+/// Reescribe las expiraciones relativas de un comando como instantes
+/// absolutos antes de ejecutarlo, para que el AOF y las replicas expiren
+/// las claves en el mismo instante que el master
+pub(crate) fn con_expiraciones_absolutas(comando: &str, tokens: &[Token]) -> Vec<Token> {
+    match comando {
+        CMD_SET | CMD_GETEX => con_expiracion_absoluta(tokens),
+        CMD_RESTORE => restore_con_expiracion_absoluta(tokens),
+        _ => tokens.to_vec(),
+    }
+}
+
+/// Obtiene los tokens con los que se propaga un comando ejecutado. El id
+/// generado por XADD se propaga para que las replicas y el AOF agreguen la
+/// entrada con el mismo id
+pub(crate) fn comando_propagado(
+    comando: &str,
+    tokens: &[Token],
+    respuesta: &DatoRedis,
+) -> Vec<Token> {
+    if comando == CMD_XADD {
+        xadd_con_id(tokens, respuesta)
+    } else {
+        tokens.to_vec()
+    }
+}
+
+/// Determina si un comando general no modifica el storage, considerando
+/// las consultas de FUNCTION que comparten nombre con sus modificaciones
+fn es_comando_de_lectura(comando: &str, tokens: &[Token]) -> bool {
+    buscar_comando(comando).is_some_and(|info| info.es_lectura(tokens))
+}
+
+/// Determina si un comando no modifica el storage
+pub(crate) fn es_operacion_no_mutable(cmd: &str) -> bool {
+    buscar_comando(cmd).is_some_and(|info| info.flags.contains(&FLAG_READONLY))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+
+    use logger::logger::Logger;
+    use redis_client::protocol::protocol_resp::resp_client_command_read;
+    use redis_client::tipos_datos::arrays::Arrays;
+
+    use super::*;
+    use crate::node_builder::NodeBuilder;
+    use crate::node_role::NodeRole;
+    use crate::storage::Storage;
+
+    /// Cliente con el handshake realizado y el extremo del socket donde se
+    /// leen sus respuestas
+    fn conectar(node: &Node) -> (Arc<RwLock<Client>>, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (lector, _) = listener.accept().unwrap();
+        let mut cliente = Client::new(
+            "127.0.0.1:5000".to_string(),
+            stream,
+            Logger::null(),
+            Arc::new(RwLock::new(NodeRole::Master)),
+        );
+        cliente.set_handshake(true);
+        let cliente = Arc::new(RwLock::new(cliente));
+        node.clientes.registrar(cliente.clone());
+        (cliente, lector)
+    }
+
+    fn ejecutar(node: &Node, client: &Arc<RwLock<Client>>, comando: &[&str]) {
+        let tokens: Vec<Token> = comando.iter().map(|s| Token::from(*s)).collect();
+        let (tx, _rx) = mpsc::channel();
+        node.procesar_un_comando(&None, &tokens, client, tx, &HashMap::new());
+    }
+
+    fn bulk(valor: &str) -> DatoRedis {
+        DatoRedis::new_bulk_string(valor.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_01_tracking_invalida_las_claves_leidas() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .storage(Arc::new(RwLock::new(Storage::new(0..16384))))
+            .build()
+            .unwrap();
+        let (lector, mut respuestas) = conectar(&node);
+        let (escritor, _) = conectar(&node);
+        let (suscriptor, mut invalidaciones) = conectar(&node);
+        let id = suscriptor.read().unwrap().get_numero().to_string();
+
+        ejecutar(&node, &escritor, &["SET", "clave", "1"]);
+        ejecutar(
+            &node,
+            &lector,
+            &["CLIENT", "TRACKING", "on", "REDIRECT", &id],
+        );
+        ejecutar(&node, &lector, &["GET", "clave"]);
+        // Sin suscripcion al canal el cliente de REDIRECT no recibe nada
+        ejecutar(&node, &escritor, &["SET", "clave", "2"]);
+        ejecutar(&node, &lector, &["GET", "clave"]);
+        suscriptor
+            .write()
+            .unwrap()
+            .add_channel(Token::from(CANAL_INVALIDACION));
+        ejecutar(&node, &escritor, &["SET", "clave", "3"]);
+        ejecutar(&node, &escritor, &["SET", "clave", "4"]);
+        ejecutar(&node, &lector, &["CLIENT", "TRACKING", "off"]);
+
+        // Las respuestas del lector no incluyen invalidaciones
+        let mut leer = || resp_client_command_read(&mut respuestas).unwrap();
+        assert!(matches!(leer(), DatoRedis::SimpleString(_)));
+        assert_eq!(leer(), bulk("1"));
+        assert_eq!(leer(), bulk("2"));
+        assert!(matches!(leer(), DatoRedis::SimpleString(_)));
+
+        // La segunda escritura no se notifica porque la clave no se volvio
+        // a leer
+        assert_eq!(
+            resp_client_command_read(&mut invalidaciones).unwrap(),
+            DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![
+                bulk("message"),
+                bulk(CANAL_INVALIDACION),
+                DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![bulk("clave")])),
+            ]))
+        );
+        invalidaciones
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        assert!(resp_client_command_read(&mut invalidaciones).is_err());
+    }
+
+    #[test]
+    fn test_02_quit_responde_y_cierra_la_conexion() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .build()
+            .unwrap();
+        let (cliente, mut respuestas) = conectar(&node);
+
+        ejecutar(&node, &cliente, &["PING"]);
+        ejecutar(&node, &cliente, &["QUIT"]);
+
+        let mut leer = || resp_client_command_read(&mut respuestas);
+        assert_eq!(
+            leer().unwrap(),
+            DatoRedis::new_simple_string("PONG".to_string()).unwrap()
+        );
+        assert_eq!(
+            leer().unwrap(),
+            DatoRedis::new_simple_string("OK".to_string()).unwrap()
+        );
+        assert!(leer().is_err());
+    }
+
+    #[test]
+    fn test_03_script_fallido_propaga_las_escrituras_que_hizo() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .storage(Arc::new(RwLock::new(Storage::new(0..16384))))
+            .build()
+            .unwrap();
+        let (cliente, _respuestas) = conectar(&node);
+        let script = "call\" XADD KEYS[1] * campo valor\"\ncall\" XLEN KEYS[1]\"\n\
+                      call\" SET KEYS[1] v EX 100\"\ncall\" INCR KEYS[1]\"";
+        let tokens: Vec<Token> = ["EVAL", script, "1", "clave"]
+            .iter()
+            .map(|s| Token::from(*s))
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        node.procesar_un_comando(&None, &tokens, &cliente, tx, &HashMap::new());
+
+        let propagados: Vec<Vec<Token>> = rx
+            .try_iter()
+            .filter_map(|mensaje| match mensaje {
+                TipoMensajeNode::InnerNode(InnerMensajeNode::RedisCommand(cmd)) => {
+                    Some(cmd.get_command())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(propagados.len(), 2);
+        assert_eq!(propagados[0][..2], ["XADD", "clave"]);
+        assert_ne!(propagados[0][2], "*");
+        assert_eq!(propagados[0][3..], ["campo", "valor"]);
+        assert_eq!(propagados[1][..4], ["SET", "clave", "v", "PXAT"]);
+        let expira_en = propagados[1][4].parse::<u64>().unwrap();
+        assert_eq!(
+            node.storage.read().unwrap().expiracion("clave"),
+            Some(expira_en)
+        );
+    }
+
+    #[test]
+    fn test_04_tracking_por_defecto_envia_push_al_cambiar_o_expirar() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .storage(Arc::new(RwLock::new(Storage::new(0..16384))))
+            .build()
+            .unwrap();
+        let (lector, mut respuestas) = conectar(&node);
+        let (escritor, _) = conectar(&node);
+
+        ejecutar(&node, &escritor, &["SET", "clave", "1"]);
+        ejecutar(&node, &escritor, &["SET", "volatil", "1", "PX", "20"]);
+        ejecutar(&node, &lector, &["CLIENT", "TRACKING", "on"]);
+        ejecutar(&node, &lector, &["GET", "clave"]);
+        ejecutar(&node, &lector, &["GET", "volatil"]);
+        ejecutar(&node, &escritor, &["SET", "clave", "2"]);
+        std::thread::sleep(Duration::from_millis(40));
+        node.expirar_claves_activamente();
+
+        let invalidacion = |clave: &str| {
+            DatoRedis::new_push_con_contenido(Arrays::new_con_contenido(vec![
+                bulk("invalidate"),
+                DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(vec![bulk(clave)])),
+            ]))
+        };
+        let mut leer = || resp_client_command_read(&mut respuestas).unwrap();
+        assert!(matches!(leer(), DatoRedis::SimpleString(_)));
+        assert_eq!(leer(), bulk("1"));
+        assert_eq!(leer(), bulk("1"));
+        assert_eq!(leer(), invalidacion("clave"));
+        assert_eq!(leer(), invalidacion("volatil"));
+    }
+
+    #[test]
+    fn test_05_cliente_suscripto_recibe_error_por_comandos_no_permitidos() {
+        let node = NodeBuilder::para_pruebas()
+            .slot_range(0..16384)
+            .build()
+            .unwrap();
+        let (cliente, mut respuestas) = conectar(&node);
+        cliente.write().unwrap().add_channel(Token::from("canal"));
+
+        ejecutar(&node, &cliente, &["Get", "clave"]);
+        ejecutar(&node, &cliente, &["PING"]);
+
+        let mut leer = || resp_client_command_read(&mut respuestas).unwrap();
+        assert_eq!(
+            leer(),
+            DatoRedis::new_simple_error(
+                "ERR".to_string(),
+                "Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context".to_string()
+            )
+        );
+        assert!(!matches!(leer(), DatoRedis::SimpleError(_)));
+    }
 }
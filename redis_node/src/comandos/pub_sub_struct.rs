@@ -6,14 +6,20 @@
 //! - Gestionar los canales y suscriptores activos.
 //! - Ejecutar comandos como SUBSCRIBE y UNSUBSCRIBE mediante un canal dedicado.
 
+use crate::client_struct::buffer_salida::{ControlBuffers, SalidaCliente};
 use crate::client_struct::client::Client;
 use crate::cluster::node_message::{InnerMensajeNode, TipoMensajeNode};
-use crate::comandos::const_cmd::{CMD_PUNSUBSCRIBE, CMD_SUNSUBSCRIBE, CMD_UNSUBSCRIBE};
+use crate::comandos::const_cmd::{
+    CMD_PSUBSCRIBE, CMD_PUNSUBSCRIBE, CMD_RESET, CMD_SUBSCRIBE, CMD_SUNSUBSCRIBE, CMD_UNSUBSCRIBE,
+};
 use crate::comandos::utils::send_msj;
 use crate::internal_protocol::moved_shard_pubsub::MovedShardPubSub;
+use crate::internal_protocol::resumen_pubsub::ResumenPubSub;
 use crate::log_msj::log_mensajes::log_cli_subscribe_channels;
+use crate::rutas_pubsub::RutasPubSub;
 use crate::utils::utils_functions::obtener_fn_pub_sub;
 use logger::logger::Logger;
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::traits::DatoRedis;
 use std::collections::HashMap;
 use std::ops::Range;
@@ -22,11 +28,11 @@ use std::sync::{Arc, RwLock};
 use std::thread::spawn;
 
 /// Alias que representa el nombre de un canal Pub/Sub.
-pub type Canal = String;
+pub type Canal = Token;
 
 /// Mapa de canales, donde cada canal tiene un diccionario de clientes suscritos identificados
-/// por su ID, asociado a su respectivo `Sender<String>` para enviar mensajes.
-pub type Channels = HashMap<Canal, HashMap<String, Sender<String>>>;
+/// por su ID, asociado a su respectiva `SalidaCliente` para enviar mensajes.
+pub type Channels = HashMap<Canal, HashMap<String, SalidaCliente>>;
 
 /// Indica el origen del comando: si proviene de un cliente externo o de un nodo del sistema.
 #[derive(Clone, Debug)]
@@ -38,7 +44,7 @@ pub enum BrokerCommandFlag {
 /// Resultado de los get cmd del struct BrokerCommand
 type PubSubParseResult = (
     BrokerCommandFlag,
-    Vec<String>,
+    Vec<Token>,
     Option<Arc<RwLock<Client>>>,
     Option<Sender<TipoMensajeNode>>,
 );
@@ -48,7 +54,7 @@ type PubSubParseResult = (
 #[derive(Debug, Clone)]
 pub struct BrokerCommand {
     flag_type: BrokerCommandFlag,
-    tokens: Vec<String>,
+    tokens: Vec<Token>,
     client: Option<Arc<RwLock<Client>>>,
     shard_sender: Option<Sender<TipoMensajeNode>>,
 }
@@ -56,7 +62,7 @@ pub struct BrokerCommand {
 impl BrokerCommand {
     /// Crea un nuevo comando proveniente de un cliente.
     pub fn new_client_cmd(
-        tokens: Vec<String>,
+        tokens: Vec<Token>,
         client: Arc<RwLock<Client>>,
         shard_sender: Option<Sender<TipoMensajeNode>>,
     ) -> Self {
@@ -72,7 +78,7 @@ impl BrokerCommand {
     ///
     /// Pueden ser solo de PUBLISH, de otros nodos; o UNSUBSCRIBE, de self node cuando un cli
     /// se desconecta.
-    pub fn new_node_cmd(tokens: Vec<String>, client: Option<Arc<RwLock<Client>>>) -> Self {
+    pub fn new_node_cmd(tokens: Vec<Token>, client: Option<Arc<RwLock<Client>>>) -> Self {
         BrokerCommand {
             flag_type: BrokerCommandFlag::Node,
             tokens,
@@ -98,6 +104,8 @@ impl BrokerCommand {
 pub struct PubSubBroker {
     logger: Logger,
     cmd_sender: Sender<BrokerCommand>,
+    buffers: Arc<ControlBuffers>,
+    rutas: Arc<RutasPubSub>,
 }
 
 impl PubSubBroker {
@@ -106,11 +114,15 @@ impl PubSubBroker {
         let (tx, rx) = channel::<BrokerCommand>();
 
         let mut core = PubSubCore::new(logger.clone(), slot_range);
+        let buffers = Arc::clone(&core.buffers);
+        let rutas = Arc::clone(&core.rutas);
         spawn(move || core.run(rx));
 
         PubSubBroker {
             logger,
             cmd_sender: tx,
+            buffers,
+            rutas,
         }
     }
 
@@ -119,9 +131,23 @@ impl PubSubBroker {
         Self {
             logger: Logger::null(),
             cmd_sender: _sender,
+            buffers: Arc::new(ControlBuffers::new()),
+            rutas: Arc::new(RutasPubSub::new()),
         }
     }
 
+    /// Limites de los buffers de salida de los suscriptores y desconexiones
+    /// que provocaron
+    pub fn buffers(&self) -> &Arc<ControlBuffers> {
+        &self.buffers
+    }
+
+    /// Resumen de las suscripciones del nodo y de los otros nodos del
+    /// clúster, usado para reenviar cada publicación solo a quien la necesita
+    pub fn rutas(&self) -> &Arc<RutasPubSub> {
+        &self.rutas
+    }
+
     /// Envía un comando al hilo del broker para su ejecución.
     pub fn send_cmd(&self, cmd: BrokerCommand) {
         if self.cmd_sender.send(cmd).is_err() {
@@ -139,19 +165,17 @@ impl PubSubBroker {
     /// al momento de desconectarse del servidor.
     pub fn borrar_canales_cliente(&self, client: Arc<RwLock<Client>>) {
         let cmd = BrokerCommand::new_node_cmd(
-            [CMD_UNSUBSCRIBE.to_string()].to_vec(),
+            vec![Token::from(CMD_UNSUBSCRIBE)],
             Option::from(client.clone()),
         );
         self.send_cmd(cmd);
         let cmd = BrokerCommand::new_node_cmd(
-            [CMD_PUNSUBSCRIBE.to_string()].to_vec(),
+            vec![Token::from(CMD_PUNSUBSCRIBE)],
             Option::from(client.clone()),
         );
         self.send_cmd(cmd);
-        let cmd = BrokerCommand::new_node_cmd(
-            [CMD_SUNSUBSCRIBE.to_string()].to_vec(),
-            Option::from(client),
-        );
+        let cmd =
+            BrokerCommand::new_node_cmd(vec![Token::from(CMD_SUNSUBSCRIBE)], Option::from(client));
         self.send_cmd(cmd);
     }
 }
@@ -166,6 +190,10 @@ pub(crate) struct PubSubCore {
     pub(crate) schannels: Channels,
     /// Rango de slots que maneja este nodo
     pub(crate) slot_range: Range<u16>,
+    /// Limites de los buffers de salida de los suscriptores
+    pub(crate) buffers: Arc<ControlBuffers>,
+    /// Resumen de suscripciones compartido con el resto del clúster
+    pub(crate) rutas: Arc<RutasPubSub>,
     /// Cualquier otra cosa que hoy estés pasando suelta
     pub(crate) logger: Logger,
 }
@@ -179,6 +207,8 @@ impl PubSubCore {
             pchannels: HashMap::new(),
             schannels: HashMap::new(),
             slot_range,
+            buffers: Arc::new(ControlBuffers::new()),
+            rutas: Arc::new(RutasPubSub::new()),
             logger,
         }
     }
@@ -210,7 +240,7 @@ impl PubSubCore {
     fn procesar_pubsub_cli_node(
         &mut self,
         client_opt: Option<Arc<RwLock<Client>>>,
-        tokens: Vec<String>,
+        tokens: Vec<Token>,
         logger: &Logger,
         shard_sender: Option<Sender<TipoMensajeNode>>,
     ) {
@@ -219,18 +249,19 @@ impl PubSubCore {
             None => return,
         };
 
-        match self.procesar_pub_sub_handler(&tokens, &client, logger) {
+        let resultado = self.procesar_pub_sub_handler(&tokens, &client, logger);
+        self.actualizar_resumen(&tokens, shard_sender.as_ref());
+        match resultado {
             Ok(resp) => {
-                if let DatoRedis::Arrays(array) = resp.clone() {
-                    if array.contains_dato(&DatoRedis::new_moved_error(0)) {
-                        if let Some(sender) = shard_sender {
-                            let moved = MovedShardPubSub::new(resp, client);
-                            let _ = sender.send(TipoMensajeNode::InnerNode(
-                                InnerMensajeNode::MovedShard(moved),
-                            ));
-                            return;
-                        }
-                    }
+                if let DatoRedis::Arrays(array) = resp.clone()
+                    && array.contains_dato(&DatoRedis::new_moved_error(0))
+                    && let Some(sender) = shard_sender
+                {
+                    let moved = MovedShardPubSub::new(resp, client);
+                    let _ = sender.send(TipoMensajeNode::InnerNode(InnerMensajeNode::MovedShard(
+                        moved,
+                    )));
+                    return;
                 }
                 send_msj(client, resp, logger)
             }
@@ -244,20 +275,46 @@ impl PubSubCore {
     fn procesar_pubsub_inner_node(
         &mut self,
         client: Option<Arc<RwLock<Client>>>,
-        tokens: Vec<String>,
+        tokens: Vec<Token>,
     ) {
         if let Some(cli) = client {
             let _ = self.unsubscribe(&tokens, cli);
+            self.actualizar_resumen(&tokens, None);
         } else {
             let _ = self.publish_internal(&tokens);
         }
     }
 
+    /// Recalcula el resumen de suscripciones del nodo luego de un comando
+    /// que modifica los canales o patrones. Si el resumen cambió y hay
+    /// `shard_sender`, avisa al hilo del clúster para que lo envíe antes de
+    /// responder al cliente.
+    fn actualizar_resumen(&self, tokens: &[Token], shard_sender: Option<&Sender<TipoMensajeNode>>) {
+        let comando = tokens[0].to_uppercase();
+        if ![
+            CMD_SUBSCRIBE,
+            CMD_UNSUBSCRIBE,
+            CMD_PSUBSCRIBE,
+            CMD_PUNSUBSCRIBE,
+            CMD_RESET,
+        ]
+        .contains(&comando.as_str())
+        {
+            return;
+        }
+        let resumen = ResumenPubSub::new(self.channels.keys(), self.pchannels.keys());
+        if self.rutas.actualizar_local(resumen)
+            && let Some(sender) = shard_sender
+        {
+            let _ = sender.send(TipoMensajeNode::InnerNode(InnerMensajeNode::ResumenPubSub));
+        }
+    }
+
     /// Procesa un comando Pub/Sub proveniente de un cliente.
     /// Si el comando es válido, se ejecuta la lógica correspondiente (e.g., SUBSCRIBE, PUBLISH).
     fn procesar_pub_sub_handler(
         &mut self,
-        tokens: &[String],
+        tokens: &[Token],
         client: &Arc<RwLock<Client>>,
         logger: &Logger,
     ) -> Result<DatoRedis, DatoRedis> {
@@ -314,7 +371,7 @@ mod tests {
 
         // Suscribirse
         let subscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["SUBSCRIBE".to_string(), "canal1".to_string()],
+            vec![Token::from("SUBSCRIBE"), Token::from("canal1")],
             cliente.clone(),
             None,
         );
@@ -333,7 +390,13 @@ mod tests {
             decrypt_message,
             "*3\r\n$9\r\nsubscribe\r\n$6\r\ncanal1\r\n:1\r\n"
         );
-        assert!(cliente.read().unwrap().get_channels().contains("canal1"));
+        assert!(
+            cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal1".as_bytes())
+        );
     }
 
     #[test]
@@ -346,9 +409,9 @@ mod tests {
         // Suscribirse
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "SUBSCRIBE".to_string(),
-                "canal1".to_string(),
-                "canal2".to_string(),
+                Token::from("SUBSCRIBE"),
+                Token::from("canal1"),
+                Token::from("canal2"),
             ],
             cliente.clone(),
             None,
@@ -369,8 +432,20 @@ mod tests {
             decrypt_message,
             "*6\r\n$9\r\nsubscribe\r\n$6\r\ncanal1\r\n:1\r\n$9\r\nsubscribe\r\n$6\r\ncanal2\r\n:2\r\n"
         );
-        assert!(cliente.read().unwrap().get_channels().contains("canal1"));
-        assert!(cliente.read().unwrap().get_channels().contains("canal2"));
+        assert!(
+            cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal1".as_bytes())
+        );
+        assert!(
+            cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal2".as_bytes())
+        );
     }
 
     #[test]
@@ -382,9 +457,9 @@ mod tests {
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "SUBSCRIBE".to_string(),
-                "canal1".to_string(),
-                "canal2".to_string(),
+                Token::from("SUBSCRIBE"),
+                Token::from("canal1"),
+                Token::from("canal2"),
             ],
             cliente.clone(),
             None,
@@ -392,7 +467,7 @@ mod tests {
         broker.send_cmd(subscribe_cmd);
 
         let unsubscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["UNSUBSCRIBE".to_string(), "canal1".to_string()],
+            vec![Token::from("UNSUBSCRIBE"), Token::from("canal1")],
             cliente.clone(),
             None,
         );
@@ -418,8 +493,20 @@ mod tests {
             all_messages,
             "*6\r\n$9\r\nsubscribe\r\n$6\r\ncanal1\r\n:1\r\n$9\r\nsubscribe\r\n$6\r\ncanal2\r\n:2\r\n*3\r\n$11\r\nunsubscribe\r\n$6\r\ncanal1\r\n:1\r\n"
         );
-        assert!(!cliente.read().unwrap().get_channels().contains("canal1"));
-        assert!(cliente.read().unwrap().get_channels().contains("canal2"));
+        assert!(
+            !cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal1".as_bytes())
+        );
+        assert!(
+            cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal2".as_bytes())
+        );
     }
 
     #[test]
@@ -431,9 +518,9 @@ mod tests {
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "SUBSCRIBE".to_string(),
-                "canal1".to_string(),
-                "canal2".to_string(),
+                Token::from("SUBSCRIBE"),
+                Token::from("canal1"),
+                Token::from("canal2"),
             ],
             cliente.clone(),
             None,
@@ -441,7 +528,7 @@ mod tests {
         broker.send_cmd(subscribe_cmd);
 
         let unsubscribe_cmd =
-            BrokerCommand::new_client_cmd(vec!["UNSUBSCRIBE".to_string()], cliente.clone(), None);
+            BrokerCommand::new_client_cmd(vec![Token::from("UNSUBSCRIBE")], cliente.clone(), None);
         broker.send_cmd(unsubscribe_cmd);
 
         thread::sleep(Duration::from_millis(50)); // esperar al hilo
@@ -463,8 +550,20 @@ mod tests {
         assert!(all_messages.contains("$11\r\nunsubscribe\r\n$6\r\ncanal1"));
         assert!(all_messages.ends_with(":0\r\n"));
 
-        assert!(!cliente.read().unwrap().get_channels().contains("canal1"));
-        assert!(!cliente.read().unwrap().get_channels().contains("canal2"));
+        assert!(
+            !cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal1".as_bytes())
+        );
+        assert!(
+            !cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal2".as_bytes())
+        );
     }
 
     #[test]
@@ -477,9 +576,9 @@ mod tests {
         // Suscribirse
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "PUBLISH".to_string(),
-                "canal1".to_string(),
-                "Hola Mundo!".to_string(),
+                Token::from("PUBLISH"),
+                Token::from("canal1"),
+                Token::from("Hola Mundo!"),
             ],
             cliente.clone(),
             None,
@@ -506,7 +605,7 @@ mod tests {
         let (cliente, mut response_tcp) = setup_cliente("cli1", logger);
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["SUBSCRIBE".to_string(), "canal1".to_string()],
+            vec![Token::from("SUBSCRIBE"), Token::from("canal1")],
             cliente.clone(),
             None,
         );
@@ -514,7 +613,7 @@ mod tests {
         thread::sleep(Duration::from_millis(50));
 
         let channels_cmd = BrokerCommand::new_client_cmd(
-            vec!["PUBSUB".to_string(), "CHANNELS".to_string()],
+            vec![Token::from("PUBSUB"), Token::from("CHANNELS")],
             cliente.clone(),
             None,
         );
@@ -545,7 +644,7 @@ mod tests {
         let (cliente, mut response_tcp) = setup_cliente("cli1", logger);
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["SUBSCRIBE".to_string(), "canal1".to_string()],
+            vec![Token::from("SUBSCRIBE"), Token::from("canal1")],
             cliente.clone(),
             None,
         );
@@ -554,9 +653,9 @@ mod tests {
 
         let numsub_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "PUBSUB".to_string(),
-                "NUMSUB".to_string(),
-                "canal1".to_string(),
+                Token::from("PUBSUB"),
+                Token::from("NUMSUB"),
+                Token::from("canal1"),
             ],
             cliente.clone(),
             None,
@@ -587,7 +686,7 @@ mod tests {
         let (cliente, mut response_tcp) = setup_cliente("cli1", logger);
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["SSUBSCRIBE".to_string(), "HOLA".to_string()],
+            vec![Token::from("SSUBSCRIBE"), Token::from("HOLA")],
             cliente.clone(),
             None,
         );
@@ -620,7 +719,7 @@ mod tests {
         let (cliente, mut response_tcp) = setup_cliente("cli1", logger);
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["SSUBSCRIBE".to_string(), "HOLA".to_string()],
+            vec![Token::from("SSUBSCRIBE"), Token::from("HOLA")],
             cliente.clone(),
             None,
         );
@@ -651,9 +750,9 @@ mod tests {
 
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "SUBSCRIBE".to_string(),
-                "canal1".to_string(),
-                "canal2".to_string(),
+                Token::from("SUBSCRIBE"),
+                Token::from("canal1"),
+                Token::from("canal2"),
             ],
             cliente.clone(),
             None,
@@ -661,7 +760,7 @@ mod tests {
         broker.send_cmd(subscribe_cmd);
 
         let unsubscribe_cmd = BrokerCommand::new_client_cmd(
-            vec!["UNSUBSCRIBE".to_string(), "canal1".to_string()],
+            vec![Token::from("UNSUBSCRIBE"), Token::from("canal1")],
             cliente.clone(),
             None,
         );
@@ -687,8 +786,20 @@ mod tests {
             all_messages,
             "*6\r\n$9\r\nsubscribe\r\n$6\r\ncanal1\r\n:1\r\n$9\r\nsubscribe\r\n$6\r\ncanal2\r\n:2\r\n*3\r\n$11\r\nunsubscribe\r\n$6\r\ncanal1\r\n:1\r\n"
         );
-        assert!(!cliente.read().unwrap().get_channels().contains("canal1"));
-        assert!(cliente.read().unwrap().get_channels().contains("canal2"));
+        assert!(
+            !cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal1".as_bytes())
+        );
+        assert!(
+            cliente
+                .read()
+                .unwrap()
+                .get_channels()
+                .contains("canal2".as_bytes())
+        );
     }
 
     #[test]
@@ -701,9 +812,9 @@ mod tests {
         // Suscribirse
         let subscribe_cmd = BrokerCommand::new_client_cmd(
             vec![
-                "PUBLISH".to_string(),
-                "canal1".to_string(),
-                "Hola Mundo!".to_string(),
+                Token::from("PUBLISH"),
+                Token::from("canal1"),
+                Token::from("Hola Mundo!"),
             ],
             cliente.clone(),
             None,
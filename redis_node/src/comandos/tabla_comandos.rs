@@ -0,0 +1,1340 @@
+//! Este módulo contiene la tabla de comandos soportados por el nodo. Cada
+//! entrada describe la aridad, los flags y las posiciones de las claves de
+//! un comando, y de ella se obtienen las respuestas de COMMAND y los
+//! metadatos que usa la persistencia.
+//!
+//! La aridad sigue la convención de redis: un valor positivo es la
+//! cantidad exacta de tokens (incluyendo el nombre del comando) y uno
+//! negativo es la cantidad mínima.
+use crate::comandos::comandos_funciones::{
+    es_carga_de_funciones, es_mutacion_de_funciones, indices_datos_funciones,
+};
+use crate::comandos::comandos_geo::inicio_posiciones_geoadd;
+use crate::comandos::comandos_scripting::{cantidad_claves, indices_datos_script};
+use crate::comandos::comandos_stream::{OPCION_STREAMS, indices_campos_xadd};
+use crate::comandos::comandos_stream_grupos::xgroup_agrega_datos;
+use crate::comandos::const_cmd::*;
+use redis_client::protocol::token::Token;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub const FLAG_WRITE: &str = "write";
+pub const FLAG_READONLY: &str = "readonly";
+pub const FLAG_PUBSUB: &str = "pubsub";
+pub const FLAG_ADMIN: &str = "admin";
+pub const FLAG_DENYOOM: &str = "denyoom";
+pub const FLAG_FAST: &str = "fast";
+pub const FLAG_BLOCKING: &str = "blocking";
+pub const FLAG_NOSCRIPT: &str = "noscript";
+pub const FLAG_LOADING: &str = "loading";
+pub const FLAG_STALE: &str = "stale";
+/// Flag que se agrega a los comandos cuyas claves dependen de sus
+/// argumentos
+pub const FLAG_MOVABLEKEYS: &str = "movablekeys";
+
+pub const GRUPO_STRING: &str = "string";
+pub const GRUPO_BITMAP: &str = "bitmap";
+pub const GRUPO_HYPERLOGLOG: &str = "hyperloglog";
+pub const GRUPO_GEO: &str = "geo";
+pub const GRUPO_LIST: &str = "list";
+pub const GRUPO_SET: &str = "set";
+pub const GRUPO_STREAM: &str = "stream";
+pub const GRUPO_GENERIC: &str = "generic";
+pub const GRUPO_SCRIPTING: &str = "scripting";
+pub const GRUPO_SERVER: &str = "server";
+pub const GRUPO_CONNECTION: &str = "connection";
+pub const GRUPO_PUBSUB: &str = "pubsub";
+
+const ESCRITURA: &[&str] = &[FLAG_WRITE];
+const ESCRITURA_DENYOOM: &[&str] = &[FLAG_WRITE, FLAG_DENYOOM];
+const ESCRITURA_RAPIDA: &[&str] = &[FLAG_WRITE, FLAG_FAST];
+const ESCRITURA_DENYOOM_RAPIDA: &[&str] = &[FLAG_WRITE, FLAG_DENYOOM, FLAG_FAST];
+const ESCRITURA_BLOQUEANTE: &[&str] = &[FLAG_WRITE, FLAG_BLOCKING];
+const LECTURA: &[&str] = &[FLAG_READONLY];
+const LECTURA_RAPIDA: &[&str] = &[FLAG_READONLY, FLAG_FAST];
+const SCRIPT_ESCRITURA: &[&str] = &[FLAG_WRITE, FLAG_NOSCRIPT];
+const SCRIPT_LECTURA: &[&str] = &[FLAG_READONLY, FLAG_NOSCRIPT];
+const ADMINISTRACION: &[&str] = &[FLAG_ADMIN, FLAG_NOSCRIPT, FLAG_LOADING, FLAG_STALE];
+const CONEXION: &[&str] = &[FLAG_NOSCRIPT, FLAG_LOADING, FLAG_STALE];
+const CONEXION_RAPIDA: &[&str] = &[FLAG_NOSCRIPT, FLAG_LOADING, FLAG_STALE, FLAG_FAST];
+const SUSCRIPCION: &[&str] = &[FLAG_PUBSUB, FLAG_NOSCRIPT, FLAG_LOADING, FLAG_STALE];
+const PUBLICACION: &[&str] = &[FLAG_PUBSUB, FLAG_LOADING, FLAG_STALE, FLAG_FAST];
+
+type FuncionIndices = fn(&[Token]) -> Vec<usize>;
+
+/// Descripción de un comando soportado por el nodo
+///
+/// # Campos
+/// - `nombre`: nombre del comando en mayúsculas.
+/// - `aridad`: cantidad de tokens, negativa si es la cantidad mínima.
+/// - `flags`: flags de redis del comando (`write`, `readonly`, `pubsub`...).
+/// - `primera_clave`, `ultima_clave`, `paso`: posiciones de las claves,
+///   con `ultima_clave` negativa si se cuenta desde el final. Un comando
+///   sin claves tiene `primera_clave` en 0.
+/// - `grupo` y `resumen`: documentación devuelta por COMMAND DOCS.
+#[derive(Debug)]
+pub struct InfoComando {
+    pub nombre: &'static str,
+    pub aridad: i64,
+    pub flags: &'static [&'static str],
+    pub primera_clave: i64,
+    pub ultima_clave: i64,
+    pub paso: i64,
+    pub grupo: &'static str,
+    pub resumen: &'static str,
+    /// Obtiene las claves cuando su posición depende de los argumentos
+    claves_movibles: Option<FuncionIndices>,
+    /// Obtiene los índices de los datos que se cifran en el AOF
+    indices_datos: FuncionIndices,
+    /// Determina si el comando escribe cuando depende del subcomando
+    escritura_segun: Option<fn(&[Token]) -> bool>,
+    /// Determina si el comando tiene el flag `denyoom` cuando depende del
+    /// subcomando
+    denyoom_segun: Option<fn(&[Token]) -> bool>,
+    /// Indica si un cliente suscripto en modo pub/sub puede ejecutarlo
+    permitido_en_pubsub: bool,
+}
+
+impl InfoComando {
+    /// Crea la descripción de un comando sin claves ni datos a cifrar
+    const fn new(
+        nombre: &'static str,
+        aridad: i64,
+        flags: &'static [&'static str],
+        grupo: &'static str,
+        resumen: &'static str,
+    ) -> Self {
+        InfoComando {
+            nombre,
+            aridad,
+            flags,
+            primera_clave: 0,
+            ultima_clave: 0,
+            paso: 0,
+            grupo,
+            resumen,
+            claves_movibles: None,
+            indices_datos: sin_datos,
+            escritura_segun: None,
+            denyoom_segun: None,
+            permitido_en_pubsub: false,
+        }
+    }
+
+    const fn claves(mut self, primera: i64, ultima: i64, paso: i64) -> Self {
+        self.primera_clave = primera;
+        self.ultima_clave = ultima;
+        self.paso = paso;
+        self
+    }
+
+    const fn una_clave(self) -> Self {
+        self.claves(1, 1, 1)
+    }
+
+    const fn claves_movibles(mut self, funcion: FuncionIndices) -> Self {
+        self.claves_movibles = Some(funcion);
+        self
+    }
+
+    const fn datos(mut self, funcion: FuncionIndices) -> Self {
+        self.indices_datos = funcion;
+        self
+    }
+
+    const fn escritura_segun(mut self, funcion: fn(&[Token]) -> bool) -> Self {
+        self.escritura_segun = Some(funcion);
+        self
+    }
+
+    const fn denyoom_segun(mut self, funcion: fn(&[Token]) -> bool) -> Self {
+        self.denyoom_segun = Some(funcion);
+        self
+    }
+
+    const fn permitido_en_pubsub(mut self) -> Self {
+        self.permitido_en_pubsub = true;
+        self
+    }
+
+    /// Indica si el comando modifica el storage
+    pub fn es_escritura(&self, tokens: &[Token]) -> bool {
+        match self.escritura_segun {
+            Some(funcion) => funcion(tokens),
+            None => self.flags.contains(&FLAG_WRITE),
+        }
+    }
+
+    /// Indica si el comando solo lee el storage
+    pub fn es_lectura(&self, tokens: &[Token]) -> bool {
+        match self.escritura_segun {
+            Some(funcion) => !funcion(tokens),
+            None => self.flags.contains(&FLAG_READONLY),
+        }
+    }
+
+    /// Indica si el comando puede aumentar la memoria usada, por lo que se
+    /// rechaza al superar `maxmemory` (flag `denyoom`)
+    pub fn es_denyoom(&self, tokens: &[Token]) -> bool {
+        match self.denyoom_segun {
+            Some(funcion) => funcion(tokens),
+            None => self.flags.contains(&FLAG_DENYOOM),
+        }
+    }
+
+    /// Indica si un cliente en modo pub/sub puede ejecutar el comando
+    pub fn es_permitido_en_pubsub(&self) -> bool {
+        self.permitido_en_pubsub
+    }
+
+    /// Indica si las claves del comando dependen de sus argumentos
+    pub fn tiene_claves_movibles(&self) -> bool {
+        self.claves_movibles.is_some()
+    }
+
+    /// Verifica que la cantidad de tokens respete la aridad del comando
+    pub fn aridad_valida(&self, cantidad: usize) -> bool {
+        let cantidad = cantidad as i64;
+        if self.aridad >= 0 {
+            cantidad == self.aridad
+        } else {
+            cantidad >= -self.aridad
+        }
+    }
+
+    /// Obtiene los índices de los tokens que son claves
+    pub fn indices_claves(&self, tokens: &[Token]) -> Vec<usize> {
+        if let Some(funcion) = self.claves_movibles {
+            return funcion(tokens);
+        }
+        if self.primera_clave <= 0 || tokens.is_empty() {
+            return Vec::new();
+        }
+        let ultima = if self.ultima_clave < 0 {
+            tokens.len() as i64 + self.ultima_clave
+        } else {
+            self.ultima_clave.min(tokens.len() as i64 - 1)
+        };
+        (self.primera_clave..=ultima)
+            .step_by(self.paso.max(1) as usize)
+            .map(|indice| indice as usize)
+            .collect()
+    }
+
+    /// Obtiene los índices de los tokens que son datos de usuario
+    pub fn indices_datos(&self, tokens: &[Token]) -> Vec<usize> {
+        (self.indices_datos)(tokens)
+    }
+}
+
+/// Índice de la tabla por nombre de comando, construido en la primera
+/// búsqueda
+static INDICE_COMANDOS: OnceLock<HashMap<&'static str, &'static InfoComando>> = OnceLock::new();
+
+/// Busca un comando en la tabla, sin distinguir mayúsculas de minúsculas
+pub fn buscar_comando(nombre: &str) -> Option<&'static InfoComando> {
+    let indice = INDICE_COMANDOS.get_or_init(|| {
+        TABLA_COMANDOS
+            .iter()
+            .map(|info| (info.nombre, info))
+            .collect()
+    });
+    match indice.get(nombre) {
+        Some(info) => Some(info),
+        None => indice.get(nombre.to_ascii_uppercase().as_str()).copied(),
+    }
+}
+
+fn sin_datos(_tokens: &[Token]) -> Vec<usize> {
+    Vec::new()
+}
+
+fn datos_segundo(tokens: &[Token]) -> Vec<usize> {
+    (2..tokens.len().min(3)).collect()
+}
+
+fn datos_tercero(tokens: &[Token]) -> Vec<usize> {
+    (3..tokens.len().min(4)).collect()
+}
+
+fn datos_desde_segundo(tokens: &[Token]) -> Vec<usize> {
+    (2..tokens.len()).collect()
+}
+
+fn datos_linsert(tokens: &[Token]) -> Vec<usize> {
+    (3..tokens.len().min(5)).collect()
+}
+
+fn datos_mset(tokens: &[Token]) -> Vec<usize> {
+    (2..tokens.len()).step_by(2).collect()
+}
+
+fn datos_geoadd(tokens: &[Token]) -> Vec<usize> {
+    (inicio_posiciones_geoadd(tokens)..tokens.len()).collect()
+}
+
+/// Los scripts pueden llamar a comandos `denyoom` y la memoria no se
+/// verifica en cada llamada, por lo que se rechazan al superar `maxmemory`
+fn script_denyoom(_tokens: &[Token]) -> bool {
+    true
+}
+
+/// Claves de EVAL, EVALSHA y FCALL, precedidas por su cantidad
+fn claves_numkeys(tokens: &[Token]) -> Vec<usize> {
+    if tokens.len() < 3 {
+        return Vec::new();
+    }
+    match cantidad_claves(tokens) {
+        Ok(cantidad) => (3..3 + cantidad).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Claves de LMPOP, precedidas por su cantidad
+fn claves_lmpop(tokens: &[Token]) -> Vec<usize> {
+    match tokens.get(1).and_then(|n| n.parse::<usize>().ok()) {
+        Some(cantidad) => (2..(2 + cantidad).min(tokens.len())).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Claves de XREAD y XREADGROUP: la primera mitad de los argumentos que
+/// siguen a STREAMS
+fn claves_streams(tokens: &[Token]) -> Vec<usize> {
+    let Some(inicio) = tokens
+        .iter()
+        .position(|t| t.eq_ignore_ascii_case(OPCION_STREAMS))
+    else {
+        return Vec::new();
+    };
+    let cantidad = (tokens.len() - inicio - 1) / 2;
+    (inicio + 1..inicio + 1 + cantidad).collect()
+}
+
+/// Comandos soportados por el nodo
+pub static TABLA_COMANDOS: &[InfoComando] = &[
+    // Comandos strings
+    InfoComando::new(
+        CMD_GET,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_STRING,
+        "Returns the string value of a key.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_SET,
+        -3,
+        ESCRITURA_DENYOOM,
+        GRUPO_STRING,
+        "Sets the string value of a key, ignoring its type.",
+    )
+    .una_clave()
+    .datos(datos_segundo),
+    InfoComando::new(
+        CMD_DEL,
+        -2,
+        ESCRITURA,
+        GRUPO_GENERIC,
+        "Deletes one or more keys.",
+    )
+    .claves(1, -1, 1),
+    InfoComando::new(
+        CMD_GETDEL,
+        2,
+        ESCRITURA_RAPIDA,
+        GRUPO_STRING,
+        "Returns the string value of a key after deleting the key.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_APPEND,
+        3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Appends a string to the value of a key.",
+    )
+    .una_clave()
+    .datos(datos_segundo),
+    InfoComando::new(
+        CMD_STRLEN,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_STRING,
+        "Returns the length of a string value.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_SUBSTR,
+        4,
+        LECTURA,
+        GRUPO_STRING,
+        "Returns a substring from a string value.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_GETRANGE,
+        4,
+        LECTURA,
+        GRUPO_STRING,
+        "Returns a substring of the string stored at a key.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_INCR,
+        2,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Increments the integer value of a key by one.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_DECR,
+        2,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Decrements the integer value of a key by one.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_INCRBY,
+        3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Increments the integer value of a key by a number.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_DECRBY,
+        3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Decrements the integer value of a key by a number.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_INCRBYFLOAT,
+        3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STRING,
+        "Increment the floating point value of a key by a number.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_MSET,
+        -3,
+        ESCRITURA_DENYOOM,
+        GRUPO_STRING,
+        "Atomically creates or modifies the string values of one or more keys.",
+    )
+    .claves(1, -1, 2)
+    .datos(datos_mset),
+    InfoComando::new(
+        CMD_MGET,
+        -2,
+        LECTURA_RAPIDA,
+        GRUPO_STRING,
+        "Atomically returns the string values of one or more keys.",
+    )
+    .claves(1, -1, 1),
+    InfoComando::new(
+        CMD_SETRANGE,
+        4,
+        ESCRITURA_DENYOOM,
+        GRUPO_STRING,
+        "Overwrites a part of a string value with another by an offset.",
+    )
+    .una_clave()
+    .datos(datos_tercero),
+    InfoComando::new(
+        CMD_GETEX,
+        -2,
+        ESCRITURA_RAPIDA,
+        GRUPO_STRING,
+        "Returns the string value of a key after setting its expiration time.",
+    )
+    .una_clave(),
+    // Comandos bitmap
+    InfoComando::new(
+        CMD_SETBIT,
+        4,
+        ESCRITURA_DENYOOM,
+        GRUPO_BITMAP,
+        "Sets or clears the bit at offset of the string value.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_GETBIT,
+        3,
+        LECTURA_RAPIDA,
+        GRUPO_BITMAP,
+        "Returns a bit value by offset.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_BITCOUNT,
+        -2,
+        LECTURA,
+        GRUPO_BITMAP,
+        "Counts the number of set bits (population counting) in a string.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_BITPOS,
+        -3,
+        LECTURA,
+        GRUPO_BITMAP,
+        "Finds the first set (1) or clear (0) bit in a string.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_BITOP,
+        -4,
+        ESCRITURA_DENYOOM,
+        GRUPO_BITMAP,
+        "Performs bitwise operations on multiple strings, and stores the result.",
+    )
+    .claves(2, -1, 1),
+    InfoComando::new(
+        CMD_BITFIELD,
+        -2,
+        ESCRITURA_DENYOOM,
+        GRUPO_BITMAP,
+        "Performs arbitrary bitfield integer operations on strings.",
+    )
+    .una_clave(),
+    // Comandos hyperloglog
+    InfoComando::new(
+        CMD_PFADD,
+        -2,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_HYPERLOGLOG,
+        "Adds elements to a HyperLogLog key.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_PFCOUNT,
+        -2,
+        LECTURA,
+        GRUPO_HYPERLOGLOG,
+        "Returns the approximated cardinality of the HyperLogLog key(s).",
+    )
+    .claves(1, -1, 1),
+    InfoComando::new(
+        CMD_PFMERGE,
+        -2,
+        ESCRITURA_DENYOOM,
+        GRUPO_HYPERLOGLOG,
+        "Merges one or more HyperLogLog values into a single key.",
+    )
+    .claves(1, -1, 1),
+    // Comandos geoespaciales
+    InfoComando::new(
+        CMD_GEOADD,
+        -5,
+        ESCRITURA_DENYOOM,
+        GRUPO_GEO,
+        "Adds one or more members to a geospatial index.",
+    )
+    .una_clave()
+    .datos(datos_geoadd),
+    InfoComando::new(
+        CMD_GEODIST,
+        -4,
+        LECTURA,
+        GRUPO_GEO,
+        "Returns the distance between two members of a geospatial index.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_GEOPOS,
+        -2,
+        LECTURA,
+        GRUPO_GEO,
+        "Returns the longitude and latitude of members from a geospatial index.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_GEOSEARCH,
+        -7,
+        LECTURA,
+        GRUPO_GEO,
+        "Queries a geospatial index for members inside an area of a box or a circle.",
+    )
+    .una_clave(),
+    // Comandos listas
+    InfoComando::new(
+        CMD_LINSERT,
+        5,
+        ESCRITURA_DENYOOM,
+        GRUPO_LIST,
+        "Inserts an element before or after another element in a list.",
+    )
+    .una_clave()
+    .datos(datos_linsert),
+    InfoComando::new(
+        CMD_LPUSH,
+        -3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_LIST,
+        "Prepends one or more elements to a list.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_RPUSH,
+        -3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_LIST,
+        "Appends one or more elements to a list.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_LLEN,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_LIST,
+        "Returns the length of a list.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_LPOP,
+        -2,
+        ESCRITURA_RAPIDA,
+        GRUPO_LIST,
+        "Returns the first elements in a list after removing it.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_RPOP,
+        -2,
+        ESCRITURA_RAPIDA,
+        GRUPO_LIST,
+        "Returns and removes the last elements of a list.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_LRANGE,
+        4,
+        LECTURA,
+        GRUPO_LIST,
+        "Returns a range of elements from a list.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_LSET,
+        4,
+        ESCRITURA_DENYOOM,
+        GRUPO_LIST,
+        "Sets the value of an element in a list by its index.",
+    )
+    .una_clave()
+    .datos(datos_tercero),
+    InfoComando::new(
+        CMD_LREM,
+        4,
+        ESCRITURA,
+        GRUPO_LIST,
+        "Removes elements from a list.",
+    )
+    .una_clave()
+    .datos(datos_tercero),
+    InfoComando::new(
+        CMD_LTRIM,
+        4,
+        ESCRITURA,
+        GRUPO_LIST,
+        "Removes elements from both ends a list.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_LINDEX,
+        3,
+        LECTURA,
+        GRUPO_LIST,
+        "Returns an element from a list by its index.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_LMOVE,
+        5,
+        ESCRITURA_DENYOOM,
+        GRUPO_LIST,
+        "Returns an element after popping it from one list and pushing it to another.",
+    )
+    .claves(1, 2, 1),
+    InfoComando::new(
+        CMD_BLPOP,
+        -3,
+        ESCRITURA_BLOQUEANTE,
+        GRUPO_LIST,
+        "Removes and returns the first element in a list, blocking if empty.",
+    )
+    .claves(1, -2, 1),
+    InfoComando::new(
+        CMD_BRPOP,
+        -3,
+        ESCRITURA_BLOQUEANTE,
+        GRUPO_LIST,
+        "Removes and returns the last element in a list, blocking if empty.",
+    )
+    .claves(1, -2, 1),
+    InfoComando::new(
+        CMD_BLMOVE,
+        6,
+        &[FLAG_WRITE, FLAG_DENYOOM, FLAG_BLOCKING],
+        GRUPO_LIST,
+        "Moves an element from a list to another, blocking if empty.",
+    )
+    .claves(1, 2, 1),
+    InfoComando::new(
+        CMD_LPOS,
+        -3,
+        LECTURA,
+        GRUPO_LIST,
+        "Returns the index of matching elements in a list.",
+    )
+    .una_clave()
+    .datos(datos_segundo),
+    InfoComando::new(
+        CMD_LMPOP,
+        -4,
+        ESCRITURA,
+        GRUPO_LIST,
+        "Returns multiple elements from a list after removing them.",
+    )
+    .claves_movibles(claves_lmpop),
+    InfoComando::new(
+        CMD_LPUSHX,
+        -3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_LIST,
+        "Prepends one or more elements to a list only when the list exists.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_RPUSHX,
+        -3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_LIST,
+        "Appends an element to a list only when the list exists.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_RPOPLPUSH,
+        3,
+        ESCRITURA_DENYOOM,
+        GRUPO_LIST,
+        "Moves the last element of a list to the head of another.",
+    )
+    .claves(1, 2, 1),
+    // Comandos set
+    InfoComando::new(
+        CMD_SADD,
+        -3,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_SET,
+        "Adds one or more members to a set.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_SCARD,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_SET,
+        "Returns the number of members in a set.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_SISMEMBER,
+        3,
+        LECTURA_RAPIDA,
+        GRUPO_SET,
+        "Determines whether a member belongs to a set.",
+    )
+    .una_clave()
+    .datos(datos_segundo),
+    InfoComando::new(
+        CMD_SREM,
+        -3,
+        ESCRITURA_RAPIDA,
+        GRUPO_SET,
+        "Removes one or more members from a set.",
+    )
+    .una_clave()
+    .datos(datos_desde_segundo),
+    InfoComando::new(
+        CMD_SMEMBERS,
+        2,
+        LECTURA,
+        GRUPO_SET,
+        "Returns all members of a set.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_SSCAN,
+        -3,
+        LECTURA,
+        GRUPO_SET,
+        "Iterates over members of a set.",
+    )
+    .una_clave(),
+    // Comandos stream
+    InfoComando::new(
+        CMD_XADD,
+        -5,
+        ESCRITURA_DENYOOM_RAPIDA,
+        GRUPO_STREAM,
+        "Appends a new message to a stream.",
+    )
+    .una_clave()
+    .datos(indices_campos_xadd),
+    InfoComando::new(
+        CMD_XLEN,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_STREAM,
+        "Return the number of messages in a stream.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XRANGE,
+        -4,
+        LECTURA,
+        GRUPO_STREAM,
+        "Returns the messages from a stream within a range of IDs.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XREVRANGE,
+        -4,
+        LECTURA,
+        GRUPO_STREAM,
+        "Returns the messages from a stream within a range of IDs in reverse order.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XTRIM,
+        -4,
+        ESCRITURA,
+        GRUPO_STREAM,
+        "Deletes messages from the beginning of a stream.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XDEL,
+        -3,
+        ESCRITURA_RAPIDA,
+        GRUPO_STREAM,
+        "Returns the number of messages after removing them from a stream.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XREAD,
+        -4,
+        &[FLAG_READONLY, FLAG_BLOCKING],
+        GRUPO_STREAM,
+        "Returns messages from multiple streams with IDs greater than the ones requested.",
+    )
+    .claves_movibles(claves_streams),
+    InfoComando::new(
+        CMD_XGROUP,
+        -2,
+        ESCRITURA,
+        GRUPO_STREAM,
+        "Manages consumer groups of a stream.",
+    )
+    .claves(2, 2, 1)
+    .denyoom_segun(xgroup_agrega_datos),
+    InfoComando::new(
+        CMD_XREADGROUP,
+        -7,
+        ESCRITURA_BLOQUEANTE,
+        GRUPO_STREAM,
+        "Returns new or historical messages from a stream for a consumer in a group.",
+    )
+    .claves_movibles(claves_streams),
+    InfoComando::new(
+        CMD_XACK,
+        -4,
+        ESCRITURA_RAPIDA,
+        GRUPO_STREAM,
+        "Acknowledges messages of a stream consumer group.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XPENDING,
+        -3,
+        LECTURA,
+        GRUPO_STREAM,
+        "Returns the pending entries list of a stream consumer group.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XCLAIM,
+        -6,
+        ESCRITURA_RAPIDA,
+        GRUPO_STREAM,
+        "Changes, or acquires, ownership of a message in a consumer group.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_XINFO,
+        -2,
+        LECTURA,
+        GRUPO_STREAM,
+        "Returns information about streams and their consumer groups.",
+    )
+    .claves(2, 2, 1),
+    // Comandos keyspace
+    InfoComando::new(
+        CMD_DUMP,
+        2,
+        LECTURA,
+        GRUPO_GENERIC,
+        "Returns a serialized representation of the value stored at a key.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_RESTORE,
+        -4,
+        ESCRITURA_DENYOOM,
+        GRUPO_GENERIC,
+        "Creates a key from the serialized representation of a value.",
+    )
+    .una_clave()
+    .datos(datos_tercero),
+    InfoComando::new(
+        CMD_MEMORY,
+        -2,
+        LECTURA,
+        GRUPO_SERVER,
+        "Estimates the memory usage of a key.",
+    )
+    .claves(2, 2, 1),
+    InfoComando::new(
+        CMD_OBJECT,
+        -2,
+        LECTURA,
+        GRUPO_GENERIC,
+        "Returns internal information about a key.",
+    )
+    .claves(2, 2, 1),
+    InfoComando::new(
+        CMD_EXISTS,
+        -2,
+        LECTURA_RAPIDA,
+        GRUPO_GENERIC,
+        "Determines whether one or more keys exist.",
+    )
+    .claves(1, -1, 1),
+    InfoComando::new(
+        CMD_TYPE,
+        2,
+        LECTURA_RAPIDA,
+        GRUPO_GENERIC,
+        "Determines the type of value stored at a key.",
+    )
+    .una_clave(),
+    InfoComando::new(
+        CMD_RENAME,
+        3,
+        ESCRITURA,
+        GRUPO_GENERIC,
+        "Renames a key and overwrites the destination.",
+    )
+    .claves(1, 2, 1),
+    InfoComando::new(
+        CMD_RENAMENX,
+        3,
+        ESCRITURA_RAPIDA,
+        GRUPO_GENERIC,
+        "Renames a key only when the target key name doesn't exist.",
+    )
+    .claves(1, 2, 1),
+    InfoComando::new(
+        CMD_COPY,
+        -3,
+        ESCRITURA_DENYOOM,
+        GRUPO_GENERIC,
+        "Copies the value of a key to a new key.",
+    )
+    .claves(1, 2, 1),
+    InfoComando::new(
+        CMD_UNLINK,
+        -2,
+        ESCRITURA_RAPIDA,
+        GRUPO_GENERIC,
+        "Asynchronously deletes one or more keys.",
+    )
+    .claves(1, -1, 1),
+    InfoComando::new(
+        CMD_DBSIZE,
+        1,
+        LECTURA_RAPIDA,
+        GRUPO_SERVER,
+        "Returns the number of keys in the database.",
+    ),
+    InfoComando::new(
+        CMD_FLUSHALL,
+        -1,
+        ESCRITURA,
+        GRUPO_SERVER,
+        "Removes all keys from all databases.",
+    ),
+    InfoComando::new(
+        CMD_RANDOMKEY,
+        1,
+        LECTURA,
+        GRUPO_GENERIC,
+        "Returns a random key name from the database.",
+    ),
+    InfoComando::new(
+        CMD_KEYS,
+        2,
+        LECTURA,
+        GRUPO_GENERIC,
+        "Returns all key names that match a pattern.",
+    ),
+    InfoComando::new(
+        CMD_SCAN,
+        -2,
+        LECTURA,
+        GRUPO_GENERIC,
+        "Iterates over the key names in the database.",
+    ),
+    // Comandos de scripting y funciones
+    InfoComando::new(
+        CMD_EVAL,
+        -3,
+        SCRIPT_ESCRITURA,
+        GRUPO_SCRIPTING,
+        "Executes a server-side script.",
+    )
+    .claves_movibles(claves_numkeys)
+    .datos(indices_datos_script)
+    .denyoom_segun(script_denyoom),
+    InfoComando::new(
+        CMD_EVALSHA,
+        -3,
+        SCRIPT_ESCRITURA,
+        GRUPO_SCRIPTING,
+        "Executes a server-side script by SHA1 digest.",
+    )
+    .claves_movibles(claves_numkeys)
+    .datos(indices_datos_script)
+    .denyoom_segun(script_denyoom),
+    InfoComando::new(
+        CMD_SCRIPT,
+        -2,
+        &[FLAG_NOSCRIPT],
+        GRUPO_SCRIPTING,
+        "Manages the server-side script cache.",
+    ),
+    InfoComando::new(
+        CMD_FUNCTION,
+        -2,
+        &[FLAG_NOSCRIPT],
+        GRUPO_SCRIPTING,
+        "Loads, lists, deletes or flushes libraries of functions.",
+    )
+    .datos(indices_datos_funciones)
+    .escritura_segun(es_mutacion_de_funciones)
+    .denyoom_segun(es_carga_de_funciones),
+    InfoComando::new(
+        CMD_FCALL,
+        -3,
+        SCRIPT_ESCRITURA,
+        GRUPO_SCRIPTING,
+        "Invokes a function.",
+    )
+    .claves_movibles(claves_numkeys)
+    .datos(indices_datos_funciones)
+    .denyoom_segun(script_denyoom),
+    InfoComando::new(
+        CMD_FCALL_RO,
+        -3,
+        SCRIPT_LECTURA,
+        GRUPO_SCRIPTING,
+        "Invokes a read-only function.",
+    )
+    .claves_movibles(claves_numkeys),
+    // Comandos de administración del servidor
+    InfoComando::new(
+        CMD_INFO,
+        -1,
+        &[FLAG_LOADING, FLAG_STALE],
+        GRUPO_SERVER,
+        "Returns information and statistics about the server.",
+    ),
+    InfoComando::new(
+        CMD_SLOWLOG,
+        -2,
+        ADMINISTRACION,
+        GRUPO_SERVER,
+        "Manages the slow log.",
+    ),
+    InfoComando::new(
+        CMD_LATENCY,
+        -2,
+        ADMINISTRACION,
+        GRUPO_SERVER,
+        "Reports and resets the latency samples of the server.",
+    ),
+    InfoComando::new(
+        CMD_CONFIG,
+        -2,
+        ADMINISTRACION,
+        GRUPO_SERVER,
+        "Gets, sets and rewrites the configuration parameters.",
+    ),
+    InfoComando::new(
+        CMD_CLIENT,
+        -2,
+        CONEXION,
+        GRUPO_CONNECTION,
+        "Manages client connections.",
+    ),
+    InfoComando::new(
+        CMD_MONITOR,
+        1,
+        ADMINISTRACION,
+        GRUPO_SERVER,
+        "Listens for all requests received by the server in real-time.",
+    ),
+    InfoComando::new(
+        CMD_TIME,
+        1,
+        &[FLAG_LOADING, FLAG_STALE, FLAG_FAST],
+        GRUPO_SERVER,
+        "Returns the server time.",
+    ),
+    InfoComando::new(
+        CMD_COMMAND,
+        -1,
+        &[FLAG_LOADING, FLAG_STALE],
+        GRUPO_SERVER,
+        "Returns detailed information about all commands.",
+    ),
+    // Comandos de conexión
+    InfoComando::new(
+        CMD_HELLO,
+        -1,
+        CONEXION_RAPIDA,
+        GRUPO_CONNECTION,
+        "Handshakes with the server.",
+    ),
+    InfoComando::new(
+        CMD_AUTH,
+        -2,
+        CONEXION_RAPIDA,
+        GRUPO_CONNECTION,
+        "Authenticates the connection.",
+    ),
+    InfoComando::new(
+        CMD_PING,
+        -1,
+        &[FLAG_FAST, FLAG_STALE],
+        GRUPO_CONNECTION,
+        "Returns the server's liveliness response.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_ECHO,
+        2,
+        &[FLAG_FAST, FLAG_STALE],
+        GRUPO_CONNECTION,
+        "Returns the given string.",
+    ),
+    InfoComando::new(
+        CMD_QUIT,
+        -1,
+        CONEXION_RAPIDA,
+        GRUPO_CONNECTION,
+        "Closes the connection.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_RESET,
+        1,
+        CONEXION_RAPIDA,
+        GRUPO_CONNECTION,
+        "Resets the connection.",
+    )
+    .permitido_en_pubsub(),
+    // Comandos pub/sub
+    InfoComando::new(
+        CMD_SUBSCRIBE,
+        -2,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Listens for messages published to channels.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_UNSUBSCRIBE,
+        -1,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Stops listening to messages posted to channels.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_PSUBSCRIBE,
+        -2,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Listens for messages published to channels that match one or more patterns.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_PUNSUBSCRIBE,
+        -1,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Stops listening to messages published to channels matching patterns.",
+    )
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_PUBLISH,
+        3,
+        PUBLICACION,
+        GRUPO_PUBSUB,
+        "Posts a message to a channel.",
+    ),
+    InfoComando::new(
+        CMD_PUBSUB,
+        -2,
+        &[FLAG_PUBSUB, FLAG_LOADING, FLAG_STALE],
+        GRUPO_PUBSUB,
+        "Inspects the state of the Pub/Sub subsystem.",
+    ),
+    InfoComando::new(
+        CMD_SSUBSCRIBE,
+        -2,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Listens for messages published to shard channels.",
+    )
+    .claves(1, -1, 1)
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_SUNSUBSCRIBE,
+        -1,
+        SUSCRIPCION,
+        GRUPO_PUBSUB,
+        "Stops listening to messages posted to shard channels.",
+    )
+    .claves(1, -1, 1)
+    .permitido_en_pubsub(),
+    InfoComando::new(
+        CMD_SPUBLISH,
+        3,
+        PUBLICACION,
+        GRUPO_PUBSUB,
+        "Post a message to a shard channel.",
+    )
+    .una_clave(),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test01_nombres_unicos_y_con_flags_consistentes() {
+        for (i, info) in TABLA_COMANDOS.iter().enumerate() {
+            assert!(
+                TABLA_COMANDOS[i + 1..]
+                    .iter()
+                    .all(|otro| otro.nombre != info.nombre),
+                "{} repetido",
+                info.nombre
+            );
+            assert!(
+                !(info.flags.contains(&FLAG_WRITE) && info.flags.contains(&FLAG_READONLY)),
+                "{}",
+                info.nombre
+            );
+        }
+        assert!(buscar_comando("get").is_some());
+        assert!(buscar_comando("NOEXISTE").is_none());
+    }
+
+    #[test]
+    fn test02_posiciones_de_claves() {
+        let indices = |comando: &[&str]| {
+            let tokens = tokens(comando);
+            buscar_comando(&tokens[0].texto())
+                .unwrap()
+                .indices_claves(&tokens)
+        };
+        assert_eq!(indices(&["GET", "a"]), vec![1]);
+        assert_eq!(indices(&["MSET", "a", "1", "b", "2"]), vec![1, 3]);
+        assert_eq!(indices(&["BLPOP", "a", "b", "0"]), vec![1, 2]);
+        assert_eq!(indices(&["EVAL", "s", "2", "a", "b", "c"]), vec![3, 4]);
+        assert_eq!(indices(&["LMPOP", "2", "a", "b", "LEFT"]), vec![2, 3]);
+        assert_eq!(
+            indices(&["XREAD", "COUNT", "1", "STREAMS", "s", "t", "0", "0"]),
+            vec![4, 5]
+        );
+        assert!(indices(&["KEYS", "*"]).is_empty());
+    }
+
+    #[test]
+    fn test03_escritura_y_aridad() {
+        let function = buscar_comando(CMD_FUNCTION).unwrap();
+        assert!(function.es_lectura(&tokens(&["FUNCTION", "LIST"])));
+        assert!(function.es_escritura(&tokens(&["FUNCTION", "FLUSH"])));
+        assert!(buscar_comando(CMD_SCARD).unwrap().es_lectura(&[]));
+        assert!(!buscar_comando(CMD_PING).unwrap().es_escritura(&[]));
+
+        let set = buscar_comando(CMD_SET).unwrap();
+        assert!(set.aridad_valida(3) && set.aridad_valida(5) && !set.aridad_valida(2));
+        let time = buscar_comando(CMD_TIME).unwrap();
+        assert!(time.aridad_valida(1) && !time.aridad_valida(2));
+    }
+
+    #[test]
+    fn test04_denyoom_segun_subcomando() {
+        let denyoom = |comando: &[&str]| {
+            let tokens = tokens(comando);
+            buscar_comando(&tokens[0].texto())
+                .unwrap()
+                .es_denyoom(&tokens)
+        };
+        assert!(denyoom(&["SET", "a", "1"]));
+        assert!(denyoom(&["XGROUP", "CREATE", "s", "g", "$"]));
+        assert!(denyoom(&["FUNCTION", "LOAD", "codigo"]));
+        assert!(!denyoom(&["XGROUP", "DESTROY", "s", "g"]));
+        assert!(!denyoom(&["XGROUP", "DELCONSUMER", "s", "g", "c"]));
+        assert!(!denyoom(&["GETEX", "a", "PERSIST"]));
+        assert!(!denyoom(&["FUNCTION", "DELETE", "lib"]));
+        assert!(!denyoom(&["DEL", "a"]));
+    }
+
+    #[test]
+    fn test05_comandos_permitidos_en_modo_pubsub() {
+        let permitido = |nombre| buscar_comando(nombre).unwrap().es_permitido_en_pubsub();
+        for nombre in [
+            CMD_PING,
+            CMD_QUIT,
+            CMD_RESET,
+            CMD_SUBSCRIBE,
+            CMD_SUNSUBSCRIBE,
+        ] {
+            assert!(permitido(nombre), "{nombre}");
+        }
+        assert!(!permitido(CMD_GET));
+        assert!(!permitido(CMD_ECHO));
+    }
+}
@@ -6,14 +6,27 @@ use std::{
     sync::{Arc, RwLock},
 };
 
+use glob::Pattern;
 use logger::logger::Logger;
+use redis_client::protocol::token::Token;
 use redis_client::{
     protocol::protocol_resp::resp_server_command_read,
-    tipos_datos::traits::{DatoRedis, TipoDatoRedis},
+    tipos_datos::{
+        arrays::Arrays,
+        traits::{DatoRedis, TipoDatoRedis},
+    },
 };
 
+use crate::comandos::tabla_comandos::buscar_comando;
+use crate::utils::utils_functions::handshake_functions;
 use crate::{client_struct::client::Client, storage::Storage};
-use crate::{comandos::const_cmd::*, utils::utils_functions::handshake_functions};
+
+/// Cantidad de elementos que intentan devolver los comandos SCAN si no se
+/// indica COUNT
+const SCAN_COUNT_DEFAULT: usize = 10;
+const OPCION_SCAN_MATCH: &str = "MATCH";
+const OPCION_SCAN_COUNT: &str = "COUNT";
+const OPCION_SCAN_TYPE: &str = "TYPE";
 
 /// Representa metadatos asociados a un comando Redis personalizado.
 ///
@@ -39,7 +52,8 @@ fn create_comando_metadata(indices: Vec<usize>, es_mutable: bool) -> ComandoMeta
     }
 }
 
-/// Devuelve los metadatos correspondientes a un comando Redis.
+/// Devuelve los metadatos correspondientes a un comando Redis, según la
+/// tabla de comandos.
 ///
 /// Define qué posiciones del comando contienen datos importantes (`indices_datos`)
 /// y si el comando modifica el estado (`es_mutable`).
@@ -50,23 +64,29 @@ fn create_comando_metadata(indices: Vec<usize>, es_mutable: bool) -> ComandoMeta
 ///
 /// # Retorna
 /// Un `ComandoMetadata` con la información correspondiente al comando dado.
-pub fn get_comando_metadata(comando: &str, tokens: &[String]) -> ComandoMetadata {
-    match comando.to_uppercase().as_str() {
-        CMD_SET | CMD_APPEND => create_comando_metadata(vec![2], true),
-        CMD_DEL | CMD_GETDEL | CMD_INCR | CMD_DECR | CMD_LPOP | CMD_RPOP | CMD_LTRIM
-        | CMD_LMOVE => create_comando_metadata(vec![], true),
-        CMD_SUBSTR | CMD_GETRANGE | CMD_GET | CMD_STRLEN | CMD_LLEN | CMD_LRANGE | CMD_LINDEX
-        | CMD_SCARD | CMD_SMEMBERS => create_comando_metadata(vec![], false),
-        CMD_LINSERT => create_comando_metadata(vec![3, 4], true),
-        CMD_LPUSH | CMD_RPUSH | CMD_SADD | CMD_SREM => {
-            create_comando_metadata((2..tokens.len()).collect(), true)
+pub fn get_comando_metadata(comando: &str, tokens: &[Token]) -> ComandoMetadata {
+    match buscar_comando(comando) {
+        Some(info) => {
+            create_comando_metadata(info.indices_datos(tokens), info.es_escritura(tokens))
         }
-        CMD_LSET | CMD_LREM => create_comando_metadata(vec![3], true),
-        CMD_SISMEMBER => create_comando_metadata(vec![2], false),
-        _ => create_comando_metadata(vec![], false),
+        None => create_comando_metadata(vec![], false),
     }
 }
 
+/// Determina si un comando puede aumentar la memoria utilizada por el
+/// storage, y por lo tanto debe rechazarse o provocar desalojos al superar
+/// `maxmemory`, segun el flag `denyoom` de la tabla de comandos.
+///
+/// # Argumentos
+/// - `comando`: Nombre del comando en mayúsculas.
+/// - `tokens`: Lista de tokens que componen el comando completo.
+///
+/// # Retorna
+/// `true` si el comando puede aumentar la memoria utilizada.
+pub fn puede_aumentar_memoria(comando: &str, tokens: &[Token]) -> bool {
+    buscar_comando(comando).is_some_and(|info| info.es_denyoom(tokens))
+}
+
 /// Determina si el largo deseado (expected) concuerda con el valor obtenido
 /// (len) y lanza un error de redis si no coinciden
 ///
@@ -79,7 +99,7 @@ pub fn get_comando_metadata(comando: &str, tokens: &[String]) -> ComandoMetadata
 /// - () en caso de que el valor real sea menor al esperadp, error de
 ///   redis en otro caso
 pub fn assert_correct_arguments_quantity(
-    key: String,
+    key: &str,
     expected: usize,
     len: usize,
 ) -> Result<(), DatoRedis> {
@@ -104,7 +124,7 @@ pub fn assert_correct_arguments_quantity(
 /// - () en caso de que ambos largos concuerden, error de redis en
 ///   otro caso
 pub fn assert_number_of_arguments_distinct(
-    key: String,
+    key: &str,
     greater: usize,
     len: usize,
 ) -> Result<(), DatoRedis> {
@@ -117,6 +137,108 @@ pub fn assert_number_of_arguments_distinct(
     Ok(())
 }
 
+/// Crea el error de redis para un subcomando no soportado
+///
+/// # Parámetros
+/// * `subcomando`: subcomando recibido
+pub fn error_subcomando_desconocido(subcomando: &str) -> DatoRedis {
+    DatoRedis::new_simple_error(
+        "ERR".to_string(),
+        format!("unknown subcommand '{subcomando}'"),
+    )
+}
+
+/// Opciones comunes a los comandos de la familia SCAN
+pub struct OpcionesScan {
+    pub(crate) patron: Option<Pattern>,
+    pub(crate) count: usize,
+    pub(crate) tipo: Option<String>,
+}
+
+impl OpcionesScan {
+    /// Indica si un elemento coincide con el patron de MATCH, de haberlo.
+    /// Los bytes que no son UTF-8 valido se reemplazan por `U+FFFD` antes de
+    /// comparar.
+    pub fn coincide(&self, elemento: &[u8]) -> bool {
+        self.patron
+            .as_ref()
+            .is_none_or(|patron| patron.matches(&String::from_utf8_lossy(elemento)))
+    }
+}
+
+/// Interpreta el cursor de un comando de la familia SCAN
+///
+/// # Parámetros
+/// * `cursor`: cursor recibido
+///
+/// # Retorna
+/// - Cursor como entero, error de redis si no es un entero sin signo
+pub fn parsear_cursor(cursor: &Token) -> Result<u64, DatoRedis> {
+    cursor
+        .parse::<u64>()
+        .map_err(|_| DatoRedis::new_simple_error("ERR".to_string(), "invalid cursor".to_string()))
+}
+
+/// Interpreta las opciones `MATCH patron`, `COUNT cantidad` y, si se
+/// admite, `TYPE tipo` de un comando de la familia SCAN
+///
+/// # Parámetros
+/// * `opciones`: tokens posteriores al cursor
+/// * `admite_tipo`: si el comando acepta la opcion TYPE
+///
+/// # Retorna
+/// - Opciones interpretadas (COUNT por defecto 10), error de redis si
+///   alguna es invalida
+pub fn parsear_opciones_scan(
+    opciones: &[Token],
+    admite_tipo: bool,
+) -> Result<OpcionesScan, DatoRedis> {
+    let error_sintaxis =
+        || DatoRedis::new_simple_error("ERR".to_string(), "syntax error".to_string());
+    let mut resultado = OpcionesScan {
+        patron: None,
+        count: SCAN_COUNT_DEFAULT,
+        tipo: None,
+    };
+
+    let mut opciones = opciones.iter();
+    while let Some(opcion) = opciones.next() {
+        let valor = opciones.next().ok_or_else(error_sintaxis)?;
+        match opcion.to_uppercase().as_str() {
+            OPCION_SCAN_MATCH => {
+                resultado.patron = Some(Pattern::new(&valor.texto()).map_err(|_| {
+                    DatoRedis::new_simple_error("ERR".to_string(), "invalid pattern".to_string())
+                })?);
+            }
+            OPCION_SCAN_COUNT => {
+                resultado.count = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or_else(error_sintaxis)?;
+            }
+            OPCION_SCAN_TYPE if admite_tipo => resultado.tipo = Some(valor.to_lowercase()),
+            _ => return Err(error_sintaxis()),
+        }
+    }
+    Ok(resultado)
+}
+
+/// Arma la respuesta de un comando de la familia SCAN: el cursor para la
+/// siguiente llamada y los elementos obtenidos
+///
+/// # Parámetros
+/// * `cursor`: cursor siguiente, 0 si la iteracion termino
+/// * `elementos`: elementos obtenidos
+pub fn respuesta_scan(cursor: u64, elementos: Vec<DatoRedis>) -> Result<DatoRedis, DatoRedis> {
+    Ok(DatoRedis::new_array_con_contenido(
+        Arrays::new_con_contenido(vec![
+            DatoRedis::new_bulk_string(cursor.to_string())?,
+            DatoRedis::new_array_con_contenido(Arrays::new_con_contenido(elementos)),
+        ]),
+    ))
+}
+
 /// Envia un mensaje de cantidad de argumentos incorrecta al logger
 ///
 /// # Parametros
@@ -142,8 +264,8 @@ pub fn send_msj_to_logger(client: &Arc<RwLock<Client>>, logger: &Logger) {
 pub fn send_msj(cli: Arc<RwLock<Client>>, mensaje: DatoRedis, logger: &Logger) {
     match cli.read() {
         Ok(cli_guard) => {
-            let resp = mensaje.convertir_a_protocolo_resp();
-            if cli_guard.send_sender(resp.to_string()).is_err() {
+            let resp = mensaje.convertir_a_protocolo_resp_bytes();
+            if cli_guard.send_sender(resp).is_err() {
                 logger.error(
                     &format!(
                         "Error al enviar mensaje al cliente: {}",
@@ -164,7 +286,7 @@ pub fn send_msj(cli: Arc<RwLock<Client>>, mensaje: DatoRedis, logger: &Logger) {
 
 pub fn do_handshake(
     client: &Arc<RwLock<Client>>,
-    comando_tokens: &[String],
+    comando_tokens: &[Token],
     logger: &Logger,
     users: &HashMap<String, String>,
 ) {
@@ -185,14 +307,14 @@ pub fn do_handshake(
     }
 }
 
-/// Obtiene un vector de tokens (strings) que conforman un comando
+/// Obtiene un vector de tokens que conforman un comando
 ///
 /// # Parametros
 /// * `reader`: TcpStream donde se encuentra el comando
 ///
 /// # Retorna
-/// - Option de vector de strings en caso de exito, None en otro caso
-pub fn leer_comando(reader: &mut TcpStream) -> Option<Vec<String>> {
+/// - Option de vector de tokens en caso de exito, None en otro caso
+pub fn leer_comando(reader: &mut TcpStream) -> Option<Vec<Token>> {
     resp_server_command_read(reader).ok()
 }
 
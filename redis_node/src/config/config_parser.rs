@@ -5,9 +5,17 @@ use std::fs;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::ops::Range;
 
+use logger::logger::NivelLog;
+
+use crate::client_struct::buffer_salida::LimitesBuffer;
+use crate::latencia::{LATENCIA_UMBRAL_DEFAULT, SLOWLOG_MAX_LEN_DEFAULT, SLOWLOG_UMBRAL_DEFAULT};
+use crate::memoria::{PoliticaEviction, parsear_memoria};
+use crate::notificaciones::parsear_flags;
+
 /// Estructura para almacenar la configuración del nodo
 #[derive(Debug)]
 pub struct Config {
+    path: String,
     address: SocketAddr,
     cluster_address: SocketAddr,
     public_address: SocketAddr,
@@ -26,6 +34,17 @@ pub struct Config {
     node_seed: Option<SocketAddr>,
     replicaof: Option<SocketAddr>,
     node_timeout: u64,
+
+    maxmemory: usize,
+    maxmemory_policy: PoliticaEviction,
+    loglevel: NivelLog,
+
+    slowlog_log_slower_than: i64,
+    slowlog_max_len: usize,
+    latency_monitor_threshold: u64,
+
+    notify_keyspace_events: String,
+    client_output_buffer_limit: LimitesBuffer,
 }
 
 impl Config {
@@ -44,6 +63,7 @@ impl Config {
         let cluster_address = Self::parse_cluster_address(&map)?;
 
         Ok(Config {
+            path: path.to_string(),
             address,
             cluster_address,
             public_address,
@@ -59,6 +79,26 @@ impl Config {
             node_seed: Self::get_socket_addr(&map, "node_id_seed")?,
             replicaof: Self::get_socket_addr(&map, "replicaof")?,
             node_timeout: Self::get_node_timeout(&map)?,
+            maxmemory: Self::parse_maxmemory(&map)?,
+            maxmemory_policy: Self::parse_maxmemory_policy(&map)?,
+            loglevel: Self::parse_loglevel(&map)?,
+            slowlog_log_slower_than: Self::parse_opcional(
+                &map,
+                "slowlog_log_slower_than",
+                SLOWLOG_UMBRAL_DEFAULT,
+            )?,
+            slowlog_max_len: Self::parse_opcional(
+                &map,
+                "slowlog_max_len",
+                SLOWLOG_MAX_LEN_DEFAULT,
+            )?,
+            latency_monitor_threshold: Self::parse_opcional(
+                &map,
+                "latency_monitor_threshold",
+                LATENCIA_UMBRAL_DEFAULT,
+            )?,
+            notify_keyspace_events: Self::parse_notify_keyspace_events(&map)?,
+            client_output_buffer_limit: Self::parse_client_output_buffer_limit(&map)?,
         })
     }
 
@@ -120,6 +160,84 @@ impl Config {
         self.public_address
     }
 
+    pub fn get_maxmemory(&self) -> usize {
+        self.maxmemory
+    }
+
+    pub fn get_maxmemory_policy(&self) -> PoliticaEviction {
+        self.maxmemory_policy
+    }
+
+    pub fn get_loglevel(&self) -> NivelLog {
+        self.loglevel
+    }
+
+    pub fn get_slowlog_log_slower_than(&self) -> i64 {
+        self.slowlog_log_slower_than
+    }
+
+    pub fn get_slowlog_max_len(&self) -> usize {
+        self.slowlog_max_len
+    }
+
+    pub fn get_latency_monitor_threshold(&self) -> u64 {
+        self.latency_monitor_threshold
+    }
+
+    pub fn get_notify_keyspace_events(&self) -> String {
+        self.notify_keyspace_events.to_string()
+    }
+
+    pub fn get_client_output_buffer_limit(&self) -> LimitesBuffer {
+        self.client_output_buffer_limit
+    }
+
+    /// Ruta del archivo de configuración del que se cargó el nodo
+    pub fn get_path(&self) -> String {
+        self.path.to_string()
+    }
+
+    /// Reescribe un archivo de configuración con los valores recibidos,
+    /// preservando comentarios, líneas en blanco y el resto de los campos.
+    /// Los parámetros que no figuran en el archivo se agregan al final.
+    ///
+    /// # Parámetros
+    /// - `path`: ruta al archivo de configuración
+    /// - `valores`: pares `(clave, valor)` a escribir
+    ///
+    /// # Retorna
+    /// - `Ok(())` si el archivo se reescribió, un mensaje de error en otro caso
+    pub fn reescribir(path: &str, valores: &[(String, String)]) -> Result<(), String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Error leyendo archivo de configuración '{path:?}': {e}"))?;
+        let mut escritos = vec![false; valores.len()];
+        let mut lineas: Vec<String> = Vec::new();
+
+        for line in contents.lines() {
+            let reemplazo = match line.split_once('=') {
+                Some((key, value)) if !line.trim_start().starts_with('#') => valores
+                    .iter()
+                    .position(|(clave, _)| clave == key.trim())
+                    .map(|i| {
+                        escritos[i] = true;
+                        let espacio = &value[..value.len() - value.trim_start().len()];
+                        format!("{key}={espacio}{}", valores[i].1)
+                    }),
+                _ => None,
+            };
+            lineas.push(reemplazo.unwrap_or_else(|| line.to_string()));
+        }
+
+        for (i, (clave, valor)) in valores.iter().enumerate() {
+            if !escritos[i] {
+                lineas.push(format!("{clave}={valor}"));
+            }
+        }
+
+        fs::write(path, lineas.join("\n") + "\n")
+            .map_err(|e| format!("Error escribiendo archivo de configuración '{path:?}': {e}"))
+    }
+
     // Funciones privadas para el manejo de la configuración
 
     /// Convierte un vector de líneas en un HashMap
@@ -320,6 +438,70 @@ impl Config {
         }
         Ok(ms)
     }
+
+    /// Función para obtener el límite de memoria (opcional, 0 = sin límite)
+    fn parse_maxmemory(map: &HashMap<String, String>) -> Result<usize, String> {
+        match map.get("maxmemory") {
+            Some(value) => parsear_memoria(value),
+            None => Ok(0),
+        }
+    }
+
+    /// Función para obtener un campo numérico opcional
+    fn parse_opcional<T: std::str::FromStr>(
+        map: &HashMap<String, String>,
+        key: &str,
+        default: T,
+    ) -> Result<T, String> {
+        match map.get(key) {
+            Some(value) => value
+                .parse::<T>()
+                .map_err(|_| format!("No se pudo parsear '{key}' (\"{value}\")")),
+            None => Ok(default),
+        }
+    }
+
+    /// Función para obtener el nivel de log (opcional, por defecto `debug`)
+    fn parse_loglevel(map: &HashMap<String, String>) -> Result<NivelLog, String> {
+        match map.get("loglevel") {
+            Some(value) => NivelLog::from_nombre(value)
+                .ok_or_else(|| format!("Valor inválido para 'loglevel': '{value}'")),
+            None => Ok(NivelLog::Debug),
+        }
+    }
+
+    /// Función para obtener los flags de notificaciones de keyspace
+    /// (opcional, por defecto deshabilitadas)
+    fn parse_notify_keyspace_events(map: &HashMap<String, String>) -> Result<String, String> {
+        match map.get("notify_keyspace_events") {
+            Some(value) => parsear_flags(value)
+                .map(|_| value.to_string())
+                .map_err(|e| format!("Valor inválido para 'notify_keyspace_events': {e}")),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Función para obtener los límites del buffer de salida de los
+    /// suscriptores (opcional, por defecto `pubsub 32mb 8mb 60`)
+    fn parse_client_output_buffer_limit(
+        map: &HashMap<String, String>,
+    ) -> Result<LimitesBuffer, String> {
+        match map.get("client_output_buffer_limit") {
+            Some(value) => LimitesBuffer::parsear(value)
+                .map_err(|e| format!("Valor inválido para 'client_output_buffer_limit': {e}")),
+            None => Ok(LimitesBuffer::default()),
+        }
+    }
+
+    /// Función para obtener la política de desalojo (opcional, por defecto
+    /// `noeviction`)
+    fn parse_maxmemory_policy(map: &HashMap<String, String>) -> Result<PoliticaEviction, String> {
+        match map.get("maxmemory_policy") {
+            Some(value) => PoliticaEviction::from_nombre(value)
+                .ok_or_else(|| format!("Valor inválido para 'maxmemory_policy': '{value}'")),
+            None => Ok(PoliticaEviction::default()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -958,4 +1140,132 @@ mod tests {
 
         remove_temp_config_file(test_file_path);
     }
+
+    #[test]
+    fn test_20_maxmemory_y_politica() {
+        let lines = [
+            "ip=127.0.0.1",
+            "port=8089",
+            "slot_range_start=0",
+            "slot_range_end=16378",
+            "max_clients=100",
+            "aof_file=aof.log",
+            "metadata_file=metadata.bin",
+            "storage_file=storage.bin",
+            "log_file=node_1.log",
+            "users_file=src/config/users_test.txt",
+            "appendonly=yes",
+            "save=900",
+            "node_timeout=5000",
+            "cluster_ip=127.0.0.1",
+            "maxmemory=10mb",
+            "maxmemory_policy=allkeys-lru",
+        ];
+        let config_content = lines.join("\n") + "\n";
+        let test_file_path = "temp_valid_config_maxmemory.txt";
+        create_temp_config_file(test_file_path, &config_content);
+
+        let config = Config::from_file(test_file_path).unwrap();
+        assert_eq!(config.get_maxmemory(), 10 * 1024 * 1024);
+        assert_eq!(config.get_maxmemory_policy(), PoliticaEviction::AllKeysLru);
+        assert_eq!(config.get_slowlog_log_slower_than(), SLOWLOG_UMBRAL_DEFAULT);
+
+        remove_temp_config_file(test_file_path);
+    }
+
+    #[test]
+    fn test_21_maxmemory_policy_invalida() {
+        let lines = [
+            "ip=127.0.0.1",
+            "port=8089",
+            "slot_range_start=0",
+            "slot_range_end=16378",
+            "max_clients=100",
+            "aof_file=aof.log",
+            "metadata_file=metadata.bin",
+            "storage_file=storage.bin",
+            "log_file=node_1.log",
+            "users_file=src/config/users_test.txt",
+            "appendonly=yes",
+            "save=900",
+            "node_timeout=5000",
+            "cluster_ip=127.0.0.1",
+            "maxmemory_policy=allkeys-random",
+        ];
+        let config_content = lines.join("\n") + "\n";
+        let test_file_path = "temp_invalid_config_maxmemory.txt";
+        create_temp_config_file(test_file_path, &config_content);
+
+        assert!(Config::from_file(test_file_path).is_err());
+
+        remove_temp_config_file(test_file_path);
+    }
+
+    #[test]
+    fn test_22_reescribir_preserva_comentarios() {
+        let lines = [
+            "# Configuracion de prueba",
+            "ip=127.0.0.1",
+            "",
+            "save = 900",
+            "# node_timeout=1",
+            "node_timeout=5000",
+        ];
+        let config_content = lines.join("\n") + "\n";
+        let test_file_path = "temp_config_rewrite.txt";
+        create_temp_config_file(test_file_path, &config_content);
+
+        let valores = [
+            ("save".to_string(), "1000".to_string()),
+            ("node_timeout".to_string(), "2000".to_string()),
+            ("loglevel".to_string(), "warning".to_string()),
+        ];
+        Config::reescribir(test_file_path, &valores).unwrap();
+
+        let reescrito = fs::read_to_string(test_file_path).unwrap();
+        let esperado = [
+            "# Configuracion de prueba",
+            "ip=127.0.0.1",
+            "",
+            "save = 1000",
+            "# node_timeout=1",
+            "node_timeout=2000",
+            "loglevel=warning",
+        ];
+        assert_eq!(reescrito, esperado.join("\n") + "\n");
+
+        remove_temp_config_file(test_file_path);
+    }
+
+    #[test]
+    fn test_23_notify_keyspace_events() {
+        let mut lines = vec![
+            "ip=127.0.0.1",
+            "port=8089",
+            "slot_range_start=0",
+            "slot_range_end=16378",
+            "max_clients=100",
+            "aof_file=aof.log",
+            "metadata_file=metadata.bin",
+            "storage_file=storage.bin",
+            "log_file=node_1.log",
+            "users_file=src/config/users_test.txt",
+            "appendonly=yes",
+            "save=900",
+            "node_timeout=5000",
+            "cluster_ip=127.0.0.1",
+            "notify_keyspace_events=KEA",
+        ];
+        let test_file_path = "temp_config_notify_keyspace_events.txt";
+        create_temp_config_file(test_file_path, &(lines.join("\n") + "\n"));
+        let config = Config::from_file(test_file_path).unwrap();
+        assert_eq!(config.get_notify_keyspace_events(), "KEA");
+
+        lines.pop();
+        lines.push("notify_keyspace_events=Kq");
+        create_temp_config_file(test_file_path, &(lines.join("\n") + "\n"));
+        assert!(Config::from_file(test_file_path).is_err());
+
+        remove_temp_config_file(test_file_path);
+    }
 }
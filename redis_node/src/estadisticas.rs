@@ -0,0 +1,95 @@
+//! Este módulo contiene las estadísticas de ejecución del nodo que se
+//! exponen a través del comando INFO
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Contadores y estado de ejecución del nodo. Todos los campos son atómicos
+/// para poder actualizarse desde los distintos hilos del nodo.
+#[derive(Debug)]
+pub struct EstadisticasNodo {
+    inicio: Instant,
+    comandos_procesados: AtomicU64,
+    conexiones_recibidas: AtomicU64,
+    ultimo_guardado: AtomicU64,
+    ultimo_guardado_ok: AtomicBool,
+    guardados_realizados: AtomicU64,
+}
+
+impl Default for EstadisticasNodo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EstadisticasNodo {
+    /// Crea las estadísticas de un nodo que acaba de iniciar.
+    pub fn new() -> Self {
+        EstadisticasNodo {
+            inicio: Instant::now(),
+            comandos_procesados: AtomicU64::new(0),
+            conexiones_recibidas: AtomicU64::new(0),
+            ultimo_guardado: AtomicU64::new(0),
+            ultimo_guardado_ok: AtomicBool::new(true),
+            guardados_realizados: AtomicU64::new(0),
+        }
+    }
+
+    /// Registra un comando recibido de un cliente.
+    pub fn registrar_comando(&self) {
+        self.comandos_procesados.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Registra una conexión de cliente aceptada.
+    pub fn registrar_conexion(&self) {
+        self.conexiones_recibidas.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Registra el resultado de un guardado del snapshot del storage.
+    ///
+    /// # Parámetros
+    /// - `exitoso`: indica si el guardado pudo completarse.
+    pub fn registrar_guardado(&self, exitoso: bool) {
+        self.ultimo_guardado_ok.store(exitoso, Ordering::Relaxed);
+        if exitoso {
+            let ahora = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            self.ultimo_guardado.store(ahora, Ordering::Relaxed);
+            self.guardados_realizados.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Reinicia los contadores de comandos y conexiones (CONFIG RESETSTAT).
+    pub fn reiniciar(&self) {
+        self.comandos_procesados.store(0, Ordering::Relaxed);
+        self.conexiones_recibidas.store(0, Ordering::Relaxed);
+    }
+
+    /// Segundos transcurridos desde el inicio del nodo.
+    pub fn segundos_activo(&self) -> u64 {
+        self.inicio.elapsed().as_secs()
+    }
+
+    pub fn comandos_procesados(&self) -> u64 {
+        self.comandos_procesados.load(Ordering::Relaxed)
+    }
+
+    pub fn conexiones_recibidas(&self) -> u64 {
+        self.conexiones_recibidas.load(Ordering::Relaxed)
+    }
+
+    /// Instante (en segundos desde UNIX epoch) del último guardado exitoso,
+    /// o 0 si todavía no se guardó.
+    pub fn ultimo_guardado(&self) -> u64 {
+        self.ultimo_guardado.load(Ordering::Relaxed)
+    }
+
+    pub fn ultimo_guardado_ok(&self) -> bool {
+        self.ultimo_guardado_ok.load(Ordering::Relaxed)
+    }
+
+    pub fn guardados_realizados(&self) -> u64 {
+        self.guardados_realizados.load(Ordering::Relaxed)
+    }
+}
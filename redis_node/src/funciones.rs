@@ -0,0 +1,293 @@
+//! Este módulo contiene las bibliotecas de funciones del nodo, cargadas con
+//! FUNCTION LOAD y ejecutadas con FCALL y FCALL_RO.
+//!
+//! Una biblioteca es un programa de Forth cuya primera línea es el header
+//! `#!forth name=<biblioteca> [version=<version>]` y cuyo resto solo define
+//! palabras. Cada
+//! palabra definida es una función de la biblioteca, que se invoca por su
+//! nombre sin distinguir mayúsculas de minúsculas.
+use std::collections::BTreeMap;
+
+use interpretefth::palabras_definidas;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+const PREFIJO_HEADER: &str = "#!";
+const MOTOR: &str = "forth";
+const PARAMETRO_NOMBRE: &str = "name=";
+const PARAMETRO_VERSION: &str = "version=";
+
+/// Biblioteca de funciones cargada en el nodo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biblioteca {
+    nombre: String,
+    /// Versión declarada en el header, de haberla
+    version: Option<String>,
+    /// Código tal como se cargó, que puede no ser UTF-8 válido
+    codigo: Vec<u8>,
+    funciones: Vec<String>,
+}
+
+impl Biblioteca {
+    /// Interpreta el código de una biblioteca, verificando su header y que
+    /// solo defina palabras
+    ///
+    /// # Retorna
+    /// - Biblioteca con las funciones que define, error de redis si el
+    ///   código es inválido
+    fn new(codigo: &[u8]) -> Result<Self, DatoRedis> {
+        let texto = String::from_utf8_lossy(codigo);
+        let mut lineas = texto.lines();
+        let (nombre, version) = metadata_de_header(lineas.next().unwrap_or(""))?;
+        let funciones = palabras_definidas(lineas.map(String::from).collect())
+            .map_err(|e| error(&format!("Error compiling function: {e}")))?;
+        if funciones.is_empty() {
+            return Err(error("No functions registered"));
+        }
+        Ok(Self {
+            nombre,
+            version,
+            codigo: codigo.to_vec(),
+            funciones,
+        })
+    }
+
+    pub fn nombre(&self) -> &str {
+        &self.nombre
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn codigo(&self) -> &[u8] {
+        &self.codigo
+    }
+
+    /// Nombres de las funciones de la biblioteca, ordenados
+    pub fn funciones(&self) -> &[String] {
+        &self.funciones
+    }
+
+    /// Obtiene el programa que ejecuta una función de la biblioteca: el
+    /// código sin su header seguido de la invocación de la función
+    pub fn programa(&self, funcion: &str) -> Vec<String> {
+        String::from_utf8_lossy(&self.codigo)
+            .lines()
+            .skip(1)
+            .map(String::from)
+            .chain(std::iter::once(funcion.to_string()))
+            .collect()
+    }
+}
+
+/// Bibliotecas de funciones del nodo, indexadas por nombre
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Funciones {
+    bibliotecas: BTreeMap<String, Biblioteca>,
+}
+
+impl Funciones {
+    /// Carga una biblioteca
+    ///
+    /// # Parámetros
+    /// * `codigo`: código de la biblioteca, incluyendo su header
+    /// * `reemplazar`: si se reemplaza una biblioteca existente del mismo
+    ///   nombre
+    ///
+    /// # Retorna
+    /// - Nombre de la biblioteca, error de redis si el código es inválido,
+    ///   la biblioteca ya existe o alguna de sus funciones pertenece a otra
+    ///   biblioteca
+    pub fn cargar(
+        &mut self,
+        codigo: impl AsRef<[u8]>,
+        reemplazar: bool,
+    ) -> Result<String, DatoRedis> {
+        let biblioteca = Biblioteca::new(codigo.as_ref())?;
+        if !reemplazar && self.bibliotecas.contains_key(&biblioteca.nombre) {
+            return Err(error(&format!(
+                "Library '{}' already exists",
+                biblioteca.nombre
+            )));
+        }
+        for funcion in &biblioteca.funciones {
+            if let Some(otra) = self.biblioteca_de_funcion(funcion)
+                && otra.nombre != biblioteca.nombre
+            {
+                return Err(error(&format!("Function {funcion} already exists")));
+            }
+        }
+        let nombre = biblioteca.nombre.clone();
+        self.bibliotecas.insert(nombre.clone(), biblioteca);
+        Ok(nombre)
+    }
+
+    /// Elimina una biblioteca con todas sus funciones
+    ///
+    /// # Retorna
+    /// - () si la biblioteca existía, error de redis en otro caso
+    pub fn eliminar(&mut self, nombre: &str) -> Result<(), DatoRedis> {
+        self.bibliotecas
+            .remove(nombre)
+            .map(|_| ())
+            .ok_or_else(|| error("Library not found"))
+    }
+
+    /// Elimina todas las bibliotecas
+    pub fn vaciar(&mut self) {
+        self.bibliotecas.clear();
+    }
+
+    /// Obtiene la biblioteca que define una función
+    pub fn biblioteca_de_funcion(&self, funcion: &str) -> Option<&Biblioteca> {
+        let funcion = funcion.to_lowercase();
+        self.bibliotecas
+            .values()
+            .find(|biblioteca| biblioteca.funciones.contains(&funcion))
+    }
+
+    /// Iterador sobre las bibliotecas, ordenadas por nombre
+    pub fn bibliotecas(&self) -> impl Iterator<Item = &Biblioteca> {
+        self.bibliotecas.values()
+    }
+}
+
+/// Obtiene el nombre y la versión de una biblioteca a partir de su header
+/// (`#!forth name=<biblioteca> [version=<version>]`)
+fn metadata_de_header(header: &str) -> Result<(String, Option<String>), DatoRedis> {
+    let Some(header) = header.strip_prefix(PREFIJO_HEADER) else {
+        return Err(error("Missing library metadata"));
+    };
+    let mut partes = header.split_whitespace();
+    let motor = partes.next().unwrap_or("");
+    if !motor.eq_ignore_ascii_case(MOTOR) {
+        return Err(error(&format!("Engine '{motor}' not found")));
+    }
+    let mut nombre = None;
+    let mut version = None;
+    for parte in partes {
+        if let Some(valor) = parte.strip_prefix(PARAMETRO_NOMBRE) {
+            nombre = Some(valor);
+        } else if let Some(valor) = parte.strip_prefix(PARAMETRO_VERSION) {
+            version = Some(valor);
+        } else {
+            return Err(error(&format!("Invalid metadata value given: {parte}")));
+        }
+    }
+    let Some(nombre) = nombre else {
+        return Err(error("Library name was not given"));
+    };
+    if nombre.is_empty()
+        || !nombre
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(error(
+            "Library names can only contain letters, numbers, or underscores(_) and must be at least one character long",
+        ));
+    }
+    if let Some(version) = version
+        && (version.is_empty()
+            || !version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')))
+    {
+        return Err(error(
+            "Library versions can only contain letters, numbers, dots(.), dashes(-) or underscores(_) and must be at least one character long",
+        ));
+    }
+    Ok((nombre.to_string(), version.map(String::from)))
+}
+
+fn error(mensaje: &str) -> DatoRedis {
+    DatoRedis::new_simple_error("ERR".to_string(), mensaje.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENTOS: &str =
+        "#!forth name=documentos\n: nuevo_id call\" INCR KEYS[1]\" ;\n: Siguiente 1 + ;";
+
+    fn mensaje(resultado: Result<String, DatoRedis>) -> String {
+        match resultado {
+            Err(DatoRedis::SimpleError(error)) => error.mensaje().to_string(),
+            otro => panic!("se esperaba un error: {otro:?}"),
+        }
+    }
+
+    #[test]
+    fn test_01_cargar_biblioteca() {
+        let mut funciones = Funciones::default();
+        assert_eq!(funciones.cargar(DOCUMENTOS, false).unwrap(), "documentos");
+
+        let biblioteca = funciones.biblioteca_de_funcion("SIGUIENTE").unwrap();
+        assert_eq!(biblioteca.nombre(), "documentos");
+        assert_eq!(biblioteca.version(), None);
+        assert_eq!(biblioteca.funciones(), ["nuevo_id", "siguiente"]);
+        assert_eq!(
+            biblioteca.programa("nuevo_id"),
+            [
+                ": nuevo_id call\" INCR KEYS[1]\" ;",
+                ": Siguiente 1 + ;",
+                "nuevo_id"
+            ]
+        );
+
+        assert!(mensaje(funciones.cargar(DOCUMENTOS, false)).contains("already exists"));
+        assert!(funciones.cargar(DOCUMENTOS, true).is_ok());
+        let otra = "#!forth name=otra\n: siguiente 2 + ;";
+        assert_eq!(
+            mensaje(funciones.cargar(otra, false)),
+            "Function siguiente already exists"
+        );
+
+        funciones.eliminar("documentos").unwrap();
+        assert!(funciones.biblioteca_de_funcion("nuevo_id").is_none());
+        assert!(funciones.eliminar("documentos").is_err());
+    }
+
+    #[test]
+    fn test_02_codigo_invalido() {
+        let mut funciones = Funciones::default();
+        let casos = [
+            (": f 1 ;", "Missing library metadata"),
+            ("#!lua name=f\n: f 1 ;", "Engine 'lua' not found"),
+            ("#!forth\n: f 1 ;", "Library name was not given"),
+            (
+                "#!forth name=f otro=1\n: f 1 ;",
+                "Invalid metadata value given",
+            ),
+            (
+                "#!forth name=a-b\n: f 1 ;",
+                "Library names can only contain",
+            ),
+            ("#!forth name=f", "No functions registered"),
+            (
+                "#!forth name=f version=\n: f 1 ;",
+                "Library versions can only",
+            ),
+            (
+                "#!forth name=f version=1/2\n: f 1 ;",
+                "Library versions can only",
+            ),
+            ("#!forth name=f\n: f 1 ;\nf", "Error compiling function"),
+            ("#!forth name=f\ncall\" GET a\"", "Error compiling function"),
+        ];
+        for (codigo, esperado) in casos {
+            assert!(mensaje(funciones.cargar(codigo, false)).starts_with(esperado));
+        }
+        assert_eq!(funciones.bibliotecas().count(), 0);
+    }
+
+    #[test]
+    fn test_03_version_del_header() {
+        let mut funciones = Funciones::default();
+        let codigo = "#!forth version=1.2-beta name=documentos\n: siguiente 1 + ;";
+        funciones.cargar(codigo, false).unwrap();
+        let biblioteca = funciones.biblioteca_de_funcion("siguiente").unwrap();
+        assert_eq!(biblioteca.nombre(), "documentos");
+        assert_eq!(biblioteca.version(), Some("1.2-beta"));
+    }
+}
@@ -1,5 +1,6 @@
 use super::fail_auth_req::FailOverAuthRequest;
 use super::redis_cmd::RedisCMD;
+use super::resumen_pubsub::ResumenPubSub;
 use crate::internal_protocol::gossip::GossipEntry;
 use crate::internal_protocol::header::MessageHeader;
 use crate::internal_protocol::internal_protocol_type::InternalProtocolType;
@@ -24,6 +25,9 @@ pub enum ClusterMessagePayload {
     /// Payload para mensajes de publish (pub/sub).
     PubSub(RedisCMD),
 
+    /// Payload con el resumen de las suscripciones pub/sub del nodo emisor.
+    PubSubSummary(ResumenPubSub),
+
     /// Payload para pedidos de votación de réplicas a masters.
     FailAuthReq(FailOverAuthRequest),
 
@@ -106,6 +110,9 @@ impl SerializeRIP for ClusterMessage {
             | ClusterMessagePayload::FailNegotiation(failover_req) => {
                 bytes.extend(FailOverAuthRequest::serialize(failover_req));
             }
+            ClusterMessagePayload::PubSubSummary(resumen) => {
+                bytes.extend(resumen.serialize());
+            }
 
             ClusterMessagePayload::Meet => {}
 
@@ -154,6 +161,10 @@ impl DeserializeRIP for ClusterMessage {
                 let rep_offset = FailOverAuthRequest::deserialize(stream)?;
                 ClusterMessagePayload::FailNegotiation(rep_offset)
             }
+            InternalProtocolType::PubSubSummary => {
+                let resumen = ResumenPubSub::deserialize(stream)?;
+                ClusterMessagePayload::PubSubSummary(resumen)
+            }
             InternalProtocolType::Meet => ClusterMessagePayload::Meet,
 
             InternalProtocolType::MeetMaster => ClusterMessagePayload::MeetMaster,
@@ -201,6 +212,7 @@ mod tests {
     use crate::internal_protocol::internal_protocol_type::InternalProtocolType;
     use crate::internal_protocol::node_flags::{ClusterState, NodeFlags};
     use crate::node_id::NodeId;
+    use redis_client::protocol::token::{Token, tokens};
     use std::io::Cursor;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
     use std::thread::spawn;
@@ -230,7 +242,7 @@ mod tests {
     }
 
     fn setup_redis_cmd(cmd: Vec<String>) -> RedisCMD {
-        RedisCMD::new(cmd)
+        RedisCMD::new(tokens(&cmd))
     }
 
     fn exchange_and_assert(stream: &mut TcpStream, expected_msg: &ClusterMessage) {
@@ -514,4 +526,22 @@ mod tests {
             format!("{:?}", deserialized.unwrap())
         );
     }
+
+    #[test]
+    fn test13_pubsub_summary_serialization_deserialization() {
+        let header = setup_header(NodeId::new(), InternalProtocolType::PubSubSummary);
+        let canales = [Token::from("noticias"), Token::from("deportes")];
+        let patrones = [Token::from("doc:*")];
+        let resumen = ResumenPubSub::new(canales.iter(), patrones.iter());
+
+        let message = ClusterMessage::new(header, ClusterMessagePayload::PubSubSummary(resumen));
+
+        let serialized = message.serialize();
+        let mut cursor = Cursor::new(serialized);
+        let deserialized = ClusterMessage::deserialize(&mut cursor);
+        assert_eq!(
+            format!("{message:?}"),
+            format!("{:?}", deserialized.unwrap())
+        );
+    }
 }
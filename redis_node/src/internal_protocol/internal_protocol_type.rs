@@ -44,6 +44,10 @@ pub enum InternalProtocolType {
 
     /// Tipo de error o desconocido. Usado como fallback ante un byte inválido.
     Error, // 12
+
+    /// Resumen de los canales y patrones con suscriptores de un nodo, usado
+    /// para reenviar cada PUBLISH solo a los nodos que pueden tener suscriptores.
+    PubSubSummary, // 13
 }
 
 impl InternalProtocolType {
@@ -68,6 +72,7 @@ impl InternalProtocolType {
             9 => InternalProtocolType::Meet,
             10 => InternalProtocolType::MeetMaster,
             11 => InternalProtocolType::MeetNewMaster,
+            13 => InternalProtocolType::PubSubSummary,
             _ => InternalProtocolType::Error,
         }
     }
@@ -110,6 +115,7 @@ mod tests {
             InternalProtocolType::Meet,
             InternalProtocolType::MeetMaster,
             InternalProtocolType::Error,
+            InternalProtocolType::PubSubSummary,
         ];
 
         for protocol in all.iter() {
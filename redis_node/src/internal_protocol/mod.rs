@@ -8,4 +8,5 @@ pub mod moved_shard_pubsub;
 pub mod node_flags;
 pub mod protocol_trait;
 pub mod redis_cmd;
+pub mod resumen_pubsub;
 pub mod utils;
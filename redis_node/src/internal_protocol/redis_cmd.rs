@@ -1,5 +1,6 @@
 use super::utils::read_exact;
 use crate::internal_protocol::protocol_trait::{DeserializeRIP, SerializeRIP};
+use redis_client::protocol::token::Token;
 use std::io::Read;
 
 /// Representa un comanbdo de Redus utilizado en la comunicación
@@ -8,11 +9,11 @@ use std::io::Read;
 ///
 ///
 /// # Campos
-/// - `command`: Vector de tokens (strings) que representan el comando
+/// - `command`: Vector de tokens que representan el comando
 /// - `len_command`: cantidad de tokens del comando
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct RedisCMD {
-    command: Vec<String>,
+    command: Vec<Token>,
     len_command: u32,
 }
 
@@ -20,11 +21,11 @@ impl RedisCMD {
     /// Crea un nuevo redis cmd a enviar a otro nodo.
     ///
     /// # Argumentos
-    /// - `command`: comando a enviar, tomado como vec de strings
+    /// - `command`: comando a enviar, tomado como vec de tokens
     ///
     /// # Retorna
     /// Una nueva instancia de `RedisCMD`.
-    pub fn new(command: Vec<String>) -> Self {
+    pub fn new(command: Vec<Token>) -> Self {
         let len_command = command.len() as u32;
         Self {
             command,
@@ -32,7 +33,7 @@ impl RedisCMD {
         }
     }
 
-    pub fn get_command(&self) -> Vec<String> {
+    pub fn get_command(&self) -> Vec<Token> {
         self.command.clone()
     }
 }
@@ -51,11 +52,10 @@ impl SerializeRIP for RedisCMD {
         bytes.extend(&self.len_command.to_be_bytes());
         let command = &self.command;
 
-        for string in command {
-            let bytes_string = string.as_bytes();
-            let len = bytes_string.len() as u32;
+        for token in command {
+            let len = token.len() as u32;
             bytes.extend(&len.to_be_bytes());
-            bytes.extend(bytes_string);
+            bytes.extend(token.bytes());
         }
         bytes
     }
@@ -78,29 +78,29 @@ impl DeserializeRIP for RedisCMD {
         let len_command = u32::from_be_bytes(read_exact::<4, _>(stream)?);
         let mut command = Vec::with_capacity(len_command as usize);
         for _ in 0..len_command {
-            command.push(deserialize_string(stream)?);
+            command.push(deserialize_token(stream)?);
         }
 
         Ok(Self::new(command))
     }
 }
 
-fn deserialize_string<T: Read>(stream: &mut T) -> std::io::Result<String> {
+fn deserialize_token<T: Read>(stream: &mut T) -> std::io::Result<Token> {
     let len_string = u32::from_be_bytes(read_exact::<4, _>(stream)?);
     let mut buf = vec![0u8; len_string as usize];
     stream.read_exact(&mut buf)?;
-    String::from_utf8(buf)
-        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "ID no válido UTF-8"))
+    Ok(Token::new(buf))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use redis_client::protocol::token::tokens;
     use std::io::Cursor;
 
     #[test]
     fn test_01_redis_cmd_struct() {
-        let command = vec!["set".to_string(), "hola".to_string(), "mundo".to_string()];
+        let command = tokens(&["set", "hola", "mundo"]);
         let entry = RedisCMD::new(command.clone());
 
         assert_eq!(entry.command, command);
@@ -109,7 +109,7 @@ mod tests {
 
     #[test]
     fn test_02_redis_cmd_serialization_deserialization() {
-        let command = vec!["set".to_string(), "hola".to_string(), "mundo".to_string()];
+        let command = tokens(&["set", "hola", "mundo"]);
         let entry = RedisCMD::new(command);
 
         let bytes = entry.serialize();
@@ -122,10 +122,10 @@ mod tests {
 
     #[test]
     fn test_03_redis_cmd_serialization_deserialization_mult() {
-        let command = vec!["set".to_string(), "hola".to_string(), "mundo".to_string()];
+        let command = tokens(&["set", "hola", "mundo"]);
         let entry = RedisCMD::new(command.clone());
 
-        let command2 = vec!["get".to_string(), "hola".to_string()];
+        let command2 = tokens(&["get", "hola"]);
         let entry2 = RedisCMD::new(command2.clone());
 
         let mut bytes = entry.serialize();
@@ -141,4 +141,16 @@ mod tests {
         assert_eq!(entry2.command, deserialized2.command);
         assert_eq!(entry2.len_command, deserialized2.len_command);
     }
+
+    #[test]
+    fn test_04_redis_cmd_con_valor_binario() {
+        let valor = Token::new(vec![0xff, 0x00, b'a', 0xc3]);
+        let entry = RedisCMD::new(vec![Token::from("set"), Token::from("clave"), valor]);
+
+        let bytes = entry.serialize();
+        assert!(bytes.ends_with(&[0, 0, 0, 4, 0xff, 0x00, b'a', 0xc3]));
+        let deserialized = RedisCMD::deserialize(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(entry.command, deserialized.command);
+    }
 }
@@ -0,0 +1,275 @@
+use super::utils::read_exact;
+use crate::internal_protocol::protocol_trait::{DeserializeRIP, SerializeRIP};
+use redis_client::protocol::token::Token;
+use std::io::{Error, ErrorKind, Read};
+
+/// Bits del filtro por cada canal agregado, para una tasa de falsos
+/// positivos cercana al 1%
+const BITS_POR_ELEMENTO: usize = 10;
+/// Cantidad de funciones de hash del filtro
+const FUNCIONES_HASH: u8 = 7;
+/// Cantidad máxima de patrones que se envían en un resumen. Con más
+/// patrones, el nodo recibe todas las publicaciones del clúster
+pub const MAX_PATRONES: usize = 64;
+/// Cantidad máxima de palabras de 64 bits de un filtro (unos 400 mil canales
+/// a la tasa de falsos positivos elegida). Con más canales el filtro no
+/// crece, y aumenta su tasa de falsos positivos
+const MAX_PALABRAS: u32 = 1 << 16;
+
+/// Filtro de Bloom sobre nombres de canales. No tiene falsos negativos: si
+/// un canal fue agregado, `contiene` siempre devuelve verdadero.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FiltroBloom {
+    palabras: Vec<u64>,
+    funciones: u8,
+}
+
+impl FiltroBloom {
+    /// Crea un filtro dimensionado para la cantidad de canales recibida. Un
+    /// filtro para 0 canales no ocupa espacio y no contiene ningún canal.
+    pub fn con_capacidad(canales: usize) -> Self {
+        let palabras = (canales * BITS_POR_ELEMENTO).div_ceil(64);
+        FiltroBloom {
+            palabras: vec![0; palabras.min(MAX_PALABRAS as usize)],
+            funciones: FUNCIONES_HASH,
+        }
+    }
+
+    pub fn agregar(&mut self, canal: &[u8]) {
+        let bits = self.palabras.len() * 64;
+        if bits == 0 {
+            return;
+        }
+        for posicion in posiciones(canal, self.funciones, bits) {
+            self.palabras[posicion / 64] |= 1 << (posicion % 64);
+        }
+    }
+
+    /// Indica si el canal puede haber sido agregado al filtro
+    pub fn contiene(&self, canal: &[u8]) -> bool {
+        let bits = self.palabras.len() * 64;
+        bits > 0
+            && posiciones(canal, self.funciones, bits)
+                .all(|posicion| self.palabras[posicion / 64] & (1 << (posicion % 64)) != 0)
+    }
+}
+
+/// Posiciones de un canal en un filtro de `bits` bits, calculadas por doble
+/// hashing a partir de un hash FNV-1a, que es igual en todos los nodos
+fn posiciones(canal: &[u8], funciones: u8, bits: usize) -> impl Iterator<Item = usize> {
+    let h1 = fnv1a(canal);
+    let h2 = mezclar(h1) | 1;
+    (0..funciones as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits as u64) as usize)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Finalizador de splitmix64, para obtener un segundo hash independiente
+fn mezclar(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Resumen de las suscripciones pub/sub de un nodo que se comparte con el
+/// resto del clúster, para que cada PUBLISH se reenvíe solo a los nodos que
+/// pueden tener suscriptores del canal.
+///
+/// # Campos
+/// - `canales`: filtro de Bloom con los canales con suscriptores.
+/// - `patrones`: patrones con suscriptores, o None si son más de
+///   `MAX_PATRONES` y el nodo debe recibir todas las publicaciones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumenPubSub {
+    canales: FiltroBloom,
+    patrones: Option<Vec<Token>>,
+}
+
+impl Default for ResumenPubSub {
+    fn default() -> Self {
+        ResumenPubSub {
+            canales: FiltroBloom::default(),
+            patrones: Some(Vec::new()),
+        }
+    }
+}
+
+impl ResumenPubSub {
+    /// Crea el resumen de los canales y patrones con suscriptores de un nodo
+    pub fn new<'a>(
+        canales: impl ExactSizeIterator<Item = &'a Token>,
+        patrones: impl ExactSizeIterator<Item = &'a Token>,
+    ) -> Self {
+        let mut filtro = FiltroBloom::con_capacidad(canales.len());
+        for canal in canales {
+            filtro.agregar(canal);
+        }
+        let patrones = if patrones.len() > MAX_PATRONES {
+            None
+        } else {
+            let mut patrones: Vec<Token> = patrones.cloned().collect();
+            patrones.sort();
+            Some(patrones)
+        };
+        ResumenPubSub {
+            canales: filtro,
+            patrones,
+        }
+    }
+
+    /// Indica si el nodo puede tener suscriptores de un canal, directamente
+    /// o a través de un patrón
+    pub fn puede_tener_suscriptores(&self, canal: &[u8]) -> bool {
+        if self.canales.contiene(canal) {
+            return true;
+        }
+        let canal = String::from_utf8_lossy(canal);
+        match &self.patrones {
+            Some(patrones) => patrones.iter().any(|patron| {
+                glob::Pattern::new(&patron.texto())
+                    .map(|p| p.matches(&canal))
+                    .unwrap_or(false)
+            }),
+            None => true,
+        }
+    }
+}
+
+impl SerializeRIP for ResumenPubSub {
+    /// Serializa el resumen en formato binario según el protocolo interno.
+    /// La serialización incluye:
+    ///
+    /// 1. Cantidad de funciones de hash del filtro (1 byte).
+    /// 2. Cantidad de palabras del filtro (4 bytes) seguida de las palabras
+    ///    (8 bytes cada una).
+    /// 3. 1 si se envían los patrones o 0 si el nodo recibe todo (1 byte).
+    /// 4. Cantidad de patrones (4 bytes), cada uno precedido por su largo.
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = vec![self.canales.funciones];
+        bytes.extend((self.canales.palabras.len() as u32).to_be_bytes());
+        for palabra in &self.canales.palabras {
+            bytes.extend(palabra.to_be_bytes());
+        }
+        match &self.patrones {
+            Some(patrones) => {
+                bytes.push(1);
+                bytes.extend((patrones.len() as u32).to_be_bytes());
+                for patron in patrones {
+                    bytes.extend((patron.len() as u32).to_be_bytes());
+                    bytes.extend(patron.bytes());
+                }
+            }
+            None => bytes.push(0),
+        }
+        bytes
+    }
+}
+
+impl DeserializeRIP for ResumenPubSub {
+    /// Deserializa el resumen desde un stream de bytes, leyendo los campos
+    /// en el mismo orden en que fueron serializados.
+    fn deserialize<T: Read>(stream: &mut T) -> std::io::Result<Self> {
+        let funciones = read_exact::<1, _>(stream)?[0];
+        let cantidad_palabras = u32::from_be_bytes(read_exact::<4, _>(stream)?);
+        if cantidad_palabras > MAX_PALABRAS {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Filtro de canales demasiado grande",
+            ));
+        }
+        let mut palabras = Vec::with_capacity(cantidad_palabras as usize);
+        for _ in 0..cantidad_palabras {
+            palabras.push(u64::from_be_bytes(read_exact::<8, _>(stream)?));
+        }
+
+        let patrones = match read_exact::<1, _>(stream)?[0] {
+            0 => None,
+            _ => {
+                let cantidad = u32::from_be_bytes(read_exact::<4, _>(stream)?);
+                if cantidad as usize > MAX_PATRONES {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Demasiados patrones en el resumen",
+                    ));
+                }
+                let mut patrones = Vec::with_capacity(cantidad as usize);
+                for _ in 0..cantidad {
+                    let largo = u32::from_be_bytes(read_exact::<4, _>(stream)?);
+                    let mut buffer = vec![0u8; largo as usize];
+                    stream.read_exact(&mut buffer)?;
+                    patrones.push(Token::new(buffer));
+                }
+                Some(patrones)
+            }
+        };
+
+        Ok(ResumenPubSub {
+            canales: FiltroBloom {
+                palabras,
+                funciones,
+            },
+            patrones,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn nombres(prefijo: &str, cantidad: usize) -> Vec<Token> {
+        (0..cantidad)
+            .map(|i| Token::from(format!("{prefijo}:{i}")))
+            .collect()
+    }
+
+    #[test]
+    fn test01_filtro_sin_falsos_negativos() {
+        let canales = nombres("documento", 1000);
+        let resumen = ResumenPubSub::new(canales.iter(), [].iter());
+        assert!(canales.iter().all(|c| resumen.puede_tener_suscriptores(c)));
+
+        let falsos_positivos = nombres("otro", 10_000)
+            .iter()
+            .filter(|c| resumen.puede_tener_suscriptores(c))
+            .count();
+        assert!(
+            falsos_positivos < 300,
+            "{falsos_positivos} falsos positivos"
+        );
+
+        let vacio = ResumenPubSub::default();
+        assert!(!vacio.puede_tener_suscriptores(b"documento:1"));
+    }
+
+    #[test]
+    fn test02_patrones() {
+        let patrones = [Token::from("documento:*")];
+        let resumen = ResumenPubSub::new([].iter(), patrones.iter());
+        assert!(resumen.puede_tener_suscriptores(b"documento:7"));
+        assert!(!resumen.puede_tener_suscriptores(b"planilla:7"));
+
+        let muchos = nombres("p", MAX_PATRONES + 1);
+        let resumen = ResumenPubSub::new([].iter(), muchos.iter());
+        assert!(resumen.puede_tener_suscriptores(b"planilla:7"));
+    }
+
+    #[test]
+    fn test03_serializacion() {
+        let canales = nombres("canal", 20);
+        let patrones = [Token::from("doc:*"), Token::from("h?llo")];
+        for resumen in [
+            ResumenPubSub::new(canales.iter(), patrones.iter()),
+            ResumenPubSub::new(canales.iter(), nombres("p", MAX_PATRONES + 1).iter()),
+            ResumenPubSub::default(),
+        ] {
+            let mut cursor = Cursor::new(resumen.serialize());
+            assert_eq!(ResumenPubSub::deserialize(&mut cursor).unwrap(), resumen);
+        }
+    }
+}
@@ -0,0 +1,388 @@
+//! Este módulo contiene el registro de comandos lentos (SLOWLOG) y el
+//! monitor de picos de latencia por tipo de evento (LATENCY)
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use redis_client::protocol::token::Token;
+
+/// Umbral por defecto (en microsegundos) a partir del cual un comando se
+/// registra en el slowlog.
+pub const SLOWLOG_UMBRAL_DEFAULT: i64 = 10_000;
+/// Cantidad máxima por defecto de entradas del slowlog.
+pub const SLOWLOG_MAX_LEN_DEFAULT: usize = 128;
+/// Umbral por defecto (en milisegundos) a partir del cual un evento se
+/// considera un pico de latencia.
+pub const LATENCIA_UMBRAL_DEFAULT: u64 = 100;
+/// Cantidad de muestras que se conservan por evento.
+const MUESTRAS_POR_EVENTO: usize = 160;
+
+/// Cantidad máxima de argumentos de un comando que se guardan en el slowlog.
+const SLOWLOG_MAX_ARGUMENTOS: usize = 32;
+/// Largo máximo de cada argumento guardado en el slowlog.
+const SLOWLOG_MAX_LARGO_ARGUMENTO: usize = 128;
+
+/// Evento de latencia: ejecución de un comando de cliente.
+pub const EVENTO_COMANDO: &str = "command";
+/// Evento de latencia: escritura de una operación en el AOF.
+pub const EVENTO_AOF: &str = "aof-write";
+/// Evento de latencia: guardado del snapshot del storage.
+pub const EVENTO_SNAPSHOT: &str = "snapshot";
+/// Evento de latencia: procesamiento de un mensaje del clúster.
+pub const EVENTO_CLUSTER: &str = "cluster-message";
+
+/// Comando registrado en el slowlog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntradaSlowlog {
+    pub id: u64,
+    /// Instante de ejecución, en segundos desde UNIX epoch.
+    pub timestamp: u64,
+    pub duracion_us: u64,
+    pub argumentos: Vec<String>,
+    pub addr: String,
+    pub nombre: String,
+}
+
+/// Buffer circular acotado de los comandos que superaron el umbral de
+/// duración configurado.
+#[derive(Debug)]
+pub struct SlowLog {
+    entradas: Mutex<VecDeque<EntradaSlowlog>>,
+    siguiente_id: AtomicU64,
+    /// Umbral en microsegundos: negativo deshabilita el registro y 0
+    /// registra todos los comandos.
+    umbral_us: AtomicI64,
+    max_len: AtomicUsize,
+}
+
+impl Default for SlowLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlowLog {
+    pub fn new() -> Self {
+        SlowLog {
+            entradas: Mutex::new(VecDeque::new()),
+            siguiente_id: AtomicU64::new(0),
+            umbral_us: AtomicI64::new(SLOWLOG_UMBRAL_DEFAULT),
+            max_len: AtomicUsize::new(SLOWLOG_MAX_LEN_DEFAULT),
+        }
+    }
+
+    pub fn umbral_us(&self) -> i64 {
+        self.umbral_us.load(Ordering::Relaxed)
+    }
+
+    pub fn set_umbral_us(&self, umbral: i64) {
+        self.umbral_us.store(umbral, Ordering::Relaxed);
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len.load(Ordering::Relaxed)
+    }
+
+    /// Cambia la cantidad máxima de entradas, descartando las más viejas si
+    /// sobran.
+    pub fn set_max_len(&self, max_len: usize) {
+        self.max_len.store(max_len, Ordering::Relaxed);
+        if let Ok(mut entradas) = self.entradas.lock() {
+            entradas.truncate(max_len);
+        }
+    }
+
+    /// Registra un comando si su duración supera el umbral.
+    ///
+    /// # Parámetros
+    /// - `duracion`: tiempo que demoró la ejecución.
+    /// - `argumentos`: tokens del comando.
+    /// - `addr`: dirección del cliente.
+    /// - `nombre`: nombre asignado al cliente con CLIENT SETNAME.
+    pub fn registrar(&self, duracion: Duration, argumentos: &[Token], addr: &str, nombre: &str) {
+        let umbral = self.umbral_us();
+        let duracion_us = duracion.as_micros() as u64;
+        if umbral < 0 || (duracion_us as i64) < umbral {
+            return;
+        }
+        let entrada = EntradaSlowlog {
+            id: self.siguiente_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: ahora_segundos(),
+            duracion_us,
+            argumentos: recortar_argumentos(argumentos),
+            addr: addr.to_string(),
+            nombre: nombre.to_string(),
+        };
+        if let Ok(mut entradas) = self.entradas.lock() {
+            entradas.push_front(entrada);
+            entradas.truncate(self.max_len());
+        }
+    }
+
+    /// Obtiene las últimas `cantidad` entradas, de la más nueva a la más
+    /// vieja.
+    pub fn obtener(&self, cantidad: usize) -> Vec<EntradaSlowlog> {
+        match self.entradas.lock() {
+            Ok(entradas) => entradas.iter().take(cantidad).cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub fn largo(&self) -> usize {
+        self.entradas.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub fn reiniciar(&self) {
+        if let Ok(mut entradas) = self.entradas.lock() {
+            entradas.clear();
+        }
+    }
+}
+
+/// Recorta los argumentos de un comando para acotar la memoria del slowlog,
+/// indicando cuántos argumentos o bytes se omitieron.
+fn recortar_argumentos(argumentos: &[Token]) -> Vec<String> {
+    let mut recortados: Vec<String> = argumentos
+        .iter()
+        .take(SLOWLOG_MAX_ARGUMENTOS)
+        .map(|argumento| {
+            if argumento.len() <= SLOWLOG_MAX_LARGO_ARGUMENTO {
+                return argumento.texto().into_owned();
+            }
+            let corte = SLOWLOG_MAX_LARGO_ARGUMENTO;
+            format!(
+                "{}... ({} more bytes)",
+                String::from_utf8_lossy(&argumento[..corte]),
+                argumento.len() - corte
+            )
+        })
+        .collect();
+    if argumentos.len() > SLOWLOG_MAX_ARGUMENTOS {
+        let restantes = argumentos.len() - SLOWLOG_MAX_ARGUMENTOS + 1;
+        recortados[SLOWLOG_MAX_ARGUMENTOS - 1] = format!("... ({restantes} more arguments)");
+    }
+    recortados
+}
+
+/// Muestra de latencia de un evento: instante (segundos desde UNIX epoch)
+/// y duración en milisegundos.
+pub type MuestraLatencia = (u64, u64);
+
+/// Historial de picos de un tipo de evento.
+#[derive(Debug, Default)]
+struct HistorialEvento {
+    muestras: VecDeque<MuestraLatencia>,
+    maximo_ms: u64,
+}
+
+/// Resumen del último pico de un evento, tal como lo devuelve
+/// LATENCY LATEST.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UltimoPico {
+    pub evento: String,
+    pub timestamp: u64,
+    pub ultimo_ms: u64,
+    pub maximo_ms: u64,
+}
+
+/// Monitor de picos de latencia, agrupados por tipo de evento.
+#[derive(Debug)]
+pub struct MonitorLatencia {
+    /// Umbral en milisegundos; 0 deshabilita el monitor.
+    umbral_ms: AtomicU64,
+    eventos: Mutex<HashMap<String, HistorialEvento>>,
+}
+
+impl Default for MonitorLatencia {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonitorLatencia {
+    pub fn new() -> Self {
+        MonitorLatencia {
+            umbral_ms: AtomicU64::new(LATENCIA_UMBRAL_DEFAULT),
+            eventos: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn umbral_ms(&self) -> u64 {
+        self.umbral_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_umbral_ms(&self, umbral: u64) {
+        self.umbral_ms.store(umbral, Ordering::Relaxed);
+    }
+
+    /// Registra la duración de un evento si supera el umbral. Si ya hay
+    /// una muestra en el mismo segundo se conserva la mayor.
+    ///
+    /// # Parámetros
+    /// - `evento`: nombre del tipo de evento (ej: `aof-write`).
+    /// - `duracion`: duración del evento.
+    pub fn registrar(&self, evento: &str, duracion: Duration) {
+        let umbral = self.umbral_ms();
+        let duracion_ms = duracion.as_millis() as u64;
+        if umbral == 0 || duracion_ms < umbral {
+            return;
+        }
+        let Ok(mut eventos) = self.eventos.lock() else {
+            return;
+        };
+        let historial = eventos.entry(evento.to_string()).or_default();
+        let ahora = ahora_segundos();
+        match historial.muestras.back_mut() {
+            Some((timestamp, ms)) if *timestamp == ahora => *ms = (*ms).max(duracion_ms),
+            _ => historial.muestras.push_back((ahora, duracion_ms)),
+        }
+        if historial.muestras.len() > MUESTRAS_POR_EVENTO {
+            historial.muestras.pop_front();
+        }
+        historial.maximo_ms = historial.maximo_ms.max(duracion_ms);
+    }
+
+    /// Obtiene el último pico de cada evento registrado, ordenados por
+    /// nombre de evento.
+    pub fn ultimos(&self) -> Vec<UltimoPico> {
+        let Ok(eventos) = self.eventos.lock() else {
+            return Vec::new();
+        };
+        let mut ultimos: Vec<UltimoPico> = eventos
+            .iter()
+            .filter_map(|(evento, historial)| {
+                historial.muestras.back().map(|(timestamp, ms)| UltimoPico {
+                    evento: evento.clone(),
+                    timestamp: *timestamp,
+                    ultimo_ms: *ms,
+                    maximo_ms: historial.maximo_ms,
+                })
+            })
+            .collect();
+        ultimos.sort_by(|a, b| a.evento.cmp(&b.evento));
+        ultimos
+    }
+
+    /// Obtiene las muestras de un evento, de la más vieja a la más nueva.
+    pub fn historial(&self, evento: &str) -> Vec<MuestraLatencia> {
+        match self.eventos.lock() {
+            Ok(eventos) => eventos
+                .get(evento)
+                .map(|h| h.muestras.iter().copied().collect())
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Elimina el historial de los eventos recibidos, o de todos si no se
+    /// especifica ninguno.
+    ///
+    /// # Retorna
+    /// La cantidad de eventos eliminados.
+    pub fn reiniciar(&self, eventos: &[String]) -> usize {
+        let Ok(mut registrados) = self.eventos.lock() else {
+            return 0;
+        };
+        if eventos.is_empty() {
+            let cantidad = registrados.len();
+            registrados.clear();
+            return cantidad;
+        }
+        eventos
+            .iter()
+            .filter(|evento| registrados.remove(evento.as_str()).is_some())
+            .count()
+    }
+}
+
+fn ahora_segundos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_01_slowlog_registra_solo_comandos_lentos() {
+        let slowlog = SlowLog::new();
+        slowlog.set_umbral_us(1000);
+        slowlog.registrar(
+            Duration::from_micros(10),
+            &tokens(&["get", "a"]),
+            "addr",
+            "",
+        );
+        slowlog.registrar(
+            Duration::from_millis(5),
+            &tokens(&["set", "a", "b"]),
+            "addr",
+            "",
+        );
+
+        let entradas = slowlog.obtener(10);
+        assert_eq!(entradas.len(), 1);
+        assert_eq!(entradas[0].argumentos, ["set", "a", "b"]);
+        assert_eq!(entradas[0].duracion_us, 5000);
+
+        slowlog.set_umbral_us(-1);
+        slowlog.registrar(Duration::from_secs(1), &tokens(&["get", "a"]), "addr", "");
+        assert_eq!(slowlog.largo(), 1);
+    }
+
+    #[test]
+    fn test_02_slowlog_acotado_y_mas_nuevo_primero() {
+        let slowlog = SlowLog::new();
+        slowlog.set_umbral_us(0);
+        slowlog.set_max_len(3);
+        for i in 0..5 {
+            slowlog.registrar(Duration::ZERO, &tokens(&["get", &i.to_string()]), "a", "");
+        }
+        let ids: Vec<u64> = slowlog.obtener(10).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![4, 3, 2]);
+        slowlog.reiniciar();
+        assert_eq!(slowlog.largo(), 0);
+    }
+
+    #[test]
+    fn test_03_argumentos_largos_se_recortan() {
+        let largo = Token::from("x".repeat(200));
+        let recortados = recortar_argumentos(&[largo]);
+        assert!(recortados[0].ends_with("... (72 more bytes)"));
+
+        let muchos: Vec<Token> = (0..40).map(|i| Token::from(i.to_string())).collect();
+        let recortados = recortar_argumentos(&muchos);
+        assert_eq!(recortados.len(), SLOWLOG_MAX_ARGUMENTOS);
+        assert_eq!(recortados[31], "... (9 more arguments)");
+    }
+
+    #[test]
+    fn test_04_monitor_latencia_por_evento() {
+        let monitor = MonitorLatencia::new();
+        monitor.set_umbral_ms(10);
+        monitor.registrar(EVENTO_AOF, Duration::from_millis(5));
+        assert!(monitor.ultimos().is_empty());
+
+        monitor.registrar(EVENTO_AOF, Duration::from_millis(20));
+        monitor.registrar(EVENTO_AOF, Duration::from_millis(50));
+        monitor.registrar(EVENTO_SNAPSHOT, Duration::from_millis(15));
+
+        let ultimos = monitor.ultimos();
+        assert_eq!(ultimos.len(), 2);
+        assert_eq!(ultimos[0].evento, EVENTO_AOF);
+        assert_eq!(ultimos[0].maximo_ms, 50);
+        assert!(!monitor.historial(EVENTO_AOF).is_empty());
+
+        assert_eq!(monitor.reiniciar(&[EVENTO_AOF.to_string()]), 1);
+        assert!(monitor.historial(EVENTO_AOF).is_empty());
+        assert_eq!(monitor.reiniciar(&[]), 1);
+    }
+}
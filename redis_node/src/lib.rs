@@ -1,16 +1,25 @@
+pub mod bloqueos;
 mod client_struct;
 mod cluster;
 mod cluster_errors;
 pub mod comandos;
 pub mod config;
 pub mod constantes;
+pub mod estadisticas;
+pub mod funciones;
 pub mod internal_protocol;
+pub mod latencia;
 pub mod log_msj;
+pub mod memoria;
 pub mod node;
 pub mod node_builder;
 pub mod node_id;
 pub mod node_role;
 pub mod node_status;
+pub mod notificaciones;
 pub mod persistence;
+pub mod rutas_pubsub;
+pub mod scripts;
 pub mod storage;
+pub mod tracking;
 mod utils;
@@ -4,6 +4,7 @@ use crate::client_struct::client::Client;
 use crate::node::Node;
 use crate::node_id::NodeId;
 use logger::logger::Logger;
+use redis_client::protocol::token::Token;
 use std::io::{Error as IoError, Error};
 use std::net::SocketAddr;
 use std::sync::{Arc, RwLock, RwLockReadGuard};
@@ -144,7 +145,7 @@ pub fn log_writer_join_error(logger: &Logger, e: Box<dyn std::any::Any + Send +
 pub fn log_cli_send_cmd_info(
     logger: &Logger,
     cli: RwLockReadGuard<'_, Client>,
-    comando_tokens: &[String],
+    comando_tokens: &[Token],
 ) {
     logger.info(
         &format!(
@@ -71,6 +71,7 @@ fn main() {
         Ok(node) => node,
         Err(_e) => Node::new_master(&config),
     };
+    node.aplicar_configuracion(&config);
 
     println!("Start listening on {:?}", config.get_node_address());
     println!("-----------------------------------");
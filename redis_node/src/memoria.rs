@@ -0,0 +1,428 @@
+//! Este módulo contiene las estructuras y funciones auxiliares para la
+//! contabilidad aproximada de memoria del storage y las políticas de
+//! desalojo (eviction) de claves al superar `maxmemory`.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+use rand::seq::index::sample;
+use redis_client::tipos_datos::stream::CamposStream;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+/// Cantidad de claves que se muestrean al elegir una víctima de desalojo.
+pub const MAXMEMORY_SAMPLES: usize = 5;
+
+/// Cantidad de claves con expiración que se muestrean en cada ronda del
+/// ciclo activo de expiración.
+pub const EXPIRACION_ACTIVA_MUESTRAS: usize = 20;
+
+/// Intervalo entre ciclos activos de expiración.
+pub const INTERVALO_EXPIRACION_ACTIVA: Duration = Duration::from_millis(100);
+
+/// Valor inicial del contador LFU de una clave nueva.
+pub const LFU_INIT_VAL: u8 = 5;
+/// Factor logarítmico del contador LFU: a mayor valor, más accesos hacen
+/// falta para incrementarlo.
+const LFU_LOG_FACTOR: f64 = 10.0;
+/// Minutos de inactividad necesarios para decrementar en uno el contador LFU.
+const LFU_DECAY_TIME_MIN: u64 = 1;
+
+/// Overhead aproximado (en bytes) de cada entrada del diccionario de claves.
+const OVERHEAD_ENTRADA: usize = 48;
+/// Overhead aproximado (en bytes) de cada valor o elemento almacenado.
+const OVERHEAD_VALOR: usize = 16;
+
+/// Política de desalojo a aplicar cuando el storage supera `maxmemory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PoliticaEviction {
+    /// No se desaloja ninguna clave: las escrituras fallan con OOM.
+    #[default]
+    NoEviction,
+    /// Desaloja, entre todas las claves, la accedida hace más tiempo.
+    AllKeysLru,
+    /// Desaloja, entre todas las claves, la accedida con menos frecuencia.
+    AllKeysLfu,
+    /// Desaloja, entre las claves con expiración, la más próxima a expirar.
+    VolatileTtl,
+}
+
+impl PoliticaEviction {
+    /// Obtiene la política a partir de su nombre en la configuración.
+    ///
+    /// # Parámetros
+    /// - `nombre`: nombre de la política (ej: `allkeys-lru`).
+    ///
+    /// # Retorna
+    /// La política correspondiente, o `None` si el nombre no es válido.
+    pub fn from_nombre(nombre: &str) -> Option<Self> {
+        match nombre.to_lowercase().as_str() {
+            "noeviction" => Some(Self::NoEviction),
+            "allkeys-lru" => Some(Self::AllKeysLru),
+            "allkeys-lfu" => Some(Self::AllKeysLfu),
+            "volatile-ttl" => Some(Self::VolatileTtl),
+            _ => None,
+        }
+    }
+
+    /// Nombre de la política tal como se escribe en la configuración.
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            Self::NoEviction => "noeviction",
+            Self::AllKeysLru => "allkeys-lru",
+            Self::AllKeysLfu => "allkeys-lfu",
+            Self::VolatileTtl => "volatile-ttl",
+        }
+    }
+
+    /// Indica si la política lleva registro de la frecuencia de acceso.
+    pub fn es_lfu(&self) -> bool {
+        matches!(self, Self::AllKeysLfu)
+    }
+}
+
+/// Metadatos de una clave necesarios para la contabilidad de memoria y las
+/// políticas de desalojo.
+///
+/// Los campos de acceso son atómicos para poder actualizarse en lecturas que
+/// solo disponen de una referencia inmutable al storage.
+#[derive(Debug)]
+pub struct MetadataClave {
+    memoria: usize,
+    ultimo_acceso: AtomicU64,
+    frecuencia: AtomicU8,
+    expira_en: Option<u64>,
+}
+
+impl MetadataClave {
+    /// Crea los metadatos de una clave recién insertada.
+    ///
+    /// # Parámetros
+    /// - `memoria`: memoria estimada de la clave y su valor.
+    pub fn new(memoria: usize) -> Self {
+        MetadataClave {
+            memoria,
+            ultimo_acceso: AtomicU64::new(ahora_ms()),
+            frecuencia: AtomicU8::new(LFU_INIT_VAL),
+            expira_en: None,
+        }
+    }
+
+    pub fn memoria(&self) -> usize {
+        self.memoria
+    }
+
+    pub fn set_memoria(&mut self, memoria: usize) {
+        self.memoria = memoria;
+    }
+
+    pub fn expira_en(&self) -> Option<u64> {
+        self.expira_en
+    }
+
+    pub fn set_expira_en(&mut self, expira_en: Option<u64>) {
+        self.expira_en = expira_en;
+    }
+
+    /// Indica si la clave expiró en el instante `ahora` (en milisegundos).
+    pub fn expirada(&self, ahora: u64) -> bool {
+        self.expira_en.is_some_and(|expira_en| expira_en <= ahora)
+    }
+
+    /// Registra un acceso a la clave: actualiza el último acceso y el
+    /// contador de frecuencia.
+    pub fn registrar_acceso(&self) {
+        let frecuencia = self.frecuencia();
+        self.frecuencia
+            .store(incrementar_frecuencia(frecuencia), Ordering::Relaxed);
+        self.ultimo_acceso.store(ahora_ms(), Ordering::Relaxed);
+    }
+
+    /// Segundos transcurridos desde el último acceso a la clave.
+    pub fn segundos_inactiva(&self) -> u64 {
+        ahora_ms().saturating_sub(self.ultimo_acceso.load(Ordering::Relaxed)) / 1000
+    }
+
+    pub fn ultimo_acceso(&self) -> u64 {
+        self.ultimo_acceso.load(Ordering::Relaxed)
+    }
+
+    /// Contador de frecuencia LFU, aplicando el decaimiento por inactividad.
+    pub fn frecuencia(&self) -> u8 {
+        let minutos = self.segundos_inactiva() / 60;
+        let decremento = minutos / LFU_DECAY_TIME_MIN;
+        let actual = self.frecuencia.load(Ordering::Relaxed);
+        actual.saturating_sub(decremento.min(u8::MAX as u64) as u8)
+    }
+}
+
+/// Conjunto de claves que permite tomar muestras aleatorias sin recorrer
+/// todas las claves, para que el desalojo y la expiración activa no
+/// dependan de la cantidad de claves del storage.
+///
+/// Las claves se guardan en un vector indexable junto a la posición de
+/// cada una, de modo que agregar, quitar y muestrear cuestan O(1) por
+/// clave.
+#[derive(Debug, Default)]
+pub struct ClavesMuestreables {
+    claves: Vec<Vec<u8>>,
+    posiciones: HashMap<Vec<u8>, usize>,
+}
+
+impl ClavesMuestreables {
+    /// Agrega una clave, si no estaba.
+    pub fn agregar(&mut self, key: &[u8]) {
+        if self.posiciones.contains_key(key) {
+            return;
+        }
+        self.posiciones.insert(key.to_vec(), self.claves.len());
+        self.claves.push(key.to_vec());
+    }
+
+    /// Quita una clave, si estaba, moviendo la última a su posición.
+    pub fn quitar(&mut self, key: &[u8]) {
+        let Some(posicion) = self.posiciones.remove(key) else {
+            return;
+        };
+        self.claves.swap_remove(posicion);
+        if let Some(movida) = self.claves.get(posicion) {
+            self.posiciones.insert(movida.to_vec(), posicion);
+        }
+    }
+
+    /// Toma una muestra aleatoria de hasta `cantidad` claves distintas.
+    pub fn muestra(&self, cantidad: usize) -> Vec<&Vec<u8>> {
+        let cantidad = cantidad.min(self.claves.len());
+        sample(&mut rand::rng(), self.claves.len(), cantidad)
+            .into_iter()
+            .map(|i| &self.claves[i])
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.claves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.claves.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.claves.clear();
+        self.posiciones.clear();
+    }
+}
+
+/// Incrementa de forma logarítmica un contador LFU: cuanto mayor es el
+/// contador, menor es la probabilidad de que un acceso lo incremente.
+fn incrementar_frecuencia(actual: u8) -> u8 {
+    if actual == u8::MAX {
+        return actual;
+    }
+    let base = actual.saturating_sub(LFU_INIT_VAL) as f64;
+    let probabilidad = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+    if rand::rng().random::<f64>() < probabilidad {
+        actual + 1
+    } else {
+        actual
+    }
+}
+
+/// Variación de la memoria de un valor modificado en el lugar, calculada a
+/// partir de los elementos agregados y quitados para no tener que volver a
+/// estimar el valor completo.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VariacionMemoria {
+    agregada: usize,
+    liberada: usize,
+}
+
+impl VariacionMemoria {
+    /// Registra un elemento agregado al valor.
+    pub fn agregar(&mut self, elemento: &DatoRedis) {
+        self.agregada += estimar_memoria_valor(elemento);
+    }
+
+    /// Registra un elemento quitado del valor.
+    pub fn liberar(&mut self, elemento: &DatoRedis) {
+        self.liberada += estimar_memoria_valor(elemento);
+    }
+
+    /// Registra una entrada agregada a un stream.
+    pub fn agregar_entrada(&mut self, campos: &CamposStream) {
+        self.agregada += estimar_memoria_entrada(campos);
+    }
+
+    /// Registra una entrada quitada de un stream.
+    pub fn liberar_entrada(&mut self, campos: &CamposStream) {
+        self.liberada += estimar_memoria_entrada(campos);
+    }
+
+    /// Aplica la variación a la memoria previa de un valor.
+    pub fn aplicar(&self, memoria: usize) -> usize {
+        (memoria + self.agregada).saturating_sub(self.liberada)
+    }
+}
+
+/// Estima la memoria ocupada por una clave y su valor.
+///
+/// # Parámetros
+/// - `key`: clave almacenada.
+/// - `dato`: valor asociado.
+///
+/// # Retorna
+/// La cantidad aproximada de bytes utilizados.
+pub fn estimar_memoria(key: &[u8], dato: &DatoRedis) -> usize {
+    OVERHEAD_ENTRADA + key.len() + estimar_memoria_valor(dato)
+}
+
+/// Estima la memoria ocupada por un valor, recorriendo sus elementos en
+/// el caso de los tipos compuestos.
+fn estimar_memoria_valor(dato: &DatoRedis) -> usize {
+    match dato {
+        DatoRedis::BulkString(bulk) => OVERHEAD_VALOR + bulk.largo().max(0) as usize,
+        DatoRedis::Arrays(arrays) => {
+            OVERHEAD_VALOR + arrays.iter().map(estimar_memoria_valor).sum::<usize>()
+        }
+        DatoRedis::Set(set) => {
+            OVERHEAD_VALOR + set.iter().map(estimar_memoria_valor).sum::<usize>()
+        }
+        DatoRedis::Stream(stream) => {
+            OVERHEAD_VALOR
+                + stream
+                    .iter()
+                    .map(|(_, campos)| estimar_memoria_entrada(campos))
+                    .sum::<usize>()
+                + stream
+                    .grupos()
+                    .iter()
+                    .map(|(nombre, grupo)| {
+                        OVERHEAD_VALOR
+                            + nombre.len()
+                            + grupo.consumidores().keys().map(String::len).sum::<usize>()
+                            + grupo.pendientes().len() * OVERHEAD_VALOR
+                    })
+                    .sum::<usize>()
+        }
+        DatoRedis::HyperLogLog(hll) => OVERHEAD_VALOR + hll.bytes_en_memoria(),
+        DatoRedis::SortedSet(sorted_set) => {
+            OVERHEAD_VALOR
+                + sorted_set
+                    .iter()
+                    .map(|(miembro, _)| OVERHEAD_VALOR + miembro.len())
+                    .sum::<usize>()
+        }
+        _ => OVERHEAD_VALOR,
+    }
+}
+
+/// Estima la memoria ocupada por una entrada de un stream.
+fn estimar_memoria_entrada(campos: &CamposStream) -> usize {
+    OVERHEAD_VALOR
+        + campos
+            .iter()
+            .map(|(campo, valor)| campo.len() + valor.len())
+            .sum::<usize>()
+}
+
+/// Parsea una cantidad de memoria con sufijo opcional (`kb`, `mb`, `gb`).
+///
+/// # Parámetros
+/// - `valor`: cantidad a parsear (ej: `100mb`, `1024`).
+///
+/// # Retorna
+/// La cantidad de bytes, o un mensaje de error si el valor es inválido.
+pub fn parsear_memoria(valor: &str) -> Result<usize, String> {
+    let valor = valor.trim().to_lowercase();
+    let (numero, multiplicador) = if let Some(numero) = valor.strip_suffix("gb") {
+        (numero, 1024 * 1024 * 1024)
+    } else if let Some(numero) = valor.strip_suffix("mb") {
+        (numero, 1024 * 1024)
+    } else if let Some(numero) = valor.strip_suffix("kb") {
+        (numero, 1024)
+    } else {
+        (valor.as_str(), 1)
+    };
+    numero
+        .trim()
+        .parse::<usize>()
+        .map(|n| n * multiplicador)
+        .map_err(|_| format!("Cantidad de memoria inválida: '{valor}'"))
+}
+
+/// Instante actual en milisegundos desde UNIX epoch.
+pub fn ahora_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_01_parsear_memoria_con_sufijos() {
+        assert_eq!(parsear_memoria("1024"), Ok(1024));
+        assert_eq!(parsear_memoria("2kb"), Ok(2048));
+        assert_eq!(parsear_memoria("1MB"), Ok(1024 * 1024));
+        assert_eq!(parsear_memoria("1gb"), Ok(1024 * 1024 * 1024));
+        assert!(parsear_memoria("mucho").is_err());
+    }
+
+    #[test]
+    fn test_02_politicas_por_nombre() {
+        for politica in [
+            PoliticaEviction::NoEviction,
+            PoliticaEviction::AllKeysLru,
+            PoliticaEviction::AllKeysLfu,
+            PoliticaEviction::VolatileTtl,
+        ] {
+            assert_eq!(
+                PoliticaEviction::from_nombre(politica.nombre()),
+                Some(politica)
+            );
+        }
+        assert_eq!(PoliticaEviction::from_nombre("allkeys-random"), None);
+    }
+
+    #[test]
+    fn test_03_memoria_crece_con_el_valor() {
+        let chico = DatoRedis::new_bulk_string("a".to_string()).unwrap();
+        let grande = DatoRedis::new_bulk_string("a".repeat(1000)).unwrap();
+        assert!(estimar_memoria(b"key", &grande) >= estimar_memoria(b"key", &chico) + 999);
+    }
+
+    #[test]
+    fn test_04_frecuencia_crece_con_los_accesos() {
+        let metadata = MetadataClave::new(0);
+        for _ in 0..1000 {
+            metadata.registrar_acceso();
+        }
+        assert!(metadata.frecuencia() > LFU_INIT_VAL);
+    }
+
+    #[test]
+    fn test_05_claves_muestreables_agrega_quita_y_muestrea() {
+        let mut claves = ClavesMuestreables::default();
+        for key in [b"a", b"b", b"c", b"a"] {
+            claves.agregar(key);
+        }
+        assert_eq!(claves.len(), 3);
+
+        claves.quitar(b"a");
+        claves.quitar(b"z");
+        assert_eq!(claves.len(), 2);
+        let mut muestra = claves.muestra(10);
+        muestra.sort();
+        assert_eq!(muestra, [b"b", b"c"]);
+        assert_eq!(claves.muestra(1).len(), 1);
+
+        claves.quitar(b"c");
+        assert_eq!(claves.muestra(5), [b"b"]);
+        claves.clear();
+        assert!(claves.is_empty());
+        assert!(claves.muestra(5).is_empty());
+    }
+}
@@ -1,22 +1,30 @@
 //! Este modulo contiene la implementacion principal del nodo de redis
 
+use crate::bloqueos::ColaBloqueos;
 use crate::client_struct::client::Client;
+use crate::client_struct::registro_clientes::RegistroClientes;
 use crate::cluster::neighboring_node::NeighboringNodeInfo;
 use crate::cluster::node_message::TipoMensajeNode;
 use crate::cluster_errors::ClusterError;
 use crate::comandos::pub_sub_struct::*;
 use crate::comandos::utils::send_msj;
 use crate::config::config_parser::Config;
+use crate::estadisticas::EstadisticasNodo;
 use crate::internal_protocol::node_flags::ClusterState;
+use crate::latencia::{MonitorLatencia, SlowLog};
 use crate::log_msj::log_mensajes::{
     log_bind_error, log_client_count, log_connection_accepted, log_error_accepting_connection,
     log_max_clients_reached, log_nodo_start, log_peer_addr_error,
 };
+use crate::memoria::INTERVALO_EXPIRACION_ACTIVA;
 use crate::node_id::NodeId;
 use crate::node_role::NodeRole;
 pub(crate) use crate::node_status::NodeStatus;
+use crate::notificaciones::NotificacionesKeyspace;
 use crate::persistence::persistencia::*;
+use crate::scripts::CacheScripts;
 use crate::storage::Storage;
+use crate::tracking::RegistroTracking;
 use crate::utils::utils_functions::abrir_persistence_file;
 use crate::utils::utils_functions::{limpiar_cliente_desconectado, sumar_puerto};
 use logger::logger::Logger;
@@ -28,7 +36,7 @@ use std::io::BufWriter;
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::ops::Range;
 use std::sync::atomic::Ordering::SeqCst;
-use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock, mpsc};
 use std::thread::{sleep, spawn};
@@ -47,9 +55,9 @@ pub struct Node {
     pub(crate) slot_range: Range<u16>,          // Sacarle el option
     pub(crate) storage: Arc<RwLock<Storage>>,
     pub(crate) pub_sub: PubSubBroker,
-    pub(crate) max_client_capacity: usize,
+    pub(crate) max_client_capacity: Arc<AtomicUsize>,
     pub(crate) act_client_active: Arc<AtomicUsize>,
-    pub(crate) save_interval: u64,
+    pub(crate) save_interval: Arc<AtomicU64>,
     pub(crate) logger: Logger,
     /// Agregados (no se usan por ahora)
     pub(crate) config_epoch: Arc<AtomicU64>, // Cambiarlo a AtomicUsize
@@ -59,7 +67,17 @@ pub struct Node {
     pub(crate) replicas: Arc<RwLock<Option<Vec<NodeId>>>>, // Quines son mis replicas
     pub(crate) knows_nodes: Arc<RwLock<HashMap<NodeId, NeighboringNodeInfo>>>,
     pub(crate) cluster_state: Arc<RwLock<ClusterState>>,
-    pub(crate) node_timeout: u64,
+    pub(crate) node_timeout: Arc<AtomicU64>,
+    pub(crate) estadisticas: Arc<EstadisticasNodo>,
+    pub(crate) clientes: Arc<RegistroClientes>,
+    pub(crate) appendonly: Arc<AtomicBool>,
+    pub(crate) config_path: Arc<RwLock<Option<String>>>,
+    pub(crate) slowlog: Arc<SlowLog>,
+    pub(crate) latencia: Arc<MonitorLatencia>,
+    pub(crate) notificaciones: Arc<NotificacionesKeyspace>,
+    pub(crate) bloqueos: Arc<ColaBloqueos>,
+    pub(crate) scripts: Arc<CacheScripts>,
+    pub(crate) tracking: Arc<RegistroTracking>,
 }
 
 /// Esta estructura representa al nodo de Redis.
@@ -114,9 +132,9 @@ impl Node {
             slot_range: config.get_node_slot_range(),
             storage: Arc::new(RwLock::new(Storage::new(config.get_node_slot_range()))),
             pub_sub: PubSubBroker::new(logger.clone(), config.get_node_slot_range()),
-            max_client_capacity: config.get_node_max_clients(),
+            max_client_capacity: Arc::new(AtomicUsize::new(config.get_node_max_clients())),
             act_client_active: Arc::new(AtomicUsize::new(0)),
-            save_interval: config.get_node_save_interval(),
+            save_interval: Arc::new(AtomicU64::new(config.get_node_save_interval())),
             logger,
 
             // Por ahora los seteo en 0
@@ -127,7 +145,17 @@ impl Node {
             replicas: Arc::new(RwLock::new(None)),
             knows_nodes: Arc::new(RwLock::new(HashMap::new())),
             cluster_state: Arc::new(RwLock::new(ClusterState::Ok)),
-            node_timeout: config.get_node_time_out(),
+            node_timeout: Arc::new(AtomicU64::new(config.get_node_time_out())),
+            estadisticas: Arc::new(EstadisticasNodo::new()),
+            clientes: Arc::new(RegistroClientes::new()),
+            appendonly: Arc::new(AtomicBool::new(false)),
+            config_path: Arc::new(RwLock::new(None)),
+            slowlog: Arc::new(SlowLog::new()),
+            latencia: Arc::new(MonitorLatencia::new()),
+            notificaciones: Arc::new(NotificacionesKeyspace::new()),
+            bloqueos: Arc::new(ColaBloqueos::new()),
+            scripts: Arc::new(CacheScripts::new()),
+            tracking: Arc::new(RegistroTracking::new()),
         };
         let _ = node.guardar_metadata(&config.get_node_metadata(), &config.get_node_log_file());
         node
@@ -147,6 +175,54 @@ impl Node {
         )
     }
 
+    /// Aplica al nodo los parámetros de la configuración que no se
+    /// persisten en la metadata: límite de memoria, política de desalojo,
+    /// nivel de log, límites de los buffers de salida y ruta del archivo
+    /// (usada por CONFIG REWRITE)
+    ///
+    /// # Parámetros
+    /// * `config`: configuración del nodo
+    pub fn aplicar_configuracion(&self, config: &Config) {
+        if let Ok(mut storage) = self.storage.write() {
+            storage.configurar_memoria(config.get_maxmemory(), config.get_maxmemory_policy());
+        }
+        self.logger.set_nivel(config.get_loglevel());
+        self.slowlog
+            .set_umbral_us(config.get_slowlog_log_slower_than());
+        self.slowlog.set_max_len(config.get_slowlog_max_len());
+        self.latencia
+            .set_umbral_ms(config.get_latency_monitor_threshold());
+        let _ = self.configurar_notificaciones(&config.get_notify_keyspace_events());
+        self.pub_sub
+            .buffers()
+            .set_limites(config.get_client_output_buffer_limit());
+        if let Ok(mut path) = self.config_path.write() {
+            *path = Some(config.get_path());
+        }
+    }
+
+    /// Configura los flags de las notificaciones de keyspace, habilitando
+    /// en el storage el registro de eventos solo si hay alguna activa
+    ///
+    /// # Parámetros
+    /// * `flags`: flags de `notify_keyspace_events`
+    ///
+    /// # Retorna
+    /// - Error con el caracter invalido, de haberlo
+    pub(crate) fn configurar_notificaciones(&self, flags: &str) -> Result<(), String> {
+        self.notificaciones.configurar(flags)?;
+        if let Ok(mut storage) = self.storage.write() {
+            storage.set_registrar_eventos(self.notificaciones.habilitadas());
+        }
+        Ok(())
+    }
+
+    /// Tiempo en milisegundos tras el cual un nodo sin responder se
+    /// considera caído
+    pub(crate) fn get_node_timeout(&self) -> u64 {
+        self.node_timeout.load(SeqCst)
+    }
+
     ///////////////////////////////////////////////////////////////////////////////
 
     /// Inicia el nodo Redis dentro del clúster.
@@ -197,11 +273,10 @@ impl Node {
             }
         };
 
-        let aof_arc = if supports_aof {
-            Some(Arc::new(RwLock::new(file)))
-        } else {
-            None
-        };
+        // El archivo queda abierto aunque AOF esté deshabilitado para
+        // poder habilitarlo en ejecución con CONFIG SET appendonly
+        self.appendonly.store(supports_aof, SeqCst);
+        let aof_arc = Some(Arc::new(RwLock::new(file)));
 
         let arc_node = self.clone();
         let tx_connect_cmd = arc_node
@@ -209,6 +284,7 @@ impl Node {
             .map_err(|_| ClusterError::new_start_node_error("INIT"))?;
 
         self.handle_saving_thread(&path_bin, aof_arc.clone());
+        self.handle_expiration_thread();
 
         log_nodo_start(&self.logger, &self);
         self.iniciar_recepcion_clientes(cli_listener, users, aof_arc, tx_connect_cmd);
@@ -342,6 +418,9 @@ impl Node {
         let role_result = self.role.write();
         if let Ok(mut role) = role_result {
             *role = NodeRole::Replica;
+            drop(role);
+            // Los clientes bloqueados deben ser redirigidos al nuevo master
+            self.bloqueos.notificar();
             true
         } else {
             self.logger
@@ -371,10 +450,10 @@ impl Node {
         let guarda_result = outgoing_streams.read();
         if let Ok(guarda) = guarda_result {
             for (id, node_stream) in guarda.iter() {
-                if let Ok(addr) = node_stream.peer_addr() {
-                    if addr == target_addr {
-                        return Some(id.clone());
-                    }
+                if let Ok(addr) = node_stream.peer_addr()
+                    && addr == target_addr
+                {
+                    return Some(id.clone());
                 }
             }
         }
@@ -406,7 +485,9 @@ impl Node {
         let storage_clone = Arc::clone(&self.storage);
         let logger_clone = self.logger.clone();
         let path_bin_string = path_bin.to_string();
-        let save_interval = self.save_interval;
+        let save_interval = Arc::clone(&self.save_interval);
+        let estadisticas = Arc::clone(&self.estadisticas);
+        let latencia = Arc::clone(&self.latencia);
 
         spawn(move || {
             save_storage(
@@ -415,10 +496,29 @@ impl Node {
                 &storage_clone,
                 &save_interval,
                 &logger_clone,
+                &estadisticas,
+                &latencia,
             );
         });
     }
 
+    /// Lanza un hilo que ejecuta periódicamente el ciclo activo de
+    /// expiración, para eliminar las claves expiradas que ningún comando
+    /// vuelve a acceder.
+    ///
+    /// # Notas
+    /// - El intervalo entre ciclos es `INTERVALO_EXPIRACION_ACTIVA`.
+    /// - El hilo es lanzado en background y no se espera su finalización (no se hace `join`).
+    fn handle_expiration_thread(self: &Arc<Self>) {
+        let node = Arc::clone(self);
+        spawn(move || {
+            loop {
+                sleep(INTERVALO_EXPIRACION_ACTIVA);
+                node.expirar_claves_activamente();
+            }
+        });
+    }
+
     /// Intenta conectarse a un stream TCP
     ///
     /// # Retorna
@@ -466,13 +566,13 @@ impl Node {
                 return;
             }
         };
-        if self.max_client_capacity <= self.act_client_active.load(SeqCst) {
+        if self.max_client_capacity.load(SeqCst) <= self.act_client_active.load(SeqCst) {
             let res = DatoRedis::new_simple_error(
                 "ERR".to_string(),
                 "max number of clients reached".to_string(),
             );
             if let Err(result) =
-                resp_server_command_write(&res.convertir_a_protocolo_resp(), &mut stream)
+                resp_server_command_write(&res.convertir_a_protocolo_resp_bytes(), &mut stream)
             {
                 let msg = result.convertir_resp_a_string();
                 log_max_clients_reached(&ip_client, &self.logger, &msg);
@@ -527,6 +627,8 @@ impl Node {
         )));
 
         log_connection_accepted(self.id.get_id(), &ip_client, &self.logger);
+        self.estadisticas.registrar_conexion();
+        self.clientes.registrar(client.clone());
         let node_arc = self.clone();
         spawn(move || {
             node_arc.handle_connection(client, aof_file, tx_connect, users);
@@ -577,13 +679,13 @@ impl Node {
                 "error writing slot range to persistence file".to_string(),
             )
         })?;
-        guardar_save_interval(&mut writer, self.save_interval).map_err(|_| {
+        guardar_save_interval(&mut writer, self.save_interval.load(SeqCst)).map_err(|_| {
             DatoRedis::new_simple_error(
                 "ERR".to_string(),
                 "error writing save interval to persistence file".to_string(),
             )
         })?;
-        guardar_max_clients(&mut writer, self.max_client_capacity).map_err(|_| {
+        guardar_max_clients(&mut writer, self.max_client_capacity.load(SeqCst)).map_err(|_| {
             DatoRedis::new_simple_error(
                 "ERR".to_string(),
                 "error writing max clients to persistence file".to_string(),
@@ -595,7 +697,7 @@ impl Node {
                 "error writing logger path to persistence file".to_string(),
             )
         })?;
-        guardar_node_timeout(&mut writer, self.node_timeout).map_err(|_| {
+        guardar_node_timeout(&mut writer, self.get_node_timeout()).map_err(|_| {
             DatoRedis::new_simple_error(
                 "ERR".to_string(),
                 "error writing node timeout to persistence file".to_string(),
@@ -650,6 +752,10 @@ impl Node {
         log_client_count(&self.logger, self.act_client_active.load(SeqCst));
         self.procesar_comandos(Arc::clone(&client), aof_file, tx_connect, users);
 
+        if let Ok(cli) = client.read() {
+            self.clientes.eliminar(cli.get_numero());
+            self.desactivar_tracking(cli.get_numero());
+        }
         limpiar_cliente_desconectado(client, &self.pub_sub, &self.act_client_active, &self.logger);
     }
 
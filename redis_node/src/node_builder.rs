@@ -1,20 +1,27 @@
 //! Este módulo contiene la estructura que permite crear el nodo a partir de
 //! su metadata
+use crate::bloqueos::ColaBloqueos;
+use crate::client_struct::registro_clientes::RegistroClientes;
 use crate::cluster::neighboring_node::NeighboringNodeInfo;
 use crate::comandos::pub_sub_struct::PubSubBroker;
+use crate::estadisticas::EstadisticasNodo;
 use crate::internal_protocol::node_flags::ClusterState;
+use crate::latencia::{MonitorLatencia, SlowLog};
 use crate::node::Node;
 use crate::node_id::NodeId;
 use crate::node_role::NodeRole;
 use crate::node_status::NodeStatus;
+use crate::notificaciones::NotificacionesKeyspace;
+use crate::scripts::CacheScripts;
 use crate::storage::Storage;
+use crate::tracking::RegistroTracking;
 use logger::logger::Logger;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
 use std::ops::Range;
-use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
 use std::sync::{Arc, RwLock};
 
 pub struct NodeBuilder {
@@ -214,11 +221,13 @@ impl NodeBuilder {
                 .storage
                 .unwrap_or_else(|| Arc::new(RwLock::new(Storage::in_memory(0..16384)))),
             pub_sub: self.pub_sub.unwrap_or_else(PubSubBroker::noop),
-            max_client_capacity: self.max_client_capacity.unwrap_or_default(),
+            max_client_capacity: Arc::new(AtomicUsize::new(
+                self.max_client_capacity.unwrap_or_default(),
+            )),
             act_client_active: self
                 .act_client_active
                 .unwrap_or_else(|| Arc::new(AtomicUsize::new(0))),
-            save_interval: self.save_interval.unwrap_or_default(),
+            save_interval: Arc::new(AtomicU64::new(self.save_interval.unwrap_or_default())),
             logger: self.logger.unwrap_or_else(Logger::null),
 
             config_epoch: self
@@ -236,11 +245,46 @@ impl NodeBuilder {
                 .knows_nodes
                 .unwrap_or_else(|| Arc::new(RwLock::new(HashMap::new()))),
             cluster_state: Arc::new(RwLock::new(ClusterState::Ok)),
-            node_timeout: self.node_timeout.unwrap_or(500),
+            node_timeout: Arc::new(AtomicU64::new(self.node_timeout.unwrap_or(500))),
+            estadisticas: Arc::new(EstadisticasNodo::new()),
+            clientes: Arc::new(RegistroClientes::new()),
+            appendonly: Arc::new(AtomicBool::new(false)),
+            config_path: Arc::new(RwLock::new(None)),
+            slowlog: Arc::new(SlowLog::new()),
+            latencia: Arc::new(MonitorLatencia::new()),
+            notificaciones: Arc::new(NotificacionesKeyspace::new()),
+            bloqueos: Arc::new(ColaBloqueos::new()),
+            scripts: Arc::new(CacheScripts::new()),
+            tracking: Arc::new(RegistroTracking::new()),
         })
     }
 }
 
+#[cfg(test)]
+impl NodeBuilder {
+    /// Crea un builder con un id nuevo y direcciones en puertos libres, para
+    /// que los nodos de distintas pruebas no compartan puertos
+    pub fn para_pruebas() -> Self {
+        let cli_addr = direccion_libre();
+        let node_addr = direccion_libre();
+        Self::new()
+            .id(NodeId::new())
+            .cli_addr(cli_addr)
+            .node_addr(node_addr)
+            .cluster_addr(node_addr)
+            .public_addr(cli_addr)
+    }
+}
+
+/// Obtiene una dirección local con un puerto libre, asignado por el
+/// sistema operativo
+#[cfg(test)]
+fn direccion_libre() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -324,7 +368,7 @@ mod tests {
 
         assert_eq!(*node.role.read().unwrap(), NodeRole::Master);
         assert_eq!(*node.status.read().unwrap(), NodeStatus::Ok);
-        assert_eq!(node.node_timeout, 500);
+        assert_eq!(node.get_node_timeout(), 500);
     }
 
     #[test]
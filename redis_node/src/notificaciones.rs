@@ -0,0 +1,466 @@
+//! Este modulo contiene la configuracion de las notificaciones de keyspace,
+//! publicadas por pub/sub en los canales `__keyspace@0__:<clave>` y
+//! `__keyevent@0__:<evento>` cada vez que un comando modifica una clave
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::const_cmd::*;
+
+/// Prefijo de los canales que notifican los eventos de una clave
+pub const PREFIJO_KEYSPACE: &str = "__keyspace@0__:";
+/// Prefijo de los canales que notifican las claves afectadas por un evento
+pub const PREFIJO_KEYEVENT: &str = "__keyevent@0__:";
+
+/// Evento generado al eliminar una clave
+pub const EVENTO_DEL: &str = "del";
+/// Evento generado al alcanzarse la expiracion de una clave
+pub const EVENTO_EXPIRED: &str = "expired";
+/// Evento generado al desalojar una clave por maxmemory
+pub const EVENTO_EVICTED: &str = "evicted";
+
+const FLAG_KEYSPACE: u16 = 1;
+const FLAG_KEYEVENT: u16 = 1 << 1;
+
+/// Clases en las que se agrupan los eventos, habilitables por separado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaseEvento {
+    Generico,
+    String,
+    Lista,
+    Set,
+    Stream,
+    SortedSet,
+    Expirado,
+    Desalojado,
+}
+
+impl ClaseEvento {
+    /// Todas las clases, en el orden en que se muestran sus flags
+    const TODAS: [ClaseEvento; 8] = [
+        ClaseEvento::Generico,
+        ClaseEvento::String,
+        ClaseEvento::Lista,
+        ClaseEvento::Set,
+        ClaseEvento::Stream,
+        ClaseEvento::SortedSet,
+        ClaseEvento::Expirado,
+        ClaseEvento::Desalojado,
+    ];
+
+    fn flag(self) -> u16 {
+        match self {
+            ClaseEvento::Generico => 1 << 2,
+            ClaseEvento::String => 1 << 3,
+            ClaseEvento::Lista => 1 << 4,
+            ClaseEvento::Set => 1 << 5,
+            ClaseEvento::Expirado => 1 << 6,
+            ClaseEvento::Desalojado => 1 << 7,
+            ClaseEvento::Stream => 1 << 8,
+            ClaseEvento::SortedSet => 1 << 9,
+        }
+    }
+
+    fn caracter(self) -> char {
+        match self {
+            ClaseEvento::Generico => 'g',
+            ClaseEvento::String => '$',
+            ClaseEvento::Lista => 'l',
+            ClaseEvento::Set => 's',
+            ClaseEvento::Stream => 't',
+            ClaseEvento::SortedSet => 'z',
+            ClaseEvento::Expirado => 'x',
+            ClaseEvento::Desalojado => 'e',
+        }
+    }
+
+    fn flags_todas() -> u16 {
+        Self::TODAS
+            .iter()
+            .fold(0, |flags, clase| flags | clase.flag())
+    }
+}
+
+/// Evento sobre una clave, pendiente de notificar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventoClave {
+    pub clase: ClaseEvento,
+    pub evento: &'static str,
+    pub clave: Vec<u8>,
+}
+
+impl EventoClave {
+    pub fn new(clase: ClaseEvento, evento: &'static str, clave: impl Into<Vec<u8>>) -> Self {
+        Self {
+            clase,
+            evento,
+            clave: clave.into(),
+        }
+    }
+}
+
+/// Flags de `notify_keyspace_events`, modificables en ejecucion
+#[derive(Debug, Default)]
+pub struct NotificacionesKeyspace {
+    flags: AtomicU16,
+}
+
+impl NotificacionesKeyspace {
+    /// Crea la configuracion con las notificaciones deshabilitadas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reemplaza los flags por los indicados en `flags`
+    ///
+    /// # Retorna
+    /// - Error con el caracter invalido, de haberlo
+    pub fn configurar(&self, flags: &str) -> Result<(), String> {
+        self.flags.store(parsear_flags(flags)?, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Representacion de los flags configurados, como la acepta `configurar`
+    pub fn flags(&self) -> String {
+        let flags = self.flags.load(Ordering::SeqCst);
+        let mut texto = String::new();
+        let todas = ClaseEvento::flags_todas();
+        if flags & todas == todas {
+            texto.push('A');
+        } else {
+            for clase in ClaseEvento::TODAS {
+                if flags & clase.flag() != 0 {
+                    texto.push(clase.caracter());
+                }
+            }
+        }
+        if flags & FLAG_KEYSPACE != 0 {
+            texto.push('K');
+        }
+        if flags & FLAG_KEYEVENT != 0 {
+            texto.push('E');
+        }
+        texto
+    }
+
+    /// Indica si se publica algun tipo de notificacion
+    pub fn habilitadas(&self) -> bool {
+        let flags = self.flags.load(Ordering::SeqCst);
+        flags & (FLAG_KEYSPACE | FLAG_KEYEVENT) != 0 && flags & ClaseEvento::flags_todas() != 0
+    }
+
+    /// Obtiene los mensajes a publicar para un evento, como pares
+    /// (canal, mensaje). No hay mensajes si la clase del evento no esta
+    /// habilitada
+    pub fn mensajes(&self, evento: &EventoClave) -> Vec<(Token, Token)> {
+        let flags = self.flags.load(Ordering::SeqCst);
+        let mut mensajes = Vec::new();
+        if flags & evento.clase.flag() == 0 {
+            return mensajes;
+        }
+        if flags & FLAG_KEYSPACE != 0 {
+            let canal = [PREFIJO_KEYSPACE.as_bytes(), &evento.clave].concat();
+            mensajes.push((Token::new(canal), Token::from(evento.evento)));
+        }
+        if flags & FLAG_KEYEVENT != 0 {
+            mensajes.push((
+                Token::from(format!("{PREFIJO_KEYEVENT}{}", evento.evento)),
+                Token::from(evento.clave.as_slice()),
+            ));
+        }
+        mensajes
+    }
+}
+
+/// Parsea los flags de `notify_keyspace_events`: `K` (keyspace), `E`
+/// (keyevent), `g` (genericos), `$` (strings), `l` (listas), `s` (sets),
+/// `t` (streams), `z` (sorted sets), `x` (expirados), `e` (desalojados) y
+/// `A` (alias de `g$lstzxe`)
+///
+/// # Retorna
+/// - Flags como mascara de bits, error si hay un caracter desconocido
+pub fn parsear_flags(texto: &str) -> Result<u16, String> {
+    let mut flags = 0;
+    for c in texto.chars() {
+        flags |= match c {
+            'K' => FLAG_KEYSPACE,
+            'E' => FLAG_KEYEVENT,
+            'A' => ClaseEvento::flags_todas(),
+            _ => ClaseEvento::TODAS
+                .iter()
+                .find(|clase| clase.caracter() == c)
+                .map(|clase| clase.flag())
+                .ok_or_else(|| format!("Invalid event class character '{c}'"))?,
+        };
+    }
+    Ok(flags)
+}
+
+/// Obtiene los eventos generados por un comando ejecutado con exito. Las
+/// eliminaciones, expiraciones y desalojos los registra el storage
+///
+/// # Parámetros
+/// * `tokens`: secuencia de strings que conforman el comando
+/// * `respuesta`: respuesta del comando, usada para descartar los que no
+///   modificaron la clave
+pub fn eventos_de_comando(tokens: &[Token], respuesta: &DatoRedis) -> Vec<EventoClave> {
+    let Some(clave) = tokens.get(1) else {
+        return Vec::new();
+    };
+    let comando = tokens[0].to_uppercase();
+    let evento = |clase, evento: &'static str| vec![EventoClave::new(clase, evento, clave.clone())];
+
+    match comando.as_str() {
+        CMD_SET => eventos_set(tokens, respuesta),
+        CMD_MSET => tokens[1..]
+            .iter()
+            .step_by(2)
+            .map(|clave| EventoClave::new(ClaseEvento::String, "set", clave.clone()))
+            .collect(),
+        CMD_SETRANGE if tokens.get(3).is_some_and(|v| !v.is_empty()) => {
+            evento(ClaseEvento::String, "setrange")
+        }
+        CMD_APPEND => evento(ClaseEvento::String, "append"),
+        CMD_INCR => evento(ClaseEvento::String, "incrby"),
+        CMD_DECR => evento(ClaseEvento::String, "decrby"),
+        CMD_INCRBY => evento(ClaseEvento::String, "incrby"),
+        CMD_DECRBY => evento(ClaseEvento::String, "decrby"),
+        CMD_INCRBYFLOAT => evento(ClaseEvento::String, "incrbyfloat"),
+        CMD_SETBIT => evento(ClaseEvento::String, "setbit"),
+        CMD_BITFIELD
+            if tokens[2..]
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case("SET") || t.eq_ignore_ascii_case("INCRBY")) =>
+        {
+            evento(ClaseEvento::String, "setbit")
+        }
+        CMD_GEOADD if entero_positivo(respuesta) => evento(ClaseEvento::SortedSet, "zadd"),
+        CMD_PFADD if entero_positivo(respuesta) => evento(ClaseEvento::String, "pfadd"),
+        CMD_PFMERGE => evento(ClaseEvento::String, "pfadd"),
+        CMD_BITOP if entero_positivo(respuesta) && tokens.len() > 2 => vec![EventoClave::new(
+            ClaseEvento::String,
+            "set",
+            tokens[2].clone(),
+        )],
+        CMD_GETEX if tokens.len() > 2 => {
+            let evento_getex = if tokens[2].to_uppercase() == "PERSIST" {
+                "persist"
+            } else {
+                "expire"
+            };
+            evento(ClaseEvento::Generico, evento_getex)
+        }
+        CMD_LPUSH => evento(ClaseEvento::Lista, "lpush"),
+        CMD_RPUSH => evento(ClaseEvento::Lista, "rpush"),
+        CMD_LPUSHX if entero_positivo(respuesta) => evento(ClaseEvento::Lista, "lpush"),
+        CMD_RPUSHX if entero_positivo(respuesta) => evento(ClaseEvento::Lista, "rpush"),
+        CMD_LSET => evento(ClaseEvento::Lista, "lset"),
+        CMD_LTRIM => evento(ClaseEvento::Lista, "ltrim"),
+        CMD_LINSERT if entero_positivo(respuesta) => evento(ClaseEvento::Lista, "linsert"),
+        CMD_LREM if entero_positivo(respuesta) => evento(ClaseEvento::Lista, "lrem"),
+        CMD_LPOP if !es_nulo(respuesta) => evento(ClaseEvento::Lista, "lpop"),
+        CMD_RPOP if !es_nulo(respuesta) => evento(ClaseEvento::Lista, "rpop"),
+        CMD_LMOVE if !es_nulo(respuesta) && tokens.len() == 5 => {
+            let (origen, destino) = match (
+                tokens[3].to_uppercase().as_str(),
+                tokens[4].to_uppercase().as_str(),
+            ) {
+                ("LEFT", "LEFT") => ("lpop", "lpush"),
+                ("LEFT", _) => ("lpop", "rpush"),
+                (_, "LEFT") => ("rpop", "lpush"),
+                _ => ("rpop", "rpush"),
+            };
+            vec![
+                EventoClave::new(ClaseEvento::Lista, origen, clave.clone()),
+                EventoClave::new(ClaseEvento::Lista, destino, tokens[2].clone()),
+            ]
+        }
+        CMD_RPOPLPUSH if !es_nulo(respuesta) && tokens.len() == 3 => vec![
+            EventoClave::new(ClaseEvento::Lista, "rpop", clave.clone()),
+            EventoClave::new(ClaseEvento::Lista, "lpush", tokens[2].clone()),
+        ],
+        CMD_LMPOP => evento_lmpop(tokens, respuesta).into_iter().collect(),
+        CMD_XADD if !es_nulo(respuesta) => evento(ClaseEvento::Stream, "xadd"),
+        CMD_XTRIM if entero_positivo(respuesta) => evento(ClaseEvento::Stream, "xtrim"),
+        CMD_XDEL if entero_positivo(respuesta) => evento(ClaseEvento::Stream, "xdel"),
+        CMD_XGROUP => evento_xgroup(tokens, respuesta).into_iter().collect(),
+        CMD_SADD if entero_positivo(respuesta) => evento(ClaseEvento::Set, "sadd"),
+        CMD_SREM if entero_positivo(respuesta) => evento(ClaseEvento::Set, "srem"),
+        CMD_RESTORE => evento(ClaseEvento::Generico, "restore"),
+        CMD_RENAME | CMD_RENAMENX
+            if tokens.len() == 3 && tokens[1] != tokens[2] && !es_cero(respuesta) =>
+        {
+            vec![
+                EventoClave::new(ClaseEvento::Generico, "rename_from", clave.clone()),
+                EventoClave::new(ClaseEvento::Generico, "rename_to", tokens[2].clone()),
+            ]
+        }
+        CMD_COPY if entero_positivo(respuesta) => vec![EventoClave::new(
+            ClaseEvento::Generico,
+            "copy_to",
+            tokens[2].clone(),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Obtiene el evento de LMPOP, cuya clave es la de la lista de la que se
+/// eliminaron elementos, informada en la respuesta
+fn evento_lmpop(tokens: &[Token], respuesta: &DatoRedis) -> Option<EventoClave> {
+    let DatoRedis::Arrays(respuesta) = respuesta else {
+        return None;
+    };
+    let Some(DatoRedis::BulkString(clave)) = respuesta.get(0) else {
+        return None;
+    };
+    let numkeys = tokens.get(1)?.parse::<usize>().ok()?;
+    let evento = match tokens.get(numkeys + 2)?.to_uppercase().as_str() {
+        "LEFT" => "lpop",
+        _ => "rpop",
+    };
+    Some(EventoClave::new(ClaseEvento::Lista, evento, clave.bytes()))
+}
+
+/// Obtiene los eventos de SET: `set` si escribio el valor y `expire` si
+/// ademas asigno una expiracion. Con GET la respuesta es el valor previo,
+/// por lo que la escritura depende de la condicion NX o XX
+fn eventos_set(tokens: &[Token], respuesta: &DatoRedis) -> Vec<EventoClave> {
+    let opciones: Vec<String> = tokens.iter().skip(3).map(|t| t.to_uppercase()).collect();
+    let tiene = |opcion: &str| opciones.iter().any(|o| o == opcion);
+    let escribio = if !tiene("GET") {
+        true
+    } else if tiene("NX") {
+        es_nulo(respuesta)
+    } else if tiene("XX") {
+        !es_nulo(respuesta)
+    } else {
+        true
+    };
+    if !escribio {
+        return Vec::new();
+    }
+    let mut eventos = vec![EventoClave::new(
+        ClaseEvento::String,
+        "set",
+        tokens[1].clone(),
+    )];
+    if ["EX", "PX", "EXAT", "PXAT"].iter().any(|o| tiene(o)) {
+        eventos.push(EventoClave::new(
+            ClaseEvento::Generico,
+            "expire",
+            tokens[1].clone(),
+        ));
+    }
+    eventos
+}
+
+/// Obtiene el evento de XGROUP, cuya clave es el tercer argumento
+fn evento_xgroup(tokens: &[Token], respuesta: &DatoRedis) -> Option<EventoClave> {
+    let evento = match tokens.get(1)?.to_uppercase().as_str() {
+        "CREATE" => "xgroup-create",
+        "SETID" => "xgroup-setid",
+        "DESTROY" if entero_positivo(respuesta) => "xgroup-destroy",
+        "CREATECONSUMER" if entero_positivo(respuesta) => "xgroup-createconsumer",
+        "DELCONSUMER" => "xgroup-delconsumer",
+        _ => return None,
+    };
+    Some(EventoClave::new(
+        ClaseEvento::Stream,
+        evento,
+        tokens.get(2)?.clone(),
+    ))
+}
+
+fn entero_positivo(respuesta: &DatoRedis) -> bool {
+    matches!(respuesta, DatoRedis::Integer(entero) if entero.valor() > 0)
+}
+
+fn es_cero(respuesta: &DatoRedis) -> bool {
+    matches!(respuesta, DatoRedis::Integer(entero) if entero.valor() == 0)
+}
+
+fn es_nulo(respuesta: &DatoRedis) -> bool {
+    matches!(respuesta, DatoRedis::Null(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use redis_client::protocol::token::tokens;
+
+    use super::*;
+
+    #[test]
+    fn test_01_parseo_y_representacion_de_flags() {
+        let notificaciones = NotificacionesKeyspace::new();
+        assert!(!notificaciones.habilitadas());
+
+        notificaciones.configurar("El$").unwrap();
+        assert_eq!(notificaciones.flags(), "$lE");
+        assert!(notificaciones.habilitadas());
+
+        notificaciones.configurar("KEA").unwrap();
+        assert_eq!(notificaciones.flags(), "AKE");
+
+        assert!(notificaciones.configurar("Kh").is_err());
+        assert_eq!(notificaciones.flags(), "AKE");
+
+        notificaciones.configurar("Kz").unwrap();
+        assert_eq!(notificaciones.flags(), "zK");
+
+        notificaciones.configurar("").unwrap();
+        assert!(!notificaciones.habilitadas());
+    }
+
+    #[test]
+    fn test_02_mensajes_segun_flags() {
+        let notificaciones = NotificacionesKeyspace::new();
+        let evento = EventoClave::new(ClaseEvento::Lista, "lpush", "cola".to_string());
+
+        notificaciones.configurar("K$").unwrap();
+        assert!(notificaciones.mensajes(&evento).is_empty());
+
+        notificaciones.configurar("Kl").unwrap();
+        assert_eq!(
+            notificaciones.mensajes(&evento),
+            vec![(Token::from("__keyspace@0__:cola"), Token::from("lpush"))]
+        );
+
+        notificaciones.configurar("KEl").unwrap();
+        assert_eq!(
+            notificaciones.mensajes(&evento),
+            vec![
+                (Token::from("__keyspace@0__:cola"), Token::from("lpush")),
+                (Token::from("__keyevent@0__:lpush"), Token::from("cola")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_03_eventos_de_comandos() {
+        let ok = DatoRedis::new_simple_string("OK".to_string()).unwrap();
+        assert_eq!(
+            eventos_de_comando(&tokens(&["set", "clave", "valor"]), &ok),
+            vec![EventoClave::new(
+                ClaseEvento::String,
+                "set",
+                "clave".to_string()
+            )]
+        );
+        assert!(
+            eventos_de_comando(&tokens(&["sadd", "s", "a"]), &DatoRedis::new_integer(0)).is_empty()
+        );
+        assert!(eventos_de_comando(&tokens(&["lpop", "l"]), &DatoRedis::new_null()).is_empty());
+        assert!(eventos_de_comando(&tokens(&["get", "clave"]), &ok).is_empty());
+
+        let movido = DatoRedis::new_bulk_string("x".to_string()).unwrap();
+        let eventos = eventos_de_comando(&tokens(&["lmove", "a", "b", "RIGHT", "LEFT"]), &movido);
+        assert_eq!(
+            eventos,
+            vec![
+                EventoClave::new(ClaseEvento::Lista, "rpop", "a".to_string()),
+                EventoClave::new(ClaseEvento::Lista, "lpush", "b".to_string()),
+            ]
+        );
+    }
+}
@@ -1 +1,2 @@
 pub mod persistencia;
+pub mod snapshot;
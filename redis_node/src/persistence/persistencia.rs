@@ -1,25 +1,31 @@
 //! Este módulo contiene la lógica principal de persistencia de la metadata
 //! y el storage del nodo
 use std::{
-    collections::HashMap,
     fs::File,
     io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     net::{SocketAddr, ToSocketAddrs},
     ops::Range,
-    sync::{Arc, RwLock, atomic::AtomicUsize},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use logger::logger::Logger;
 use redis_client::protocol::{
     dataencryption::{decrypt_from_hex, encrypt_y_encode_hex},
-    protocol_resp::parsear_comando,
+    protocol_resp::{escapar_token, parsear_comando},
+    token::Token,
 };
 use redis_client::tipos_datos::traits::DatoRedis;
 
 use crate::{
     comandos::pub_sub_struct::PubSubBroker,
+    estadisticas::EstadisticasNodo,
+    latencia::{EVENTO_SNAPSHOT, MonitorLatencia},
+    persistence::snapshot::{deserializar_snapshot, serializar_snapshot},
     utils::utils_functions::{obtener_fn_normal, sumar_puerto},
 };
 use crate::{comandos::utils::get_comando_metadata, node_builder::NodeBuilder};
@@ -309,37 +315,20 @@ fn restaurar_address(reader: &mut BufReader<File>) -> Result<SocketAddr, io::Err
     Ok(socket_addr)
 }
 
-/// Guarda el contenido completo del `Storage` del nodo en el archivo de persistencia.
+/// Guarda el contenido completo del `Storage` del nodo en el archivo de persistencia,
+/// usando el formato versionado definido en `snapshot`.
 ///
 /// # Errores
-/// Retorna un `io::Error` si ocurre un problema durante la escritura.
+/// Retorna un `io::Error` si ocurre un problema durante la serialización o la escritura.
 pub fn guardar_storage(writer: &mut BufWriter<File>, storage: &Storage) -> Result<(), io::Error> {
-    guardar_range(&storage.get_slot_range(), writer)?;
-
-    for (slot, mapa) in storage.iter() {
-        writer.write_all(&slot.to_be_bytes())?;
-
-        let cantidad = mapa.len() as u32;
-        writer.write_all(&cantidad.to_be_bytes())?;
-
-        for (key, dato) in mapa {
-            let key_bytes = key.as_bytes();
-            let key_len = key_bytes.len() as u32;
-            writer.write_all(&key_len.to_be_bytes())?;
-            writer.write_all(key_bytes)?;
-
-            let dato_bytes = dato.to_bytes();
-            let dato_len = dato_bytes.len() as u32;
-            writer.write_all(&dato_len.to_be_bytes())?;
-            writer.write_all(&dato_bytes)?;
-        }
-    }
-
-    Ok(())
+    let snapshot = serializar_snapshot(storage)?;
+    writer.write_all(&snapshot)
 }
 
 /// Restaura el contenido completo del `Storage` desde un archivo binario de persistencia.
 ///
+/// Acepta tanto el formato versionado actual como el formato legacy sin header.
+///
 /// # Parámetros
 /// - `reader`: Reader con buffer desde el archivo de persistencia.
 ///
@@ -349,49 +338,9 @@ pub fn guardar_storage(writer: &mut BufWriter<File>, storage: &Storage) -> Resul
 /// # Errores
 /// Retorna un `io::Error` si hay problemas de lectura, formato incorrecto o datos corruptos.
 fn restaurar_storage_bin(reader: &mut BufReader<File>) -> Result<Storage, io::Error> {
-    let slot_range = restaurar_slot_range(reader)?;
-
-    let mut hashes_slots = HashMap::new();
-
-    while reader.fill_buf().is_ok() {
-        let mut slot_buf = [0u8; 2];
-        if reader.read_exact(&mut slot_buf).is_err() {
-            break;
-        }
-        let slot = u16::from_be_bytes(slot_buf);
-
-        let mut cantidad_buf = [0u8; 4];
-        reader.read_exact(&mut cantidad_buf)?;
-        let cantidad = u32::from_be_bytes(cantidad_buf);
-
-        let mut mapa = HashMap::new();
-        for _ in 0..cantidad {
-            reader.read_exact(&mut cantidad_buf)?;
-            let key_len = u32::from_be_bytes(cantidad_buf) as usize;
-
-            let mut key_buf = vec![0u8; key_len];
-            reader.read_exact(&mut key_buf)?;
-            let key = String::from_utf8_lossy(&key_buf).to_string();
-
-            reader.read_exact(&mut cantidad_buf)?;
-            let dato_len = u32::from_be_bytes(cantidad_buf) as usize;
-
-            let mut dato_buf = vec![0u8; dato_len];
-            reader.read_exact(&mut dato_buf)?;
-            let dato = DatoRedis::from_encrypted_bytes(&dato_buf).map_err(|e| {
-                io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Error en DatoRedis: {e:?}"),
-                )
-            })?;
-
-            mapa.insert(key, dato);
-        }
-
-        hashes_slots.insert(slot, mapa);
-    }
-
-    Ok(Storage::new_with_content(slot_range, hashes_slots))
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    deserializar_snapshot(&bytes)
 }
 
 /// Guarda una operación mutante en el archivo AOF.
@@ -401,23 +350,21 @@ fn restaurar_storage_bin(reader: &mut BufReader<File>) -> Result<Storage, io::Er
 ///
 /// # Parámetros
 /// - `file`: Referencia mutable al archivo AOF donde se guarda la operación.
-/// - `operacion`: Vector de strings que representa la operación ejecutada (ej: `["SET", "clave", "valor"]`).
+/// - `operacion`: Vector de tokens que representa la operación ejecutada (ej: `["SET", "clave", "valor"]`).
 /// - `operaciones_no_mutables`: Conjunto con los nombres de operaciones que no deben persistirse.
 ///
 /// # Errores
 /// Devuelve un error si falla al escribir en el archivo.
-pub fn guardar_operacion(
-    file: &Arc<RwLock<File>>,
-    operacion: Vec<String>,
-) -> Result<(), io::Error> {
-    let metadata = get_comando_metadata(&operacion[0], &operacion);
+pub fn guardar_operacion(file: &Arc<RwLock<File>>, operacion: Vec<Token>) -> Result<(), io::Error> {
+    let metadata = get_comando_metadata(&operacion[0].texto(), &operacion);
     if !metadata.es_mutable {
         return Ok(());
     }
-    let mut tokens_encriptados = operacion.to_vec();
+    let mut tokens_encriptados: Vec<String> =
+        operacion.iter().map(|token| escapar_token(token)).collect();
 
     for &i in &metadata.indices_datos {
-        match encrypt_y_encode_hex(&tokens_encriptados[i]) {
+        match encrypt_y_encode_hex(&operacion[i]) {
             Ok(hex) => tokens_encriptados[i] = hex,
             Err(_) => {
                 io::Error::other("Error al encriptar datos AOF");
@@ -442,12 +389,12 @@ pub fn guardar_operacion(
 /// - `file`: Archivo AOF a leer.
 ///
 /// # Retorna
-/// Un vector de operaciones, donde cada operación es un vector de strings.
+/// Un vector de operaciones, donde cada operación es un vector de tokens.
 ///
 /// # Errores
 /// - Si ocurre un error de E/S.
 /// - Si el archivo está vacío, se devuelve un error de `UnexpectedEof`.
-fn restaurar_operaciones(file: File) -> Result<Vec<Vec<String>>, io::Error> {
+fn restaurar_operaciones(file: File) -> Result<Vec<Vec<Token>>, io::Error> {
     let reader = BufReader::new(file);
     let mut operaciones = Vec::new();
 
@@ -455,13 +402,13 @@ fn restaurar_operaciones(file: File) -> Result<Vec<Vec<String>>, io::Error> {
         let line = line?;
         let linea = parsear_comando(line);
 
-        let metadata = get_comando_metadata(&linea[0], &linea);
+        let metadata = get_comando_metadata(&linea[0].texto(), &linea);
         let mut desencriptado = linea.to_vec();
 
         for &i in &metadata.indices_datos {
             if i < linea.len() {
-                match decrypt_from_hex(&linea[i]) {
-                    Ok(descifrado) => desencriptado[i] = descifrado,
+                match decrypt_from_hex(&linea[i].texto()) {
+                    Ok(descifrado) => desencriptado[i] = Token::new(descifrado),
                     Err(_) => {
                         continue;
                     }
@@ -758,21 +705,34 @@ fn restaurar_storage(
 /// * `storage`: arc del storage a guardar
 /// * `save_interval`: intervalo de guardado
 /// * `logger`: logger para registrar errores
+/// * `estadisticas`: estadisticas del nodo donde registrar el resultado
+/// * `latencia`: monitor donde registrar la duracion de cada guardado
 pub fn save_storage(
     path_bin: &str,
     aof: Option<Arc<RwLock<File>>>,
     storage: &Arc<RwLock<Storage>>,
-    save_interval: &u64,
+    save_interval: &AtomicU64,
     logger: &Logger,
+    estadisticas: &EstadisticasNodo,
+    latencia: &MonitorLatencia,
 ) {
     loop {
-        thread::sleep(Duration::from_millis(*save_interval));
-        match guardar_storage_bin(path_bin, storage, &aof, logger) {
-            Ok(_) => logger.info("[SAVE-RDB THREAD] Guardado completado", "Node"),
-            Err(e) => logger.error(
-                &format!("[SAVE-RDB THREAD] Error {e:?} guardando en {path_bin}"),
-                "Node",
-            ),
+        thread::sleep(Duration::from_millis(save_interval.load(Ordering::SeqCst)));
+        let inicio = Instant::now();
+        let resultado = guardar_storage_bin(path_bin, storage, &aof, logger);
+        latencia.registrar(EVENTO_SNAPSHOT, inicio.elapsed());
+        match resultado {
+            Ok(_) => {
+                estadisticas.registrar_guardado(true);
+                logger.info("[SAVE-RDB THREAD] Guardado completado", "Node")
+            }
+            Err(e) => {
+                estadisticas.registrar_guardado(false);
+                logger.error(
+                    &format!("[SAVE-RDB THREAD] Error {e:?} guardando en {path_bin}"),
+                    "Node",
+                )
+            }
         }
     }
 }
@@ -782,6 +742,7 @@ mod tests {
     use super::*;
     use crate::node_id::NodeId;
     use logger::logger::Logger;
+    use redis_client::protocol::token::tokens;
     use std::fs::File;
     use std::io::BufWriter;
 
@@ -896,19 +857,19 @@ mod tests {
         let mut storage = Storage::new(slot_range);
         storage
             .set(
-                "key1".to_string(),
+                "key1",
                 DatoRedis::new_bulk_string("value1".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key2".to_string(),
+                "key2",
                 DatoRedis::new_bulk_string("value2".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key3".to_string(),
+                "key3",
                 DatoRedis::new_bulk_string("value3".to_string()).unwrap(),
             )
             .unwrap();
@@ -939,19 +900,19 @@ mod tests {
         let mut storage = Storage::new(slot_range.clone());
         storage
             .set(
-                "key1".to_string(),
+                "key1",
                 DatoRedis::new_bulk_string("value1".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key2".to_string(),
+                "key2",
                 DatoRedis::new_bulk_string("value2".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key3".to_string(),
+                "key3",
                 DatoRedis::new_bulk_string("value3".to_string()).unwrap(),
             )
             .unwrap();
@@ -992,19 +953,19 @@ mod tests {
         let mut storage = Storage::new(slot_range);
         storage
             .set(
-                "key1".to_string(),
+                "key1",
                 DatoRedis::new_bulk_string("value1".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key2".to_string(),
+                "key2",
                 DatoRedis::new_bulk_string("value2".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key3".to_string(),
+                "key3",
                 DatoRedis::new_bulk_string("value3".to_string()).unwrap(),
             )
             .unwrap();
@@ -1016,19 +977,19 @@ mod tests {
 
         storage
             .set(
-                "key4".to_string(),
+                "key4",
                 DatoRedis::new_bulk_string("value4".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key5".to_string(),
+                "key5",
                 DatoRedis::new_bulk_string("value5".to_string()).unwrap(),
             )
             .unwrap();
         storage
             .set(
-                "key6".to_string(),
+                "key6",
                 DatoRedis::new_bulk_string("value6".to_string()).unwrap(),
             )
             .unwrap();
@@ -1112,7 +1073,7 @@ mod tests {
         use std::fs::File;
         use std::sync::{Arc, RwLock};
 
-        let comando = vec!["SET".to_string(), "clave".to_string(), "valor".to_string()];
+        let comando = tokens(&["SET", "clave", "valor"]);
 
         let file = File::create("test_aof.log").expect("No se pudo crear archivo");
         let file_arc = Arc::new(RwLock::new(file));
@@ -1134,4 +1095,88 @@ mod tests {
 
         std::fs::remove_file("test_aof.log").expect("No se pudo borrar archivo");
     }
+
+    #[test]
+    fn test_17_aof_con_claves_y_valores_binarios() {
+        let comando = vec![
+            Token::from("SET"),
+            Token::from(&b"clave con\r\n \"espacios\" \xff"[..]),
+            Token::from(&[0x00, 0xff, b' ', 0xc3][..]),
+        ];
+        let file = File::create("test_aof_binario.log").expect("No se pudo crear archivo");
+        let file_arc = Arc::new(RwLock::new(file));
+
+        guardar_operacion(&file_arc, comando.clone()).expect("Error guardando operacion");
+
+        let file_lectura =
+            File::open("test_aof_binario.log").expect("No se pudo abrir archivo para lectura");
+        let operaciones_restauradas =
+            restaurar_operaciones(file_lectura).expect("Error restaurando operaciones");
+        std::fs::remove_file("test_aof_binario.log").expect("No se pudo borrar archivo");
+
+        assert_eq!(operaciones_restauradas, vec![comando]);
+    }
+
+    #[test]
+    fn test_18_aof_restaura_funciones() {
+        let codigo = "#!forth name=documentos\n: nuevo_id call\" INCR KEYS[1]\" ;";
+        let operaciones = vec![
+            vec![
+                "FUNCTION".to_string(),
+                "LOAD".to_string(),
+                codigo.to_string(),
+            ],
+            vec![
+                "FCALL".to_string(),
+                "nuevo_id".to_string(),
+                "1".to_string(),
+                "documentos:id".to_string(),
+            ],
+            vec!["FUNCTION".to_string(), "LIST".to_string()],
+        ];
+        let file = File::create("test_aof_funciones.log").expect("No se pudo crear archivo");
+        let file_arc = Arc::new(RwLock::new(file));
+        for operacion in &operaciones {
+            guardar_operacion(&file_arc, tokens(operacion)).expect("Error guardando operacion");
+        }
+
+        let storage = Arc::new(RwLock::new(Storage::new(0..16378)));
+        let restaurado = restaurar_storage_de_aof(&"test_aof_funciones.log".to_string(), &storage);
+        std::fs::remove_file("test_aof_funciones.log").expect("No se pudo borrar archivo");
+        restaurado.unwrap();
+
+        let storage = storage.read().unwrap();
+        let biblioteca = storage.funciones().bibliotecas().next().unwrap();
+        assert_eq!(biblioteca.codigo(), codigo.as_bytes());
+        assert_eq!(
+            storage.get("documentos:id").unwrap(),
+            DatoRedis::new_bulk_string("1".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_19_aof_con_claves_y_valores_vacios() {
+        let operaciones = vec![
+            vec![Token::from("SET"), Token::from(""), Token::from("")],
+            vec![
+                Token::from("RPUSH"),
+                Token::from("lista"),
+                Token::from(""),
+                Token::from("a"),
+            ],
+        ];
+        let file = File::create("test_aof_vacios.log").expect("No se pudo crear archivo");
+        let file_arc = Arc::new(RwLock::new(file));
+        for operacion in &operaciones {
+            guardar_operacion(&file_arc, operacion.clone()).expect("Error guardando operacion");
+        }
+
+        let file_lectura =
+            File::open("test_aof_vacios.log").expect("No se pudo abrir archivo para lectura");
+        let operaciones_restauradas =
+            restaurar_operaciones(file_lectura).expect("Error restaurando operaciones");
+        std::fs::remove_file("test_aof_vacios.log").expect("No se pudo borrar archivo");
+
+        assert_eq!(operaciones_restauradas, operaciones);
+    }
 }
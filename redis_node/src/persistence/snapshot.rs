@@ -0,0 +1,899 @@
+//! Este módulo define el formato binario versionado de los snapshots del
+//! storage (RDB) y la serialización de valores individuales que usan los
+//! comandos DUMP y RESTORE.
+//!
+//! # Formato del snapshot (versión 1)
+//!
+//! Todos los enteros se escriben en big endian.
+//!
+//! | Campo        | Tamaño   | Descripción                                      |
+//! |--------------|----------|--------------------------------------------------|
+//! | magic        | 8 bytes  | `RUSTYRDB`                                       |
+//! | versión      | u16      | versión del formato (`SNAPSHOT_VERSION`)         |
+//! | slot range   | 2 x u16  | inicio y fin del rango de slots del storage      |
+//! | secciones    | variable | una sección por slot con claves                  |
+//! | funciones    | variable | sección opcional con las bibliotecas de funciones |
+//! | fin          | u8       | `OPCODE_EOF`                                     |
+//! | checksum     | u32      | CRC32 de todos los bytes anteriores              |
+//!
+//! Cada sección comienza con `OPCODE_SLOT`, el número de slot (u16) y la
+//! cantidad de claves (u32). Cada clave se escribe como: largo de la clave
+//! (u32), clave, instante de expiración en milisegundos desde UNIX epoch
+//! (u64, `SIN_EXPIRACION` si la clave es persistente), tag de tipo (u8),
+//! largo del valor (u32) y el valor codificado y encriptado con AES
+//! (`encrypt_bytes`). Las claves expiradas no se escriben, y las que
+//! expiraron mientras el nodo estaba detenido se descartan al leer.
+//!
+//! La sección de funciones comienza con `OPCODE_FUNCIONES` y la cantidad de
+//! bibliotecas (u32), y contiene el código de cada una como bloque
+//! encriptado. El código incluye el header con el nombre y la versión de la
+//! biblioteca. Las bibliotecas se vuelven a cargar al restaurar el
+//! snapshot, y los snapshots sin la sección se leen sin funciones.
+//!
+//! # Codificación de valores
+//!
+//! - `TAG_STRING`: bytes del string.
+//! - `TAG_LIST` y `TAG_SET`: cantidad de elementos (u32) y, por cada uno,
+//!   su tag (u8), largo (u32) y codificación.
+//! - `TAG_STREAM`: último id generado (2 x u64), cantidad de entradas
+//!   agregadas desde su creación (u64), cantidad de entradas (u32) y, por
+//!   cada una, su id (2 x u64), cantidad de campos (u32) y cada campo y
+//!   valor como bloque (largo u32 y bytes). Le sigue la cantidad de grupos
+//!   de consumidores (u32) y, por cada uno, su nombre como bloque, su
+//!   último id entregado, la cantidad de consumidores (u32) con el nombre
+//!   y el instante de última actividad (u64) de cada uno, y la cantidad de
+//!   entradas pendientes (u32) con su id, consumidor, instante de entrega
+//!   (u64) y cantidad de entregas (u64). Los streams sin la sección de
+//!   grupos se leen sin grupos.
+//! - `TAG_HYPERLOGLOG`: representación en bytes del HyperLogLog, con su
+//!   codificación dispersa o densa.
+//! - `TAG_SORTED_SET`: cantidad de miembros (u32) y, por cada uno, su
+//!   nombre como bloque y su puntaje (f64).
+//!
+//! # Payload de DUMP
+//!
+//! `tag (u8) | valor codificado | versión (u16) | CRC32 (u32)`. El valor no
+//! se encripta, ya que su destino es otro nodo o un backup del cliente.
+//!
+//! # Compatibilidad
+//!
+//! Los archivos que no comienzan con el magic corresponden al formato previo
+//! (versión 0): rango de slots seguido de las entradas con el `DatoRedis`
+//! encriptado en RESP. Se leen con `leer_snapshot_legacy` y quedan
+//! actualizados a la versión actual en el siguiente guardado.
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, BufRead, Cursor, Read},
+};
+
+use common::crc32::crc32;
+use redis_client::protocol::dataencryption::{decrypt_bytes, encrypt_bytes};
+use redis_client::tipos_datos::{
+    arrays::Arrays,
+    hyperloglog::HyperLogLog,
+    set::Set,
+    sorted_set::SortedSet,
+    stream::{CamposStream, GrupoConsumidores, Pendiente, Stream, StreamId},
+    traits::DatoRedis,
+};
+
+use crate::memoria::ahora_ms;
+use crate::storage::Storage;
+
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"RUSTYRDB";
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// Instante de expiración que se escribe para las claves persistentes
+const SIN_EXPIRACION: u64 = 0;
+
+const OPCODE_FUNCIONES: u8 = 0xFD;
+const OPCODE_SLOT: u8 = 0xFE;
+const OPCODE_EOF: u8 = 0xFF;
+
+pub const TAG_STRING: u8 = 0;
+pub const TAG_LIST: u8 = 1;
+pub const TAG_SET: u8 = 2;
+pub const TAG_STREAM: u8 = 3;
+pub const TAG_HYPERLOGLOG: u8 = 4;
+pub const TAG_SORTED_SET: u8 = 5;
+
+/// Serializa el storage completo en el formato de snapshot vigente.
+///
+/// # Parámetros
+/// - `storage`: Storage a serializar.
+///
+/// # Retorna
+/// Los bytes del snapshot, incluyendo header y checksum.
+///
+/// # Errores
+/// Retorna un `io::Error` si algún valor no puede codificarse o encriptarse.
+pub fn serializar_snapshot(storage: &Storage) -> Result<Vec<u8>, io::Error> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(SNAPSHOT_MAGIC);
+    buffer.extend_from_slice(&SNAPSHOT_VERSION.to_be_bytes());
+
+    let slot_range = storage.get_slot_range();
+    buffer.extend_from_slice(&slot_range.start.to_be_bytes());
+    buffer.extend_from_slice(&slot_range.end.to_be_bytes());
+
+    let ahora = ahora_ms();
+    for (slot, mapa) in storage.iter() {
+        let vigentes: Vec<_> = mapa
+            .iter()
+            .map(|(key, dato)| (key, dato, storage.expiracion(key)))
+            .filter(|(_, _, expira_en)| expira_en.is_none_or(|expira_en| expira_en > ahora))
+            .collect();
+        buffer.push(OPCODE_SLOT);
+        buffer.extend_from_slice(&slot.to_be_bytes());
+        buffer.extend_from_slice(&(vigentes.len() as u32).to_be_bytes());
+
+        for (key, dato, expira_en) in vigentes {
+            escribir_bloque(&mut buffer, key);
+            let expira_en = expira_en.unwrap_or(SIN_EXPIRACION);
+            buffer.extend_from_slice(&expira_en.to_be_bytes());
+
+            let (tag, valor) = codificar_valor(dato)?;
+            let valor_encriptado = encrypt_bytes(&valor).map_err(error_encriptado)?;
+            buffer.push(tag);
+            escribir_bloque(&mut buffer, &valor_encriptado);
+        }
+    }
+
+    let bibliotecas: Vec<_> = storage.funciones().bibliotecas().collect();
+    if !bibliotecas.is_empty() {
+        buffer.push(OPCODE_FUNCIONES);
+        buffer.extend_from_slice(&(bibliotecas.len() as u32).to_be_bytes());
+        for biblioteca in bibliotecas {
+            let codigo_encriptado = encrypt_bytes(biblioteca.codigo()).map_err(error_encriptado)?;
+            escribir_bloque(&mut buffer, &codigo_encriptado);
+        }
+    }
+
+    buffer.push(OPCODE_EOF);
+    let checksum = crc32(&buffer);
+    buffer.extend_from_slice(&checksum.to_be_bytes());
+    Ok(buffer)
+}
+
+/// Reconstruye un storage a partir de los bytes de un snapshot.
+///
+/// Detecta la versión del formato: los snapshots sin magic se interpretan
+/// con el formato legacy, y los de versiones posteriores a la soportada
+/// se rechazan.
+///
+/// # Parámetros
+/// - `bytes`: Contenido completo del snapshot.
+///
+/// # Retorna
+/// El `Storage` restaurado.
+///
+/// # Errores
+/// Retorna un `io::Error` si el checksum no coincide, la versión no es
+/// soportada o los datos están corruptos.
+pub fn deserializar_snapshot(bytes: &[u8]) -> Result<Storage, io::Error> {
+    if !bytes.starts_with(SNAPSHOT_MAGIC) {
+        return leer_snapshot_legacy(&mut Cursor::new(bytes));
+    }
+
+    let mut cursor = Cursor::new(&bytes[SNAPSHOT_MAGIC.len()..]);
+    let version = leer_u16(&mut cursor)?;
+    match version {
+        1 => leer_snapshot_v1(bytes),
+        v => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Versión de snapshot no soportada: {v}"),
+        )),
+    }
+}
+
+/// Lee un snapshot en la versión 1 del formato.
+///
+/// # Errores
+/// Retorna un `io::Error` si el checksum no coincide o los datos están
+/// truncados o corruptos.
+fn leer_snapshot_v1(bytes: &[u8]) -> Result<Storage, io::Error> {
+    let contenido = verificar_checksum(bytes)?;
+    let mut cursor = Cursor::new(&contenido[SNAPSHOT_MAGIC.len() + 2..]);
+
+    let start = leer_u16(&mut cursor)?;
+    let end = leer_u16(&mut cursor)?;
+
+    let ahora = ahora_ms();
+    let mut hashes_slots = HashMap::new();
+    let mut expiraciones = Vec::new();
+    let mut bibliotecas = Vec::new();
+    loop {
+        match leer_u8(&mut cursor)? {
+            OPCODE_SLOT => {
+                let slot = leer_u16(&mut cursor)?;
+                let cantidad = leer_u32(&mut cursor)?;
+
+                let mut mapa = HashMap::new();
+                for _ in 0..cantidad {
+                    let key = leer_bloque(&mut cursor)?;
+                    let expira_en = leer_u64(&mut cursor)?;
+                    let tag = leer_u8(&mut cursor)?;
+                    let valor_encriptado = leer_bloque(&mut cursor)?;
+                    let valor = decrypt_bytes(&valor_encriptado).map_err(error_encriptado)?;
+                    let dato = decodificar_valor(tag, &valor)?;
+                    if expira_en == SIN_EXPIRACION {
+                        mapa.insert(key, dato);
+                    } else if expira_en > ahora {
+                        expiraciones.push((key.clone(), expira_en));
+                        mapa.insert(key, dato);
+                    }
+                }
+                hashes_slots.insert(slot, mapa);
+            }
+            OPCODE_FUNCIONES => {
+                for _ in 0..leer_u32(&mut cursor)? {
+                    let codigo_encriptado = leer_bloque(&mut cursor)?;
+                    let codigo = decrypt_bytes(&codigo_encriptado).map_err(error_encriptado)?;
+                    bibliotecas.push(codigo);
+                }
+            }
+            OPCODE_EOF => break,
+            opcode => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Opcode de snapshot desconocido: {opcode:#x}"),
+                ));
+            }
+        }
+    }
+
+    let mut storage = Storage::new_with_content(start..end, hashes_slots);
+    for (key, expira_en) in expiraciones {
+        storage.set_expiracion(key, Some(expira_en)).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Clave fuera del rango del snapshot: {e:?}"),
+            )
+        })?;
+    }
+    for codigo in bibliotecas {
+        storage
+            .funciones_mut()
+            .cargar(&codigo, false)
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Biblioteca de funciones inválida: {e:?}"),
+                )
+            })?;
+    }
+    Ok(storage)
+}
+
+/// Lee un snapshot en el formato previo al versionado (versión 0).
+///
+/// # Errores
+/// Retorna un `io::Error` si hay problemas de lectura o datos corruptos.
+pub fn leer_snapshot_legacy<R: BufRead>(reader: &mut R) -> Result<Storage, io::Error> {
+    let start = leer_u16(reader)?;
+    let end = leer_u16(reader)?;
+
+    let mut hashes_slots = HashMap::new();
+
+    while reader.fill_buf().is_ok() {
+        let mut slot_buf = [0u8; 2];
+        if reader.read_exact(&mut slot_buf).is_err() {
+            break;
+        }
+        let slot = u16::from_be_bytes(slot_buf);
+        let cantidad = leer_u32(reader)?;
+
+        let mut mapa = HashMap::new();
+        for _ in 0..cantidad {
+            let key = leer_bloque(reader)?;
+            let dato_buf = leer_bloque(reader)?;
+            let dato = DatoRedis::from_encrypted_bytes(&dato_buf).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Error en DatoRedis: {e:?}"),
+                )
+            })?;
+            mapa.insert(key, dato);
+        }
+
+        hashes_slots.insert(slot, mapa);
+    }
+
+    Ok(Storage::new_with_content(start..end, hashes_slots))
+}
+
+/// Serializa un único valor en el formato de payload de DUMP.
+///
+/// # Parámetros
+/// - `dato`: Valor almacenado en una clave.
+///
+/// # Retorna
+/// El payload con tag, valor, versión y checksum.
+///
+/// # Errores
+/// Retorna un error de redis si el tipo del valor no es serializable.
+pub fn serializar_dump(dato: &DatoRedis) -> Result<Vec<u8>, DatoRedis> {
+    let (tag, valor) = codificar_valor(dato)
+        .map_err(|e| DatoRedis::new_simple_error("ERR".to_string(), e.to_string()))?;
+
+    let mut payload = Vec::with_capacity(valor.len() + 7);
+    payload.push(tag);
+    payload.extend_from_slice(&valor);
+    payload.extend_from_slice(&SNAPSHOT_VERSION.to_be_bytes());
+    let checksum = crc32(&payload);
+    payload.extend_from_slice(&checksum.to_be_bytes());
+    Ok(payload)
+}
+
+/// Reconstruye un valor a partir de un payload generado por DUMP.
+///
+/// # Parámetros
+/// - `payload`: Bytes generados por `serializar_dump`.
+///
+/// # Retorna
+/// El valor deserializado.
+///
+/// # Errores
+/// Retorna un error de redis si la versión o el checksum no son válidos.
+pub fn deserializar_dump(payload: &[u8]) -> Result<DatoRedis, DatoRedis> {
+    let error_payload = || {
+        DatoRedis::new_simple_error(
+            "ERR".to_string(),
+            "DUMP payload version or checksum are wrong".to_string(),
+        )
+    };
+
+    let contenido = verificar_checksum(payload).map_err(|_| error_payload())?;
+    if contenido.len() < 3 {
+        return Err(error_payload());
+    }
+    let (datos, version) = contenido.split_at(contenido.len() - 2);
+    let version = u16::from_be_bytes([version[0], version[1]]);
+    if version == 0 || version > SNAPSHOT_VERSION {
+        return Err(error_payload());
+    }
+
+    decodificar_valor(datos[0], &datos[1..]).map_err(|_| error_payload())
+}
+
+/// Codifica un valor del storage según su tipo.
+///
+/// # Retorna
+/// Tupla con el tag de tipo y los bytes de la codificación.
+fn codificar_valor(dato: &DatoRedis) -> Result<(u8, Vec<u8>), io::Error> {
+    match dato {
+        DatoRedis::BulkString(bulk_string) => Ok((TAG_STRING, bulk_string.bytes().to_vec())),
+        DatoRedis::Arrays(arrays) => {
+            Ok((TAG_LIST, codificar_elementos(arrays.len(), arrays.iter())?))
+        }
+        DatoRedis::Set(set) => Ok((TAG_SET, codificar_elementos(set.len(), set.iter())?)),
+        DatoRedis::Stream(stream) => Ok((TAG_STREAM, codificar_stream(stream))),
+        DatoRedis::HyperLogLog(hll) => Ok((TAG_HYPERLOGLOG, hll.a_bytes())),
+        DatoRedis::SortedSet(sorted_set) => Ok((TAG_SORTED_SET, codificar_sorted_set(sorted_set))),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Tipo de dato no persistible",
+        )),
+    }
+}
+
+/// Codifica una colección de elementos como cantidad seguida de cada
+/// elemento con su tag y largo.
+fn codificar_elementos<'a>(
+    cantidad: usize,
+    elementos: impl Iterator<Item = &'a DatoRedis>,
+) -> Result<Vec<u8>, io::Error> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&(cantidad as u32).to_be_bytes());
+    for elemento in elementos {
+        let (tag, valor) = codificar_valor(elemento)?;
+        buffer.push(tag);
+        escribir_bloque(&mut buffer, &valor);
+    }
+    Ok(buffer)
+}
+
+/// Codifica las entradas de un stream junto con su último id.
+fn codificar_stream(stream: &Stream) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    escribir_stream_id(&mut buffer, &stream.ultimo_id());
+    buffer.extend_from_slice(&stream.agregadas().to_be_bytes());
+    buffer.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+    for (id, campos) in stream.iter() {
+        escribir_stream_id(&mut buffer, id);
+        buffer.extend_from_slice(&(campos.len() as u32).to_be_bytes());
+        for (campo, valor) in campos {
+            escribir_bloque(&mut buffer, campo);
+            escribir_bloque(&mut buffer, valor);
+        }
+    }
+    buffer.extend_from_slice(&(stream.grupos().len() as u32).to_be_bytes());
+    for (nombre, grupo) in stream.grupos() {
+        escribir_texto(&mut buffer, nombre);
+        escribir_stream_id(&mut buffer, &grupo.ultimo_entregado());
+        buffer.extend_from_slice(&(grupo.consumidores().len() as u32).to_be_bytes());
+        for (consumidor, visto_ms) in grupo.consumidores() {
+            escribir_texto(&mut buffer, consumidor);
+            buffer.extend_from_slice(&visto_ms.to_be_bytes());
+        }
+        buffer.extend_from_slice(&(grupo.pendientes().len() as u32).to_be_bytes());
+        for (id, pendiente) in grupo.pendientes() {
+            escribir_stream_id(&mut buffer, id);
+            escribir_texto(&mut buffer, &pendiente.consumidor);
+            buffer.extend_from_slice(&pendiente.entregado_ms.to_be_bytes());
+            buffer.extend_from_slice(&pendiente.entregas.to_be_bytes());
+        }
+    }
+    buffer
+}
+
+/// Decodifica un stream codificado con `codificar_stream`.
+fn decodificar_stream(bytes: &[u8]) -> Result<DatoRedis, io::Error> {
+    let mut cursor = Cursor::new(bytes);
+    let ultimo_id = leer_stream_id(&mut cursor)?;
+    let agregadas = leer_u64(&mut cursor)?;
+    let cantidad = leer_u32(&mut cursor)?;
+    let mut entradas = BTreeMap::new();
+    for _ in 0..cantidad {
+        let id = leer_stream_id(&mut cursor)?;
+        let cantidad_campos = leer_u32(&mut cursor)?;
+        let mut campos = CamposStream::new();
+        for _ in 0..cantidad_campos {
+            let campo = leer_bloque(&mut cursor)?;
+            let valor = leer_bloque(&mut cursor)?;
+            campos.push((campo, valor));
+        }
+        entradas.insert(id, campos);
+    }
+    let mut grupos = BTreeMap::new();
+    if (cursor.position() as usize) < bytes.len() {
+        for _ in 0..leer_u32(&mut cursor)? {
+            let (nombre, grupo) = decodificar_grupo(&mut cursor)?;
+            grupos.insert(nombre, grupo);
+        }
+    }
+    if cursor.position() as usize != bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Bytes sobrantes en la codificación",
+        ));
+    }
+    Ok(DatoRedis::Stream(Stream::new_con_contenido(
+        entradas, ultimo_id, agregadas, grupos,
+    )))
+}
+
+/// Decodifica un grupo de consumidores de un stream.
+fn decodificar_grupo<R: Read>(reader: &mut R) -> Result<(String, GrupoConsumidores), io::Error> {
+    let nombre = leer_texto(reader)?;
+    let ultimo_entregado = leer_stream_id(reader)?;
+    let mut consumidores = BTreeMap::new();
+    for _ in 0..leer_u32(reader)? {
+        let consumidor = leer_texto(reader)?;
+        consumidores.insert(consumidor, leer_u64(reader)?);
+    }
+    let mut pendientes = BTreeMap::new();
+    for _ in 0..leer_u32(reader)? {
+        let id = leer_stream_id(reader)?;
+        let pendiente = Pendiente {
+            consumidor: leer_texto(reader)?,
+            entregado_ms: leer_u64(reader)?,
+            entregas: leer_u64(reader)?,
+        };
+        pendientes.insert(id, pendiente);
+    }
+    let grupo = GrupoConsumidores::new_con_contenido(ultimo_entregado, pendientes, consumidores);
+    Ok((nombre, grupo))
+}
+
+/// Codifica los miembros de un sorted set junto con sus puntajes.
+fn codificar_sorted_set(sorted_set: &SortedSet) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&(sorted_set.len() as u32).to_be_bytes());
+    for (miembro, puntaje) in sorted_set.iter() {
+        escribir_bloque(&mut buffer, miembro);
+        buffer.extend_from_slice(&puntaje.to_bits().to_be_bytes());
+    }
+    buffer
+}
+
+/// Decodifica un sorted set codificado con `codificar_sorted_set`.
+fn decodificar_sorted_set(bytes: &[u8]) -> Result<DatoRedis, io::Error> {
+    let mut cursor = Cursor::new(bytes);
+    let mut sorted_set = SortedSet::new();
+    for _ in 0..leer_u32(&mut cursor)? {
+        let miembro = leer_bloque(&mut cursor)?;
+        sorted_set.insertar(miembro, f64::from_bits(leer_u64(&mut cursor)?));
+    }
+    if cursor.position() as usize != bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Bytes sobrantes en la codificación",
+        ));
+    }
+    Ok(DatoRedis::SortedSet(sorted_set))
+}
+
+fn escribir_stream_id(buffer: &mut Vec<u8>, id: &StreamId) {
+    buffer.extend_from_slice(&id.ms.to_be_bytes());
+    buffer.extend_from_slice(&id.seq.to_be_bytes());
+}
+
+fn leer_stream_id<R: Read>(reader: &mut R) -> Result<StreamId, io::Error> {
+    Ok(StreamId::new(leer_u64(reader)?, leer_u64(reader)?))
+}
+
+/// Lee un bloque que contiene un string UTF-8.
+fn leer_texto<R: Read>(reader: &mut R) -> Result<String, io::Error> {
+    String::from_utf8(leer_bloque(reader)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "String inválido en snapshot"))
+}
+
+/// Decodifica un valor a partir de su tag de tipo y sus bytes.
+fn decodificar_valor(tag: u8, bytes: &[u8]) -> Result<DatoRedis, io::Error> {
+    match tag {
+        TAG_STRING => DatoRedis::new_bulk_string_desde_bytes(bytes.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "String inválido en snapshot")),
+        TAG_LIST => Ok(DatoRedis::new_array_con_contenido(
+            Arrays::new_con_contenido(decodificar_elementos(bytes)?),
+        )),
+        TAG_SET => {
+            let mut set = Set::new();
+            for elemento in decodificar_elementos(bytes)? {
+                set.insert(elemento);
+            }
+            Ok(DatoRedis::new_set_con_contenido(set))
+        }
+        TAG_STREAM => decodificar_stream(bytes),
+        TAG_HYPERLOGLOG => HyperLogLog::desde_bytes(bytes)
+            .map(DatoRedis::HyperLogLog)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "HyperLogLog inválido en snapshot",
+                )
+            }),
+        TAG_SORTED_SET => decodificar_sorted_set(bytes),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Tag de tipo desconocido: {tag}"),
+        )),
+    }
+}
+
+/// Decodifica los elementos de una lista o set.
+fn decodificar_elementos(bytes: &[u8]) -> Result<Vec<DatoRedis>, io::Error> {
+    let mut cursor = Cursor::new(bytes);
+    let cantidad = leer_u32(&mut cursor)?;
+    let mut elementos = Vec::new();
+    for _ in 0..cantidad {
+        let tag = leer_u8(&mut cursor)?;
+        let valor = leer_bloque(&mut cursor)?;
+        elementos.push(decodificar_valor(tag, &valor)?);
+    }
+    if cursor.position() as usize != bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Bytes sobrantes en la codificación",
+        ));
+    }
+    Ok(elementos)
+}
+
+/// Verifica el CRC32 ubicado en los últimos 4 bytes del buffer.
+///
+/// # Retorna
+/// El contenido sin el checksum.
+fn verificar_checksum(bytes: &[u8]) -> Result<&[u8], io::Error> {
+    if bytes.len() < 4 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Datos truncados",
+        ));
+    }
+    let (contenido, checksum) = bytes.split_at(bytes.len() - 4);
+    let esperado = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    if crc32(contenido) != esperado {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Checksum inválido",
+        ));
+    }
+    Ok(contenido)
+}
+
+/// Escribe un bloque de bytes precedido por su largo (u32).
+fn escribir_bloque(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Escribe un string como bloque con sus bytes UTF-8.
+fn escribir_texto(buffer: &mut Vec<u8>, texto: &str) {
+    escribir_bloque(buffer, texto.as_bytes());
+}
+
+/// Lee un bloque de bytes precedido por su largo (u32).
+fn leer_bloque<R: Read>(reader: &mut R) -> Result<Vec<u8>, io::Error> {
+    let largo = leer_u32(reader)? as usize;
+    let mut buffer = Vec::new();
+    reader.take(largo as u64).read_to_end(&mut buffer)?;
+    if buffer.len() != largo {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Bloque truncado",
+        ));
+    }
+    Ok(buffer)
+}
+
+fn leer_u8<R: Read>(reader: &mut R) -> Result<u8, io::Error> {
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn leer_u16<R: Read>(reader: &mut R) -> Result<u16, io::Error> {
+    let mut buffer = [0u8; 2];
+    reader.read_exact(&mut buffer)?;
+    Ok(u16::from_be_bytes(buffer))
+}
+
+fn leer_u32<R: Read>(reader: &mut R) -> Result<u32, io::Error> {
+    let mut buffer = [0u8; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_be_bytes(buffer))
+}
+
+fn leer_u64<R: Read>(reader: &mut R) -> Result<u64, io::Error> {
+    let mut buffer = [0u8; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_be_bytes(buffer))
+}
+
+fn error_encriptado(e: DatoRedis) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    const RANGE: Range<u16> = Range {
+        start: 0,
+        end: 16378,
+    };
+
+    fn storage_de_prueba() -> Storage {
+        let mut storage = Storage::new(RANGE);
+        storage
+            .set(
+                "texto",
+                DatoRedis::new_bulk_string("valor con espacios".to_string()).unwrap(),
+            )
+            .unwrap();
+
+        let mut lista = Arrays::new();
+        lista.append(DatoRedis::new_bulk_string("a".to_string()).unwrap());
+        lista.append(DatoRedis::new_bulk_string("b".to_string()).unwrap());
+        storage
+            .set("lista", DatoRedis::new_array_con_contenido(lista))
+            .unwrap();
+
+        let mut set = Set::new();
+        set.insert(DatoRedis::new_bulk_string("x".to_string()).unwrap());
+        set.insert(DatoRedis::new_bulk_string("y".to_string()).unwrap());
+        storage
+            .set("set", DatoRedis::new_set_con_contenido(set))
+            .unwrap();
+        storage
+    }
+
+    /// Genera un snapshot en el formato legacy (sin magic ni versión)
+    fn snapshot_legacy(storage: &Storage) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let range = storage.get_slot_range();
+        buffer.extend_from_slice(&range.start.to_be_bytes());
+        buffer.extend_from_slice(&range.end.to_be_bytes());
+        for (slot, mapa) in storage.iter() {
+            buffer.extend_from_slice(&slot.to_be_bytes());
+            buffer.extend_from_slice(&(mapa.len() as u32).to_be_bytes());
+            for (key, dato) in mapa {
+                escribir_bloque(&mut buffer, key);
+                escribir_bloque(&mut buffer, &dato.to_bytes());
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_01_snapshot_roundtrip() {
+        let storage = storage_de_prueba();
+        let bytes = serializar_snapshot(&storage).unwrap();
+
+        assert!(bytes.starts_with(SNAPSHOT_MAGIC));
+        assert_eq!(deserializar_snapshot(&bytes).unwrap(), storage);
+    }
+
+    #[test]
+    fn test_02_snapshot_vacio() {
+        let storage = Storage::new(RANGE);
+        let bytes = serializar_snapshot(&storage).unwrap();
+        assert_eq!(deserializar_snapshot(&bytes).unwrap(), storage);
+    }
+
+    #[test]
+    fn test_03_snapshot_corrupto_falla_checksum() {
+        let storage = storage_de_prueba();
+        let mut bytes = serializar_snapshot(&storage).unwrap();
+        let medio = bytes.len() / 2;
+        bytes[medio] ^= 0xFF;
+
+        let error = deserializar_snapshot(&bytes).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_04_snapshot_version_futura_rechazada() {
+        let storage = Storage::new(RANGE);
+        let mut bytes = serializar_snapshot(&storage).unwrap();
+        let version_futura = (SNAPSHOT_VERSION + 1).to_be_bytes();
+        bytes[8] = version_futura[0];
+        bytes[9] = version_futura[1];
+
+        assert!(deserializar_snapshot(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_05_snapshot_legacy_se_actualiza() {
+        let storage = storage_de_prueba();
+        let legacy = snapshot_legacy(&storage);
+
+        let restaurado = deserializar_snapshot(&legacy).unwrap();
+        assert_eq!(restaurado, storage);
+
+        let actualizado = serializar_snapshot(&restaurado).unwrap();
+        assert!(actualizado.starts_with(SNAPSHOT_MAGIC));
+        assert_eq!(deserializar_snapshot(&actualizado).unwrap(), storage);
+    }
+
+    #[test]
+    fn test_06_dump_y_restore_de_cada_tipo() {
+        let storage = storage_de_prueba();
+        for (_, mapa) in storage.iter() {
+            for dato in mapa.values() {
+                let payload = serializar_dump(dato).unwrap();
+                assert_eq!(&deserializar_dump(&payload).unwrap(), dato);
+            }
+        }
+    }
+
+    #[test]
+    fn test_07_dump_payload_corrupto() {
+        let dato = DatoRedis::new_bulk_string("hola".to_string()).unwrap();
+        let mut payload = serializar_dump(&dato).unwrap();
+        payload[1] ^= 0x01;
+        assert!(deserializar_dump(&payload).is_err());
+        assert!(deserializar_dump(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_08_snapshot_conserva_expiraciones() {
+        let mut storage = storage_de_prueba();
+        let expira_en = ahora_ms() + 60_000;
+        storage.set_expiracion("texto", Some(expira_en)).unwrap();
+
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        assert_eq!(restaurado, storage);
+        assert_eq!(restaurado.expiracion("texto"), Some(expira_en));
+        assert_eq!(restaurado.expiracion("lista"), None);
+    }
+
+    #[test]
+    fn test_09_snapshot_omite_claves_expiradas() {
+        let mut storage = storage_de_prueba();
+        storage
+            .set_expiracion("texto", Some(ahora_ms() - 1))
+            .unwrap();
+
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        assert!(
+            restaurado
+                .iter()
+                .all(|(_, mapa)| !mapa.contains_key(b"texto".as_slice()))
+        );
+        assert!(restaurado.get("texto").is_err());
+    }
+
+    #[test]
+    fn test_10_stream_se_restaura_con_su_ultimo_id() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            let campos = vec![(b"campo".to_vec(), format!("valor {ms}").into_bytes())];
+            stream.agregar(StreamId::new(ms, 0), campos).unwrap();
+        }
+        stream.eliminar(&StreamId::new(3, 0));
+        stream.crear_grupo("grupo", StreamId::MIN);
+        let grupo = stream.grupo_mutable("grupo").unwrap();
+        grupo.registrar_consumidor("consumidor", 100);
+        grupo.entregar(StreamId::new(1, 0), "consumidor", 100, true);
+        let dato = DatoRedis::Stream(stream);
+
+        let payload = serializar_dump(&dato).unwrap();
+        let restaurado = deserializar_dump(&payload).unwrap();
+        assert_eq!(restaurado, dato);
+        let DatoRedis::Stream(restaurado) = restaurado else {
+            panic!("se esperaba un stream");
+        };
+        assert_eq!(restaurado.ultimo_id(), StreamId::new(3, 0));
+        assert_eq!(restaurado.len(), 2);
+    }
+
+    #[test]
+    fn test_11_string_binario_se_conserva() {
+        let dato = DatoRedis::new_bulk_string_desde_bytes(vec![0x00, 0xFF, 0x80, b'a']).unwrap();
+        let payload = serializar_dump(&dato).unwrap();
+        assert_eq!(deserializar_dump(&payload).unwrap(), dato);
+
+        let mut storage = Storage::new(0..16378);
+        storage.set("bitmap", dato.clone()).unwrap();
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        assert_eq!(restaurado.get("bitmap").unwrap(), dato);
+    }
+
+    #[test]
+    fn test_12_hyperloglog_conserva_su_codificacion() {
+        let mut storage = Storage::new(0..16378);
+        for (clave, cantidad) in [("disperso", 10), ("denso", 5_000)] {
+            let mut hll = HyperLogLog::new();
+            for i in 0..cantidad {
+                hll.agregar(format!("usuario:{i}").as_bytes());
+            }
+            storage.set(clave, DatoRedis::HyperLogLog(hll)).unwrap();
+        }
+
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        for (clave, denso) in [("disperso", false), ("denso", true)] {
+            let dato = storage.get(clave).unwrap();
+            assert_eq!(restaurado.get(clave).unwrap(), dato);
+            let DatoRedis::HyperLogLog(hll) = &dato else {
+                panic!("se esperaba un HyperLogLog");
+            };
+            assert_eq!(hll.es_denso(), denso);
+            assert_eq!(
+                deserializar_dump(&serializar_dump(&dato).unwrap()).unwrap(),
+                dato
+            );
+        }
+    }
+
+    #[test]
+    fn test_13_sorted_set_se_restaura() {
+        let mut sorted_set = SortedSet::new();
+        sorted_set.insertar(b"Palermo".to_vec(), 3479099956230698.0);
+        sorted_set.insertar(b"Catania".to_vec(), 3479447370796909.0);
+        let dato = DatoRedis::SortedSet(sorted_set);
+        let payload = serializar_dump(&dato).unwrap();
+        assert_eq!(deserializar_dump(&payload).unwrap(), dato);
+
+        let mut storage = Storage::new(0..16378);
+        storage.set("sicilia", dato.clone()).unwrap();
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        assert_eq!(restaurado.get("sicilia").unwrap(), dato);
+    }
+
+    #[test]
+    fn test_14_funciones_se_restauran() {
+        let codigo = "#!forth name=documentos version=1.0\n: nuevo_id call\" INCR KEYS[1]\" ;";
+        let mut storage = storage_de_prueba();
+        storage.funciones_mut().cargar(codigo, false).unwrap();
+
+        let restaurado = deserializar_snapshot(&serializar_snapshot(&storage).unwrap()).unwrap();
+        assert_eq!(restaurado, storage);
+        assert_eq!(restaurado.funciones(), storage.funciones());
+        let biblioteca = restaurado.funciones().bibliotecas().next().unwrap();
+        assert_eq!(biblioteca.codigo(), codigo.as_bytes());
+        assert_eq!(biblioteca.version(), Some("1.0"));
+    }
+}
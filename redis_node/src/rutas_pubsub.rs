@@ -0,0 +1,254 @@
+//! Este módulo contiene las rutas de pub/sub del clúster: el resumen de
+//! las suscripciones del nodo que se comparte con el resto del clúster y
+//! los resúmenes recibidos de los otros nodos.
+//!
+//! Cada PUBLISH se reenvía solo a los nodos cuyo resumen indica que pueden
+//! tener suscriptores del canal (o de un patrón que lo incluya), y cada
+//! SPUBLISH solo a los nodos que atienden el slot del canal. Un nodo del
+//! que todavía no se recibió un resumen recibe todas las publicaciones.
+//!
+//! El resumen se envía apenas cambia por un SUBSCRIBE o UNSUBSCRIBE, y se
+//! reenvía periódicamente para recuperar resúmenes perdidos.
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use common::cr16::crc16;
+use redis_client::protocol::token::Token;
+
+use crate::comandos::const_cmd::CMD_SPUBLISH;
+use crate::constantes::TOTAL_SLOTS;
+use crate::internal_protocol::resumen_pubsub::ResumenPubSub;
+use crate::node_id::NodeId;
+
+/// Intervalo con el que se reenvía el resumen aunque no haya cambiado
+pub const REFRESCO_RESUMEN: Duration = Duration::from_secs(10);
+
+/// Resumen local del nodo y estado de su envío al clúster
+#[derive(Debug, Default)]
+struct ResumenLocal {
+    resumen: ResumenPubSub,
+    /// El resumen cambió desde el último envío
+    pendiente: bool,
+    ultimo_envio: Option<Instant>,
+}
+
+/// Rutas de pub/sub del clúster, compartidas entre el broker de pub/sub,
+/// que actualiza el resumen local, y el hilo del clúster, que lo envía y
+/// decide a qué nodos reenviar cada publicación
+#[derive(Debug, Default)]
+pub struct RutasPubSub {
+    local: Mutex<ResumenLocal>,
+    remotos: RwLock<HashMap<NodeId, ResumenPubSub>>,
+    enviados: AtomicU64,
+    omitidos: AtomicU64,
+}
+
+impl RutasPubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reemplaza el resumen de las suscripciones del nodo
+    ///
+    /// # Retorna
+    /// - Verdadero si el resumen cambió y debe enviarse al clúster
+    pub fn actualizar_local(&self, resumen: ResumenPubSub) -> bool {
+        let Ok(mut local) = self.local.lock() else {
+            return false;
+        };
+        if local.resumen == resumen {
+            return false;
+        }
+        local.resumen = resumen;
+        local.pendiente = true;
+        true
+    }
+
+    /// Devuelve el resumen local si cambió desde el último envío, si nunca
+    /// se envió o si pasó `REFRESCO_RESUMEN` desde el último envío, y lo
+    /// marca como enviado
+    pub fn resumen_a_enviar(&self) -> Option<ResumenPubSub> {
+        let mut local = self.local.lock().ok()?;
+        let vencido = local
+            .ultimo_envio
+            .is_none_or(|envio| envio.elapsed() >= REFRESCO_RESUMEN);
+        if !local.pendiente && !vencido {
+            return None;
+        }
+        local.pendiente = false;
+        local.ultimo_envio = Some(Instant::now());
+        Some(local.resumen.clone())
+    }
+
+    /// Registra el resumen recibido de otro nodo
+    pub fn registrar_remoto(&self, id: NodeId, resumen: ResumenPubSub) {
+        if let Ok(mut remotos) = self.remotos.write() {
+            remotos.insert(id, resumen);
+        }
+    }
+
+    /// Selecciona los nodos a los que reenviar una publicación
+    ///
+    /// # Parámetros
+    /// * `tokens`: comando PUBLISH o SPUBLISH
+    /// * `candidatos`: nodos conectados, con su rango de slots si se conoce
+    ///
+    /// # Retorna
+    /// - Nodos que pueden tener suscriptores de la publicación
+    pub fn destinos(
+        &self,
+        tokens: &[Token],
+        candidatos: Vec<(NodeId, Option<Range<u16>>)>,
+    ) -> Vec<NodeId> {
+        let total = candidatos.len() as u64;
+        let destinos: Vec<NodeId> = match tokens.get(1) {
+            Some(canal) if tokens[0].eq_ignore_ascii_case(CMD_SPUBLISH) => {
+                let slot = crc16(canal) % TOTAL_SLOTS;
+                candidatos
+                    .into_iter()
+                    .filter(|(_, slots)| slots.as_ref().is_none_or(|s| s.contains(&slot)))
+                    .map(|(id, _)| id)
+                    .collect()
+            }
+            Some(canal) => match self.remotos.read() {
+                Ok(remotos) => candidatos
+                    .into_iter()
+                    .filter(|(id, _)| {
+                        remotos
+                            .get(id)
+                            .is_none_or(|resumen| resumen.puede_tener_suscriptores(canal))
+                    })
+                    .map(|(id, _)| id)
+                    .collect(),
+                Err(_) => candidatos.into_iter().map(|(id, _)| id).collect(),
+            },
+            None => candidatos.into_iter().map(|(id, _)| id).collect(),
+        };
+        self.enviados
+            .fetch_add(destinos.len() as u64, Ordering::Relaxed);
+        self.omitidos
+            .fetch_add(total - destinos.len() as u64, Ordering::Relaxed);
+        destinos
+    }
+
+    /// Cantidad de publicaciones reenviadas a otros nodos
+    pub fn enviados(&self) -> u64 {
+        self.enviados.load(Ordering::Relaxed)
+    }
+
+    /// Cantidad de reenvíos evitados por no tener suscriptores el destino
+    pub fn omitidos(&self) -> u64 {
+        self.omitidos.load(Ordering::Relaxed)
+    }
+
+    /// Reinicia los contadores de reenvíos (CONFIG RESETSTAT)
+    pub fn reiniciar(&self) {
+        self.enviados.store(0, Ordering::Relaxed);
+        self.omitidos.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(comando: &[&str]) -> Vec<Token> {
+        comando.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    fn canales(nombres: &[&str]) -> Vec<Token> {
+        nombres.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test01_publish_solo_a_nodos_con_suscriptores() {
+        let rutas = RutasPubSub::new();
+        let (a, b, c) = (NodeId::new(), NodeId::new(), NodeId::new());
+        let suscriptos = canales(&["noticias"]);
+        let patrones = canales(&["doc:*"]);
+        rutas.registrar_remoto(a.clone(), ResumenPubSub::new(suscriptos.iter(), [].iter()));
+        rutas.registrar_remoto(b.clone(), ResumenPubSub::new([].iter(), patrones.iter()));
+        let candidatos = vec![(a.clone(), None), (b.clone(), None), (c.clone(), None)];
+
+        // c no envió su resumen, por lo que recibe todas las publicaciones
+        let destinos = rutas.destinos(&tokens(&["PUBLISH", "noticias", "m"]), candidatos.clone());
+        assert_eq!(destinos, vec![a.clone(), c.clone()]);
+        let destinos = rutas.destinos(&tokens(&["publish", "doc:1", "m"]), candidatos.clone());
+        assert_eq!(destinos, vec![b.clone(), c.clone()]);
+        assert_eq!(rutas.enviados(), 4);
+        assert_eq!(rutas.omitidos(), 2);
+
+        rutas.reiniciar();
+        assert_eq!(rutas.enviados(), 0);
+    }
+
+    #[test]
+    fn test02_spublish_solo_a_nodos_del_slot() {
+        let rutas = RutasPubSub::new();
+        let (a, b) = (NodeId::new(), NodeId::new());
+        let slot = crc16(b"canal") % TOTAL_SLOTS;
+        let candidatos = vec![
+            (a.clone(), Some(slot..slot + 1)),
+            (b.clone(), Some(slot + 1..TOTAL_SLOTS)),
+        ];
+        let destinos = rutas.destinos(&tokens(&["SPUBLISH", "canal", "m"]), candidatos);
+        assert_eq!(destinos, vec![a]);
+    }
+
+    #[test]
+    fn test03_resumen_se_envia_solo_al_cambiar() {
+        let rutas = RutasPubSub::new();
+        // El resumen inicial se envía una vez aunque no haya suscripciones
+        assert_eq!(rutas.resumen_a_enviar(), Some(ResumenPubSub::default()));
+        assert_eq!(rutas.resumen_a_enviar(), None);
+
+        let suscriptos = canales(&["noticias"]);
+        let resumen = ResumenPubSub::new(suscriptos.iter(), [].iter());
+        assert!(rutas.actualizar_local(resumen.clone()));
+        assert!(!rutas.actualizar_local(resumen.clone()));
+        assert_eq!(rutas.resumen_a_enviar(), Some(resumen));
+        assert_eq!(rutas.resumen_a_enviar(), None);
+    }
+
+    #[test]
+    fn test04_reduce_el_trafico_en_un_cluster_de_9_nodos() {
+        // Cada nodo tiene suscriptores de 50 de 1000 canales, y se publica
+        // en todos los canales desde todos los nodos
+        let ids: Vec<NodeId> = (0..9).map(|_| NodeId::new()).collect();
+        let rutas: Vec<RutasPubSub> = ids.iter().map(|_| RutasPubSub::new()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let suscriptos: Vec<Token> = (0..50)
+                .map(|c| Token::from(format!("canal:{}", i * 50 + c)))
+                .collect();
+            let resumen = ResumenPubSub::new(suscriptos.iter(), [].iter());
+            for (j, rutas_nodo) in rutas.iter().enumerate() {
+                if i != j {
+                    rutas_nodo.registrar_remoto(id.clone(), resumen.clone());
+                }
+            }
+        }
+
+        let mut difusion = 0;
+        for (i, rutas_nodo) in rutas.iter().enumerate() {
+            let candidatos: Vec<(NodeId, Option<Range<u16>>)> = ids
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, id)| (id.clone(), None))
+                .collect();
+            for canal in 0..1000 {
+                let comando = tokens(&["PUBLISH", &format!("canal:{canal}"), "m"]);
+                difusion += candidatos.len() as u64;
+                rutas_nodo.destinos(&comando, candidatos.clone());
+            }
+        }
+        let enviados: u64 = rutas.iter().map(RutasPubSub::enviados).sum();
+        // Sin falsos negativos, cada canal con suscriptores se reenvía a su
+        // nodo desde los otros 8
+        assert!(enviados >= 450 * 8);
+        assert!(enviados * 10 < difusion, "{enviados} de {difusion}");
+    }
+}
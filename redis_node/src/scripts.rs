@@ -0,0 +1,122 @@
+//! Este módulo contiene la cache de scripts del nodo, que permite ejecutar
+//! con EVALSHA un script cargado previamente con SCRIPT LOAD o EVAL
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use common::sha1::sha1;
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::const_cmd::{CMD_EVAL, CMD_EVALSHA};
+
+/// Scripts cargados en el nodo, indexados por el SHA1 de su contenido en
+/// hexadecimal
+#[derive(Debug, Default)]
+pub struct CacheScripts {
+    scripts: RwLock<HashMap<String, Token>>,
+}
+
+impl CacheScripts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Guarda un script en la cache
+    ///
+    /// # Retorna
+    /// - SHA1 del script, con el que se lo ejecuta mediante EVALSHA
+    pub fn cargar(&self, script: &[u8]) -> String {
+        let sha = sha_de_script(script);
+        if let Ok(mut scripts) = self.scripts.write() {
+            scripts.insert(sha.clone(), Token::from(script));
+        }
+        sha
+    }
+
+    /// Determina si hay un script cargado con el SHA1 indicado
+    pub fn existe(&self, sha: &str) -> bool {
+        self.scripts
+            .read()
+            .map(|scripts| scripts.contains_key(&sha.to_lowercase()))
+            .unwrap_or(false)
+    }
+
+    /// Elimina todos los scripts de la cache
+    pub fn vaciar(&self) {
+        if let Ok(mut scripts) = self.scripts.write() {
+            scripts.clear();
+        }
+    }
+
+    /// Obtiene los tokens de EVAL equivalentes a un EVAL o EVALSHA. Los
+    /// scripts de EVAL quedan cacheados.
+    ///
+    /// # Parámetros
+    /// * `tokens`: tokens del comando EVAL o EVALSHA
+    ///
+    /// # Retorna
+    /// - Tokens del EVAL a ejecutar, error NOSCRIPT si el SHA1 no
+    ///   corresponde a ningún script cargado
+    pub fn como_eval(&self, tokens: &[Token]) -> Result<Vec<Token>, DatoRedis> {
+        let Some(referencia) = tokens.get(1) else {
+            return Ok(tokens.to_vec());
+        };
+        if !tokens[0].eq_ignore_ascii_case(CMD_EVALSHA) {
+            self.cargar(referencia);
+            return Ok(tokens.to_vec());
+        }
+        let script = self
+            .scripts
+            .read()
+            .ok()
+            .and_then(|scripts| scripts.get(&referencia.to_lowercase()).cloned())
+            .ok_or_else(|| {
+                DatoRedis::new_simple_error(
+                    "NOSCRIPT".to_string(),
+                    "No matching script. Please use EVAL.".to_string(),
+                )
+            })?;
+        let mut eval = vec![Token::from(CMD_EVAL), script];
+        eval.extend_from_slice(&tokens[2..]);
+        Ok(eval)
+    }
+}
+
+/// Calcula el SHA1 de un script en hexadecimal
+pub fn sha_de_script(script: &[u8]) -> String {
+    hex::encode(sha1(script))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(vector: &[&str]) -> Vec<Token> {
+        vector.iter().map(|s| Token::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_01_evalsha_se_traduce_a_eval() {
+        let cache = CacheScripts::new();
+        let sha = cache.cargar(b"1 2 +");
+        assert_eq!(sha.len(), 40);
+        assert!(cache.existe(&sha.to_uppercase()));
+
+        let eval = cache
+            .como_eval(&tokens(&["evalsha", &sha, "1", "clave", "arg"]))
+            .unwrap();
+        assert_eq!(eval, tokens(&["EVAL", "1 2 +", "1", "clave", "arg"]));
+
+        cache.vaciar();
+        assert!(!cache.existe(&sha));
+        assert!(cache.como_eval(&tokens(&["evalsha", &sha, "0"])).is_err());
+    }
+
+    #[test]
+    fn test_02_eval_cachea_el_script() {
+        let cache = CacheScripts::new();
+        let eval = tokens(&["eval", "3 4 *", "0"]);
+        assert_eq!(cache.como_eval(&eval).unwrap(), eval);
+        assert!(cache.existe(&sha_de_script(b"3 4 *")));
+    }
+}
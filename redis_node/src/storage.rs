@@ -3,17 +3,54 @@ use std::collections::HashMap;
 use std::ops::Range;
 
 use crate::constantes::TOTAL_SLOTS;
+use crate::funciones::Funciones;
+use crate::memoria::{
+    ClavesMuestreables, EXPIRACION_ACTIVA_MUESTRAS, MAXMEMORY_SAMPLES, MetadataClave,
+    PoliticaEviction, VariacionMemoria, ahora_ms, estimar_memoria,
+};
+use crate::notificaciones::{ClaseEvento, EVENTO_DEL, EVENTO_EVICTED, EVENTO_EXPIRED, EventoClave};
+use crate::tracking::Modificaciones;
 use common::cr16::crc16;
 use redis_client::tipos_datos::traits::DatoRedis;
 
 /// Estructura que representa el almacenamiento interno del sistema.
 ///
 /// Cada `Storage` contiene un rango de slots que define qué y cuantas claves puede manejar.
-/// Cada slot mantiene un hashmap de claves (String) y valores `DatoRedis`.
-#[derive(Debug, PartialEq)]
+/// Cada slot mantiene un hashmap de claves (bytes) y valores `DatoRedis`.
+///
+/// Además lleva, por cada clave, los metadatos necesarios para estimar la
+/// memoria utilizada y aplicar la política de desalojo al superar `maxmemory`.
+/// Las claves, y aparte las que tienen expiración, se indexan para poder
+/// muestrearlas sin recorrer todo el keyspace.
+///
+/// Si las notificaciones de keyspace están habilitadas, registra las
+/// claves eliminadas, expiradas y desalojadas para que el nodo las publique.
+///
+/// Si algún cliente tiene CLIENT TRACKING activo, registra las claves
+/// modificadas para que el nodo invalide las que cachean sus clientes.
+#[derive(Debug)]
 pub struct Storage {
     slot_range: Range<u16>,
-    hashes_slots: HashMap<u16, HashMap<String, DatoRedis>>,
+    hashes_slots: HashMap<u16, HashMap<Vec<u8>, DatoRedis>>,
+    metadata: HashMap<Vec<u8>, MetadataClave>,
+    claves: ClavesMuestreables,
+    claves_volatiles: ClavesMuestreables,
+    memoria_usada: usize,
+    maxmemory: usize,
+    politica: PoliticaEviction,
+    clave_modificada: Option<Vec<u8>>,
+    claves_desalojadas: u64,
+    registrar_eventos: bool,
+    eventos: Vec<EventoClave>,
+    registrar_modificaciones: bool,
+    modificaciones: Modificaciones,
+    funciones: Funciones,
+}
+
+impl PartialEq for Storage {
+    fn eq(&self, other: &Self) -> bool {
+        self.slot_range == other.slot_range && self.hashes_slots == other.hashes_slots
+    }
 }
 
 impl Storage {
@@ -25,27 +62,47 @@ impl Storage {
     /// # Retorna
     /// Una instancia vacía de `Storage`.
     pub fn new(slot_range: Range<u16>) -> Self {
-        Storage {
-            slot_range,
-            hashes_slots: HashMap::new(),
-        }
+        Self::new_with_content(slot_range, HashMap::new())
     }
 
     /// Crea un `Storage` con datos iniciales predefinidos.
     ///
     /// # Parámetros
     /// - `slot_range`: Rango de slots válido.
-    /// - `hashes_slots`: Mapa que asocia slots a sus datos (`HashMap<String, DatoRedis>`).
+    /// - `hashes_slots`: Mapa que asocia slots a sus datos (`HashMap<Vec<u8>, DatoRedis>`).
     ///
     /// # Retorna
     /// Un `Storage` inicializado con contenido.
     pub fn new_with_content(
         slot_range: Range<u16>,
-        hashes_slots: HashMap<u16, HashMap<String, DatoRedis>>,
+        hashes_slots: HashMap<u16, HashMap<Vec<u8>, DatoRedis>>,
     ) -> Self {
+        let mut metadata = HashMap::new();
+        let mut claves = ClavesMuestreables::default();
+        let mut memoria_usada = 0;
+        for (key, dato) in hashes_slots.values().flatten() {
+            let memoria = estimar_memoria(key, dato);
+            memoria_usada += memoria;
+            metadata.insert(key.to_vec(), MetadataClave::new(memoria));
+            claves.agregar(key);
+        }
+
         Storage {
             slot_range,
             hashes_slots,
+            metadata,
+            claves,
+            claves_volatiles: ClavesMuestreables::default(),
+            memoria_usada,
+            maxmemory: 0,
+            politica: PoliticaEviction::default(),
+            clave_modificada: None,
+            claves_desalojadas: 0,
+            registrar_eventos: false,
+            eventos: Vec::new(),
+            registrar_modificaciones: false,
+            modificaciones: Modificaciones::default(),
+            funciones: Funciones::default(),
         }
     }
 
@@ -56,38 +113,58 @@ impl Storage {
     ///
     /// # Retorna
     /// - `Ok(DatoRedis)`: Si la clave existe y está en el rango del slot.
-    /// - `Err(DatoRedis::Null)`: Si no se encuentra.
-    pub fn get(&self, key: String) -> Result<DatoRedis, DatoRedis> {
-        let slot = self.calculate_slot_for_key(&key);
+    /// - `Err(DatoRedis::Null)`: Si no se encuentra o expiró.
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<DatoRedis, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
         self.verificar_key_in_range(slot)?;
+        let metadata = self.metadata_vigente(key)?;
         let slot_hash = self.hashes_slots.get(&slot).ok_or(DatoRedis::new_null())?;
-        let value = slot_hash.get(&key).ok_or(DatoRedis::new_null())?;
+        let value = slot_hash.get(key).ok_or(DatoRedis::new_null())?;
+        metadata.registrar_acceso();
         Ok(value.clone())
     }
 
     /// Obtiene una referencia mutable al valor de una clave.
     ///
+    /// La memoria de la clave se recalcula en la siguiente operación sobre
+    /// el storage, una vez que el valor fue modificado, salvo que quien lo
+    /// modifique informe la variación con `registrar_variacion`.
+    ///
     /// # Parámetros
     /// - `key`: Clave a consultar.
     ///
     /// # Retorna
     /// - `Ok(&mut DatoRedis)`: Si la clave existe.
     /// - `Err(DatoRedis::Null)`: Si la clave no se encuentra o está fuera del rango.
-    pub fn get_mutable(&mut self, key: String) -> Result<&mut DatoRedis, DatoRedis> {
-        let slot = self.calculate_slot_for_key(&key);
+    pub fn get_mutable(&mut self, key: impl AsRef<[u8]>) -> Result<&mut DatoRedis, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
         self.verificar_key_in_range(slot)?;
+        self.sincronizar_memoria();
+        self.expirar_si_corresponde(key);
+
+        if let Some(metadata) = self.metadata.get(key) {
+            metadata.registrar_acceso();
+        }
+        self.clave_modificada = Some(key.to_vec());
 
         let slot_hash = self
             .hashes_slots
             .get_mut(&slot)
             .ok_or(DatoRedis::new_null())?;
 
-        let value = slot_hash.get_mut(&key).ok_or(DatoRedis::new_null())?;
+        let value = slot_hash.get_mut(key).ok_or(DatoRedis::new_null())?;
+        if self.registrar_modificaciones {
+            self.modificaciones.agregar(key);
+        }
         Ok(value)
     }
 
     /// Inserta o actualiza una clave con un valor.
     ///
+    /// Si la clave ya existía conserva su expiración.
+    ///
     /// # Parámetros
     /// - `key`: Clave a insertar.
     /// - `value`: Valor a asociar.
@@ -95,11 +172,26 @@ impl Storage {
     /// # Retorna
     /// - `Ok(())`: Si se guardó correctamente.
     /// - `Err`: Si la clave no pertenece al rango del `Storage`.
-    pub fn set(&mut self, key: String, value: DatoRedis) -> Result<(), DatoRedis> {
-        let slot = self.calculate_slot_for_key(&key);
+    pub fn set(&mut self, key: impl AsRef<[u8]>, value: DatoRedis) -> Result<(), DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
         self.verificar_key_in_range(slot)?;
+        self.sincronizar_memoria();
+        self.expirar_si_corresponde(key);
+
+        let memoria = estimar_memoria(key, &value);
+        let metadata = self
+            .metadata
+            .entry(key.to_vec())
+            .or_insert_with(|| MetadataClave::new(0));
+        self.memoria_usada = self.memoria_usada - metadata.memoria() + memoria;
+        metadata.set_memoria(memoria);
+        metadata.registrar_acceso();
+        self.claves.agregar(key);
+
         let slot_to_save = self.hashes_slots.entry(slot).or_default();
-        slot_to_save.insert(key.to_string(), value);
+        slot_to_save.insert(key.to_vec(), value);
+        self.registrar_modificacion(key);
         Ok(())
     }
 
@@ -111,19 +203,558 @@ impl Storage {
     /// # Retorna
     /// - `Ok(DatoRedis)`: Valor eliminado.
     /// - `Err(DatoRedis::Integer(0))`: Si no existía.
-    pub fn remove(&mut self, key: String) -> Result<DatoRedis, DatoRedis> {
-        let slot = self.calculate_slot_for_key(&key);
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Result<DatoRedis, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
         self.verificar_key_in_range(slot)?;
+        self.sincronizar_memoria();
+        self.expirar_si_corresponde(key);
 
-        let slot_hash = self
-            .hashes_slots
-            .get_mut(&slot)
+        let eliminado = self
+            .eliminar_clave(slot, key)
             .ok_or(DatoRedis::new_integer(0))?;
+        self.registrar_evento(ClaseEvento::Generico, EVENTO_DEL, key);
+        Ok(eliminado)
+    }
+
+    /// Asigna o quita la expiración de una clave existente.
+    ///
+    /// # Parámetros
+    /// - `key`: Clave a modificar.
+    /// - `expira_en`: Instante de expiración en milisegundos desde UNIX epoch,
+    ///   o `None` para que la clave sea persistente.
+    ///
+    /// # Retorna
+    /// - `Ok(())`: Si la clave existe.
+    /// - `Err(DatoRedis::Null)`: Si la clave no existe.
+    pub fn set_expiracion(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        expira_en: Option<u64>,
+    ) -> Result<(), DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
+        self.verificar_key_in_range(slot)?;
+        self.expirar_si_corresponde(key);
+
+        if !self.metadata.contains_key(key) {
+            return Err(DatoRedis::new_null());
+        }
+        self.actualizar_expiracion(key, expira_en);
+        self.registrar_modificacion(key);
+        Ok(())
+    }
+
+    /// Actualiza la memoria de una clave obtenida con `get_mutable` con la
+    /// variación calculada al modificarla, en lugar de volver a estimar el
+    /// valor completo en la siguiente operación.
+    ///
+    /// # Parámetros
+    /// - `key`: Clave modificada.
+    /// - `variacion`: Memoria agregada y liberada por la modificación.
+    pub fn registrar_variacion(&mut self, key: impl AsRef<[u8]>, variacion: VariacionMemoria) {
+        let key = key.as_ref();
+        if self.clave_modificada.as_deref() == Some(key) {
+            self.clave_modificada = None;
+        }
+        if let Some(metadata) = self.metadata.get_mut(key) {
+            let memoria = variacion.aplicar(metadata.memoria());
+            self.memoria_usada = self.memoria_usada - metadata.memoria() + memoria;
+            metadata.set_memoria(memoria);
+        }
+    }
+
+    /// Obtiene el instante de expiración de una clave, aunque ya haya
+    /// expirado, sin registrar un acceso.
+    ///
+    /// # Retorna
+    /// El instante en milisegundos desde UNIX epoch, o `None` si la clave
+    /// no existe o es persistente.
+    pub fn expiracion(&self, key: impl AsRef<[u8]>) -> Option<u64> {
+        let key = key.as_ref();
+        self.metadata.get(key).and_then(MetadataClave::expira_en)
+    }
+
+    /// Renombra una clave conservando su valor y su expiración. Si el
+    /// destino existía, su valor y expiración se descartan.
+    ///
+    /// # Parámetros
+    /// - `origen`: Clave a renombrar.
+    /// - `destino`: Nuevo nombre de la clave.
+    ///
+    /// # Retorna
+    /// - `Ok(())`: Si la clave origen existe.
+    /// - `Err(DatoRedis::Null)`: Si la clave origen no existe.
+    pub fn renombrar(
+        &mut self,
+        origen: impl AsRef<[u8]>,
+        destino: impl AsRef<[u8]>,
+    ) -> Result<(), DatoRedis> {
+        let (origen, destino) = (origen.as_ref(), destino.as_ref());
+        self.verificar_key_in_range(self.calculate_slot_for_key(destino))?;
+        let expira_en = self.expiracion_vigente(origen)?;
+        if origen == destino {
+            return Ok(());
+        }
+
+        let slot = self.calculate_slot_for_key(origen);
+        let valor = self
+            .eliminar_clave(slot, origen)
+            .ok_or(DatoRedis::new_null())?;
+        self.reemplazar(destino, valor, expira_en)
+    }
+
+    /// Copia el valor y la expiración de una clave en otra.
+    ///
+    /// # Parámetros
+    /// - `origen`: Clave a copiar.
+    /// - `destino`: Clave donde se guarda la copia.
+    /// - `reemplazar`: Si es verdadero, sobrescribe el destino si existía.
+    ///
+    /// # Retorna
+    /// - `Ok(true)`: Si se realizó la copia.
+    /// - `Ok(false)`: Si el destino existía y no debía reemplazarse.
+    /// - `Err(DatoRedis::Null)`: Si la clave origen no existe.
+    pub fn copiar(
+        &mut self,
+        origen: impl AsRef<[u8]>,
+        destino: impl AsRef<[u8]>,
+        reemplazar: bool,
+    ) -> Result<bool, DatoRedis> {
+        let (origen, destino) = (origen.as_ref(), destino.as_ref());
+        self.verificar_key_in_range(self.calculate_slot_for_key(destino))?;
+        let expira_en = self.expiracion_vigente(origen)?;
+        let valor = self.get(origen)?;
+
+        self.expirar_si_corresponde(destino);
+        if self.metadata.contains_key(destino) && !reemplazar {
+            return Ok(false);
+        }
+        self.reemplazar(destino, valor, expira_en)?;
+        Ok(true)
+    }
+
+    /// Elimina todas las claves del almacenamiento.
+    pub fn vaciar(&mut self) {
+        self.hashes_slots.clear();
+        self.metadata.clear();
+        self.claves.clear();
+        self.claves_volatiles.clear();
+        self.memoria_usada = 0;
+        self.clave_modificada = None;
+        if self.registrar_modificaciones {
+            self.modificaciones = Modificaciones::Todas;
+        }
+    }
+
+    /// Obtiene las bibliotecas de funciones del nodo. Se guardan junto a
+    /// las claves para persistirse y replicarse con ellas, aunque no se
+    /// eliminan al vaciar el almacenamiento.
+    pub fn funciones(&self) -> &Funciones {
+        &self.funciones
+    }
+
+    /// Obtiene las bibliotecas de funciones del nodo para modificarlas.
+    pub fn funciones_mut(&mut self) -> &mut Funciones {
+        &mut self.funciones
+    }
 
-        if let Some(result) = slot_hash.remove(&key) {
-            return Ok(result);
+    /// Obtiene una clave al azar del índice de claves. Si la elegida ya
+    /// expiró la elimina y vuelve a elegir, hasta encontrar una vigente o
+    /// vaciar el storage.
+    pub fn clave_aleatoria(&mut self) -> Option<Vec<u8>> {
+        self.sincronizar_memoria();
+        loop {
+            let key = self.claves.muestra(1).first()?.to_vec();
+            if !self.hay_claves_expiradas(&[&key]) {
+                return Some(key);
+            }
+            self.expirar_si_corresponde(&key);
+        }
+    }
+
+    /// Recorre las claves vigentes de a slots completos, comenzando por el
+    /// slot indicado en `cursor`, hasta reunir al menos `count` claves.
+    ///
+    /// Como cada slot se devuelve entero, toda clave presente durante la
+    /// iteración completa se devuelve, aunque entre llamadas se agreguen o
+    /// eliminen otras claves.
+    ///
+    /// # Parámetros
+    /// - `cursor`: Slot desde el cual continuar, 0 para comenzar.
+    /// - `count`: Cantidad de claves a partir de la cual se detiene.
+    ///
+    /// # Retorna
+    /// El cursor para la siguiente llamada (0 si la iteración terminó) y
+    /// las claves obtenidas junto a sus valores.
+    pub fn escanear(&self, cursor: u64, count: usize) -> (u64, Vec<(&Vec<u8>, &DatoRedis)>) {
+        let ahora = ahora_ms();
+        let fin = self.slot_range.end as u64;
+        let mut slot = cursor.max(self.slot_range.start as u64);
+        let mut claves = Vec::new();
+
+        while slot < fin && claves.len() < count {
+            if let Some(hash) = self.hashes_slots.get(&(slot as u16)) {
+                claves.extend(hash.iter().filter(|(key, _)| {
+                    !self
+                        .metadata
+                        .get(*key)
+                        .is_some_and(|metadata| metadata.expirada(ahora))
+                }));
+            }
+            slot += 1;
+        }
+
+        let siguiente = if slot < fin { slot } else { 0 };
+        (siguiente, claves)
+    }
+
+    /// Estima la memoria utilizada por una clave y su valor.
+    ///
+    /// # Parámetros
+    /// - `key`: Clave a consultar.
+    ///
+    /// # Retorna
+    /// - `Ok(usize)`: Cantidad aproximada de bytes.
+    /// - `Err(DatoRedis::Null)`: Si la clave no existe.
+    pub fn uso_memoria(&mut self, key: impl AsRef<[u8]>) -> Result<usize, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
+        self.verificar_key_in_range(slot)?;
+        self.sincronizar_memoria();
+        Ok(self.metadata_vigente(key)?.memoria())
+    }
+
+    /// Obtiene el contador de frecuencia LFU de una clave, sin registrar
+    /// un nuevo acceso.
+    ///
+    /// # Errores
+    /// Retorna `DatoRedis::Null` si la clave no existe.
+    pub fn frecuencia(&self, key: impl AsRef<[u8]>) -> Result<u8, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
+        self.verificar_key_in_range(slot)?;
+        Ok(self.metadata_vigente(key)?.frecuencia())
+    }
+
+    /// Obtiene los segundos transcurridos desde el último acceso a una
+    /// clave, sin registrar un nuevo acceso.
+    ///
+    /// # Errores
+    /// Retorna `DatoRedis::Null` si la clave no existe.
+    pub fn segundos_inactiva(&self, key: impl AsRef<[u8]>) -> Result<u64, DatoRedis> {
+        let key = key.as_ref();
+        let slot = self.calculate_slot_for_key(key);
+        self.verificar_key_in_range(slot)?;
+        Ok(self.metadata_vigente(key)?.segundos_inactiva())
+    }
+
+    /// Configura el límite de memoria y la política de desalojo.
+    ///
+    /// # Parámetros
+    /// - `maxmemory`: Límite en bytes (0 indica sin límite).
+    /// - `politica`: Política de desalojo a aplicar al superar el límite.
+    pub fn configurar_memoria(&mut self, maxmemory: usize, politica: PoliticaEviction) {
+        self.maxmemory = maxmemory;
+        self.politica = politica;
+    }
+
+    pub fn get_maxmemory(&self) -> usize {
+        self.maxmemory
+    }
+
+    pub fn get_politica(&self) -> PoliticaEviction {
+        self.politica
+    }
+
+    /// Memoria total estimada de las claves almacenadas.
+    pub fn memoria_usada(&mut self) -> usize {
+        self.sincronizar_memoria();
+        self.memoria_usada
+    }
+
+    /// Cantidad de claves desalojadas desde el inicio del nodo.
+    pub fn claves_desalojadas(&self) -> u64 {
+        self.claves_desalojadas
+    }
+
+    /// Reinicia el contador de claves desalojadas
+    pub fn reiniciar_estadisticas(&mut self) {
+        self.claves_desalojadas = 0;
+    }
+
+    /// Cantidad de claves almacenadas, incluyendo las expiradas que todavía
+    /// no fueron eliminadas.
+    pub fn cantidad_claves(&self) -> usize {
+        self.claves.len()
+    }
+
+    /// Cantidad de claves con expiración asignada, incluyendo las expiradas
+    /// que todavía no fueron eliminadas.
+    pub fn cantidad_claves_volatiles(&self) -> usize {
+        self.claves_volatiles.len()
+    }
+
+    /// Cantidad de claves de cada slot no vacío, ordenadas por slot.
+    pub fn claves_por_slot(&self) -> Vec<(u16, usize)> {
+        let mut claves: Vec<(u16, usize)> = self
+            .hashes_slots
+            .iter()
+            .filter(|(_, hash)| !hash.is_empty())
+            .map(|(slot, hash)| (*slot, hash.len()))
+            .collect();
+        claves.sort();
+        claves
+    }
+
+    /// Desaloja claves según la política configurada hasta que la memoria
+    /// utilizada quede por debajo de `maxmemory`.
+    ///
+    /// Las víctimas se eligen entre una muestra aleatoria de
+    /// `MAXMEMORY_SAMPLES` claves, aproximando la política exacta.
+    ///
+    /// # Retorna
+    /// - `Ok(usize)`: Cantidad de claves desalojadas.
+    /// - `Err(DatoRedis)`: Error OOM si no es posible liberar memoria
+    ///   suficiente (por ejemplo, con la política `noeviction`).
+    pub fn liberar_memoria(&mut self) -> Result<usize, DatoRedis> {
+        self.sincronizar_memoria();
+        let mut desalojadas = 0;
+
+        while self.maxmemory > 0 && self.memoria_usada > self.maxmemory {
+            let victima = self.elegir_victima().ok_or_else(|| {
+                DatoRedis::new_simple_error(
+                    "OOM".to_string(),
+                    "command not allowed when used memory > 'maxmemory'.".to_string(),
+                )
+            })?;
+            let slot = self.calculate_slot_for_key(&victima);
+            self.eliminar_clave(slot, &victima);
+            self.registrar_evento(ClaseEvento::Desalojado, EVENTO_EVICTED, &victima);
+            desalojadas += 1;
+        }
+
+        self.claves_desalojadas += desalojadas as u64;
+        Ok(desalojadas)
+    }
+
+    /// Ejecuta un ciclo activo de expiración: elimina las expiradas de una
+    /// muestra aleatoria del índice de claves con expiración y repite mientras más de
+    /// un cuarto de la muestra haya expirado, para que las claves que nadie
+    /// vuelve a acceder no sigan ocupando memoria.
+    ///
+    /// # Retorna
+    /// Cantidad de claves expiradas.
+    pub fn expirar_claves_activamente(&mut self) -> usize {
+        self.sincronizar_memoria();
+        let mut expiradas = 0;
+        loop {
+            let ahora = ahora_ms();
+            let muestra = self.claves_volatiles.muestra(EXPIRACION_ACTIVA_MUESTRAS);
+            let muestreadas = muestra.len();
+            let vencidas: Vec<Vec<u8>> = muestra
+                .into_iter()
+                .filter(|key| {
+                    self.metadata
+                        .get(*key)
+                        .is_some_and(|metadata| metadata.expirada(ahora))
+                })
+                .map(|key| key.to_vec())
+                .collect();
+            for key in &vencidas {
+                self.expirar_si_corresponde(key);
+            }
+            expiradas += vencidas.len();
+            if vencidas.len() * 4 <= muestreadas {
+                return expiradas;
+            }
+        }
+    }
+
+    /// Elige, entre una muestra aleatoria de claves candidatas, la que debe
+    /// desalojarse según la política configurada. Con `volatile-ttl` solo
+    /// son candidatas las claves con expiración.
+    fn elegir_victima(&self) -> Option<Vec<u8>> {
+        let politica = self.politica;
+        let candidatas = match politica {
+            PoliticaEviction::VolatileTtl => &self.claves_volatiles,
+            _ => &self.claves,
+        };
+        let muestra = candidatas
+            .muestra(MAXMEMORY_SAMPLES)
+            .into_iter()
+            .filter_map(|key| self.metadata.get(key).map(|metadata| (key, metadata)))
+            .collect::<Vec<_>>();
+
+        let victima = match politica {
+            PoliticaEviction::NoEviction => None,
+            PoliticaEviction::AllKeysLru => muestra
+                .into_iter()
+                .min_by_key(|(_, metadata)| metadata.ultimo_acceso()),
+            PoliticaEviction::AllKeysLfu => muestra
+                .into_iter()
+                .min_by_key(|(_, metadata)| (metadata.frecuencia(), metadata.ultimo_acceso())),
+            PoliticaEviction::VolatileTtl => muestra
+                .into_iter()
+                .min_by_key(|(_, metadata)| metadata.expira_en()),
+        };
+        victima.map(|(key, _)| key.to_vec())
+    }
+
+    /// Obtiene la expiración de una clave, expirándola antes si corresponde.
+    fn expiracion_vigente(&mut self, key: &[u8]) -> Result<Option<u64>, DatoRedis> {
+        self.verificar_key_in_range(self.calculate_slot_for_key(key))?;
+        self.sincronizar_memoria();
+        self.expirar_si_corresponde(key);
+        self.metadata
+            .get(key)
+            .map(|metadata| metadata.expira_en())
+            .ok_or(DatoRedis::new_null())
+    }
+
+    /// Guarda un valor con la expiración indicada, descartando el valor y
+    /// la expiración previos de la clave.
+    fn reemplazar(
+        &mut self,
+        key: &[u8],
+        valor: DatoRedis,
+        expira_en: Option<u64>,
+    ) -> Result<(), DatoRedis> {
+        let slot = self.calculate_slot_for_key(key);
+        self.verificar_key_in_range(slot)?;
+        self.eliminar_clave(slot, key);
+        self.set(key, valor)?;
+        self.actualizar_expiracion(key, expira_en);
+        Ok(())
+    }
+
+    /// Obtiene los metadatos de una clave existente y no expirada.
+    fn metadata_vigente(&self, key: &[u8]) -> Result<&MetadataClave, DatoRedis> {
+        match self.metadata.get(key) {
+            Some(metadata) if !metadata.expirada(ahora_ms()) => Ok(metadata),
+            _ => Err(DatoRedis::new_null()),
+        }
+    }
+
+    /// Elimina la clave si su expiración ya fue alcanzada.
+    fn expirar_si_corresponde(&mut self, key: &[u8]) {
+        if self
+            .metadata
+            .get(key)
+            .is_some_and(|metadata| metadata.expirada(ahora_ms()))
+        {
+            let slot = self.calculate_slot_for_key(key);
+            self.eliminar_clave(slot, key);
+            self.registrar_evento(ClaseEvento::Expirado, EVENTO_EXPIRED, key);
+        }
+    }
+
+    /// Indica si alguna de las claves recibidas expiró y todavía no fue
+    /// eliminada.
+    pub fn hay_claves_expiradas(&self, claves: &[impl AsRef<[u8]>]) -> bool {
+        let ahora = ahora_ms();
+        claves.iter().any(|key| {
+            self.metadata
+                .get(key.as_ref())
+                .is_some_and(|metadata| metadata.expirada(ahora))
+        })
+    }
+
+    /// Elimina las claves recibidas que ya expiraron, registrando su
+    /// evento de expiración.
+    pub fn expirar_claves(&mut self, claves: &[impl AsRef<[u8]>]) {
+        self.sincronizar_memoria();
+        for key in claves {
+            self.expirar_si_corresponde(key.as_ref());
+        }
+    }
+
+    /// Habilita o deshabilita el registro de eventos para las
+    /// notificaciones de keyspace. Al deshabilitarlo descarta los pendientes.
+    pub fn set_registrar_eventos(&mut self, registrar: bool) {
+        self.registrar_eventos = registrar;
+        if !registrar {
+            self.eventos.clear();
+        }
+    }
+
+    /// Retira los eventos registrados desde la última llamada.
+    pub fn tomar_eventos(&mut self) -> Vec<EventoClave> {
+        std::mem::take(&mut self.eventos)
+    }
+
+    /// Registra un evento sobre una clave, si el registro está habilitado.
+    fn registrar_evento(&mut self, clase: ClaseEvento, evento: &'static str, key: &[u8]) {
+        if self.registrar_eventos {
+            self.eventos
+                .push(EventoClave::new(clase, evento, key.to_vec()));
+        }
+    }
+
+    /// Habilita o deshabilita el registro de las claves modificadas, para
+    /// invalidar las cacheadas por los clientes con tracking. Al
+    /// deshabilitarlo descarta las pendientes.
+    pub fn set_registrar_modificaciones(&mut self, registrar: bool) {
+        self.registrar_modificaciones = registrar;
+        if !registrar {
+            self.modificaciones = Modificaciones::default();
+        }
+    }
+
+    /// Retira las claves modificadas desde la última llamada.
+    pub fn tomar_modificaciones(&mut self) -> Modificaciones {
+        std::mem::take(&mut self.modificaciones)
+    }
+
+    /// Registra la modificación de una clave, si el registro está habilitado.
+    fn registrar_modificacion(&mut self, key: &[u8]) {
+        if self.registrar_modificaciones {
+            self.modificaciones.agregar(key);
+        }
+    }
+
+    /// Quita una clave y sus metadatos, descontando su memoria del total.
+    fn eliminar_clave(&mut self, slot: u16, key: &[u8]) -> Option<DatoRedis> {
+        if let Some(metadata) = self.metadata.remove(key) {
+            self.memoria_usada -= metadata.memoria();
+        }
+        self.claves.quitar(key);
+        self.claves_volatiles.quitar(key);
+        let eliminado = self.hashes_slots.get_mut(&slot)?.remove(key);
+        if eliminado.is_some() {
+            self.registrar_modificacion(key);
+        }
+        eliminado
+    }
+
+    /// Asigna la expiración de una clave existente y la agrega o quita del
+    /// índice de claves con expiración.
+    fn actualizar_expiracion(&mut self, key: &[u8], expira_en: Option<u64>) {
+        let Some(metadata) = self.metadata.get_mut(key) else {
+            return;
+        };
+        metadata.set_expira_en(expira_en);
+        match expira_en {
+            Some(_) => self.claves_volatiles.agregar(key),
+            None => self.claves_volatiles.quitar(key),
+        }
+    }
+
+    /// Recalcula la memoria de la última clave obtenida con `get_mutable`,
+    /// cuyo valor pudo haber sido modificado en el lugar.
+    fn sincronizar_memoria(&mut self) {
+        let Some(key) = self.clave_modificada.take() else {
+            return;
+        };
+        let slot = self.calculate_slot_for_key(&key);
+        let Some(dato) = self.hashes_slots.get(&slot).and_then(|hash| hash.get(&key)) else {
+            return;
+        };
+        let memoria = estimar_memoria(&key, dato);
+        if let Some(metadata) = self.metadata.get_mut(&key) {
+            self.memoria_usada = self.memoria_usada - metadata.memoria() + memoria;
+            metadata.set_memoria(memoria);
         }
-        Err(DatoRedis::new_integer(0))
     }
 
     /// Verifica si un slot se encuentra dentro del rango asignado al `Storage`.
@@ -144,19 +775,19 @@ impl Storage {
     /// Calcula el slot correspondiente a una clave usando CRC16.
     ///
     /// # Parámetros
-    /// - `slot`: Clave de la cual se desea obtener el slot.
+    /// - `key`: Clave de la cual se desea obtener el slot.
     ///
     /// # Retorna
     /// El número de slot correspondiente.
-    fn calculate_slot_for_key(&self, slot: &str) -> u16 {
-        crc16(slot.as_bytes()) % TOTAL_SLOTS
+    pub fn calculate_slot_for_key(&self, key: impl AsRef<[u8]>) -> u16 {
+        crc16(key.as_ref()) % TOTAL_SLOTS
     }
 
     /// Iterador sobre los slots y sus hashmaps de claves/valores.
     ///
     /// # Retorna
-    /// Un iterador sobre `(&u16, &HashMap<String, DatoRedis>)`.
-    pub fn iter(&self) -> impl Iterator<Item = (&u16, &HashMap<String, DatoRedis>)> {
+    /// Un iterador sobre `(&u16, &HashMap<Vec<u8>, DatoRedis>)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&u16, &HashMap<Vec<u8>, DatoRedis>)> {
         self.hashes_slots.iter()
     }
     /// Obtiene una copia del rango de slots del `Storage`.
@@ -164,11 +795,17 @@ impl Storage {
         self.slot_range.clone()
     }
 
+    /// Reemplaza el rango de slots del `Storage`. Los scripts lo limitan al
+    /// slot de sus claves mientras se ejecutan.
+    ///
+    /// # Parámetros
+    /// - `slot_range`: Nuevo rango de slots.
+    pub fn restringir_slots(&mut self, slot_range: Range<u16>) {
+        self.slot_range = slot_range;
+    }
+
     pub fn in_memory(slot_range: Range<u16>) -> Self {
-        Self {
-            slot_range,
-            hashes_slots: HashMap::new(),
-        }
+        Self::new(slot_range)
     }
 }
 
@@ -178,9 +815,15 @@ impl Storage {
         &self.slot_range
     }
 
-    pub fn hashes_slots(&self) -> &HashMap<u16, HashMap<String, DatoRedis>> {
+    pub fn hashes_slots(&self) -> &HashMap<u16, HashMap<Vec<u8>, DatoRedis>> {
         &self.hashes_slots
     }
+
+    /// Memoria contabilizada, sin recalcular la de la última clave
+    /// obtenida con `get_mutable`
+    pub fn memoria_contabilizada(&self) -> usize {
+        self.memoria_usada
+    }
 }
 
 #[cfg(test)]
@@ -189,7 +832,10 @@ mod tests {
 
     use redis_client::tipos_datos::traits::{DatoRedis, TipoDatoRedis};
 
+    use crate::memoria::{PoliticaEviction, ahora_ms};
+    use crate::notificaciones::{ClaseEvento, EVENTO_DEL, EVENTO_EXPIRED, EventoClave};
     use crate::storage::Storage;
+    use crate::tracking::Modificaciones;
     const RANGE: Range<u16> = Range {
         start: 0,
         end: 16378,
@@ -206,7 +852,7 @@ mod tests {
     fn storage_sets_key_value_correctly() {
         let mut stg = Storage::new(RANGE);
         let value = DatoRedis::new_bulk_string("Mundo".to_string()).unwrap();
-        let _ = stg.set("Hola".to_string(), value);
+        let _ = stg.set("Hola", value);
         assert!(!stg.hashes_slots().is_empty());
     }
 
@@ -214,8 +860,8 @@ mod tests {
     fn storage_gets_value_correctly() {
         let mut stg = Storage::new(RANGE);
         let value_original = DatoRedis::new_bulk_string("Mundo".to_string()).unwrap();
-        let _ = stg.set("Hola".to_string(), value_original.clone());
-        let value_getted = stg.get("Hola".to_string()).unwrap();
+        let _ = stg.set("Hola", value_original.clone());
+        let value_getted = stg.get("Hola").unwrap();
 
         assert_eq!(
             value_getted.convertir_a_protocolo_resp(),
@@ -227,11 +873,225 @@ mod tests {
     fn storage_removes_value_correctly() {
         let mut stg = Storage::new(RANGE);
         let value_original = DatoRedis::new_bulk_string("Mundo".to_string()).unwrap();
-        let _ = stg.set("Hola".to_string(), value_original.clone());
-        assert_eq!(stg.remove("Hola".to_string()).unwrap(), value_original);
+        let _ = stg.set("Hola", value_original.clone());
+        assert_eq!(stg.remove("Hola").unwrap(), value_original);
         assert!(matches!(
-            stg.get("Hola".to_string()),
+            stg.get("Hola"),
             Err(err) if err == DatoRedis::new_null()
         ));
     }
+
+    fn bulk(valor: &str) -> DatoRedis {
+        DatoRedis::new_bulk_string(valor.to_string()).unwrap()
+    }
+
+    #[test]
+    fn storage_tracks_memory_usage() {
+        let mut stg = Storage::new(RANGE);
+        assert_eq!(stg.memoria_usada(), 0);
+        let _ = stg.set("Hola", bulk("Mundo"));
+        let con_una_clave = stg.memoria_usada();
+        assert!(con_una_clave > 0);
+
+        if let Ok(DatoRedis::BulkString(valor)) = stg.get_mutable("Hola") {
+            valor.concatenar("x".repeat(100));
+        }
+        assert!(stg.memoria_usada() >= con_una_clave + 100);
+
+        let _ = stg.remove("Hola");
+        assert_eq!(stg.memoria_usada(), 0);
+    }
+
+    #[test]
+    fn storage_noeviction_returns_oom() {
+        let mut stg = Storage::new(RANGE);
+        stg.configurar_memoria(1, PoliticaEviction::NoEviction);
+        let _ = stg.set("Hola", bulk("Mundo"));
+        assert!(stg.liberar_memoria().is_err());
+        assert!(stg.get("Hola").is_ok());
+    }
+
+    #[test]
+    fn storage_allkeys_lru_evicts_until_under_limit() {
+        let mut stg = Storage::new(RANGE);
+        for i in 0..20 {
+            let _ = stg.set(format!("key{i}"), bulk("valor"));
+        }
+        let limite = stg.memoria_usada() / 2;
+        stg.configurar_memoria(limite, PoliticaEviction::AllKeysLru);
+
+        let desalojadas = stg.liberar_memoria().unwrap();
+        assert!(desalojadas >= 10);
+        assert!(stg.memoria_usada() <= limite);
+        assert_eq!(stg.claves_desalojadas(), desalojadas as u64);
+    }
+
+    #[test]
+    fn storage_allkeys_lfu_evicts_least_used() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("frio", bulk("valor"));
+        let _ = stg.set("caliente", bulk("valor"));
+        for _ in 0..1000 {
+            let _ = stg.get("caliente");
+        }
+        let limite = stg.memoria_usada() - 1;
+        stg.configurar_memoria(limite, PoliticaEviction::AllKeysLfu);
+
+        assert_eq!(stg.liberar_memoria().unwrap(), 1);
+        assert!(stg.get("caliente").is_ok());
+        assert!(stg.get("frio").is_err());
+    }
+
+    #[test]
+    fn storage_volatile_ttl_only_evicts_keys_with_expiration() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("persistente", bulk("valor"));
+        let _ = stg.set("volatil", bulk("valor"));
+        let _ = stg.set_expiracion("volatil", Some(ahora_ms() + 60_000));
+        let limite = stg.memoria_usada() - 1;
+        stg.configurar_memoria(limite, PoliticaEviction::VolatileTtl);
+
+        assert_eq!(stg.liberar_memoria().unwrap(), 1);
+        assert!(stg.get("persistente").is_ok());
+
+        stg.configurar_memoria(1, PoliticaEviction::VolatileTtl);
+        assert!(stg.liberar_memoria().is_err());
+    }
+
+    #[test]
+    fn storage_persisted_and_renamed_keys_keep_the_volatile_index() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("a", bulk("valor"));
+        let _ = stg.set("b", bulk("valor"));
+        let _ = stg.set_expiracion("a", Some(ahora_ms() + 60_000));
+        let _ = stg.set_expiracion("b", Some(ahora_ms() + 60_000));
+        let _ = stg.set_expiracion("a", None);
+        let _ = stg.renombrar("b", "c");
+        assert_eq!(stg.claves_volatiles.muestra(10), [b"c"]);
+
+        stg.configurar_memoria(1, PoliticaEviction::VolatileTtl);
+        assert_eq!(stg.liberar_memoria().ok(), None);
+        assert!(stg.get("a").is_ok());
+        assert!(stg.get("c").is_err());
+        assert_eq!(stg.claves.muestra(10), [b"a"]);
+    }
+
+    #[test]
+    fn storage_expired_keys_are_not_returned() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("Hola", bulk("Mundo"));
+        let _ = stg.set_expiracion("Hola", Some(ahora_ms() - 1));
+        assert!(stg.get("Hola").is_err());
+        assert!(stg.remove("Hola").is_err());
+        assert_eq!(stg.memoria_usada(), 0);
+    }
+
+    #[test]
+    fn storage_random_key_skips_and_removes_expired_keys() {
+        let mut stg = Storage::new(RANGE);
+        stg.set_registrar_eventos(true);
+        for i in 0..20 {
+            let key = format!("vencida{i}");
+            let _ = stg.set(&key, bulk("valor"));
+            let _ = stg.set_expiracion(&key, Some(ahora_ms() - 1));
+        }
+        assert_eq!(stg.cantidad_claves(), 20);
+        assert_eq!(stg.clave_aleatoria(), None);
+        assert_eq!(stg.cantidad_claves(), 0);
+        assert_eq!(stg.tomar_eventos().len(), 20);
+
+        let _ = stg.set("vencida", bulk("valor"));
+        let _ = stg.set_expiracion("vencida", Some(ahora_ms() - 1));
+        let _ = stg.set("vigente", bulk("valor"));
+        assert_eq!(stg.clave_aleatoria(), Some(b"vigente".to_vec()));
+    }
+
+    #[test]
+    fn storage_actively_expires_unaccessed_keys() {
+        let mut stg = Storage::new(RANGE);
+        stg.set_registrar_eventos(true);
+        for i in 0..50 {
+            let key = format!("clave{i}");
+            let _ = stg.set(&key, bulk("valor"));
+            let _ = stg.set_expiracion(key, Some(ahora_ms() - 1));
+        }
+        let _ = stg.set("vigente", bulk("valor"));
+        let _ = stg.set_expiracion("vigente", Some(ahora_ms() + 60_000));
+        let _ = stg.set("persistente", bulk("valor"));
+
+        assert_eq!(stg.expirar_claves_activamente(), 50);
+        assert_eq!(stg.metadata.len(), 2);
+        assert_eq!(stg.claves_volatiles.muestra(10), [b"vigente"]);
+        assert_eq!(stg.tomar_eventos().len(), 50);
+        assert_eq!(stg.expirar_claves_activamente(), 0);
+    }
+
+    #[test]
+    fn storage_records_key_events_when_enabled() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("a", bulk("1"));
+        let _ = stg.remove("a");
+        assert!(stg.tomar_eventos().is_empty());
+
+        stg.set_registrar_eventos(true);
+        let _ = stg.set("a", bulk("1"));
+        let _ = stg.set("b", bulk("2"));
+        let _ = stg.set_expiracion("b", Some(ahora_ms() - 1));
+        let _ = stg.remove("a");
+        let _ = stg.remove("b");
+        assert_eq!(
+            stg.tomar_eventos(),
+            vec![
+                EventoClave::new(ClaseEvento::Generico, EVENTO_DEL, "a".to_string()),
+                EventoClave::new(ClaseEvento::Expirado, EVENTO_EXPIRED, "b".to_string()),
+            ]
+        );
+        assert!(stg.tomar_eventos().is_empty());
+    }
+
+    #[test]
+    fn storage_expires_read_keys_and_records_their_events() {
+        let mut stg = Storage::new(RANGE);
+        stg.set_registrar_eventos(true);
+        let _ = stg.set("a", bulk("1"));
+        let _ = stg.set("b", bulk("2"));
+        let claves = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert!(!stg.hay_claves_expiradas(&claves));
+
+        let _ = stg.set_expiracion("b", Some(ahora_ms() - 1));
+        assert!(stg.hay_claves_expiradas(&claves));
+        stg.expirar_claves(&claves);
+        assert!(!stg.hay_claves_expiradas(&claves));
+        assert_eq!(stg.cantidad_claves(), 1);
+        assert_eq!(
+            stg.tomar_eventos(),
+            vec![EventoClave::new(
+                ClaseEvento::Expirado,
+                EVENTO_EXPIRED,
+                "b".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn storage_records_modified_keys_when_enabled() {
+        let mut stg = Storage::new(RANGE);
+        let _ = stg.set("a", bulk("1"));
+        assert!(stg.tomar_modificaciones().is_empty());
+
+        stg.set_registrar_modificaciones(true);
+        let _ = stg.get("a");
+        assert!(stg.tomar_modificaciones().is_empty());
+        let _ = stg.set("b", bulk("2"));
+        let _ = stg.renombrar("a", "c");
+        let _ = stg.remove("z");
+        assert_eq!(
+            stg.tomar_modificaciones(),
+            Modificaciones::Claves([b"a", b"b", b"c"].iter().map(|s| s.to_vec()).collect())
+        );
+
+        stg.vaciar();
+        assert_eq!(stg.tomar_modificaciones(), Modificaciones::Todas);
+        assert!(stg.tomar_modificaciones().is_empty());
+    }
 }
@@ -0,0 +1,496 @@
+//! Este módulo contiene el registro de client side caching del nodo: los
+//! clientes con CLIENT TRACKING activo y las claves que leyó cada uno.
+//!
+//! Cuando una clave cambia, los clientes que la leyeron (o, en modo
+//! broadcast, los que siguen un prefijo de la clave) reciben las claves a
+//! descartar de su cache local. Por defecto la invalidación se envía a la
+//! propia conexión como un mensaje push de RESP3, intercalado con sus
+//! respuestas. Con REDIRECT se envía en cambio como un mensaje de pub/sub
+//! del canal `__redis__:invalidate` al cliente indicado, que debe estar
+//! suscripto a ese canal.
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::RwLock;
+
+use redis_client::protocol::token::Token;
+use redis_client::tipos_datos::arrays::Arrays;
+use redis_client::tipos_datos::traits::DatoRedis;
+
+use crate::comandos::comandos_scripting::cantidad_claves;
+use crate::comandos::const_cmd::*;
+
+/// Canal en el que recibe las invalidaciones el cliente de REDIRECT
+pub const CANAL_INVALIDACION: &str = "__redis__:invalidate";
+
+/// Tipo de los mensajes push de invalidación
+const PUSH_INVALIDACION: &str = "invalidate";
+
+const ARGUMENTO_STREAMS: &str = "STREAMS";
+
+/// Claves modificadas en el storage desde la última consulta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Modificaciones {
+    /// Claves modificadas, eliminadas o expiradas
+    Claves(BTreeSet<Vec<u8>>),
+    /// Se eliminaron todas las claves
+    Todas,
+}
+
+impl Default for Modificaciones {
+    fn default() -> Self {
+        Modificaciones::Claves(BTreeSet::new())
+    }
+}
+
+impl Modificaciones {
+    /// Registra la modificación de una clave
+    pub fn agregar(&mut self, clave: &[u8]) {
+        if let Modificaciones::Claves(claves) = self {
+            claves.insert(clave.to_vec());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Modificaciones::Claves(claves) if claves.is_empty())
+    }
+}
+
+/// Opciones de CLIENT TRACKING de un cliente
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpcionesTracking {
+    /// Cliente que recibe las invalidaciones, en lugar del propio
+    pub redirect: Option<u64>,
+    /// Si se notifican todas las claves de los prefijos, sin registrar
+    /// las lecturas
+    pub bcast: bool,
+    /// Prefijos seguidos en modo broadcast, todas las claves si no hay
+    pub prefijos: Vec<Token>,
+    /// Si solo se registran las lecturas precedidas por CLIENT CACHING yes
+    pub optin: bool,
+    /// Si no se registran las lecturas precedidas por CLIENT CACHING no
+    pub optout: bool,
+    /// Si no se notifican las claves que modifica el propio cliente
+    pub noloop: bool,
+}
+
+impl OpcionesTracking {
+    fn sigue_clave(&self, clave: &[u8]) -> bool {
+        self.prefijos.is_empty()
+            || self
+                .prefijos
+                .iter()
+                .any(|prefijo| clave.starts_with(prefijo))
+    }
+}
+
+/// Invalidación a enviar a un cliente
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invalidacion {
+    /// Cliente con tracking cuyas claves se invalidan
+    pub cliente: u64,
+    /// Cliente al que se envía el mensaje
+    pub destino: u64,
+    /// Si el mensaje se envía a otro cliente con REDIRECT, en lugar de
+    /// enviarse como push a la propia conexión
+    pub redirigida: bool,
+    /// Claves a invalidar, None si se invalidan todas
+    pub claves: Option<Vec<Vec<u8>>>,
+}
+
+impl Invalidacion {
+    /// Obtiene el mensaje con las claves invalidadas, o null si se
+    /// invalidan todas: un push `invalidate` para la propia conexión, o un
+    /// mensaje de pub/sub del canal de invalidaciones para el cliente de
+    /// REDIRECT
+    pub fn mensaje(&self) -> Result<DatoRedis, DatoRedis> {
+        let claves = match &self.claves {
+            Some(claves) => {
+                let mut array = Arrays::new();
+                for clave in claves {
+                    array.append(DatoRedis::new_bulk_string_desde_bytes(clave.clone())?);
+                }
+                DatoRedis::new_array_con_contenido(array)
+            }
+            None => DatoRedis::new_null(),
+        };
+        let mut mensaje = Arrays::new();
+        if !self.redirigida {
+            mensaje.append(DatoRedis::new_bulk_string(PUSH_INVALIDACION.to_string())?);
+            mensaje.append(claves);
+            return Ok(DatoRedis::new_push_con_contenido(mensaje));
+        }
+        mensaje.append(DatoRedis::new_bulk_string("message".to_string())?);
+        mensaje.append(DatoRedis::new_bulk_string(CANAL_INVALIDACION.to_string())?);
+        mensaje.append(claves);
+        Ok(DatoRedis::new_array_con_contenido(mensaje))
+    }
+}
+
+#[derive(Debug, Default)]
+struct EstadoTracking {
+    clientes: HashMap<u64, OpcionesTracking>,
+    lectores: HashMap<Vec<u8>, HashSet<u64>>,
+    caching: HashMap<u64, bool>,
+}
+
+/// Clientes con tracking activo y claves leídas por cada uno, indexados por
+/// el id numérico del cliente
+#[derive(Debug, Default)]
+pub struct RegistroTracking {
+    estado: RwLock<EstadoTracking>,
+}
+
+impl RegistroTracking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Activa el tracking de un cliente, reemplazando sus opciones previas
+    pub fn activar(&self, cliente: u64, opciones: OpcionesTracking) {
+        if let Ok(mut estado) = self.estado.write() {
+            if opciones.bcast {
+                olvidar_lecturas(&mut estado, cliente);
+            }
+            estado.caching.remove(&cliente);
+            estado.clientes.insert(cliente, opciones);
+        }
+    }
+
+    /// Desactiva el tracking de un cliente, olvidando las claves que leyó
+    pub fn desactivar(&self, cliente: u64) {
+        if let Ok(mut estado) = self.estado.write() {
+            estado.clientes.remove(&cliente);
+            estado.caching.remove(&cliente);
+            olvidar_lecturas(&mut estado, cliente);
+        }
+    }
+
+    /// Obtiene las opciones de un cliente, None si no tiene tracking activo
+    pub fn opciones(&self, cliente: u64) -> Option<OpcionesTracking> {
+        self.estado
+            .read()
+            .ok()
+            .and_then(|estado| estado.clientes.get(&cliente).cloned())
+    }
+
+    /// Indica si algún cliente tiene tracking activo
+    pub fn hay_clientes(&self) -> bool {
+        self.estado
+            .read()
+            .map(|estado| !estado.clientes.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Registra la elección de CLIENT CACHING, que aplica solo al próximo
+    /// comando del cliente
+    pub fn set_caching(&self, cliente: u64, caching: bool) {
+        if let Ok(mut estado) = self.estado.write() {
+            estado.caching.insert(cliente, caching);
+        }
+    }
+
+    /// Descarta la elección de CLIENT CACHING una vez ejecutado el comando
+    /// al que aplicaba
+    pub fn finalizar_comando(&self, cliente: u64) {
+        let pendiente = self
+            .estado
+            .read()
+            .map(|estado| estado.caching.contains_key(&cliente))
+            .unwrap_or(false);
+        if pendiente && let Ok(mut estado) = self.estado.write() {
+            estado.caching.remove(&cliente);
+        }
+    }
+
+    /// Registra las claves leídas por un cliente, para invalidarlas cuando
+    /// cambien. No se registran en modo broadcast ni cuando OPTIN u OPTOUT
+    /// lo excluyen
+    pub fn registrar_lectura(&self, cliente: u64, claves: &[Token]) {
+        if claves.is_empty() {
+            return;
+        }
+        let Ok(mut estado) = self.estado.write() else {
+            return;
+        };
+        let Some(opciones) = estado.clientes.get(&cliente) else {
+            return;
+        };
+        let caching = estado.caching.get(&cliente).copied();
+        let registrar = if opciones.bcast {
+            false
+        } else if opciones.optin {
+            caching == Some(true)
+        } else if opciones.optout {
+            caching != Some(false)
+        } else {
+            true
+        };
+        if !registrar {
+            return;
+        }
+        for clave in claves {
+            estado
+                .lectores
+                .entry(clave.to_vec())
+                .or_default()
+                .insert(cliente);
+        }
+    }
+
+    /// Obtiene las invalidaciones a enviar por las modificaciones del
+    /// storage. Las claves invalidadas dejan de estar registradas para los
+    /// clientes que las leyeron, hasta que las vuelvan a leer
+    ///
+    /// # Parámetros
+    /// * `modificaciones`: claves modificadas
+    /// * `origen`: cliente que ejecutó las modificaciones, si lo hubo
+    pub fn invalidar(
+        &self,
+        modificaciones: Modificaciones,
+        origen: Option<u64>,
+    ) -> Vec<Invalidacion> {
+        if modificaciones.is_empty() {
+            return Vec::new();
+        }
+        let Ok(mut estado) = self.estado.write() else {
+            return Vec::new();
+        };
+        let claves = match modificaciones {
+            Modificaciones::Todas => {
+                estado.lectores.clear();
+                return estado
+                    .clientes
+                    .iter()
+                    .map(|(cliente, opciones)| invalidacion(*cliente, opciones, None))
+                    .collect();
+            }
+            Modificaciones::Claves(claves) => claves,
+        };
+
+        let mut por_cliente: HashMap<u64, Vec<Vec<u8>>> = HashMap::new();
+        for clave in claves {
+            let lectores = estado.lectores.remove(&clave).unwrap_or_default();
+            for (cliente, opciones) in &estado.clientes {
+                let notificar = if opciones.bcast {
+                    opciones.sigue_clave(&clave)
+                } else {
+                    lectores.contains(cliente)
+                };
+                if notificar && !(opciones.noloop && origen == Some(*cliente)) {
+                    por_cliente.entry(*cliente).or_default().push(clave.clone());
+                }
+            }
+        }
+        let mut invalidaciones: Vec<Invalidacion> = por_cliente
+            .into_iter()
+            .filter_map(|(cliente, claves)| {
+                let opciones = estado.clientes.get(&cliente)?;
+                Some(invalidacion(cliente, opciones, Some(claves)))
+            })
+            .collect();
+        invalidaciones.sort_by_key(|invalidacion| invalidacion.cliente);
+        invalidaciones
+    }
+}
+
+fn invalidacion(
+    cliente: u64,
+    opciones: &OpcionesTracking,
+    claves: Option<Vec<Vec<u8>>>,
+) -> Invalidacion {
+    Invalidacion {
+        cliente,
+        destino: opciones.redirect.unwrap_or(cliente),
+        redirigida: opciones.redirect.is_some(),
+        claves,
+    }
+}
+
+fn olvidar_lecturas(estado: &mut EstadoTracking, cliente: u64) {
+    estado.lectores.retain(|_, lectores| {
+        lectores.remove(&cliente);
+        !lectores.is_empty()
+    });
+}
+
+/// Obtiene las claves que lee un comando que no modifica el storage, para
+/// registrarlas en el tracking del cliente que lo ejecuta
+///
+/// # Parámetros
+/// * `comando`: nombre del comando en mayúsculas
+/// * `tokens`: secuencia de strings que conforman el comando
+pub fn claves_leidas(comando: &str, tokens: &[Token]) -> Vec<Token> {
+    match comando {
+        CMD_DBSIZE | CMD_RANDOMKEY | CMD_KEYS | CMD_SCAN | CMD_FUNCTION => Vec::new(),
+        CMD_EXISTS | CMD_MGET | CMD_PFCOUNT => tokens.iter().skip(1).cloned().collect(),
+        CMD_MEMORY | CMD_OBJECT | CMD_XINFO => tokens.get(2).cloned().into_iter().collect(),
+        CMD_XREAD => {
+            let Some(inicio) = tokens
+                .iter()
+                .position(|t| t.eq_ignore_ascii_case(ARGUMENTO_STREAMS))
+            else {
+                return Vec::new();
+            };
+            let streams = &tokens[inicio + 1..];
+            streams[..streams.len() / 2].to_vec()
+        }
+        CMD_FCALL_RO if tokens.len() > 2 => match cantidad_claves(tokens) {
+            Ok(cantidad) => tokens.iter().skip(3).take(cantidad).cloned().collect(),
+            Err(_) => Vec::new(),
+        },
+        CMD_FCALL_RO => Vec::new(),
+        _ => tokens.get(1).cloned().into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redis_client::tipos_datos::traits::TipoDatoRedis;
+
+    use redis_client::protocol::token::tokens;
+
+    use super::*;
+
+    fn claves(claves: &[&str]) -> Vec<Vec<u8>> {
+        claves.iter().map(|s| s.as_bytes().to_vec()).collect()
+    }
+
+    fn modificadas(claves: &[&str]) -> Modificaciones {
+        Modificaciones::Claves(claves.iter().map(|s| s.as_bytes().to_vec()).collect())
+    }
+
+    #[test]
+    fn test_01_invalida_una_vez_las_claves_leidas() {
+        let registro = RegistroTracking::new();
+        registro.activar(1, OpcionesTracking::default());
+        registro.activar(2, OpcionesTracking::default());
+        registro.registrar_lectura(1, &tokens(&["a", "b"]));
+        registro.registrar_lectura(2, &tokens(&["b"]));
+
+        let invalidaciones = registro.invalidar(modificadas(&["b", "c"]), Some(3));
+        assert_eq!(
+            invalidaciones,
+            vec![
+                Invalidacion {
+                    cliente: 1,
+                    destino: 1,
+                    redirigida: false,
+                    claves: Some(claves(&["b"]))
+                },
+                Invalidacion {
+                    cliente: 2,
+                    destino: 2,
+                    redirigida: false,
+                    claves: Some(claves(&["b"]))
+                },
+            ]
+        );
+        assert!(registro.invalidar(modificadas(&["b"]), None).is_empty());
+
+        registro.desactivar(1);
+        assert!(registro.invalidar(modificadas(&["a"]), None).is_empty());
+    }
+
+    #[test]
+    fn test_02_broadcast_con_prefijos_y_noloop() {
+        let registro = RegistroTracking::new();
+        let opciones = OpcionesTracking {
+            bcast: true,
+            prefijos: tokens(&["user:"]),
+            noloop: true,
+            redirect: Some(9),
+            ..OpcionesTracking::default()
+        };
+        registro.activar(1, opciones);
+
+        let invalidaciones = registro.invalidar(modificadas(&["user:1", "otra"]), Some(2));
+        assert_eq!(invalidaciones.len(), 1);
+        assert_eq!(invalidaciones[0].destino, 9);
+        assert!(invalidaciones[0].redirigida);
+        assert_eq!(invalidaciones[0].claves, Some(claves(&["user:1"])));
+        assert!(
+            registro
+                .invalidar(modificadas(&["user:1"]), Some(1))
+                .is_empty()
+        );
+        assert_eq!(
+            registro.invalidar(Modificaciones::Todas, Some(1))[0].claves,
+            None
+        );
+    }
+
+    #[test]
+    fn test_03_optin_y_optout_segun_client_caching() {
+        let registro = RegistroTracking::new();
+        let optin = OpcionesTracking {
+            optin: true,
+            ..OpcionesTracking::default()
+        };
+        let optout = OpcionesTracking {
+            optout: true,
+            ..OpcionesTracking::default()
+        };
+        registro.activar(1, optin);
+        registro.activar(2, optout);
+
+        registro.registrar_lectura(1, &tokens(&["a"]));
+        registro.set_caching(1, true);
+        registro.registrar_lectura(1, &tokens(&["b"]));
+        registro.finalizar_comando(1);
+        registro.set_caching(2, false);
+        registro.registrar_lectura(2, &tokens(&["a"]));
+        registro.finalizar_comando(2);
+        registro.registrar_lectura(2, &tokens(&["b"]));
+
+        let invalidaciones = registro.invalidar(modificadas(&["a", "b"]), None);
+        let clientes: Vec<(u64, Option<Vec<Vec<u8>>>)> = invalidaciones
+            .into_iter()
+            .map(|i| (i.cliente, i.claves))
+            .collect();
+        assert_eq!(
+            clientes,
+            vec![(1, Some(claves(&["b"]))), (2, Some(claves(&["b"])))]
+        );
+    }
+
+    #[test]
+    fn test_04_claves_leidas_por_comando() {
+        assert_eq!(claves_leidas(CMD_GET, &tokens(&["get", "a"])), ["a"]);
+        assert_eq!(
+            claves_leidas(CMD_MGET, &tokens(&["mget", "a", "b"])),
+            ["a", "b"]
+        );
+        assert_eq!(
+            claves_leidas(
+                CMD_XREAD,
+                &tokens(&["xread", "count", "2", "streams", "s1", "s2", "0", "0"])
+            ),
+            ["s1", "s2"]
+        );
+        assert_eq!(
+            claves_leidas(CMD_FCALL_RO, &tokens(&["fcall_ro", "f", "1", "k", "arg"])),
+            ["k"]
+        );
+        assert!(claves_leidas(CMD_KEYS, &tokens(&["keys", "*"])).is_empty());
+    }
+
+    #[test]
+    fn test_05_mensaje_push_o_de_pub_sub_segun_redirect() {
+        let mut invalidacion = Invalidacion {
+            cliente: 1,
+            destino: 1,
+            redirigida: false,
+            claves: None,
+        };
+        assert_eq!(
+            invalidacion.mensaje().unwrap().convertir_a_protocolo_resp(),
+            ">2\r\n$10\r\ninvalidate\r\n_\r\n"
+        );
+        invalidacion.destino = 2;
+        invalidacion.redirigida = true;
+        invalidacion.claves = Some(claves(&["a"]));
+        assert_eq!(
+            invalidacion.mensaje().unwrap().convertir_a_protocolo_resp(),
+            "*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n*1\r\n$1\r\na\r\n"
+        );
+    }
+}
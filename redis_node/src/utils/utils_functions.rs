@@ -1,16 +1,33 @@
 //! Este modulo tiene funciones auxiliares para redis_node
 use crate::client_struct::client::Client;
+use crate::comandos::comandos_bitmap::{bitcount, bitfield, bitop, bitpos, getbit, setbit};
+use crate::comandos::comandos_funciones::{fcall, function};
+use crate::comandos::comandos_geo::{geoadd, geodist, geopos, geosearch};
+use crate::comandos::comandos_hyperloglog::{pfadd, pfcount, pfmerge};
+use crate::comandos::comandos_keyspace::{
+    copy, dbsize, dump, exists, flushall, key_type, keys, memory, object, randomkey, rename,
+    renamenx, restore, scan, unlink,
+};
 use crate::comandos::comandos_list::{
-    lindex, linsert, llen, lmove, lpop, lpush, lrange, lrem, lset, ltrim, rpop, rpush,
+    blmove, blpop, brpop, lindex, linsert, llen, lmove, lmpop, lpop, lpos, lpush, lpushx, lrange,
+    lrem, lset, ltrim, rpop, rpoplpush, rpush, rpushx,
+};
+use crate::comandos::comandos_scripting::eval;
+use crate::comandos::comandos_set::{sadd, scard, sismember, smembers, srem, sscan};
+use crate::comandos::comandos_stream::{xadd, xdel, xlen, xrange, xread, xrevrange, xtrim};
+use crate::comandos::comandos_stream_grupos::{xack, xclaim, xgroup, xinfo, xpending, xreadgroup};
+use crate::comandos::comandos_string::{
+    append, decr, decrby, del, get, getdel, getex, incr, incrby, incrbyfloat, mget, mset, set,
+    setrange, strlen, substr,
 };
-use crate::comandos::comandos_set::{sadd, scard, sismember, smembers, srem};
-use crate::comandos::comandos_string::{append, decr, del, get, getdel, incr, set, strlen, substr};
 use crate::comandos::const_cmd::*;
 use crate::comandos::handshake::{auth, hello};
 use crate::comandos::pub_sub_struct::{PubSubBroker, PubSubCore};
 use crate::log_msj::log_mensajes::{log_stream_clone_error, log_stream_read_error};
+use crate::node::Node;
 use crate::storage::Storage;
 use logger::logger::Logger;
+use redis_client::protocol::token::Token;
 use redis_client::tipos_datos::traits::DatoRedis;
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
@@ -19,13 +36,16 @@ use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::{Arc, RwLock};
 
-pub type CommandFunction = fn(&[String], &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis>;
+pub type CommandFunction = fn(&[Token], &Arc<RwLock<Storage>>) -> Result<DatoRedis, DatoRedis>;
+
+pub type NodeCommandFunction =
+    fn(&Node, &[Token], &Arc<RwLock<Client>>) -> Result<DatoRedis, DatoRedis>;
 
 type PubSubMethod =
-    fn(&mut PubSubCore, &[String], Arc<RwLock<Client>>) -> Result<DatoRedis, DatoRedis>;
+    fn(&mut PubSubCore, &[Token], Arc<RwLock<Client>>) -> Result<DatoRedis, DatoRedis>;
 
 pub type HandshakeFunction = fn(
-    &[String],
+    &[Token],
     &mut Arc<RwLock<Client>>,
     &HashMap<String, String>,
 ) -> Result<DatoRedis, DatoRedis>;
@@ -92,6 +112,33 @@ pub fn obtener_fn_pub_sub(nombre: &str) -> Option<PubSubMethod> {
         CMD_PUBLISH | CMD_SPUBLISH => Some(PubSubCore::publish),
         CMD_UNSUBSCRIBE | CMD_PUNSUBSCRIBE | CMD_SUNSUBSCRIBE => Some(PubSubCore::unsubscribe),
         CMD_PUBSUB => Some(PubSubCore::pub_sub),
+        CMD_RESET => Some(PubSubCore::reset),
+        _ => None,
+    }
+}
+
+/// Obtiene el metodo asociado a un nombre de comando de administracion
+/// del nodo, que opera sobre su estado y no sobre el storage
+///
+/// # Parámetros
+/// - Nombre del comando
+///
+/// # Retorna
+/// - Metodo del nodo que ejecuta el comando
+pub fn obtener_fn_nodo(nombre: &str) -> Option<NodeCommandFunction> {
+    match nombre {
+        CMD_INFO => Some(Node::info),
+        CMD_SLOWLOG => Some(Node::slowlog),
+        CMD_LATENCY => Some(Node::latency),
+        CMD_CLIENT => Some(Node::client),
+        CMD_CONFIG => Some(Node::config),
+        CMD_MONITOR => Some(Node::monitor),
+        CMD_SCRIPT => Some(Node::script),
+        CMD_TIME => Some(Node::time),
+        CMD_COMMAND => Some(Node::command),
+        CMD_PING => Some(Node::ping),
+        CMD_ECHO => Some(Node::echo),
+        CMD_QUIT => Some(Node::quit),
         _ => None,
     }
 }
@@ -115,6 +162,26 @@ pub fn obtener_fn_normal(nombre_comando: &str) -> Option<CommandFunction> {
         CMD_GETRANGE => Some(substr),
         CMD_INCR => Some(incr),
         CMD_DECR => Some(decr),
+        CMD_INCRBY => Some(incrby),
+        CMD_DECRBY => Some(decrby),
+        CMD_INCRBYFLOAT => Some(incrbyfloat),
+        CMD_MSET => Some(mset),
+        CMD_MGET => Some(mget),
+        CMD_SETRANGE => Some(setrange),
+        CMD_GETEX => Some(getex),
+        CMD_SETBIT => Some(setbit),
+        CMD_GETBIT => Some(getbit),
+        CMD_BITCOUNT => Some(bitcount),
+        CMD_BITPOS => Some(bitpos),
+        CMD_BITOP => Some(bitop),
+        CMD_BITFIELD => Some(bitfield),
+        CMD_PFADD => Some(pfadd),
+        CMD_PFCOUNT => Some(pfcount),
+        CMD_PFMERGE => Some(pfmerge),
+        CMD_GEOADD => Some(geoadd),
+        CMD_GEODIST => Some(geodist),
+        CMD_GEOPOS => Some(geopos),
+        CMD_GEOSEARCH => Some(geosearch),
         CMD_LINSERT => Some(linsert),
         CMD_LPUSH => Some(lpush),
         CMD_RPUSH => Some(rpush),
@@ -127,11 +194,51 @@ pub fn obtener_fn_normal(nombre_comando: &str) -> Option<CommandFunction> {
         CMD_LTRIM => Some(ltrim),
         CMD_LINDEX => Some(lindex),
         CMD_LMOVE => Some(lmove),
+        CMD_BLPOP => Some(blpop),
+        CMD_BRPOP => Some(brpop),
+        CMD_BLMOVE => Some(blmove),
+        CMD_LPOS => Some(lpos),
+        CMD_LMPOP => Some(lmpop),
+        CMD_LPUSHX => Some(lpushx),
+        CMD_RPUSHX => Some(rpushx),
+        CMD_RPOPLPUSH => Some(rpoplpush),
+        CMD_XADD => Some(xadd),
+        CMD_XLEN => Some(xlen),
+        CMD_XRANGE => Some(xrange),
+        CMD_XREVRANGE => Some(xrevrange),
+        CMD_XTRIM => Some(xtrim),
+        CMD_XDEL => Some(xdel),
+        CMD_XREAD => Some(xread),
+        CMD_XGROUP => Some(xgroup),
+        CMD_XREADGROUP => Some(xreadgroup),
+        CMD_XACK => Some(xack),
+        CMD_XPENDING => Some(xpending),
+        CMD_XCLAIM => Some(xclaim),
+        CMD_XINFO => Some(xinfo),
         CMD_SADD => Some(sadd),
         CMD_SCARD => Some(scard),
         CMD_SISMEMBER => Some(sismember),
         CMD_SREM => Some(srem),
         CMD_SMEMBERS => Some(smembers),
+        CMD_DUMP => Some(dump),
+        CMD_RESTORE => Some(restore),
+        CMD_MEMORY => Some(memory),
+        CMD_OBJECT => Some(object),
+        CMD_EXISTS => Some(exists),
+        CMD_TYPE => Some(key_type),
+        CMD_RENAME => Some(rename),
+        CMD_RENAMENX => Some(renamenx),
+        CMD_COPY => Some(copy),
+        CMD_UNLINK => Some(unlink),
+        CMD_DBSIZE => Some(dbsize),
+        CMD_FLUSHALL => Some(flushall),
+        CMD_RANDOMKEY => Some(randomkey),
+        CMD_KEYS => Some(keys),
+        CMD_SCAN => Some(scan),
+        CMD_SSCAN => Some(sscan),
+        CMD_EVAL | CMD_EVALSHA => Some(eval),
+        CMD_FUNCTION => Some(function),
+        CMD_FCALL | CMD_FCALL_RO => Some(fcall),
         _ => None,
     }
 }
@@ -176,7 +283,7 @@ pub fn limpiar_cliente_desconectado(
     logger: &Logger,
 ) {
     pub_sub.borrar_canales_cliente(client.clone());
-    if let Ok(mut cli) = client.write() {
+    if let Ok(cli) = client.write() {
         logger.info("Initialize shutdown", "Shutdown");
         let stream_guard = cli.get_stream();
         if let Ok(ip) = stream_guard.peer_addr() {
@@ -184,7 +291,7 @@ pub fn limpiar_cliente_desconectado(
         }
         let stream = cli.get_stream();
         let _ = stream.shutdown(Shutdown::Both);
-        drop(cli.get_sender());
+        drop(cli.get_salida());
         logger.info("Finished shutdown", "Shutdown");
     }
     act_client_active.fetch_sub(1, SeqCst);